@@ -24,6 +24,9 @@ pub async fn main() -> Result<()> {
                 Sub::TxUpdateVp(TxUpdateVp(args)) => {
                     tx::submit_update_vp(ctx, args).await;
                 }
+                Sub::TxUpdateKey(TxUpdateKey(args)) => {
+                    tx::submit_update_key(ctx, args).await;
+                }
                 Sub::TxInitAccount(TxInitAccount(args)) => {
                     tx::submit_init_account(ctx, args).await;
                 }
@@ -39,6 +42,9 @@ pub async fn main() -> Result<()> {
                 Sub::TxRevealPk(TxRevealPk(args)) => {
                     tx::submit_reveal_pk(ctx, args).await;
                 }
+                Sub::TxSubmitSigned(TxSubmitSigned(args)) => {
+                    tx::submit_signed(ctx, args).await;
+                }
                 Sub::Bond(Bond(args)) => {
                     tx::submit_bond(ctx, args).await;
                 }
@@ -48,6 +54,12 @@ pub async fn main() -> Result<()> {
                 Sub::Withdraw(Withdraw(args)) => {
                     tx::submit_withdraw(ctx, args).await;
                 }
+                Sub::TxUnjailValidator(TxUnjailValidator(args)) => {
+                    tx::submit_unjail_validator(ctx, args).await;
+                }
+                Sub::TxClaimRewards(TxClaimRewards(args)) => {
+                    tx::submit_claim_rewards(ctx, args).await;
+                }
                 // Ledger queries
                 Sub::QueryEpoch(QueryEpoch(args)) => {
                     rpc::query_epoch(args).await;
@@ -64,6 +76,9 @@ pub async fn main() -> Result<()> {
                 Sub::QueryBalance(QueryBalance(args)) => {
                     rpc::query_balance(ctx, args).await;
                 }
+                Sub::QueryTokenMetadata(QueryTokenMetadata(args)) => {
+                    rpc::query_token_metadata(ctx, args).await;
+                }
                 Sub::QueryBonds(QueryBonds(args)) => {
                     rpc::query_bonds(ctx, args).await;
                 }
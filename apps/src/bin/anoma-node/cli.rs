@@ -19,6 +19,14 @@ pub fn main() -> Result<()> {
                 ledger::reset(ctx.config.ledger)
                     .wrap_err("Failed to reset Anoma node")?;
             }
+            cmds::Ledger::Rollback(_) => {
+                ledger::rollback(ctx.config.ledger)
+                    .wrap_err("Failed to rollback Anoma node")?;
+            }
+            cmds::Ledger::DumpState(cmds::LedgerDumpState(args)) => {
+                ledger::dump_state(ctx.config.ledger, args.height)
+                    .wrap_err("Failed to dump Anoma node's state")?;
+            }
         },
         cmds::AnomaNode::Config(sub) => match sub {
             cmds::Config::Gen(cmds::ConfigGen) => {
@@ -1,18 +1,29 @@
 //! Anoma Wallet CLI.
 
-use std::fs::File;
+use std::fmt::Debug;
+use std::fs::{self, File};
 use std::io::{self, Write};
 
-use borsh::BorshSerialize;
+use borsh::{BorshDeserialize, BorshSerialize};
 use color_eyre::eyre::Result;
+use data_encoding::HEXLOWER;
 use itertools::sorted;
 use masp_primitives::zip32::ExtendedFullViewingKey;
+use namada::types::hash::Hash;
 use namada::types::key::*;
 use namada::types::masp::{MaspValue, PaymentAddress};
-use namada_apps::cli;
+use namada::types::token;
+use namada::types::transaction::offline::OfflineTx;
+use namada::types::transaction::{
+    governance, pos, InitAccount, InitValidator, UpdateKey, UpdateVp,
+};
 use namada_apps::cli::{args, cmds, Context};
 use namada_apps::client::tx::find_valid_diversifier;
-use namada_apps::wallet::{DecryptionError, FindKeyError};
+use namada_apps::wallet::{
+    read_bip39_passphrase, read_mnemonic, DecryptionError, FindKeyError, Wallet,
+};
+use namada_apps::wasm_loader::Checksums;
+use namada_apps::{cli, config};
 use rand_core::OsRng;
 
 pub fn main() -> Result<()> {
@@ -22,6 +33,9 @@ pub fn main() -> Result<()> {
             cmds::WalletKey::Gen(cmds::KeyGen(args)) => {
                 key_and_address_gen(ctx, args)
             }
+            cmds::WalletKey::Restore(cmds::KeyRestore(args)) => {
+                key_and_address_restore(ctx, args)
+            }
             cmds::WalletKey::Find(cmds::KeyFind(args)) => key_find(ctx, args),
             cmds::WalletKey::List(cmds::KeyList(args)) => key_list(ctx, args),
             cmds::WalletKey::Export(cmds::Export(args)) => {
@@ -44,6 +58,9 @@ pub fn main() -> Result<()> {
             cmds::WalletMasp::GenSpendKey(cmds::MaspGenSpendKey(args)) => {
                 spending_key_gen(ctx, args)
             }
+            cmds::WalletMasp::RestoreSpendKey(cmds::MaspRestoreSpendKey(
+                args,
+            )) => spending_key_restore(ctx, args),
             cmds::WalletMasp::GenPayAddr(cmds::MaspGenPayAddr(args)) => {
                 payment_address_gen(ctx, args)
             }
@@ -60,10 +77,119 @@ pub fn main() -> Result<()> {
                 address_key_find(ctx, args)
             }
         },
+        cmds::AnomaWallet::Sign(cmds::WalletSign(args)) => sign_tx(ctx, args),
     }
     Ok(())
 }
 
+/// Sign a transaction dumped for offline signing and write it back to its
+/// file.
+fn sign_tx(
+    mut ctx: Context,
+    args::SignTx {
+        tx_path,
+        signing_key,
+    }: args::SignTx,
+) {
+    let mut offline_tx = fs::read(&tx_path)
+        .and_then(|bytes| OfflineTx::try_from_slice(&bytes))
+        .unwrap_or_else(|err| {
+            eprintln!(
+                "Failed to read the transaction from {}: {}",
+                tx_path.to_string_lossy(),
+                err
+            );
+            cli::safe_exit(1)
+        });
+    print_offline_tx(&ctx, &offline_tx);
+    let keypair = ctx.get_cached(&signing_key);
+    if let Err(err) = offline_tx.sign(&keypair) {
+        eprintln!("{}", err);
+        cli::safe_exit(1)
+    }
+    let file_data = offline_tx
+        .try_to_vec()
+        .expect("Encoding an offline transaction shouldn't fail");
+    fs::write(&tx_path, file_data).unwrap_or_else(|err| {
+        eprintln!(
+            "Failed to write the signed transaction to {}: {}",
+            tx_path.to_string_lossy(),
+            err
+        );
+        cli::safe_exit(1)
+    });
+    println!(
+        "Signed the transaction of {} with the key {}, written to {}.",
+        offline_tx.signer.encode(),
+        keypair.ref_to(),
+        tx_path.to_string_lossy()
+    );
+}
+
+/// Print what a transaction dumped for offline signing contains. Its data is
+/// decoded if its code is one of the transactions from the checksums of the
+/// WASM directory.
+fn print_offline_tx(ctx: &Context, offline_tx: &OfflineTx) {
+    let tx = &offline_tx.tx;
+    let code_hash = Hash::sha256(&tx.code).to_string().to_lowercase();
+    let checksums_path =
+        ctx.wasm_dir().join(config::DEFAULT_WASM_CHECKSUMS_FILE);
+    let tx_name = fs::read(checksums_path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<Checksums>(&bytes).ok())
+        .and_then(|checksums| {
+            checksums.0.into_iter().find_map(|(name, file_name)| {
+                file_name.contains(&code_hash).then_some(name)
+            })
+        });
+    println!("Chain ID: {}", tx.chain_id);
+    println!("Signer: {}", offline_tx.signer.encode());
+    println!(
+        "Code hash: {} ({})",
+        code_hash,
+        tx_name.as_deref().unwrap_or("unknown transaction")
+    );
+    let data = match &tx.data {
+        Some(data) => data,
+        None => {
+            println!("Data: none");
+            return;
+        }
+    };
+    let decoded = match tx_name.as_deref() {
+        Some("tx_transfer.wasm") => decode_tx_data::<token::Transfer>(data),
+        Some("tx_bond.wasm") => decode_tx_data::<pos::Bond>(data),
+        Some("tx_unbond.wasm") => decode_tx_data::<pos::Unbond>(data),
+        Some("tx_withdraw.wasm") => decode_tx_data::<pos::Withdraw>(data),
+        Some("tx_change_validator_commission.wasm") => {
+            decode_tx_data::<pos::CommissionChange>(data)
+        }
+        Some("tx_init_account.wasm") => decode_tx_data::<InitAccount>(data),
+        Some("tx_init_validator.wasm") => decode_tx_data::<InitValidator>(data),
+        Some("tx_update_vp.wasm") => decode_tx_data::<UpdateVp>(data),
+        Some("tx_update_key.wasm") => decode_tx_data::<UpdateKey>(data),
+        Some("tx_reveal_pk.wasm") => decode_tx_data::<common::PublicKey>(data),
+        Some("tx_init_proposal.wasm") => {
+            decode_tx_data::<governance::InitProposalData>(data)
+        }
+        Some("tx_vote_proposal.wasm") => {
+            decode_tx_data::<governance::VoteProposalData>(data)
+        }
+        _ => None,
+    };
+    match decoded {
+        Some(decoded) => println!("Data: {}", decoded),
+        None => println!("Data (hex): {}", HEXLOWER.encode(data)),
+    }
+}
+
+/// Decode the data of a transaction to be printed
+fn decode_tx_data<T: BorshDeserialize + Debug>(data: &[u8]) -> Option<String> {
+    T::try_from_slice(data)
+        .ok()
+        .map(|decoded| format!("{:#?}", decoded))
+}
+
 /// Find shielded address or key
 fn address_key_find(
     ctx: Context,
@@ -137,6 +263,10 @@ fn spending_keys_list(
             }
             // Always print the corresponding viewing key
             writeln!(w, "    Viewing Key: {}", key).unwrap();
+            if let Some(path) = wallet.find_spending_key_derivation_path(&alias)
+            {
+                writeln!(w, "    Derivation path: {}", path).unwrap();
+            }
             // A subset of viewing keys will have corresponding spending keys.
             // Print those too if they are available and requested.
             if unsafe_show_secret {
@@ -194,12 +324,29 @@ fn spending_key_gen(
     ctx: Context,
     args::MaspSpendKeyGen {
         alias,
+        hd,
+        bip39_passphrase,
+        derivation_path,
         unsafe_dont_encrypt,
     }: args::MaspSpendKeyGen,
 ) {
     let mut wallet = ctx.wallet;
     let alias = alias.to_lowercase();
-    let (alias, _key) = wallet.gen_spending_key(alias, unsafe_dont_encrypt);
+    let (alias, _key) = if hd {
+        let mnemonic = Wallet::gen_mnemonic();
+        let passphrase = read_opt_bip39_passphrase(bip39_passphrase, true);
+        let added = wallet.derive_spending_key_from_mnemonic(
+            &mnemonic,
+            &passphrase,
+            derivation_path,
+            alias,
+            unsafe_dont_encrypt,
+        );
+        print_mnemonic(&mnemonic, bip39_passphrase);
+        added
+    } else {
+        wallet.gen_spending_key(alias, unsafe_dont_encrypt)
+    };
     wallet.save().unwrap_or_else(|err| eprintln!("{}", err));
     println!(
         "Successfully added a spending key with alias: \"{}\"",
@@ -207,6 +354,60 @@ fn spending_key_gen(
     );
 }
 
+/// Restore a spending key from its mnemonic code.
+fn spending_key_restore(
+    ctx: Context,
+    args::MaspSpendKeyRestore {
+        alias,
+        mnemonic,
+        bip39_passphrase,
+        derivation_path,
+        unsafe_dont_encrypt,
+    }: args::MaspSpendKeyRestore,
+) {
+    let mut wallet = ctx.wallet;
+    let alias = alias.to_lowercase();
+    let mnemonic = read_mnemonic(mnemonic);
+    let passphrase = read_opt_bip39_passphrase(bip39_passphrase, false);
+    let (alias, _key) = wallet.derive_spending_key_from_mnemonic(
+        &mnemonic,
+        &passphrase,
+        derivation_path,
+        alias,
+        unsafe_dont_encrypt,
+    );
+    wallet.save().unwrap_or_else(|err| eprintln!("{}", err));
+    println!(
+        "Successfully restored a spending key with alias: \"{}\"",
+        alias
+    );
+}
+
+/// Print a newly generated mnemonic code for the user to back up.
+fn print_mnemonic(mnemonic: &bip39::Mnemonic, bip39_passphrase: bool) {
+    println!(
+        "Write down the following mnemonic code and keep it safe. It is the \
+         only way to restore the key if the wallet is lost:\n{}",
+        mnemonic.phrase()
+    );
+    if bip39_passphrase {
+        println!(
+            "The key can only be restored from this mnemonic code together \
+             with its BIP39 passphrase."
+        );
+    }
+}
+
+/// Read the BIP39 passphrase of a mnemonic code if it is protected by one,
+/// or use the empty passphrase.
+fn read_opt_bip39_passphrase(bip39_passphrase: bool, confirm: bool) -> String {
+    if bip39_passphrase {
+        read_bip39_passphrase(confirm)
+    } else {
+        String::new()
+    }
+}
+
 /// Generate a shielded payment address from the given key.
 fn payment_address_gen(
     mut ctx: Context,
@@ -302,11 +503,29 @@ fn key_and_address_gen(
     args::KeyAndAddressGen {
         scheme,
         alias,
+        hd,
+        bip39_passphrase,
+        derivation_path,
         unsafe_dont_encrypt,
     }: args::KeyAndAddressGen,
 ) {
     let mut wallet = ctx.wallet;
-    let (alias, _key) = wallet.gen_key(scheme, alias, unsafe_dont_encrypt);
+    let (alias, _key) = if hd {
+        let mnemonic = Wallet::gen_mnemonic();
+        let passphrase = read_opt_bip39_passphrase(bip39_passphrase, true);
+        let added = wallet.derive_key_from_mnemonic(
+            scheme,
+            &mnemonic,
+            &passphrase,
+            derivation_path,
+            alias,
+            unsafe_dont_encrypt,
+        );
+        print_mnemonic(&mnemonic, bip39_passphrase);
+        added
+    } else {
+        wallet.gen_key(scheme, alias, unsafe_dont_encrypt)
+    };
     wallet.save().unwrap_or_else(|err| eprintln!("{}", err));
     println!(
         "Successfully added a key and an address with alias: \"{}\"",
@@ -314,6 +533,37 @@ fn key_and_address_gen(
     );
 }
 
+/// Restore a keypair from its mnemonic code, derive the implicit address from
+/// it and store them in the wallet.
+fn key_and_address_restore(
+    ctx: Context,
+    args::KeyRestore {
+        scheme,
+        alias,
+        mnemonic,
+        bip39_passphrase,
+        derivation_path,
+        unsafe_dont_encrypt,
+    }: args::KeyRestore,
+) {
+    let mut wallet = ctx.wallet;
+    let mnemonic = read_mnemonic(mnemonic);
+    let passphrase = read_opt_bip39_passphrase(bip39_passphrase, false);
+    let (alias, _key) = wallet.derive_key_from_mnemonic(
+        scheme,
+        &mnemonic,
+        &passphrase,
+        derivation_path,
+        alias,
+        unsafe_dont_encrypt,
+    );
+    wallet.save().unwrap_or_else(|err| eprintln!("{}", err));
+    println!(
+        "Successfully restored a key and an address with alias: \"{}\"",
+        alias
+    );
+}
+
 /// Find a keypair in the wallet store.
 fn key_find(
     ctx: Context,
@@ -385,6 +635,9 @@ fn key_list(
             if let Some(pkh) = pkh {
                 writeln!(w, "    Public key hash: {}", pkh).unwrap();
             }
+            if let Some(path) = wallet.find_derivation_path(&alias) {
+                writeln!(w, "    Derivation path: {}", path).unwrap();
+            }
             match stored_keypair.get(decrypt, None) {
                 Ok(keypair) => {
                     writeln!(w, "    Public key: {}", keypair.ref_to())
@@ -442,6 +695,20 @@ fn address_list(ctx: Context) {
         for (alias, address) in sorted(known_addresses) {
             writeln!(w, "  \"{}\": {}", alias, address.to_pretty_string())
                 .unwrap();
+            // List the keys of accounts whose key has been rotated, the last
+            // one being the current key
+            if let Some(history) = wallet.find_key_history(&alias) {
+                writeln!(w, "    Key history:").unwrap();
+                for pkh in history {
+                    match wallet.find_alias_by_pkh(pkh) {
+                        Some(key_alias) => {
+                            writeln!(w, "      {} (\"{}\")", pkh, key_alias)
+                        }
+                        None => writeln!(w, "      {}", pkh),
+                    }
+                    .unwrap();
+                }
+            }
         }
     }
 }
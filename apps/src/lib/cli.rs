@@ -155,8 +155,10 @@ pub mod cmds {
                 .subcommand(TxTransfer::def().display_order(1))
                 .subcommand(TxIbcTransfer::def().display_order(1))
                 .subcommand(TxUpdateVp::def().display_order(1))
+                .subcommand(TxUpdateKey::def().display_order(1))
                 .subcommand(TxInitAccount::def().display_order(1))
                 .subcommand(TxRevealPk::def().display_order(1))
+                .subcommand(TxSubmitSigned::def().display_order(1))
                 // Proposal transactions
                 .subcommand(TxInitProposal::def().display_order(1))
                 .subcommand(TxVoteProposal::def().display_order(1))
@@ -165,12 +167,15 @@ pub mod cmds {
                 .subcommand(Bond::def().display_order(2))
                 .subcommand(Unbond::def().display_order(2))
                 .subcommand(Withdraw::def().display_order(2))
+                .subcommand(TxUnjailValidator::def().display_order(2))
+                .subcommand(TxClaimRewards::def().display_order(2))
                 // Queries
                 .subcommand(QueryEpoch::def().display_order(3))
                 .subcommand(QueryTransfers::def().display_order(3))
                 .subcommand(QueryConversions::def().display_order(3))
                 .subcommand(QueryBlock::def().display_order(3))
                 .subcommand(QueryBalance::def().display_order(3))
+                .subcommand(QueryTokenMetadata::def().display_order(3))
                 .subcommand(QueryBonds::def().display_order(3))
                 .subcommand(QueryBondedStake::def().display_order(3))
                 .subcommand(QuerySlashes::def().display_order(3))
@@ -189,10 +194,13 @@ pub mod cmds {
             let tx_transfer = Self::parse_with_ctx(matches, TxTransfer);
             let tx_ibc_transfer = Self::parse_with_ctx(matches, TxIbcTransfer);
             let tx_update_vp = Self::parse_with_ctx(matches, TxUpdateVp);
+            let tx_update_key = Self::parse_with_ctx(matches, TxUpdateKey);
             let tx_init_account = Self::parse_with_ctx(matches, TxInitAccount);
             let tx_init_validator =
                 Self::parse_with_ctx(matches, TxInitValidator);
             let tx_reveal_pk = Self::parse_with_ctx(matches, TxRevealPk);
+            let tx_submit_signed =
+                Self::parse_with_ctx(matches, TxSubmitSigned);
             let tx_init_proposal =
                 Self::parse_with_ctx(matches, TxInitProposal);
             let tx_vote_proposal =
@@ -200,12 +208,18 @@ pub mod cmds {
             let bond = Self::parse_with_ctx(matches, Bond);
             let unbond = Self::parse_with_ctx(matches, Unbond);
             let withdraw = Self::parse_with_ctx(matches, Withdraw);
+            let tx_unjail_validator =
+                Self::parse_with_ctx(matches, TxUnjailValidator);
+            let tx_claim_rewards =
+                Self::parse_with_ctx(matches, TxClaimRewards);
             let query_epoch = Self::parse_with_ctx(matches, QueryEpoch);
             let query_transfers = Self::parse_with_ctx(matches, QueryTransfers);
             let query_conversions =
                 Self::parse_with_ctx(matches, QueryConversions);
             let query_block = Self::parse_with_ctx(matches, QueryBlock);
             let query_balance = Self::parse_with_ctx(matches, QueryBalance);
+            let query_token_metadata =
+                Self::parse_with_ctx(matches, QueryTokenMetadata);
             let query_bonds = Self::parse_with_ctx(matches, QueryBonds);
             let query_bonded_stake =
                 Self::parse_with_ctx(matches, QueryBondedStake);
@@ -222,19 +236,24 @@ pub mod cmds {
                 .or(tx_transfer)
                 .or(tx_ibc_transfer)
                 .or(tx_update_vp)
+                .or(tx_update_key)
                 .or(tx_init_account)
                 .or(tx_reveal_pk)
+                .or(tx_submit_signed)
                 .or(tx_init_proposal)
                 .or(tx_vote_proposal)
                 .or(tx_init_validator)
                 .or(bond)
                 .or(unbond)
                 .or(withdraw)
+                .or(tx_unjail_validator)
+                .or(tx_claim_rewards)
                 .or(query_epoch)
                 .or(query_transfers)
                 .or(query_conversions)
                 .or(query_block)
                 .or(query_balance)
+                .or(query_token_metadata)
                 .or(query_bonds)
                 .or(query_bonded_stake)
                 .or(query_slashes)
@@ -284,19 +303,24 @@ pub mod cmds {
         TxIbcTransfer(TxIbcTransfer),
         QueryResult(QueryResult),
         TxUpdateVp(TxUpdateVp),
+        TxUpdateKey(TxUpdateKey),
         TxInitAccount(TxInitAccount),
         TxInitValidator(TxInitValidator),
         TxInitProposal(TxInitProposal),
         TxVoteProposal(TxVoteProposal),
         TxRevealPk(TxRevealPk),
+        TxSubmitSigned(TxSubmitSigned),
         Bond(Bond),
         Unbond(Unbond),
         Withdraw(Withdraw),
+        TxUnjailValidator(TxUnjailValidator),
+        TxClaimRewards(TxClaimRewards),
         QueryEpoch(QueryEpoch),
         QueryTransfers(QueryTransfers),
         QueryConversions(QueryConversions),
         QueryBlock(QueryBlock),
         QueryBalance(QueryBalance),
+        QueryTokenMetadata(QueryTokenMetadata),
         QueryBonds(QueryBonds),
         QueryBondedStake(QueryBondedStake),
         QueryCommissionRate(QueryCommissionRate),
@@ -316,6 +340,8 @@ pub mod cmds {
         Address(WalletAddress),
         /// MASP key, address management commands
         Masp(WalletMasp),
+        /// Sign a transaction dumped for offline signing
+        Sign(WalletSign),
     }
 
     impl Cmd for AnomaWallet {
@@ -323,13 +349,15 @@ pub mod cmds {
             app.subcommand(WalletKey::def())
                 .subcommand(WalletAddress::def())
                 .subcommand(WalletMasp::def())
+                .subcommand(WalletSign::def())
         }
 
         fn parse(matches: &ArgMatches) -> Option<Self> {
             let key = SubCmd::parse(matches).map(Self::Key);
             let address = SubCmd::parse(matches).map(Self::Address);
             let masp = SubCmd::parse(matches).map(Self::Masp);
-            key.or(address).or(masp)
+            let sign = SubCmd::parse(matches).map(Self::Sign);
+            key.or(address).or(masp).or(sign)
         }
     }
 
@@ -355,6 +383,7 @@ pub mod cmds {
     #[allow(clippy::large_enum_variant)]
     pub enum WalletKey {
         Gen(KeyGen),
+        Restore(KeyRestore),
         Find(KeyFind),
         List(KeyList),
         Export(Export),
@@ -366,10 +395,11 @@ pub mod cmds {
         fn parse(matches: &ArgMatches) -> Option<Self> {
             matches.subcommand_matches(Self::CMD).and_then(|matches| {
                 let generate = SubCmd::parse(matches).map(Self::Gen);
+                let restore = SubCmd::parse(matches).map(Self::Restore);
                 let lookup = SubCmd::parse(matches).map(Self::Find);
                 let list = SubCmd::parse(matches).map(Self::List);
                 let export = SubCmd::parse(matches).map(Self::Export);
-                generate.or(lookup).or(list).or(export)
+                generate.or(restore).or(lookup).or(list).or(export)
             })
         }
 
@@ -381,6 +411,7 @@ pub mod cmds {
                 )
                 .setting(AppSettings::SubcommandRequiredElseHelp)
                 .subcommand(KeyGen::def())
+                .subcommand(KeyRestore::def())
                 .subcommand(KeyFind::def())
                 .subcommand(KeyList::def())
                 .subcommand(Export::def())
@@ -411,6 +442,30 @@ pub mod cmds {
         }
     }
 
+    /// Restore a keypair derived from a mnemonic code and its implicit address
+    #[derive(Clone, Debug)]
+    pub struct KeyRestore(pub args::KeyRestore);
+
+    impl SubCmd for KeyRestore {
+        const CMD: &'static str = "restore";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::KeyRestore::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Restores a keypair from the mnemonic code it was derived \
+                     from and derives the implicit address from its public \
+                     key. The address will be stored with the same alias.",
+                )
+                .add_args::<args::KeyRestore>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct KeyFind(pub args::KeyFind);
 
@@ -468,11 +523,39 @@ pub mod cmds {
         }
     }
 
+    /// Sign a transaction dumped for offline signing
+    #[derive(Clone, Debug)]
+    pub struct WalletSign(pub args::SignTx);
+
+    impl SubCmd for WalletSign {
+        const CMD: &'static str = "sign";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::SignTx::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Sign a transaction dumped with `--dump-tx` without \
+                     connecting to the ledger. Its chain ID, code hash and \
+                     data are printed before it is signed. The signature is \
+                     added to the transaction's file, which can be passed on \
+                     to the other signers of a multisignature account and \
+                     then submitted with `submit-signed`.",
+                )
+                .add_args::<args::SignTx>()
+        }
+    }
+
     #[allow(clippy::large_enum_variant)]
     #[derive(Clone, Debug)]
     pub enum WalletMasp {
         GenPayAddr(MaspGenPayAddr),
         GenSpendKey(MaspGenSpendKey),
+        RestoreSpendKey(MaspRestoreSpendKey),
         AddAddrKey(MaspAddAddrKey),
         ListPayAddrs(MaspListPayAddrs),
         ListKeys(MaspListKeys),
@@ -486,11 +569,19 @@ pub mod cmds {
             matches.subcommand_matches(Self::CMD).and_then(|matches| {
                 let genpa = SubCmd::parse(matches).map(Self::GenPayAddr);
                 let gensk = SubCmd::parse(matches).map(Self::GenSpendKey);
+                let restoresk =
+                    SubCmd::parse(matches).map(Self::RestoreSpendKey);
                 let addak = SubCmd::parse(matches).map(Self::AddAddrKey);
                 let listpa = SubCmd::parse(matches).map(Self::ListPayAddrs);
                 let listsk = SubCmd::parse(matches).map(Self::ListKeys);
                 let findak = SubCmd::parse(matches).map(Self::FindAddrKey);
-                gensk.or(genpa).or(addak).or(listpa).or(listsk).or(findak)
+                gensk
+                    .or(restoresk)
+                    .or(genpa)
+                    .or(addak)
+                    .or(listpa)
+                    .or(listsk)
+                    .or(findak)
             })
         }
 
@@ -503,6 +594,7 @@ pub mod cmds {
                 )
                 .setting(AppSettings::SubcommandRequiredElseHelp)
                 .subcommand(MaspGenSpendKey::def())
+                .subcommand(MaspRestoreSpendKey::def())
                 .subcommand(MaspGenPayAddr::def())
                 .subcommand(MaspAddAddrKey::def())
                 .subcommand(MaspListPayAddrs::def())
@@ -605,11 +697,37 @@ pub mod cmds {
 
         fn def() -> App {
             App::new(Self::CMD)
-                .about("Generates a random spending key")
+                .about(
+                    "Generates a random spending key, or one derived from a \
+                     new mnemonic code with `--hd`",
+                )
                 .add_args::<args::MaspSpendKeyGen>()
         }
     }
 
+    /// Restore a spending key derived from a mnemonic code
+    #[derive(Clone, Debug)]
+    pub struct MaspRestoreSpendKey(pub args::MaspSpendKeyRestore);
+
+    impl SubCmd for MaspRestoreSpendKey {
+        const CMD: &'static str = "restore-key";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                MaspRestoreSpendKey(args::MaspSpendKeyRestore::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Restores a spending key from the mnemonic code it was \
+                     derived from",
+                )
+                .add_args::<args::MaspSpendKeyRestore>()
+        }
+    }
+
     /// Generate a payment address from a viewing key or payment address
     #[derive(Clone, Debug)]
     pub struct MaspGenPayAddr(pub args::MaspPayAddrGen);
@@ -755,6 +873,8 @@ pub mod cmds {
     pub enum Ledger {
         Run(LedgerRun),
         Reset(LedgerReset),
+        Rollback(LedgerRollback),
+        DumpState(LedgerDumpState),
     }
 
     impl SubCmd for Ledger {
@@ -764,7 +884,11 @@ pub mod cmds {
             matches.subcommand_matches(Self::CMD).and_then(|matches| {
                 let run = SubCmd::parse(matches).map(Self::Run);
                 let reset = SubCmd::parse(matches).map(Self::Reset);
+                let rollback = SubCmd::parse(matches).map(Self::Rollback);
+                let dump_state = SubCmd::parse(matches).map(Self::DumpState);
                 run.or(reset)
+                    .or(rollback)
+                    .or(dump_state)
                     // The `run` command is the default if no sub-command given
                     .or(Some(Self::Run(LedgerRun)))
             })
@@ -778,6 +902,8 @@ pub mod cmds {
                 )
                 .subcommand(LedgerRun::def())
                 .subcommand(LedgerReset::def())
+                .subcommand(LedgerRollback::def())
+                .subcommand(LedgerDumpState::def())
         }
     }
 
@@ -814,6 +940,47 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct LedgerRollback;
+
+    impl SubCmd for LedgerRollback {
+        const CMD: &'static str = "rollback";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|_matches| Self)
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD).about(
+                "Roll back Anoma ledger node's and Tendermint node's state by \
+                 one block height, so that the last block is applied again \
+                 when the node is restarted.",
+            )
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct LedgerDumpState(pub args::LedgerDumpState);
+
+    impl SubCmd for LedgerDumpState {
+        const CMD: &'static str = "dump-state";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::LedgerDumpState::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Export the state of Anoma ledger node at a committed \
+                     block height, from which a new chain can be started.",
+                )
+                .add_args::<args::LedgerDumpState>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub enum Config {
         Gen(ConfigGen),
@@ -1018,6 +1185,28 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct TxUpdateKey(pub args::TxUpdateKey);
+
+    impl SubCmd for TxUpdateKey {
+        const CMD: &'static str = "update-key";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| TxUpdateKey(args::TxUpdateKey::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Send a transaction signed with the current key of an \
+                     established account to replace it with a new key.",
+                )
+                .add_args::<args::TxUpdateKey>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct TxInitAccount(pub args::TxInitAccount);
 
@@ -1119,6 +1308,50 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct TxUnjailValidator(pub args::TxUnjailValidator);
+
+    impl SubCmd for TxUnjailValidator {
+        const CMD: &'static str = "unjail-validator";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxUnjailValidator(args::TxUnjailValidator::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Send a signed transaction to unjail a validator that has \
+                     been jailed for missing too many blocks.",
+                )
+                .add_args::<args::TxUnjailValidator>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct TxClaimRewards(pub args::TxClaimRewards);
+
+    impl SubCmd for TxClaimRewards {
+        const CMD: &'static str = "claim-rewards";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxClaimRewards(args::TxClaimRewards::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Send a signed transaction to claim the inflation rewards \
+                     accumulated in the rewards pool of a bond.",
+                )
+                .add_args::<args::TxClaimRewards>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct QueryEpoch(pub args::Query);
 
@@ -1195,6 +1428,25 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct QueryTokenMetadata(pub args::QueryTokenMetadata);
+
+    impl SubCmd for QueryTokenMetadata {
+        const CMD: &'static str = "query-token";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                QueryTokenMetadata(args::QueryTokenMetadata::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Query the denomination, symbol and name of a token.")
+                .add_args::<args::QueryTokenMetadata>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct QueryBonds(pub args::QueryBonds);
 
@@ -1386,6 +1638,34 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct TxSubmitSigned(pub args::SubmitSigned);
+
+    impl SubCmd for TxSubmitSigned {
+        const CMD: &'static str = "submit-signed";
+
+        fn parse(matches: &ArgMatches) -> Option<Self>
+        where
+            Self: Sized,
+        {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxSubmitSigned(args::SubmitSigned::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Submit a transaction dumped with `--dump-tx` and signed \
+                     offline with the wallet's `sign` command. The wrapper of \
+                     the transaction, which pays its fee, is signed with the \
+                     `--signing-key` or the key of the `--signer` from this \
+                     wallet.",
+                )
+                .add_args::<args::SubmitSigned>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub enum Utils {
         JoinNetwork(JoinNetwork),
@@ -1519,6 +1799,7 @@ pub mod args {
     use namada::types::key::*;
     use namada::types::masp::MaspValue;
     use namada::types::storage::{self, Epoch};
+    use namada::types::time::DateTimeUtc;
     use namada::types::token;
     use namada::types::transaction::GasLimit;
     use rust_decimal::Decimal;
@@ -1531,12 +1812,13 @@ pub mod args {
     use crate::config::TendermintMode;
     use crate::facade::tendermint::Timeout;
     use crate::facade::tendermint_config::net::Address as TendermintAddress;
+    use crate::wallet::DerivationPath;
 
     const ADDRESS: Arg<WalletAddress> = arg("address");
     const ALIAS_OPT: ArgOpt<String> = ALIAS.opt();
     const ALIAS: Arg<String> = arg("alias");
     const ALLOW_DUPLICATE_IP: ArgFlag = flag("allow-duplicate-ip");
-    const AMOUNT: Arg<token::Amount> = arg("amount");
+    const AMOUNT: Arg<token::DecimalAmount> = arg("amount");
     const ARCHIVE_DIR: ArgOpt<PathBuf> = arg_opt("archive-dir");
     const BALANCE_OWNER: ArgOpt<WalletBalanceOwner> = arg_opt("owner");
     const BASE_DIR: ArgDefault<PathBuf> = arg_default(
@@ -1546,6 +1828,8 @@ pub mod args {
             Err(_) => config::DEFAULT_BASE_DIR.into(),
         }),
     );
+    const BIP39_PASSPHRASE: ArgFlag = flag("bip39-passphrase");
+    const BLOCK_HEIGHT_OPT: ArgOpt<u64> = arg_opt("height");
     const BROADCAST_ONLY: ArgFlag = flag("broadcast-only");
     const CHAIN_ID: Arg<ChainId> = arg("chain-id");
     const CHAIN_ID_OPT: ArgOpt<ChainId> = CHAIN_ID.opt();
@@ -1561,19 +1845,23 @@ pub mod args {
     const DATA_PATH_OPT: ArgOpt<PathBuf> = arg_opt("data-path");
     const DATA_PATH: Arg<PathBuf> = arg("data-path");
     const DECRYPT: ArgFlag = flag("decrypt");
+    const DERIVATION_PATH: ArgOpt<DerivationPath> = arg_opt("derivation-path");
     const DONT_ARCHIVE: ArgFlag = flag("dont-archive");
+    const DUMP_TX: ArgOpt<PathBuf> = arg_opt("dump-tx");
     const DRY_RUN_TX: ArgFlag = flag("dry-run");
     const EPOCH: ArgOpt<Epoch> = arg_opt("epoch");
+    const EXPIRATION_OPT: ArgOpt<DateTimeUtc> = arg_opt("expiration");
     const FORCE: ArgFlag = flag("force");
     const DONT_PREFETCH_WASM: ArgFlag = flag("dont-prefetch-wasm");
-    const GAS_AMOUNT: ArgDefault<token::Amount> =
-        arg_default("gas-amount", DefaultFn(|| token::Amount::from(0)));
+    const GAS_AMOUNT: ArgDefault<token::DecimalAmount> =
+        arg_default("gas-amount", DefaultFn(token::DecimalAmount::default));
     const GAS_LIMIT: ArgDefault<token::Amount> =
         arg_default("gas-limit", DefaultFn(|| token::Amount::from(0)));
     const GAS_TOKEN: ArgDefaultFromCtx<WalletAddress> =
         arg_default_from_ctx("gas-token", DefaultFn(|| "NAM".into()));
     const GENESIS_PATH: Arg<PathBuf> = arg("genesis-path");
     const GENESIS_VALIDATOR: ArgOpt<String> = arg("genesis-validator").opt();
+    const HD: ArgFlag = flag("hd");
     const LEDGER_ADDRESS_ABOUT: &str =
         "Address of a ledger node as \"{scheme}://{host}:{port}\". If the \
          scheme is not supplied, it is assumed to be TCP.";
@@ -1588,7 +1876,9 @@ pub mod args {
     const MASP_VALUE: Arg<MaspValue> = arg("value");
     const MAX_COMMISSION_RATE_CHANGE: Arg<Decimal> =
         arg("max-commission-rate-change");
+    const MNEMONIC: ArgOpt<String> = arg_opt("mnemonic");
     const MODE: ArgOpt<String> = arg_opt("mode");
+    const MULTISIG_KEYS: ArgMulti<WalletPublicKey> = arg_multi("multisig-keys");
     const NET_ADDRESS: Arg<SocketAddr> = arg("net-address");
     const NO_CONVERSIONS: ArgFlag = flag("no-conversions");
     const OWNER: ArgOpt<WalletAddress> = arg_opt("owner");
@@ -1617,13 +1907,18 @@ pub mod args {
     const SOURCE_OPT: ArgOpt<WalletAddress> = SOURCE.opt();
     const STORAGE_KEY: Arg<storage::Key> = arg("storage-key");
     const SUB_PREFIX: ArgOpt<String> = arg_opt("sub-prefix");
+    const THRESHOLD: ArgOpt<u8> = arg_opt("threshold");
     const TIMEOUT_HEIGHT: ArgOpt<u64> = arg_opt("timeout-height");
     const TIMEOUT_SEC_OFFSET: ArgOpt<u64> = arg_opt("timeout-sec-offset");
     const TOKEN_OPT: ArgOpt<WalletAddress> = TOKEN.opt();
     const TOKEN: Arg<WalletAddress> = arg("token");
+    const TOKEN_DENOMINATION: ArgOpt<u8> = arg_opt("token-denomination");
+    const TOKEN_NAME: ArgOpt<String> = arg_opt("token-name");
+    const TOKEN_SYMBOL: ArgOpt<String> = arg_opt("token-symbol");
     const TRANSFER_SOURCE: Arg<WalletTransferSource> = arg("source");
     const TRANSFER_TARGET: Arg<WalletTransferTarget> = arg("target");
     const TX_HASH: Arg<String> = arg("tx-hash");
+    const TX_PATH: Arg<PathBuf> = arg("tx-path");
     const UNSAFE_DONT_ENCRYPT: ArgFlag = flag("unsafe-dont-encrypt");
     const UNSAFE_SHOW_SECRET: ArgFlag = flag("unsafe-show-secret");
     const VALIDATOR: Arg<WalletAddress> = arg("validator");
@@ -1762,21 +2057,26 @@ pub mod args {
         pub token: WalletAddress,
         /// Transferred token address
         pub sub_prefix: Option<String>,
-        /// Transferred token amount
-        pub amount: token::Amount,
+        /// Transferred token amount in decimal, whose raw amount depends on
+        /// the token's denomination
+        pub amount: token::DecimalAmount,
     }
 
     impl TxTransfer {
+        /// Resolve the arguments from the wallet, with the transferred and
+        /// fee amounts converted with their token's denomination
         pub fn parse_from_context(
             &self,
             ctx: &mut Context,
+            amount: token::Amount,
+            fee_amount: token::Amount,
         ) -> ParsedTxTransferArgs {
             ParsedTxTransferArgs {
-                tx: self.tx.parse_from_context(ctx),
+                tx: self.tx.parse_from_context(ctx, fee_amount),
                 source: ctx.get_cached(&self.source),
                 target: ctx.get(&self.target),
                 token: ctx.get(&self.token),
-                amount: self.amount,
+                amount,
             }
         }
     }
@@ -1811,7 +2111,10 @@ pub mod args {
                 ))
                 .arg(TOKEN.def().about("The transfer token."))
                 .arg(SUB_PREFIX.def().about("The token's sub prefix."))
-                .arg(AMOUNT.def().about("The amount to transfer in decimal."))
+                .arg(AMOUNT.def().about(
+                    "The amount to transfer in decimal, with at most as many \
+                     decimal places as the token's denomination.",
+                ))
         }
     }
 
@@ -1828,8 +2131,9 @@ pub mod args {
         pub token: WalletAddress,
         /// Transferred token address
         pub sub_prefix: Option<String>,
-        /// Transferred token amount
-        pub amount: token::Amount,
+        /// Transferred token amount in decimal, whose raw amount depends on
+        /// the token's denomination
+        pub amount: token::DecimalAmount,
         /// Port ID
         pub port_id: PortId,
         /// Channel ID
@@ -1877,7 +2181,10 @@ pub mod args {
                 ))
                 .arg(TOKEN.def().about("The transfer token."))
                 .arg(SUB_PREFIX.def().about("The token's sub prefix."))
-                .arg(AMOUNT.def().about("The amount to transfer in decimal."))
+                .arg(AMOUNT.def().about(
+                    "The amount to transfer in decimal, with at most as many \
+                     decimal places as the token's denomination.",
+                ))
                 .arg(PORT_ID.def().about("The port ID."))
                 .arg(CHANNEL_ID.def().about("The channel ID."))
                 .arg(
@@ -1900,6 +2207,12 @@ pub mod args {
         pub vp_code_path: Option<PathBuf>,
         /// Public key for the new account
         pub public_key: WalletPublicKey,
+        /// Public keys of a new multisignature account
+        pub multisig_keys: Vec<WalletPublicKey>,
+        /// Number of signatures required by a new multisignature account
+        pub threshold: Option<u8>,
+        /// Metadata of a new token account
+        pub token_metadata: Option<token::Metadata>,
     }
 
     impl Args for TxInitAccount {
@@ -1908,11 +2221,25 @@ pub mod args {
             let source = SOURCE.parse(matches);
             let vp_code_path = CODE_PATH_OPT.parse(matches);
             let public_key = PUBLIC_KEY.parse(matches);
+            let multisig_keys = MULTISIG_KEYS.parse(matches);
+            let threshold = THRESHOLD.parse(matches);
+            let denomination = TOKEN_DENOMINATION.parse(matches);
+            let name = TOKEN_NAME.parse(matches);
+            let token_metadata =
+                TOKEN_SYMBOL.parse(matches).map(|symbol| token::Metadata {
+                    denomination: denomination
+                        .unwrap_or(token::MAX_DECIMAL_PLACES as u8),
+                    name: name.unwrap_or_else(|| symbol.clone()),
+                    symbol,
+                });
             Self {
                 tx,
                 source,
                 vp_code_path,
                 public_key,
+                multisig_keys,
+                threshold,
+                token_metadata,
             }
         }
 
@@ -1930,6 +2257,29 @@ pub mod args {
                     "A public key to be used for the new account in \
                      hexadecimal encoding.",
                 ))
+                .arg(MULTISIG_KEYS.def().about(
+                    "The public keys of a new multisignature account. The \
+                     signatures of its transactions are indexed by the \
+                     position of their key in this list.",
+                ))
+                .arg(THRESHOLD.def().requires(MULTISIG_KEYS.name).about(
+                    "The number of the multisignature keys that must sign the \
+                     transactions of the new account.",
+                ))
+                .arg(TOKEN_SYMBOL.def().about(
+                    "The ticker symbol of a new token account, e.g. \"NAM\". \
+                     Its metadata cannot be changed once it's created.",
+                ))
+                .arg(
+                    TOKEN_DENOMINATION.def().requires(TOKEN_SYMBOL.name).about(
+                        "The number of decimal places of the new token's \
+                         amounts. Defaults to 6.",
+                    ),
+                )
+                .arg(TOKEN_NAME.def().requires(TOKEN_SYMBOL.name).about(
+                    "The display name of the new token. Defaults to its \
+                     symbol.",
+                ))
         }
     }
 
@@ -2056,6 +2406,43 @@ pub mod args {
         }
     }
 
+    /// Transaction to rotate the public key of an account arguments
+    #[derive(Clone, Debug)]
+    pub struct TxUpdateKey {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// Address of the account whose key is to be rotated
+        pub addr: WalletAddress,
+        /// The new public key of the account
+        pub public_key: WalletPublicKey,
+    }
+
+    impl Args for TxUpdateKey {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let addr = ADDRESS.parse(matches);
+            let public_key = PUBLIC_KEY.parse(matches);
+            Self {
+                tx,
+                addr,
+                public_key,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>()
+                .arg(ADDRESS.def().about(
+                    "The established account's address. Its current key is \
+                     used to produce the signature.",
+                ))
+                .arg(PUBLIC_KEY.def().about(
+                    "The new public key of the account. Its secret key must \
+                     be in the wallet to sign the rotation. The old key is \
+                     kept in the wallet's key history of the account's alias.",
+                ))
+        }
+    }
+
     /// Bond arguments
     #[derive(Clone, Debug)]
     pub struct Bond {
@@ -2063,8 +2450,9 @@ pub mod args {
         pub tx: Tx,
         /// Validator address
         pub validator: WalletAddress,
-        /// Amount of tokens to stake in a bond
-        pub amount: token::Amount,
+        /// Amount of tokens to stake in a bond in decimal, converted with the
+        /// staking token's denomination
+        pub amount: token::DecimalAmount,
         /// Source address for delegations. For self-bonds, the validator is
         /// also the source.
         pub source: Option<WalletAddress>,
@@ -2087,7 +2475,10 @@ pub mod args {
         fn def(app: App) -> App {
             app.add_args::<Tx>()
                 .arg(VALIDATOR.def().about("Validator address."))
-                .arg(AMOUNT.def().about("Amount of tokens to stake in a bond."))
+                .arg(AMOUNT.def().about(
+                    "Amount of tokens to stake in a bond in decimal, with at \
+                     most as many decimal places as the token's denomination.",
+                ))
                 .arg(SOURCE_OPT.def().about(
                     "Source address for delegations. For self-bonds, the \
                      validator is also the source.",
@@ -2102,8 +2493,9 @@ pub mod args {
         pub tx: Tx,
         /// Validator address
         pub validator: WalletAddress,
-        /// Amount of tokens to unbond from a bond
-        pub amount: token::Amount,
+        /// Amount of tokens to unbond from a bond in decimal, converted with
+        /// the staking token's denomination
+        pub amount: token::DecimalAmount,
         /// Source address for unbonding from delegations. For unbonding from
         /// self-bonds, the validator is also the source
         pub source: Option<WalletAddress>,
@@ -2126,11 +2518,11 @@ pub mod args {
         fn def(app: App) -> App {
             app.add_args::<Tx>()
                 .arg(VALIDATOR.def().about("Validator address."))
-                .arg(
-                    AMOUNT
-                        .def()
-                        .about("Amount of tokens to unbond from a bond."),
-                )
+                .arg(AMOUNT.def().about(
+                    "Amount of tokens to unbond from a bond in decimal, with \
+                     at most as many decimal places as the token's \
+                     denomination.",
+                ))
                 .arg(SOURCE_OPT.def().about(
                     "Source address for unbonding from delegations. For \
                      unbonding from self-bonds, the validator is also the \
@@ -2217,11 +2609,11 @@ pub mod args {
                             DATA_PATH_OPT.name,
                         ]),
                 )
-                .arg(
-                    PROPOSAL_VOTE
-                        .def()
-                        .about("The vote for the proposal. Either yay or nay."),
-                )
+                .arg(PROPOSAL_VOTE.def().about(
+                    "The vote for the proposal. Either yay, nay or abstain. \
+                     An earlier vote is replaced until the end of the voting \
+                     period.",
+                ))
                 .arg(
                     PROPOSAL_OFFLINE
                         .def()
@@ -2262,6 +2654,62 @@ pub mod args {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct SubmitSigned {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// Path to the file of the transaction signed offline
+        pub tx_path: PathBuf,
+    }
+
+    impl Args for SubmitSigned {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let tx_path = TX_PATH.parse(matches);
+            Self { tx, tx_path }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>().arg(
+                TX_PATH
+                    .def()
+                    .about("The path to the file of the signed transaction."),
+            )
+        }
+    }
+
+    /// Offline transaction signing arguments
+    #[derive(Clone, Debug)]
+    pub struct SignTx {
+        /// Path to the file of the transaction to be signed
+        pub tx_path: PathBuf,
+        /// The key to sign the transaction with
+        pub signing_key: WalletKeypair,
+    }
+
+    impl Args for SignTx {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx_path = TX_PATH.parse(matches);
+            let signing_key = SIGNING_KEY.parse(matches);
+            Self {
+                tx_path,
+                signing_key,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(
+                TX_PATH
+                    .def()
+                    .about("The path to the file of the transaction to sign."),
+            )
+            .arg(SIGNING_KEY.def().about(
+                "Sign the transaction with the key for the given public key, \
+                 public key hash or alias from your wallet.",
+            ))
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct QueryProposal {
         /// Common query args
@@ -2388,6 +2836,67 @@ pub mod args {
         }
     }
 
+    /// Unjail validator arguments
+    #[derive(Clone, Debug)]
+    pub struct TxUnjailValidator {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// Validator address
+        pub validator: WalletAddress,
+    }
+
+    impl Args for TxUnjailValidator {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let validator = VALIDATOR.parse(matches);
+            Self { tx, validator }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>().arg(
+                VALIDATOR
+                    .def()
+                    .about("The address of the jailed validator."),
+            )
+        }
+    }
+
+    /// Claim rewards arguments
+    #[derive(Clone, Debug)]
+    pub struct TxClaimRewards {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// Validator address
+        pub validator: WalletAddress,
+        /// Source address for claiming the rewards of delegations. For
+        /// claiming the rewards of self-bonds, the validator is also the
+        /// source
+        pub source: Option<WalletAddress>,
+    }
+
+    impl Args for TxClaimRewards {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let validator = VALIDATOR.parse(matches);
+            let source = SOURCE_OPT.parse(matches);
+            Self {
+                tx,
+                validator,
+                source,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>()
+                .arg(VALIDATOR.def().about("Validator address."))
+                .arg(SOURCE_OPT.def().about(
+                    "Source address for claiming the rewards of delegations. \
+                     For claiming the rewards of self-bonds, the validator is \
+                     also the source.",
+                ))
+        }
+    }
+
     /// Query asset conversions
     #[derive(Clone, Debug)]
     pub struct QueryConversions {
@@ -2684,6 +3193,32 @@ pub mod args {
             )
         }
     }
+
+    /// Query token metadata arguments
+    #[derive(Clone, Debug)]
+    pub struct QueryTokenMetadata {
+        /// Common query args
+        pub query: Query,
+        /// Address of a token
+        pub token: WalletAddress,
+    }
+
+    impl Args for QueryTokenMetadata {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            let token = TOKEN.parse(matches);
+            Self { query, token }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query>().arg(
+                TOKEN
+                    .def()
+                    .about("The token's address whose metadata to query."),
+            )
+        }
+    }
+
     /// Query the raw bytes of given storage key
     #[derive(Clone, Debug)]
     pub struct QueryRawBytes {
@@ -2719,8 +3254,9 @@ pub mod args {
         /// If any new account is initialized by the tx, use the given alias to
         /// save it in the wallet.
         pub initialized_account_alias: Option<String>,
-        /// The amount being payed to include the transaction
-        pub fee_amount: token::Amount,
+        /// The amount being payed to include the transaction in decimal,
+        /// converted with the fee token's denomination
+        pub fee_amount: token::DecimalAmount,
         /// The token in which the fee is being paid
         pub fee_token: WalletAddress,
         /// The max amount of gas used to process tx
@@ -2729,10 +3265,21 @@ pub mod args {
         pub signing_key: Option<WalletKeypair>,
         /// Sign the tx with the keypair of the public key of the given address
         pub signer: Option<WalletAddress>,
+        /// The optional expiration of the transaction
+        pub expiration: Option<DateTimeUtc>,
+        /// Write the unsigned tx to the given file to be signed offline,
+        /// instead of signing and submitting it
+        pub dump_tx: Option<PathBuf>,
     }
 
     impl Tx {
-        pub fn parse_from_context(&self, ctx: &mut Context) -> ParsedTxArgs {
+        /// Resolve the arguments from the wallet, with the fee amount
+        /// converted with the fee token's denomination
+        pub fn parse_from_context(
+            &self,
+            ctx: &mut Context,
+            fee_amount: token::Amount,
+        ) -> ParsedTxArgs {
             ParsedTxArgs {
                 dry_run: self.dry_run,
                 force: self.force,
@@ -2741,7 +3288,7 @@ pub mod args {
                 initialized_account_alias: self
                     .initialized_account_alias
                     .clone(),
-                fee_amount: self.fee_amount,
+                fee_amount,
                 fee_token: ctx.get(&self.fee_token),
                 gas_limit: self.gas_limit.clone(),
                 signing_key: self
@@ -2749,6 +3296,8 @@ pub mod args {
                     .as_ref()
                     .map(|sk| ctx.get_cached(sk)),
                 signer: self.signer.as_ref().map(|signer| ctx.get(signer)),
+                expiration: self.expiration,
+                dump_tx: self.dump_tx.clone(),
             }
         }
     }
@@ -2775,9 +3324,16 @@ pub mod args {
                  address joined with a number.",
             ))
             .arg(GAS_AMOUNT.def().about(
-                "The amount being paid for the inclusion of this transaction",
+                "The amount being paid for the inclusion of this transaction \
+                 in decimal, with at most as many decimal places as the gas \
+                 token's denomination.",
             ))
             .arg(GAS_TOKEN.def().about("The token for paying the gas"))
+            .arg(EXPIRATION_OPT.def().about(
+                "The expiration datetime of the transaction as an RFC 3339 \
+                 string (e.g. \"2012-12-12T12:12:12Z\"), after which the tx \
+                 won't be accepted anymore.",
+            ))
             .arg(
                 GAS_LIMIT.def().about(
                     "The maximum amount of gas needed to run transaction",
@@ -2802,6 +3358,21 @@ pub mod args {
                     )
                     .conflicts_with(SIGNING_KEY_OPT.name),
             )
+            .arg(
+                DUMP_TX
+                    .def()
+                    .about(
+                        "Write the unsigned transaction to the given file, to \
+                         be signed offline with the wallet's `sign` command \
+                         and then submitted with `submit-signed`. The \
+                         transaction's signer must be given by `--signer`. \
+                         Not supported by `init-validator`.",
+                    )
+                    .conflicts_with_all(&[
+                        SIGNING_KEY_OPT.name,
+                        DRY_RUN_TX.name,
+                    ]),
+            )
         }
 
         fn parse(matches: &ArgMatches) -> Self {
@@ -2816,6 +3387,8 @@ pub mod args {
 
             let signing_key = SIGNING_KEY_OPT.parse(matches);
             let signer = SIGNER.parse(matches);
+            let expiration = EXPIRATION_OPT.parse(matches);
+            let dump_tx = DUMP_TX.parse(matches);
             Self {
                 dry_run,
                 force,
@@ -2827,6 +3400,8 @@ pub mod args {
                 gas_limit,
                 signing_key,
                 signer,
+                expiration,
+                dump_tx,
             }
         }
     }
@@ -2895,6 +3470,12 @@ pub mod args {
     pub struct MaspSpendKeyGen {
         /// Key alias
         pub alias: String,
+        /// Derive the key from a new mnemonic code
+        pub hd: bool,
+        /// Protect the new mnemonic code with a BIP39 passphrase
+        pub bip39_passphrase: bool,
+        /// The derivation path of the key from the mnemonic code
+        pub derivation_path: Option<DerivationPath>,
         /// Don't encrypt the keypair
         pub unsafe_dont_encrypt: bool,
     }
@@ -2902,9 +3483,84 @@ pub mod args {
     impl Args for MaspSpendKeyGen {
         fn parse(matches: &ArgMatches) -> Self {
             let alias = ALIAS.parse(matches);
+            let hd = HD.parse(matches);
+            let bip39_passphrase = BIP39_PASSPHRASE.parse(matches);
+            let derivation_path = DERIVATION_PATH.parse(matches);
+            let unsafe_dont_encrypt = UNSAFE_DONT_ENCRYPT.parse(matches);
+            Self {
+                alias,
+                hd,
+                bip39_passphrase,
+                derivation_path,
+                unsafe_dont_encrypt,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(
+                ALIAS
+                    .def()
+                    .about("An alias to be associated with the spending key."),
+            )
+            .arg(HD.def().about(
+                "Derive the spending key from a new mnemonic code, which is \
+                 printed so that the key can be restored with `masp \
+                 restore-key`.",
+            ))
+            .arg(
+                BIP39_PASSPHRASE
+                    .def()
+                    .about(
+                        "Protect the new mnemonic code with a BIP39 \
+                         passphrase, which is prompted for and is needed \
+                         together with the code to restore the key.",
+                    )
+                    .requires(HD.name),
+            )
+            .arg(
+                DERIVATION_PATH
+                    .def()
+                    .about(
+                        "The ZIP32 derivation path of the spending key. If \
+                         none provided, the default path m/32'/877'/0' is \
+                         used.",
+                    )
+                    .requires(HD.name),
+            )
+            .arg(UNSAFE_DONT_ENCRYPT.def().about(
+                "UNSAFE: Do not encrypt the keypair. Do not use this for keys \
+                 used in a live network.",
+            ))
+        }
+    }
+
+    /// MASP restore spending key arguments
+    #[derive(Clone, Debug)]
+    pub struct MaspSpendKeyRestore {
+        /// Key alias
+        pub alias: String,
+        /// The mnemonic code, prompted for if not given
+        pub mnemonic: Option<String>,
+        /// The mnemonic code is protected by a BIP39 passphrase
+        pub bip39_passphrase: bool,
+        /// The derivation path of the key from the mnemonic code
+        pub derivation_path: Option<DerivationPath>,
+        /// Don't encrypt the keypair
+        pub unsafe_dont_encrypt: bool,
+    }
+
+    impl Args for MaspSpendKeyRestore {
+        fn parse(matches: &ArgMatches) -> Self {
+            let alias = ALIAS.parse(matches);
+            let mnemonic = MNEMONIC.parse(matches);
+            let bip39_passphrase = BIP39_PASSPHRASE.parse(matches);
+            let derivation_path = DERIVATION_PATH.parse(matches);
             let unsafe_dont_encrypt = UNSAFE_DONT_ENCRYPT.parse(matches);
             Self {
                 alias,
+                mnemonic,
+                bip39_passphrase,
+                derivation_path,
                 unsafe_dont_encrypt,
             }
         }
@@ -2915,6 +3571,18 @@ pub mod args {
                     .def()
                     .about("An alias to be associated with the spending key."),
             )
+            .arg(MNEMONIC.def().about(
+                "The mnemonic code of the spending key. If none provided, it \
+                 will be prompted for.",
+            ))
+            .arg(BIP39_PASSPHRASE.def().about(
+                "The mnemonic code is protected by a BIP39 passphrase, which \
+                 is prompted for.",
+            ))
+            .arg(DERIVATION_PATH.def().about(
+                "The ZIP32 derivation path of the spending key. If none \
+                 provided, the default path m/32'/877'/0' is used.",
+            ))
             .arg(UNSAFE_DONT_ENCRYPT.def().about(
                 "UNSAFE: Do not encrypt the keypair. Do not use this for keys \
                  used in a live network.",
@@ -2966,6 +3634,12 @@ pub mod args {
         pub scheme: SchemeType,
         /// Key alias
         pub alias: Option<String>,
+        /// Derive the key from a new mnemonic code
+        pub hd: bool,
+        /// Protect the new mnemonic code with a BIP39 passphrase
+        pub bip39_passphrase: bool,
+        /// The derivation path of the key from the mnemonic code
+        pub derivation_path: Option<DerivationPath>,
         /// Don't encrypt the keypair
         pub unsafe_dont_encrypt: bool,
     }
@@ -2974,10 +3648,16 @@ pub mod args {
         fn parse(matches: &ArgMatches) -> Self {
             let scheme = SCHEME.parse(matches);
             let alias = ALIAS_OPT.parse(matches);
+            let hd = HD.parse(matches);
+            let bip39_passphrase = BIP39_PASSPHRASE.parse(matches);
+            let derivation_path = DERIVATION_PATH.parse(matches);
             let unsafe_dont_encrypt = UNSAFE_DONT_ENCRYPT.parse(matches);
             Self {
                 scheme,
                 alias,
+                hd,
+                bip39_passphrase,
+                derivation_path,
                 unsafe_dont_encrypt,
             }
         }
@@ -2992,6 +3672,96 @@ pub mod args {
                 "The key and address alias. If none provided, the alias will \
                  be the public key hash.",
             ))
+            .arg(HD.def().about(
+                "Derive the key from a new mnemonic code, which is printed so \
+                 that the key can be restored with `key restore`.",
+            ))
+            .arg(
+                BIP39_PASSPHRASE
+                    .def()
+                    .about(
+                        "Protect the new mnemonic code with a BIP39 \
+                         passphrase, which is prompted for and is needed \
+                         together with the code to restore the key.",
+                    )
+                    .requires(HD.name),
+            )
+            .arg(
+                DERIVATION_PATH
+                    .def()
+                    .about(
+                        "The BIP44 derivation path of the key. If none \
+                         provided, the default path of the scheme is used, \
+                         m/44'/877'/0'/0'/0' for ed25519 and \
+                         m/44'/877'/0'/0/0 for secp256k1.",
+                    )
+                    .requires(HD.name),
+            )
+            .arg(UNSAFE_DONT_ENCRYPT.def().about(
+                "UNSAFE: Do not encrypt the keypair. Do not use this for keys \
+                 used in a live network.",
+            ))
+        }
+    }
+
+    /// Wallet restore key and implicit address arguments
+    #[derive(Clone, Debug)]
+    pub struct KeyRestore {
+        /// Scheme type
+        pub scheme: SchemeType,
+        /// Key alias
+        pub alias: Option<String>,
+        /// The mnemonic code, prompted for if not given
+        pub mnemonic: Option<String>,
+        /// The mnemonic code is protected by a BIP39 passphrase
+        pub bip39_passphrase: bool,
+        /// The derivation path of the key from the mnemonic code
+        pub derivation_path: Option<DerivationPath>,
+        /// Don't encrypt the keypair
+        pub unsafe_dont_encrypt: bool,
+    }
+
+    impl Args for KeyRestore {
+        fn parse(matches: &ArgMatches) -> Self {
+            let scheme = SCHEME.parse(matches);
+            let alias = ALIAS_OPT.parse(matches);
+            let mnemonic = MNEMONIC.parse(matches);
+            let bip39_passphrase = BIP39_PASSPHRASE.parse(matches);
+            let derivation_path = DERIVATION_PATH.parse(matches);
+            let unsafe_dont_encrypt = UNSAFE_DONT_ENCRYPT.parse(matches);
+            Self {
+                scheme,
+                alias,
+                mnemonic,
+                bip39_passphrase,
+                derivation_path,
+                unsafe_dont_encrypt,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(SCHEME.def().about(
+                "The type of key that should be restored. Argument must be \
+                 either ed25519 or secp256k1. If none provided, the default \
+                 key scheme is ed25519.",
+            ))
+            .arg(ALIAS_OPT.def().about(
+                "The key and address alias. If none provided, the alias will \
+                 be the public key hash.",
+            ))
+            .arg(MNEMONIC.def().about(
+                "The mnemonic code of the key. If none provided, it will be \
+                 prompted for.",
+            ))
+            .arg(BIP39_PASSPHRASE.def().about(
+                "The mnemonic code is protected by a BIP39 passphrase, which \
+                 is prompted for.",
+            ))
+            .arg(DERIVATION_PATH.def().about(
+                "The BIP44 derivation path of the key. If none provided, the \
+                 default path of the scheme is used, m/44'/877'/0'/0'/0' for \
+                 ed25519 and m/44'/877'/0'/0/0 for secp256k1.",
+            ))
             .arg(UNSAFE_DONT_ENCRYPT.def().about(
                 "UNSAFE: Do not encrypt the keypair. Do not use this for keys \
                  used in a live network.",
@@ -3397,6 +4167,26 @@ pub mod args {
                 ))
         }
     }
+
+    #[derive(Clone, Debug)]
+    pub struct LedgerDumpState {
+        /// The block height of the exported state
+        pub height: Option<u64>,
+    }
+
+    impl Args for LedgerDumpState {
+        fn parse(matches: &ArgMatches) -> Self {
+            let height = BLOCK_HEIGHT_OPT.parse(matches);
+            Self { height }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(BLOCK_HEIGHT_OPT.def().about(
+                "The block height of the exported state. Defaults to the last \
+                 committed block height.",
+            ))
+        }
+    }
 }
 
 pub fn anoma_cli() -> (cmds::Anoma, String) {
@@ -34,23 +34,26 @@ use namada::ledger::pos::{
     self, is_validator_slashes_key, BondId, Bonds, PosParams, Slash, Unbonds,
 };
 use namada::ledger::queries::{self, RPC};
-use namada::ledger::storage::ConversionState;
+use namada::ledger::storage::{
+    verify_batch_proof, ConversionState, Sha256Hasher, StoreType,
+};
 use namada::proto::{SignedTxData, Tx};
 use namada::types::address::{masp, tokens, Address};
 use namada::types::governance::{
-    OfflineProposal, OfflineVote, ProposalResult, ProposalVote, TallyResult,
+    OfflineProposal, OfflineVote, ProposalResult, ProposalType, ProposalVote,
     VotePower,
 };
 use namada::types::hash::Hash;
 use namada::types::key::*;
 use namada::types::masp::{BalanceOwner, ExtendedViewingKey, PaymentAddress};
 use namada::types::storage::{
-    BlockHeight, BlockResults, Epoch, Key, KeySeg, PrefixValue, TxIndex,
+    BlockHeight, BlockResults, Epoch, Key, KeySeg, PrefixProof, PrefixValue,
+    TxIndex,
 };
 use namada::types::token::{balance_key, Transfer};
 use namada::types::transaction::{
-    process_tx, AffineCurve, DecryptedTx, EllipticCurve, PairingEngine, TxType,
-    WrapperTx,
+    process_tx, AffineCurve, DecryptedTx, EllipticCurve, EncryptionKey,
+    PairingEngine, TxType, WrapperTx,
 };
 use namada::types::{address, storage, token};
 use rust_decimal::Decimal;
@@ -128,6 +131,18 @@ pub async fn query_epoch(args: args::Query) -> Epoch {
     epoch
 }
 
+/// Query the key that the wrapper txs of the given epoch must be encrypted
+/// with. The default key is used in the epochs without a DKG session.
+pub async fn query_encryption_key(
+    args: args::Query,
+    epoch: Epoch,
+) -> EncryptionKey {
+    let client = HttpClient::new(args.ledger_address).unwrap();
+    query_storage_value(&client, &dkg_session_keys::dkg_session_pk_key(epoch))
+        .await
+        .unwrap_or_default()
+}
+
 /// Query the last committed block
 pub async fn query_block(
     args: args::Query,
@@ -411,6 +426,35 @@ fn extract_payload(
     }
 }
 
+/// Query the metadata of a token
+pub async fn query_token_metadata(
+    mut ctx: Context,
+    args: args::QueryTokenMetadata,
+) {
+    let client = HttpClient::new(args.query.ledger_address).unwrap();
+    let token = ctx.get(&args.token);
+    match get_token_metadata(&client, &token).await {
+        Some(token::Metadata {
+            denomination,
+            symbol,
+            name,
+        }) => {
+            println!("Token {}", token);
+            println!("  Symbol: {}", symbol);
+            println!("  Name: {}", name);
+            println!("  Denomination: {}", denomination);
+        }
+        None => {
+            println!(
+                "No metadata found for token {}, its amounts have {} decimal \
+                 places",
+                token,
+                token::MAX_DECIMAL_PLACES
+            );
+        }
+    }
+}
+
 /// Query the raw bytes of given storage key
 pub async fn query_raw_bytes(_ctx: Context, args: args::QueryRawBytes) {
     let client = HttpClient::new(args.query.ledger_address).unwrap();
@@ -478,15 +522,21 @@ pub async fn query_transparent_balance(
                 .get(&token)
                 .map(|c| Cow::Borrowed(*c))
                 .unwrap_or_else(|| Cow::Owned(token.to_string()));
+            let denom = get_token_denomination(&client, &token).await;
             match query_storage_value::<token::Amount>(&client, &key).await {
                 Some(balance) => match &args.sub_prefix {
                     Some(sub_prefix) => {
+                        let balance = balance.to_string_with_denom(denom);
                         println!(
                             "{} with {}: {}",
                             currency_code, sub_prefix, balance
                         );
                     }
-                    None => println!("{}: {}", currency_code, balance),
+                    None => println!(
+                        "{}: {}",
+                        currency_code,
+                        balance.to_string_with_denom(denom)
+                    ),
                 },
                 None => {
                     println!("No {} balance found for {}", currency_code, owner)
@@ -501,10 +551,12 @@ pub async fn query_transparent_balance(
                     query_storage_prefix::<token::Amount>(&client, &prefix)
                         .await;
                 if let Some(balances) = balances {
+                    let denom = get_token_denomination(&client, &token).await;
                     print_balances(
                         ctx,
                         balances,
                         &token,
+                        denom,
                         owner.address().as_ref(),
                     );
                 }
@@ -516,7 +568,8 @@ pub async fn query_transparent_balance(
             let balances =
                 query_storage_prefix::<token::Amount>(&client, &prefix).await;
             if let Some(balances) = balances {
-                print_balances(ctx, balances, &token, None);
+                let denom = get_token_denomination(&client, &token).await;
+                print_balances(ctx, balances, &token, denom, None);
             }
         }
         (None, None) => {
@@ -525,7 +578,8 @@ pub async fn query_transparent_balance(
                 let balances =
                     query_storage_prefix::<token::Amount>(&client, &key).await;
                 if let Some(balances) = balances {
-                    print_balances(ctx, balances, &token, None);
+                    let denom = get_token_denomination(&client, &token).await;
+                    print_balances(ctx, balances, &token, denom, None);
                 }
             }
         }
@@ -674,6 +728,7 @@ fn print_balances(
     ctx: &Context,
     balances: impl Iterator<Item = (storage::Key, token::Amount)>,
     token: &Address,
+    denom: u8,
     target: Option<&Address>,
 ) {
     let stdout = io::stdout();
@@ -695,7 +750,7 @@ fn print_balances(
                     format!(
                         "with {}: {}, owned by {}",
                         sub_prefix,
-                        balance,
+                        balance.to_string_with_denom(denom),
                         lookup_alias(ctx, owner)
                     ),
                 )),
@@ -704,7 +759,7 @@ fn print_balances(
                         owner.clone(),
                         format!(
                             ": {}, owned by {}",
-                            balance,
+                            balance.to_string_with_denom(denom),
                             lookup_alias(ctx, owner)
                         ),
                     )
@@ -763,9 +818,16 @@ pub async fn query_proposal(_ctx: Context, args: args::QueryProposal) {
             .await?;
             let grace_epoch =
                 query_storage_value::<Epoch>(client, &grace_epoch_key).await?;
+            let proposal_type = query_storage_value::<ProposalType>(
+                client,
+                &gov_storage::get_proposal_type_key(id),
+            )
+            .await
+            .unwrap_or_default();
 
             println!("Proposal: {}", id);
             println!("{:4}Author: {}", "", author);
+            println!("{:4}Type: {}", "", proposal_type);
             println!("{:4}Content:", "");
             for (key, value) in &content {
                 println!("{:8}{}: {}", "", key, value);
@@ -779,7 +841,8 @@ pub async fn query_proposal(_ctx: Context, args: args::QueryProposal) {
             {
                 let votes = get_proposal_votes(client, start_epoch, id).await;
                 let partial_proposal_result =
-                    compute_tally(client, start_epoch, votes).await;
+                    compute_tally(client, start_epoch, votes, &proposal_type)
+                        .await;
                 println!(
                     "{:4}Yay votes: {}",
                     "", partial_proposal_result.total_yay_power
@@ -788,11 +851,16 @@ pub async fn query_proposal(_ctx: Context, args: args::QueryProposal) {
                     "{:4}Nay votes: {}",
                     "", partial_proposal_result.total_nay_power
                 );
+                println!(
+                    "{:4}Abstain votes: {}",
+                    "", partial_proposal_result.total_abstain_power
+                );
                 println!("{:4}Status: on-going", "");
             } else {
                 let votes = get_proposal_votes(client, start_epoch, id).await;
                 let proposal_result =
-                    compute_tally(client, start_epoch, votes).await;
+                    compute_tally(client, start_epoch, votes, &proposal_type)
+                        .await;
                 println!("{:4}Status: done", "");
                 println!("{:4}Result: {}", "", proposal_result);
             }
@@ -1172,8 +1240,20 @@ pub async fn query_proposal_result(
                     if current_epoch > end_epoch {
                         let votes =
                             get_proposal_votes(&client, end_epoch, id).await;
-                        let proposal_result =
-                            compute_tally(&client, end_epoch, votes).await;
+                        let proposal_type =
+                            query_storage_value::<ProposalType>(
+                                &client,
+                                &gov_storage::get_proposal_type_key(id),
+                            )
+                            .await
+                            .unwrap_or_default();
+                        let proposal_result = compute_tally(
+                            &client,
+                            end_epoch,
+                            votes,
+                            &proposal_type,
+                        )
+                        .await;
                         println!("Proposal: {}", id);
                         println!("{:4}Result: {}", "", proposal_result);
                     } else {
@@ -1265,9 +1345,13 @@ pub async fn query_proposal_result(
                             files,
                         )
                         .await;
-                        let proposal_result =
-                            compute_tally(&client, proposal.tally_epoch, votes)
-                                .await;
+                        let proposal_result = compute_tally(
+                            &client,
+                            proposal.tally_epoch,
+                            votes,
+                            &ProposalType::Default,
+                        )
+                        .await;
 
                         println!("{:4}Result: {}", "", proposal_result);
                     }
@@ -1351,6 +1435,18 @@ pub async fn query_protocol_parameters(
         "{:4}Light client attack minimum slash rate: {}",
         "", pos_params.light_client_attack_min_slash_rate
     );
+    println!(
+        "{:4}Liveness window length: {}",
+        "", pos_params.liveness_window_len
+    );
+    println!(
+        "{:4}Liveness threshold: {}",
+        "", pos_params.liveness_threshold
+    );
+    println!(
+        "{:4}Downtime slash rate: {}",
+        "", pos_params.downtime_slash_rate
+    );
     println!(
         "{:4}Max. validator slots: {}",
         "", pos_params.max_validator_slots
@@ -1364,6 +1460,8 @@ pub async fn query_protocol_parameters(
 pub async fn query_bonds(ctx: Context, args: args::QueryBonds) {
     let epoch = query_epoch(args.query.clone()).await;
     let client = HttpClient::new(args.query.ledger_address).unwrap();
+    // The bonds are amounts of the staking token
+    let denom = get_token_denomination(&client, &ctx.native_token).await;
     match (args.owner, args.validator) {
         (Some(owner), Some(validator)) => {
             let source = ctx.get(&owner);
@@ -1396,7 +1494,7 @@ pub async fn query_bonds(ctx: Context, args: args::QueryBonds) {
                 };
                 writeln!(w, "{}:", bond_type).unwrap();
                 process_bonds_query(
-                    bonds, &slashes, &epoch, None, None, None, &mut w,
+                    bonds, &slashes, &epoch, None, None, None, denom, &mut w,
                 );
             }
 
@@ -1408,7 +1506,7 @@ pub async fn query_bonds(ctx: Context, args: args::QueryBonds) {
                 };
                 writeln!(w, "{}:", bond_type).unwrap();
                 process_unbonds_query(
-                    unbonds, &slashes, &epoch, None, None, None, &mut w,
+                    unbonds, &slashes, &epoch, None, None, None, denom, &mut w,
                 );
             }
 
@@ -1449,14 +1547,14 @@ pub async fn query_bonds(ctx: Context, args: args::QueryBonds) {
             if let Some(bonds) = &bonds {
                 writeln!(w, "Self-bonds:").unwrap();
                 process_bonds_query(
-                    bonds, &slashes, &epoch, None, None, None, &mut w,
+                    bonds, &slashes, &epoch, None, None, None, denom, &mut w,
                 );
             }
 
             if let Some(unbonds) = &unbonds {
                 writeln!(w, "Unbonded self-bonds:").unwrap();
                 process_unbonds_query(
-                    unbonds, &slashes, &epoch, None, None, None, &mut w,
+                    unbonds, &slashes, &epoch, None, None, None, denom, &mut w,
                 );
             }
 
@@ -1519,6 +1617,7 @@ pub async fn query_bonds(ctx: Context, args: args::QueryBonds) {
                                 Some(&source),
                                 Some(total),
                                 Some(total_active),
+                                denom,
                                 &mut w,
                             );
                             total = tot;
@@ -1531,7 +1630,10 @@ pub async fn query_bonds(ctx: Context, args: args::QueryBonds) {
                 }
             }
             if total_active != 0.into() && total_active != total {
-                println!("Active bonds total: {}", total_active);
+                println!(
+                    "Active bonds total: {}",
+                    total_active.to_string_with_denom(denom)
+                );
             }
 
             let mut total: token::Amount = 0.into();
@@ -1567,6 +1669,7 @@ pub async fn query_bonds(ctx: Context, args: args::QueryBonds) {
                                 Some(&source),
                                 Some(total),
                                 Some(total_withdrawable),
+                                denom,
                                 &mut w,
                             );
                             total = tot;
@@ -1579,7 +1682,10 @@ pub async fn query_bonds(ctx: Context, args: args::QueryBonds) {
                 }
             }
             if total_withdrawable != 0.into() {
-                println!("Withdrawable total: {}", total_withdrawable);
+                println!(
+                    "Withdrawable total: {}",
+                    total_withdrawable.to_string_with_denom(denom)
+                );
             }
 
             if !any_bonds {
@@ -1633,6 +1739,7 @@ pub async fn query_bonds(ctx: Context, args: args::QueryBonds) {
                                 Some(&source),
                                 Some(total),
                                 Some(total_active),
+                                denom,
                                 &mut w,
                             );
                             total = tot;
@@ -1645,9 +1752,12 @@ pub async fn query_bonds(ctx: Context, args: args::QueryBonds) {
                 }
             }
             if total_active != 0.into() && total_active != total {
-                println!("Bond total active: {}", total_active);
+                println!(
+                    "Bond total active: {}",
+                    total_active.to_string_with_denom(denom)
+                );
             }
-            println!("Bond total: {}", total);
+            println!("Bond total: {}", total.to_string_with_denom(denom));
 
             let mut total: token::Amount = 0.into();
             let mut total_withdrawable: token::Amount = 0.into();
@@ -1688,6 +1798,7 @@ pub async fn query_bonds(ctx: Context, args: args::QueryBonds) {
                                 Some(&source),
                                 Some(total),
                                 Some(total_withdrawable),
+                                denom,
                                 &mut w,
                             );
                             total = tot;
@@ -1700,9 +1811,12 @@ pub async fn query_bonds(ctx: Context, args: args::QueryBonds) {
                 }
             }
             if total_withdrawable != 0.into() {
-                println!("Withdrawable total: {}", total_withdrawable);
+                println!(
+                    "Withdrawable total: {}",
+                    total_withdrawable.to_string_with_denom(denom)
+                );
             }
-            println!("Unbonded total: {}", total);
+            println!("Unbonded total: {}", total.to_string_with_denom(denom));
         }
     }
 }
@@ -1714,6 +1828,10 @@ pub async fn query_bonded_stake(ctx: Context, args: args::QueryBondedStake) {
         None => query_epoch(args.query.clone()).await,
     };
     let client = HttpClient::new(args.query.ledger_address).unwrap();
+    // The stake is an amount of the staking token
+    let denom = get_token_denomination(&client, &ctx.native_token).await;
+    let stake_to_string =
+        |stake: u64| token::Amount::from(stake).to_string_with_denom(denom);
 
     // Find the validator set
     let validator_set_key = pos::validator_set_key();
@@ -1755,7 +1873,7 @@ pub async fn query_bonded_stake(ctx: Context, args: args::QueryBondedStake) {
                         "Validator {} is {}, bonded stake: {}",
                         validator.encode(),
                         if is_active { "active" } else { "inactive" },
-                        bonded_stake,
+                        stake_to_string(bonded_stake),
                     )
                 }
                 None => {
@@ -1774,7 +1892,7 @@ pub async fn query_bonded_stake(ctx: Context, args: args::QueryBondedStake) {
                     w,
                     "  {}: {}",
                     active.address.encode(),
-                    active.bonded_stake
+                    stake_to_string(active.bonded_stake)
                 )
                 .unwrap();
             }
@@ -1785,7 +1903,7 @@ pub async fn query_bonded_stake(ctx: Context, args: args::QueryBondedStake) {
                         w,
                         "  {}: {}",
                         inactive.address.encode(),
-                        inactive.bonded_stake
+                        stake_to_string(inactive.bonded_stake)
                     )
                     .unwrap();
                 }
@@ -1804,7 +1922,10 @@ pub async fn query_bonded_stake(ctx: Context, args: args::QueryBondedStake) {
         .try_into()
         .expect("total_bonded_stake should be a positive value");
 
-    println!("Total bonded stake: {}", total_bonded_stake);
+    println!(
+        "Total bonded stake: {}",
+        stake_to_string(total_bonded_stake)
+    );
 }
 
 /// Query PoS validator's commission rate
@@ -1954,6 +2075,58 @@ pub async fn get_public_key(
     query_storage_value(&client, &key).await
 }
 
+/// Get the metadata of a token written into its storage sub-space
+pub async fn get_token_metadata(
+    client: &HttpClient,
+    token: &Address,
+) -> Option<token::Metadata> {
+    let key = token::metadata_key(token);
+    query_storage_value(client, &key).await
+}
+
+/// Get the number of decimal places of a token's amounts. Tokens without
+/// metadata have the native denomination.
+pub async fn get_token_denomination(
+    client: &HttpClient,
+    token: &Address,
+) -> u8 {
+    get_token_metadata(client, token)
+        .await
+        .map(|metadata| metadata.denomination)
+        .unwrap_or(token::MAX_DECIMAL_PLACES as u8)
+}
+
+/// Convert a decimal amount of a token into its raw amount with the token's
+/// denomination, which is returned too to print the other amounts of the
+/// token. Exits if the amount doesn't fit the denomination.
+pub async fn amount_with_denom(
+    client: &HttpClient,
+    token: &Address,
+    amount: token::DecimalAmount,
+) -> (token::Amount, u8) {
+    let denom = get_token_denomination(client, token).await;
+    let raw_amount = token::Amount::from_decimal_with_denom(amount, denom)
+        .unwrap_or_else(|err| {
+            eprintln!(
+                "Invalid amount {} of token {} with {} decimal places: {}",
+                amount, token, denom, err
+            );
+            cli::safe_exit(1)
+        });
+    (raw_amount, denom)
+}
+
+/// Get the public keys and threshold of a multisignature account stored in
+/// its storage sub-space
+pub async fn get_multisig(
+    address: &Address,
+    ledger_address: TendermintAddress,
+) -> Option<Multisig> {
+    let client = HttpClient::new(ledger_address).unwrap();
+    let key = multisig_key(address);
+    query_storage_value(&client, &key).await
+}
+
 /// Check if the given address is a known validator.
 pub async fn is_validator(
     address: &Address,
@@ -2008,20 +2181,22 @@ pub async fn known_address(
 }
 
 /// Accumulate slashes starting from `epoch_start` until (optionally)
-/// `withdraw_epoch` and apply them to the token amount `delta`.
+/// `withdraw_epoch` and apply them to the token amount `delta`. The slashes
+/// are printed to the optional output with the given denomination of the
+/// staking token.
 fn apply_slashes(
     slashes: &[Slash],
     mut delta: token::Amount,
     epoch_start: PosEpoch,
     withdraw_epoch: Option<PosEpoch>,
-    mut w: Option<&mut std::io::StdoutLock>,
+    mut w: Option<(&mut std::io::StdoutLock, u8)>,
 ) -> token::Amount {
     let mut slashed = token::Amount::default();
     for slash in slashes {
         if slash.epoch >= epoch_start
             && slash.epoch < withdraw_epoch.unwrap_or_else(|| u64::MAX.into())
         {
-            if let Some(w) = w.as_mut() {
+            if let Some((w, _)) = w.as_mut() {
                 writeln!(
                     *w,
                     "    ⚠ Slash: {} from epoch {}",
@@ -2036,10 +2211,20 @@ fn apply_slashes(
             delta -= current_slashed;
         }
     }
-    if let Some(w) = w.as_mut() {
+    if let Some((w, denom)) = w.as_mut() {
         if slashed != 0.into() {
-            writeln!(*w, "    ⚠ Slash total: {}", slashed).unwrap();
-            writeln!(*w, "    ⚠ After slashing: Δ {}", delta).unwrap();
+            writeln!(
+                *w,
+                "    ⚠ Slash total: {}",
+                slashed.to_string_with_denom(*denom)
+            )
+            .unwrap();
+            writeln!(
+                *w,
+                "    ⚠ After slashing: Δ {}",
+                delta.to_string_with_denom(*denom)
+            )
+            .unwrap();
         }
     }
     delta
@@ -2048,6 +2233,7 @@ fn apply_slashes(
 /// Process the result of a blonds query to determine total bonds
 /// and total active bonds. This includes taking into account
 /// an aggregation of slashes since the start of the given epoch.
+#[allow(clippy::too_many_arguments)]
 fn process_bonds_query(
     bonds: &Bonds,
     slashes: &[Slash],
@@ -2055,15 +2241,27 @@ fn process_bonds_query(
     source: Option<&Address>,
     total: Option<token::Amount>,
     total_active: Option<token::Amount>,
+    denom: u8,
     w: &mut std::io::StdoutLock,
 ) -> (token::Amount, token::Amount) {
     let mut total_active = total_active.unwrap_or_else(|| 0.into());
     let mut current_total: token::Amount = 0.into();
     for bond in bonds.iter() {
         for (epoch_start, &(mut delta)) in bond.pos_deltas.iter().sorted() {
-            writeln!(w, "  Active from epoch {}: Δ {}", epoch_start, delta)
-                .unwrap();
-            delta = apply_slashes(slashes, delta, *epoch_start, None, Some(w));
+            writeln!(
+                w,
+                "  Active from epoch {}: Δ {}",
+                epoch_start,
+                delta.to_string_with_denom(denom)
+            )
+            .unwrap();
+            delta = apply_slashes(
+                slashes,
+                delta,
+                *epoch_start,
+                None,
+                Some((&mut *w, denom)),
+            );
             current_total += delta;
             let epoch_start: Epoch = (*epoch_start).into();
             if epoch >= &epoch_start {
@@ -2074,14 +2272,25 @@ fn process_bonds_query(
     let total = total.unwrap_or_else(|| 0.into()) + current_total;
     match source {
         Some(addr) => {
-            writeln!(w, "  Bonded total from {}: {}", addr, current_total)
-                .unwrap();
+            writeln!(
+                w,
+                "  Bonded total from {}: {}",
+                addr,
+                current_total.to_string_with_denom(denom)
+            )
+            .unwrap();
         }
         None => {
             if total_active != 0.into() && total_active != total {
-                writeln!(w, "Active bonds total: {}", total_active).unwrap();
+                writeln!(
+                    w,
+                    "Active bonds total: {}",
+                    total_active.to_string_with_denom(denom)
+                )
+                .unwrap();
             }
-            writeln!(w, "Bonds total: {}", total).unwrap();
+            writeln!(w, "Bonds total: {}", total.to_string_with_denom(denom))
+                .unwrap();
         }
     }
     (total, total_active)
@@ -2091,6 +2300,7 @@ fn process_bonds_query(
 /// and total withdrawable bonds. This includes taking into account
 /// an aggregation of slashes since the start of the given epoch up
 /// until the withdrawal epoch.
+#[allow(clippy::too_many_arguments)]
 fn process_unbonds_query(
     unbonds: &Unbonds,
     slashes: &[Slash],
@@ -2098,6 +2308,7 @@ fn process_unbonds_query(
     source: Option<&Address>,
     total: Option<token::Amount>,
     total_withdrawable: Option<token::Amount>,
+    denom: u8,
     w: &mut std::io::StdoutLock,
 ) -> (token::Amount, token::Amount) {
     let mut withdrawable = total_withdrawable.unwrap_or_else(|| 0.into());
@@ -2110,7 +2321,9 @@ fn process_unbonds_query(
             writeln!(
                 w,
                 "  Withdrawable from epoch {} (active from {}): Δ {}",
-                withdraw_epoch, epoch_start, delta
+                withdraw_epoch,
+                epoch_start,
+                delta.to_string_with_denom(denom)
             )
             .unwrap();
             delta = apply_slashes(
@@ -2118,7 +2331,7 @@ fn process_unbonds_query(
                 delta,
                 *epoch_start,
                 Some(withdraw_epoch),
-                Some(w),
+                Some((&mut *w, denom)),
             );
             current_total += delta;
             let epoch_end: Epoch = (*epoch_end).into();
@@ -2130,14 +2343,29 @@ fn process_unbonds_query(
     let total = total.unwrap_or_else(|| 0.into()) + current_total;
     match source {
         Some(addr) => {
-            writeln!(w, "  Unbonded total from {}: {}", addr, current_total)
-                .unwrap();
+            writeln!(
+                w,
+                "  Unbonded total from {}: {}",
+                addr,
+                current_total.to_string_with_denom(denom)
+            )
+            .unwrap();
         }
         None => {
             if withdrawable != 0.into() {
-                writeln!(w, "Withdrawable total: {}", withdrawable).unwrap();
+                writeln!(
+                    w,
+                    "Withdrawable total: {}",
+                    withdrawable.to_string_with_denom(denom)
+                )
+                .unwrap();
             }
-            writeln!(w, "Unbonded total: {}", total).unwrap();
+            writeln!(
+                w,
+                "Unbonded total: {}",
+                total.to_string_with_denom(denom)
+            )
+            .unwrap();
         }
     }
     (total, withdrawable)
@@ -2314,6 +2542,49 @@ where
     }
 }
 
+/// Query the storage values with a matching prefix at the given height, with
+/// a batch proof of them, and verify the proof against the given app hash of
+/// the height. For a prefix in the IBC sub-tree, the proof shows that the
+/// range of the keys with the prefix's segments is complete. In the other
+/// sub-trees, whose keys are hashed, it only proves that the returned values
+/// exist. Returns the storage keys paired with their raw values.
+pub async fn query_storage_prefix_proven(
+    client: &HttpClient,
+    key: &storage::Key,
+    height: BlockHeight,
+    app_hash: &[u8],
+) -> eyre::Result<Vec<(storage::Key, Vec<u8>)>> {
+    let data = PrefixProof::Batch.try_to_vec()?;
+    let response = RPC
+        .shell()
+        .storage_prefix(client, Some(data), Some(height), true, key)
+        .await
+        .wrap_err("Failed querying the storage prefix")?;
+    let proof = response
+        .proof
+        .ok_or_else(|| eyre!("The storage prefix query has no proof"))?;
+    let key_vals: Vec<_> = response
+        .data
+        .into_iter()
+        .map(|PrefixValue { key, value }| (key, value))
+        .collect();
+    let (store_type, _) =
+        StoreType::sub_key(key).wrap_err("Invalid storage prefix")?;
+    let complete_prefixes = if store_type.proves_complete_ranges() {
+        vec![key.clone()]
+    } else {
+        vec![]
+    };
+    verify_batch_proof::<Sha256Hasher>(
+        &proof,
+        app_hash,
+        &key_vals,
+        &complete_prefixes,
+    )
+    .wrap_err("Invalid proof of the storage prefix")?;
+    Ok(key_vals)
+}
+
 /// Query to check if the given storage key exists.
 pub async fn query_has_storage_key(
     client: &HttpClient,
@@ -2517,24 +2788,25 @@ pub async fn get_proposal_votes(
     let vote_iter =
         query_storage_prefix::<ProposalVote>(client, &vote_prefix_key).await;
 
-    let mut yay_validators: HashMap<Address, VotePower> = HashMap::new();
-    let mut yay_delegators: HashMap<Address, HashMap<Address, VotePower>> =
-        HashMap::new();
-    let mut nay_delegators: HashMap<Address, HashMap<Address, VotePower>> =
+    let mut validator_votes: HashMap<Address, (ProposalVote, VotePower)> =
         HashMap::new();
+    let mut delegator_votes: HashMap<
+        Address,
+        HashMap<Address, (ProposalVote, VotePower)>,
+    > = HashMap::new();
 
     if let Some(vote_iter) = vote_iter {
         for (key, vote) in vote_iter {
             let voter_address = gov_storage::get_voter_address(&key)
                 .expect("Vote key should contain the voting address.")
                 .clone();
-            if vote.is_yay() && validators.contains(&voter_address) {
+            if validators.contains(&voter_address) {
                 let amount: VotePower =
                     get_validator_stake(client, epoch, &voter_address)
                         .await
                         .into();
-                yay_validators.insert(voter_address, amount);
-            } else if !validators.contains(&voter_address) {
+                validator_votes.insert(voter_address, (vote, amount));
+            } else {
                 let validator_address =
                     gov_storage::get_vote_delegation_address(&key)
                         .expect(
@@ -2549,26 +2821,18 @@ pub async fn get_proposal_votes(
                 )
                 .await;
                 if let Some(amount) = delegator_token_amount {
-                    if vote.is_yay() {
-                        let entry =
-                            yay_delegators.entry(voter_address).or_default();
-                        entry
-                            .insert(validator_address, VotePower::from(amount));
-                    } else {
-                        let entry =
-                            nay_delegators.entry(voter_address).or_default();
-                        entry
-                            .insert(validator_address, VotePower::from(amount));
-                    }
+                    delegator_votes.entry(voter_address).or_default().insert(
+                        validator_address,
+                        (vote, VotePower::from(amount)),
+                    );
                 }
             }
         }
     }
 
     Votes {
-        yay_validators,
-        yay_delegators,
-        nay_delegators,
+        validators: validator_votes,
+        delegators: delegator_votes,
     }
 }
 
@@ -2581,11 +2845,12 @@ pub async fn get_proposal_offline_votes(
 
     let proposal_hash = proposal.compute_hash();
 
-    let mut yay_validators: HashMap<Address, VotePower> = HashMap::new();
-    let mut yay_delegators: HashMap<Address, HashMap<Address, VotePower>> =
-        HashMap::new();
-    let mut nay_delegators: HashMap<Address, HashMap<Address, VotePower>> =
+    let mut validator_votes: HashMap<Address, (ProposalVote, VotePower)> =
         HashMap::new();
+    let mut delegator_votes: HashMap<
+        Address,
+        HashMap<Address, (ProposalVote, VotePower)>,
+    > = HashMap::new();
 
     for path in files {
         let file = File::open(&path).expect("Proposal file must exist.");
@@ -2603,9 +2868,7 @@ pub async fn get_proposal_offline_votes(
             continue;
         }
 
-        if proposal_vote.vote.is_yay()
-            && validators.contains(&proposal_vote.address)
-        {
+        if validators.contains(&proposal_vote.address) {
             let amount: VotePower = get_validator_stake(
                 client,
                 proposal.tally_epoch,
@@ -2613,7 +2876,8 @@ pub async fn get_proposal_offline_votes(
             )
             .await
             .into();
-            yay_validators.insert(proposal_vote.address, amount);
+            validator_votes
+                .insert(proposal_vote.address, (proposal_vote.vote, amount));
         } else if is_delegator_at(
             client,
             &proposal_vote.address,
@@ -2675,32 +2939,24 @@ pub async fn get_proposal_offline_votes(
                         pos::get_validator_address_from_bond(&key).expect(
                             "Delegation key should contain validator address.",
                         );
-                    if proposal_vote.vote.is_yay() {
-                        let entry = yay_delegators
-                            .entry(proposal_vote.address.clone())
-                            .or_default();
-                        entry.insert(
+                    delegator_votes
+                        .entry(proposal_vote.address.clone())
+                        .or_default()
+                        .insert(
                             validator_address,
-                            VotePower::from(delegated_amount),
+                            (
+                                proposal_vote.vote.clone(),
+                                VotePower::from(delegated_amount),
+                            ),
                         );
-                    } else {
-                        let entry = nay_delegators
-                            .entry(proposal_vote.address.clone())
-                            .or_default();
-                        entry.insert(
-                            validator_address,
-                            VotePower::from(delegated_amount),
-                        );
-                    }
                 }
             }
         }
     }
 
     Votes {
-        yay_validators,
-        yay_delegators,
-        nay_delegators,
+        validators: validator_votes,
+        delegators: delegator_votes,
     }
 }
 
@@ -2709,54 +2965,17 @@ pub async fn compute_tally(
     client: &HttpClient,
     epoch: Epoch,
     votes: Votes,
+    proposal_type: &ProposalType,
 ) -> ProposalResult {
     let total_staked_tokens: VotePower =
         get_total_staked_tokens(client, epoch).await.into();
+    let gov_params = get_governance_parameters(client).await;
 
-    let Votes {
-        yay_validators,
-        yay_delegators,
-        nay_delegators,
-    } = votes;
-
-    let mut total_yay_staked_tokens = VotePower::from(0_u64);
-    for (_, amount) in yay_validators.clone().into_iter() {
-        total_yay_staked_tokens += amount;
-    }
-
-    // YAY: Add delegator amount whose validator didn't vote / voted nay
-    for (_, vote_map) in yay_delegators.iter() {
-        for (validator_address, vote_power) in vote_map.iter() {
-            if !yay_validators.contains_key(validator_address) {
-                total_yay_staked_tokens += vote_power;
-            }
-        }
-    }
-
-    // NAY: Remove delegator amount whose validator validator vote yay
-    for (_, vote_map) in nay_delegators.iter() {
-        for (validator_address, vote_power) in vote_map.iter() {
-            if yay_validators.contains_key(validator_address) {
-                total_yay_staked_tokens -= vote_power;
-            }
-        }
-    }
-
-    if total_yay_staked_tokens >= (total_staked_tokens / 3) * 2 {
-        ProposalResult {
-            result: TallyResult::Passed,
-            total_voting_power: total_staked_tokens,
-            total_yay_power: total_yay_staked_tokens,
-            total_nay_power: 0,
-        }
-    } else {
-        ProposalResult {
-            result: TallyResult::Rejected,
-            total_voting_power: total_staked_tokens,
-            total_yay_power: total_yay_staked_tokens,
-            total_nay_power: 0,
-        }
-    }
+    votes.tally(
+        total_staked_tokens,
+        gov_params.min_quorum,
+        gov_params.pass_threshold(proposal_type),
+    )
 }
 
 pub async fn get_bond_amount_at(
@@ -2885,6 +3104,32 @@ pub async fn get_governance_parameters(client: &HttpClient) -> GovParams {
         .await
         .expect("Parameter should be definied.");
 
+    // The tally thresholds may be missing from the storage of chains that
+    // started without them, in which case they fall back to their defaults
+    let defaults = GovParams::default();
+
+    let key = gov_storage::get_min_quorum_key();
+    let min_quorum = query_storage_value::<Decimal>(client, &key)
+        .await
+        .unwrap_or(defaults.min_quorum);
+
+    let key = gov_storage::get_pass_threshold_key();
+    let pass_threshold = query_storage_value::<Decimal>(client, &key)
+        .await
+        .unwrap_or(defaults.pass_threshold);
+
+    let key = gov_storage::get_parameter_change_pass_threshold_key();
+    let parameter_change_pass_threshold =
+        query_storage_value::<Option<Decimal>>(client, &key)
+            .await
+            .flatten();
+
+    let key = gov_storage::get_treasury_transfer_pass_threshold_key();
+    let treasury_transfer_pass_threshold =
+        query_storage_value::<Option<Decimal>>(client, &key)
+            .await
+            .flatten();
+
     GovParams {
         min_proposal_fund: u64::from(min_proposal_fund),
         max_proposal_code_size,
@@ -2892,6 +3137,10 @@ pub async fn get_governance_parameters(client: &HttpClient) -> GovParams {
         max_proposal_period,
         max_proposal_content_size,
         min_proposal_grace_epochs,
+        min_quorum,
+        pass_threshold,
+        parameter_change_pass_threshold,
+        treasury_transfer_pass_threshold,
     }
 }
 
@@ -1,11 +1,15 @@
 //! Helpers for making digital signatures using cryptographic keys from the
 //! wallet.
 
+use std::fs;
+use std::path::Path;
+
 use borsh::BorshSerialize;
 use namada::proto::Tx;
 use namada::types::address::{Address, ImplicitAddress};
 use namada::types::key::*;
 use namada::types::storage::Epoch;
+use namada::types::transaction::offline::OfflineTx;
 use namada::types::transaction::{hash_tx, Fee, WrapperTx};
 
 use super::rpc;
@@ -13,6 +17,7 @@ use crate::cli::context::{WalletAddress, WalletKeypair};
 use crate::cli::{self, args, Context};
 use crate::client::tendermint_rpc_types::TxBroadcastData;
 use crate::facade::tendermint_config::net::Address as TendermintAddress;
+use crate::facade::tendermint_rpc::HttpClient;
 use crate::wallet::Wallet;
 
 /// Find the public key for the given address and try to load the keypair
@@ -146,9 +151,23 @@ pub async fn sign_tx(
     args: &args::Tx,
     default: TxSigningKey,
 ) -> (Context, TxBroadcastData) {
+    let signer = signer_address(&ctx, args, &default);
     let keypair = tx_signer(&mut ctx, args, default).await;
-    let tx = tx.sign(&keypair);
+    let mut tx = tx.sign(&keypair);
+    if let Some(signer) = signer {
+        tx = collect_multisig_sigs(&mut ctx.wallet, args, &signer, tx).await;
+    }
+    wrap_tx(ctx, tx, args, &keypair).await
+}
 
+/// Put a signed transaction in a wrapper signed with the given keypair. If it
+/// is a dry run, it is not put in a wrapper, but returned as is.
+pub async fn wrap_tx(
+    ctx: Context,
+    tx: Tx,
+    args: &args::Tx,
+    keypair: &common::SecretKey,
+) -> (Context, TxBroadcastData) {
     let epoch = rpc::query_epoch(args::Query {
         ledger_address: args.ledger_address.clone(),
     })
@@ -156,11 +175,102 @@ pub async fn sign_tx(
     let broadcast_data = if args.dry_run {
         TxBroadcastData::DryRun(tx)
     } else {
-        sign_wrapper(&ctx, args, epoch, tx, &keypair).await
+        sign_wrapper(&ctx, args, epoch, tx, keypair).await
     };
     (ctx, broadcast_data)
 }
 
+/// Write an unsigned transaction to the given file, with the public keys of
+/// its signer, to be signed offline. The signer must be given by its address,
+/// because its keys are not expected to be in this wallet.
+pub async fn dump_tx(
+    ctx: &Context,
+    tx: Tx,
+    args: &args::Tx,
+    default: TxSigningKey,
+    path: &Path,
+) {
+    let signer = signer_address(ctx, args, &default).unwrap_or_else(|| {
+        eprintln!(
+            "A transaction dumped for offline signing requires the address of \
+             its signer, given by `--signer`."
+        );
+        cli::safe_exit(1)
+    });
+    let public_key =
+        rpc::get_public_key(&signer, args.ledger_address.clone()).await;
+    let multisig =
+        rpc::get_multisig(&signer, args.ledger_address.clone()).await;
+    let offline_tx = OfflineTx::new(tx, signer.clone(), public_key, multisig);
+    let file_data = offline_tx
+        .try_to_vec()
+        .expect("Encoding an offline transaction shouldn't fail");
+    if let Err(err) = fs::write(path, file_data) {
+        eprintln!(
+            "Failed to write the transaction to {}: {}",
+            path.to_string_lossy(),
+            err
+        );
+        cli::safe_exit(1)
+    }
+    println!(
+        "The transaction to be signed by {} was written to {}.",
+        signer.encode(),
+        path.to_string_lossy()
+    );
+}
+
+/// Get the address of the account that authorizes a transaction, if it is
+/// not signed with an explicit signing key
+fn signer_address(
+    ctx: &Context,
+    args: &args::Tx,
+    default: &TxSigningKey,
+) -> Option<Address> {
+    match (&args.signing_key, &args.signer, default) {
+        (None, Some(signer), _)
+        | (None, None, TxSigningKey::WalletAddress(signer)) => {
+            Some(ctx.get(signer))
+        }
+        _ => None,
+    }
+}
+
+/// If the signer of a transaction is a multisignature account, add the
+/// signatures of all of its keys that can be found in the wallet. Exits if
+/// these don't reach the account's threshold, unless forced.
+pub async fn collect_multisig_sigs(
+    wallet: &mut Wallet,
+    args: &args::Tx,
+    signer: &Address,
+    mut tx: Tx,
+) -> Tx {
+    let multisig =
+        match rpc::get_multisig(signer, args.ledger_address.clone()).await {
+            Some(multisig) => multisig,
+            None => return tx,
+        };
+    for (index, public_key) in (0..=u8::MAX).zip(&multisig.public_keys) {
+        if let Ok(keypair) = wallet.find_key_by_pk(public_key) {
+            tx = tx
+                .sign_multisig(&keypair, index)
+                .expect("A signed transaction should have data");
+        }
+    }
+    if let Err(err) = tx.verify_multisig(&multisig) {
+        eprintln!(
+            "The transaction is not authorized by the multisignature account \
+             {}: {}",
+            signer.encode(),
+            err
+        );
+        if !args.force {
+            cli::safe_exit(1)
+        }
+    }
+    tx
+}
+
 /// Create a wrapper tx from a normal tx. Get the hash of the
 /// wrapper and its payload which is needed for monitoring its
 /// progress on chain.
@@ -171,18 +281,29 @@ pub async fn sign_wrapper(
     tx: Tx,
     keypair: &common::SecretKey,
 ) -> TxBroadcastData {
+    let encryption_key = rpc::query_encryption_key(
+        args::Query {
+            ledger_address: args.ledger_address.clone(),
+        },
+        epoch,
+    )
+    .await;
+    // Convert the fee with the fee token's denomination
+    let client = HttpClient::new(args.ledger_address.clone()).unwrap();
+    let fee_token = ctx.get(&args.fee_token);
+    let (fee_amount, _) =
+        rpc::amount_with_denom(&client, &fee_token, args.fee_amount).await;
     let tx = {
         WrapperTx::new(
             Fee {
-                amount: args.fee_amount,
-                token: ctx.get(&args.fee_token),
+                amount: fee_amount,
+                token: fee_token,
             },
             keypair,
             epoch,
             args.gas_limit.clone(),
             tx,
-            // TODO: Actually use the fetched encryption key
-            Default::default(),
+            encryption_key,
         )
     };
 
@@ -193,7 +314,7 @@ pub async fn sign_wrapper(
     let decrypted_hash = tx.tx_hash.to_string();
     TxBroadcastData::Wrapper {
         tx: tx
-            .sign(keypair)
+            .sign(keypair, ctx.config.ledger.chain_id.clone(), args.expiration)
             .expect("Wrapper tx signing keypair should be correct"),
         wrapper_hash,
         decrypted_hash,
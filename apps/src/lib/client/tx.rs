@@ -3,7 +3,7 @@ use std::collections::hash_map::Entry;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
 use std::fmt::Debug;
-use std::fs::{File, OpenOptions};
+use std::fs::{self, File, OpenOptions};
 use std::io::{Read, Write};
 use std::ops::Deref;
 use std::path::PathBuf;
@@ -38,11 +38,14 @@ use namada::ibc::Height as IbcHeight;
 use namada::ibc_proto::cosmos::base::v1beta1::Coin;
 use namada::ledger::governance::storage as gov_storage;
 use namada::ledger::masp;
-use namada::ledger::pos::{BondId, Bonds, CommissionRates, Unbonds};
+use namada::ledger::pos::types::ValidatorState;
+use namada::ledger::pos::{
+    BondId, Bonds, CommissionRates, Unbonds, ValidatorStates,
+};
 use namada::proto::Tx;
-use namada::types::address::{masp, masp_tx_key, Address};
+use namada::types::address::{masp, Address};
 use namada::types::governance::{
-    OfflineProposal, OfflineVote, Proposal, ProposalVote,
+    OfflineProposal, OfflineVote, Proposal, ProposalType, ProposalVote,
 };
 use namada::types::key::*;
 use namada::types::masp::{PaymentAddress, TransferTarget};
@@ -56,7 +59,10 @@ use namada::types::token::{
 use namada::types::transaction::governance::{
     InitProposalData, VoteProposalData,
 };
-use namada::types::transaction::{pos, InitAccount, InitValidator, UpdateVp};
+use namada::types::transaction::offline::OfflineTx;
+use namada::types::transaction::{
+    pos, InitAccount, InitValidator, UpdateKey, UpdateVp,
+};
 use namada::types::{storage, token};
 use namada::{ledger, vm};
 use rand_core::{CryptoRng, OsRng, RngCore};
@@ -69,7 +75,9 @@ use super::types::ShieldedTransferContext;
 use crate::cli::context::WalletAddress;
 use crate::cli::{args, safe_exit, Context};
 use crate::client::rpc::{query_conversion, query_storage_value};
-use crate::client::signing::{find_keypair, sign_tx, tx_signer, TxSigningKey};
+use crate::client::signing::{
+    dump_tx, find_keypair, sign_tx, tx_signer, wrap_tx, TxSigningKey,
+};
 use crate::client::tendermint_rpc_types::{TxBroadcastData, TxResponse};
 use crate::client::types::ParsedTxTransferArgs;
 use crate::facade::tendermint_config::net::Address as TendermintAddress;
@@ -84,6 +92,7 @@ const TX_INIT_PROPOSAL: &str = "tx_init_proposal.wasm";
 const TX_VOTE_PROPOSAL: &str = "tx_vote_proposal.wasm";
 const TX_REVEAL_PK: &str = "tx_reveal_pk.wasm";
 const TX_UPDATE_VP_WASM: &str = "tx_update_vp.wasm";
+const TX_UPDATE_KEY_WASM: &str = "tx_update_key.wasm";
 const TX_TRANSFER_WASM: &str = "tx_transfer.wasm";
 const TX_IBC_WASM: &str = "tx_ibc.wasm";
 const VP_USER_WASM: &str = "vp_user.wasm";
@@ -91,6 +100,8 @@ const TX_BOND_WASM: &str = "tx_bond.wasm";
 const TX_UNBOND_WASM: &str = "tx_unbond.wasm";
 const TX_WITHDRAW_WASM: &str = "tx_withdraw.wasm";
 const TX_CHANGE_COMMISSION_WASM: &str = "tx_change_validator_commission.wasm";
+const TX_UNJAIL_VALIDATOR_WASM: &str = "tx_unjail_validator.wasm";
+const TX_CLAIM_REWARDS_WASM: &str = "tx_claim_rewards.wasm";
 
 /// Timeout for requests to the `/accepted` and `/applied`
 /// ABCI query endpoints.
@@ -106,7 +117,12 @@ pub async fn submit_custom(ctx: Context, args: args::TxCustom) {
     let data = args.data_path.map(|data_path| {
         std::fs::read(data_path).expect("Expected a file at given data path")
     });
-    let tx = Tx::new(tx_code, data);
+    let tx = Tx::new(
+        tx_code,
+        data,
+        ctx.config.ledger.chain_id.clone(),
+        args.tx.expiration,
+    );
     let (ctx, initialized_accounts) =
         process_tx(ctx, &args.tx, tx, TxSigningKey::None).await;
     save_initialized_accounts(ctx, &args.tx, initialized_accounts).await;
@@ -162,10 +178,98 @@ pub async fn submit_update_vp(ctx: Context, args: args::TxUpdateVp) {
     let data = UpdateVp { addr, vp_code };
     let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
 
-    let tx = Tx::new(tx_code, Some(data));
+    let tx = Tx::new(
+        tx_code,
+        Some(data),
+        ctx.config.ledger.chain_id.clone(),
+        args.tx.expiration,
+    );
     process_tx(ctx, &args.tx, tx, TxSigningKey::WalletAddress(args.addr)).await;
 }
 
+pub async fn submit_update_key(mut ctx: Context, args: args::TxUpdateKey) {
+    let addr = ctx.get(&args.addr);
+    let public_key = ctx.get_cached(&args.public_key);
+
+    // Check that the address is established and exists on chain
+    match &addr {
+        Address::Established(_) => {
+            let exists =
+                rpc::known_address(&addr, args.tx.ledger_address.clone()).await;
+            if !exists {
+                eprintln!("The address {} doesn't exist on chain.", addr);
+                if !args.tx.force {
+                    safe_exit(1)
+                }
+            }
+        }
+        Address::Implicit(_) => {
+            eprintln!(
+                "The public key of an implicit address cannot be updated, as \
+                 the address is derived from it. You can use an established \
+                 address for this purpose."
+            );
+            if !args.tx.force {
+                safe_exit(1)
+            }
+        }
+        Address::Internal(_) => {
+            eprintln!("An internal address doesn't have a public key.");
+            if !args.tx.force {
+                safe_exit(1)
+            }
+        }
+    }
+
+    let old_pk =
+        rpc::get_public_key(&addr, args.tx.ledger_address.clone()).await;
+    if old_pk.as_ref() == Some(&public_key) && !args.tx.force {
+        println!("The account {} already has this public key.", addr);
+        return;
+    }
+
+    // The new key must sign the rotation to prove that it is held
+    let new_keypair =
+        ctx.wallet
+            .find_key_by_pk(&public_key)
+            .unwrap_or_else(|err| {
+                eprintln!(
+                    "The secret key of the new public key {} is required to \
+                     prove its possession: {}",
+                    public_key, err
+                );
+                safe_exit(1)
+            });
+
+    let tx_code = ctx.read_wasm(TX_UPDATE_KEY_WASM);
+
+    let data = UpdateKey::new(addr.clone(), &new_keypair);
+    let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
+
+    let tx = Tx::new(
+        tx_code,
+        Some(data),
+        ctx.config.ledger.chain_id.clone(),
+        args.tx.expiration,
+    );
+    let (mut ctx, _) =
+        process_tx(ctx, &args.tx, tx, TxSigningKey::WalletAddress(args.addr))
+            .await;
+
+    // Once the new key is applied, link it to the old one in the wallet
+    let new_pk =
+        rpc::get_public_key(&addr, args.tx.ledger_address.clone()).await;
+    if new_pk.as_ref() == Some(&public_key) {
+        if let Some(alias) =
+            ctx.wallet
+                .add_key_rotation(&addr, old_pk.as_ref(), &public_key)
+        {
+            ctx.wallet.save().unwrap_or_else(|err| eprintln!("{}", err));
+            println!("Added the new key to the key history of \"{}\".", alias);
+        }
+    }
+}
+
 pub async fn submit_init_account(mut ctx: Context, args: args::TxInitAccount) {
     let public_key = ctx.get_cached(&args.public_key);
     let vp_code = args
@@ -180,20 +284,111 @@ pub async fn submit_init_account(mut ctx: Context, args: args::TxInitAccount) {
         }
     }
 
+    let multisig = if args.multisig_keys.is_empty() {
+        None
+    } else {
+        let public_keys = args
+            .multisig_keys
+            .iter()
+            .map(|key| ctx.get_cached(key))
+            .collect();
+        let multisig = Multisig {
+            public_keys,
+            threshold: args.threshold.unwrap_or(1),
+        };
+        if !multisig.is_valid() {
+            eprintln!(
+                "The multisignature threshold {} cannot be reached with the \
+                 {} public keys, which must be unique.",
+                multisig.threshold,
+                multisig.public_keys.len()
+            );
+            safe_exit(1)
+        }
+        Some(multisig)
+    };
+
+    if let Some(metadata) = &args.token_metadata {
+        if !metadata.is_valid() {
+            eprintln!(
+                "The token denomination {} is greater than the maximum 38 or \
+                 the token symbol is empty.",
+                metadata.denomination
+            );
+            safe_exit(1)
+        }
+    }
+
     let tx_code = ctx.read_wasm(TX_INIT_ACCOUNT_WASM);
     let data = InitAccount {
         public_key,
         vp_code,
+        multisig,
+        token_metadata: args.token_metadata,
     };
     let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
 
-    let tx = Tx::new(tx_code, Some(data));
+    let tx = Tx::new(
+        tx_code,
+        Some(data),
+        ctx.config.ledger.chain_id.clone(),
+        args.tx.expiration,
+    );
     let (ctx, initialized_accounts) =
         process_tx(ctx, &args.tx, tx, TxSigningKey::WalletAddress(args.source))
             .await;
     save_initialized_accounts(ctx, &args.tx, initialized_accounts).await;
 }
 
+pub async fn submit_signed(mut ctx: Context, args: args::SubmitSigned) {
+    let offline_tx = fs::read(&args.tx_path)
+        .and_then(|bytes| OfflineTx::try_from_slice(&bytes))
+        .unwrap_or_else(|err| {
+            eprintln!(
+                "Failed to read the signed transaction from {}: {}",
+                args.tx_path.to_string_lossy(),
+                err
+            );
+            safe_exit(1)
+        });
+    if args.tx.signing_key.is_none() && args.tx.signer.is_none() {
+        eprintln!(
+            "The wrapper of a transaction signed offline must be signed with \
+             a key from this wallet, given by `--signing-key` or `--signer`."
+        );
+        safe_exit(1)
+    }
+    // An implicit signer may have to reveal its public key first
+    if let (Address::Implicit(_), Some(public_key)) =
+        (&offline_tx.signer, &offline_tx.public_key)
+    {
+        reveal_pk_if_needed(&mut ctx, public_key, &args.tx).await;
+    }
+    let multisig = offline_tx.multisig.clone();
+    let tx = offline_tx.signed_tx().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        safe_exit(1)
+    });
+    if let Some(multisig) = multisig {
+        if let Err(err) = tx.verify_multisig(&multisig) {
+            eprintln!(
+                "The transaction is not authorized by its multisignature \
+                 account: {}",
+                err
+            );
+            if !args.tx.force {
+                safe_exit(1)
+            }
+        }
+    }
+
+    let keypair = tx_signer(&mut ctx, &args.tx, TxSigningKey::None).await;
+    let (ctx, to_broadcast) = wrap_tx(ctx, tx, &args.tx, &keypair).await;
+    let (ctx, initialized_accounts) =
+        process_wrapped_tx(ctx, &args.tx, to_broadcast).await;
+    save_initialized_accounts(ctx, &args.tx, initialized_accounts).await;
+}
+
 pub async fn submit_init_validator(
     mut ctx: Context,
     args::TxInitValidator {
@@ -209,6 +404,15 @@ pub async fn submit_init_validator(
         unsafe_dont_encrypt,
     }: args::TxInitValidator,
 ) {
+    // The validator's keys are only saved once its account is created, which
+    // doesn't happen when the tx is dumped to be signed offline
+    if tx_args.dump_tx.is_some() {
+        eprintln!(
+            "A validator cannot be initialized with a transaction dumped for \
+             offline signing, because its generated keys would be lost."
+        );
+        safe_exit(1)
+    }
     let alias = tx_args
         .initialized_account_alias
         .as_ref()
@@ -314,7 +518,12 @@ pub async fn submit_init_validator(
         validator_vp_code,
     };
     let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
-    let tx = Tx::new(tx_code, Some(data));
+    let tx = Tx::new(
+        tx_code,
+        Some(data),
+        ctx.config.ledger.chain_id.clone(),
+        tx_args.expiration,
+    );
     let (mut ctx, initialized_accounts) =
         process_tx(ctx, &tx_args, tx, TxSigningKey::WalletAddress(source))
             .await;
@@ -1296,7 +1505,6 @@ fn convert_amount(
 async fn gen_shielded_transfer<C>(
     ctx: &mut C,
     args: &ParsedTxTransferArgs,
-    shielded_gas: bool,
 ) -> Result<Option<(Transaction, TransactionMetadata)>, builder::Error>
 where
     C: ShieldedTransferContext,
@@ -1318,22 +1526,17 @@ where
     // Transactions with transparent input and shielded output
     // may be affected if constructed close to epoch boundary
     let mut epoch_sensitive: bool = false;
+    // The transaction fees are never paid from the shielded pool, but by the
+    // transparent signer of the wrapper transaction
+    builder.set_fee(Amount::zero())?;
     // If there are shielded inputs
     if let Some(sk) = spending_key {
-        // Transaction fees need to match the amount in the wrapper Transfer
-        // when MASP source is used
-        let (_, fee) =
-            convert_amount(epoch, &args.tx.fee_token, args.tx.fee_amount);
-        builder.set_fee(fee.clone())?;
-        // If the gas is coming from the shielded pool, then our shielded inputs
-        // must also cover the gas fee
-        let required_amt = if shielded_gas { amount + fee } else { amount };
         // Locate unspent notes that can help us meet the transaction amount
         let (_, unspent_notes, used_convs) = ctx
             .collect_unspent_notes(
                 args.tx.ledger_address.clone(),
                 &to_viewing_key(&sk).vk,
-                required_amt,
+                amount,
                 epoch,
             )
             .await;
@@ -1352,9 +1555,6 @@ where
             }
         }
     } else {
-        // No transfer fees come from the shielded transaction for non-MASP
-        // sources
-        builder.set_fee(Amount::zero())?;
         // We add a dummy UTXO to our transaction, but only the source of the
         // parent Transfer object is used to validate fund availability
         let secp_sk =
@@ -1476,7 +1676,16 @@ where
 }
 
 pub async fn submit_transfer(mut ctx: Context, args: args::TxTransfer) {
-    let parsed_args = args.parse_from_context(&mut ctx);
+    let client = HttpClient::new(args.tx.ledger_address.clone()).unwrap();
+    // Convert the amounts with their token's denomination
+    let token = ctx.get(&args.token);
+    let (transfer_amount, denom) =
+        rpc::amount_with_denom(&client, &token, args.amount).await;
+    let fee_token = ctx.get(&args.tx.fee_token);
+    let (fee_amount, _) =
+        rpc::amount_with_denom(&client, &fee_token, args.tx.fee_amount).await;
+    let parsed_args =
+        args.parse_from_context(&mut ctx, transfer_amount, fee_amount);
     let source = parsed_args.source.effective_address();
     let target = parsed_args.target.effective_address();
     // Check that the source address exists on chain
@@ -1525,16 +1734,18 @@ pub async fn submit_transfer(mut ctx: Context, args: args::TxTransfer) {
         }
         None => (None, token::balance_key(&parsed_args.token, &source)),
     };
-    let client = HttpClient::new(args.tx.ledger_address.clone()).unwrap();
     match rpc::query_storage_value::<token::Amount>(&client, &balance_key).await
     {
         Some(balance) => {
-            if balance < args.amount {
+            if balance < transfer_amount {
                 eprintln!(
                     "The balance of the source {} of token {} is lower than \
                      the amount to be transferred. Amount to transfer is {} \
                      and the balance is {}.",
-                    source, parsed_args.token, args.amount, balance
+                    source,
+                    parsed_args.token,
+                    args.amount,
+                    balance.to_string_with_denom(denom)
                 );
                 if !args.tx.force {
                     safe_exit(1)
@@ -1554,37 +1765,40 @@ pub async fn submit_transfer(mut ctx: Context, args: args::TxTransfer) {
 
     let tx_code = ctx.read_wasm(TX_TRANSFER_WASM);
     let masp_addr = masp();
-    // For MASP sources, use a special sentinel key recognized by VPs as default
-    // signer. Also, if the transaction is shielded, redact the amount and token
-    // types by setting the transparent value to 0 and token type to a constant.
-    // This has no side-effect because transaction is to self.
+    // The fees cannot be paid from the shielded pool, so a transfer from a
+    // MASP source has no default signer and must be signed by a transparent
+    // account that pays the fee
+    if source == masp_addr
+        && args.tx.signer.is_none()
+        && args.tx.signing_key.is_none()
+    {
+        eprintln!(
+            "The fee of a transfer from a shielded source cannot be paid by \
+             the MASP. Please specify a --signer or a --signing-key of a \
+             transparent account to pay the fee."
+        );
+        safe_exit(1)
+    }
+    // If the transaction is shielded, redact the amount and token types by
+    // setting the transparent value to 0 and token type to a constant. This
+    // has no side-effect because transaction is to self.
     let (default_signer, amount, token) =
         if source == masp_addr && target == masp_addr {
             // TODO Refactor me, we shouldn't rely on any specific token here.
-            (
-                TxSigningKey::SecretKey(masp_tx_key()),
-                0.into(),
-                ctx.native_token.clone(),
-            )
+            (TxSigningKey::None, 0.into(), ctx.native_token.clone())
         } else if source == masp_addr {
             (
-                TxSigningKey::SecretKey(masp_tx_key()),
-                args.amount,
+                TxSigningKey::None,
+                transfer_amount,
                 parsed_args.token.clone(),
             )
         } else {
             (
                 TxSigningKey::WalletAddress(args.source.to_address()),
-                args.amount,
+                transfer_amount,
                 parsed_args.token.clone(),
             )
         };
-    // If our chosen signer is the MASP sentinel key, then our shielded inputs
-    // will need to cover the gas fees.
-    let chosen_signer = tx_signer(&mut ctx, &args.tx, default_signer.clone())
-        .await
-        .ref_to();
-    let shielded_gas = masp_tx_key().ref_to() == chosen_signer;
     // Determine whether to pin this transaction to a storage key
     let key = match ctx.get(&args.target) {
         TransferTarget::PaymentAddress(pa) if pa.is_pinned() => Some(pa.hash()),
@@ -1620,20 +1834,15 @@ pub async fn submit_transfer(mut ctx: Context, args: args::TxTransfer) {
                 // short-circuited
                 let _ = ctx.shielded.save();
                 let stx_result =
-                    gen_shielded_transfer(&mut ctx, &parsed_args, shielded_gas)
-                        .await;
+                    gen_shielded_transfer(&mut ctx, &parsed_args).await;
                 match stx_result {
                     Ok(stx) => stx.map(|x| x.0),
                     Err(builder::Error::ChangeIsNegative(_)) => {
                         eprintln!(
                             "The balance of the source {} is lower than the \
-                             amount to be transferred and fees. Amount to \
-                             transfer is {} {} and fees are {} {}.",
-                            parsed_args.source,
-                            args.amount,
-                            parsed_args.token,
-                            args.tx.fee_amount,
-                            parsed_args.tx.fee_token,
+                             amount to be transferred. Amount to transfer is \
+                             {} {}.",
+                            parsed_args.source, args.amount, parsed_args.token,
                         );
                         safe_exit(1)
                     }
@@ -1647,7 +1856,12 @@ pub async fn submit_transfer(mut ctx: Context, args: args::TxTransfer) {
         .try_to_vec()
         .expect("Encoding tx data shouldn't fail");
 
-    let tx = Tx::new(tx_code, Some(data));
+    let tx = Tx::new(
+        tx_code,
+        Some(data),
+        ctx.config.ledger.chain_id.clone(),
+        args.tx.expiration,
+    );
     let signing_address = TxSigningKey::WalletAddress(args.source.to_address());
     process_tx(ctx, &args.tx, tx, signing_address).await;
 }
@@ -1689,15 +1903,21 @@ pub async fn submit_ibc_transfer(ctx: Context, args: args::TxIbcTransfer) {
         None => (None, token::balance_key(&token, &source)),
     };
     let client = HttpClient::new(args.tx.ledger_address.clone()).unwrap();
+    // Convert the amount with the token's denomination
+    let (amount, token_denom) =
+        rpc::amount_with_denom(&client, &token, args.amount).await;
     match rpc::query_storage_value::<token::Amount>(&client, &balance_key).await
     {
         Some(balance) => {
-            if balance < args.amount {
+            if balance < amount {
                 eprintln!(
                     "The balance of the source {} of token {} is lower than \
                      the amount to be transferred. Amount to transfer is {} \
                      and the balance is {}.",
-                    source, token, args.amount, balance
+                    source,
+                    token,
+                    args.amount,
+                    balance.to_string_with_denom(token_denom)
                 );
                 if !args.tx.force {
                     safe_exit(1)
@@ -1721,9 +1941,11 @@ pub async fn submit_ibc_transfer(ctx: Context, args: args::TxIbcTransfer) {
         Some(sp) => sp.to_string().replace(RESERVED_ADDRESS_PREFIX, ""),
         None => token.to_string(),
     };
+    // The ledger reads the raw amount from the coin, in the encoding of
+    // `token::Amount`'s `Display`
     let token = Some(Coin {
         denom,
-        amount: args.amount.to_string(),
+        amount: amount.to_string(),
     });
 
     // this height should be that of the destination chain, not this chain
@@ -1758,7 +1980,12 @@ pub async fn submit_ibc_transfer(ctx: Context, args: args::TxIbcTransfer) {
     prost::Message::encode(&any_msg, &mut data)
         .expect("Encoding tx data shouldn't fail");
 
-    let tx = Tx::new(tx_code, Some(data));
+    let tx = Tx::new(
+        tx_code,
+        Some(data),
+        ctx.config.ledger.chain_id.clone(),
+        args.tx.expiration,
+    );
     process_tx(ctx, &args.tx, tx, TxSigningKey::WalletAddress(args.source))
         .await;
 }
@@ -1892,11 +2119,30 @@ pub async fn submit_init_proposal(mut ctx: Context, args: args::InitProposal) {
             safe_exit(1);
         }
 
+        if !init_proposal_data.r#type.is_valid() {
+            eprintln!("Invalid proposal type: {}", init_proposal_data.r#type);
+            safe_exit(1);
+        }
+
+        if init_proposal_data.proposal_code.is_some()
+            && init_proposal_data.r#type != ProposalType::Default
+        {
+            eprintln!(
+                "Proposal code can only be attached to default proposals."
+            );
+            safe_exit(1);
+        }
+
         let data = init_proposal_data
             .try_to_vec()
             .expect("Encoding proposal data shouldn't fail");
         let tx_code = ctx.read_wasm(TX_INIT_PROPOSAL);
-        let tx = Tx::new(tx_code, Some(data));
+        let tx = Tx::new(
+            tx_code,
+            Some(data),
+            ctx.config.ledger.chain_id.clone(),
+            args.tx.expiration,
+        );
 
         process_tx(ctx, &args.tx, tx, TxSigningKey::WalletAddress(signer))
             .await;
@@ -2013,6 +2259,7 @@ pub async fn submit_vote_proposal(mut ctx: Context, args: args::VoteProposal) {
                         &client,
                         delegations,
                         proposal_id,
+                        &voter_address,
                         &args.vote,
                     )
                     .await;
@@ -2029,7 +2276,12 @@ pub async fn submit_vote_proposal(mut ctx: Context, args: args::VoteProposal) {
                     .try_to_vec()
                     .expect("Encoding proposal data shouldn't fail");
                 let tx_code = ctx.read_wasm(TX_VOTE_PROPOSAL);
-                let tx = Tx::new(tx_code, Some(data));
+                let tx = Tx::new(
+                    tx_code,
+                    Some(data),
+                    ctx.config.ledger.chain_id.clone(),
+                    args.tx.expiration,
+                );
 
                 process_tx(
                     ctx,
@@ -2099,7 +2351,12 @@ pub async fn submit_reveal_pk_aux(
         .try_to_vec()
         .expect("Encoding a public key shouldn't fail");
     let tx_code = ctx.read_wasm(TX_REVEAL_PK);
-    let tx = Tx::new(tx_code, Some(tx_data));
+    let tx = Tx::new(
+        tx_code,
+        Some(tx_data),
+        ctx.config.ledger.chain_id.clone(),
+        args.expiration,
+    );
 
     // submit_tx without signing the inner tx
     let keypair = if let Some(signing_key) = &args.signing_key {
@@ -2193,11 +2450,13 @@ async fn is_safe_voting_window(
 }
 
 /// Removes validators whose vote corresponds to that of the delegator (needless
-/// vote)
+/// vote), unless the delegator has already voted through them, in which case
+/// the earlier vote must be overwritten
 async fn filter_delegations(
     client: &HttpClient,
     delegations: HashSet<Address>,
     proposal_id: u64,
+    delegator: &Address,
     delegator_vote: &ProposalVote,
 ) -> HashSet<Address> {
     // Filter delegations by their validator's vote concurrently
@@ -2217,7 +2476,18 @@ async fn filter_delegations(
                     rpc::query_storage_value::<ProposalVote>(client, &vote_key)
                         .await
                 {
-                    if &validator_vote == delegator_vote {
+                    let delegator_vote_key = gov_storage::get_vote_proposal_key(
+                        proposal_id,
+                        delegator.to_owned(),
+                        validator_address.to_owned(),
+                    );
+                    let has_voted = rpc::query_storage_value::<ProposalVote>(
+                        client,
+                        &delegator_vote_key,
+                    )
+                    .await
+                    .is_some();
+                    if &validator_vote == delegator_vote && !has_voted {
                         return None;
                     }
                 }
@@ -2260,15 +2530,20 @@ pub async fn submit_bond(ctx: Context, args: args::Bond) {
     let bond_source = source.as_ref().unwrap_or(&validator);
     let balance_key = token::balance_key(&ctx.native_token, bond_source);
     let client = HttpClient::new(args.tx.ledger_address.clone()).unwrap();
+    // Convert the amount with the staking token's denomination
+    let (amount, denom) =
+        rpc::amount_with_denom(&client, &ctx.native_token, args.amount).await;
     match rpc::query_storage_value::<token::Amount>(&client, &balance_key).await
     {
         Some(balance) => {
-            if balance < args.amount {
+            if balance < amount {
                 eprintln!(
                     "The balance of the source {} is lower than the amount to \
                      be transferred. Amount to transfer is {} and the balance \
                      is {}.",
-                    bond_source, args.amount, balance
+                    bond_source,
+                    args.amount,
+                    balance.to_string_with_denom(denom)
                 );
                 if !args.tx.force {
                     safe_exit(1)
@@ -2285,12 +2560,17 @@ pub async fn submit_bond(ctx: Context, args: args::Bond) {
     let tx_code = ctx.read_wasm(TX_BOND_WASM);
     let bond = pos::Bond {
         validator,
-        amount: args.amount,
+        amount,
         source,
     };
     let data = bond.try_to_vec().expect("Encoding tx data shouldn't fail");
 
-    let tx = Tx::new(tx_code, Some(data));
+    let tx = Tx::new(
+        tx_code,
+        Some(data),
+        ctx.config.ledger.chain_id.clone(),
+        args.tx.expiration,
+    );
     let default_signer = args.source.unwrap_or(args.validator);
     process_tx(
         ctx,
@@ -2327,6 +2607,9 @@ pub async fn submit_unbond(ctx: Context, args: args::Unbond) {
     };
     let bond_key = ledger::pos::bond_key(&bond_id);
     let client = HttpClient::new(args.tx.ledger_address.clone()).unwrap();
+    // Convert the amount with the staking token's denomination
+    let (amount, denom) =
+        rpc::amount_with_denom(&client, &ctx.native_token, args.amount).await;
     let bonds = rpc::query_storage_value::<Bonds>(&client, &bond_key).await;
     match bonds {
         Some(bonds) => {
@@ -2336,12 +2619,14 @@ pub async fn submit_unbond(ctx: Context, args: args::Unbond) {
                     bond_amount += *delta;
                 }
             }
-            if args.amount > bond_amount {
+            if amount > bond_amount {
                 eprintln!(
                     "The total bonds of the source {} is lower than the \
                      amount to be unbonded. Amount to unbond is {} and the \
                      total bonds is {}.",
-                    bond_source, args.amount, bond_amount
+                    bond_source,
+                    args.amount,
+                    bond_amount.to_string_with_denom(denom)
                 );
                 if !args.tx.force {
                     safe_exit(1)
@@ -2358,12 +2643,17 @@ pub async fn submit_unbond(ctx: Context, args: args::Unbond) {
 
     let data = pos::Unbond {
         validator,
-        amount: args.amount,
+        amount,
         source,
     };
     let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
 
-    let tx = Tx::new(tx_code, Some(data));
+    let tx = Tx::new(
+        tx_code,
+        Some(data),
+        ctx.config.ledger.chain_id.clone(),
+        args.tx.expiration,
+    );
     let default_signer = args.source.unwrap_or(args.validator);
     process_tx(
         ctx,
@@ -2436,7 +2726,12 @@ pub async fn submit_withdraw(ctx: Context, args: args::Withdraw) {
     let data = pos::Withdraw { validator, source };
     let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
 
-    let tx = Tx::new(tx_code, Some(data));
+    let tx = Tx::new(
+        tx_code,
+        Some(data),
+        ctx.config.ledger.chain_id.clone(),
+        args.tx.expiration,
+    );
     let default_signer = args.source.unwrap_or(args.validator);
     process_tx(
         ctx,
@@ -2518,7 +2813,72 @@ pub async fn submit_validator_commission_change(
     };
     let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
 
-    let tx = Tx::new(tx_code, Some(data));
+    let tx = Tx::new(
+        tx_code,
+        Some(data),
+        ctx.config.ledger.chain_id.clone(),
+        args.tx.expiration,
+    );
+    let default_signer = args.validator;
+    process_tx(
+        ctx,
+        &args.tx,
+        tx,
+        TxSigningKey::WalletAddress(default_signer),
+    )
+    .await;
+}
+
+pub async fn submit_unjail_validator(
+    ctx: Context,
+    args: args::TxUnjailValidator,
+) {
+    let epoch = rpc::query_epoch(args::Query {
+        ledger_address: args.tx.ledger_address.clone(),
+    })
+    .await;
+
+    let validator = ctx.get(&args.validator);
+    let tx_code = ctx.read_wasm(TX_UNJAIL_VALIDATOR_WASM);
+
+    // Check that the validator is jailed
+    let state_key = ledger::pos::validator_state_key(&validator);
+    let client = HttpClient::new(args.tx.ledger_address.clone()).unwrap();
+    let states =
+        rpc::query_storage_value::<ValidatorStates>(&client, &state_key).await;
+    match states {
+        Some(states) => {
+            if states.get(epoch) != Some(&ValidatorState::Jailed) {
+                eprintln!(
+                    "The validator {} is not jailed in the current epoch {}.",
+                    validator, epoch
+                );
+                if !args.tx.force {
+                    safe_exit(1)
+                }
+            }
+        }
+        None => {
+            eprintln!(
+                "The address {} doesn't belong to any known validator account.",
+                validator
+            );
+            if !args.tx.force {
+                safe_exit(1)
+            }
+        }
+    }
+
+    let data = validator
+        .try_to_vec()
+        .expect("Encoding tx data shouldn't fail");
+
+    let tx = Tx::new(
+        tx_code,
+        Some(data),
+        ctx.config.ledger.chain_id.clone(),
+        args.tx.expiration,
+    );
     let default_signer = args.validator;
     process_tx(
         ctx,
@@ -2529,6 +2889,55 @@ pub async fn submit_validator_commission_change(
     .await;
 }
 
+pub async fn submit_claim_rewards(ctx: Context, args: args::TxClaimRewards) {
+    let validator = ctx.get(&args.validator);
+    let source = ctx.get_opt(&args.source);
+    let tx_code = ctx.read_wasm(TX_CLAIM_REWARDS_WASM);
+
+    // Check that the bond has some rewards to claim
+    let bond_source = source.clone().unwrap_or_else(|| validator.clone());
+    let bond_id = BondId {
+        source: bond_source.clone(),
+        validator: validator.clone(),
+    };
+    let pool_key = ledger::pos::bond_rewards_key(&bond_id);
+    let client = HttpClient::new(args.tx.ledger_address.clone()).unwrap();
+    let rewards =
+        rpc::query_storage_value::<token::Amount>(&client, &pool_key).await;
+    match rewards {
+        Some(rewards) if rewards != token::Amount::default() => {
+            println!("Claiming rewards of {}.", rewards);
+        }
+        _ => {
+            eprintln!(
+                "The bond of {} to validator {} has no rewards to claim.",
+                bond_source, validator
+            );
+            if !args.tx.force {
+                safe_exit(1)
+            }
+        }
+    }
+
+    let data = pos::ClaimRewards { validator, source };
+    let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
+
+    let tx = Tx::new(
+        tx_code,
+        Some(data),
+        ctx.config.ledger.chain_id.clone(),
+        args.tx.expiration,
+    );
+    let default_signer = args.source.unwrap_or(args.validator);
+    process_tx(
+        ctx,
+        &args.tx,
+        tx,
+        TxSigningKey::WalletAddress(default_signer),
+    )
+    .await;
+}
+
 /// Submit transaction and wait for result. Returns a list of addresses
 /// initialized in the transaction if any. In dry run, this is always empty.
 async fn process_tx(
@@ -2537,7 +2946,20 @@ async fn process_tx(
     tx: Tx,
     default_signer: TxSigningKey,
 ) -> (Context, Vec<Address>) {
+    if let Some(path) = &args.dump_tx {
+        dump_tx(&ctx, tx, args, default_signer, path).await;
+        return (ctx, vec![]);
+    }
     let (ctx, to_broadcast) = sign_tx(ctx, tx, args, default_signer).await;
+    process_wrapped_tx(ctx, args, to_broadcast).await
+}
+
+/// Dry run or submit a transaction that has been signed and wrapped
+async fn process_wrapped_tx(
+    ctx: Context,
+    args: &args::Tx,
+    to_broadcast: TxBroadcastData,
+) -> (Context, Vec<Address>) {
     // NOTE: use this to print the request JSON body:
 
     // let request =
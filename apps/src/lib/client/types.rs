@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use async_trait::async_trait;
 use masp_primitives::merkle_tree::MerklePath;
 use masp_primitives::primitives::{Diversifier, Note, ViewingKey};
@@ -6,6 +8,7 @@ use masp_primitives::transaction::components::Amount;
 use namada::types::address::Address;
 use namada::types::masp::{TransferSource, TransferTarget};
 use namada::types::storage::Epoch;
+use namada::types::time::DateTimeUtc;
 use namada::types::transaction::GasLimit;
 use namada::types::{key, token};
 
@@ -37,6 +40,10 @@ pub struct ParsedTxArgs {
     pub signing_key: Option<key::common::SecretKey>,
     /// Sign the tx with the keypair of the public key of the given address
     pub signer: Option<Address>,
+    /// The optional expiration of the transaction
+    pub expiration: Option<DateTimeUtc>,
+    /// Write the unsigned tx to the given file to be signed offline
+    pub dump_tx: Option<PathBuf>,
 }
 
 #[derive(Clone, Debug)]
@@ -41,8 +41,8 @@ pub mod genesis_config {
     use thiserror::Error;
 
     use super::{
-        EstablishedAccount, Genesis, ImplicitAccount, Parameters, TokenAccount,
-        Validator,
+        EstablishedAccount, Genesis, ImplicitAccount, Parameters,
+        StateExportFile, TokenAccount, Validator,
     };
     use crate::cli;
 
@@ -125,6 +125,9 @@ pub mod genesis_config {
         pub gov_params: GovernanceParamsConfig,
         // Wasm definitions
         pub wasm: HashMap<String, WasmConfig>,
+        // State exported from another chain to start from, in place of the
+        // accounts, validators and parameters above
+        pub state_export: Option<StateExportConfig>,
     }
 
     #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -147,6 +150,32 @@ pub mod genesis_config {
         // Minimum number of epoch between end and grace epoch
         // XXX: u64 doesn't work with toml-rs!
         pub min_proposal_grace_epochs: u64,
+        // Minimum fraction of the voting power that must vote on a proposal,
+        // defaults to 2/3
+        #[serde(default = "GovernanceParamsConfig::default_min_quorum")]
+        pub min_quorum: Decimal,
+        // Minimum fraction of the yay and nay votes that must be yay for a
+        // proposal to pass, defaults to 2/3
+        #[serde(default = "GovernanceParamsConfig::default_pass_threshold")]
+        pub pass_threshold: Decimal,
+        // Pass threshold of parameter change proposals, defaults to
+        // `pass_threshold`
+        #[serde(default)]
+        pub parameter_change_pass_threshold: Option<Decimal>,
+        // Pass threshold of treasury transfer proposals, defaults to
+        // `pass_threshold`
+        #[serde(default)]
+        pub treasury_transfer_pass_threshold: Option<Decimal>,
+    }
+
+    impl GovernanceParamsConfig {
+        fn default_min_quorum() -> Decimal {
+            GovParams::default().min_quorum
+        }
+
+        fn default_pass_threshold() -> Decimal {
+            GovParams::default().pass_threshold
+        }
     }
 
     /// Validator pre-genesis configuration can be created with client utils
@@ -197,9 +226,17 @@ pub mod genesis_config {
         pub address: Option<String>,
         // Filename of token account VP. (default: token VP)
         pub vp: Option<String>,
-        // Initial balances held by accounts defined elsewhere.
+        // Initial balances held by accounts defined elsewhere, in whole
+        // tokens.
         // XXX: u64 doesn't work with toml-rs!
         pub balances: Option<HashMap<String, u64>>,
+        // Number of decimal places of the token's amounts. (default: 6)
+        pub denomination: Option<u8>,
+        // Ticker symbol of the token. (default: the token's name in the
+        // config)
+        pub symbol: Option<String>,
+        // Display name of the token. (default: the symbol)
+        pub name: Option<String>,
     }
 
     #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -277,6 +314,16 @@ pub mod genesis_config {
         // light client attack.
         // XXX: u64 doesn't work with toml-rs!
         pub light_client_attack_min_slash_rate: Decimal,
+        // Number of the most recent blocks over which the liveness of the
+        // active validators is checked.
+        // XXX: u64 doesn't work with toml-rs!
+        pub liveness_window_len: u64,
+        // Minimum portion of the blocks in the liveness window that an
+        // active validator must sign to avoid being jailed.
+        pub liveness_threshold: Decimal,
+        // Portion of a validator's stake that should be slashed when it gets
+        // jailed for downtime.
+        pub downtime_slash_rate: Decimal,
     }
 
     #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -285,6 +332,14 @@ pub mod genesis_config {
         pub sha256: Option<HexString>,
     }
 
+    #[derive(Clone, Debug, Deserialize, Serialize)]
+    pub struct StateExportConfig {
+        // Path to the file of the export, relative to the base directory
+        pub path: String,
+        // SHA-256 hash of the file
+        pub sha256: HexString,
+    }
+
     fn load_validator(
         config: &ValidatorConfig,
         wasm: &HashMap<String, WasmConfig>,
@@ -359,6 +414,7 @@ pub mod genesis_config {
     }
 
     fn load_token(
+        name: &str,
         config: &TokenAccountConfig,
         wasm: &HashMap<String, WasmConfig>,
         validators: &HashMap<String, Validator>,
@@ -367,6 +423,22 @@ pub mod genesis_config {
     ) -> TokenAccount {
         let token_vp_name = config.vp.as_ref().unwrap();
         let token_vp_config = wasm.get(token_vp_name).unwrap();
+        let symbol = config.symbol.clone().unwrap_or_else(|| name.to_owned());
+        let metadata = token::Metadata {
+            denomination: config
+                .denomination
+                .unwrap_or(token::MAX_DECIMAL_PLACES as u8),
+            name: config.name.clone().unwrap_or_else(|| symbol.clone()),
+            symbol,
+        };
+        if !metadata.is_valid() {
+            eprintln!(
+                "Invalid metadata of token {}, the denomination must be at \
+                 most 38 and the symbol must not be empty",
+                name
+            );
+            cli::safe_exit(1)
+        }
 
         TokenAccount {
             address: Address::decode(config.address.as_ref().unwrap()).unwrap(),
@@ -435,10 +507,21 @@ pub mod genesis_config {
                                 }
                             }
                         },
-                        token::Amount::whole(*amount),
+                        token::Amount::whole_with_denom(
+                            *amount,
+                            metadata.denomination,
+                        )
+                        .unwrap_or_else(|err| {
+                            eprintln!(
+                                "Invalid balance {} of {} of token {}: {}",
+                                amount, alias_or_address, name, err
+                            );
+                            cli::safe_exit(1)
+                        }),
                     )
                 })
                 .collect(),
+            metadata,
         }
     }
 
@@ -503,6 +586,7 @@ pub mod genesis_config {
             pos_params,
             gov_params,
             wasm,
+            state_export,
         } = config;
 
         let native_token = Address::decode(
@@ -533,11 +617,11 @@ pub mod genesis_config {
             .iter()
             .map(|(name, cfg)| (name.clone(), load_implicit(cfg)))
             .collect();
-        #[allow(clippy::iter_kv_map)]
         let token_accounts = token
             .iter()
-            .map(|(_name, cfg)| {
+            .map(|(name, cfg)| {
                 load_token(
+                    name,
                     cfg,
                     &wasm,
                     &validators,
@@ -592,6 +676,10 @@ pub mod genesis_config {
             max_proposal_content_size,
             min_proposal_grace_epochs,
             max_proposal_period,
+            min_quorum,
+            pass_threshold,
+            parameter_change_pass_threshold,
+            treasury_transfer_pass_threshold,
         } = gov_params;
         let gov_params = GovParams {
             min_proposal_fund,
@@ -600,6 +688,10 @@ pub mod genesis_config {
             max_proposal_content_size,
             min_proposal_grace_epochs,
             max_proposal_period,
+            min_quorum,
+            pass_threshold,
+            parameter_change_pass_threshold,
+            treasury_transfer_pass_threshold,
         };
 
         let PosParamsConfig {
@@ -613,6 +705,9 @@ pub mod genesis_config {
             target_staked_ratio,
             duplicate_vote_min_slash_rate,
             light_client_attack_min_slash_rate,
+            liveness_window_len,
+            liveness_threshold,
+            downtime_slash_rate,
         } = pos_params;
         let pos_params = PosParams {
             max_validator_slots,
@@ -625,6 +720,9 @@ pub mod genesis_config {
             target_staked_ratio,
             duplicate_vote_min_slash_rate,
             light_client_attack_min_slash_rate,
+            liveness_window_len,
+            liveness_threshold,
+            downtime_slash_rate,
         };
 
         let mut genesis = Genesis {
@@ -637,6 +735,13 @@ pub mod genesis_config {
             parameters,
             pos_params,
             gov_params,
+            state_export: state_export.map(|config| StateExportFile {
+                sha256: config
+                    .sha256
+                    .to_sha256_bytes()
+                    .expect("Invalid state export sha256 hash"),
+                path: config.path,
+            }),
         };
         genesis.init();
         genesis
@@ -685,6 +790,7 @@ pub struct Genesis {
     pub parameters: Parameters,
     pub pos_params: PosParams,
     pub gov_params: GovParams,
+    pub state_export: Option<StateExportFile>,
 }
 
 impl Genesis {
@@ -762,6 +868,9 @@ pub struct TokenAccount {
     /// Accounts' balances of this token
     #[derivative(PartialOrd = "ignore", Ord = "ignore")]
     pub balances: HashMap<Address, token::Amount>,
+    /// The token's denomination, symbol and name
+    #[derivative(PartialOrd = "ignore", Ord = "ignore")]
+    pub metadata: token::Metadata,
 }
 
 #[derive(
@@ -780,6 +889,17 @@ pub struct ImplicitAccount {
     pub public_key: common::PublicKey,
 }
 
+/// A state exported from another chain with `anoma node ledger dump-state`,
+/// from which the chain starts instead of the genesis accounts, validators
+/// and parameters
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
+pub struct StateExportFile {
+    /// Path to the file of the export, relative to the base directory
+    pub path: String,
+    /// Expected SHA-256 hash of the file
+    pub sha256: [u8; 32],
+}
+
 /// Protocol parameters. This is almost the same as
 /// `ledger::parameters::Parameters`, but instead of having the `implicit_vp`
 /// WASM code bytes, it only has the name and sha as the actual code is loaded
@@ -934,12 +1054,17 @@ pub fn genesis() -> Genesis {
         ((&validator.account_key).into(), default_key_tokens),
     ]);
     let token_accounts = address::tokens()
-        .into_keys()
-        .map(|address| TokenAccount {
+        .into_iter()
+        .map(|(address, symbol)| TokenAccount {
             address,
             vp_code_path: vp_token_path.into(),
             vp_sha256: Default::default(),
             balances: balances.clone(),
+            metadata: token::Metadata {
+                denomination: token::MAX_DECIMAL_PLACES as u8,
+                symbol: symbol.to_owned(),
+                name: symbol.to_owned(),
+            },
         })
         .collect();
     Genesis {
@@ -952,6 +1077,7 @@ pub fn genesis() -> Genesis {
         pos_params: PosParams::default(),
         gov_params: GovParams::default(),
         native_token: address::nam(),
+        state_export: None,
     }
 }
 
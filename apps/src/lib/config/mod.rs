@@ -10,6 +10,7 @@ use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
+use namada::ledger::storage::PruningMode;
 use namada::types::chain::ChainId;
 use namada::types::time::Rfc3339String;
 use serde::{Deserialize, Serialize};
@@ -32,6 +33,8 @@ pub const FILENAME: &str = "config.toml";
 pub const TENDERMINT_DIR: &str = "tendermint";
 /// Chain-specific Anoma DB. Nested in chain dirs.
 pub const DB_DIR: &str = "db";
+/// Chain-specific snapshots of the state. Nested in chain dirs.
+pub const SNAPSHOTS_DIR: &str = "snapshots";
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Config {
@@ -91,10 +94,74 @@ pub struct Shell {
     /// When set, will limit the how many block heights in the past can the
     /// storage be queried for reading values.
     pub storage_read_past_height_limit: Option<u64>,
+    /// The gas budget of the txs included in a block proposed by this node.
+    /// When not set, defaults to the protocol's block gas limit.
+    pub block_gas_limit: Option<u64>,
+    /// The maximum size in bytes of the txs included in a block proposed by
+    /// this node. Tendermint's `max_tx_bytes` limit always applies as well.
+    pub block_max_tx_bytes: Option<u64>,
     /// Use the [`Ledger::db_dir()`] method to read the value.
     db_dir: PathBuf,
     /// Use the [`Ledger::tendermint_dir()`] method to read the value.
     tendermint_dir: PathBuf,
+    /// Which heights' state is kept in the storage to read and prove values
+    /// at past heights. Defaults to keeping every height.
+    #[serde(default)]
+    pub pruning: PruningMode,
+    /// When set, a snapshot of the state is taken at every block height that
+    /// is a multiple of this interval, to be served to the nodes that state
+    /// sync.
+    #[serde(default)]
+    pub snapshot_interval: Option<u64>,
+    /// The DB in which the state is stored. Defaults to RocksDB.
+    #[serde(default)]
+    pub db_backend: DbBackend,
+    /// The Ethereum node whose bridge events a validator votes on. When not
+    /// set, the validator doesn't relay any Ethereum events.
+    #[serde(default)]
+    pub ethereum_bridge: Option<EthereumBridge>,
+}
+
+/// The Ethereum node watched by the oracle of a validator, which relays the
+/// events emitted by the bridge smart contract to the ledger
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EthereumBridge {
+    /// The URL of the JSON-RPC endpoint of the Ethereum node
+    pub rpc_url: String,
+    /// The address of the bridge smart contract, e.g. `0x1234...`
+    pub bridge_contract: String,
+    /// The Ethereum block from which the events are relayed, i.e. the block
+    /// in which the bridge smart contract was deployed
+    #[serde(default)]
+    pub start_block: u64,
+    /// The number of blocks that must be built on top of an Ethereum block
+    /// before its events are relayed
+    #[serde(default = "EthereumBridge::default_min_confirmations")]
+    pub min_confirmations: u64,
+}
+
+impl EthereumBridge {
+    fn default_min_confirmations() -> u64 {
+        100
+    }
+}
+
+/// The DB backends in which the ledger can store its state
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum DbBackend {
+    /// RocksDB in the chain's DB directory
+    #[default]
+    RocksDb,
+    /// Ordered in-memory maps, persisted in an append-only log in the chain's
+    /// DB directory. As the whole state is kept in memory, it requires the
+    /// `keep_recent` pruning mode.
+    BTree,
+    /// Ordered in-memory maps that aren't persisted, e.g. for testing. The
+    /// state is lost when the ledger stops.
+    InMemory,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -118,6 +185,23 @@ pub struct Tendermint {
     pub instrumentation_prometheus: bool,
     pub instrumentation_prometheus_listen_addr: SocketAddr,
     pub instrumentation_namespace: String,
+    /// When set, a node without any state syncs it from the snapshots of its
+    /// peers instead of replaying the chain from the genesis
+    #[serde(default)]
+    pub state_sync: Option<StateSync>,
+}
+
+/// The trusted block with which Tendermint verifies the snapshots of the state
+/// offered by its peers
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StateSync {
+    /// The RPC servers of the nodes to verify the snapshots with, of which
+    /// there must be at least two
+    pub rpc_servers: Vec<SocketAddr>,
+    /// The height of the trusted block
+    pub trust_height: u64,
+    /// The hash of the trusted block
+    pub trust_hash: String,
 }
 
 impl Ledger {
@@ -140,8 +224,14 @@ impl Ledger {
                 tx_wasm_compilation_cache_bytes: None,
                 // Default corresponds to 1 hour of past blocks at 1 block/sec
                 storage_read_past_height_limit: Some(3600),
+                block_gas_limit: None,
+                block_max_tx_bytes: None,
                 db_dir: DB_DIR.into(),
                 tendermint_dir: TENDERMINT_DIR.into(),
+                pruning: PruningMode::default(),
+                snapshot_interval: None,
+                db_backend: DbBackend::default(),
+                ethereum_bridge: None,
             },
             tendermint: Tendermint {
                 rpc_address: SocketAddr::new(
@@ -164,6 +254,7 @@ impl Ledger {
                     26661,
                 ),
                 instrumentation_namespace: "anoman_tm".to_string(),
+                state_sync: None,
             },
         }
     }
@@ -190,6 +281,11 @@ impl Shell {
         self.base_dir.join(chain_id.as_str()).join(&self.db_dir)
     }
 
+    /// Get the directory path to the snapshots of the state
+    pub fn snapshots_dir(&self, chain_id: &ChainId) -> PathBuf {
+        self.base_dir.join(chain_id.as_str()).join(SNAPSHOTS_DIR)
+    }
+
     /// Get the directory path to Tendermint
     pub fn tendermint_dir(&self, chain_id: &ChainId) -> PathBuf {
         self.base_dir
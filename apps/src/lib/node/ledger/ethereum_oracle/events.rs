@@ -0,0 +1,395 @@
+//! Decoding of the logs of the events emitted by the Ethereum bridge smart
+//! contract. Their data is ABI-encoded, as declared by these signatures:
+//!
+//! - `TransferToNamada(uint256 nonce, address[] assets, uint256[] amounts,
+//!   string[] receivers)`
+//! - `ValidatorSetUpdate(uint256 nonce, bytes32 bridgeValidatorSetHash, bytes32
+//!   governanceValidatorSetHash)`
+
+use data_encoding::HEXLOWER_PERMISSIVE;
+use namada::types::address::{self, Address};
+use namada::types::ethereum_events::{
+    EthAddress, EthereumEvent, TransferToNamada,
+};
+use namada::types::hash::Hash;
+use namada::types::token;
+use sha3::{Digest, Keccak256};
+use thiserror::Error;
+
+/// The signature of the event of a batch of transfers to Namada
+pub const TRANSFER_TO_NAMADA_SIGNATURE: &str =
+    "TransferToNamada(uint256,address[],uint256[],string[])";
+/// The signature of the event of an update of the bridge validator sets
+pub const VALIDATOR_SET_UPDATE_SIGNATURE: &str =
+    "ValidatorSetUpdate(uint256,bytes32,bytes32)";
+
+/// The size of an ABI-encoded word
+const WORD: usize = 32;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Invalid hex encoding of the log: {0}")]
+    InvalidHex(data_encoding::DecodeError),
+    #[error("The log has no topic identifying its event")]
+    MissingTopic,
+    #[error("Unknown event topic 0x{0}")]
+    UnknownTopic(String),
+    #[error("The event data is too short to be read at byte {0}")]
+    OutOfBounds(usize),
+    #[error("Invalid ABI-encoded {0}")]
+    InvalidValue(&'static str),
+    #[error("The transfers have {0} assets, {1} amounts and {2} receivers")]
+    MismatchedTransfers(usize, usize, usize),
+    #[error("Invalid receiver address: {0}")]
+    InvalidReceiver(address::DecodeError),
+}
+
+/// Result of decoding an event
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The event decoded from a log
+#[derive(Debug)]
+pub struct DecodedLog {
+    /// The event, without the transfers that cannot be represented on Namada
+    pub event: EthereumEvent,
+    /// The transfers of the log that cannot be represented on Namada
+    pub invalid_transfers: Vec<InvalidTransfer>,
+}
+
+/// A transfer of a `TransferToNamada` log whose amount doesn't fit in a token
+/// amount or whose receiver is not a valid address
+#[derive(Debug)]
+pub struct InvalidTransfer {
+    /// The index of the transfer in the log
+    pub index: usize,
+    /// The transferred asset
+    pub asset: EthAddress,
+    /// The reason why the transfer cannot be represented
+    pub error: Error,
+}
+
+/// Get the topic identifying the logs of the event with the given signature
+pub fn event_topic(signature: &str) -> [u8; 32] {
+    Keccak256::digest(signature.as_bytes()).into()
+}
+
+/// Decode the event of a log, from the hex encoding of its topics and data
+pub fn decode_log(topics: &[String], data: &str) -> Result<DecodedLog> {
+    let topic = topics.first().ok_or(Error::MissingTopic)?;
+    let topic = decode_hex(topic)?;
+    let data = decode_hex(data)?;
+    if topic == event_topic(TRANSFER_TO_NAMADA_SIGNATURE) {
+        decode_transfers_to_namada(&data)
+    } else if topic == event_topic(VALIDATOR_SET_UPDATE_SIGNATURE) {
+        Ok(DecodedLog {
+            event: decode_validator_set_update(&data)?,
+            invalid_transfers: vec![],
+        })
+    } else {
+        Err(Error::UnknownTopic(HEXLOWER_PERMISSIVE.encode(&topic)))
+    }
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    HEXLOWER_PERMISSIVE
+        .decode(hex.as_bytes())
+        .map_err(Error::InvalidHex)
+}
+
+/// Decode the transfers of a log one by one, so that the transfers that
+/// cannot be represented on Namada don't invalidate the others
+fn decode_transfers_to_namada(data: &[u8]) -> Result<DecodedLog> {
+    let nonce = read_u64(data, 0, "nonce")?;
+    let assets = read_array(data, WORD, read_address)?;
+    let amounts = read_array(data, 2 * WORD, |elems, offset| {
+        Ok(read_u128(elems, offset, "amount").and_then(|amount| {
+            token::Amount::try_from(amount)
+                .map_err(|_| Error::InvalidValue("amount"))
+        }))
+    })?;
+    // a malformed encoding invalidates the whole log, but a receiver that
+    // isn't a valid address only invalidates its transfer
+    let receivers =
+        read_array(data, 3 * WORD, |elems, offset| {
+            match read_string(elems, offset) {
+                Ok(receiver) => {
+                    Ok(Address::decode(receiver)
+                        .map_err(Error::InvalidReceiver))
+                }
+                Err(err @ Error::InvalidValue("string")) => Ok(Err(err)),
+                Err(err) => Err(err),
+            }
+        })?;
+    if assets.len() != amounts.len() || assets.len() != receivers.len() {
+        return Err(Error::MismatchedTransfers(
+            assets.len(),
+            amounts.len(),
+            receivers.len(),
+        ));
+    }
+    let mut transfers = vec![];
+    let mut invalid_transfers = vec![];
+    for (index, ((asset, amount), receiver)) in
+        assets.into_iter().zip(amounts).zip(receivers).enumerate()
+    {
+        match amount.and_then(|amount| Ok((amount, receiver?))) {
+            Ok((amount, receiver)) => transfers.push(TransferToNamada {
+                asset,
+                amount,
+                receiver,
+            }),
+            Err(error) => invalid_transfers.push(InvalidTransfer {
+                index,
+                asset,
+                error,
+            }),
+        }
+    }
+    Ok(DecodedLog {
+        event: EthereumEvent::TransfersToNamada { nonce, transfers },
+        invalid_transfers,
+    })
+}
+
+fn decode_validator_set_update(data: &[u8]) -> Result<EthereumEvent> {
+    Ok(EthereumEvent::ValidatorSetUpdate {
+        nonce: read_u64(data, 0, "nonce")?,
+        bridge_validator_hash: Hash(*read_word(data, WORD)?),
+        governance_validator_hash: Hash(*read_word(data, 2 * WORD)?),
+    })
+}
+
+/// Read `len` bytes of the data, starting at `offset`
+fn read_slice(data: &[u8], offset: usize, len: usize) -> Result<&[u8]> {
+    offset
+        .checked_add(len)
+        .and_then(|end| data.get(offset..end))
+        .ok_or(Error::OutOfBounds(offset))
+}
+
+fn read_word(data: &[u8], offset: usize) -> Result<&[u8; WORD]> {
+    let word = read_slice(data, offset, WORD)?;
+    Ok(word
+        .try_into()
+        .expect("The slice must have the size of a word"))
+}
+
+/// Read an `uint256` that must fit in an `u128`
+fn read_u128(data: &[u8], offset: usize, what: &'static str) -> Result<u128> {
+    let word = read_word(data, offset)?;
+    let (high, low) = word.split_at(WORD - 16);
+    if high.iter().any(|byte| *byte != 0) {
+        return Err(Error::InvalidValue(what));
+    }
+    Ok(u128::from_be_bytes(
+        low.try_into().expect("Must have 16 bytes"),
+    ))
+}
+
+/// Read an `uint256` that must fit in an `u64`
+fn read_u64(data: &[u8], offset: usize, what: &'static str) -> Result<u64> {
+    let value = read_u128(data, offset, what)?;
+    u64::try_from(value).map_err(|_| Error::InvalidValue(what))
+}
+
+fn read_usize(data: &[u8], offset: usize, what: &'static str) -> Result<usize> {
+    let value = read_u64(data, offset, what)?;
+    usize::try_from(value).map_err(|_| Error::InvalidValue(what))
+}
+
+fn read_address(data: &[u8], offset: usize) -> Result<EthAddress> {
+    let word = read_word(data, offset)?;
+    let (high, low) = word.split_at(WORD - 20);
+    if high.iter().any(|byte| *byte != 0) {
+        return Err(Error::InvalidValue("address"));
+    }
+    Ok(EthAddress(low.try_into().expect("Must have 20 bytes")))
+}
+
+/// Read a `string` whose offset relative to the data is at `offset`
+fn read_string(data: &[u8], offset: usize) -> Result<String> {
+    let start = read_usize(data, offset, "string offset")?;
+    let len = read_usize(data, start, "string length")?;
+    let bytes = read_slice(data, start.saturating_add(WORD), len)?;
+    String::from_utf8(bytes.to_vec()).map_err(|_| Error::InvalidValue("string"))
+}
+
+/// Read an array whose offset relative to the data is at `offset`, with a
+/// function that reads the element at the given offset of the array's
+/// elements
+fn read_array<T>(
+    data: &[u8],
+    offset: usize,
+    read_elem: impl Fn(&[u8], usize) -> Result<T>,
+) -> Result<Vec<T>> {
+    let start = read_usize(data, offset, "array offset")?;
+    let len = read_usize(data, start, "array length")?;
+    let elems = data
+        .get(start.saturating_add(WORD)..)
+        .ok_or(Error::OutOfBounds(start))?;
+    // every element has at least one word in the array's head
+    if len > elems.len() / WORD {
+        return Err(Error::OutOfBounds(start));
+    }
+    (0..len).map(|i| read_elem(elems, i * WORD)).collect()
+}
+
+#[cfg(test)]
+mod test_events {
+    use namada::types::address::testing::established_address_1;
+
+    use super::*;
+
+    fn word(value: u64) -> Vec<u8> {
+        let mut word = vec![0; WORD];
+        word[WORD - 8..].copy_from_slice(&value.to_be_bytes());
+        word
+    }
+
+    fn hex(data: &[u8]) -> String {
+        format!("0x{}", HEXLOWER_PERMISSIVE.encode(data))
+    }
+
+    /// Test that the topics are the Keccak hashes of the event signatures
+    #[test]
+    fn test_event_topic() {
+        assert_eq!(
+            hex(&event_topic("Transfer(address,address,uint256)")),
+            "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+        );
+    }
+
+    /// Test decoding the ABI-encoded event of a batch of transfers
+    #[test]
+    fn test_decode_transfers_to_namada() {
+        let asset = EthAddress([1; 20]);
+        let receiver = established_address_1();
+        let receiver_str = receiver.encode();
+
+        let mut data = word(7);
+        // the offsets of the assets, amounts and receivers
+        data.extend(word(4 * 32));
+        data.extend(word(6 * 32));
+        data.extend(word(8 * 32));
+        // assets
+        data.extend(word(1));
+        data.extend([vec![0; 12], asset.0.to_vec()].concat());
+        // amounts
+        data.extend(word(1));
+        data.extend(word(100));
+        // receivers, with the offset of the string relative to the elements
+        data.extend(word(1));
+        data.extend(word(32));
+        data.extend(word(receiver_str.len() as u64));
+        let mut string = receiver_str.as_bytes().to_vec();
+        string.resize((string.len() + WORD - 1) / WORD * WORD, 0);
+        data.extend(string);
+
+        let topics = vec![hex(&event_topic(TRANSFER_TO_NAMADA_SIGNATURE))];
+        let decoded = decode_log(&topics, &hex(&data)).expect("Test failed");
+        assert!(decoded.invalid_transfers.is_empty());
+        assert_eq!(
+            decoded.event,
+            EthereumEvent::TransfersToNamada {
+                nonce: 7,
+                transfers: vec![TransferToNamada {
+                    asset,
+                    amount: token::Amount::from(100),
+                    receiver,
+                }],
+            }
+        );
+
+        // the data must not be truncated
+        let truncated = &data[..data.len() - WORD];
+        assert!(decode_log(&topics, &hex(truncated)).is_err());
+    }
+
+    /// Test that a transfer whose amount doesn't fit in a token amount is
+    /// reported and left out of the event, without dropping the other
+    /// transfers of the log
+    #[test]
+    fn test_decode_unrepresentable_transfer() {
+        let asset = EthAddress([1; 20]);
+        let receiver = established_address_1();
+        let receiver_str = receiver.encode();
+        let mut padded = receiver_str.as_bytes().to_vec();
+        padded.resize((padded.len() + WORD - 1) / WORD * WORD, 0);
+
+        let mut data = word(7);
+        // the offsets of the assets, amounts and receivers
+        data.extend(word(4 * 32));
+        data.extend(word(7 * 32));
+        data.extend(word(10 * 32));
+        // assets
+        data.extend(word(2));
+        data.extend([vec![0; 12], asset.0.to_vec()].concat());
+        data.extend([vec![0; 12], asset.0.to_vec()].concat());
+        // amounts, the first one exceeding a token amount and the second
+        // one exceeding an `u64`, like the amounts of 18 decimal places
+        data.extend(word(2));
+        data.extend([vec![0; WORD - 17], vec![1], vec![0; 16]].concat());
+        data.extend([vec![0; WORD - 9], vec![1], vec![0; 8]].concat());
+        // receivers, with the offsets of the strings relative to the
+        // elements
+        data.extend(word(2));
+        data.extend(word(2 * 32));
+        data.extend(word((3 * 32 + padded.len()) as u64));
+        for _ in 0..2 {
+            data.extend(word(receiver_str.len() as u64));
+            data.extend(padded.clone());
+        }
+
+        let topics = vec![hex(&event_topic(TRANSFER_TO_NAMADA_SIGNATURE))];
+        let decoded = decode_log(&topics, &hex(&data)).expect("Test failed");
+        assert!(matches!(
+            decoded.invalid_transfers.as_slice(),
+            [InvalidTransfer {
+                index: 0,
+                error: Error::InvalidValue("amount"),
+                ..
+            }]
+        ));
+        assert_eq!(
+            decoded.event,
+            EthereumEvent::TransfersToNamada {
+                nonce: 7,
+                transfers: vec![TransferToNamada {
+                    asset,
+                    amount: token::Amount::try_from(1_u128 << 64).unwrap(),
+                    receiver,
+                }],
+            }
+        );
+    }
+
+    /// Test decoding the ABI-encoded event of a validator set update
+    #[test]
+    fn test_decode_validator_set_update() {
+        let mut data = word(3);
+        data.extend([2; 32]);
+        data.extend([3; 32]);
+        let topics = vec![hex(&event_topic(VALIDATOR_SET_UPDATE_SIGNATURE))];
+        let decoded = decode_log(&topics, &hex(&data)).expect("Test failed");
+        assert_eq!(
+            decoded.event,
+            EthereumEvent::ValidatorSetUpdate {
+                nonce: 3,
+                bridge_validator_hash: Hash([2; 32]),
+                governance_validator_hash: Hash([3; 32]),
+            }
+        );
+    }
+
+    /// Test that the logs of unknown events are rejected
+    #[test]
+    fn test_decode_unknown_event() {
+        let topics = vec![hex(&event_topic("Unknown()"))];
+        assert!(matches!(
+            decode_log(&topics, "0x"),
+            Err(Error::UnknownTopic(_))
+        ));
+    }
+}
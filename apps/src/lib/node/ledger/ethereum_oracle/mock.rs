@@ -0,0 +1,26 @@
+//! A mock Ethereum oracle, which relays the events that it is given instead
+//! of watching an Ethereum node. This allows to use the bridge offline.
+
+use namada::types::ethereum_events::EthereumEvent;
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::EthereumReceiver;
+
+/// A handle to relay Ethereum events to the ledger
+#[derive(Clone, Debug)]
+pub struct MockEthereumOracle(UnboundedSender<EthereumEvent>);
+
+impl MockEthereumOracle {
+    /// Create a new mock oracle, paired with the receiver of the events that
+    /// it relays
+    pub fn new() -> (Self, EthereumReceiver) {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        (Self(sender), EthereumReceiver::new(receiver))
+    }
+
+    /// Relay an Ethereum event to the ledger. Returns `false` if the
+    /// receiver has been dropped.
+    pub fn send(&self, event: EthereumEvent) -> bool {
+        self.0.send(event).is_ok()
+    }
+}
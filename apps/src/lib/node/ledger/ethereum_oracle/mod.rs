@@ -0,0 +1,268 @@
+//! Receiving the events emitted by the Ethereum bridge smart contracts, as
+//! relayed by an Ethereum oracle.
+
+pub mod events;
+#[cfg(test)]
+pub mod mock;
+
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use data_encoding::HEXLOWER;
+use namada::types::ethereum_events::{
+    EthAddress, EthereumEvent, ParseEthAddressError,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use thiserror::Error;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+
+use crate::config::EthereumBridge;
+
+/// How long the oracle waits before polling the Ethereum node again
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// The maximum number of Ethereum blocks whose logs are requested at once
+const MAX_BLOCK_RANGE: u64 = 1000;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Failed to request the Ethereum node: {0}")]
+    Request(reqwest::Error),
+    #[error("Invalid response from the Ethereum node: {0}")]
+    InvalidResponse(String),
+    #[error("The Ethereum node returned an error: {0}")]
+    Rpc(Value),
+}
+
+/// A log of an event, as returned by the `eth_getLogs` method
+#[derive(Debug, Deserialize)]
+struct Log {
+    topics: Vec<String>,
+    data: String,
+    #[serde(default)]
+    removed: bool,
+}
+
+/// An oracle that watches an Ethereum node over JSON-RPC and relays the
+/// events emitted by the bridge smart contract in its confirmed blocks
+#[derive(Debug)]
+pub struct EthereumOracle {
+    client: reqwest::Client,
+    rpc_url: String,
+    bridge_contract: EthAddress,
+    min_confirmations: u64,
+    /// The next Ethereum block whose events are to be relayed
+    next_block: u64,
+    sender: UnboundedSender<EthereumEvent>,
+}
+
+impl EthereumOracle {
+    /// Create a new oracle watching the configured Ethereum node, paired
+    /// with the receiver of the events that it relays
+    pub fn new(
+        config: &EthereumBridge,
+    ) -> Result<(Self, EthereumReceiver), ParseEthAddressError> {
+        let bridge_contract = config.bridge_contract.parse()?;
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let oracle = Self {
+            client: reqwest::Client::new(),
+            rpc_url: config.rpc_url.clone(),
+            bridge_contract,
+            min_confirmations: config.min_confirmations,
+            next_block: config.start_block,
+            sender,
+        };
+        Ok((oracle, EthereumReceiver::new(receiver)))
+    }
+
+    /// Loop until an abort signal is received, relaying the events of the
+    /// Ethereum blocks as they get confirmed
+    pub async fn run(
+        &mut self,
+        abort_recv: tokio::sync::oneshot::Receiver<()>,
+    ) {
+        tracing::info!("Starting the Ethereum oracle.");
+        tokio::select! {
+            _ = self.run_loop() => {
+                tracing::info!("The Ethereum events receiver was dropped.");
+                tracing::info!("Shutting down the Ethereum oracle...");
+            },
+            resp_sender = abort_recv => {
+                if let Err(err) = resp_sender {
+                    tracing::error!(
+                        "The Ethereum oracle abort sender has unexpectedly \
+                         dropped: {}",
+                        err
+                    );
+                }
+                tracing::info!("Shutting down the Ethereum oracle...");
+            }
+        }
+    }
+
+    /// Poll the Ethereum node until the receiver of the events is dropped
+    async fn run_loop(&mut self) {
+        while !self.sender.is_closed() {
+            if let Err(err) = self.relay_confirmed_events().await {
+                tracing::warn!("Failed to relay the Ethereum events: {}", err);
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Relay the events of the confirmed Ethereum blocks that have not been
+    /// relayed yet
+    async fn relay_confirmed_events(&mut self) -> Result<(), Error> {
+        let latest_block = self.block_number().await?;
+        let last_confirmed =
+            match latest_block.checked_sub(self.min_confirmations) {
+                Some(block) => block,
+                None => return Ok(()),
+            };
+        while self.next_block <= last_confirmed {
+            let to_block = last_confirmed
+                .min(self.next_block.saturating_add(MAX_BLOCK_RANGE - 1));
+            for log in self.get_logs(self.next_block, to_block).await? {
+                if log.removed {
+                    continue;
+                }
+                match events::decode_log(&log.topics, &log.data) {
+                    Ok(decoded) => {
+                        for invalid in decoded.invalid_transfers {
+                            tracing::warn!(
+                                "Skipping the transfer {} of {} of the \
+                                 Ethereum bridge event {}: {}",
+                                invalid.index,
+                                invalid.asset,
+                                decoded.event.hash(),
+                                invalid.error
+                            );
+                        }
+                        // the receiver is only dropped on shut down
+                        let _ = self.sender.send(decoded.event);
+                    }
+                    Err(err) => {
+                        tracing::warn!(
+                            "Skipping an invalid Ethereum bridge event: {}",
+                            err
+                        );
+                    }
+                }
+            }
+            self.next_block = to_block + 1;
+        }
+        Ok(())
+    }
+
+    /// Get the number of the latest Ethereum block
+    async fn block_number(&self) -> Result<u64, Error> {
+        let result =
+            self.call("eth_blockNumber", serde_json::json!([])).await?;
+        result
+            .as_str()
+            .and_then(|quantity| quantity.strip_prefix("0x"))
+            .and_then(|hex| u64::from_str_radix(hex, 16).ok())
+            .ok_or_else(|| {
+                Error::InvalidResponse(format!(
+                    "Invalid block number {}",
+                    result
+                ))
+            })
+    }
+
+    /// Get the logs of the bridge smart contract's events in the given
+    /// inclusive range of Ethereum blocks
+    async fn get_logs(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<Log>, Error> {
+        let topics = [
+            events::TRANSFER_TO_NAMADA_SIGNATURE,
+            events::VALIDATOR_SET_UPDATE_SIGNATURE,
+        ]
+        .map(|signature| {
+            format!("0x{}", HEXLOWER.encode(&events::event_topic(signature)))
+        });
+        let filter = serde_json::json!([{
+            "address": self.bridge_contract.to_string(),
+            "fromBlock": format!("0x{:x}", from_block),
+            "toBlock": format!("0x{:x}", to_block),
+            "topics": [topics],
+        }]);
+        let result = self.call("eth_getLogs", filter).await?;
+        serde_json::from_value(result)
+            .map_err(|err| Error::InvalidResponse(err.to_string()))
+    }
+
+    /// Call a JSON-RPC method of the Ethereum node
+    async fn call(&self, method: &str, params: Value) -> Result<Value, Error> {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let response = self
+            .client
+            .post(&self.rpc_url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(request.to_string())
+            .send()
+            .await
+            .map_err(Error::Request)?
+            .text()
+            .await
+            .map_err(Error::Request)?;
+        let mut response: Value = serde_json::from_str(&response)
+            .map_err(|err| Error::InvalidResponse(err.to_string()))?;
+        if let Some(err) = response.get("error") {
+            return Err(Error::Rpc(err.clone()));
+        }
+        response
+            .get_mut("result")
+            .map(Value::take)
+            .ok_or_else(|| Error::InvalidResponse("Missing result".into()))
+    }
+}
+
+/// Receives the Ethereum events relayed by an oracle and queues them until
+/// they have been voted on
+#[derive(Debug)]
+pub struct EthereumReceiver {
+    channel: UnboundedReceiver<EthereumEvent>,
+    queue: BTreeSet<EthereumEvent>,
+}
+
+impl EthereumReceiver {
+    /// Create a new receiver of the events relayed through the given channel
+    pub fn new(channel: UnboundedReceiver<EthereumEvent>) -> Self {
+        Self {
+            channel,
+            queue: BTreeSet::new(),
+        }
+    }
+
+    /// Move the events relayed since the last call into the queue. Returns
+    /// the events that were not queued yet.
+    pub fn fill_queue(&mut self) -> Vec<EthereumEvent> {
+        let mut new_events = vec![];
+        while let Ok(event) = self.channel.try_recv() {
+            if self.queue.insert(event.clone()) {
+                new_events.push(event);
+            }
+        }
+        new_events
+    }
+
+    /// Get the queued events, in ascending order
+    pub fn get_events(&self) -> Vec<EthereumEvent> {
+        self.queue.iter().cloned().collect()
+    }
+
+    /// Only keep the queued events that satisfy the given predicate
+    pub fn retain(&mut self, f: impl FnMut(&EthereumEvent) -> bool) {
+        self.queue.retain(f);
+    }
+}
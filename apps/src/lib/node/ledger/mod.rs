@@ -1,5 +1,6 @@
 mod abortable;
 mod broadcaster;
+pub mod ethereum_oracle;
 mod shell;
 mod shims;
 pub mod storage;
@@ -14,7 +15,9 @@ use std::thread;
 use byte_unit::Byte;
 use futures::future::TryFutureExt;
 use namada::ledger::governance::storage as gov_storage;
-use namada::types::storage::Key;
+use namada::ledger::storage::PruningMode;
+use namada::types::address::Address;
+use namada::types::storage::{BlockHeight, Key};
 use once_cell::unsync::Lazy;
 use sysinfo::{RefreshKind, System, SystemExt};
 use tokio::task;
@@ -23,14 +26,16 @@ use tower::ServiceBuilder;
 use self::abortable::AbortableSpawner;
 use self::shims::abcipp_shim::AbciService;
 use crate::config::utils::num_of_threads;
-use crate::config::TendermintMode;
+use crate::config::{DbBackend, TendermintMode};
 use crate::facade::tendermint_proto::abci::CheckTxType;
 use crate::facade::tower_abci::{response, split, Server};
 use crate::node::ledger::broadcaster::Broadcaster;
 use crate::node::ledger::config::genesis;
+use crate::node::ledger::ethereum_oracle::{EthereumOracle, EthereumReceiver};
 use crate::node::ledger::shell::{Error, MempoolTxType, Shell};
 use crate::node::ledger::shims::abcipp_shim::AbcippShim;
 use crate::node::ledger::shims::abcipp_shim_types::shim::{Request, Response};
+use crate::node::ledger::storage::LedgerDB;
 use crate::{config, wasm_loader};
 
 /// Env. var to set a number of Tokio RT worker threads
@@ -55,7 +60,7 @@ const ENV_VAR_RAYON_THREADS: &str = "ANOMA_RAYON_THREADS";
 //         Poll::Ready(Ok(()))
 //     }
 //```
-impl Shell {
+impl<D: LedgerDB> Shell<D> {
     fn load_proposals(&mut self) {
         let proposals_key = gov_storage::get_commiting_proposals_prefix(
             self.storage.last_epoch.0,
@@ -140,16 +145,16 @@ impl Shell {
                 Ok(Response::CheckTx(self.mempool_validate(&tx.tx, r#type)))
             }
             Request::ListSnapshots(_) => {
-                Ok(Response::ListSnapshots(Default::default()))
+                Ok(Response::ListSnapshots(self.list_snapshots()))
             }
-            Request::OfferSnapshot(_) => {
-                Ok(Response::OfferSnapshot(Default::default()))
+            Request::OfferSnapshot(req) => {
+                Ok(Response::OfferSnapshot(self.offer_snapshot(req)))
             }
-            Request::LoadSnapshotChunk(_) => {
-                Ok(Response::LoadSnapshotChunk(Default::default()))
+            Request::LoadSnapshotChunk(req) => {
+                Ok(Response::LoadSnapshotChunk(self.load_snapshot_chunk(req)))
             }
-            Request::ApplySnapshotChunk(_) => {
-                Ok(Response::ApplySnapshotChunk(Default::default()))
+            Request::ApplySnapshotChunk(req) => {
+                Ok(Response::ApplySnapshotChunk(self.apply_snapshot_chunk(req)))
             }
         }
     }
@@ -198,6 +203,24 @@ pub fn reset(config: config::Ledger) -> Result<(), shell::Error> {
     shell::reset(config)
 }
 
+/// Rolls back the tendermint_node state and the DB by one block height
+pub fn rollback(config: config::Ledger) -> Result<(), shell::Error> {
+    shell::rollback(config)
+}
+
+/// Exports the state at the given block height, or at the last committed
+/// block height, into a file from which a new chain can be started
+pub fn dump_state(
+    config: config::Ledger,
+    height: Option<u64>,
+) -> Result<(), shell::Error> {
+    #[cfg(not(feature = "dev"))]
+    let genesis = genesis::genesis(&config.shell.base_dir, &config.chain_id);
+    #[cfg(feature = "dev")]
+    let genesis = genesis::genesis();
+    shell::dump_state(config, genesis.native_token, height.map(BlockHeight))
+}
+
 /// Runs and monitors a few concurrent tasks.
 ///
 /// This includes:
@@ -205,6 +228,8 @@ pub fn reset(config: config::Ledger) -> Result<(), shell::Error> {
 ///   - A shell which contains an ABCI server, for talking to the Tendermint
 ///     node.
 ///   - A [`Broadcaster`], for the ledger to submit txs to Tendermint's mempool.
+///   - An [`EthereumOracle`], for a validator to relay the events of the
+///     Ethereum bridge.
 ///
 /// All must be alive for correct functioning.
 async fn run_aux(config: config::Ledger, wasm_dir: PathBuf) {
@@ -217,23 +242,24 @@ async fn run_aux(config: config::Ledger, wasm_dir: PathBuf) {
     // Start Tendermint node
     let tendermint_node = start_tendermint(&mut spawner, &config);
 
-    // Start ABCI server and broadcaster (the latter only if we are a validator
-    // node)
-    let (abci, broadcaster, shell_handler) = start_abci_broadcaster_shell(
-        &mut spawner,
-        wasm_dir,
-        setup_data,
-        config,
-    );
+    // Start ABCI server, broadcaster and Ethereum oracle (the latter two only
+    // if we are a validator node)
+    let (abci, broadcaster, eth_oracle, shell_handler) =
+        start_abci_broadcaster_shell(
+            &mut spawner,
+            wasm_dir,
+            setup_data,
+            config,
+        );
 
     // Wait for interrupt signal or abort message
     let aborted = spawner.wait_for_abort().await.child_terminated();
 
     // Wait for all managed tasks to finish.
-    let res = tokio::try_join!(tendermint_node, abci, broadcaster);
+    let res = tokio::try_join!(tendermint_node, abci, broadcaster, eth_oracle);
 
     match res {
-        Ok((tendermint_res, abci_res, _)) => {
+        Ok((tendermint_res, abci_res, _, _)) => {
             // we ignore errors on user-initiated shutdown
             if aborted {
                 if let Err(err) = tendermint_res {
@@ -274,6 +300,18 @@ async fn run_aux_setup(
     config: &config::Ledger,
     wasm_dir: &PathBuf,
 ) -> RunAuxSetup {
+    // The BTree DB keeps the whole state and its versions in memory, which is
+    // only bounded when the state of the older heights is pruned
+    if config.shell.db_backend == DbBackend::BTree
+        && !matches!(config.shell.pruning, PruningMode::KeepRecent { .. })
+    {
+        panic!(
+            "The `b_tree` DB backend requires the `keep_recent` pruning mode, \
+             found {:?}",
+            config.shell.pruning
+        );
+    }
+
     // Prefetch needed wasm artifacts
     wasm_loader::pre_fetch_wasm(wasm_dir).await;
 
@@ -362,10 +400,11 @@ async fn run_aux_setup(
     }
 }
 
-/// Launches two tasks into the asynchronous runtime:
+/// Launches three tasks into the asynchronous runtime:
 ///
 ///   1. An ABCI server.
 ///   2. A service for broadcasting transactions via an HTTP client.
+///   3. An oracle relaying the events of the Ethereum bridge.
 ///
 /// Lastly, this function executes an ABCI shell on a new OS thread.
 fn start_abci_broadcaster_shell(
@@ -376,6 +415,7 @@ fn start_abci_broadcaster_shell(
 ) -> (
     task::JoinHandle<shell::Result<()>>,
     task::JoinHandle<()>,
+    task::JoinHandle<()>,
     thread::JoinHandle<()>,
 ) {
     let rpc_address = config.tendermint.rpc_address.to_string();
@@ -417,26 +457,81 @@ fn start_abci_broadcaster_shell(
     };
 
     // Setup DB cache, it must outlive the DB instance that's in the shell
-    let db_cache =
+    let db_cache = (config.shell.db_backend == DbBackend::RocksDb).then(|| {
         rocksdb::Cache::new_lru_cache(db_block_cache_size_bytes as usize)
-            .unwrap();
+            .unwrap()
+    });
 
     // Construct our ABCI application.
-    let tendermint_mode = config.tendermint.tendermint_mode.clone();
     let ledger_address = config.shell.ledger_address;
     #[cfg(not(feature = "dev"))]
     let genesis = genesis::genesis(&config.shell.base_dir, &config.chain_id);
     #[cfg(feature = "dev")]
     let genesis = genesis::genesis();
-    let (shell, abci_service) = AbcippShim::new(
-        config,
-        wasm_dir,
-        broadcaster_sender,
-        &db_cache,
-        vp_wasm_compilation_cache,
-        tx_wasm_compilation_cache,
-        genesis.native_token,
-    );
+
+    // Start the Ethereum oracle, if this validator watches an Ethereum node
+    let (eth_oracle, eth_receiver) = match &config.shell.ethereum_bridge {
+        Some(eth_config)
+            if matches!(
+                config.tendermint.tendermint_mode,
+                TendermintMode::Validator
+            ) =>
+        {
+            let (mut eth_oracle, eth_receiver) =
+                EthereumOracle::new(eth_config)
+                    .expect("Invalid address of the Ethereum bridge contract");
+            let (oracle_abort_send, oracle_abort_recv) =
+                tokio::sync::oneshot::channel::<()>();
+            let eth_oracle = spawner
+                .spawn_abortable("Ethereum oracle", move |aborter| async move {
+                    eth_oracle.run(oracle_abort_recv).await;
+                    tracing::info!("Ethereum oracle is no longer running.");
+
+                    drop(aborter);
+                })
+                .with_cleanup(async move {
+                    let _ = oracle_abort_send.send(());
+                });
+            (eth_oracle, eth_receiver)
+        }
+        _ => {
+            // Without an oracle, no Ethereum events are relayed
+            let (_, receiver) = tokio::sync::mpsc::unbounded_channel();
+            (spawn_dummy_task(()), EthereumReceiver::new(receiver))
+        }
+    };
+    let (abci_service, shell_handler) = match config.shell.db_backend {
+        DbBackend::RocksDb => start_shell::<storage::PersistentDB>(
+            config,
+            wasm_dir,
+            broadcaster_sender,
+            eth_receiver,
+            db_cache.as_ref(),
+            vp_wasm_compilation_cache,
+            tx_wasm_compilation_cache,
+            genesis.native_token,
+        ),
+        DbBackend::BTree => start_shell::<storage::PersistentBTreeDB>(
+            config,
+            wasm_dir,
+            broadcaster_sender,
+            eth_receiver,
+            None,
+            vp_wasm_compilation_cache,
+            tx_wasm_compilation_cache,
+            genesis.native_token,
+        ),
+        DbBackend::InMemory => start_shell::<storage::InMemoryDB>(
+            config,
+            wasm_dir,
+            broadcaster_sender,
+            eth_receiver,
+            None,
+            vp_wasm_compilation_cache,
+            tx_wasm_compilation_cache,
+            genesis.native_token,
+        ),
+    };
 
     // Channel for signalling shut down to ABCI server
     let (abci_abort_send, abci_abort_recv) = tokio::sync::oneshot::channel();
@@ -454,6 +549,33 @@ fn start_abci_broadcaster_shell(
             let _ = abci_abort_send.send(());
         });
 
+    (abci, broadcaster, eth_oracle, shell_handler)
+}
+
+/// Construct the shell with the DB of the given backend and start it in a new
+/// OS thread. Returns the ABCI service that passes messages to the shell.
+fn start_shell<D: LedgerDB>(
+    config: config::Ledger,
+    wasm_dir: PathBuf,
+    broadcaster_sender: tokio::sync::mpsc::UnboundedSender<Vec<u8>>,
+    eth_receiver: EthereumReceiver,
+    db_cache: Option<&D::Cache>,
+    vp_wasm_compilation_cache: u64,
+    tx_wasm_compilation_cache: u64,
+    native_token: Address,
+) -> (AbciService, thread::JoinHandle<()>) {
+    let tendermint_mode = config.tendermint.tendermint_mode.clone();
+    let (shell, abci_service) = AbcippShim::<D>::new(
+        config,
+        wasm_dir,
+        broadcaster_sender,
+        eth_receiver,
+        db_cache,
+        vp_wasm_compilation_cache,
+        tx_wasm_compilation_cache,
+        native_token,
+    );
+
     // Start the shell in a new OS thread
     let thread_builder = thread::Builder::new().name("ledger-shell".into());
     let shell_handler = thread_builder
@@ -471,7 +593,7 @@ fn start_abci_broadcaster_shell(
         })
         .expect("Must be able to start a thread for the shell");
 
-    (abci, broadcaster, shell_handler)
+    (abci_service, shell_handler)
 }
 
 /// Runs the an asynchronous ABCI server with four sub-components for consensus,
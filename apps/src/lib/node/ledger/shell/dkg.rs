@@ -0,0 +1,371 @@
+//! The keys used to encrypt and decrypt the wrapped txs of each epoch.
+//!
+//! Every validator derives its secret share of an epoch's keys from its DKG
+//! session keypair. While the validator set of an epoch is being settled, its
+//! active validators submit their encryption key shares for the epoch in
+//! protocol txs. When the epoch begins, the validators whose shares were
+//! accepted become the participants of the epoch's DKG session and the sum of
+//! their shares becomes the epoch's encryption key. Once the epoch is over,
+//! the participants reveal their decryption key shares and the wrapper txs of
+//! the epoch can be decrypted as soon as all of the shares are known.
+//!
+//! The shares must be revealed within [`DKG_REVEAL_EPOCHS`] epochs after the
+//! end of the epoch. If any of the participants fails to do so, the wrapper
+//! txs of the epoch can never be decrypted and they are included in blocks as
+//! undecryptable, so that they don't block the txs queued after them.
+//!
+//! Epochs without any participants fall back to the default keys.
+//!
+//! This is not a threshold scheme: every participant's share is needed to
+//! decrypt and a single withheld share makes all the wrapper txs of an epoch
+//! undecryptable. The txs of an epoch are also only decrypted after the epoch
+//! is over. Running a ferveo DKG with the session keys of
+//! [`namada::types::key::dkg_session_keys`] and aggregating the threshold
+//! decryption shares in [`Shell::prepare_proposal`] is out of the scope of
+//! this module.
+
+use namada::types::key::dkg_session_keys::{
+    dkg_decryption_share_key, dkg_encryption_share_key, dkg_participants_key,
+    dkg_session_pk_key,
+};
+use namada::types::storage::Epoch;
+use namada::types::transaction::protocol::{ProtocolTx, ProtocolTxType};
+use namada::types::transaction::{
+    DecryptionKey, EncryptionKey, EncryptionKeyShare, EpochKeyShare,
+};
+
+use super::*;
+
+/// The number of epochs after the end of an epoch in which the participants of
+/// its DKG session can reveal their decryption key shares
+pub const DKG_REVEAL_EPOCHS: u64 = 1;
+
+/// The state of the key to decrypt the wrapper txs of an epoch
+#[derive(Clone, Debug)]
+pub enum EpochDecryption {
+    /// All the participants have revealed their decryption key shares
+    Key(DecryptionKey),
+    /// Some of the decryption key shares are yet to be revealed
+    Pending,
+    /// Some of the participants failed to reveal their decryption key shares
+    /// in time, so the wrapper txs of the epoch cannot be decrypted
+    TimedOut,
+}
+
+impl<D, H> Shell<D, H>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    /// Get the key that the wrapper txs of the given epoch must be encrypted
+    /// with
+    pub fn epoch_encryption_key(&self, epoch: Epoch) -> EncryptionKey {
+        self.read_storage_key(&dkg_session_pk_key(epoch))
+            .unwrap_or_default()
+    }
+
+    /// Get the key to decrypt the wrapper txs of the given epoch, if all the
+    /// participants of its DKG session have revealed their decryption key
+    /// shares
+    pub fn epoch_decryption(&self, epoch: Epoch) -> EpochDecryption {
+        let participants = self.dkg_participants(epoch);
+        if participants.is_empty() {
+            return EpochDecryption::Key(DecryptionKey::default());
+        }
+        let shares = participants
+            .iter()
+            .map(|validator| {
+                self.read_storage_key::<DecryptionKey>(
+                    &dkg_decryption_share_key(validator, epoch),
+                )
+            })
+            .collect::<Option<Vec<_>>>();
+        match shares {
+            Some(shares) => {
+                EpochDecryption::Key(DecryptionKey::aggregate(&shares))
+            }
+            None if self.is_dkg_reveal_over(epoch) => EpochDecryption::TimedOut,
+            None => EpochDecryption::Pending,
+        }
+    }
+
+    /// Check if the period in which the participants of the DKG session of
+    /// the given epoch can reveal their decryption key shares is over
+    fn is_dkg_reveal_over(&self, epoch: Epoch) -> bool {
+        let (current_epoch, _) = self.storage.get_current_epoch();
+        current_epoch.0 > epoch.0 + DKG_REVEAL_EPOCHS
+    }
+
+    /// Check if wrapper txs encrypted for the given epoch can be included in
+    /// a block. The epoch must have started and, unless it fell back to the
+    /// default keys, it must not be over yet, as its decryption key shares
+    /// may already have been revealed.
+    pub(super) fn accepts_wrappers_of_epoch(&self, epoch: Epoch) -> bool {
+        let (current_epoch, _) = self.storage.get_current_epoch();
+        epoch == current_epoch
+            || (epoch < current_epoch
+                && self.dkg_participants(epoch).is_empty())
+    }
+
+    /// Get the participants of the DKG session of the given epoch
+    pub(super) fn dkg_participants(&self, epoch: Epoch) -> Vec<Address> {
+        self.read_storage_key(&dkg_participants_key(epoch))
+            .unwrap_or_default()
+    }
+
+    /// Start the DKG session of the current epoch from the encryption key
+    /// shares submitted by its active validators. Must be called when a new
+    /// epoch begins.
+    pub(super) fn start_dkg_session(&mut self) {
+        let (epoch, _) = self.storage.get_current_epoch();
+        let (participants, shares): (Vec<Address>, Vec<EncryptionKey>) = self
+            .storage
+            .read_validator_set()
+            .get(epoch)
+            .expect("Validators for the current epoch should be known")
+            .active
+            .iter()
+            .filter_map(|validator| {
+                self.read_storage_key::<EncryptionKeyShare>(
+                    &dkg_encryption_share_key(&validator.address, epoch),
+                )
+                .map(|share| (validator.address.clone(), share.key))
+            })
+            .unzip();
+        if participants.is_empty() {
+            return;
+        }
+        tracing::info!(
+            "Starting the DKG session of epoch {} with {} participants",
+            epoch,
+            participants.len()
+        );
+        let encryption_key = EncryptionKey::aggregate(&shares);
+        self.storage
+            .write(
+                &dkg_participants_key(epoch),
+                participants
+                    .try_to_vec()
+                    .expect("Serializing the participants should not fail"),
+            )
+            .expect("Writing the DKG participants should not fail");
+        self.storage
+            .write(
+                &dkg_session_pk_key(epoch),
+                encryption_key
+                    .try_to_vec()
+                    .expect("Serializing the encryption key should not fail"),
+            )
+            .expect("Writing the epoch encryption key should not fail");
+    }
+
+    /// Broadcast this validator's encryption key share for the next epoch
+    /// and, if it participated in the DKG session of the last epoch, its
+    /// decryption key share for that epoch. Must be called when a new epoch
+    /// begins.
+    pub(super) fn broadcast_dkg_key_shares(&self) {
+        let (data, broadcast_sender) = match &self.mode {
+            ShellMode::Validator {
+                data,
+                broadcast_sender,
+                ..
+            } => (data, broadcast_sender),
+            _ => return,
+        };
+        let dkg_keypair = match &data.keys.dkg_keypair {
+            Some(dkg_keypair) => dkg_keypair,
+            None => return,
+        };
+        let (epoch, _) = self.storage.get_current_epoch();
+        let next_epoch = epoch.next();
+        let mut txs = vec![ProtocolTxType::EncryptionKeyShare(
+            next_epoch,
+            EpochKeyShare::derive(dkg_keypair, next_epoch)
+                .encryption_share(&data.address, next_epoch),
+        )];
+        if epoch.0 > 0 {
+            let last_epoch = epoch.prev();
+            if self.dkg_participants(last_epoch).contains(&data.address) {
+                txs.push(ProtocolTxType::DecryptionKeyShare(
+                    last_epoch,
+                    EpochKeyShare::derive(dkg_keypair, last_epoch)
+                        .decryption_share(),
+                ));
+            }
+        }
+        let protocol_keypair = &data.keys.protocol_keypair;
+        for tx in txs {
+            let tx = tx.sign(
+                &protocol_keypair.ref_to(),
+                protocol_keypair,
+                self.chain_id.clone(),
+            );
+            if let Err(err) = broadcast_sender.send(tx.to_bytes()) {
+                tracing::error!("Failed to broadcast a DKG key share: {}", err);
+            }
+        }
+    }
+
+    /// Check that a protocol tx carries a DKG key share that can be applied
+    /// in the current epoch. Returns the address of the validator that owns
+    /// the share.
+    pub(super) fn validate_dkg_key_share(
+        &self,
+        tx: &ProtocolTx,
+    ) -> std::result::Result<Address, String> {
+        let (current_epoch, _) = self.storage.get_current_epoch();
+        match &tx.tx {
+            ProtocolTxType::EncryptionKeyShare(epoch, share) => {
+                if *epoch <= current_epoch {
+                    return Err(format!(
+                        "The encryption key shares of epoch {} can no longer \
+                         be submitted",
+                        epoch
+                    ));
+                }
+                let validator = self
+                    .storage
+                    .read_validator_set()
+                    .get(*epoch)
+                    .and_then(|validators| {
+                        validators
+                            .active
+                            .iter()
+                            .find(|validator| {
+                                self.is_protocol_pk_of(
+                                    &validator.address,
+                                    &tx.pk,
+                                )
+                            })
+                            .map(|validator| validator.address.clone())
+                    })
+                    .ok_or_else(|| {
+                        format!(
+                            "The signer is not an active validator of epoch {}",
+                            epoch
+                        )
+                    })?;
+                let key = dkg_encryption_share_key(&validator, *epoch);
+                if self.has_dkg_key_share(&key) {
+                    return Err(format!(
+                        "The encryption key share of {} for epoch {} has \
+                         already been submitted",
+                        validator, epoch
+                    ));
+                }
+                if !share.verify(&validator, *epoch) {
+                    return Err(format!(
+                        "The encryption key share of {} for epoch {} carries \
+                         an invalid proof",
+                        validator, epoch
+                    ));
+                }
+                Ok(validator)
+            }
+            ProtocolTxType::DecryptionKeyShare(epoch, share) => {
+                if *epoch >= current_epoch {
+                    return Err(format!(
+                        "The decryption key shares of epoch {} cannot be \
+                         revealed before the epoch is over",
+                        epoch
+                    ));
+                }
+                if self.is_dkg_reveal_over(*epoch) {
+                    return Err(format!(
+                        "The decryption key shares of epoch {} can no longer \
+                         be revealed",
+                        epoch
+                    ));
+                }
+                let validator = self
+                    .dkg_participants(*epoch)
+                    .into_iter()
+                    .find(|validator| self.is_protocol_pk_of(validator, &tx.pk))
+                    .ok_or_else(|| {
+                        format!(
+                            "The signer did not participate in the DKG \
+                             session of epoch {}",
+                            epoch
+                        )
+                    })?;
+                let key = dkg_decryption_share_key(&validator, *epoch);
+                if self.has_dkg_key_share(&key) {
+                    return Err(format!(
+                        "The decryption key share of {} for epoch {} has \
+                         already been revealed",
+                        validator, epoch
+                    ));
+                }
+                let encryption_share = self
+                    .read_storage_key::<EncryptionKeyShare>(
+                        &dkg_encryption_share_key(&validator, *epoch),
+                    )
+                    .ok_or_else(|| {
+                        format!(
+                            "The encryption key share of {} for epoch {} is \
+                             missing",
+                            validator, epoch
+                        )
+                    })?;
+                if !encryption_share.matches(share) {
+                    return Err(format!(
+                        "The decryption key share of {} for epoch {} does not \
+                         match its encryption key share",
+                        validator, epoch
+                    ));
+                }
+                Ok(validator)
+            }
+            _ => Err("The protocol tx doesn't carry a DKG key share".into()),
+        }
+    }
+
+    /// Write the DKG key share carried by a protocol tx, that has been
+    /// validated with [`Shell::validate_dkg_key_share`], to the write log
+    pub(super) fn write_dkg_key_share(
+        &mut self,
+        validator: &Address,
+        tx: &ProtocolTx,
+    ) {
+        let (key, value) = match &tx.tx {
+            ProtocolTxType::EncryptionKeyShare(epoch, share) => (
+                dkg_encryption_share_key(validator, *epoch),
+                share.try_to_vec(),
+            ),
+            ProtocolTxType::DecryptionKeyShare(epoch, share) => (
+                dkg_decryption_share_key(validator, *epoch),
+                share.try_to_vec(),
+            ),
+            _ => return,
+        };
+        let value = value.expect("Serializing a DKG key share should not fail");
+        self.write_log
+            .write(&key, value)
+            .expect("Writing a DKG key share should not fail");
+    }
+
+    /// Check if a DKG key share has already been stored, either in a
+    /// committed block or earlier in the block that is being finalized
+    fn has_dkg_key_share(&self, key: &Key) -> bool {
+        if let Some(StorageModification::Write { .. }) =
+            self.write_log.read(key).0
+        {
+            return true;
+        }
+        self.storage
+            .has_key(key)
+            .expect("Storage read in the protocol must not fail")
+            .0
+    }
+
+    /// Check if the given public key is the protocol key of a validator
+    fn is_protocol_pk_of(
+        &self,
+        validator: &Address,
+        pk: &common::PublicKey,
+    ) -> bool {
+        self.read_storage_key::<common::PublicKey>(&protocol_pk_key(validator))
+            .as_ref()
+            == Some(pk)
+    }
+}
@@ -0,0 +1,394 @@
+//! The votes of the validators on the events of the Ethereum bridge.
+//!
+//! Validators vote on the Ethereum events relayed by their oracle in their
+//! vote extensions, which the next block proposer bundles in a protocol tx.
+//! Without vote extensions, every validator broadcasts its votes in a
+//! protocol tx instead. Each vote adds the bonded stake of the validator to
+//! the tally of the events it carries and an event is applied once more than
+//! 2/3 of the stake of the active validators voted for it.
+
+use namada::ledger::eth_bridge::storage::{
+    eth_msg_key, validator_set_update_key, wrapped_erc20_balance_key,
+    wrapped_erc20_supply_key,
+};
+use namada::proto::Signed;
+use namada::types::ethereum_events::{
+    EthEventsVext, EthMsg, EthereumEvent, SignedEthEventsVext,
+};
+use namada::types::transaction::protocol::ProtocolTxType;
+
+use super::*;
+#[cfg(feature = "abcipp")]
+use crate::node::ledger::shims::abcipp_shim_types::shim::TxBytes;
+
+impl<D, H> Shell<D, H>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    /// Receive the Ethereum events newly relayed by the oracle and stop
+    /// voting on the events that this validator has already voted on.
+    /// Without vote extensions, the votes on the newly relayed events are
+    /// broadcast in a protocol tx. Must be called at the end of every block.
+    pub(super) fn update_eth_events_queue(&mut self) {
+        let (address, _new_events) = match &mut self.mode {
+            ShellMode::Validator {
+                data, eth_receiver, ..
+            } => (data.address.clone(), eth_receiver.fill_queue()),
+            _ => return,
+        };
+        let voted: Vec<EthereumEvent> = self
+            .eth_events_queue()
+            .into_iter()
+            .filter(|event| {
+                self.read_eth_msg(event).map_or(false, |msg| {
+                    msg.seen || msg.seen_by.contains(&address)
+                })
+            })
+            .collect();
+        if let ShellMode::Validator { eth_receiver, .. } = &mut self.mode {
+            eth_receiver.retain(|event| !voted.contains(event));
+        }
+
+        // With vote extensions, the queued events are voted on in
+        // [`Shell::extend_vote`] instead
+        #[cfg(not(feature = "abcipp"))]
+        {
+            let (height, _) = self.storage.get_block_height();
+            let new_events: Vec<EthereumEvent> = _new_events
+                .into_iter()
+                .filter(|event| !voted.contains(event))
+                .collect();
+            if let (
+                Some(vext),
+                ShellMode::Validator {
+                    data,
+                    broadcast_sender,
+                    ..
+                },
+            ) = (self.sign_eth_events_vext(height, new_events), &self.mode)
+            {
+                let protocol_key = &data.keys.protocol_keypair;
+                let tx = ProtocolTxType::EthereumEvents(vec![vext]).sign(
+                    &protocol_key.ref_to(),
+                    protocol_key,
+                    self.chain_id.clone(),
+                );
+                if let Err(err) = broadcast_sender.send(tx.to_bytes()) {
+                    tracing::error!(
+                        "Failed to broadcast the votes on Ethereum events: {}",
+                        err
+                    );
+                }
+            }
+        }
+    }
+
+    /// Get the Ethereum events that this validator has yet to vote on
+    pub(super) fn eth_events_queue(&self) -> Vec<EthereumEvent> {
+        match &self.mode {
+            ShellMode::Validator { eth_receiver, .. } => {
+                eth_receiver.get_events()
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Sign the votes of this validator on the given Ethereum events, made
+    /// at the given block height. Returns `None` if there are no events or
+    /// if this node is not a validator.
+    pub(super) fn sign_eth_events_vext(
+        &self,
+        block_height: BlockHeight,
+        mut ethereum_events: Vec<EthereumEvent>,
+    ) -> Option<SignedEthEventsVext> {
+        let data = match &self.mode {
+            ShellMode::Validator { data, .. } => data,
+            _ => return None,
+        };
+        if ethereum_events.is_empty() {
+            return None;
+        }
+        ethereum_events.sort();
+        ethereum_events.dedup();
+        Some(Signed::new(
+            &data.keys.protocol_keypair,
+            EthEventsVext {
+                validator_addr: data.address.clone(),
+                block_height,
+                ethereum_events,
+            },
+        ))
+    }
+
+    /// Check that the votes of a validator on Ethereum events, made no later
+    /// than the given block height, can be tallied in the current state.
+    /// Returns the bonded stake of the validator.
+    pub(super) fn validate_eth_events_vext(
+        &self,
+        vext: &SignedEthEventsVext,
+        max_height: BlockHeight,
+    ) -> std::result::Result<u64, String> {
+        let EthEventsVext {
+            validator_addr,
+            block_height,
+            ethereum_events,
+        } = &vext.data;
+        if *block_height > max_height {
+            return Err(format!(
+                "The votes of {} were made for the future block height {}",
+                validator_addr, block_height
+            ));
+        }
+        if ethereum_events.is_empty() {
+            return Err(format!(
+                "The votes of {} carry no Ethereum events",
+                validator_addr
+            ));
+        }
+        if !ethereum_events.windows(2).all(|pair| pair[0] < pair[1]) {
+            return Err(format!(
+                "The Ethereum events voted on by {} are not sorted or contain \
+                 duplicates",
+                validator_addr
+            ));
+        }
+        let (current_epoch, _) = self.storage.get_current_epoch();
+        let stake = self
+            .storage
+            .read_validator_set()
+            .get(current_epoch)
+            .and_then(|validators| {
+                validators
+                    .active
+                    .iter()
+                    .find(|validator| &validator.address == validator_addr)
+                    .map(|validator| validator.bonded_stake)
+            })
+            .ok_or_else(|| {
+                format!("{} is not an active validator", validator_addr)
+            })?;
+        let protocol_pk = self
+            .read_storage_key::<common::PublicKey>(&protocol_pk_key(
+                validator_addr,
+            ))
+            .ok_or_else(|| {
+                format!("The protocol key of {} is unknown", validator_addr)
+            })?;
+        vext.verify(&protocol_pk).map_err(|err| {
+            format!(
+                "The votes of {} are not signed with its protocol key: {}",
+                validator_addr, err
+            )
+        })?;
+        for event in ethereum_events {
+            if let Some(msg) = self.read_eth_msg(event) {
+                if msg.seen {
+                    return Err(format!(
+                        "The Ethereum event {} has already been applied",
+                        event.hash()
+                    ));
+                }
+                if msg.seen_by.contains(validator_addr) {
+                    return Err(format!(
+                        "{} has already voted on the Ethereum event {}",
+                        validator_addr,
+                        event.hash()
+                    ));
+                }
+            }
+        }
+        Ok(stake)
+    }
+
+    /// Check that the votes of validators on Ethereum events, bundled in a
+    /// protocol tx, can all be tallied in the current state
+    pub(super) fn validate_eth_events_vexts(
+        &self,
+        vexts: &[SignedEthEventsVext],
+    ) -> std::result::Result<(), String> {
+        if vexts.is_empty() {
+            return Err("The protocol tx carries no votes".into());
+        }
+        let mut voters = HashSet::new();
+        for vext in vexts {
+            if !voters.insert(&vext.data.validator_addr) {
+                return Err(format!(
+                    "The protocol tx carries several votes of {}",
+                    vext.data.validator_addr
+                ));
+            }
+            self.validate_eth_events_vext(vext, self.storage.last_height)?;
+        }
+        Ok(())
+    }
+
+    /// Bundle the valid votes on Ethereum events from the given vote
+    /// extensions in a protocol tx signed by this validator. Returns `None`
+    /// if there are no valid votes or if this node is not a validator.
+    #[cfg(feature = "abcipp")]
+    pub(super) fn build_eth_events_tx(
+        &self,
+        vote_extensions: impl IntoIterator<Item = Vec<u8>>,
+    ) -> Option<TxBytes> {
+        let protocol_key = match &self.mode {
+            ShellMode::Validator { data, .. } => &data.keys.protocol_keypair,
+            _ => return None,
+        };
+        let mut voters = HashSet::new();
+        let vexts: Vec<SignedEthEventsVext> = vote_extensions
+            .into_iter()
+            .filter(|vote_extension| !vote_extension.is_empty())
+            .filter_map(|vote_extension| {
+                SignedEthEventsVext::try_from_slice(&vote_extension).ok()
+            })
+            .filter(|vext| {
+                self.validate_eth_events_vext(vext, self.storage.last_height)
+                    .is_ok()
+                    && voters.insert(vext.data.validator_addr.clone())
+            })
+            .collect();
+        if vexts.is_empty() {
+            return None;
+        }
+        Some(
+            ProtocolTxType::EthereumEvents(vexts)
+                .sign(
+                    &protocol_key.ref_to(),
+                    protocol_key,
+                    self.chain_id.clone(),
+                )
+                .to_bytes(),
+        )
+    }
+
+    /// Tally the votes of validators on Ethereum events, that have been
+    /// validated with [`Shell::validate_eth_events_vexts`], and apply the
+    /// events that have been voted for by more than 2/3 of the voting power.
+    /// The changes are written to the write log.
+    pub(super) fn apply_eth_events_vexts(
+        &mut self,
+        vexts: &[SignedEthEventsVext],
+    ) {
+        let (current_epoch, _) = self.storage.get_current_epoch();
+        let validators = self
+            .storage
+            .read_validator_set()
+            .get(current_epoch)
+            .expect("Validators for the current epoch should be known")
+            .active
+            .clone();
+        let total_stake: u128 = validators
+            .iter()
+            .map(|validator| u128::from(validator.bonded_stake))
+            .sum();
+        for vext in vexts {
+            let voter = &vext.data.validator_addr;
+            let stake = validators
+                .iter()
+                .find(|validator| &validator.address == voter)
+                .map(|validator| validator.bonded_stake)
+                .unwrap_or_default();
+            for event in &vext.data.ethereum_events {
+                let mut msg = self
+                    .read_eth_msg(event)
+                    .unwrap_or_else(|| EthMsg::new(event.clone()));
+                if msg.seen || !msg.seen_by.insert(voter.clone()) {
+                    continue;
+                }
+                msg.voting_power = msg.voting_power.saturating_add(stake);
+                if 3 * u128::from(msg.voting_power) > 2 * total_stake {
+                    msg.seen = true;
+                    self.apply_ethereum_event(event);
+                }
+                self.write_log
+                    .write(
+                        &eth_msg_key(&event.hash()),
+                        msg.try_to_vec()
+                            .expect("Serializing a tally should not fail"),
+                    )
+                    .expect("Writing a tally should not fail");
+            }
+        }
+    }
+
+    /// Apply an Ethereum event that has been voted for by more than 2/3 of
+    /// the voting power
+    fn apply_ethereum_event(&mut self, event: &EthereumEvent) {
+        tracing::info!("Applying the Ethereum event {}", event.hash());
+        match event {
+            EthereumEvent::TransfersToNamada { transfers, .. } => {
+                for transfer in transfers {
+                    let supply_key = wrapped_erc20_supply_key(&transfer.asset);
+                    let supply: token::Amount = self
+                        .read_storage_key_post(&supply_key)
+                        .unwrap_or_default();
+                    // A balance never exceeds the supply, so the transfer
+                    // can be minted if the supply doesn't overflow
+                    if supply.checked_add(&transfer.amount).is_none() {
+                        tracing::warn!(
+                            "Rejecting the transfer of {} of {} to {}, as the \
+                             supply of the wrapped token would overflow",
+                            transfer.amount,
+                            transfer.asset,
+                            transfer.receiver
+                        );
+                        continue;
+                    }
+                    // mint the wrapped tokens
+                    for key in [
+                        wrapped_erc20_balance_key(
+                            &transfer.asset,
+                            &transfer.receiver,
+                        ),
+                        supply_key,
+                    ] {
+                        let amount: token::Amount = self
+                            .read_storage_key_post(&key)
+                            .unwrap_or_default();
+                        let amount = amount + transfer.amount;
+                        self.write_log
+                            .write(
+                                &key,
+                                amount.try_to_vec().expect(
+                                    "Serializing an amount should not fail",
+                                ),
+                            )
+                            .expect("Writing a balance should not fail");
+                    }
+                }
+            }
+            EthereumEvent::ValidatorSetUpdate { .. } => {
+                self.write_log
+                    .write(
+                        &validator_set_update_key(),
+                        event
+                            .try_to_vec()
+                            .expect("Serializing an event should not fail"),
+                    )
+                    .expect("Writing a validator set update should not fail");
+            }
+        }
+    }
+
+    /// Get the tally of the votes on an Ethereum event, if it has been voted
+    /// on
+    fn read_eth_msg(&self, event: &EthereumEvent) -> Option<EthMsg> {
+        self.read_storage_key_post(&eth_msg_key(&event.hash()))
+    }
+
+    /// Read the value of a storage key, including the changes from the write
+    /// log
+    fn read_storage_key_post<T>(&self, key: &Key) -> Option<T>
+    where
+        T: Clone + BorshDeserialize,
+    {
+        match self.write_log.read(key).0 {
+            Some(StorageModification::Write { value }) => {
+                T::try_from_slice(value).ok()
+            }
+            Some(_) => None,
+            None => self.read_storage_key(key),
+        }
+    }
+}
@@ -1,12 +1,15 @@
 //! Implementation of the `FinalizeBlock` ABCI++ method for the Shell
 
+use namada::ledger::inflation::{self, RewardsController};
 use namada::ledger::pos::types::into_tm_voting_power;
-use namada::ledger::protocol;
-use namada::types::storage::{BlockHash, BlockResults, Header};
+use namada::ledger::{parameters, protocol, slash_fund, storage_api};
+use namada::types::storage::{BlockHash, BlockResults, Epoch, Header};
 
 use super::governance::execute_governance_proposals;
 use super::*;
-use crate::facade::tendermint_proto::abci::Misbehavior as Evidence;
+use crate::facade::tendermint_proto::abci::{
+    Misbehavior as Evidence, VoteInfo,
+};
 use crate::facade::tendermint_proto::crypto::PublicKey as TendermintPublicKey;
 
 impl<D, H> Shell<D, H>
@@ -34,6 +37,10 @@ where
     ///   3: Wasm runtime error
     ///   4: Invalid order of decrypted txs
     ///   5. More decrypted txs than expected
+    ///   6. Undecryptable tx
+    ///   7. Replayed tx
+    ///   8. Invalid chain ID
+    ///   9. Expired tx
     pub fn finalize_block(
         &mut self,
         req: shim::request::FinalizeBlock,
@@ -45,8 +52,25 @@ where
         // begin the next block and check if a new epoch began
         let (height, new_epoch) =
             self.update_state(req.header, req.hash, req.byzantine_validators);
+        let block_time = self
+            .storage
+            .header
+            .as_ref()
+            .expect("The block header must be set")
+            .time;
+        self.prune_expired_applied_txs(block_time);
+
+        // Record the rewards earned by the validators for the last block
+        self.log_block_rewards(height, &req.proposer_address, &req.votes);
+        // Jail the validators that missed too many blocks
+        self.track_liveness(height, &req.votes);
+        // The wrapper txs fees are paid to the block proposer
+        let fee_target = self.get_fee_target(&req.proposer_address);
 
         if new_epoch {
+            self.apply_inflation();
+            self.start_dkg_session();
+            self.broadcast_dkg_key_shares();
             let _proposals_result =
                 execute_governance_proposals(self, &mut response)?;
         }
@@ -99,6 +123,7 @@ where
                 continue;
             }
 
+            let tx_expiration = tx.expiration;
             let tx_type = if let Ok(tx_type) = process_tx(tx) {
                 tx_type
             } else {
@@ -128,13 +153,56 @@ where
             }
 
             let mut tx_event = match &tx_type {
-                TxType::Wrapper(_wrapper) => {
-                    self.storage.tx_queue.push(_wrapper.clone());
-                    Event::new_tx_event(&tx_type, height.0)
+                TxType::Wrapper(wrapper) => {
+                    let mut event = Event::new_tx_event(&tx_type, height.0);
+                    // A wrapper tx may have been included twice in this block
+                    let wrapper_hash = hash_tx(&processed_tx.tx);
+                    if self.is_tx_applied(&wrapper_hash)
+                        || self.is_tx_applied(&wrapper.tx_hash)
+                    {
+                        event["code"] = ErrorCodes::ReplayTx.into();
+                        event["info"] = "Tx rejected: replay attempt".into();
+                        event["gas_used"] = "0".into();
+                        response.events.push(event);
+                        continue;
+                    }
+                    let fee = self.accept_wrapper(
+                        wrapper,
+                        &wrapper_hash,
+                        tx_expiration,
+                        &fee_target,
+                    );
+                    match fee {
+                        Some(fee) => {
+                            self.storage.tx_queue.push(wrapper.clone());
+                            event["fee"] = fee.to_string();
+                            event
+                        }
+                        None => {
+                            event["code"] = ErrorCodes::InvalidTx.into();
+                            event["info"] = "Tx rejected: the fee payer has \
+                                             insufficient balance to pay the \
+                                             fee"
+                            .into();
+                            event["gas_used"] = "0".into();
+                            response.events.push(event);
+                            continue;
+                        }
+                    }
                 }
                 TxType::Decrypted(inner) => {
                     // We remove the corresponding wrapper tx from the queue
-                    self.storage.tx_queue.pop();
+                    let wrapper = self.storage.tx_queue.pop();
+                    // The expiration of the inner tx is only known once it's
+                    // decrypted
+                    if let (Some(wrapper), DecryptedTx::Decrypted(tx)) =
+                        (wrapper, inner)
+                    {
+                        if let Some(expiration) = tx.expiration {
+                            self.index_applied_tx(&wrapper.tx_hash, expiration);
+                            self.write_log.commit_tx();
+                        }
+                    }
                     let mut event = Event::new_tx_event(&tx_type, height.0);
                     if let DecryptedTx::Undecryptable(_) = inner {
                         event["log"] =
@@ -150,12 +218,21 @@ where
                     );
                     continue;
                 }
-                TxType::Protocol(_) => {
-                    tracing::error!(
-                        "Internal logic error: FinalizeBlock received a \
-                         TxType::Protocol transaction"
-                    );
-                    continue;
+                TxType::Protocol(protocol_tx) => {
+                    let mut event = Event::new_tx_event(&tx_type, height.0);
+                    // The protocol tx may conflict with an earlier one in
+                    // this block. Its changes are committed together with
+                    // the tx below.
+                    match self.apply_protocol_tx(protocol_tx) {
+                        Ok(()) => event,
+                        Err(err) => {
+                            event["code"] = ErrorCodes::InvalidTx.into();
+                            event["info"] = format!("Tx rejected: {}", err);
+                            event["gas_used"] = "0".into();
+                            response.events.push(event);
+                            continue;
+                        }
+                    }
                 }
             };
 
@@ -242,6 +319,7 @@ where
         if new_epoch {
             self.update_epoch(&mut response);
         }
+        self.update_eth_events_queue();
 
         let _ = self
             .gas_meter
@@ -288,6 +366,339 @@ where
         (height, new_epoch)
     }
 
+    /// Get the address to which the fees of the wrapper txs included in this
+    /// block are paid. This is the block proposer or if the proposer cannot
+    /// be found, the fees are collected in the slash fund.
+    fn get_fee_target(&self, proposer_address: &[u8]) -> Address {
+        if proposer_address.is_empty() {
+            return slash_fund::ADDRESS;
+        }
+        let proposer_raw_hash = tm_raw_hash_to_string(proposer_address);
+        self.storage
+            .read_validator_address_raw_hash(&proposer_raw_hash)
+            .unwrap_or_else(|| {
+                tracing::error!(
+                    "Cannot find the block proposer's address from raw hash \
+                     {}, the fees will be paid to the slash fund",
+                    proposer_raw_hash
+                );
+                slash_fund::ADDRESS
+            })
+    }
+
+    /// Charge the fee of a wrapper tx and record the hashes of the wrapper
+    /// and of its inner tx for replay protection. These changes are committed
+    /// in the write log right away, so that they are kept even if applying
+    /// the wrapper tx fails afterwards. Returns the amount that was charged,
+    /// or `None` if the fee payer cannot pay the fee, in which case the
+    /// wrapper tx must be rejected and nothing is written.
+    fn accept_wrapper(
+        &mut self,
+        wrapper: &WrapperTx,
+        wrapper_hash: &Hash,
+        expiration: Option<DateTimeUtc>,
+        fee_target: &Address,
+    ) -> Option<token::Amount> {
+        let fee = self.charge_fee(wrapper, fee_target)?;
+        self.record_applied_tx(wrapper_hash);
+        if let Some(expiration) = expiration {
+            self.index_applied_tx(wrapper_hash, expiration);
+        }
+        self.record_applied_tx(&wrapper.tx_hash);
+        self.write_log.commit_tx();
+        Some(fee)
+    }
+
+    /// Transfer the fee of a wrapper tx from the fee payer to the given
+    /// target. The transfer is written into the tx write log. Returns the
+    /// amount that was charged.
+    ///
+    /// The fee payer's balance has been checked in
+    /// [`Shell::process_proposal`], but it may have been spent by the
+    /// decrypted txs in this block. In that case nothing is charged and
+    /// `None` is returned.
+    fn charge_fee(
+        &mut self,
+        wrapper: &WrapperTx,
+        fee_target: &Address,
+    ) -> Option<token::Amount> {
+        let token = &wrapper.fee.token;
+        let fee_payer = wrapper.fee_payer();
+        let mut payer_balance = self.read_balance_post(token, &fee_payer);
+        let fee = wrapper.fee.amount;
+        if payer_balance < fee {
+            tracing::info!(
+                "The fee payer {} has insufficient balance {} to pay the fee \
+                 of {}",
+                fee_payer,
+                payer_balance,
+                fee
+            );
+            return None;
+        }
+        if fee == token::Amount::default() || &fee_payer == fee_target {
+            return Some(fee);
+        }
+        payer_balance.spend(&fee);
+        let mut target_balance = self.read_balance_post(token, fee_target);
+        target_balance.receive(&fee);
+        self.write_log
+            .write(
+                &token::balance_key(token, &fee_payer),
+                payer_balance.try_to_vec().unwrap(),
+            )
+            .expect("Writing the fee payer's balance shouldn't fail");
+        self.write_log
+            .write(
+                &token::balance_key(token, fee_target),
+                target_balance.try_to_vec().unwrap(),
+            )
+            .expect("Writing the fee target's balance shouldn't fail");
+        Some(fee)
+    }
+
+    /// Record the hash of a tx in the replay protection storage, so that it
+    /// cannot be applied again. The entry is written into the tx write log.
+    fn record_applied_tx(&mut self, hash: &Hash) {
+        self.write_log
+            .write(&replay_protection::get_tx_hash_key(hash), vec![])
+            .expect("Writing the replay protection entry shouldn't fail");
+    }
+
+    /// Index the hash of an applied tx by the tx's expiration, so that its
+    /// replay protection entry can be pruned once the tx has expired. The
+    /// entries of the txs without an expiration are never pruned. The index
+    /// entry is written into the tx write log.
+    fn index_applied_tx(&mut self, hash: &Hash, expiration: DateTimeUtc) {
+        self.write_log
+            .write(
+                &replay_protection::get_tx_expiration_key(hash, &expiration),
+                vec![],
+            )
+            .expect("Writing the replay protection entry shouldn't fail");
+    }
+
+    /// Remove the replay protection entries of the txs that expired before
+    /// the given block time, as these txs are rejected anyway
+    fn prune_expired_applied_txs(&mut self, block_time: DateTimeUtc) {
+        let prefix = replay_protection::get_tx_expiration_prefix();
+        let (iter, _gas) = self.storage.iter_prefix(&prefix);
+        // The index is ordered by the expiration
+        let expired: Vec<(Key, Key)> = iter
+            .map_while(|(key, _value, _gas)| {
+                let key = Key::parse(key).ok()?;
+                let (expiration, hash_key) =
+                    replay_protection::parse_tx_expiration_key(&key)?;
+                (expiration < block_time.0.timestamp())
+                    .then_some((key, hash_key))
+            })
+            .collect();
+        for (index_key, hash_key) in expired {
+            self.write_log
+                .delete(&index_key)
+                .expect("Deleting the replay protection entry shouldn't fail");
+            self.write_log
+                .delete(&hash_key)
+                .expect("Deleting the replay protection entry shouldn't fail");
+        }
+        self.write_log.commit_tx();
+    }
+
+    /// Read a token balance with the modifications from the write log
+    /// applied.
+    fn read_balance_post(
+        &self,
+        token: &Address,
+        owner: &Address,
+    ) -> token::Amount {
+        let key = token::balance_key(token, owner);
+        match self.write_log.read(&key).0 {
+            Some(StorageModification::Write { value }) => {
+                token::Amount::try_from_slice(value).unwrap_or_default()
+            }
+            Some(StorageModification::Delete) => token::Amount::default(),
+            Some(_) => token::Amount::default(),
+            None => self.get_balance(token, owner),
+        }
+    }
+
+    /// Record the fractions of the block rewards earned by the active
+    /// validators from the block proposer and the votes on the last
+    /// committed block.
+    fn log_block_rewards(
+        &mut self,
+        height: BlockHeight,
+        proposer_address: &[u8],
+        votes: &[VoteInfo],
+    ) {
+        // There are no votes on the genesis block
+        if votes.is_empty() || proposer_address.is_empty() {
+            return;
+        }
+        let epoch = match self.last_block_epoch(height) {
+            Some(epoch) => epoch,
+            None => return,
+        };
+        let proposer_raw_hash = tm_raw_hash_to_string(proposer_address);
+        let proposer = match self
+            .storage
+            .read_validator_address_raw_hash(&proposer_raw_hash)
+        {
+            Some(proposer) => proposer,
+            None => {
+                tracing::error!(
+                    "Cannot find the block proposer's address from raw hash {}",
+                    proposer_raw_hash
+                );
+                return;
+            }
+        };
+        let signers = self.last_block_signers(votes);
+        if let Err(err) =
+            self.storage.log_block_rewards(epoch, &proposer, &signers)
+        {
+            tracing::error!("Failed to log the block rewards: {}", err);
+        }
+    }
+
+    /// Record the active validators that didn't sign the last committed
+    /// block and jail the validators that missed too many blocks in their
+    /// liveness window.
+    fn track_liveness(&mut self, height: BlockHeight, votes: &[VoteInfo]) {
+        // There are no votes on the genesis block
+        if votes.is_empty() {
+            return;
+        }
+        let last_height = BlockHeight(height.0 - 1);
+        let epoch = match self.last_block_epoch(height) {
+            Some(epoch) => epoch,
+            None => return,
+        };
+        let signers = self.last_block_signers(votes);
+        let validators_to_jail =
+            self.storage.track_liveness(epoch, last_height.0, &signers);
+        if validators_to_jail.is_empty() {
+            return;
+        }
+        let pos_params = self.storage.read_pos_params();
+        let current_epoch = self.storage.block.epoch;
+        for validator in validators_to_jail {
+            tracing::info!(
+                "Jailing {} for missing too many blocks in epoch {}, block \
+                 height {}",
+                validator,
+                epoch,
+                last_height
+            );
+            if let Err(err) = self.storage.jail_validator(
+                &pos_params,
+                current_epoch,
+                epoch,
+                last_height.0,
+                &validator,
+            ) {
+                tracing::error!("Error in jailing: {}", err);
+            }
+        }
+    }
+
+    /// Get the epoch of the last committed block, on which the votes in the
+    /// block at the given height were made
+    fn last_block_epoch(&self, height: BlockHeight) -> Option<Epoch> {
+        let last_height = BlockHeight(height.0 - 1);
+        let epoch = self.storage.block.pred_epochs.get_epoch(last_height);
+        if epoch.is_none() {
+            tracing::error!(
+                "Couldn't find epoch for the last block height {}",
+                last_height
+            );
+        }
+        epoch
+    }
+
+    /// Get the addresses of the validators that signed the last committed
+    /// block
+    fn last_block_signers(&self, votes: &[VoteInfo]) -> HashSet<Address> {
+        votes
+            .iter()
+            .filter(|vote| vote.signed_last_block)
+            .filter_map(|vote| {
+                let validator = vote.validator.as_ref()?;
+                let raw_hash = tm_raw_hash_to_string(&validator.address);
+                self.storage.read_validator_address_raw_hash(&raw_hash)
+            })
+            .collect()
+    }
+
+    /// Run the PD controller to calculate the PoS inflation for the epoch
+    /// that has just ended, mint it into the rewards pools of the bonds and
+    /// update the staked ratio and inflation amount parameters.
+    fn apply_inflation(&mut self) {
+        let (params, _gas) = parameters::read(&self.storage)
+            .expect("Couldn't read protocol parameters");
+        let pos_params = self.storage.read_pos_params();
+        let staking_token = self.storage.native_token.clone();
+
+        // The tokens locked in PoS are the total bonded stake. The PoS
+        // account's balance also holds unbonded tokens that are yet to be
+        // withdrawn and the unclaimed rewards, which are not counted.
+        let current_epoch = self.storage.block.epoch;
+        let locked_tokens: token::Change = self
+            .storage
+            .read_total_deltas()
+            .get(current_epoch)
+            .unwrap_or_default();
+        let locked_tokens = token::Amount::from(
+            u64::try_from(locked_tokens)
+                .expect("Total bonded stake should be a positive value"),
+        );
+        let total_tokens = storage_api::token::read_total_supply(
+            &self.storage,
+            &staking_token,
+        )
+        .expect("Couldn't read the total supply of the staking token");
+
+        let inflation::ValsToUpdate {
+            locked_ratio,
+            inflation,
+        } = RewardsController {
+            locked_tokens,
+            total_tokens,
+            locked_ratio_target: pos_params.target_staked_ratio,
+            locked_ratio_last: params.staked_ratio,
+            max_reward_rate: pos_params.max_inflation_rate,
+            last_inflation_amount: token::Amount::from(
+                params.pos_inflation_amount,
+            ),
+            p_gain_nom: params.pos_gain_p,
+            d_gain_nom: params.pos_gain_d,
+            epochs_per_year: params.epochs_per_year,
+        }
+        .run();
+
+        // The rewards were earned in the epoch that has just ended
+        let minted = self.storage.distribute_rewards(
+            token::Amount::from(inflation),
+            current_epoch.prev(),
+        );
+        tracing::info!(
+            "Minted {} PoS inflation rewards, staked ratio is {}",
+            minted,
+            locked_ratio
+        );
+
+        parameters::update_staked_ratio_parameter(
+            &mut self.storage,
+            &locked_ratio,
+        )
+        .expect("Must be able to update the staked ratio parameter");
+        parameters::update_pos_inflation_amount_parameter(
+            &mut self.storage,
+            &inflation,
+        )
+        .expect("Must be able to update the PoS inflation amount parameter");
+    }
+
     /// If a new epoch begins, we update the response to include
     /// changes to the validator sets and consensus parameters
     fn update_epoch(&self, response: &mut shim::response::FinalizeBlock) {
@@ -330,6 +741,12 @@ where
 /// are covered by the e2e tests.
 #[cfg(test)]
 mod test_finalize_block {
+    use namada::ledger::eth_bridge::storage::{
+        wrapped_erc20_balance_key, wrapped_erc20_supply_key,
+    };
+    use namada::types::ethereum_events::{
+        EthAddress, EthereumEvent, TransferToNamada,
+    };
     use namada::types::storage::Epoch;
     use namada::types::transaction::{EncryptionKey, Fee};
 
@@ -353,6 +770,8 @@ mod test_finalize_block {
             let raw_tx = Tx::new(
                 "wasm_code".as_bytes().to_owned(),
                 Some(format!("transaction data: {}", i).as_bytes().to_owned()),
+                shell.chain_id.clone(),
+                None,
             );
             let wrapper = WrapperTx::new(
                 Fee {
@@ -365,7 +784,9 @@ mod test_finalize_block {
                 raw_tx.clone(),
                 Default::default(),
             );
-            let tx = wrapper.sign(&keypair).expect("Test failed");
+            let tx = wrapper
+                .sign(&keypair, shell.chain_id.clone(), None)
+                .expect("Test failed");
             if i > 1 {
                 processed_txs.push(ProcessedTx {
                     tx: tx.to_bytes(),
@@ -424,6 +845,8 @@ mod test_finalize_block {
         let raw_tx = Tx::new(
             "wasm_code".as_bytes().to_owned(),
             Some(String::from("transaction data").as_bytes().to_owned()),
+            shell.chain_id.clone(),
+            None,
         );
         let wrapper = WrapperTx::new(
             Fee {
@@ -542,6 +965,8 @@ mod test_finalize_block {
                         .as_bytes()
                         .to_owned(),
                 ),
+                shell.chain_id.clone(),
+                None,
             );
             let wrapper_tx = WrapperTx::new(
                 Fee {
@@ -573,6 +998,8 @@ mod test_finalize_block {
                         .as_bytes()
                         .to_owned(),
                 ),
+                shell.chain_id.clone(),
+                None,
             );
             let wrapper_tx = WrapperTx::new(
                 Fee {
@@ -585,7 +1012,9 @@ mod test_finalize_block {
                 raw_tx.clone(),
                 Default::default(),
             );
-            let wrapper = wrapper_tx.sign(&keypair).expect("Test failed");
+            let wrapper = wrapper_tx
+                .sign(&keypair, shell.chain_id.clone(), None)
+                .expect("Test failed");
             valid_txs.push(wrapper_tx);
             processed_txs.push(ProcessedTx {
                 tx: wrapper.to_bytes(),
@@ -642,4 +1071,418 @@ mod test_finalize_block {
         }
         assert_eq!(counter, 2);
     }
+
+    /// Test that the fee of an accepted wrapper tx is transferred from the
+    /// fee payer to the fee target and that the charged amount is reported
+    /// in the tx event
+    #[test]
+    fn test_wrapper_fee_is_charged() {
+        let (mut shell, _) = setup();
+        let keypair = gen_keypair();
+        let native_token = shell.storage.native_token.clone();
+        let raw_tx = Tx::new(
+            "wasm_code".as_bytes().to_owned(),
+            Some(String::from("transaction data").as_bytes().to_owned()),
+            shell.chain_id.clone(),
+            None,
+        );
+        let wrapper = WrapperTx::new(
+            Fee {
+                amount: token::Amount::whole(10),
+                token: native_token.clone(),
+            },
+            &keypair,
+            Epoch(0),
+            0.into(),
+            raw_tx,
+            Default::default(),
+        );
+        // give the fee payer enough balance to pay the fee
+        let fee_payer = wrapper.fee_payer();
+        shell
+            .storage
+            .write(
+                &token::balance_key(&native_token, &fee_payer),
+                token::Amount::whole(100).try_to_vec().unwrap(),
+            )
+            .expect("Test failed");
+        let tx = wrapper
+            .sign(&keypair, shell.chain_id.clone(), None)
+            .expect("Test failed");
+
+        // the block has no proposer, so the fee goes to the slash fund
+        let events = shell
+            .finalize_block(FinalizeBlock {
+                txs: vec![ProcessedTx {
+                    tx: tx.to_bytes(),
+                    result: TxResult {
+                        code: ErrorCodes::Ok.into(),
+                        info: "".into(),
+                    },
+                }],
+                ..Default::default()
+            })
+            .expect("Test failed");
+        assert_eq!(events.len(), 1);
+        let fee = events[0].attributes.get("fee").expect("Test failed");
+        assert_eq!(fee, &token::Amount::whole(10).to_string());
+
+        assert_eq!(
+            shell.read_balance_post(&native_token, &fee_payer),
+            token::Amount::whole(90)
+        );
+        assert_eq!(
+            shell.read_balance_post(&native_token, &slash_fund::ADDRESS),
+            token::Amount::whole(10)
+        );
+    }
+
+    /// Test that a wrapper tx whose fee payer spent its balance before the
+    /// wrapper is applied is rejected without being charged and that its
+    /// inner tx is not queued for decryption
+    #[test]
+    fn test_wrapper_with_insufficient_balance_is_rejected() {
+        let (mut shell, _) = setup();
+        let keypair = gen_keypair();
+        let native_token = shell.storage.native_token.clone();
+        let raw_tx = Tx::new(
+            "wasm_code".as_bytes().to_owned(),
+            Some(String::from("transaction data").as_bytes().to_owned()),
+            shell.chain_id.clone(),
+            None,
+        );
+        let wrapper = WrapperTx::new(
+            Fee {
+                amount: token::Amount::whole(10),
+                token: native_token.clone(),
+            },
+            &keypair,
+            Epoch(0),
+            0.into(),
+            raw_tx,
+            Default::default(),
+        );
+        // the fee payer only has a part of the fee left
+        let fee_payer = wrapper.fee_payer();
+        shell
+            .storage
+            .write(
+                &token::balance_key(&native_token, &fee_payer),
+                token::Amount::whole(5).try_to_vec().unwrap(),
+            )
+            .expect("Test failed");
+        let tx = wrapper
+            .sign(&keypair, shell.chain_id.clone(), None)
+            .expect("Test failed");
+
+        let events = shell
+            .finalize_block(FinalizeBlock {
+                txs: vec![ProcessedTx {
+                    tx: tx.to_bytes(),
+                    result: TxResult {
+                        code: ErrorCodes::Ok.into(),
+                        info: "".into(),
+                    },
+                }],
+                ..Default::default()
+            })
+            .expect("Test failed");
+        assert_eq!(events.len(), 1);
+        let code = events[0].attributes.get("code").expect("Test failed");
+        assert_eq!(code, &String::from(ErrorCodes::InvalidTx));
+        assert!(events[0].attributes.get("fee").is_none());
+        assert_eq!(shell.iter_tx_queue().count(), 0);
+
+        assert_eq!(
+            shell.read_balance_post(&native_token, &fee_payer),
+            token::Amount::whole(5)
+        );
+        assert_eq!(
+            shell.read_balance_post(&native_token, &slash_fund::ADDRESS),
+            token::Amount::default()
+        );
+        assert!(!shell.is_tx_applied(&hash_tx(&tx.to_bytes())));
+        assert!(!shell.is_tx_applied(&wrapper.tx_hash));
+    }
+
+    /// Test that a wrapper tx included twice is only accepted once and that
+    /// the hashes of the wrapper and of its inner tx are recorded for replay
+    /// protection
+    #[test]
+    fn test_replayed_wrapper_is_rejected() {
+        let (mut shell, _) = setup();
+        let keypair = gen_keypair();
+        let raw_tx = Tx::new(
+            "wasm_code".as_bytes().to_owned(),
+            Some(String::from("transaction data").as_bytes().to_owned()),
+            shell.chain_id.clone(),
+            None,
+        );
+        let wrapper = WrapperTx::new(
+            Fee {
+                amount: 0.into(),
+                token: shell.storage.native_token.clone(),
+            },
+            &keypair,
+            Epoch(0),
+            0.into(),
+            raw_tx,
+            Default::default(),
+        );
+        let inner_hash = wrapper.tx_hash.clone();
+        let tx = wrapper
+            .sign(&keypair, shell.chain_id.clone(), None)
+            .expect("Test failed")
+            .to_bytes();
+        let processed_tx = ProcessedTx {
+            tx: tx.clone(),
+            result: TxResult {
+                code: ErrorCodes::Ok.into(),
+                info: "".into(),
+            },
+        };
+
+        let events = shell
+            .finalize_block(FinalizeBlock {
+                txs: vec![processed_tx.clone(), processed_tx],
+                ..Default::default()
+            })
+            .expect("Test failed");
+        assert_eq!(events.len(), 2);
+        let code = events[0].attributes.get("code").expect("Test failed");
+        assert_eq!(code, &String::from(ErrorCodes::Ok));
+        let code = events[1].attributes.get("code").expect("Test failed");
+        assert_eq!(code, &String::from(ErrorCodes::ReplayTx));
+        assert_eq!(shell.iter_tx_queue().count(), 1);
+
+        assert!(shell.is_tx_applied(&hash_tx(&tx)));
+        assert!(shell.is_tx_applied(&inner_hash));
+    }
+
+    /// Test that when applying a wrapper tx fails and its changes are dropped
+    /// from the write log, the fee is still charged and the wrapper cannot be
+    /// replayed
+    #[test]
+    fn test_wrapper_fee_kept_when_apply_tx_fails() {
+        let (mut shell, _) = setup();
+        let keypair = gen_keypair();
+        let native_token = shell.storage.native_token.clone();
+        let raw_tx = Tx::new(
+            "wasm_code".as_bytes().to_owned(),
+            Some(String::from("transaction data").as_bytes().to_owned()),
+            shell.chain_id.clone(),
+            None,
+        );
+        let wrapper = WrapperTx::new(
+            Fee {
+                amount: token::Amount::whole(10),
+                token: native_token.clone(),
+            },
+            &keypair,
+            Epoch(0),
+            0.into(),
+            raw_tx,
+            Default::default(),
+        );
+        let fee_payer = wrapper.fee_payer();
+        shell
+            .storage
+            .write(
+                &token::balance_key(&native_token, &fee_payer),
+                token::Amount::whole(100).try_to_vec().unwrap(),
+            )
+            .expect("Test failed");
+        let tx = wrapper
+            .sign(&keypair, shell.chain_id.clone(), None)
+            .expect("Test failed");
+        let wrapper_hash = hash_tx(&tx.to_bytes());
+
+        let fee = shell.accept_wrapper(
+            &wrapper,
+            &wrapper_hash,
+            None,
+            &slash_fund::ADDRESS,
+        );
+        assert_eq!(fee, Some(token::Amount::whole(10)));
+
+        // Applying the tx fails, so its changes are dropped
+        shell
+            .write_log
+            .write(
+                &token::balance_key(&native_token, &fee_payer),
+                token::Amount::whole(1_000).try_to_vec().unwrap(),
+            )
+            .expect("Test failed");
+        let result = protocol::apply_tx(
+            TxType::Raw(tx),
+            0,
+            TxIndex::default(),
+            &mut shell.gas_meter,
+            &mut shell.write_log,
+            &shell.storage,
+            &mut shell.vp_wasm_cache,
+            &mut shell.tx_wasm_cache,
+        );
+        assert!(result.is_err());
+        shell.write_log.drop_tx();
+
+        assert_eq!(
+            shell.read_balance_post(&native_token, &fee_payer),
+            token::Amount::whole(90)
+        );
+        assert_eq!(
+            shell.read_balance_post(&native_token, &slash_fund::ADDRESS),
+            token::Amount::whole(10)
+        );
+        assert!(shell.is_tx_applied(&wrapper_hash));
+        assert!(shell.is_tx_applied(&wrapper.tx_hash));
+    }
+
+    /// Test that the replay protection entries of the expired txs are pruned
+    /// and that the entries of the txs that have not expired yet or that have
+    /// no expiration are kept
+    #[test]
+    fn test_expired_applied_txs_are_pruned() {
+        let (mut shell, _) = setup();
+        let expired = Hash::sha256(b"expired");
+        let not_expired = Hash::sha256(b"not expired");
+        let no_expiration = Hash::sha256(b"no expiration");
+        let time = |secs| -> DateTimeUtc {
+            Utc.timestamp_opt(secs, 0)
+                .single()
+                .expect("Test failed")
+                .into()
+        };
+        let block_time = time(1_000_000);
+
+        shell.record_applied_tx(&expired);
+        shell.index_applied_tx(&expired, time(999));
+        shell.record_applied_tx(&not_expired);
+        shell.index_applied_tx(&not_expired, time(1_000_001));
+        shell.record_applied_tx(&no_expiration);
+        shell.write_log.commit_tx();
+        shell
+            .write_log
+            .commit_block(&mut shell.storage)
+            .expect("Test failed");
+
+        shell.prune_expired_applied_txs(block_time);
+
+        assert!(!shell.is_tx_applied(&expired));
+        assert!(shell.is_tx_applied(&not_expired));
+        assert!(shell.is_tx_applied(&no_expiration));
+    }
+
+    /// An Ethereum event transferring some wrapped tokens to a new address
+    fn transfer_to_namada_event() -> (EthereumEvent, TransferToNamada) {
+        let transfer = TransferToNamada {
+            asset: EthAddress([1; 20]),
+            amount: token::Amount::whole(100),
+            receiver: address::testing::gen_established_address(),
+        };
+        (
+            EthereumEvent::TransfersToNamada {
+                nonce: 0,
+                transfers: vec![transfer.clone()],
+            },
+            transfer,
+        )
+    }
+
+    /// Test that an Ethereum event voted for by more than 2/3 of the voting
+    /// power is applied, minting the transferred wrapped tokens, and that
+    /// the votes cannot be tallied twice
+    #[test]
+    fn test_eth_events_votes_are_applied() {
+        let (mut shell, _) = setup();
+        let (event, transfer) = transfer_to_namada_event();
+        let vext = shell
+            .sign_eth_events_vext(shell.storage.last_height, vec![event])
+            .expect("Test failed");
+        let protocol_key = wallet::defaults::validator_keys().0;
+        let tx = ProtocolTxType::EthereumEvents(vec![vext]).sign(
+            &protocol_key.ref_to(),
+            &protocol_key,
+            shell.chain_id.clone(),
+        );
+        let processed_tx = ProcessedTx {
+            tx: tx.to_bytes(),
+            result: TxResult {
+                code: ErrorCodes::Ok.into(),
+                info: "".into(),
+            },
+        };
+
+        // the only validator holds all the voting power
+        let events = shell
+            .finalize_block(FinalizeBlock {
+                txs: vec![processed_tx.clone()],
+                ..Default::default()
+            })
+            .expect("Test failed");
+        assert_eq!(events.len(), 1);
+        let code = events[0].attributes.get("code").expect("Test failed");
+        assert_eq!(code, &String::from(ErrorCodes::Ok));
+        shell.commit();
+
+        let balance_key =
+            wrapped_erc20_balance_key(&transfer.asset, &transfer.receiver);
+        let supply_key = wrapped_erc20_supply_key(&transfer.asset);
+        for key in [&balance_key, &supply_key] {
+            assert_eq!(
+                shell.read_storage_key::<token::Amount>(key),
+                Some(transfer.amount)
+            );
+        }
+
+        // the same votes are rejected in the next block
+        let events = shell
+            .finalize_block(FinalizeBlock {
+                txs: vec![processed_tx],
+                ..Default::default()
+            })
+            .expect("Test failed");
+        assert_eq!(events.len(), 1);
+        let code = events[0].attributes.get("code").expect("Test failed");
+        assert_eq!(code, &String::from(ErrorCodes::InvalidTx));
+        shell.commit();
+        for key in [&balance_key, &supply_key] {
+            assert_eq!(
+                shell.read_storage_key::<token::Amount>(key),
+                Some(transfer.amount)
+            );
+        }
+    }
+
+    /// Test that without vote extensions, a validator broadcasts its votes on
+    /// the Ethereum events newly relayed by its oracle at the end of a block
+    #[cfg(not(feature = "abcipp"))]
+    #[test]
+    fn test_eth_events_votes_are_broadcast() {
+        let (mut shell, mut broadcast_receiver) = setup();
+        let (event, _) = transfer_to_namada_event();
+        assert!(shell.eth_oracle.send(event.clone()));
+
+        shell
+            .finalize_block(FinalizeBlock::default())
+            .expect("Test failed");
+        let tx = broadcast_receiver.try_recv().expect("Test failed");
+        let tx = Tx::try_from(tx.as_ref()).expect("Test failed");
+        match process_tx(tx) {
+            Ok(TxType::Protocol(ProtocolTx {
+                tx: ProtocolTxType::EthereumEvents(vexts),
+                ..
+            })) => {
+                assert_eq!(vexts.len(), 1);
+                assert_eq!(vexts[0].data.ethereum_events, vec![event]);
+            }
+            _ => panic!("Test failed"),
+        }
+
+        // the votes are only broadcast once
+        shell
+            .finalize_block(FinalizeBlock::default())
+            .expect("Test failed");
+        assert!(broadcast_receiver.try_recv().is_err());
+    }
 }
@@ -1,15 +1,18 @@
 use namada::ledger::events::EventType;
 use namada::ledger::governance::utils::{
-    compute_tally, get_proposal_votes, ProposalEvent,
+    compute_tally, execute_parameter_changes, execute_treasury_transfers,
+    get_proposal_votes, ProposalEvent,
 };
 use namada::ledger::governance::{
     storage as gov_storage, ADDRESS as gov_address,
 };
 use namada::ledger::slash_fund::ADDRESS as slash_fund_address;
 use namada::ledger::storage::types::encode;
+use namada::ledger::storage::write_log::WriteLogStorage;
 use namada::ledger::storage::{DBIter, StorageHasher, DB};
+use namada::ledger::storage_api::{self, StorageRead, StorageWrite};
 use namada::types::address::Address;
-use namada::types::governance::TallyResult;
+use namada::types::governance::{ProposalType, TallyResult};
 use namada::types::storage::Epoch;
 use namada::types::token;
 
@@ -49,13 +52,23 @@ where
                 )
             })?;
 
+        let proposal_type_key = gov_storage::get_proposal_type_key(id);
+        let proposal_type = shell
+            .read_storage_key::<ProposalType>(&proposal_type_key)
+            .unwrap_or_default();
+
         let votes = get_proposal_votes(&shell.storage, proposal_end_epoch, id);
-        let is_accepted = votes.and_then(|votes| {
-            compute_tally(&shell.storage, proposal_end_epoch, votes)
+        let tally_result = votes.and_then(|votes| {
+            compute_tally(
+                &shell.storage,
+                proposal_end_epoch,
+                votes,
+                &proposal_type,
+            )
         });
 
-        let transfer_address = match is_accepted {
-            Ok(true) => {
+        let transfer_address = match tally_result.map(|result| result.result) {
+            Ok(TallyResult::Passed) => {
                 let proposal_author_key = gov_storage::get_author_key(id);
                 let proposal_author = shell
                     .read_storage_key::<Address>(&proposal_author_key)
@@ -69,9 +82,14 @@ where
                 let proposal_code_key = gov_storage::get_proposal_code_key(id);
                 let proposal_code =
                     shell.read_storage_key_bytes(&proposal_code_key);
-                match proposal_code {
-                    Some(proposal_code) => {
-                        let tx = Tx::new(proposal_code, Some(encode(&id)));
+                match (proposal_type, proposal_code) {
+                    (ProposalType::Default, Some(proposal_code)) => {
+                        let tx = Tx::new(
+                            proposal_code,
+                            Some(encode(&id)),
+                            shell.chain_id.clone(),
+                            None,
+                        );
                         let tx_type =
                             TxType::Decrypted(DecryptedTx::Decrypted(tx));
                         let pending_execution_key =
@@ -97,44 +115,26 @@ where
                             .delete(&pending_execution_key)
                             .expect("Should be able to delete the storage.");
                         match tx_result {
-                            Ok(tx_result) => {
-                                if tx_result.is_accepted() {
-                                    shell.write_log.commit_tx();
-                                    let proposal_event: Event =
-                                        ProposalEvent::new(
-                                            EventType::Proposal.to_string(),
-                                            TallyResult::Passed,
-                                            id,
-                                            true,
-                                            true,
-                                        )
-                                        .into();
-                                    response.events.push(proposal_event);
-                                    proposals_result.passed.push(id);
-
-                                    proposal_author
-                                } else {
-                                    shell.write_log.drop_tx();
-                                    let proposal_event: Event =
-                                        ProposalEvent::new(
-                                            EventType::Proposal.to_string(),
-                                            TallyResult::Passed,
-                                            id,
-                                            true,
-                                            false,
-                                        )
-                                        .into();
-                                    response.events.push(proposal_event);
-                                    proposals_result.rejected.push(id);
-
-                                    slash_fund_address
-                                }
+                            Ok(tx_result) if tx_result.is_accepted() => {
+                                shell.write_log.commit_tx();
+                                let proposal_event: Event = ProposalEvent::new(
+                                    EventType::Proposal.to_string(),
+                                    TallyResult::Passed,
+                                    id,
+                                    true,
+                                    true,
+                                )
+                                .into();
+                                response.events.push(proposal_event);
+                                proposals_result.passed.push(id);
+
+                                proposal_author
                             }
-                            Err(_e) => {
+                            _ => {
                                 shell.write_log.drop_tx();
                                 let proposal_event: Event = ProposalEvent::new(
                                     EventType::Proposal.to_string(),
-                                    TallyResult::Passed,
+                                    TallyResult::ExecutionFailed,
                                     id,
                                     true,
                                     false,
@@ -147,7 +147,7 @@ where
                             }
                         }
                     }
-                    None => {
+                    (ProposalType::Default, None) => {
                         let proposal_event: Event = ProposalEvent::new(
                             EventType::Proposal.to_string(),
                             TallyResult::Passed,
@@ -161,9 +161,36 @@ where
 
                         proposal_author
                     }
+                    (proposal_type, _) => {
+                        let is_executed =
+                            execute_typed_proposal(shell, id, &proposal_type);
+                        let tally = if is_executed {
+                            TallyResult::Passed
+                        } else {
+                            TallyResult::ExecutionFailed
+                        };
+                        let proposal_event: Event = ProposalEvent::new(
+                            EventType::Proposal.to_string(),
+                            tally,
+                            id,
+                            true,
+                            is_executed,
+                        )
+                        .into();
+                        response.events.push(proposal_event);
+                        if is_executed {
+                            proposals_result.passed.push(id);
+                            proposal_author
+                        } else {
+                            proposals_result.rejected.push(id);
+                            slash_fund_address
+                        }
+                    }
                 }
             }
-            Ok(false) => {
+            Ok(TallyResult::Rejected)
+            | Ok(TallyResult::Failed)
+            | Ok(TallyResult::ExecutionFailed) => {
                 let proposal_event: Event = ProposalEvent::new(
                     EventType::Proposal.to_string(),
                     TallyResult::Rejected,
@@ -196,15 +223,110 @@ where
             }
         };
 
-        let native_token = shell.storage.native_token.clone();
-        // transfer proposal locked funds
-        shell.storage.transfer(
-            &native_token,
-            funds,
-            &gov_address,
-            &transfer_address,
-        );
+        // transfer proposal locked funds, on top of the changes of the
+        // executed proposals that are still in the write log
+        transfer_proposal_funds(shell, funds, &transfer_address)
+            .map_err(|err| Error::BadProposal(id, err.to_string()))?;
     }
 
     Ok(proposals_result)
 }
+
+/// Transfer the funds locked by a proposal from the governance account to the
+/// given target, through the write log
+fn transfer_proposal_funds<D, H>(
+    shell: &mut Shell<D, H>,
+    funds: token::Amount,
+    target: &Address,
+) -> storage_api::Result<()>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    let native_token = shell.storage.native_token.clone();
+    let mut storage =
+        WriteLogStorage::new(&mut shell.write_log, &shell.storage);
+    let source_key = token::balance_key(&native_token, &gov_address);
+    let target_key = token::balance_key(&native_token, target);
+    let mut source_balance: token::Amount =
+        storage.read(&source_key)?.unwrap_or_default();
+    source_balance.spend(&funds);
+    storage.write(&source_key, source_balance)?;
+    let mut target_balance: token::Amount =
+        storage.read(&target_key)?.unwrap_or_default();
+    target_balance.receive(&funds);
+    storage.write(&target_key, target_balance)?;
+    shell.write_log.commit_tx();
+    Ok(())
+}
+
+/// Natively execute a passed proposal of a type other than the default one.
+/// The changes are written in a write log transaction, which is only committed
+/// if the whole proposal was executed. Returns whether it was executed.
+fn execute_typed_proposal<D, H>(
+    shell: &mut Shell<D, H>,
+    id: u64,
+    proposal_type: &ProposalType,
+) -> bool
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    let mut storage =
+        WriteLogStorage::new(&mut shell.write_log, &shell.storage);
+    let result = match proposal_type {
+        ProposalType::Default => Ok(true),
+        ProposalType::ParameterChange(changes) => {
+            execute_parameter_changes(&mut storage, changes).map(|()| true)
+        }
+        ProposalType::TreasuryTransfer(transfers) => {
+            execute_treasury_transfers(&mut storage, transfers)
+        }
+    };
+    let is_executed = result.unwrap_or_else(|err| {
+        tracing::error!(
+            "Unexpectedly failed to execute proposal ID {} with error {}",
+            id,
+            err
+        );
+        false
+    });
+    if is_executed {
+        shell.write_log.commit_tx();
+    } else {
+        shell.write_log.drop_tx();
+    }
+    is_executed
+}
+
+#[cfg(test)]
+mod test_governance {
+    use namada::ledger::parameters::storage as parameter_storage;
+    use namada::types::governance::ParameterChange;
+
+    use super::*;
+    use crate::node::ledger::shell::test_utils::TestShell;
+
+    /// Test that none of the changes of a proposal whose execution fails are
+    /// applied
+    #[test]
+    fn test_failed_proposal_execution_is_reverted() {
+        let (mut shell, _) = TestShell::new();
+        let key = parameter_storage::get_epochs_per_year_key();
+
+        // the PoS parameters are not in storage, so the second change fails
+        // after the first one was written
+        let proposal_type = ProposalType::ParameterChange(vec![
+            ParameterChange::EpochsPerYear(10),
+            ParameterChange::MaxValidatorSlots(5),
+        ]);
+        assert!(!execute_typed_proposal(&mut shell, 0, &proposal_type));
+        assert!(shell.write_log.read(&key).0.is_none());
+
+        let proposal_type = ProposalType::ParameterChange(vec![
+            ParameterChange::EpochsPerYear(10),
+        ]);
+        assert!(execute_typed_proposal(&mut shell, 1, &proposal_type));
+        assert!(shell.write_log.read(&key).0.is_some());
+    }
+}
@@ -2,8 +2,10 @@
 use std::collections::HashMap;
 use std::hash::Hash;
 
-use namada::ledger::parameters::Parameters;
+use namada::ledger::parameters::{self, Parameters};
 use namada::ledger::pos::into_tm_voting_power;
+use namada::ledger::storage::export::StateExport;
+use namada::ledger::storage_api;
 use namada::types::key::*;
 #[cfg(not(feature = "dev"))]
 use sha2::{Digest, Sha256};
@@ -62,6 +64,14 @@ where
         .expect("genesis time should be a valid timestamp")
         .into();
 
+        if let Some(state_export) = genesis.state_export {
+            return self.init_chain_from_state_export(
+                state_export,
+                initial_height,
+                genesis_time,
+            );
+        }
+
         // Initialize protocol parameters
         let genesis::Parameters {
             epoch_duration,
@@ -189,6 +199,7 @@ where
             vp_code_path,
             vp_sha256,
             balances,
+            metadata,
         } in genesis.token_accounts
         {
             let vp_code =
@@ -217,6 +228,13 @@ where
                 .write(&Key::validity_predicate(&address), vp_code)
                 .unwrap();
 
+            self.storage
+                .write(
+                    &token::metadata_key(&address),
+                    metadata.try_to_vec().unwrap(),
+                )
+                .unwrap();
+
             for (owner, amount) in balances {
                 self.storage
                     .write(
@@ -224,6 +242,12 @@ where
                         amount.try_to_vec().unwrap(),
                     )
                     .unwrap();
+                storage_api::token::increase_total_supply(
+                    &mut self.storage,
+                    &address,
+                    amount,
+                )
+                .unwrap();
             }
         }
 
@@ -278,6 +302,13 @@ where
                         .expect("encode token amount"),
                 )
                 .expect("Unable to set genesis balance");
+            let native_token = self.storage.native_token.clone();
+            storage_api::token::increase_total_supply(
+                &mut self.storage,
+                &native_token,
+                validator.non_staked_balance,
+            )
+            .expect("Unable to set genesis total supply");
             self.storage
                 .write(
                     &protocol_pk_key(addr),
@@ -329,6 +360,72 @@ where
         }
         Ok(response)
     }
+
+    /// Initialize the genesis block from a state exported from another chain,
+    /// instead of the genesis accounts, validators and parameters. The chain
+    /// continues from the epoch of the export with its validator set.
+    fn init_chain_from_state_export(
+        &mut self,
+        state_export: genesis::StateExportFile,
+        initial_height: BlockHeight,
+        genesis_time: DateTimeUtc,
+    ) -> Result<response::InitChain> {
+        let bytes = std::fs::read(self.base_dir.join(&state_export.path))
+            .map_err(Error::StateExport)?;
+        let export =
+            StateExport::try_from_slice(&bytes).map_err(Error::StateExport)?;
+        assert_eq!(
+            export.hash().0,
+            state_export.sha256,
+            "Invalid state export sha256 hash for {}",
+            state_export.path
+        );
+        tracing::info!(
+            "Starting the chain from the state of {} at height {}",
+            export.chain_id,
+            export.height
+        );
+        self.storage
+            .import_state(export, initial_height)
+            .map_err(Error::Storage)?;
+
+        // Depends on the parameters being imported
+        let (parameters, _gas) = parameters::read(&self.storage)
+            .expect("Couldn't read protocol parameters");
+        self.storage
+            .init_genesis_epoch(initial_height, genesis_time, &parameters)
+            .expect("Initializing genesis epoch must not fail");
+
+        // Set the initial validator set from the imported PoS state
+        let mut response = response::InitChain::default();
+        let (current_epoch, _gas) = self.storage.get_current_epoch();
+        let pos_params = self.storage.read_pos_params();
+        let validators = self
+            .storage
+            .read_validator_set()
+            .get(current_epoch)
+            .expect("Validators for the current epoch should be known")
+            .active
+            .clone();
+        for validator in validators {
+            let consensus_key = self
+                .storage
+                .read_validator_consensus_key(&validator.address)
+                .and_then(|keys| keys.get(current_epoch).cloned())
+                .expect("The consensus key of a validator should be known");
+            let pub_key = TendermintPublicKey {
+                sum: Some(key_to_tendermint(&consensus_key).unwrap()),
+            };
+            response.validators.push(abci::ValidatorUpdate {
+                pub_key: Some(pub_key),
+                power: into_tm_voting_power(
+                    pos_params.tm_votes_per_token,
+                    validator.bonded_stake,
+                ),
+            });
+        }
+        Ok(response)
+    }
 }
 
 trait HashMapExt<K, V>
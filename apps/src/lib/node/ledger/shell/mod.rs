@@ -5,12 +5,15 @@
 //! and [`Shell::process_proposal`] must be also reverted
 //! (unless we can simply overwrite them in the next block).
 //! More info in <https://github.com/anoma/anoma/issues/362>.
+mod dkg;
+mod ethereum_events;
 mod finalize_block;
 mod governance;
 mod init_chain;
 mod prepare_proposal;
 mod process_proposal;
 mod queries;
+mod snapshots;
 
 use std::collections::HashSet;
 use std::convert::{TryFrom, TryInto};
@@ -20,27 +23,32 @@ use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
 use borsh::{BorshDeserialize, BorshSerialize};
+use data_encoding::HEXLOWER;
 use namada::ledger::events::log::EventLog;
 use namada::ledger::events::Event;
-use namada::ledger::gas::BlockGasMeter;
+use namada::ledger::gas::{BlockGasMeter, BLOCK_GAS_LIMIT};
 use namada::ledger::pos::namada_proof_of_stake::types::{
     ActiveValidator, ValidatorSetUpdate,
 };
 use namada::ledger::pos::namada_proof_of_stake::PosBase;
-use namada::ledger::storage::write_log::WriteLog;
+use namada::ledger::storage::write_log::{StorageModification, WriteLog};
 use namada::ledger::storage::{
-    DBIter, Sha256Hasher, Storage, StorageHasher, DB,
+    DBIter, PruningMode, Sha256Hasher, Storage, StorageHasher, DB,
 };
-use namada::ledger::{ibc, pos, protocol};
+use namada::ledger::{ibc, pos, protocol, replay_protection};
 use namada::proto::{self, Tx};
-use namada::types::address::{masp, masp_tx_key, Address};
+use namada::types::address::{masp_tx_key, Address};
 use namada::types::chain::ChainId;
+#[cfg(feature = "abcipp")]
+use namada::types::ethereum_events::SignedEthEventsVext;
+use namada::types::hash::Hash;
 use namada::types::key::*;
 use namada::types::storage::{BlockHeight, Key, TxIndex};
 use namada::types::time::{DateTimeUtc, TimeZone, Utc};
+use namada::types::transaction::protocol::{ProtocolTx, ProtocolTxType};
 use namada::types::transaction::{
-    hash_tx, process_tx, verify_decrypted_correctly, AffineCurve, DecryptedTx,
-    EllipticCurve, PairingEngine, TxType, WrapperTx,
+    hash_tx, process_tx, verify_decrypted_correctly, DecryptedTx,
+    EllipticCurve, TxType, WrapperTx,
 };
 use namada::types::{address, token};
 use namada::vm::wasm::{TxCache, VpCache};
@@ -50,7 +58,7 @@ use num_traits::{FromPrimitive, ToPrimitive};
 use thiserror::Error;
 use tokio::sync::mpsc::UnboundedSender;
 
-use crate::config::{genesis, TendermintMode};
+use crate::config::{genesis, DbBackend, TendermintMode};
 #[cfg(feature = "abcipp")]
 use crate::facade::tendermint_proto::abci::response_verify_vote_extension::VerifyStatus;
 use crate::facade::tendermint_proto::abci::{
@@ -58,8 +66,10 @@ use crate::facade::tendermint_proto::abci::{
 };
 use crate::facade::tendermint_proto::crypto::public_key;
 use crate::facade::tower_abci::{request, response};
+use crate::node::ledger::ethereum_oracle::EthereumReceiver;
 use crate::node::ledger::shims::abcipp_shim_types::shim;
 use crate::node::ledger::shims::abcipp_shim_types::shim::response::TxResult;
+use crate::node::ledger::storage::LedgerDB;
 use crate::node::ledger::{storage, tendermint_node};
 #[allow(unused_imports)]
 use crate::wallet::ValidatorData;
@@ -82,6 +92,8 @@ fn key_to_tendermint(
 pub enum Error {
     #[error("Error removing the DB data: {0}")]
     RemoveDB(std::io::Error),
+    #[error("Storage error: {0}")]
+    Storage(namada::ledger::storage::Error),
     #[error("chain ID mismatch: {0}")]
     ChainId(String),
     #[error("Error decoding a transaction from bytes: {0}")]
@@ -100,6 +112,8 @@ pub enum Error {
     BadProposal(u64, String),
     #[error("Error reading wasm: {0}")]
     ReadingWasm(#[from] eyre::Error),
+    #[error("Error reading or writing the state export: {0}")]
+    StateExport(std::io::Error),
 }
 
 impl From<Error> for TxResult {
@@ -123,6 +137,25 @@ pub enum ErrorCodes {
     InvalidOrder = 4,
     ExtraTxs = 5,
     Undecryptable = 6,
+    ReplayTx = 7,
+    InvalidChainId = 8,
+    ExpiredTx = 9,
+}
+
+impl ErrorCodes {
+    /// Checks if the given [`ErrorCodes`] value only invalidates the tx that
+    /// it was returned for. Otherwise, the whole block proposal including the
+    /// tx must be rejected.
+    pub fn is_recoverable(&self) -> bool {
+        use ErrorCodes::*;
+        // NOTE: pattern match on all `ErrorCodes` variants, in order
+        // to catch potential bugs when adding new codes
+        match self {
+            Ok | InvalidTx | InvalidSig | WasmRuntimeError | ReplayTx
+            | InvalidChainId | ExpiredTx => true,
+            InvalidOrder | ExtraTxs | Undecryptable => false,
+        }
+    }
 }
 
 impl From<ErrorCodes> for u32 {
@@ -152,12 +185,118 @@ pub fn reset(config: config::Ledger) -> Result<()> {
     Ok(())
 }
 
+/// Roll back the state of Tendermint and of the DB by one block height, so
+/// that the last block is applied again when the node is restarted
+pub fn rollback(config: config::Ledger) -> Result<()> {
+    match config.shell.db_backend {
+        DbBackend::RocksDb => rollback_db::<storage::PersistentDB>(config),
+        DbBackend::BTree => rollback_db::<storage::PersistentBTreeDB>(config),
+        DbBackend::InMemory => rollback_db::<storage::InMemoryDB>(config),
+    }
+}
+
+/// Roll back the state of Tendermint and of the DB of the given backend
+fn rollback_db<D: LedgerDB>(config: config::Ledger) -> Result<()> {
+    let tendermint_height = tendermint_node::rollback(config.tendermint_dir())
+        .map_err(Error::Tendermint)?;
+    let mut db = D::open(config.db_dir(), None);
+    let last_height = db
+        .read_last_block()
+        .map_err(Error::Storage)?
+        .map(|block| block.height)
+        .unwrap_or_default();
+    // Tendermint doesn't roll back its state more than once, in which case
+    // its height already matches the height of the rolled back DB
+    if last_height <= tendermint_height {
+        tracing::info!(
+            "The state is already at the height {}, nothing to roll back",
+            last_height
+        );
+        return Ok(());
+    }
+    let height = db.rollback().map_err(Error::Storage)?;
+    // The state of the rolled back height will be committed again
+    let snapshot_dir = config
+        .shell
+        .snapshots_dir(&config.chain_id)
+        .join(last_height.to_string());
+    match std::fs::remove_dir_all(snapshot_dir) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => (),
+        res => res.map_err(Error::RemoveDB)?,
+    };
+    tracing::info!("Rolled back the state to the height {}", height);
+    Ok(())
+}
+
+/// Export the state at the given block height, or at the last committed block
+/// height, into a file in the chain's directory. The path of the file and its
+/// hash are printed to be set in the genesis of the new chain.
+pub fn dump_state(
+    config: config::Ledger,
+    native_token: Address,
+    height: Option<BlockHeight>,
+) -> Result<()> {
+    match config.shell.db_backend {
+        DbBackend::RocksDb => {
+            dump_state_db::<storage::PersistentDB>(config, native_token, height)
+        }
+        DbBackend::BTree => dump_state_db::<storage::PersistentBTreeDB>(
+            config,
+            native_token,
+            height,
+        ),
+        DbBackend::InMemory => {
+            dump_state_db::<storage::InMemoryDB>(config, native_token, height)
+        }
+    }
+}
+
+/// Export the state from the DB of the given backend
+fn dump_state_db<D: LedgerDB>(
+    config: config::Ledger,
+    native_token: Address,
+    height: Option<BlockHeight>,
+) -> Result<()> {
+    let mut storage = Storage::<D, storage::PersistentStorageHasher>::open(
+        config.db_dir(),
+        config.chain_id.clone(),
+        native_token,
+        None,
+    );
+    storage.load_last_state().map_err(Error::Storage)?;
+    let height = height.unwrap_or(storage.last_height);
+    let export = storage.export_state(height).map_err(Error::Storage)?;
+    let path = config
+        .shell
+        .base_dir
+        .join(config.chain_id.as_str())
+        .join(format!("state_export_{}", height));
+    std::fs::write(
+        &path,
+        export
+            .try_to_vec()
+            .expect("Encoding a state export shouldn't fail"),
+    )
+    .map_err(Error::StateExport)?;
+    println!(
+        "Exported the state at the height {} to {}",
+        height,
+        path.to_string_lossy()
+    );
+    println!(
+        "SHA-256 hash of the export: {}",
+        HEXLOWER.encode(&export.hash().0)
+    );
+    Ok(())
+}
+
 #[derive(Debug)]
 #[allow(dead_code, clippy::large_enum_variant)]
 pub(super) enum ShellMode {
     Validator {
         data: ValidatorData,
         broadcast_sender: UnboundedSender<Vec<u8>>,
+        eth_receiver: EthereumReceiver,
     },
     Full,
     Seed,
@@ -217,6 +356,24 @@ where
     /// limit the how many block heights in the past can the storage be
     /// queried for reading values.
     storage_read_past_height_limit: Option<u64>,
+    /// Taken from config `pruning`. Which heights' state is kept in the
+    /// storage once it's committed.
+    pruning_mode: PruningMode,
+    /// Taken from config `block_gas_limit`. The gas budget of the txs
+    /// included in the blocks proposed by this node.
+    block_gas_limit: u64,
+    /// Taken from config `block_max_tx_bytes`. When set, will limit the size
+    /// of the txs included in the blocks proposed by this node.
+    block_max_tx_bytes: Option<u64>,
+    /// Taken from config `snapshot_interval`. When set, a snapshot of the
+    /// state is taken at every block height that is a multiple of it.
+    snapshot_interval: Option<u64>,
+    /// Path to the directory with the snapshots of the state
+    snapshots_dir: PathBuf,
+    /// The task taking a snapshot of the state in the background, if any
+    snapshot_task: Option<std::thread::JoinHandle<()>>,
+    /// The snapshot offered by a peer that is being restored, if any
+    snapshot_restore: Option<snapshots::SnapshotRestore>,
     /// Proposal execution tracking
     pub proposal_data: HashSet<u64>,
     /// Log of events emitted by `FinalizeBlock` ABCI calls.
@@ -234,6 +391,7 @@ where
         config: config::Ledger,
         wasm_dir: PathBuf,
         broadcast_sender: UnboundedSender<Vec<u8>>,
+        eth_receiver: EthereumReceiver,
         db_cache: Option<&D::Cache>,
         vp_wasm_compilation_cache: u64,
         tx_wasm_compilation_cache: u64,
@@ -245,6 +403,12 @@ where
         let mode = config.tendermint.tendermint_mode;
         let storage_read_past_height_limit =
             config.shell.storage_read_past_height_limit;
+        let pruning_mode = config.shell.pruning;
+        let block_gas_limit =
+            config.shell.block_gas_limit.unwrap_or(BLOCK_GAS_LIMIT);
+        let block_max_tx_bytes = config.shell.block_max_tx_bytes;
+        let snapshot_interval = config.shell.snapshot_interval;
+        let snapshots_dir = config.shell.snapshots_dir(&chain_id);
         if !Path::new(&base_dir).is_dir() {
             std::fs::create_dir(&base_dir)
                 .expect("Creating directory for Anoma should not fail");
@@ -287,6 +451,7 @@ where
                         .map(|data| ShellMode::Validator {
                             data,
                             broadcast_sender,
+                            eth_receiver,
                         })
                         .expect(
                             "Validator data should have been stored in the \
@@ -305,6 +470,7 @@ where
                             },
                         },
                         broadcast_sender,
+                        eth_receiver,
                     }
                 }
             }
@@ -330,6 +496,13 @@ where
                 tx_wasm_compilation_cache as usize,
             ),
             storage_read_past_height_limit,
+            pruning_mode,
+            block_gas_limit,
+            block_max_tx_bytes,
+            snapshot_interval,
+            snapshots_dir,
+            snapshot_task: None,
+            snapshot_restore: None,
             proposal_data: HashSet::new(),
             // TODO: config event log params
             event_log: EventLog::default(),
@@ -410,6 +583,119 @@ where
         }
     }
 
+    /// Check if a tx with the given hash has already been applied, either in
+    /// a committed block or earlier in the block that is being finalized.
+    fn is_tx_applied(&self, hash: &Hash) -> bool {
+        let key = replay_protection::get_tx_hash_key(hash);
+        match self.write_log.read(&key).0 {
+            Some(StorageModification::Write { .. }) => true,
+            // The entry has been pruned
+            Some(StorageModification::Delete) => false,
+            _ => {
+                self.storage
+                    .has_key(&key)
+                    .expect("Storage read in the protocol must not fail")
+                    .0
+            }
+        }
+    }
+
+    /// Check that a protocol tx can be applied in the current state
+    fn validate_protocol_tx(
+        &self,
+        tx: &ProtocolTx,
+    ) -> std::result::Result<(), String> {
+        match &tx.tx {
+            ProtocolTxType::EncryptionKeyShare(..)
+            | ProtocolTxType::DecryptionKeyShare(..) => {
+                self.validate_dkg_key_share(tx).map(|_| ())
+            }
+            ProtocolTxType::EthereumEvents(vexts) => {
+                self.validate_eth_events_vexts(vexts)
+            }
+            _ => Err("This type of protocol tx is not supported yet".into()),
+        }
+    }
+
+    /// Validate a protocol tx and write its changes to the write log
+    fn apply_protocol_tx(
+        &mut self,
+        tx: &ProtocolTx,
+    ) -> std::result::Result<(), String> {
+        match &tx.tx {
+            ProtocolTxType::EncryptionKeyShare(..)
+            | ProtocolTxType::DecryptionKeyShare(..) => {
+                let validator = self.validate_dkg_key_share(tx)?;
+                self.write_dkg_key_share(&validator, tx);
+            }
+            ProtocolTxType::EthereumEvents(vexts) => {
+                self.validate_eth_events_vexts(vexts)?;
+                self.apply_eth_events_vexts(vexts);
+            }
+            _ => self.validate_protocol_tx(tx)?,
+        }
+        Ok(())
+    }
+
+    /// Check that a tx that is not yet applied is meant for this chain and
+    /// that it has not expired at the given time.
+    fn validate_tx_chain_and_expiration(
+        &self,
+        tx: &Tx,
+        time: DateTimeUtc,
+    ) -> std::result::Result<(), TxResult> {
+        if tx.chain_id != self.chain_id {
+            return Err(TxResult {
+                code: ErrorCodes::InvalidChainId.into(),
+                info: format!(
+                    "Tx carries a wrong chain id: expected {}, found {}",
+                    self.chain_id, tx.chain_id
+                ),
+            });
+        }
+        if let Some(expiration) = tx.expiration {
+            if time > expiration {
+                return Err(TxResult {
+                    code: ErrorCodes::ExpiredTx.into(),
+                    info: format!(
+                        "Tx expired at {}, block time: {}",
+                        expiration.to_rfc3339(),
+                        time.to_rfc3339()
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the fee payer of a wrapper tx can pay its fee. The fee of a
+    /// wrapper signed with the MASP sentinel key cannot be paid, as it would
+    /// be taken from the tokens held in the shielded pool.
+    fn validate_wrapper_fee(
+        &self,
+        wrapper: &WrapperTx,
+    ) -> std::result::Result<(), TxResult> {
+        if wrapper.pk == masp_tx_key().ref_to() {
+            return Err(TxResult {
+                code: ErrorCodes::InvalidTx.into(),
+                info: "The fee of a wrapper transaction cannot be paid by the \
+                       MASP"
+                    .into(),
+            });
+        }
+        let balance =
+            self.get_balance(&wrapper.fee.token, &wrapper.fee_payer());
+        if wrapper.fee.amount > balance {
+            return Err(TxResult {
+                code: ErrorCodes::InvalidTx.into(),
+                info: "The address given does not have sufficient balance to \
+                       pay fee"
+                    .into(),
+            });
+        }
+        Ok(())
+    }
+
     /// Apply PoS slashes from the evidence
     fn slash(&mut self) {
         if !self.byzantine_validators.is_empty() {
@@ -519,23 +805,60 @@ where
         }
     }
 
+    /// Extend the vote of this validator with its votes on the Ethereum
+    /// events that it has yet to vote on.
+    ///
     /// INVARIANT: This method must be stateless.
     #[cfg(feature = "abcipp")]
     pub fn extend_vote(
         &self,
-        _req: request::ExtendVote,
+        req: request::ExtendVote,
     ) -> response::ExtendVote {
-        Default::default()
+        let height = BlockHeight(req.height as u64);
+        response::ExtendVote {
+            vote_extension: self
+                .sign_eth_events_vext(height, self.eth_events_queue())
+                .map(|vext| {
+                    vext.try_to_vec()
+                        .expect("Serializing a vote extension should not fail")
+                })
+                .unwrap_or_default(),
+        }
     }
 
+    /// Check that a vote extension is either empty or carries valid votes on
+    /// Ethereum events, made at the height of the block being voted on.
+    ///
     /// INVARIANT: This method must be stateless.
     #[cfg(feature = "abcipp")]
     pub fn verify_vote_extension(
         &self,
-        _req: request::VerifyVoteExtension,
+        req: request::VerifyVoteExtension,
     ) -> response::VerifyVoteExtension {
+        let height = BlockHeight(req.height as u64);
+        let valid = req.vote_extension.is_empty()
+            || SignedEthEventsVext::try_from_slice(&req.vote_extension)
+                .map_err(|err| err.to_string())
+                .and_then(|vext| {
+                    if vext.data.block_height != height {
+                        return Err(format!(
+                            "The vote extension was made for the block height \
+                             {}",
+                            vext.data.block_height
+                        ));
+                    }
+                    self.validate_eth_events_vext(&vext, height)
+                })
+                .map_err(|err| {
+                    tracing::warn!("Rejecting a vote extension: {}", err)
+                })
+                .is_ok();
         response::VerifyVoteExtension {
-            status: VerifyStatus::Accept as i32,
+            status: if valid {
+                VerifyStatus::Accept as i32
+            } else {
+                VerifyStatus::Reject as i32
+            },
         }
     }
 
@@ -554,6 +877,19 @@ where
                 e
             )
         });
+        // prune the state of the heights that are no longer kept
+        self.storage.prune(self.pruning_mode).unwrap_or_else(|e| {
+            tracing::error!(
+                "Encountered a storage error while pruning the state {:?}",
+                e
+            )
+        });
+        // take a snapshot of the state at the configured interval
+        if let Some(interval) = self.snapshot_interval {
+            if interval > 0 && self.storage.last_height.0 % interval == 0 {
+                self.take_snapshot();
+            }
+        }
 
         let root = self.storage.merkle_root();
         tracing::info!(
@@ -574,13 +910,56 @@ where
         r#_type: MempoolTxType,
     ) -> response::CheckTx {
         let mut response = response::CheckTx::default();
-        match Tx::try_from(tx_bytes).map_err(Error::TxDecoding) {
-            Ok(_) => response.log = String::from("Mempool validation passed"),
+        let tx = match Tx::try_from(tx_bytes).map_err(Error::TxDecoding) {
+            Ok(tx) => tx,
             Err(msg) => {
-                response.code = 1;
+                response.code = ErrorCodes::InvalidTx.into();
                 response.log = msg.to_string();
+                return response;
             }
+        };
+        if let Err(result) =
+            self.validate_tx_chain_and_expiration(&tx, DateTimeUtc::now())
+        {
+            response.code = result.code;
+            response.log = result.info;
+            return response;
         }
+        match process_tx(tx) {
+            // Reject the txs that have already been applied
+            Ok(TxType::Wrapper(wrapper)) => {
+                if self.is_tx_applied(&hash_tx(tx_bytes)) {
+                    response.code = ErrorCodes::ReplayTx.into();
+                    response.log = "Wrapper transaction hash already in \
+                                    storage, replay attempt"
+                        .into();
+                    return response;
+                }
+                if self.is_tx_applied(&wrapper.tx_hash) {
+                    response.code = ErrorCodes::ReplayTx.into();
+                    response.log = "Inner transaction hash already in \
+                                    storage, replay attempt"
+                        .into();
+                    return response;
+                }
+                if let Err(result) = self.validate_wrapper_fee(&wrapper) {
+                    response.code = result.code;
+                    response.log = result.info;
+                    return response;
+                }
+            }
+            // Reject the protocol txs that cannot be applied
+            Ok(TxType::Protocol(protocol_tx)) => {
+                if let Err(err) = self.validate_protocol_tx(&protocol_tx) {
+                    response.code = ErrorCodes::InvalidTx.into();
+                    response.log =
+                        format!("Invalid protocol transaction: {}", err);
+                    return response;
+                }
+            }
+            _ => {}
+        }
+        response.log = String::from("Mempool validation passed");
         response
     }
 
@@ -666,14 +1045,21 @@ mod test_utils {
     use std::ops::{Deref, DerefMut};
     use std::path::PathBuf;
 
+    use ark_std::rand::SeedableRng;
     use namada::ledger::storage::mockdb::MockDB;
     use namada::ledger::storage::{BlockStateWrite, MerkleTree, Sha256Hasher};
     use namada::types::address::EstablishedAddressGen;
     use namada::types::chain::ChainId;
     use namada::types::hash::Hash;
+    use namada::types::key::dkg_session_keys::{
+        dkg_decryption_share_key, dkg_encryption_share_key,
+        dkg_participants_key, dkg_session_pk_key, DkgKeypair,
+    };
     use namada::types::key::*;
     use namada::types::storage::{BlockHash, BlockResults, Epoch, Header};
-    use namada::types::transaction::Fee;
+    use namada::types::transaction::{
+        EncryptionKey, EncryptionKeyShare, EpochKeyShare, Fee,
+    };
     use tempfile::tempdir;
     use tokio::sync::mpsc::UnboundedReceiver;
 
@@ -682,6 +1068,7 @@ mod test_utils {
         RequestInitChain, RequestProcessProposal,
     };
     use crate::facade::tendermint_proto::google::protobuf::Timestamp;
+    use crate::node::ledger::ethereum_oracle::mock::MockEthereumOracle;
     use crate::node::ledger::shims::abcipp_shim_types::shim::request::{
         FinalizeBlock, ProcessedTx,
     };
@@ -722,6 +1109,8 @@ mod test_utils {
     /// modifications for testing purposes
     pub(super) struct TestShell {
         pub shell: Shell<MockDB, Sha256Hasher>,
+        /// Relays Ethereum events to the shell
+        pub eth_oracle: MockEthereumOracle,
     }
 
     impl Deref for TestShell {
@@ -753,6 +1142,7 @@ mod test_utils {
             let base_dir = tempdir().unwrap().as_ref().canonicalize().unwrap();
             let vp_wasm_compilation_cache = 50 * 1024 * 1024; // 50 kiB
             let tx_wasm_compilation_cache = 50 * 1024 * 1024; // 50 kiB
+            let (eth_oracle, eth_receiver) = MockEthereumOracle::new();
             (
                 Self {
                     shell: Shell::<MockDB, Sha256Hasher>::new(
@@ -763,11 +1153,13 @@ mod test_utils {
                         ),
                         top_level_directory().join("wasm"),
                         sender,
+                        eth_receiver,
                         None,
                         vp_wasm_compilation_cache,
                         tx_wasm_compilation_cache,
                         address::nam(),
                     ),
+                    eth_oracle,
                 },
                 receiver,
             )
@@ -842,6 +1234,86 @@ mod test_utils {
         (test, receiver)
     }
 
+    /// Simulate the DKG session of the given epoch between the given number
+    /// of validators, by storing their encryption key shares together with
+    /// the participants and the encryption key of the session. Returns the
+    /// participants paired with their secret key shares, which can be
+    /// revealed with [`reveal_dkg_key_shares`].
+    pub(super) fn simulate_dkg_session(
+        shell: &mut TestShell,
+        epoch: Epoch,
+        validators: usize,
+    ) -> Vec<(Address, EpochKeyShare)> {
+        let participants: Vec<(Address, EpochKeyShare)> = (0..validators)
+            .map(|_| {
+                let dkg_keypair: DkgKeypair =
+                    ferveo_common::Keypair::<EllipticCurve>::new(
+                        &mut ark_std::rand::rngs::StdRng::from_entropy(),
+                    )
+                    .into();
+                (
+                    address::testing::gen_established_address(),
+                    EpochKeyShare::derive(&dkg_keypair, epoch),
+                )
+            })
+            .collect();
+        let encryption_shares: Vec<EncryptionKeyShare> = participants
+            .iter()
+            .map(|(validator, share)| share.encryption_share(validator, epoch))
+            .collect();
+        for ((validator, _), share) in
+            participants.iter().zip(encryption_shares.iter())
+        {
+            shell
+                .storage
+                .write(
+                    &dkg_encryption_share_key(validator, epoch),
+                    share.try_to_vec().expect("Test failed"),
+                )
+                .expect("Test failed");
+        }
+        let addresses: Vec<Address> = participants
+            .iter()
+            .map(|(validator, _)| validator.clone())
+            .collect();
+        shell
+            .storage
+            .write(
+                &dkg_participants_key(epoch),
+                addresses.try_to_vec().expect("Test failed"),
+            )
+            .expect("Test failed");
+        let encryption_key = EncryptionKey::aggregate(
+            encryption_shares.iter().map(|share| &share.key),
+        );
+        shell
+            .storage
+            .write(
+                &dkg_session_pk_key(epoch),
+                encryption_key.try_to_vec().expect("Test failed"),
+            )
+            .expect("Test failed");
+        participants
+    }
+
+    /// Store the decryption key shares of the given participants of the DKG
+    /// session of the given epoch
+    pub(super) fn reveal_dkg_key_shares(
+        shell: &mut TestShell,
+        epoch: Epoch,
+        participants: &[(Address, EpochKeyShare)],
+    ) {
+        for (validator, share) in participants {
+            shell
+                .storage
+                .write(
+                    &dkg_decryption_share_key(validator, epoch),
+                    share.decryption_share().try_to_vec().expect("Test failed"),
+                )
+                .expect("Test failed");
+        }
+    }
+
     /// This is just to be used in testing. It is not
     /// a meaningful default.
     impl Default for FinalizeBlock {
@@ -855,6 +1327,8 @@ mod test_utils {
                 },
                 byzantine_validators: vec![],
                 txs: vec![],
+                proposer_address: vec![],
+                votes: vec![],
             }
         }
     }
@@ -877,6 +1351,7 @@ mod test_utils {
             ),
             top_level_directory().join("wasm"),
             sender.clone(),
+            MockEthereumOracle::new().1,
             None,
             vp_wasm_compilation_cache,
             tx_wasm_compilation_cache,
@@ -887,6 +1362,8 @@ mod test_utils {
         let tx = Tx::new(
             "wasm_code".as_bytes().to_owned(),
             Some("transaction data".as_bytes().to_owned()),
+            shell.chain_id.clone(),
+            None,
         );
         let wrapper = WrapperTx::new(
             Fee {
@@ -937,6 +1414,7 @@ mod test_utils {
             ),
             top_level_directory().join("wasm"),
             sender,
+            MockEthereumOracle::new().1,
             None,
             vp_wasm_compilation_cache,
             tx_wasm_compilation_cache,
@@ -1,15 +1,18 @@
 //! Implementation of the [`RequestPrepareProposal`] ABCI++ method for the Shell
 
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
 use namada::ledger::storage::{DBIter, StorageHasher, DB};
 use namada::proto::Tx;
 use namada::types::transaction::tx_types::TxType;
-use namada::types::transaction::wrapper::wrapper_tx::PairingEngine;
-use namada::types::transaction::{AffineCurve, DecryptedTx, EllipticCurve};
+use namada::types::transaction::{DecryptedTx, WrapperTx};
 
 use super::super::*;
 use crate::facade::tendermint_proto::abci::RequestPrepareProposal;
 #[cfg(feature = "abcipp")]
 use crate::facade::tendermint_proto::abci::{tx_record::TxAction, TxRecord};
+use crate::node::ledger::shell::dkg::EpochDecryption;
 use crate::node::ledger::shell::{process_tx, ShellMode};
 use crate::node::ledger::shims::abcipp_shim_types::shim::TxBytes;
 
@@ -20,9 +23,12 @@ where
 {
     /// Begin a new block.
     ///
-    /// We include half of the new wrapper txs given to us from the mempool
-    /// by tendermint. The rest of the block is filled with decryptions
-    /// of the wrapper txs from the previously committed block.
+    /// The block is filled with the decryptions of the wrapper txs from the
+    /// previously committed block first, preceded with vote extensions by
+    /// the votes on Ethereum events from the last block. The remaining gas and
+    /// bytes budgets of the block are then filled with the new wrapper txs
+    /// given to us from the mempool by tendermint, in order of decreasing
+    /// fee per unit of gas.
     ///
     /// INVARIANT: Any changes applied in this method must be reverted if
     /// the proposal is rejected (unless we can simply overwrite
@@ -32,60 +38,97 @@ where
         req: RequestPrepareProposal,
     ) -> response::PrepareProposal {
         let txs = if let ShellMode::Validator { .. } = self.mode {
-            // TODO: This should not be hardcoded
-            let privkey = <EllipticCurve as PairingEngine>::G2Affine::prime_subgroup_generator();
-
-            // TODO: Craft the Ethereum state update tx
-            // filter in half of the new txs from Tendermint, only keeping
-            // wrappers
-            let number_of_new_txs = 1 + req.txs.len() / 2;
-            #[cfg(feature = "abcipp")]
-            let mut txs: Vec<TxRecord> = req
-                .txs
-                .into_iter()
-                .take(number_of_new_txs)
-                .map(|tx_bytes| {
-                    if let Ok(Ok(TxType::Wrapper(_))) =
-                        Tx::try_from(tx_bytes.as_slice()).map(process_tx)
+            // decrypt the wrapper txs included in the previous blocks, up to
+            // the first one whose epoch decryption key is not known yet. The
+            // txs of the epochs whose decryption key shares were not revealed
+            // in time are undecryptable.
+            let mut decryption_keys = BTreeMap::new();
+            let decrypted_txs: Vec<TxBytes> = self
+                .storage
+                .tx_queue
+                .iter()
+                .map_while(|tx| {
+                    let decrypted = match decryption_keys
+                        .entry(tx.epoch)
+                        .or_insert_with(|| self.epoch_decryption(tx.epoch))
                     {
-                        record::keep(tx_bytes)
-                    } else {
-                        record::remove(tx_bytes)
-                    }
+                        EpochDecryption::Key(privkey) => {
+                            match tx.decrypt(privkey.0.clone()) {
+                                Ok(tx) => DecryptedTx::Decrypted(tx),
+                                _ => DecryptedTx::Undecryptable(tx.clone()),
+                            }
+                        }
+                        EpochDecryption::TimedOut => {
+                            DecryptedTx::Undecryptable(tx.clone())
+                        }
+                        EpochDecryption::Pending => return None,
+                    };
+                    Some(Tx::from(decrypted).to_bytes())
                 })
                 .collect();
-            #[cfg(not(feature = "abcipp"))]
-            let mut txs: Vec<TxBytes> = req
-                .txs
+
+            // with vote extensions, the votes on Ethereum events from the
+            // last block are bundled in a protocol tx
+            #[cfg(feature = "abcipp")]
+            let protocol_txs: Vec<TxBytes> = self
+                .build_eth_events_tx(
+                    req.local_last_commit
+                        .iter()
+                        .flat_map(|commit| commit.votes.iter())
+                        .map(|vote| vote.vote_extension.clone()),
+                )
                 .into_iter()
-                .take(number_of_new_txs)
-                .filter_map(|tx_bytes| {
-                    if let Ok(Ok(TxType::Wrapper(_))) =
-                        Tx::try_from(tx_bytes.as_slice()).map(process_tx)
-                    {
-                        Some(tx_bytes)
-                    } else {
-                        None
-                    }
-                })
                 .collect();
+            #[cfg(not(feature = "abcipp"))]
+            let protocol_txs: Vec<TxBytes> = vec![];
+
+            // reserve the space for the protocol and decrypted txs, the rest
+            // of the block is available for the txs from the mempool
+            let decrypted_gas = self
+                .storage
+                .tx_queue
+                .iter()
+                .take(decrypted_txs.len())
+                .fold(0_u64, |acc, tx| {
+                    acc.saturating_add(u64::from(&tx.gas_limit))
+                });
+            let reserved_bytes = protocol_txs
+                .iter()
+                .chain(decrypted_txs.iter())
+                .fold(0_u64, |acc, tx| acc.saturating_add(tx.len() as u64));
+            let max_tx_bytes = [
+                u64::try_from(req.max_tx_bytes).ok().filter(|max| *max > 0),
+                self.block_max_tx_bytes,
+            ]
+            .into_iter()
+            .flatten()
+            .min()
+            .unwrap_or(u64::MAX);
+            let (included, excluded) = self.select_mempool_txs(
+                req.txs,
+                self.block_gas_limit.saturating_sub(decrypted_gas),
+                max_tx_bytes.saturating_sub(reserved_bytes),
+            );
 
-            // decrypt the wrapper txs included in the previous block
-            let decrypted_txs = self.storage.tx_queue.iter().map(|tx| {
-                Tx::from(match tx.decrypt(privkey) {
-                    Ok(tx) => DecryptedTx::Decrypted(tx),
-                    _ => DecryptedTx::Undecryptable(tx.clone()),
-                })
-                .to_bytes()
-            });
             #[cfg(feature = "abcipp")]
-            let mut decrypted_txs: Vec<_> =
-                decrypted_txs.map(record::add).collect();
+            {
+                included
+                    .into_iter()
+                    .map(record::keep)
+                    .chain(excluded.into_iter().map(record::remove))
+                    .chain(protocol_txs.into_iter().map(record::add))
+                    .chain(decrypted_txs.into_iter().map(record::add))
+                    .collect::<Vec<TxRecord>>()
+            }
             #[cfg(not(feature = "abcipp"))]
-            let mut decrypted_txs: Vec<_> = decrypted_txs.collect();
-
-            txs.append(&mut decrypted_txs);
-            txs
+            {
+                // the excluded txs are simply left out of the proposal
+                drop(excluded);
+                let mut txs = protocol_txs;
+                txs.extend(included);
+                txs.extend(decrypted_txs);
+                txs
+            }
         } else {
             vec![]
         };
@@ -102,6 +145,80 @@ where
             response::PrepareProposal { txs }
         }
     }
+
+    /// Select the txs from the mempool to include in the block, within the
+    /// given gas and bytes budgets. The valid protocol txs come first. The
+    /// wrappers of the current epoch are then considered in order of
+    /// decreasing fee per unit of gas and any wrapper that doesn't fit in
+    /// the remaining budgets is skipped.
+    ///
+    /// Returns the included txs in the order in which they should appear in
+    /// the block and the excluded txs, which also contain all the other
+    /// txs.
+    fn select_mempool_txs(
+        &self,
+        txs: Vec<TxBytes>,
+        mut gas_budget: u64,
+        mut bytes_budget: u64,
+    ) -> (Vec<TxBytes>, Vec<TxBytes>) {
+        let mut included = vec![];
+        let mut excluded = vec![];
+        let mut wrappers = vec![];
+        for tx_bytes in txs {
+            match Tx::try_from(tx_bytes.as_slice()).map(process_tx) {
+                Ok(Ok(TxType::Wrapper(wrapper)))
+                    if self.accepts_wrappers_of_epoch(wrapper.epoch) =>
+                {
+                    wrappers.push((wrapper, tx_bytes));
+                }
+                Ok(Ok(TxType::Protocol(protocol)))
+                    if self.validate_protocol_tx(&protocol).is_ok()
+                        && tx_bytes.len() as u64 <= bytes_budget =>
+                {
+                    bytes_budget -= tx_bytes.len() as u64;
+                    included.push(tx_bytes);
+                }
+                _ => excluded.push(tx_bytes),
+            }
+        }
+        // the sort is stable, so wrappers of equal priority keep their
+        // mempool order
+        wrappers.sort_by(|(a, _), (b, _)| self.cmp_fee_per_gas(a, b));
+
+        for (wrapper, tx_bytes) in wrappers {
+            let gas = u64::from(&wrapper.gas_limit);
+            let bytes = tx_bytes.len() as u64;
+            if gas <= gas_budget && bytes <= bytes_budget {
+                gas_budget -= gas;
+                bytes_budget -= bytes;
+                included.push(tx_bytes);
+            } else {
+                excluded.push(tx_bytes);
+            }
+        }
+        (included, excluded)
+    }
+
+    /// Order two wrapper txs by decreasing fee per unit of gas. Fees paid in
+    /// a token other than the native token cannot be compared, so these
+    /// wrappers are ordered after the ones paying in the native token.
+    fn cmp_fee_per_gas(&self, a: &WrapperTx, b: &WrapperTx) -> Ordering {
+        let native_token = &self.storage.native_token;
+        match (&a.fee.token == native_token, &b.fee.token == native_token) {
+            (true, true) => {
+                // Compare the ratios without a division. A zero gas limit is
+                // counted as a single unit of gas to keep the order total.
+                let a_gas = u64::from(&a.gas_limit).max(1) as u128;
+                let b_gas = u64::from(&b.gas_limit).max(1) as u128;
+                let a_fee = u128::from(a.fee.amount).saturating_mul(b_gas);
+                let b_fee = u128::from(b.fee.amount).saturating_mul(a_gas);
+                b_fee.cmp(&a_fee)
+            }
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => Ordering::Equal,
+        }
+    }
 }
 
 /// Functions for creating the appropriate TxRecord given the
@@ -141,10 +258,12 @@ pub(super) mod record {
 mod test_prepare_proposal {
     use borsh::BorshSerialize;
     use namada::types::storage::Epoch;
-    use namada::types::transaction::{Fee, WrapperTx};
+    use namada::types::transaction::{DecryptionKey, Fee, WrapperTx};
 
     use super::*;
-    use crate::node::ledger::shell::test_utils::{gen_keypair, TestShell};
+    use crate::node::ledger::shell::test_utils::{
+        gen_keypair, reveal_dkg_key_shares, simulate_dkg_session, TestShell,
+    };
 
     /// Test that if a tx from the mempool is not a
     /// WrapperTx type, it is not included in the
@@ -155,6 +274,8 @@ mod test_prepare_proposal {
         let tx = Tx::new(
             "wasm_code".as_bytes().to_owned(),
             Some("transaction_data".as_bytes().to_owned()),
+            shell.chain_id.clone(),
+            None,
         );
         let req = RequestPrepareProposal {
             txs: vec![tx.to_bytes()],
@@ -180,6 +301,8 @@ mod test_prepare_proposal {
         let tx = Tx::new(
             "wasm_code".as_bytes().to_owned(),
             Some("transaction_data".as_bytes().to_owned()),
+            shell.chain_id.clone(),
+            None,
         );
         // an unsigned wrapper will cause an error in processing
         let wrapper = Tx::new(
@@ -199,6 +322,8 @@ mod test_prepare_proposal {
                 .try_to_vec()
                 .expect("Test failed"),
             ),
+            shell.chain_id.clone(),
+            None,
         )
         .to_bytes();
         #[allow(clippy::redundant_clone)]
@@ -237,6 +362,8 @@ mod test_prepare_proposal {
             let tx = Tx::new(
                 "wasm_code".as_bytes().to_owned(),
                 Some(format!("transaction data: {}", i).as_bytes().to_owned()),
+                shell.chain_id.clone(),
+                None,
             );
             expected_decrypted
                 .push(Tx::from(DecryptedTx::Decrypted(tx.clone())));
@@ -251,7 +378,9 @@ mod test_prepare_proposal {
                 tx,
                 Default::default(),
             );
-            let wrapper = wrapper_tx.sign(&keypair).expect("Test failed");
+            let wrapper = wrapper_tx
+                .sign(&keypair, shell.chain_id.clone(), None)
+                .expect("Test failed");
             shell.enqueue_tx(wrapper_tx);
             expected_wrapper.push(wrapper.clone());
             req.txs.push(wrapper.to_bytes());
@@ -310,4 +439,179 @@ mod test_prepare_proposal {
             assert_eq!(received, expected_txs);
         }
     }
+
+    /// Test that the new wrapper txs are included in order of decreasing
+    /// fee per unit of gas and that the wrappers that don't fit in the gas
+    /// budget of the block are left out
+    #[test]
+    fn test_wrappers_prioritized_by_fee_per_gas() {
+        let (mut shell, _) = TestShell::new();
+        let keypair = gen_keypair();
+        // the gas budget fits exactly three units of gas resolution
+        shell.block_gas_limit = 3_000_000;
+        let mut wrappers = vec![];
+        // (fee amount, gas limit) of the wrappers, in mempool order
+        let fees: [(u64, u64); 3] =
+            [(10, 1_000_000), (30, 1_000_000), (40, 2_000_000)];
+        for (i, (fee, gas_limit)) in fees.into_iter().enumerate() {
+            let tx = Tx::new(
+                "wasm_code".as_bytes().to_owned(),
+                Some(format!("transaction data: {}", i).as_bytes().to_owned()),
+                shell.chain_id.clone(),
+                None,
+            );
+            let wrapper = WrapperTx::new(
+                Fee {
+                    amount: fee.into(),
+                    token: shell.storage.native_token.clone(),
+                },
+                &keypair,
+                Epoch(0),
+                gas_limit.into(),
+                tx,
+                Default::default(),
+            )
+            .sign(&keypair, shell.chain_id.clone(), None)
+            .expect("Test failed");
+            wrappers.push(wrapper);
+        }
+        let req = RequestPrepareProposal {
+            txs: wrappers.iter().map(|tx| tx.to_bytes()).collect(),
+            max_tx_bytes: 0,
+            ..Default::default()
+        };
+        // the second wrapper pays the most per unit of gas, then the third,
+        // while the first one no longer fits in the block
+        #[cfg(feature = "abcipp")]
+        assert_eq!(
+            shell.prepare_proposal(req).tx_records,
+            vec![
+                record::keep(wrappers[1].to_bytes()),
+                record::keep(wrappers[2].to_bytes()),
+                record::remove(wrappers[0].to_bytes()),
+            ]
+        );
+        #[cfg(not(feature = "abcipp"))]
+        assert_eq!(
+            shell.prepare_proposal(req).txs,
+            vec![wrappers[1].to_bytes(), wrappers[2].to_bytes()]
+        );
+    }
+
+    /// Test that the wrapper txs encrypted with the key of a DKG session are
+    /// only decrypted once all the participants of the session have
+    /// revealed their decryption key shares
+    #[test]
+    fn test_decryption_awaits_all_dkg_key_shares() {
+        let (mut shell, _) = TestShell::new();
+        let keypair = gen_keypair();
+        let participants = simulate_dkg_session(&mut shell, Epoch(0), 3);
+        let tx = Tx::new(
+            "wasm_code".as_bytes().to_owned(),
+            Some("transaction data".as_bytes().to_owned()),
+            shell.chain_id.clone(),
+            None,
+        );
+        let wrapper = WrapperTx::new(
+            Fee {
+                amount: 0.into(),
+                token: shell.storage.native_token.clone(),
+            },
+            &keypair,
+            Epoch(0),
+            0.into(),
+            tx.clone(),
+            shell.epoch_encryption_key(Epoch(0)),
+        );
+        // the default key can no longer decrypt the wrapped tx
+        assert!(wrapper.decrypt(DecryptionKey::default().0).is_err());
+        shell.enqueue_tx(wrapper);
+
+        // the mempool is empty, so only the decrypted txs get proposed
+        let proposed_txs = |shell: &TestShell| -> Vec<Vec<u8>> {
+            let req = RequestPrepareProposal {
+                txs: vec![],
+                max_tx_bytes: 0,
+                ..Default::default()
+            };
+            #[cfg(feature = "abcipp")]
+            let txs: Vec<TxBytes> = shell
+                .prepare_proposal(req)
+                .tx_records
+                .into_iter()
+                .map(|record| record.tx)
+                .collect();
+            #[cfg(not(feature = "abcipp"))]
+            let txs = shell.prepare_proposal(req).txs;
+            txs.into_iter()
+                .map(|tx_bytes| {
+                    Tx::try_from(tx_bytes.as_slice())
+                        .expect("Test failed")
+                        .data
+                        .expect("Test failed")
+                })
+                .collect()
+        };
+
+        reveal_dkg_key_shares(&mut shell, Epoch(0), &participants[..2]);
+        assert!(proposed_txs(&shell).is_empty());
+
+        reveal_dkg_key_shares(&mut shell, Epoch(0), &participants[2..]);
+        let expected = Tx::from(DecryptedTx::Decrypted(tx))
+            .data
+            .expect("Test failed");
+        assert_eq!(proposed_txs(&shell), vec![expected]);
+    }
+
+    /// Test that the wrapper txs of an epoch are proposed as undecryptable
+    /// once the reveal period of its DKG session is over, if one of the
+    /// participants never revealed its decryption key share
+    #[test]
+    fn test_undecryptable_if_dkg_key_share_never_revealed() {
+        let (mut shell, _) = TestShell::new();
+        let keypair = gen_keypair();
+        let participants = simulate_dkg_session(&mut shell, Epoch(0), 3);
+        let tx = Tx::new(
+            "wasm_code".as_bytes().to_owned(),
+            Some("transaction data".as_bytes().to_owned()),
+            shell.chain_id.clone(),
+            None,
+        );
+        let wrapper = WrapperTx::new(
+            Fee {
+                amount: 0.into(),
+                token: shell.storage.native_token.clone(),
+            },
+            &keypair,
+            Epoch(0),
+            0.into(),
+            tx,
+            shell.epoch_encryption_key(Epoch(0)),
+        );
+        shell.enqueue_tx(wrapper.clone());
+        // the last participant never reveals its share
+        reveal_dkg_key_shares(&mut shell, Epoch(0), &participants[..2]);
+
+        let req = RequestPrepareProposal {
+            txs: vec![],
+            max_tx_bytes: 0,
+            ..Default::default()
+        };
+        // the shares can still be revealed in the next epoch
+        shell.storage.block.epoch = Epoch(1);
+        #[cfg(feature = "abcipp")]
+        assert!(shell.prepare_proposal(req.clone()).tx_records.is_empty());
+        #[cfg(not(feature = "abcipp"))]
+        assert!(shell.prepare_proposal(req.clone()).txs.is_empty());
+
+        shell.storage.block.epoch = Epoch(2);
+        let expected = Tx::from(DecryptedTx::Undecryptable(wrapper)).to_bytes();
+        #[cfg(feature = "abcipp")]
+        assert_eq!(
+            shell.prepare_proposal(req).tx_records,
+            vec![record::add(expected)]
+        );
+        #[cfg(not(feature = "abcipp"))]
+        assert_eq!(shell.prepare_proposal(req).txs, vec![expected]);
+    }
 }
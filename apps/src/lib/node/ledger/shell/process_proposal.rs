@@ -1,6 +1,9 @@
 //! Implementation of the ['VerifyHeader`], [`ProcessProposal`],
 //! and [`RevertProposal`] ABCI++ methods for the Shell
 
+use namada::types::transaction::DecryptionKey;
+
+use super::dkg::EpochDecryption;
 use super::*;
 use crate::facade::tendermint_proto::abci::response_process_proposal::ProposalStatus;
 use crate::facade::tendermint_proto::abci::RequestProcessProposal;
@@ -27,10 +30,20 @@ where
         &self,
         req: RequestProcessProposal,
     ) -> ProcessProposal {
-        let tx_results = self.process_txs(&req.txs);
+        // If the proposal doesn't carry a valid time, fall back to the local
+        // time to check the txs expiration
+        let block_time = req
+            .time
+            .and_then(|time| DateTimeUtc::try_from(time).ok())
+            .unwrap_or_else(DateTimeUtc::now);
+        let tx_results = self.process_txs(&req.txs, block_time);
 
         ProcessProposal {
-            status: if tx_results.iter().any(|res| res.code > 3) {
+            status: if tx_results.iter().any(|res| {
+                !ErrorCodes::from_u32(res.code)
+                    .map(|code| code.is_recoverable())
+                    .unwrap_or_default()
+            }) {
                 ProposalStatus::Reject as i32
             } else {
                 ProposalStatus::Accept as i32
@@ -39,12 +52,17 @@ where
         }
     }
 
-    /// Check all the given txs.
-    pub fn process_txs(&self, txs: &[Vec<u8>]) -> Vec<TxResult> {
+    /// Check all the given txs. The expiration of the txs is checked against
+    /// the given block time.
+    pub fn process_txs(
+        &self,
+        txs: &[Vec<u8>],
+        block_time: DateTimeUtc,
+    ) -> Vec<TxResult> {
         let mut tx_queue_iter = self.storage.tx_queue.iter();
         txs.iter()
             .map(|tx_bytes| {
-                self.process_single_tx(tx_bytes, &mut tx_queue_iter)
+                self.process_single_tx(tx_bytes, &mut tx_queue_iter, block_time)
             })
             .collect()
     }
@@ -56,6 +74,14 @@ where
     /// is in fact so. Also checks that decrypted txs were submitted in
     /// correct order.
     ///
+    /// Checks that the wrapper and inner txs are meant for this chain, that
+    /// they have not expired and that they have not been applied before.
+    ///
+    /// Checks that a wrapper tx is encrypted for an epoch whose decryption key
+    /// cannot have been revealed yet, that decrypted txs are only proposed
+    /// once the decryption key of their epoch is known and that protocol txs
+    /// can be applied.
+    ///
     /// Error codes:
     ///   0: Ok
     ///   1: Invalid tx
@@ -63,6 +89,10 @@ where
     ///   3: Wasm runtime error
     ///   4: Invalid order of decrypted txs
     ///   5. More decrypted txs than expected
+    ///   6. Undecryptable tx
+    ///   7. Replayed tx
+    ///   8. Invalid chain ID
+    ///   9. Expired tx
     ///
     /// INVARIANT: Any changes applied in this method must be reverted if the
     /// proposal is rejected (unless we can simply overwrite them in the
@@ -71,6 +101,7 @@ where
         &self,
         tx_bytes: &[u8],
         tx_queue_iter: &mut impl Iterator<Item = &'a WrapperTx>,
+        block_time: DateTimeUtc,
     ) -> TxResult {
         let tx = match Tx::try_from(tx_bytes) {
             Ok(tx) => tx,
@@ -82,8 +113,10 @@ where
                 };
             }
         };
-        // TODO: This should not be hardcoded
-        let privkey = <EllipticCurve as PairingEngine>::G2Affine::prime_subgroup_generator();
+        // The chain ID and expiration of a wrapper tx are only found on the
+        // outer tx
+        let chain_and_expiration =
+            self.validate_tx_chain_and_expiration(&tx, block_time);
 
         match process_tx(tx) {
             // This occurs if the wrapper / protocol tx signature is invalid
@@ -99,12 +132,25 @@ where
                            are not supported"
                         .into(),
                 },
-                TxType::Protocol(_) => TxResult {
-                    code: ErrorCodes::InvalidTx.into(),
-                    info: "Protocol transactions are a fun new feature that \
-                           is coming soon to a blockchain near you. Patience."
-                        .into(),
-                },
+                TxType::Protocol(tx) => {
+                    if let Err(result) = chain_and_expiration {
+                        result
+                    } else if let Err(err) = self.validate_protocol_tx(&tx) {
+                        TxResult {
+                            code: ErrorCodes::InvalidTx.into(),
+                            info: format!(
+                                "Invalid protocol transaction: {}",
+                                err
+                            ),
+                        }
+                    } else {
+                        TxResult {
+                            code: ErrorCodes::Ok.into(),
+                            info: "Process Proposal accepted this transaction"
+                                .into(),
+                        }
+                    }
+                }
                 TxType::Decrypted(tx) => match tx_queue_iter.next() {
                     Some(wrapper) => {
                         if wrapper.tx_hash != tx.hash_commitment() {
@@ -115,19 +161,41 @@ where
                                        determined in the previous block"
                                     .into(),
                             }
-                        } else if verify_decrypted_correctly(&tx, privkey) {
-                            TxResult {
-                                code: ErrorCodes::Ok.into(),
-                                info: "Process Proposal accepted this \
-                                       transaction"
-                                    .into(),
-                            }
                         } else {
-                            TxResult {
-                                code: ErrorCodes::InvalidTx.into(),
-                                info: "The encrypted payload of tx was \
-                                       incorrectly marked as un-decryptable"
-                                    .into(),
+                            match self.epoch_decryption(wrapper.epoch) {
+                                EpochDecryption::Key(privkey) => self
+                                    .validate_decrypted_tx(
+                                        &tx, privkey, block_time,
+                                    ),
+                                EpochDecryption::TimedOut => {
+                                    if let DecryptedTx::Undecryptable(_) = &tx {
+                                        TxResult {
+                                            code: ErrorCodes::Ok.into(),
+                                            info: "Process Proposal accepted \
+                                                   this transaction"
+                                                .into(),
+                                        }
+                                    } else {
+                                        TxResult {
+                                            code: ErrorCodes::InvalidTx.into(),
+                                            info: format!(
+                                                "The decryption key shares of \
+                                                 epoch {} were not revealed \
+                                                 in time, its txs must be \
+                                                 undecryptable",
+                                                wrapper.epoch
+                                            ),
+                                        }
+                                    }
+                                }
+                                EpochDecryption::Pending => TxResult {
+                                    code: ErrorCodes::InvalidTx.into(),
+                                    info: format!(
+                                        "The decryption key of epoch {} is \
+                                         not known yet",
+                                        wrapper.epoch
+                                    ),
+                                },
                             }
                         }
                     }
@@ -148,34 +216,38 @@ where
                                 hash_tx(tx_bytes)
                             ),
                         }
+                    } else if let Err(result) = chain_and_expiration {
+                        result
+                    } else if !self.accepts_wrappers_of_epoch(tx.epoch) {
+                        TxResult {
+                            code: ErrorCodes::InvalidTx.into(),
+                            info: format!(
+                                "Wrapper transactions encrypted for epoch {} \
+                                 are not accepted in this epoch",
+                                tx.epoch
+                            ),
+                        }
+                    } else if self.is_tx_applied(&hash_tx(tx_bytes)) {
+                        TxResult {
+                            code: ErrorCodes::ReplayTx.into(),
+                            info: "Wrapper transaction hash already in \
+                                   storage, replay attempt"
+                                .into(),
+                        }
+                    } else if self.is_tx_applied(&tx.tx_hash) {
+                        TxResult {
+                            code: ErrorCodes::ReplayTx.into(),
+                            info: "Inner transaction hash already in storage, \
+                                   replay attempt"
+                                .into(),
+                        }
+                    } else if let Err(result) = self.validate_wrapper_fee(&tx) {
+                        result
                     } else {
-                        // If the public key corresponds to the MASP sentinel
-                        // transaction key, then the fee payer is effectively
-                        // the MASP, otherwise derive
-                        // they payer from public key.
-                        let fee_payer = if tx.pk != masp_tx_key().ref_to() {
-                            tx.fee_payer()
-                        } else {
-                            masp()
-                        };
-                        // check that the fee payer has sufficient balance
-                        let balance =
-                            self.get_balance(&tx.fee.token, &fee_payer);
-
-                        if tx.fee.amount <= balance {
-                            TxResult {
-                                code: ErrorCodes::Ok.into(),
-                                info: "Process proposal accepted this \
-                                       transaction"
-                                    .into(),
-                            }
-                        } else {
-                            TxResult {
-                                code: ErrorCodes::InvalidTx.into(),
-                                info: "The address given does not have \
-                                       sufficient balance to pay fee"
-                                    .into(),
-                            }
+                        TxResult {
+                            code: ErrorCodes::Ok.into(),
+                            info: "Process proposal accepted this transaction"
+                                .into(),
                         }
                     }
                 }
@@ -183,6 +255,36 @@ where
         }
     }
 
+    /// Check that a decrypted tx was correctly decrypted with the given key
+    /// and, if it carries an inner tx, that it is meant for this chain and
+    /// has not expired
+    fn validate_decrypted_tx(
+        &self,
+        tx: &DecryptedTx,
+        privkey: DecryptionKey,
+        block_time: DateTimeUtc,
+    ) -> TxResult {
+        if !verify_decrypted_correctly(tx, privkey.0) {
+            return TxResult {
+                code: ErrorCodes::InvalidTx.into(),
+                info: "The encrypted payload of tx was incorrectly marked as \
+                       un-decryptable"
+                    .into(),
+            };
+        }
+        if let DecryptedTx::Decrypted(inner) = tx {
+            if let Err(result) =
+                self.validate_tx_chain_and_expiration(inner, block_time)
+            {
+                return result;
+            }
+        }
+        TxResult {
+            code: ErrorCodes::Ok.into(),
+            info: "Process Proposal accepted this transaction".into(),
+        }
+    }
+
     pub fn revert_proposal(
         &mut self,
         _req: shim::request::RevertProposal,
@@ -208,7 +310,8 @@ mod test_process_proposal {
     use crate::facade::tendermint_proto::abci::RequestInitChain;
     use crate::facade::tendermint_proto::google::protobuf::Timestamp;
     use crate::node::ledger::shell::test_utils::{
-        gen_keypair, ProcessProposal, TestError, TestShell,
+        gen_keypair, reveal_dkg_key_shares, simulate_dkg_session,
+        ProcessProposal, TestError, TestShell,
     };
 
     /// Test that if a wrapper tx is not signed, it is rejected
@@ -220,6 +323,8 @@ mod test_process_proposal {
         let tx = Tx::new(
             "wasm_code".as_bytes().to_owned(),
             Some("transaction data".as_bytes().to_owned()),
+            shell.chain_id.clone(),
+            None,
         );
         let wrapper = WrapperTx::new(
             Fee {
@@ -235,6 +340,8 @@ mod test_process_proposal {
         let tx = Tx::new(
             vec![],
             Some(TxType::Wrapper(wrapper).try_to_vec().expect("Test failed")),
+            shell.chain_id.clone(),
+            None,
         )
         .to_bytes();
         #[allow(clippy::redundant_clone)]
@@ -266,6 +373,8 @@ mod test_process_proposal {
         let tx = Tx::new(
             "wasm_code".as_bytes().to_owned(),
             Some("transaction data".as_bytes().to_owned()),
+            shell.chain_id.clone(),
+            None,
         );
         let timestamp = tx.timestamp;
         let mut wrapper = WrapperTx::new(
@@ -279,11 +388,12 @@ mod test_process_proposal {
             tx,
             Default::default(),
         )
-        .sign(&keypair)
+        .sign(&keypair, shell.chain_id.clone(), None)
         .expect("Test failed");
         let new_tx = if let Some(Ok(SignedTxData {
             data: Some(data),
             sig,
+            ..
         })) = wrapper
             .data
             .take()
@@ -309,11 +419,14 @@ mod test_process_proposal {
                     SignedTxData {
                         sig,
                         data: Some(new_data),
+                        sigs: Default::default(),
                     }
                     .try_to_vec()
                     .expect("Test failed"),
                 ),
                 timestamp,
+                chain_id: shell.chain_id.clone(),
+                expiration: None,
             }
         } else {
             panic!("Test failed");
@@ -349,6 +462,8 @@ mod test_process_proposal {
         let tx = Tx::new(
             "wasm_code".as_bytes().to_owned(),
             Some("transaction data".as_bytes().to_owned()),
+            shell.chain_id.clone(),
+            None,
         );
         let wrapper = WrapperTx::new(
             Fee {
@@ -361,7 +476,7 @@ mod test_process_proposal {
             tx,
             Default::default(),
         )
-        .sign(&keypair)
+        .sign(&keypair, shell.chain_id.clone(), None)
         .expect("Test failed");
         let request = ProcessProposal {
             txs: vec![wrapper.to_bytes()],
@@ -402,6 +517,8 @@ mod test_process_proposal {
         let tx = Tx::new(
             "wasm_code".as_bytes().to_owned(),
             Some("transaction data".as_bytes().to_owned()),
+            shell.chain_id.clone(),
+            None,
         );
         let wrapper = WrapperTx::new(
             Fee {
@@ -414,7 +531,7 @@ mod test_process_proposal {
             tx,
             Default::default(),
         )
-        .sign(&keypair)
+        .sign(&keypair, shell.chain_id.clone(), None)
         .expect("Test failed");
 
         let request = ProcessProposal {
@@ -439,6 +556,54 @@ mod test_process_proposal {
         );
     }
 
+    /// Test that a wrapper tx signed with the MASP sentinel key is rejected,
+    /// as its fee would be paid from the shielded pool
+    #[test]
+    fn test_wrapper_paid_by_masp_rejected() {
+        let (mut shell, _) = TestShell::new();
+        let keypair = masp_tx_key();
+        let tx = Tx::new(
+            "wasm_code".as_bytes().to_owned(),
+            Some("transaction data".as_bytes().to_owned()),
+            shell.chain_id.clone(),
+            None,
+        );
+        let wrapper = WrapperTx::new(
+            Fee {
+                amount: Amount::whole(1),
+                token: shell.storage.native_token.clone(),
+            },
+            &keypair,
+            Epoch(0),
+            0.into(),
+            tx,
+            Default::default(),
+        )
+        .sign(&keypair, shell.chain_id.clone(), None)
+        .expect("Test failed");
+
+        let request = ProcessProposal {
+            txs: vec![wrapper.to_bytes()],
+        };
+
+        let response = if let [resp] = shell
+            .process_proposal(request)
+            .expect("Test failed")
+            .as_slice()
+        {
+            resp.clone()
+        } else {
+            panic!("Test failed")
+        };
+        assert_eq!(response.result.code, u32::from(ErrorCodes::InvalidTx));
+        assert_eq!(
+            response.result.info,
+            String::from(
+                "The fee of a wrapper transaction cannot be paid by the MASP"
+            )
+        );
+    }
+
     /// Test that if the expected order of decrypted txs is
     /// validated, [`process_proposal`] rejects it
     #[test]
@@ -450,6 +615,8 @@ mod test_process_proposal {
             let tx = Tx::new(
                 "wasm_code".as_bytes().to_owned(),
                 Some(format!("transaction data: {}", i).as_bytes().to_owned()),
+                shell.chain_id.clone(),
+                None,
             );
             let wrapper = WrapperTx::new(
                 Fee {
@@ -514,6 +681,8 @@ mod test_process_proposal {
         let tx = Tx::new(
             "wasm_code".as_bytes().to_owned(),
             Some("transaction data".as_bytes().to_owned()),
+            shell.chain_id.clone(),
+            None,
         );
         let wrapper = WrapperTx::new(
             Fee {
@@ -573,6 +742,8 @@ mod test_process_proposal {
         let tx = Tx::new(
             "wasm_code".as_bytes().to_owned(),
             Some("transaction data".as_bytes().to_owned()),
+            shell.chain_id.clone(),
+            None,
         );
         let mut wrapper = WrapperTx::new(
             Fee {
@@ -668,6 +839,8 @@ mod test_process_proposal {
         let tx = Tx::new(
             "wasm_code".as_bytes().to_owned(),
             Some("transaction data".as_bytes().to_owned()),
+            shell.chain_id.clone(),
+            None,
         );
 
         let tx = Tx::from(TxType::Decrypted(DecryptedTx::Decrypted(tx)));
@@ -701,6 +874,8 @@ mod test_process_proposal {
         let tx = Tx::new(
             "wasm_code".as_bytes().to_owned(),
             Some("transaction data".as_bytes().to_owned()),
+            shell.chain_id.clone(),
+            None,
         );
         let tx = Tx::from(TxType::Raw(tx));
         let request = ProcessProposal {
@@ -724,4 +899,181 @@ mod test_process_proposal {
             ),
         );
     }
+
+    /// Test that a decrypted tx is rejected until all the participants of
+    /// the DKG session of its epoch have revealed their decryption key
+    /// shares, and that it is accepted afterwards
+    #[test]
+    fn test_decrypted_tx_awaits_epoch_decryption_key() {
+        let (mut shell, _) = TestShell::new();
+        let keypair = gen_keypair();
+        let participants = simulate_dkg_session(&mut shell, Epoch(0), 3);
+        let tx = Tx::new(
+            "wasm_code".as_bytes().to_owned(),
+            Some("transaction data".as_bytes().to_owned()),
+            shell.chain_id.clone(),
+            None,
+        );
+        let wrapper = WrapperTx::new(
+            Fee {
+                amount: 0.into(),
+                token: shell.storage.native_token.clone(),
+            },
+            &keypair,
+            Epoch(0),
+            0.into(),
+            tx.clone(),
+            shell.epoch_encryption_key(Epoch(0)),
+        );
+        shell.enqueue_tx(wrapper);
+        let request = ProcessProposal {
+            txs: vec![Tx::from(TxType::Decrypted(DecryptedTx::Decrypted(tx)))
+                .to_bytes()],
+        };
+
+        let response = if let [resp] = shell
+            .process_proposal(request.clone())
+            .expect("Test failed")
+            .as_slice()
+        {
+            resp.clone()
+        } else {
+            panic!("Test failed")
+        };
+        assert_eq!(response.result.code, u32::from(ErrorCodes::InvalidTx));
+        assert_eq!(
+            response.result.info,
+            String::from("The decryption key of epoch 0 is not known yet"),
+        );
+
+        reveal_dkg_key_shares(&mut shell, Epoch(0), &participants);
+        let response = if let [resp] = shell
+            .process_proposal(request)
+            .expect("Test failed")
+            .as_slice()
+        {
+            resp.clone()
+        } else {
+            panic!("Test failed")
+        };
+        assert_eq!(response.result.code, u32::from(ErrorCodes::Ok));
+    }
+
+    /// Test that once the reveal period of a DKG session is over, the wrapper
+    /// txs of its epoch are only accepted as undecryptable if one of the
+    /// participants never revealed its decryption key share
+    #[test]
+    fn test_only_undecryptable_accepted_if_dkg_key_share_never_revealed() {
+        let (mut shell, _) = TestShell::new();
+        let keypair = gen_keypair();
+        let participants = simulate_dkg_session(&mut shell, Epoch(0), 3);
+        let tx = Tx::new(
+            "wasm_code".as_bytes().to_owned(),
+            Some("transaction data".as_bytes().to_owned()),
+            shell.chain_id.clone(),
+            None,
+        );
+        let wrapper = WrapperTx::new(
+            Fee {
+                amount: 0.into(),
+                token: shell.storage.native_token.clone(),
+            },
+            &keypair,
+            Epoch(0),
+            0.into(),
+            tx.clone(),
+            shell.epoch_encryption_key(Epoch(0)),
+        );
+        shell.enqueue_tx(wrapper.clone());
+        // the last participant never reveals its share
+        reveal_dkg_key_shares(&mut shell, Epoch(0), &participants[..2]);
+        shell.storage.block.epoch = Epoch(2);
+
+        let request = ProcessProposal {
+            txs: vec![Tx::from(TxType::Decrypted(DecryptedTx::Decrypted(tx)))
+                .to_bytes()],
+        };
+        let response = if let [resp] = shell
+            .process_proposal(request)
+            .expect("Test failed")
+            .as_slice()
+        {
+            resp.clone()
+        } else {
+            panic!("Test failed")
+        };
+        assert_eq!(response.result.code, u32::from(ErrorCodes::InvalidTx));
+        assert_eq!(
+            response.result.info,
+            String::from(
+                "The decryption key shares of epoch 0 were not revealed in \
+                 time, its txs must be undecryptable"
+            ),
+        );
+
+        let request = ProcessProposal {
+            txs: vec![Tx::from(TxType::Decrypted(DecryptedTx::Undecryptable(
+                wrapper,
+            )))
+            .to_bytes()],
+        };
+        let response = if let [resp] = shell
+            .process_proposal(request)
+            .expect("Test failed")
+            .as_slice()
+        {
+            resp.clone()
+        } else {
+            panic!("Test failed")
+        };
+        assert_eq!(response.result.code, u32::from(ErrorCodes::Ok));
+    }
+
+    /// Test that a wrapper tx encrypted for an epoch that has not begun yet
+    /// is rejected
+    #[test]
+    fn test_wrapper_of_future_epoch_rejected() {
+        let (mut shell, _) = TestShell::new();
+        let keypair = gen_keypair();
+        let tx = Tx::new(
+            "wasm_code".as_bytes().to_owned(),
+            Some("transaction data".as_bytes().to_owned()),
+            shell.chain_id.clone(),
+            None,
+        );
+        let wrapper = WrapperTx::new(
+            Fee {
+                amount: 0.into(),
+                token: shell.storage.native_token.clone(),
+            },
+            &keypair,
+            Epoch(1),
+            0.into(),
+            tx,
+            Default::default(),
+        )
+        .sign(&keypair, shell.chain_id.clone(), None)
+        .expect("Test failed");
+        let request = ProcessProposal {
+            txs: vec![wrapper.to_bytes()],
+        };
+
+        let response = if let [resp] = shell
+            .process_proposal(request)
+            .expect("Test failed")
+            .as_slice()
+        {
+            resp.clone()
+        } else {
+            panic!("Test failed")
+        };
+        assert_eq!(response.result.code, u32::from(ErrorCodes::InvalidTx));
+        assert_eq!(
+            response.result.info,
+            String::from(
+                "Wrapper transactions encrypted for epoch 1 are not accepted \
+                 in this epoch"
+            ),
+        );
+    }
 }
@@ -0,0 +1,436 @@
+//! Snapshots of the state, with which Tendermint state syncs new nodes.
+//!
+//! A snapshot is taken after committing every block whose height is a
+//! multiple of the configured `snapshot_interval`. To not hold up the
+//! commit, it's built in the background from a checkpoint of the DB. Its
+//! encoding is split into chunks that are stored in the chain's snapshots
+//! directory, next to a metadata file with the hashes of the chunks. A node
+//! without any state accepts a snapshot offered by its peers, verifies every
+//! chunk it receives against the metadata and restores the state once all the
+//! chunks have been applied and the Merkle root of their key-vals and block
+//! metadata matches the trusted app hash of the offered height.
+
+use std::{fs, io};
+
+use namada::ledger::storage::snapshot::{
+    Snapshot, SnapshotMetadata, SNAPSHOT_CHUNK_SIZE, SNAPSHOT_FORMAT,
+};
+
+use super::*;
+use crate::facade::tendermint_proto::abci::{
+    response_apply_snapshot_chunk, response_offer_snapshot,
+    Snapshot as AbciSnapshot,
+};
+
+/// The number of the most recent snapshots that are kept
+const SNAPSHOTS_KEPT: usize = 2;
+
+/// The name of the file with the metadata of a snapshot
+const METADATA_FILE: &str = "metadata";
+
+/// The name of the directory of the checkpoint of the DB from which a snapshot
+/// is built
+const CHECKPOINT_DIR: &str = "checkpoint";
+
+/// A snapshot offered by a peer, whose chunks are being applied
+#[derive(Debug)]
+pub struct SnapshotRestore {
+    /// The metadata of the snapshot, at the offered height
+    metadata: SnapshotMetadata,
+    /// The trusted app hash of the snapshot's height
+    app_hash: Vec<u8>,
+    /// The chunks that have been applied so far, in order
+    chunks: Vec<Vec<u8>>,
+}
+
+impl<D, H> Shell<D, H>
+where
+    D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
+    H: StorageHasher + Sync + 'static,
+{
+    /// Take a snapshot of the state at the last committed block height and
+    /// remove the snapshots that are no longer kept. Must be called after a
+    /// block is committed. Only a checkpoint of the DB is created on the
+    /// commit path, from which the snapshot is built in the background. If
+    /// the previous snapshot is still being built, no snapshot is taken.
+    pub(super) fn take_snapshot(&mut self) {
+        let height = self.storage.last_height;
+        if let Some(task) = &self.snapshot_task {
+            if !task.is_finished() {
+                tracing::warn!(
+                    "Skipping the snapshot at height {}, as the previous one \
+                     is still being taken",
+                    height
+                );
+                return;
+            }
+        }
+        let checkpoint_dir = self.snapshots_dir.join(CHECKPOINT_DIR);
+        // Remove the checkpoint that may be left if the node stopped while
+        // taking a snapshot
+        let checkpoint = match fs::remove_dir_all(&checkpoint_dir) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => fs::create_dir_all(&self.snapshots_dir),
+        }
+        .and_then(|()| {
+            self.storage.db.checkpoint(&checkpoint_dir).map_err(|e| {
+                io::Error::new(io::ErrorKind::Other, e.to_string())
+            })
+        });
+        let mut checkpoint = match checkpoint {
+            Ok(checkpoint) => checkpoint,
+            Err(e) => {
+                tracing::error!(
+                    "Failed to take a snapshot at height {}: {}",
+                    height,
+                    e
+                );
+                return;
+            }
+        };
+        let snapshots_dir = self.snapshots_dir.clone();
+        self.snapshot_task = Some(std::thread::spawn(move || {
+            let result = Snapshot::from_db(&mut checkpoint)
+                .map_err(|e| {
+                    io::Error::new(io::ErrorKind::Other, e.to_string())
+                })
+                .and_then(|snapshot| write_snapshot(&snapshots_dir, &snapshot))
+                .and_then(|()| remove_old_snapshots(&snapshots_dir));
+            // The checkpoint is removed once the snapshot has been built
+            drop(checkpoint);
+            let removed = match fs::remove_dir_all(&checkpoint_dir) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            };
+            match result.and(removed) {
+                Ok(()) => {
+                    tracing::info!("Took a snapshot at height {}", height)
+                }
+                Err(e) => tracing::error!(
+                    "Failed to take a snapshot at height {}: {}",
+                    height,
+                    e
+                ),
+            }
+        }));
+    }
+
+    /// List the snapshots of the state that can be served to peers. This is
+    /// returned when ABCI sends a `list_snapshots` request.
+    pub fn list_snapshots(&self) -> response::ListSnapshots {
+        let snapshots = snapshot_heights(&self.snapshots_dir)
+            .into_iter()
+            .filter_map(|height| {
+                let metadata = self.read_snapshot_metadata(height).ok()?;
+                Some(AbciSnapshot {
+                    height,
+                    format: SNAPSHOT_FORMAT,
+                    chunks: metadata.chunk_hashes.len() as u32,
+                    hash: metadata.hash().0.to_vec(),
+                    metadata: metadata.try_to_vec().expect(
+                        "Encoding a snapshot's metadata shouldn't fail",
+                    ),
+                })
+            })
+            .collect();
+        response::ListSnapshots { snapshots }
+    }
+
+    /// Decide whether to restore the state from a snapshot offered by a
+    /// peer. Only snapshots in a known format whose metadata is at the
+    /// offered height are accepted and only if this node has no state yet.
+    pub fn offer_snapshot(
+        &mut self,
+        req: request::OfferSnapshot,
+    ) -> response::OfferSnapshot {
+        use response_offer_snapshot::Result;
+
+        let result = match req.snapshot {
+            Some(snapshot) if snapshot.format != SNAPSHOT_FORMAT => {
+                Result::RejectFormat
+            }
+            Some(snapshot) if self.storage.last_height.0 == 0 => {
+                match SnapshotMetadata::try_from_slice(&snapshot.metadata) {
+                    Ok(metadata)
+                        if metadata.hash().0.as_slice() == snapshot.hash
+                            && metadata.height.0 == snapshot.height
+                            && metadata.chunk_hashes.len()
+                                == snapshot.chunks as usize =>
+                    {
+                        tracing::info!(
+                            "Restoring the state from a snapshot at height {}",
+                            snapshot.height
+                        );
+                        self.snapshot_restore = Some(SnapshotRestore {
+                            metadata,
+                            app_hash: req.app_hash,
+                            chunks: vec![],
+                        });
+                        Result::Accept
+                    }
+                    _ => Result::Reject,
+                }
+            }
+            _ => Result::Reject,
+        };
+        response::OfferSnapshot {
+            result: result as i32,
+        }
+    }
+
+    /// Load a chunk of a snapshot to be served to a peer
+    pub fn load_snapshot_chunk(
+        &self,
+        req: request::LoadSnapshotChunk,
+    ) -> response::LoadSnapshotChunk {
+        let chunk = if req.format == SNAPSHOT_FORMAT {
+            fs::read(
+                snapshot_dir(&self.snapshots_dir, req.height)
+                    .join(req.chunk.to_string()),
+            )
+                .unwrap_or_else(|e| {
+                    tracing::error!(
+                        "Failed to load the chunk {} of the snapshot at \
+                         height {}: {}",
+                        req.chunk,
+                        req.height,
+                        e
+                    );
+                    vec![]
+                })
+        } else {
+            vec![]
+        };
+        response::LoadSnapshotChunk { chunk }
+    }
+
+    /// Apply a chunk of the snapshot that is being restored. The chunks are
+    /// applied in order and the state is restored with the last one, if the
+    /// snapshot is at the offered height.
+    pub fn apply_snapshot_chunk(
+        &mut self,
+        req: request::ApplySnapshotChunk,
+    ) -> response::ApplySnapshotChunk {
+        use response_apply_snapshot_chunk::Result;
+
+        let mut response = response::ApplySnapshotChunk::default();
+        let restore = match self.snapshot_restore.as_mut() {
+            Some(restore) => restore,
+            None => {
+                response.result = Result::Abort as i32;
+                return response;
+            }
+        };
+        let index = req.index as usize;
+        if index != restore.chunks.len()
+            || !restore.metadata.verify_chunk(index, &req.chunk)
+        {
+            tracing::info!(
+                "Rejecting the chunk {} of the snapshot from {}",
+                req.index,
+                req.sender
+            );
+            response.result = Result::Retry as i32;
+            response.refetch_chunks = vec![req.index];
+            response.reject_senders = vec![req.sender];
+            return response;
+        }
+        restore.chunks.push(req.chunk);
+        if restore.chunks.len() < restore.metadata.chunk_hashes.len() {
+            response.result = Result::Accept as i32;
+            return response;
+        }
+
+        let restore = self
+            .snapshot_restore
+            .take()
+            .expect("The snapshot being restored should be known");
+        let result = Snapshot::from_chunks(restore.chunks)
+            .map_err(|e| e.to_string())
+            .and_then(|snapshot| {
+                self.storage
+                    .restore_snapshot(
+                        snapshot,
+                        restore.metadata.height,
+                        &restore.app_hash,
+                    )
+                    .map_err(|e| e.to_string())
+            });
+        response.result = match result {
+            Ok(()) => {
+                tracing::info!(
+                    "Restored the state from a snapshot at height {}",
+                    self.storage.last_height
+                );
+                Result::Accept
+            }
+            Err(e) => {
+                tracing::error!(
+                    "Failed to restore the state from a snapshot: {}",
+                    e
+                );
+                Result::RejectSnapshot
+            }
+        } as i32;
+        response
+    }
+
+    /// Read the metadata of the snapshot at the given height
+    fn read_snapshot_metadata(
+        &self,
+        height: u64,
+    ) -> io::Result<SnapshotMetadata> {
+        let bytes = fs::read(
+            snapshot_dir(&self.snapshots_dir, height).join(METADATA_FILE),
+        )?;
+        SnapshotMetadata::try_from_slice(&bytes)
+    }
+}
+
+/// Write the chunks of a snapshot and its metadata to the snapshots
+/// directory. The metadata is written last, so that only complete snapshots
+/// are listed.
+fn write_snapshot(snapshots_dir: &Path, snapshot: &Snapshot) -> io::Result<()> {
+    let dir = snapshot_dir(snapshots_dir, snapshot.height.0);
+    fs::create_dir_all(&dir)?;
+    let (chunks, metadata) = snapshot.to_chunks(SNAPSHOT_CHUNK_SIZE);
+    for (index, chunk) in chunks.iter().enumerate() {
+        fs::write(dir.join(index.to_string()), chunk)?;
+    }
+    fs::write(dir.join(METADATA_FILE), metadata.try_to_vec()?)
+}
+
+/// Remove all but the most recent snapshots
+fn remove_old_snapshots(snapshots_dir: &Path) -> io::Result<()> {
+    let heights = snapshot_heights(snapshots_dir);
+    let old = heights.len().saturating_sub(SNAPSHOTS_KEPT);
+    for height in &heights[..old] {
+        fs::remove_dir_all(snapshot_dir(snapshots_dir, *height))?;
+    }
+    Ok(())
+}
+
+/// Get the heights of the stored snapshots, in ascending order
+fn snapshot_heights(snapshots_dir: &Path) -> Vec<u64> {
+    let mut heights: Vec<u64> = fs::read_dir(snapshots_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| {
+                    entry.ok()?.file_name().to_str()?.parse().ok()
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    heights.sort_unstable();
+    heights
+}
+
+/// Get the path to the directory of the snapshot at the given height
+fn snapshot_dir(snapshots_dir: &Path, height: u64) -> PathBuf {
+    snapshots_dir.join(height.to_string())
+}
+
+#[cfg(test)]
+mod test_snapshots {
+    use super::*;
+    use crate::node::ledger::shell::test_utils::TestShell;
+
+    /// Test that a snapshot taken by one shell can be served to and
+    /// restored by another one without any state.
+    #[test]
+    fn test_snapshot_sync() {
+        let (mut shell, _) = TestShell::new();
+        shell.snapshot_interval = Some(1);
+        let key = Key::parse("test").unwrap();
+        shell
+            .storage
+            .write(&key, "value".try_to_vec().unwrap())
+            .unwrap();
+        shell.storage.block.height = BlockHeight(1);
+        shell.commit();
+        shell
+            .snapshot_task
+            .take()
+            .expect("A snapshot should be taken")
+            .join()
+            .unwrap();
+        let app_hash = shell.storage.merkle_root().0.to_vec();
+
+        let snapshots = shell.list_snapshots().snapshots;
+        assert_eq!(snapshots.len(), 1);
+        let snapshot = snapshots[0].clone();
+        assert_eq!(snapshot.height, 1);
+
+        let (mut restored, _) = TestShell::new();
+        assert_eq!(
+            restored
+                .offer_snapshot(request::OfferSnapshot {
+                    snapshot: Some(AbciSnapshot {
+                        format: SNAPSHOT_FORMAT + 1,
+                        ..snapshot.clone()
+                    }),
+                    app_hash: app_hash.clone(),
+                })
+                .result,
+            response_offer_snapshot::Result::RejectFormat as i32
+        );
+        // the snapshot must be at the offered height
+        assert_eq!(
+            restored
+                .offer_snapshot(request::OfferSnapshot {
+                    snapshot: Some(AbciSnapshot {
+                        height: snapshot.height + 1,
+                        ..snapshot.clone()
+                    }),
+                    app_hash: app_hash.clone(),
+                })
+                .result,
+            response_offer_snapshot::Result::Reject as i32
+        );
+        assert_eq!(
+            restored
+                .offer_snapshot(request::OfferSnapshot {
+                    snapshot: Some(snapshot.clone()),
+                    app_hash,
+                })
+                .result,
+            response_offer_snapshot::Result::Accept as i32
+        );
+        for index in 0..snapshot.chunks {
+            let chunk = shell
+                .load_snapshot_chunk(request::LoadSnapshotChunk {
+                    height: snapshot.height,
+                    format: snapshot.format,
+                    chunk: index,
+                })
+                .chunk;
+            // a corrupted chunk must be fetched again
+            let response =
+                restored.apply_snapshot_chunk(request::ApplySnapshotChunk {
+                    index,
+                    chunk: vec![0; chunk.len()],
+                    sender: "peer".into(),
+                });
+            assert_eq!(
+                response.result,
+                response_apply_snapshot_chunk::Result::Retry as i32
+            );
+            assert_eq!(response.refetch_chunks, vec![index]);
+            let response =
+                restored.apply_snapshot_chunk(request::ApplySnapshotChunk {
+                    index,
+                    chunk,
+                    sender: "peer".into(),
+                });
+            assert_eq!(
+                response.result,
+                response_apply_snapshot_chunk::Result::Accept as i32
+            );
+        }
+        assert_eq!(restored.storage.last_height, BlockHeight(1));
+        assert_eq!(
+            restored.read_storage_key::<String>(&key),
+            Some("value".to_string())
+        );
+    }
+}
@@ -15,6 +15,7 @@ use namada::types::transaction::hash_tx;
 use tokio::sync::mpsc::UnboundedSender;
 use tower::Service;
 
+use super::super::storage::{LedgerDB, PersistentDB};
 use super::super::Shell;
 use super::abcipp_shim_types::shim::request::{FinalizeBlock, ProcessedTx};
 #[cfg(not(feature = "abcipp"))]
@@ -29,8 +30,11 @@ use crate::facade::tower_abci::{BoxError, Request as Req, Response as Resp};
 /// The shim makes a crude translation between the ABCI interface currently used
 /// by tendermint and the shell's interface.
 #[derive(Debug)]
-pub struct AbcippShim {
-    service: Shell,
+pub struct AbcippShim<D = PersistentDB>
+where
+    D: LedgerDB,
+{
+    service: Shell<D>,
     #[cfg(not(feature = "abcipp"))]
     begin_block_request: Option<RequestBeginBlock>,
     #[cfg(not(feature = "abcipp"))]
@@ -41,14 +45,15 @@ pub struct AbcippShim {
     )>,
 }
 
-impl AbcippShim {
+impl<D: LedgerDB> AbcippShim<D> {
     /// Create a shell with a ABCI service that passes messages to and from the
     /// shell.
     pub fn new(
         config: config::Ledger,
         wasm_dir: PathBuf,
         broadcast_sender: UnboundedSender<Vec<u8>>,
-        db_cache: &rocksdb::Cache,
+        eth_receiver: EthereumReceiver,
+        db_cache: Option<&D::Cache>,
         vp_wasm_compilation_cache: u64,
         tx_wasm_compilation_cache: u64,
         native_token: Address,
@@ -62,7 +67,8 @@ impl AbcippShim {
                     config,
                     wasm_dir,
                     broadcast_sender,
-                    Some(db_cache),
+                    eth_receiver,
+                    db_cache,
                     vp_wasm_compilation_cache,
                     tx_wasm_compilation_cache,
                     native_token,
@@ -103,8 +109,11 @@ impl AbcippShim {
                 #[cfg(feature = "abcipp")]
                 Req::FinalizeBlock(block) => {
                     let unprocessed_txs = block.txs.clone();
-                    let processing_results =
-                        self.service.process_txs(&block.txs);
+                    let mut finalize_req: FinalizeBlock = block.into();
+                    let processing_results = self.service.process_txs(
+                        &unprocessed_txs,
+                        finalize_req.header.time,
+                    );
                     let mut txs = Vec::with_capacity(unprocessed_txs.len());
                     for (result, tx) in processing_results
                         .into_iter()
@@ -112,7 +121,6 @@ impl AbcippShim {
                     {
                         txs.push(ProcessedTx { tx, result });
                     }
-                    let mut finalize_req: FinalizeBlock = block.into();
                     finalize_req.txs = txs;
                     self.service
                         .call(Request::FinalizeBlock(finalize_req))
@@ -137,8 +145,12 @@ impl AbcippShim {
                 }
                 #[cfg(not(feature = "abcipp"))]
                 Req::EndBlock(_) => {
-                    let processing_results =
-                        self.service.process_txs(&self.delivered_txs);
+                    let mut end_block_request: FinalizeBlock =
+                        self.begin_block_request.take().unwrap().into();
+                    let processing_results = self.service.process_txs(
+                        &self.delivered_txs,
+                        end_block_request.header.time,
+                    );
                     let mut txs = Vec::with_capacity(self.delivered_txs.len());
                     let mut delivered = vec![];
                     std::mem::swap(&mut self.delivered_txs, &mut delivered);
@@ -148,8 +160,6 @@ impl AbcippShim {
                     {
                         txs.push(ProcessedTx { tx, result });
                     }
-                    let mut end_block_request: FinalizeBlock =
-                        self.begin_block_request.take().unwrap().into();
                     let hash = self.get_hash();
                     end_block_request.hash = BlockHash::from(hash.clone());
                     end_block_request.txs = txs;
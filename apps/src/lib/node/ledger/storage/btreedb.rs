@@ -0,0 +1,1242 @@
+//! The storage in ordered in-memory maps, which can be persisted in an
+//! append-only log.
+//!
+//! The key-vals are stored under the same paths as in RocksDB, except for the
+//! versions of the accounts sub-spaces' key-vals, which are kept in a map
+//! sorted by the key first and then by the height. The values at past heights
+//! are read from the versions, so the diffs of the blocks aren't stored:
+//! - `height`: the last committed block height
+//! - `tx_queue`: txs to be decrypted in the next block
+//! - `next_epoch_min_start_height`: minimum block height from which the next
+//!   epoch can start
+//! - `next_epoch_min_start_time`: minimum block time from which the next epoch
+//!   can start
+//! - `pred`: predecessor values of the top-level keys of the same name
+//!   - `tx_queue`
+//!   - `next_epoch_min_start_height`
+//!   - `next_epoch_min_start_time`
+//! - `pruning`: the progress of the pruning of the state
+//! - `results/{h}`: the results of the block at height `h`
+//! - `subspace`: accounts sub-spaces
+//!   - `{address}/{dyn}`: any byte data associated with accounts
+//! - `h`: for each block at height `h`:
+//!   - `tree`: merkle tree
+//!     - `root`: root hash
+//!     - `store`: the tree's store
+//!   - `hash`: block hash
+//!   - `epoch`: block epoch
+//!   - `pred_epochs`: predecessor block epochs
+//!   - `address_gen`: established address generator
+//!   - `header`: block's header
+//!
+//! When the DB is persisted, the write operations of every change are
+//! appended to a log in the DB directory as a single record, so that the
+//! change is applied atomically when the log is replayed on opening the DB.
+//! An incomplete record at the end of the log, e.g. after a crash, is
+//! discarded. Once the log holds many more write operations than there are
+//! key-vals in the state, it's compacted to the operations that write the
+//! current state.
+//!
+//! The whole state is kept in memory, including the versions of the key-vals
+//! and the Merkle tree stores of every height that hasn't been pruned. The
+//! ledger therefore only uses the persisted DB with the `keep_recent` pruning
+//! mode, with which the memory grows with the state and the number of kept
+//! heights only, apart from the small metadata of every block.
+
+use std::collections::{btree_map, BTreeMap, BTreeSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::ops::Bound::{Excluded, Included, Unbounded};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use namada::ledger::storage::{
+    types, BlockMetadataRead, BlockStateRead, BlockStateWrite, DBIter,
+    DBWriteBatch, Error, HistoricalPrefixIter, MerkleTreeStoresRead,
+    PruningState, Result, StoreType, DB,
+};
+use namada::types::hash::Hash;
+use namada::types::storage::{
+    BlockHeight, BlockResults, Header, Key, TxQueue, KEY_SEGMENT_SEPARATOR,
+};
+use namada::types::time::DateTimeUtc;
+
+/// The name of the log file in the DB directory
+const LOG_FILE: &str = "btreedb.log";
+
+/// The log is compacted once it holds this many times more write operations
+/// than there are key-vals in the state
+const LOG_COMPACTION_RATIO: u64 = 4;
+
+/// The log isn't compacted before it holds this many write operations
+const LOG_COMPACTION_MIN_OPS: u64 = 100_000;
+
+/// The maximum number of write operations in a record of a compacted log
+const COMPACTED_RECORD_OPS: usize = 1024;
+
+/// The size in bytes of the header of a record in the log, made of the
+/// length of the record and its SHA-256 hash
+const RECORD_HEADER_LEN: usize = 8 + 32;
+
+/// A DB that keeps the state in ordered in-memory maps. With `PERSISTENT`
+/// set, the state is persisted in a log in the DB directory, otherwise it's
+/// lost when the DB is dropped.
+#[derive(Debug, Default)]
+pub struct BTreeDB<const PERSISTENT: bool> {
+    /// The key-vals, except for the versions of the account subspace
+    kvs: BTreeMap<String, Vec<u8>>,
+    /// The versions of the account subspace key-vals set at every height, or
+    /// `None` if deleted
+    history: BTreeMap<(String, BlockHeight), Option<Vec<u8>>>,
+    /// The keys of the versions set at every height
+    changes: BTreeSet<(BlockHeight, String)>,
+    /// The log in which the state is persisted, if any
+    log: Option<Mutex<Log>>,
+}
+
+/// DB Handle for batch writes.
+#[derive(Debug, Default)]
+pub struct BTreeDBWriteBatch(Vec<WriteOp>);
+
+/// A write operation on the state of a [`BTreeDB`]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
+enum WriteOp {
+    /// Set a key-val
+    Put(String, Vec<u8>),
+    /// Delete a key-val
+    Delete(String),
+    /// Set the version of an account subspace key-val at a height, or `None`
+    /// if it was deleted
+    PutVersion(String, BlockHeight, Option<Vec<u8>>),
+    /// Delete the version of an account subspace key-val at a height
+    DeleteVersion(String, BlockHeight),
+}
+
+/// An append-only log of the write operations on a [`BTreeDB`]
+#[derive(Debug)]
+struct Log {
+    /// The path to the log file
+    path: PathBuf,
+    /// The writer that appends the records to the log file
+    writer: BufWriter<File>,
+    /// The number of write operations in the log
+    ops: u64,
+}
+
+/// The path of an account subspace key-val
+fn subspace_key(key: impl std::fmt::Display) -> String {
+    format!("subspace/{}", key)
+}
+
+/// The least path that's greater than all the paths with the given prefix, if
+/// any
+fn prefix_upper_bound(prefix: &str) -> Option<String> {
+    let mut chars: Vec<char> = prefix.chars().collect();
+    while let Some(last) = chars.pop() {
+        // the surrogates aren't valid chars
+        let next = match last {
+            '\u{D7FF}' => Some('\u{E000}'),
+            last => char::from_u32(last as u32 + 1),
+        };
+        if let Some(next) = next {
+            chars.push(next);
+            return Some(chars.into_iter().collect());
+        }
+    }
+    None
+}
+
+/// Write a record of the given write operations
+fn write_record(writer: &mut impl Write, ops: &[WriteOp]) -> io::Result<()> {
+    let record = ops.try_to_vec()?;
+    writer.write_all(&(record.len() as u64).to_le_bytes())?;
+    writer.write_all(&Hash::sha256(&record).0)?;
+    writer.write_all(&record)
+}
+
+/// Read the write operations of the next record from the reader of a log
+/// with `remaining` bytes left. Returns the operations with the length of the
+/// record, or `None` if the record is incomplete or corrupted.
+fn read_record(
+    reader: &mut impl Read,
+    remaining: u64,
+) -> Option<(Vec<WriteOp>, u64)> {
+    let mut header = [0; RECORD_HEADER_LEN];
+    reader.read_exact(&mut header).ok()?;
+    let len = u64::from_le_bytes(header[..8].try_into().ok()?);
+    // A corrupted length mustn't allocate more than what's left in the log
+    let end = (RECORD_HEADER_LEN as u64).checked_add(len)?;
+    if end > remaining {
+        return None;
+    }
+    let mut record = vec![0; usize::try_from(len).ok()?];
+    reader.read_exact(&mut record).ok()?;
+    if Hash::sha256(&record).0[..] != header[8..] {
+        return None;
+    }
+    let ops = BorshDeserialize::try_from_slice(&record).ok()?;
+    Some((ops, end))
+}
+
+fn io_error(error: io::Error) -> Error {
+    Error::DBError(error.to_string())
+}
+
+impl Drop for Log {
+    fn drop(&mut self) {
+        self.sync().expect("syncing the log failed");
+    }
+}
+
+impl Log {
+    /// Append a record of the given write operations to the log
+    fn append(&mut self, ops: &[WriteOp]) -> Result<()> {
+        write_record(&mut self.writer, ops).map_err(io_error)?;
+        self.ops += ops.len() as u64;
+        Ok(())
+    }
+
+    /// Write the buffered records to the log file
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Write the buffered records to the log file and wait until they're
+    /// persisted on the disk
+    fn sync(&mut self) -> io::Result<()> {
+        self.flush()?;
+        self.writer.get_ref().sync_data()
+    }
+}
+
+impl<const PERSISTENT: bool> BTreeDB<PERSISTENT> {
+    /// Open the DB persisted in the log in the given directory and replay the
+    /// log to restore the state
+    fn open_log(path: &Path) -> Result<Self> {
+        fs::create_dir_all(path).map_err(io_error)?;
+        let log_path = path.join(LOG_FILE);
+        let log_len = match fs::metadata(&log_path) {
+            Ok(metadata) => metadata.len(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(io_error(e)),
+        };
+        let mut db = Self::default();
+        let mut offset = 0;
+        let mut ops = 0;
+        if log_len > 0 {
+            // The records are replayed one at a time, without reading the
+            // whole log in memory
+            let mut reader =
+                BufReader::new(File::open(&log_path).map_err(io_error)?);
+            while let Some((record, len)) =
+                read_record(&mut reader, log_len - offset)
+            {
+                ops += record.len() as u64;
+                record.into_iter().for_each(|op| db.apply(op));
+                offset += len;
+            }
+        }
+        if offset < log_len {
+            tracing::warn!(
+                "Discarding an incomplete record of {} bytes at the end of \
+                 the DB log {}",
+                log_len - offset,
+                log_path.to_string_lossy()
+            );
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_path)
+            .map_err(io_error)?;
+        file.set_len(offset).map_err(io_error)?;
+        db.log = Some(Mutex::new(Log {
+            path: log_path,
+            writer: BufWriter::new(file),
+            ops,
+        }));
+        db.maybe_compact_log()?;
+        Ok(db)
+    }
+
+    /// Apply a write operation to the state
+    fn apply(&mut self, op: WriteOp) {
+        match op {
+            WriteOp::Put(key, value) => {
+                self.kvs.insert(key, value);
+            }
+            WriteOp::Delete(key) => {
+                self.kvs.remove(&key);
+            }
+            WriteOp::PutVersion(key, height, value) => {
+                self.changes.insert((height, key.clone()));
+                self.history.insert((key, height), value);
+            }
+            WriteOp::DeleteVersion(key, height) => {
+                self.history.remove(&(key, height));
+                self.changes.remove(&(height, key));
+            }
+        }
+    }
+
+    /// Apply the write operations to the state atomically
+    fn write(&mut self, ops: Vec<WriteOp>) -> Result<()> {
+        if ops.is_empty() {
+            return Ok(());
+        }
+        if let Some(log) = self.log.as_mut() {
+            log.get_mut()
+                .expect("The DB log shouldn't be poisoned")
+                .append(&ops)?;
+        }
+        ops.into_iter().for_each(|op| self.apply(op));
+        Ok(())
+    }
+
+    /// Compact the log if it holds many more write operations than there are
+    /// key-vals in the state
+    fn maybe_compact_log(&mut self) -> Result<()> {
+        let state_ops = (self.kvs.len() + self.history.len()) as u64;
+        let log_ops = match self.log.as_mut() {
+            Some(log) => {
+                log.get_mut().expect("The DB log shouldn't be poisoned").ops
+            }
+            None => return Ok(()),
+        };
+        if log_ops < LOG_COMPACTION_MIN_OPS
+            || log_ops < LOG_COMPACTION_RATIO * state_ops
+        {
+            return Ok(());
+        }
+        self.compact_log()
+    }
+
+    /// Rewrite the log with the write operations that write the current state
+    /// only
+    fn compact_log(&mut self) -> Result<()> {
+        let log = match self.log.as_mut() {
+            Some(log) => {
+                log.get_mut().expect("The DB log shouldn't be poisoned")
+            }
+            None => return Ok(()),
+        };
+        let state_ops = (self.kvs.len() + self.history.len()) as u64;
+        // The compacted log replaces the log once it's fully written
+        let compacted_path = log.path.with_extension("compacted");
+        let mut writer =
+            BufWriter::new(File::create(&compacted_path).map_err(io_error)?);
+        let ops = self
+            .kvs
+            .iter()
+            .map(|(key, value)| WriteOp::Put(key.clone(), value.clone()))
+            .chain(self.history.iter().map(|((key, height), value)| {
+                WriteOp::PutVersion(key.clone(), *height, value.clone())
+            }));
+        let mut record = Vec::with_capacity(COMPACTED_RECORD_OPS);
+        for op in ops {
+            record.push(op);
+            if record.len() == COMPACTED_RECORD_OPS {
+                write_record(&mut writer, &record).map_err(io_error)?;
+                record.clear();
+            }
+        }
+        if !record.is_empty() {
+            write_record(&mut writer, &record).map_err(io_error)?;
+        }
+        let file = writer.into_inner().map_err(|e| io_error(e.into_error()))?;
+        file.sync_all().map_err(io_error)?;
+        fs::rename(&compacted_path, &log.path).map_err(io_error)?;
+
+        let file = OpenOptions::new()
+            .append(true)
+            .open(&log.path)
+            .map_err(io_error)?;
+        // The buffered records of the replaced log file are already in the
+        // compacted log
+        log.writer = BufWriter::new(file);
+        log.ops = state_ops;
+        tracing::debug!(
+            "Compacted the DB log to {} write operations",
+            state_ops
+        );
+        Ok(())
+    }
+
+    /// Read and decode the value of a key-val, if any
+    fn read_value<T: BorshDeserialize>(&self, key: &str) -> Result<Option<T>> {
+        self.kvs
+            .get(key)
+            .map(|bytes| types::decode(bytes).map_err(Error::CodingError))
+            .transpose()
+    }
+
+    /// Get the last version of an account subspace key-val set at or before
+    /// the given height, with the height of the version
+    fn read_version(
+        &self,
+        key: &str,
+        height: BlockHeight,
+    ) -> Option<(BlockHeight, Option<&Vec<u8>>)> {
+        self.history
+            .range(
+                (key.to_owned(), BlockHeight::default())
+                    ..=(key.to_owned(), height),
+            )
+            .next_back()
+            .map(|((_, version_height), value)| {
+                (*version_height, value.as_ref())
+            })
+    }
+
+    /// Get the keys of the account subspace key-vals changed at the given
+    /// height
+    fn changed_keys(&self, height: BlockHeight) -> Vec<String> {
+        self.changes
+            .range((height, String::new())..)
+            .take_while(|(change_height, _)| *change_height == height)
+            .map(|(_, key)| key.clone())
+            .collect()
+    }
+
+    /// Get the paths of the key-vals of the block at the given height
+    fn block_paths(&self, height: BlockHeight) -> Vec<String> {
+        let prefix = format!("{}/", height.raw());
+        self.kvs
+            .range(prefix.clone()..)
+            .take_while(|(path, _)| path.starts_with(&prefix))
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    /// Roll back the state by one block height. The state of the last block
+    /// is deleted and the account subspace key-vals changed in it are
+    /// restored from their versions at the previous height, which must not
+    /// have been pruned. Returns the height of the restored block.
+    pub fn rollback(&mut self) -> Result<BlockHeight> {
+        let last_height: BlockHeight = match self.read_value("height")? {
+            Some(height) => height,
+            None => {
+                return Err(Error::Temporary {
+                    error: "No block has been committed".to_string(),
+                });
+            }
+        };
+        let height = match last_height.0.checked_sub(1) {
+            Some(height) => BlockHeight(height),
+            None => {
+                return Err(Error::Temporary {
+                    error: "There is no block to roll back to".to_string(),
+                });
+            }
+        };
+        if !self.kvs.contains_key(&format!("{}/hash", height.raw())) {
+            return Err(Error::Temporary {
+                error: format!(
+                    "There is no block at the height {} to roll back to",
+                    height
+                ),
+            });
+        }
+        if self.is_pruned(height)? {
+            return Err(Error::Pruned { height });
+        }
+
+        let mut ops =
+            vec![WriteOp::Put("height".to_owned(), types::encode(&height))];
+        for key in [
+            "next_epoch_min_start_height",
+            "next_epoch_min_start_time",
+            "tx_queue",
+        ] {
+            let pred_key = format!("pred/{}", key);
+            let pred_value =
+                self.kvs.get(&pred_key).cloned().ok_or_else(|| {
+                    Error::Temporary {
+                        error: format!(
+                            "The predecessor value of {} is missing, the last \
+                             block might have already been rolled back",
+                            key
+                        ),
+                    }
+                })?;
+            ops.push(WriteOp::Put(key.to_owned(), pred_value));
+            // The predecessor of the restored value is unknown
+            ops.push(WriteOp::Delete(pred_key));
+        }
+        ops.push(WriteOp::Delete(format!("results/{}", last_height.raw())));
+        ops.extend(
+            self.block_paths(last_height)
+                .into_iter()
+                .map(WriteOp::Delete),
+        );
+        // Restore the account subspace key-vals changed in the last block
+        for key in self.changed_keys(last_height) {
+            match self.read_version(&key, height) {
+                Some((_, Some(value))) => {
+                    ops.push(WriteOp::Put(subspace_key(&key), value.clone()))
+                }
+                // the key-val was added or deleted before the previous height
+                _ => ops.push(WriteOp::Delete(subspace_key(&key))),
+            }
+            ops.push(WriteOp::DeleteVersion(key, last_height));
+        }
+        self.write(ops)?;
+        self.flush(true)?;
+        Ok(height)
+    }
+}
+
+impl<const PERSISTENT: bool> DB for BTreeDB<PERSISTENT> {
+    /// There is no cache for BTreeDB
+    type Cache = ();
+    type WriteBatch = BTreeDBWriteBatch;
+
+    fn open(
+        db_path: impl AsRef<std::path::Path>,
+        _cache: Option<&Self::Cache>,
+    ) -> Self {
+        if PERSISTENT {
+            Self::open_log(db_path.as_ref()).expect("cannot open the DB")
+        } else {
+            Self::default()
+        }
+    }
+
+    fn flush(&self, wait: bool) -> Result<()> {
+        if let Some(log) = &self.log {
+            let mut log = log.lock().expect("The DB log shouldn't be poisoned");
+            let result = if wait { log.sync() } else { log.flush() };
+            result.map_err(io_error)?;
+        }
+        Ok(())
+    }
+
+    fn read_last_block(&mut self) -> Result<Option<BlockStateRead>> {
+        // Block height
+        let height: BlockHeight = match self.read_value("height")? {
+            Some(height) => height,
+            None => return Ok(None),
+        };
+
+        // Block results
+        let results_path = format!("results/{}", height.raw());
+        let results: BlockResults = match self.read_value(&results_path)? {
+            Some(results) => results,
+            None => return Ok(None),
+        };
+
+        // Epoch start height and time
+        let next_epoch_min_start_height: BlockHeight =
+            match self.read_value("next_epoch_min_start_height")? {
+                Some(height) => height,
+                None => {
+                    tracing::error!(
+                        "Couldn't load next epoch start height from the DB"
+                    );
+                    return Ok(None);
+                }
+            };
+        let next_epoch_min_start_time: DateTimeUtc =
+            match self.read_value("next_epoch_min_start_time")? {
+                Some(time) => time,
+                None => {
+                    tracing::error!(
+                        "Couldn't load next epoch start time from the DB"
+                    );
+                    return Ok(None);
+                }
+            };
+        let tx_queue: TxQueue = match self.read_value("tx_queue")? {
+            Some(tx_queue) => tx_queue,
+            None => {
+                tracing::error!("Couldn't load tx queue from the DB");
+                return Ok(None);
+            }
+        };
+
+        // Load data at the height
+        let prefix = format!("{}/", height.raw());
+        let mut merkle_tree_stores = MerkleTreeStoresRead::default();
+        let mut hash = None;
+        let mut epoch = None;
+        let mut pred_epochs = None;
+        let mut address_gen = None;
+        for (path, bytes) in self
+            .kvs
+            .range(prefix.clone()..)
+            .take_while(|(path, _)| path.starts_with(&prefix))
+        {
+            let segments: Vec<&str> =
+                path.split(KEY_SEGMENT_SEPARATOR).collect();
+            match segments.get(1) {
+                Some(prefix) => match *prefix {
+                    "tree" => match segments.get(2) {
+                        Some(s) => {
+                            let st = StoreType::from_str(s)?;
+                            match segments.get(3) {
+                                Some(&"root") => merkle_tree_stores.set_root(
+                                    &st,
+                                    types::decode(bytes)
+                                        .map_err(Error::CodingError)?,
+                                ),
+                                Some(&"store") => merkle_tree_stores
+                                    .set_store(st.decode_store(bytes)?),
+                                _ => unknown_key_error(path)?,
+                            }
+                        }
+                        None => unknown_key_error(path)?,
+                    },
+                    "header" => {
+                        // the block header doesn't have to be restored
+                    }
+                    "hash" => {
+                        hash = Some(
+                            types::decode(bytes).map_err(Error::CodingError)?,
+                        )
+                    }
+                    "epoch" => {
+                        epoch = Some(
+                            types::decode(bytes).map_err(Error::CodingError)?,
+                        )
+                    }
+                    "pred_epochs" => {
+                        pred_epochs = Some(
+                            types::decode(bytes).map_err(Error::CodingError)?,
+                        )
+                    }
+                    "address_gen" => {
+                        address_gen = Some(
+                            types::decode(bytes).map_err(Error::CodingError)?,
+                        );
+                    }
+                    _ => unknown_key_error(path)?,
+                },
+                None => unknown_key_error(path)?,
+            }
+        }
+        match (hash, epoch, pred_epochs, address_gen) {
+            (Some(hash), Some(epoch), Some(pred_epochs), Some(address_gen)) => {
+                Ok(Some(BlockStateRead {
+                    merkle_tree_stores,
+                    hash,
+                    height,
+                    epoch,
+                    pred_epochs,
+                    results,
+                    next_epoch_min_start_height,
+                    next_epoch_min_start_time,
+                    address_gen,
+                    tx_queue,
+                }))
+            }
+            _ => Err(Error::Temporary {
+                error: "Essential data couldn't be read from the DB"
+                    .to_string(),
+            }),
+        }
+    }
+
+    fn write_block(&mut self, state: BlockStateWrite) -> Result<()> {
+        let BlockStateWrite {
+            merkle_tree_stores,
+            header,
+            hash,
+            height,
+            epoch,
+            pred_epochs,
+            results,
+            next_epoch_min_start_height,
+            next_epoch_min_start_time,
+            address_gen,
+            tx_queue,
+        }: BlockStateWrite = state;
+        let mut ops = Vec::new();
+
+        // Epoch start height and time, and the tx queue
+        for (key, value) in [
+            (
+                "next_epoch_min_start_height",
+                types::encode(&next_epoch_min_start_height),
+            ),
+            (
+                "next_epoch_min_start_time",
+                types::encode(&next_epoch_min_start_time),
+            ),
+            ("tx_queue", types::encode(tx_queue)),
+        ] {
+            if let Some(current_value) = self.kvs.get(key) {
+                // Write the predecessor value for rollback
+                ops.push(WriteOp::Put(
+                    format!("pred/{}", key),
+                    current_value.clone(),
+                ));
+            }
+            ops.push(WriteOp::Put(key.to_owned(), value));
+        }
+
+        let prefix = height.raw();
+        // Merkle tree
+        for st in StoreType::iter() {
+            ops.push(WriteOp::Put(
+                format!("{}/tree/{}/root", prefix, st),
+                types::encode(merkle_tree_stores.root(st)),
+            ));
+            ops.push(WriteOp::Put(
+                format!("{}/tree/{}/store", prefix, st),
+                merkle_tree_stores.store(st).encode(),
+            ));
+        }
+        // Block header
+        if let Some(h) = header {
+            ops.push(WriteOp::Put(
+                format!("{}/header", prefix),
+                h.try_to_vec().expect("serialization failed"),
+            ));
+        }
+        // Block hash
+        ops.push(WriteOp::Put(
+            format!("{}/hash", prefix),
+            types::encode(&hash),
+        ));
+        // Block epoch
+        ops.push(WriteOp::Put(
+            format!("{}/epoch", prefix),
+            types::encode(&epoch),
+        ));
+        // Block results
+        ops.push(WriteOp::Put(
+            format!("results/{}", prefix),
+            types::encode(&results),
+        ));
+        // Predecessor block epochs
+        ops.push(WriteOp::Put(
+            format!("{}/pred_epochs", prefix),
+            types::encode(&pred_epochs),
+        ));
+        // Address gen
+        ops.push(WriteOp::Put(
+            format!("{}/address_gen", prefix),
+            types::encode(&address_gen),
+        ));
+        // Block height
+        ops.push(WriteOp::Put("height".to_owned(), types::encode(&height)));
+
+        self.write(ops)?;
+        self.maybe_compact_log()?;
+        // Flush without waiting
+        self.flush(false)
+    }
+
+    fn read_block_header(&self, height: BlockHeight) -> Result<Option<Header>> {
+        match self.kvs.get(&format!("{}/header", height.raw())) {
+            Some(v) => Ok(Some(
+                Header::try_from_slice(&v[..])
+                    .map_err(Error::BorshCodingError)?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    fn read_block_metadata(
+        &self,
+        height: BlockHeight,
+    ) -> Result<Option<BlockMetadataRead>> {
+        let prefix = height.raw();
+        match (
+            self.read_value(&format!("{}/hash", prefix))?,
+            self.read_value(&format!("{}/epoch", prefix))?,
+            self.read_value(&format!("{}/address_gen", prefix))?,
+        ) {
+            (Some(hash), Some(epoch), Some(address_gen)) => {
+                Ok(Some(BlockMetadataRead {
+                    hash,
+                    epoch,
+                    address_gen,
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn read_merkle_tree_stores(
+        &self,
+        height: BlockHeight,
+    ) -> Result<Option<MerkleTreeStoresRead>> {
+        let mut merkle_tree_stores = MerkleTreeStoresRead::default();
+        for st in StoreType::iter() {
+            let prefix = format!("{}/tree/{}", height.raw(), st);
+            match self.read_value(&format!("{}/root", prefix))? {
+                Some(root) => merkle_tree_stores.set_root(st, root),
+                // the added sub-trees are missing in the stores written
+                // before they existed
+                None if st.is_added() => continue,
+                None => return Ok(None),
+            }
+            match self.kvs.get(&format!("{}/store", prefix)) {
+                Some(b) => {
+                    merkle_tree_stores.set_store(st.decode_store(b)?);
+                }
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(merkle_tree_stores))
+    }
+
+    fn read_subspace_val(&self, key: &Key) -> Result<Option<Vec<u8>>> {
+        Ok(self.kvs.get(&subspace_key(key)).cloned())
+    }
+
+    fn read_subspace_val_with_height(
+        &self,
+        key: &Key,
+        height: BlockHeight,
+        _last_height: BlockHeight,
+    ) -> Result<Option<Vec<u8>>> {
+        if self.is_pruned(height)? {
+            return Err(Error::Pruned { height });
+        }
+        Ok(self
+            .read_version(&key.to_string(), height)
+            .and_then(|(_, value)| value.cloned()))
+    }
+
+    fn write_subspace_val(
+        &mut self,
+        height: BlockHeight,
+        key: &Key,
+        value: impl AsRef<[u8]>,
+    ) -> Result<i64> {
+        let mut batch = Self::batch();
+        let size_diff =
+            self.batch_write_subspace_val(&mut batch, height, key, value)?;
+        self.exec_batch(batch)?;
+        Ok(size_diff)
+    }
+
+    fn delete_subspace_val(
+        &mut self,
+        height: BlockHeight,
+        key: &Key,
+    ) -> Result<i64> {
+        let mut batch = Self::batch();
+        let prev_len =
+            self.batch_delete_subspace_val(&mut batch, height, key)?;
+        self.exec_batch(batch)?;
+        Ok(prev_len)
+    }
+
+    fn batch() -> Self::WriteBatch {
+        BTreeDBWriteBatch::default()
+    }
+
+    fn exec_batch(&mut self, batch: Self::WriteBatch) -> Result<()> {
+        self.write(batch.0)
+    }
+
+    fn batch_write_subspace_val(
+        &self,
+        batch: &mut Self::WriteBatch,
+        height: BlockHeight,
+        key: &Key,
+        value: impl AsRef<[u8]>,
+    ) -> Result<i64> {
+        let value = value.as_ref();
+        let subspace_key = subspace_key(key);
+        let size_diff = match self.kvs.get(&subspace_key) {
+            Some(prev_value) => value.len() as i64 - prev_value.len() as i64,
+            None => value.len() as i64,
+        };
+        batch.0.push(WriteOp::PutVersion(
+            key.to_string(),
+            height,
+            Some(value.to_vec()),
+        ));
+        batch.0.push(WriteOp::Put(subspace_key, value.to_vec()));
+        Ok(size_diff)
+    }
+
+    fn batch_delete_subspace_val(
+        &self,
+        batch: &mut Self::WriteBatch,
+        height: BlockHeight,
+        key: &Key,
+    ) -> Result<i64> {
+        let subspace_key = subspace_key(key);
+        // Check the length of previous value, if any
+        let prev_len = match self.kvs.get(&subspace_key) {
+            Some(prev_value) => {
+                batch.0.push(WriteOp::PutVersion(
+                    key.to_string(),
+                    height,
+                    None,
+                ));
+                prev_value.len() as i64
+            }
+            None => 0,
+        };
+        batch.0.push(WriteOp::Delete(subspace_key));
+        Ok(prev_len)
+    }
+
+    fn read_pruning_state(&self) -> Result<PruningState> {
+        Ok(self.read_value("pruning")?.unwrap_or_default())
+    }
+
+    fn write_pruning_state(&mut self, state: &PruningState) -> Result<()> {
+        self.write(vec![WriteOp::Put(
+            "pruning".to_owned(),
+            types::encode(state),
+        )])
+    }
+
+    fn prune_height(
+        &mut self,
+        height: BlockHeight,
+        merge_diffs: bool,
+    ) -> Result<()> {
+        let mut ops = Vec::new();
+
+        // Merkle tree
+        for st in StoreType::iter() {
+            for suffix in ["root", "store"] {
+                ops.push(WriteOp::Delete(format!(
+                    "{}/tree/{}/{}",
+                    height.raw(),
+                    st,
+                    suffix
+                )));
+            }
+        }
+
+        // With some lower heights kept, the versions set at the pruned height
+        // are moved to the next height, unless a new value is written at the
+        // next height, which is the latest one. Otherwise, the versions that
+        // are superseded by the ones set at the pruned height aren't needed
+        // anymore and neither are the deletions.
+        if merge_diffs {
+            let next_height = height.next_height();
+            for key in self.changed_keys(height) {
+                if let Some(value) = self.history.get(&(key.clone(), height)) {
+                    if !self.history.contains_key(&(key.clone(), next_height)) {
+                        ops.push(WriteOp::PutVersion(
+                            key.clone(),
+                            next_height,
+                            value.clone(),
+                        ));
+                    }
+                }
+                ops.push(WriteOp::DeleteVersion(key, height));
+            }
+        } else {
+            for key in self.changed_keys(height) {
+                if let Some(prev_height) = height.0.checked_sub(1) {
+                    if let Some((version_height, _)) =
+                        self.read_version(&key, BlockHeight(prev_height))
+                    {
+                        ops.push(WriteOp::DeleteVersion(
+                            key.clone(),
+                            version_height,
+                        ));
+                    }
+                }
+                if let Some(None) = self.history.get(&(key.clone(), height)) {
+                    ops.push(WriteOp::DeleteVersion(key, height));
+                }
+            }
+        }
+
+        self.write(ops)
+    }
+
+    fn is_pruned(&self, height: BlockHeight) -> Result<bool> {
+        let pruned_height = self.read_pruning_state()?.pruned_height;
+        // The pruning starts from the first height after the genesis
+        if pruned_height == BlockHeight::default() || height > pruned_height {
+            return Ok(false);
+        }
+        // The Merkle tree stores are only deleted when a height is pruned
+        let root_key =
+            format!("{}/tree/{}/root", height.raw(), StoreType::Base);
+        Ok(!self.kvs.contains_key(&root_key))
+    }
+
+    fn checkpoint(&self, _dir: &Path) -> Result<Self> {
+        // The copy is only kept in memory, without a log
+        Ok(Self {
+            kvs: self.kvs.clone(),
+            history: self.history.clone(),
+            changes: self.changes.clone(),
+            log: None,
+        })
+    }
+}
+
+impl<'iter, const PERSISTENT: bool> DBIter<'iter> for BTreeDB<PERSISTENT> {
+    type PrefixIter = BTreePrefixIterator<'iter>;
+
+    fn iter_prefix(&'iter self, prefix: &Key) -> BTreePrefixIterator<'iter> {
+        BTreePrefixIterator::new(
+            &self.kvs,
+            "subspace/",
+            subspace_key(prefix),
+            false,
+        )
+    }
+
+    fn rev_iter_prefix(&'iter self, prefix: &Key) -> Self::PrefixIter {
+        BTreePrefixIterator::new(
+            &self.kvs,
+            "subspace/",
+            subspace_key(prefix),
+            true,
+        )
+    }
+
+    fn iter_results(&'iter self) -> BTreePrefixIterator<'iter> {
+        BTreePrefixIterator::new(
+            &self.kvs,
+            "results/",
+            "results".to_owned(),
+            false,
+        )
+    }
+
+    fn iter_prefix_at_height(
+        &'iter self,
+        prefix: &Key,
+        height: BlockHeight,
+    ) -> Result<HistoricalPrefixIter> {
+        if self.is_pruned(height)? {
+            return Err(Error::Pruned { height });
+        }
+
+        let prefix = prefix.to_string();
+        let mut result = Vec::new();
+        // The versions of the keys with the prefix follow each other, starting
+        // from the versions of the prefix itself
+        let mut start = Included((prefix.clone(), BlockHeight::default()));
+        while let Some(((key, _), _)) =
+            self.history.range((start, Unbounded)).next()
+        {
+            if !key.starts_with(&prefix) {
+                break;
+            }
+            let has_prefix = prefix.is_empty()
+                || *key == prefix
+                || key[prefix.len()..].starts_with(KEY_SEGMENT_SEPARATOR);
+            if has_prefix {
+                // Find the last version of this key at or before the height
+                if let Some((_, Some(value))) = self.read_version(key, height) {
+                    let gas = key.len() + value.len();
+                    result.push((key.clone(), value.clone(), gas as _));
+                }
+            }
+            // Skip the remaining versions of this key
+            start = Excluded((key.clone(), BlockHeight(u64::MAX)));
+        }
+        Ok(result.into_iter())
+    }
+}
+
+/// A prefix iterator for the [`BTreeDB`].
+#[derive(Debug)]
+pub struct BTreePrefixIterator<'a> {
+    /// The key-vals whose paths have the prefix
+    iter: btree_map::Range<'a, String, Vec<u8>>,
+    /// The prefix of the DB paths that isn't part of the keys
+    db_prefix: String,
+    /// Is the iterator in reverse order?
+    reverse_order: bool,
+}
+
+impl<'a> BTreePrefixIterator<'a> {
+    /// Iterate the key-vals whose paths have the given prefix. The keys are
+    /// the paths without the `db_prefix`.
+    fn new(
+        kvs: &'a BTreeMap<String, Vec<u8>>,
+        db_prefix: &str,
+        prefix: String,
+        reverse_order: bool,
+    ) -> Self {
+        let upper_bound = match prefix_upper_bound(&prefix) {
+            Some(upper_bound) => Excluded(upper_bound),
+            None => Unbounded,
+        };
+        Self {
+            iter: kvs.range((Included(prefix), upper_bound)),
+            db_prefix: db_prefix.to_owned(),
+            reverse_order,
+        }
+    }
+}
+
+impl<'a> Iterator for BTreePrefixIterator<'a> {
+    type Item = (String, Vec<u8>, u64);
+
+    /// Returns the next pair and the gas cost
+    fn next(&mut self) -> Option<(String, Vec<u8>, u64)> {
+        loop {
+            let (path, val) = if self.reverse_order {
+                self.iter.next_back()?
+            } else {
+                self.iter.next()?
+            };
+            if let Some(k) = path.strip_prefix(&self.db_prefix) {
+                let gas = k.len() + val.len();
+                return Some((k.to_owned(), val.clone(), gas as _));
+            }
+        }
+    }
+}
+
+impl DBWriteBatch for BTreeDBWriteBatch {
+    fn put<K, V>(&mut self, key: K, value: V)
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let key = String::from_utf8(key.as_ref().to_vec())
+            .expect("Cannot convert from bytes to key string");
+        self.0.push(WriteOp::Put(key, value.as_ref().to_vec()))
+    }
+
+    fn delete<K: AsRef<[u8]>>(&mut self, key: K) {
+        let key = String::from_utf8(key.as_ref().to_vec())
+            .expect("Cannot convert from bytes to key string");
+        self.0.push(WriteOp::Delete(key))
+    }
+}
+
+fn unknown_key_error(key: &str) -> Result<()> {
+    Err(Error::UnknownKey {
+        key: key.to_owned(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::node::ledger::storage::{InMemoryDB, PersistentBTreeDB};
+
+    /// Write a value of an account subspace key-val at the given height
+    fn write_value<const PERSISTENT: bool>(
+        db: &mut BTreeDB<PERSISTENT>,
+        height: u64,
+        key: &Key,
+        value: Vec<u8>,
+    ) {
+        db.write_subspace_val(BlockHeight(height), key, value)
+            .unwrap();
+        db.flush(true).unwrap();
+    }
+
+    /// Test that the state persisted in the log is restored when the DB is
+    /// reopened, with the versions of the key-vals.
+    #[test]
+    fn test_log_replay() {
+        let dir = tempdir().unwrap();
+        let key = Key::parse("test").unwrap();
+        {
+            let mut db = PersistentBTreeDB::open(dir.path(), None);
+            write_value(&mut db, 1, &key, vec![1]);
+            write_value(&mut db, 2, &key, vec![2]);
+        }
+
+        let db = PersistentBTreeDB::open(dir.path(), None);
+        assert_eq!(db.read_subspace_val(&key).unwrap(), Some(vec![2]));
+        assert_eq!(
+            db.read_subspace_val_with_height(
+                &key,
+                BlockHeight(1),
+                BlockHeight(2)
+            )
+            .unwrap(),
+            Some(vec![1])
+        );
+    }
+
+    /// Test that an incomplete record at the end of the log is discarded and
+    /// that the log can still be appended to afterwards.
+    #[test]
+    fn test_log_incomplete_record() {
+        let dir = tempdir().unwrap();
+        let key = Key::parse("test").unwrap();
+        {
+            let mut db = PersistentBTreeDB::open(dir.path(), None);
+            write_value(&mut db, 1, &key, vec![1]);
+        }
+        let log_path = dir.path().join(LOG_FILE);
+        let mut record = vec![];
+        write_record(&mut record, &[WriteOp::Delete(subspace_key(&key))])
+            .unwrap();
+        let mut bytes = fs::read(&log_path).unwrap();
+        let len = bytes.len();
+        bytes.extend_from_slice(&record[..record.len() - 1]);
+        fs::write(&log_path, bytes).unwrap();
+
+        {
+            let mut db = PersistentBTreeDB::open(dir.path(), None);
+            assert_eq!(db.read_subspace_val(&key).unwrap(), Some(vec![1]));
+            assert_eq!(fs::metadata(&log_path).unwrap().len(), len as u64);
+            write_value(&mut db, 2, &key, vec![2]);
+        }
+
+        let db = PersistentBTreeDB::open(dir.path(), None);
+        assert_eq!(db.read_subspace_val(&key).unwrap(), Some(vec![2]));
+    }
+
+    /// Test that the compacted log restores the same state.
+    #[test]
+    fn test_log_compaction() {
+        let dir = tempdir().unwrap();
+        let key = Key::parse("test").unwrap();
+        {
+            let mut db = PersistentBTreeDB::open(dir.path(), None);
+            for height in 1..10 {
+                write_value(&mut db, height, &key, vec![height as u8]);
+            }
+            let log_len =
+                fs::metadata(dir.path().join(LOG_FILE)).unwrap().len();
+            db.compact_log().unwrap();
+            db.flush(true).unwrap();
+            assert!(
+                fs::metadata(dir.path().join(LOG_FILE)).unwrap().len()
+                    < log_len
+            );
+            // the log can be appended to after the compaction
+            write_value(&mut db, 10, &key, vec![10]);
+        }
+
+        let db = PersistentBTreeDB::open(dir.path(), None);
+        assert_eq!(db.read_subspace_val(&key).unwrap(), Some(vec![10]));
+        assert_eq!(
+            db.read_subspace_val_with_height(
+                &key,
+                BlockHeight(5),
+                BlockHeight(10)
+            )
+            .unwrap(),
+            Some(vec![5])
+        );
+    }
+
+    /// Test that the in-memory DB doesn't write anything to its directory.
+    #[test]
+    fn test_in_memory() {
+        let dir = tempdir().unwrap();
+        let key = Key::parse("test").unwrap();
+        {
+            let mut db = InMemoryDB::open(dir.path(), None);
+            write_value(&mut db, 1, &key, vec![1]);
+            assert_eq!(db.read_subspace_val(&key).unwrap(), Some(vec![1]));
+        }
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+
+        let db = InMemoryDB::open(dir.path(), None);
+        assert_eq!(db.read_subspace_val(&key).unwrap(), None);
+    }
+}
@@ -1,6 +1,7 @@
 //! The storage module handles both the current state in-memory and the stored
 //! state in DB.
 
+mod btreedb;
 mod rocksdb;
 
 use std::fmt;
@@ -9,15 +10,45 @@ use arse_merkle_tree::blake2b::Blake2bHasher;
 use arse_merkle_tree::traits::Hasher;
 use arse_merkle_tree::H256;
 use blake2b_rs::{Blake2b, Blake2bBuilder};
-use namada::ledger::storage::{Storage, StorageHasher};
+use namada::ledger::storage::{DBIter, Result, Storage, StorageHasher, DB};
+use namada::types::storage::BlockHeight;
 
 #[derive(Default)]
 pub struct PersistentStorageHasher(Blake2bHasher);
 
 pub type PersistentDB = rocksdb::RocksDB;
 
+/// A DB in ordered in-memory maps, persisted in a log in the DB directory
+pub type PersistentBTreeDB = btreedb::BTreeDB<true>;
+
+/// A DB in ordered in-memory maps, whose state is lost when the node stops
+pub type InMemoryDB = btreedb::BTreeDB<false>;
+
 pub type PersistentStorage = Storage<PersistentDB, PersistentStorageHasher>;
 
+/// A DB backend of the ledger, which is selected with the `db_backend` of the
+/// shell's config
+pub trait LedgerDB:
+    DB + for<'iter> DBIter<'iter> + Send + Sync + 'static
+{
+    /// Roll back the state by one block height, so that the last block is
+    /// applied again when the node is restarted. Returns the height of the
+    /// restored block.
+    fn rollback(&mut self) -> Result<BlockHeight>;
+}
+
+impl LedgerDB for PersistentDB {
+    fn rollback(&mut self) -> Result<BlockHeight> {
+        rocksdb::RocksDB::rollback(self)
+    }
+}
+
+impl<const PERSISTENT: bool> LedgerDB for btreedb::BTreeDB<PERSISTENT> {
+    fn rollback(&mut self) -> Result<BlockHeight> {
+        btreedb::BTreeDB::rollback(self)
+    }
+}
+
 impl Hasher for PersistentStorageHasher {
     fn write_bytes(&mut self, h: &[u8]) {
         self.0.write_bytes(h)
@@ -50,10 +81,17 @@ fn new_blake2b() -> Blake2b {
 
 #[cfg(test)]
 mod tests {
-    use namada::ledger::storage::types;
-    use namada::types::address;
+    use std::path::Path;
+
+    use namada::ledger::storage::{
+        types, BlockStateWrite, Error, MerkleTree, PruningMode, Sha256Hasher,
+    };
+    use namada::types::address::{self, EstablishedAddressGen};
     use namada::types::chain::ChainId;
-    use namada::types::storage::{BlockHash, BlockHeight, Key};
+    use namada::types::storage::{
+        BlockHash, BlockHeight, BlockResults, Epoch, Epochs, Key, TxQueue,
+    };
+    use namada::types::time::DateTimeUtc;
     use proptest::collection::vec;
     use proptest::prelude::*;
     use proptest::test_runner::Config;
@@ -61,16 +99,24 @@ mod tests {
 
     use super::*;
 
+    /// Open a storage in the given directory with the DB backend `D`
+    fn open_storage<D: LedgerDB>(
+        db_path: &Path,
+    ) -> Storage<D, PersistentStorageHasher> {
+        Storage::open(db_path, ChainId::default(), address::nam(), None)
+    }
+
     #[test]
     fn test_crud_value() {
+        test_crud_value_aux::<PersistentDB>();
+        test_crud_value_aux::<PersistentBTreeDB>();
+        test_crud_value_aux::<InMemoryDB>();
+    }
+
+    fn test_crud_value_aux<D: LedgerDB>() {
         let db_path =
             TempDir::new().expect("Unable to create a temporary DB directory");
-        let mut storage = PersistentStorage::open(
-            db_path.path(),
-            ChainId::default(),
-            address::nam(),
-            None,
-        );
+        let mut storage = open_storage::<D>(db_path.path());
         let key = Key::parse("key").expect("cannot parse the key string");
         let value: u64 = 1;
         let value_bytes = types::encode(&value);
@@ -108,16 +154,170 @@ mod tests {
         assert_eq!(result, None);
     }
 
+    #[test]
+    fn test_load_state() {
+        test_load_state_aux::<PersistentDB>();
+        test_load_state_aux::<PersistentBTreeDB>();
+        test_load_state_aux::<InMemoryDB>();
+    }
+
+    /// Test that a block written can be loaded back from DB.
+    fn test_load_state_aux<D: LedgerDB>() {
+        let dir = TempDir::new().unwrap();
+        let mut db = D::open(dir.path(), None);
+
+        let mut batch = D::batch();
+        let last_height = BlockHeight::default();
+        db.batch_write_subspace_val(
+            &mut batch,
+            last_height,
+            &Key::parse("test").unwrap(),
+            vec![1_u8, 1, 1, 1],
+        )
+        .unwrap();
+        db.exec_batch(batch).unwrap();
+
+        let merkle_tree = MerkleTree::<Sha256Hasher>::default();
+        let merkle_tree_stores = merkle_tree.stores();
+        let hash = BlockHash::default();
+        let epoch = Epoch::default();
+        let pred_epochs = Epochs::default();
+        let height = BlockHeight::default();
+        let next_epoch_min_start_height = BlockHeight::default();
+        let next_epoch_min_start_time = DateTimeUtc::now();
+        let address_gen = EstablishedAddressGen::new("whatever");
+        let tx_queue = TxQueue::default();
+        let results = BlockResults::default();
+        let block = BlockStateWrite {
+            merkle_tree_stores,
+            header: None,
+            hash: &hash,
+            height,
+            epoch,
+            results: &results,
+            pred_epochs: &pred_epochs,
+            next_epoch_min_start_height,
+            next_epoch_min_start_time,
+            address_gen: &address_gen,
+            tx_queue: &tx_queue,
+        };
+
+        db.write_block(block).unwrap();
+
+        let _state = db
+            .read_last_block()
+            .expect("Should be able to read last block")
+            .expect("Block should have been written");
+    }
+
+    #[test]
+    fn test_read() {
+        test_read_aux::<PersistentDB>();
+        test_read_aux::<PersistentBTreeDB>();
+        test_read_aux::<InMemoryDB>();
+    }
+
+    fn test_read_aux<D: LedgerDB>() {
+        let dir = TempDir::new().unwrap();
+        let mut db = D::open(dir.path(), None);
+
+        let key = Key::parse("test").unwrap();
+
+        let mut batch = D::batch();
+        let last_height = BlockHeight(100);
+        db.batch_write_subspace_val(
+            &mut batch,
+            last_height,
+            &key,
+            vec![1_u8, 1, 1, 1],
+        )
+        .unwrap();
+        db.exec_batch(batch).unwrap();
+
+        let mut batch = D::batch();
+        let last_height = BlockHeight(111);
+        db.batch_write_subspace_val(
+            &mut batch,
+            last_height,
+            &key,
+            vec![2_u8, 2, 2, 2],
+        )
+        .unwrap();
+        db.exec_batch(batch).unwrap();
+
+        let prev_value = db
+            .read_subspace_val_with_height(&key, BlockHeight(100), last_height)
+            .expect("read should succeed");
+        assert_eq!(prev_value, Some(vec![1_u8, 1, 1, 1]));
+
+        let latest_value =
+            db.read_subspace_val(&key).expect("read should succeed");
+        assert_eq!(latest_value, Some(vec![2_u8, 2, 2, 2]));
+    }
+
+    #[test]
+    fn test_iter_prefix_at_height() {
+        test_iter_prefix_at_height_aux::<PersistentDB>();
+        test_iter_prefix_at_height_aux::<PersistentBTreeDB>();
+        test_iter_prefix_at_height_aux::<InMemoryDB>();
+    }
+
+    /// Test iterating the key-vals with a prefix at past heights.
+    fn test_iter_prefix_at_height_aux<D: LedgerDB>() {
+        let dir = TempDir::new().unwrap();
+        let mut db = D::open(dir.path(), None);
+
+        let prefix = Key::parse("test").unwrap();
+        let key_a = prefix.push(&"a".to_owned()).unwrap();
+        let key_b = prefix.push(&"b".to_owned()).unwrap();
+        // Not in the prefix, as it doesn't match a whole key segment
+        let other_key = Key::parse("tested").unwrap();
+
+        let mut batch = D::batch();
+        for key in [&key_a, &key_b, &other_key] {
+            db.batch_write_subspace_val(
+                &mut batch,
+                BlockHeight(1),
+                key,
+                [1_u8],
+            )
+            .unwrap();
+        }
+        db.exec_batch(batch).unwrap();
+        let mut batch = D::batch();
+        db.batch_write_subspace_val(&mut batch, BlockHeight(2), &key_a, [2_u8])
+            .unwrap();
+        db.batch_delete_subspace_val(&mut batch, BlockHeight(2), &key_b)
+            .unwrap();
+        db.exec_batch(batch).unwrap();
+
+        let read = |height| {
+            db.iter_prefix_at_height(&prefix, BlockHeight(height))
+                .unwrap()
+                .map(|(key, value, _gas)| (key, value))
+                .collect::<Vec<_>>()
+        };
+        assert!(read(0).is_empty());
+        assert_eq!(
+            read(1),
+            vec![
+                (key_a.to_string(), vec![1_u8]),
+                (key_b.to_string(), vec![1_u8])
+            ]
+        );
+        assert_eq!(read(2), vec![(key_a.to_string(), vec![2_u8])]);
+    }
+
     #[test]
     fn test_commit_block() {
+        test_commit_block_aux::<PersistentDB>();
+        test_commit_block_aux::<PersistentBTreeDB>();
+    }
+
+    fn test_commit_block_aux<D: LedgerDB>() {
         let db_path =
             TempDir::new().expect("Unable to create a temporary DB directory");
-        let mut storage = PersistentStorage::open(
-            db_path.path(),
-            ChainId::default(),
-            address::nam(),
-            None,
-        );
+        let mut storage = open_storage::<D>(db_path.path());
         storage
             .begin_block(BlockHash::default(), BlockHeight(100))
             .expect("begin_block failed");
@@ -138,12 +338,7 @@ mod tests {
         drop(storage);
 
         // load the last state
-        let mut storage = PersistentStorage::open(
-            db_path.path(),
-            ChainId::default(),
-            address::nam(),
-            None,
-        );
+        let mut storage = open_storage::<D>(db_path.path());
         storage
             .load_last_state()
             .expect("loading the last state failed");
@@ -157,16 +352,98 @@ mod tests {
         assert_eq!(val.expect("no value"), value_bytes);
     }
 
+    /// Test that rolling back the last committed block restores the state
+    /// of the previous height, which can then be committed again.
+    #[test]
+    fn test_rollback() {
+        test_rollback_aux::<PersistentDB>();
+        test_rollback_aux::<PersistentBTreeDB>();
+    }
+
+    fn test_rollback_aux<D: LedgerDB>() {
+        let db_path =
+            TempDir::new().expect("Unable to create a temporary DB directory");
+        let mut storage = open_storage::<D>(db_path.path());
+        let updated = Key::parse("updated").expect("cannot parse the key");
+        let deleted = Key::parse("deleted").expect("cannot parse the key");
+        let added = Key::parse("added").expect("cannot parse the key");
+
+        storage
+            .begin_block(BlockHash::default(), BlockHeight(1))
+            .expect("begin_block failed");
+        storage
+            .write(&updated, types::encode(&1_u64))
+            .expect("write failed");
+        storage
+            .write(&deleted, types::encode(&1_u64))
+            .expect("write failed");
+        storage.commit().expect("commit failed");
+        let root = storage.merkle_root().0;
+
+        storage
+            .begin_block(BlockHash::default(), BlockHeight(2))
+            .expect("begin_block failed");
+        storage
+            .write(&updated, types::encode(&2_u64))
+            .expect("write failed");
+        storage.delete(&deleted).expect("delete failed");
+        storage
+            .write(&added, types::encode(&2_u64))
+            .expect("write failed");
+        storage.commit().expect("commit failed");
+        assert_ne!(storage.merkle_root().0, root);
+
+        // roll back the last block and load the state again
+        let height = storage.db.rollback().expect("rollback failed");
+        assert_eq!(height, BlockHeight(1));
+        drop(storage);
+        let mut storage = open_storage::<D>(db_path.path());
+        storage
+            .load_last_state()
+            .expect("loading the last state failed");
+        let (loaded_root, height) =
+            storage.get_state().expect("no block exists");
+        assert_eq!(loaded_root.0, root);
+        assert_eq!(height, 1);
+        let (val, _) = storage.read(&updated).expect("read failed");
+        assert_eq!(val, Some(types::encode(&1_u64)));
+        let (val, _) = storage.read(&deleted).expect("read failed");
+        assert_eq!(val, Some(types::encode(&1_u64)));
+        let (val, _) = storage.read(&added).expect("read failed");
+        assert_eq!(val, None);
+
+        // there's no block before the first one to roll back to
+        assert!(storage.db.rollback().is_err());
+
+        // commit the rolled back height again
+        storage
+            .begin_block(BlockHash::default(), BlockHeight(2))
+            .expect("begin_block failed");
+        storage
+            .write(&updated, types::encode(&3_u64))
+            .expect("write failed");
+        storage.commit().expect("commit failed");
+        let (val, _) = storage
+            .read_with_height(&updated, BlockHeight(1))
+            .expect("read failed");
+        assert_eq!(val, Some(types::encode(&1_u64)));
+        let (val, _) = storage
+            .read_with_height(&added, BlockHeight(2))
+            .expect("read failed");
+        assert_eq!(val, None);
+    }
+
     #[test]
     fn test_iter() {
+        test_iter_aux::<PersistentDB>();
+        test_iter_aux::<PersistentBTreeDB>();
+        test_iter_aux::<InMemoryDB>();
+    }
+
+    fn test_iter_aux<D: LedgerDB>() {
         let db_path =
             TempDir::new().expect("Unable to create a temporary DB directory");
-        let mut storage = PersistentStorage::open(
-            db_path.path(),
-            ChainId::default(),
-            address::nam(),
-            None,
-        );
+        let mut storage = open_storage::<D>(db_path.path());
         storage
             .begin_block(BlockHash::default(), BlockHeight(100))
             .expect("begin_block failed");
@@ -201,16 +478,63 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_iter_prefix_at_height_order() {
+        test_iter_prefix_at_height_order_aux::<PersistentDB>();
+        test_iter_prefix_at_height_order_aux::<PersistentBTreeDB>();
+        test_iter_prefix_at_height_order_aux::<InMemoryDB>();
+    }
+
+    /// Test that the key-vals at a past height are in the same order as the
+    /// ones from `iter_prefix`, with keys that are sorted differently by their
+    /// segments than by their strings
+    fn test_iter_prefix_at_height_order_aux<D: LedgerDB>() {
+        let db_path =
+            TempDir::new().expect("Unable to create a temporary DB directory");
+        let mut storage = open_storage::<D>(db_path.path());
+        storage
+            .begin_block(BlockHash::default(), BlockHeight(1))
+            .expect("begin_block failed");
+
+        let prefix = Key::parse("prefix").expect("cannot parse the key string");
+        // "a/b" is sorted before "a-c" by the segments, but after it by the
+        // strings
+        for sub_key in ["a", "a/b", "a-c", "b"] {
+            let key = Key::parse(format!("{}/{}", prefix, sub_key))
+                .expect("cannot parse the key string");
+            storage
+                .write(&key, types::encode(&1_u64))
+                .expect("write failed");
+        }
+        storage.commit().expect("commit failed");
+        storage
+            .begin_block(BlockHash::default(), BlockHeight(2))
+            .expect("begin_block failed");
+        storage.commit().expect("commit failed");
+
+        let (iter, _gas) = storage.iter_prefix(&prefix);
+        let expected: Vec<String> = iter.map(|(key, _, _)| key).collect();
+        let mut sorted = expected.clone();
+        sorted.sort();
+        assert_eq!(expected, sorted);
+        let (iter, _gas) = storage
+            .iter_prefix_at_height(&prefix, BlockHeight(1))
+            .expect("iter_prefix_at_height failed");
+        let keys: Vec<String> = iter.map(|(key, _, _)| key).collect();
+        assert_eq!(keys, expected);
+    }
+
     #[test]
     fn test_validity_predicate() {
+        test_validity_predicate_aux::<PersistentDB>();
+        test_validity_predicate_aux::<PersistentBTreeDB>();
+        test_validity_predicate_aux::<InMemoryDB>();
+    }
+
+    fn test_validity_predicate_aux<D: LedgerDB>() {
         let db_path =
             TempDir::new().expect("Unable to create a temporary DB directory");
-        let mut storage = PersistentStorage::open(
-            db_path.path(),
-            ChainId::default(),
-            address::nam(),
-            None,
-        );
+        let mut storage = open_storage::<D>(db_path.path());
         storage
             .begin_block(BlockHash::default(), BlockHeight(100))
             .expect("begin_block failed");
@@ -242,7 +566,12 @@ mod tests {
         })]
         #[test]
         fn test_read_with_height(blocks_write_value in vec(any::<bool>(), 20)) {
-            test_read_with_height_aux(blocks_write_value).unwrap()
+            let values = blocks_write_value;
+            test_read_with_height_aux::<PersistentDB>(values.clone())
+                .unwrap();
+            test_read_with_height_aux::<PersistentBTreeDB>(values.clone())
+                .unwrap();
+            test_read_with_height_aux::<InMemoryDB>(values).unwrap()
         }
     }
 
@@ -258,17 +587,12 @@ mod tests {
     ///    deleted.
     /// 3. We try to read past the last height and we expect the last written
     ///    value, if any.
-    fn test_read_with_height_aux(
+    fn test_read_with_height_aux<D: LedgerDB>(
         blocks_write_value: Vec<bool>,
     ) -> namada::ledger::storage::Result<()> {
         let db_path =
             TempDir::new().expect("Unable to create a temporary DB directory");
-        let mut storage = PersistentStorage::open(
-            db_path.path(),
-            ChainId::default(),
-            address::nam(),
-            None,
-        );
+        let mut storage = open_storage::<D>(db_path.path());
 
         // 1. For each `blocks_write_value`, write the current block height if
         // true or delete otherwise.
@@ -344,4 +668,105 @@ mod tests {
 
         Ok(())
     }
+
+    proptest! {
+        #![proptest_config(Config {
+            cases: 5,
+            .. Config::default()
+        })]
+        #[test]
+        fn test_prune(
+            blocks_write_value in vec(any::<Option<bool>>(), 20),
+        ) {
+            let values = blocks_write_value;
+            test_prune_aux::<PersistentDB>(values.clone()).unwrap();
+            test_prune_aux::<PersistentBTreeDB>(values.clone()).unwrap();
+            test_prune_aux::<InMemoryDB>(values).unwrap()
+        }
+    }
+
+    /// Test that after pruning, the values at the kept heights can still be
+    /// read, while reading at the pruned heights fails. At the heights with
+    /// `None`, the value is left unchanged.
+    fn test_prune_aux<D: LedgerDB>(
+        blocks_write_value: Vec<Option<bool>>,
+    ) -> namada::ledger::storage::Result<()> {
+        let db_path =
+            TempDir::new().expect("Unable to create a temporary DB directory");
+        let mut storage = open_storage::<D>(db_path.path());
+        let mode = PruningMode::KeepEvery {
+            keep_recent: 3,
+            keep_every: 4,
+        };
+
+        // Write the current block height, delete the value or leave it
+        // unchanged at every height, starting from `1`, and prune after every
+        // commit. The expected value is the height at which it was last
+        // written, if it wasn't deleted since.
+        let mut expected = None;
+        let blocks_write_value: Vec<(
+            BlockHeight,
+            Option<bool>,
+            Option<BlockHeight>,
+        )> = blocks_write_value
+            .into_iter()
+            .enumerate()
+            .map(|(index, write_value)| {
+                let height = BlockHeight::from(index as u64 + 1);
+                match write_value {
+                    Some(true) => expected = Some(height),
+                    Some(false) => expected = None,
+                    None => {}
+                }
+                (height, write_value, expected)
+            })
+            .collect();
+        let key = Key::parse("key").expect("cannot parse the key string");
+        for (height, write_value, _) in blocks_write_value.iter().copied() {
+            storage.begin_block(BlockHash::default(), height)?;
+            match write_value {
+                Some(true) => {
+                    let value_bytes = types::encode(&storage.block.height);
+                    storage.write(&key, value_bytes)?;
+                }
+                Some(false) => {
+                    storage.delete(&key)?;
+                }
+                None => {}
+            }
+            storage.commit()?;
+            storage.prune(mode)?;
+        }
+
+        let last_prunable = mode
+            .last_prunable_height(storage.last_height)
+            .expect("Some heights should be prunable");
+        for (height, _, expected) in blocks_write_value {
+            if height <= last_prunable && !mode.keeps(height) {
+                assert!(matches!(
+                    storage.read_with_height(&key, height),
+                    Err(Error::Pruned { .. })
+                ));
+                assert!(matches!(
+                    storage.get_non_existence_proof(&key, height),
+                    Err(Error::Pruned { .. })
+                ));
+                continue;
+            }
+            let (value_bytes, _gas) = storage.read_with_height(&key, height)?;
+            if let Some(expected) = expected {
+                let value_bytes = value_bytes.unwrap_or_else(|| {
+                    panic!("Couldn't read from height {height}")
+                });
+                let value: BlockHeight = types::decode(value_bytes).unwrap();
+                assert_eq!(value, expected);
+            } else if value_bytes.is_some() {
+                let value: BlockHeight =
+                    types::decode(value_bytes.unwrap()).unwrap();
+                panic!("Expected no value at height {height}, got {}", value,);
+            }
+        }
+
+        Ok(())
+    }
 }
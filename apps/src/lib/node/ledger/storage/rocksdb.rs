@@ -13,8 +13,16 @@
 //! - `pred`: predecessor values of the top-level keys of the same name
 //!   - `next_epoch_min_start_height`
 //!   - `next_epoch_min_start_time`
+//! - `pruning`: the progress of the pruning of the state
 //! - `subspace`: accounts sub-spaces
 //!   - `{address}/{dyn}`: any byte data associated with accounts
+//! - `history_start`: the lowest block height from which the history index is
+//!   complete
+//! - `history_migration`: the last key up to which the history index of a DB
+//!   created before the index existed has been built, while it's being built
+//! - `history`: versions of accounts sub-spaces' key-vals, sorted by the key
+//!   first and then by the height
+//!   - `{dyn}/{h}`: value set in block height `h`, or `None` if deleted
 //! - `h`: for each block at height `h`:
 //!   - `tree`: merkle tree
 //!     - `root`: root hash
@@ -22,26 +30,30 @@
 //!   - `hash`: block hash
 //!   - `epoch`: block epoch
 //!   - `address_gen`: established address generator
-//!   - `diffs`: diffs in account subspaces' key-vals
+//!   - `diffs`: diffs in account subspaces' key-vals, which list the changes of
+//!     the block, while the `history` finds the value of a key at a height
 //!     - `new/{dyn}`: value set in block height `h`
 //!     - `old/{dyn}`: value from predecessor block height
 //!   - `header`: block's header
 
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::path::Path;
 use std::str::FromStr;
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use namada::ledger::storage::types::PrefixIterator;
 use namada::ledger::storage::{
-    types, BlockStateRead, BlockStateWrite, DBIter, DBWriteBatch, Error,
-    MerkleTreeStoresRead, Result, StoreType, DB,
+    types, BlockMetadataRead, BlockStateRead, BlockStateWrite, DBIter,
+    DBWriteBatch, Error, HistoricalPrefixIter, MerkleTreeStoresRead,
+    PruningState, Result, StoreType, DB,
 };
 use namada::types::storage::{
     BlockHeight, BlockResults, Header, Key, KeySeg, TxQueue,
     KEY_SEGMENT_SEPARATOR,
 };
 use namada::types::time::DateTimeUtc;
+use rocksdb::checkpoint::Checkpoint;
 use rocksdb::{
     BlockBasedOptions, Direction, FlushOptions, IteratorMode, Options,
     ReadOptions, SliceTransform, WriteBatch, WriteOptions,
@@ -114,9 +126,11 @@ pub fn open(
     cf_opts.set_prefix_extractor(extractor);
     // TODO use column families
 
-    rocksdb::DB::open_cf_descriptors(&cf_opts, path, vec![])
+    let mut db = rocksdb::DB::open_cf_descriptors(&cf_opts, path, vec![])
         .map(RocksDB)
-        .map_err(|e| Error::DBError(e.into_string()))
+        .map_err(|e| Error::DBError(e.into_string()))?;
+    db.migrate_history_index()?;
+    Ok(db)
 }
 
 /// A custom key comparator is used to sort keys by the height. In
@@ -139,13 +153,50 @@ fn key_comparator(a: &[u8], b: &[u8]) -> Ordering {
                 a_h.cmp(&b_h)
             }
         }
-        _ => {
-            // the key doesn't include the height
-            a_str.cmp(b_str)
+        _ => match (split_history_key(&a_vec), split_history_key(&b_vec)) {
+            // the versions of a key follow each other, sorted by the height
+            (Some(a_version), Some(b_version)) => a_version.cmp(&b_version),
+            _ => {
+                // the key doesn't include the height
+                a_str.cmp(b_str)
+            }
+        },
+    }
+}
+
+/// Split the segments of a key in the history index into the segments of the
+/// versioned key and the height of the version.
+fn split_history_key<'a>(
+    segments: &'a [&'a str],
+) -> Option<(&'a [&'a str], u64)> {
+    match segments {
+        ["history", key @ .., height] if !key.is_empty() => {
+            Some((key, height.parse().ok()?))
         }
+        _ => None,
     }
 }
 
+/// The number of versions that are written at once when the history index of
+/// an existing DB is built
+const HISTORY_MIGRATION_CHUNK_LEN: usize = 10_000;
+
+/// The key of the version of an account subspace key-val set at the given
+/// height in the history index.
+fn history_key(key: impl std::fmt::Display, height: BlockHeight) -> String {
+    format!("history/{}/{}", key, height.raw())
+}
+
+/// Parse a key of the history index into the versioned key and the height of
+/// the version.
+fn parse_history_key(path: &[u8]) -> Option<(&str, BlockHeight)> {
+    let (key, raw_height) = std::str::from_utf8(path)
+        .ok()?
+        .strip_prefix("history/")?
+        .rsplit_once(KEY_SEGMENT_SEPARATOR)?;
+    Some((key, BlockHeight(raw_height.parse().ok()?)))
+}
+
 impl Drop for RocksDB {
     fn drop(&mut self) {
         self.flush(true).expect("flush failed");
@@ -162,7 +213,14 @@ impl RocksDB {
     }
 
     /// Persist the diff of an account subspace key-val under the height where
-    /// it was changed.
+    /// it was changed, and its new version in the history index.
+    ///
+    /// Both are needed, although they hold the same new value. The diffs are
+    /// sorted by the height first, so they list the changes of a block, which
+    /// the rollback and the pruning replay, and they're the only history of
+    /// the heights before the index starts. The index is sorted by the key
+    /// first, so that a single seek finds the value of a key at any height.
+    /// See `bench_history_index` for the cost of the extra write.
     fn write_subspace_diff(
         &mut self,
         height: BlockHeight,
@@ -195,11 +253,18 @@ impl RocksDB {
                 .put(new_val_key, new_value)
                 .map_err(|e| Error::DBError(e.into_string()))?;
         }
-        Ok(())
+
+        self.0
+            .put(
+                history_key(key, height),
+                types::encode(&new_value.map(<[u8]>::to_vec)),
+            )
+            .map_err(|e| Error::DBError(e.into_string()))
     }
 
     /// Persist the diff of an account subspace key-val under the height where
-    /// it was changed in a batch write.
+    /// it was changed, and its new version in the history index, in a batch
+    /// write. See [`RocksDB::write_subspace_diff`] for why both are written.
     fn batch_write_subspace_diff(
         batch: &mut RocksDBWriteBatch,
         height: BlockHeight,
@@ -228,6 +293,11 @@ impl RocksDB {
                 .to_string();
             batch.0.put(new_val_key, new_value);
         }
+
+        batch.0.put(
+            history_key(key, height),
+            types::encode(&new_value.map(<[u8]>::to_vec)),
+        );
         Ok(())
     }
 
@@ -238,6 +308,465 @@ impl RocksDB {
             .write_opt(batch, &write_opts)
             .map_err(|e| Error::DBError(e.into_string()))
     }
+
+    /// Read the lowest height from which the history index is complete, if
+    /// the index has been built.
+    fn read_history_start(&self) -> Result<Option<BlockHeight>> {
+        match self
+            .0
+            .get("history_start")
+            .map_err(|e| Error::DBError(e.into_string()))?
+        {
+            Some(bytes) => {
+                types::decode(bytes).map(Some).map_err(Error::CodingError)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Find the last version of an account subspace key-val in the history
+    /// index, that was set at or before the given height, with a single seek.
+    /// Returns the height of the version and its value, which is `None` if
+    /// the key-val was deleted.
+    fn seek_subspace_version(
+        &self,
+        key: impl std::fmt::Display,
+        height: BlockHeight,
+    ) -> Result<Option<(BlockHeight, Option<Vec<u8>>)>> {
+        let key_prefix = format!("history/{}/", key);
+        let mut read_opts = ReadOptions::default();
+        // don't use the prefix bloom filter
+        read_opts.set_total_order_seek(true);
+        let mut iter = self.0.raw_iterator_opt(read_opts);
+        iter.seek_for_prev(history_key(key, height));
+        iter.status().map_err(|e| Error::DBError(e.into_string()))?;
+        let (found_key, bytes) = match (iter.key(), iter.value()) {
+            (Some(found_key), Some(bytes)) => (found_key, bytes),
+            _ => return Ok(None),
+        };
+        // The seek may land on a preceding key if there's no version of this
+        // key at or before the height
+        let version_height = match std::str::from_utf8(found_key)
+            .ok()
+            .and_then(|found_key| found_key.strip_prefix(&key_prefix))
+            .and_then(|raw_height| raw_height.parse::<u64>().ok())
+        {
+            Some(raw_height) => BlockHeight(raw_height),
+            None => return Ok(None),
+        };
+        let value = types::decode(bytes).map_err(Error::CodingError)?;
+        Ok(Some((version_height, value)))
+    }
+
+    /// Read the value of an account subspace key at the given height by
+    /// looking for its diffs from the given `height`, possibly up to the
+    /// `last_height`. This is only needed for the heights before the start of
+    /// the history index.
+    fn read_subspace_val_from_diffs(
+        &self,
+        key: &Key,
+        height: BlockHeight,
+        last_height: BlockHeight,
+    ) -> Result<Option<Vec<u8>>> {
+        // Check if the value changed at this height
+        let key_prefix = Key::from(height.to_db_key())
+            .push(&"diffs".to_owned())
+            .map_err(Error::KeyError)?;
+        let new_val_key = key_prefix
+            .push(&"new".to_owned())
+            .map_err(Error::KeyError)?
+            .join(key)
+            .to_string();
+
+        // If it has a "new" val, it was written at this height
+        match self
+            .0
+            .get(new_val_key)
+            .map_err(|e| Error::DBError(e.into_string()))?
+        {
+            Some(new_val) => {
+                return Ok(Some(new_val));
+            }
+            None => {
+                let old_val_key = key_prefix
+                    .push(&"old".to_owned())
+                    .map_err(Error::KeyError)?
+                    .join(key)
+                    .to_string();
+                // If it has an "old" val, it was deleted at this height
+                if self.0.key_may_exist(old_val_key.clone()) {
+                    // check if it actually exists
+                    if self
+                        .0
+                        .get(old_val_key)
+                        .map_err(|e| Error::DBError(e.into_string()))?
+                        .is_some()
+                    {
+                        return Ok(None);
+                    }
+                }
+            }
+        }
+
+        // If the value didn't change at the given height, we try to look for it
+        // at successor heights, up to the `last_height`
+        let mut raw_height = height.0 + 1;
+        loop {
+            // Try to find the next diff on this key
+            let key_prefix = Key::from(BlockHeight(raw_height).to_db_key())
+                .push(&"diffs".to_owned())
+                .map_err(Error::KeyError)?;
+            let old_val_key = key_prefix
+                .push(&"old".to_owned())
+                .map_err(Error::KeyError)?
+                .join(key)
+                .to_string();
+            let old_val = self
+                .0
+                .get(old_val_key)
+                .map_err(|e| Error::DBError(e.into_string()))?;
+            // If it has an "old" val, it's the one we're looking for
+            match old_val {
+                Some(bytes) => return Ok(Some(bytes)),
+                None => {
+                    // Check if the value was created at this height instead,
+                    // which would mean that it wasn't present before
+                    let new_val_key = key_prefix
+                        .push(&"new".to_owned())
+                        .map_err(Error::KeyError)?
+                        .join(key)
+                        .to_string();
+                    if self.0.key_may_exist(new_val_key.clone()) {
+                        // check if it actually exists
+                        if self
+                            .0
+                            .get(new_val_key)
+                            .map_err(|e| Error::DBError(e.into_string()))?
+                            .is_some()
+                        {
+                            return Ok(None);
+                        }
+                    }
+
+                    if raw_height >= last_height.0 {
+                        // Read from latest height
+                        return self.read_subspace_val(key);
+                    } else {
+                        raw_height += 1
+                    }
+                }
+            }
+        }
+    }
+
+    /// Build the history index of a DB that was created before the index
+    /// existed. If the DB has never been pruned, the versions are replayed
+    /// from the diffs of every height. Otherwise, some of the diffs are gone
+    /// and the index starts from the last committed height, with the current
+    /// account subspace key-vals.
+    fn migrate_history_index(&mut self) -> Result<()> {
+        self.migrate_history_index_in_chunks(HISTORY_MIGRATION_CHUNK_LEN)
+    }
+
+    /// Build the history index, writing it in chunks of the given number of
+    /// versions. Every chunk is written together with the DB key up to which
+    /// the index has been built, from which the migration resumes if the node
+    /// stops before it is done.
+    fn migrate_history_index_in_chunks(
+        &mut self,
+        chunk_len: usize,
+    ) -> Result<()> {
+        if self.read_history_start()?.is_some() {
+            return Ok(());
+        }
+        let last_height: Option<BlockHeight> = match self
+            .0
+            .get("height")
+            .map_err(|e| Error::DBError(e.into_string()))?
+        {
+            Some(bytes) => {
+                Some(types::decode(bytes).map_err(Error::CodingError)?)
+            }
+            None => None,
+        };
+        let resume_from: Option<String> = match self
+            .0
+            .get("history_migration")
+            .map_err(|e| Error::DBError(e.into_string()))?
+        {
+            Some(bytes) => {
+                let path = types::decode(bytes).map_err(Error::CodingError)?;
+                tracing::info!(
+                    "Resuming to build the history index from {}...",
+                    path
+                );
+                Some(path)
+            }
+            None => None,
+        };
+        let mut read_opts = ReadOptions::default();
+        // don't use the prefix bloom filter
+        read_opts.set_total_order_seek(true);
+
+        let mut batch = WriteBatch::default();
+        let history_start = match last_height {
+            // A new DB
+            None => BlockHeight::default(),
+            Some(last_height)
+                if self.read_pruning_state()?.pruned_height
+                    == BlockHeight::default() =>
+            {
+                tracing::info!(
+                    "Building the history index from the diffs up to the \
+                     height {}...",
+                    last_height
+                );
+                let mode = match &resume_from {
+                    Some(path) => {
+                        IteratorMode::From(path.as_bytes(), Direction::Forward)
+                    }
+                    None => IteratorMode::Start,
+                };
+                for value in self.0.iterator_opt(mode, read_opts) {
+                    let (key, bytes) =
+                        value.map_err(|e| Error::DBError(e.into_string()))?;
+                    let path = match std::str::from_utf8(&key) {
+                        Ok(path) => path,
+                        Err(_) => continue,
+                    };
+                    if resume_from.as_deref() == Some(path) {
+                        continue;
+                    }
+                    let mut segments = path.splitn(4, KEY_SEGMENT_SEPARATOR);
+                    let (height, kind, sub_key) = match (
+                        segments.next().map(str::parse::<u64>),
+                        segments.next(),
+                        segments.next(),
+                        segments.next(),
+                    ) {
+                        (
+                            Some(Ok(height)),
+                            Some("diffs"),
+                            Some(kind),
+                            Some(sub_key),
+                        ) => (BlockHeight(height), kind, sub_key),
+                        _ => continue,
+                    };
+                    match kind {
+                        "new" => batch.put(
+                            history_key(sub_key, height),
+                            types::encode(&Some(bytes.to_vec())),
+                        ),
+                        "old" => {
+                            // Without a new value, the key-val was deleted
+                            let new_val_key = format!(
+                                "{}/diffs/new/{}",
+                                height.raw(),
+                                sub_key
+                            );
+                            if self
+                                .0
+                                .get(new_val_key)
+                                .map_err(|e| Error::DBError(e.into_string()))?
+                                .is_none()
+                            {
+                                batch.put(
+                                    history_key(sub_key, height),
+                                    types::encode(&None::<Vec<u8>>),
+                                )
+                            }
+                        }
+                        _ => unknown_key_error(path)?,
+                    }
+                    if batch.len() >= chunk_len {
+                        self.write_history_migration_chunk(&mut batch, path)?;
+                    }
+                }
+                BlockHeight::default()
+            }
+            Some(last_height) => {
+                tracing::info!(
+                    "Building the history index from the state at the height \
+                     {}...",
+                    last_height
+                );
+                let start = resume_from
+                    .clone()
+                    .unwrap_or_else(|| "subspace/".to_owned());
+                for value in self.0.iterator_opt(
+                    IteratorMode::From(start.as_bytes(), Direction::Forward),
+                    read_opts,
+                ) {
+                    let (key, bytes) =
+                        value.map_err(|e| Error::DBError(e.into_string()))?;
+                    let path = match std::str::from_utf8(&key) {
+                        Ok(path) => path,
+                        Err(_) => continue,
+                    };
+                    if resume_from.as_deref() == Some(path) {
+                        continue;
+                    }
+                    let sub_key = match path.strip_prefix("subspace/") {
+                        Some(sub_key) => sub_key,
+                        None => break,
+                    };
+                    batch.put(
+                        history_key(sub_key, last_height),
+                        types::encode(&Some(bytes.to_vec())),
+                    );
+                    if batch.len() >= chunk_len {
+                        self.write_history_migration_chunk(&mut batch, path)?;
+                    }
+                }
+                last_height
+            }
+        };
+        batch.put("history_start", types::encode(&history_start));
+        batch.delete("history_migration");
+        self.exec_batch(batch)
+    }
+
+    /// Write a chunk of the history index, together with the DB key up to
+    /// which it has been built
+    fn write_history_migration_chunk(
+        &self,
+        batch: &mut WriteBatch,
+        last_path: &str,
+    ) -> Result<()> {
+        batch.put("history_migration", types::encode(&last_path.to_owned()));
+        self.0
+            .write(std::mem::take(batch))
+            .map_err(|e| Error::DBError(e.into_string()))
+    }
+
+    /// Roll back the last committed block, restoring the state of its
+    /// predecessor height from the diffs of the last height. The values of
+    /// the top-level keys are restored from their `pred` values, which are
+    /// only written when a block is committed, so the state can only be
+    /// rolled back by one height until the next block is committed. Returns
+    /// the height that the state has been rolled back to.
+    pub fn rollback(&mut self) -> Result<BlockHeight> {
+        let last_height: BlockHeight = match self
+            .0
+            .get("height")
+            .map_err(|e| Error::DBError(e.into_string()))?
+        {
+            Some(bytes) => types::decode(bytes).map_err(Error::CodingError)?,
+            None => {
+                return Err(Error::Temporary {
+                    error: "No block has been committed".to_string(),
+                });
+            }
+        };
+        let height = match last_height.0.checked_sub(1) {
+            Some(height) => BlockHeight(height),
+            None => {
+                return Err(Error::Temporary {
+                    error: "There is no block to roll back to".to_string(),
+                });
+            }
+        };
+        if self
+            .0
+            .get(format!("{}/hash", height.raw()))
+            .map_err(|e| Error::DBError(e.into_string()))?
+            .is_none()
+        {
+            return Err(Error::Temporary {
+                error: format!(
+                    "There is no block at the height {} to roll back to",
+                    height
+                ),
+            });
+        }
+        if self.is_pruned(height)? {
+            return Err(Error::Pruned { height });
+        }
+
+        let mut batch = WriteBatch::default();
+        batch.put("height", types::encode(&height));
+        for key in [
+            "next_epoch_min_start_height",
+            "next_epoch_min_start_time",
+            "tx_queue",
+        ] {
+            let pred_key = format!("pred/{}", key);
+            let pred_value = self
+                .0
+                .get(&pred_key)
+                .map_err(|e| Error::DBError(e.into_string()))?
+                .ok_or_else(|| Error::Temporary {
+                    error: format!(
+                        "The predecessor value of {} is missing, the last \
+                         block might have already been rolled back",
+                        key
+                    ),
+                })?;
+            batch.put(key, pred_value);
+            // The predecessor of the restored value is unknown
+            batch.delete(pred_key);
+        }
+        batch.delete(format!("results/{}", last_height.raw()));
+
+        // Delete everything stored under the last height and restore the
+        // account subspace key-vals changed in it
+        let prefix = format!("{}/", last_height.raw());
+        let diffs_prefix = format!("{}diffs/", prefix);
+        let mut read_opts = ReadOptions::default();
+        // don't use the prefix bloom filter
+        read_opts.set_total_order_seek(true);
+        read_opts.set_iterate_upper_bound(format!(
+            "{}/",
+            last_height.next_height().raw()
+        ));
+        for value in self.0.iterator_opt(
+            IteratorMode::From(prefix.as_bytes(), Direction::Forward),
+            read_opts,
+        ) {
+            let (key, bytes) =
+                value.map_err(|e| Error::DBError(e.into_string()))?;
+            let path = String::from_utf8((*key).to_vec()).map_err(|e| {
+                Error::Temporary {
+                    error: format!(
+                        "Cannot convert path from utf8 bytes to string: {}",
+                        e
+                    ),
+                }
+            })?;
+            if let Some(diff) = path.strip_prefix(&diffs_prefix) {
+                let sub_key = match diff.split_once(KEY_SEGMENT_SEPARATOR) {
+                    Some(("old", sub_key)) => {
+                        batch.put(format!("subspace/{}", sub_key), bytes);
+                        sub_key
+                    }
+                    Some(("new", sub_key)) => {
+                        // Without an old value, the key-val was added in the
+                        // last height
+                        let old_val_key =
+                            format!("{}old/{}", diffs_prefix, sub_key);
+                        if self
+                            .0
+                            .get(old_val_key)
+                            .map_err(|e| Error::DBError(e.into_string()))?
+                            .is_none()
+                        {
+                            batch.delete(format!("subspace/{}", sub_key));
+                        }
+                        sub_key
+                    }
+                    _ => {
+                        unknown_key_error(&path)?;
+                        continue;
+                    }
+                };
+                batch.delete(history_key(sub_key, last_height));
+            }
+            batch.delete(path);
+        }
+        self.exec_batch(batch)?;
+        self.flush(true)?;
+        Ok(height)
+    }
 }
 
 impl DB for RocksDB {
@@ -577,6 +1106,31 @@ impl DB for RocksDB {
         }
     }
 
+    fn read_block_metadata(
+        &self,
+        height: BlockHeight,
+    ) -> Result<Option<BlockMetadataRead>> {
+        let prefix_key = Key::from(height.to_db_key());
+        let read = |name: &str| -> Result<Option<Vec<u8>>> {
+            let key =
+                prefix_key.push(&name.to_owned()).map_err(Error::KeyError)?;
+            self.0
+                .get(key.to_string())
+                .map_err(|e| Error::DBError(e.into_string()))
+        };
+        match (read("hash")?, read("epoch")?, read("address_gen")?) {
+            (Some(hash), Some(epoch), Some(address_gen)) => {
+                Ok(Some(BlockMetadataRead {
+                    hash: types::decode(hash).map_err(Error::CodingError)?,
+                    epoch: types::decode(epoch).map_err(Error::CodingError)?,
+                    address_gen: types::decode(address_gen)
+                        .map_err(Error::CodingError)?,
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
+
     fn read_merkle_tree_stores(
         &self,
         height: BlockHeight,
@@ -601,6 +1155,9 @@ impl DB for RocksDB {
                     let root = types::decode(b).map_err(Error::CodingError)?;
                     merkle_tree_stores.set_root(st, root);
                 }
+                // the added sub-trees are missing in the stores written
+                // before they existed
+                None if st.is_added() => continue,
                 None => return Ok(None),
             }
 
@@ -635,94 +1192,15 @@ impl DB for RocksDB {
         height: BlockHeight,
         last_height: BlockHeight,
     ) -> Result<Option<Vec<u8>>> {
-        // Check if the value changed at this height
-        let key_prefix = Key::from(height.to_db_key())
-            .push(&"diffs".to_owned())
-            .map_err(Error::KeyError)?;
-        let new_val_key = key_prefix
-            .push(&"new".to_owned())
-            .map_err(Error::KeyError)?
-            .join(key)
-            .to_string();
-
-        // If it has a "new" val, it was written at this height
-        match self
-            .0
-            .get(new_val_key)
-            .map_err(|e| Error::DBError(e.into_string()))?
-        {
-            Some(new_val) => {
-                return Ok(Some(new_val));
-            }
-            None => {
-                let old_val_key = key_prefix
-                    .push(&"old".to_owned())
-                    .map_err(Error::KeyError)?
-                    .join(key)
-                    .to_string();
-                // If it has an "old" val, it was deleted at this height
-                if self.0.key_may_exist(old_val_key.clone()) {
-                    // check if it actually exists
-                    if self
-                        .0
-                        .get(old_val_key)
-                        .map_err(|e| Error::DBError(e.into_string()))?
-                        .is_some()
-                    {
-                        return Ok(None);
-                    }
-                }
-            }
+        if self.is_pruned(height)? {
+            return Err(Error::Pruned { height });
         }
 
-        // If the value didn't change at the given height, we try to look for it
-        // at successor heights, up to the `last_height`
-        let mut raw_height = height.0 + 1;
-        loop {
-            // Try to find the next diff on this key
-            let key_prefix = Key::from(BlockHeight(raw_height).to_db_key())
-                .push(&"diffs".to_owned())
-                .map_err(Error::KeyError)?;
-            let old_val_key = key_prefix
-                .push(&"old".to_owned())
-                .map_err(Error::KeyError)?
-                .join(key)
-                .to_string();
-            let old_val = self
-                .0
-                .get(old_val_key)
-                .map_err(|e| Error::DBError(e.into_string()))?;
-            // If it has an "old" val, it's the one we're looking for
-            match old_val {
-                Some(bytes) => return Ok(Some(bytes)),
-                None => {
-                    // Check if the value was created at this height instead,
-                    // which would mean that it wasn't present before
-                    let new_val_key = key_prefix
-                        .push(&"new".to_owned())
-                        .map_err(Error::KeyError)?
-                        .join(key)
-                        .to_string();
-                    if self.0.key_may_exist(new_val_key.clone()) {
-                        // check if it actually exists
-                        if self
-                            .0
-                            .get(new_val_key)
-                            .map_err(|e| Error::DBError(e.into_string()))?
-                            .is_some()
-                        {
-                            return Ok(None);
-                        }
-                    }
-
-                    if raw_height >= last_height.0 {
-                        // Read from latest height
-                        return self.read_subspace_val(key);
-                    } else {
-                        raw_height += 1
-                    }
-                }
-            }
+        match self.read_history_start()? {
+            Some(history_start) if height >= history_start => Ok(self
+                .seek_subspace_version(key, height)?
+                .and_then(|(_, value)| value)),
+            _ => self.read_subspace_val_from_diffs(key, height, last_height),
         }
     }
 
@@ -859,7 +1337,7 @@ impl DB for RocksDB {
         // Check the length of previous value, if any
         let prev_len = match self
             .0
-            .get(key.to_string())
+            .get(subspace_key.to_string())
             .map_err(|e| Error::DBError(e.into_string()))?
         {
             Some(prev_value) => {
@@ -882,6 +1360,174 @@ impl DB for RocksDB {
 
         Ok(prev_len)
     }
+
+    fn read_pruning_state(&self) -> Result<PruningState> {
+        match self
+            .0
+            .get("pruning")
+            .map_err(|e| Error::DBError(e.into_string()))?
+        {
+            Some(bytes) => types::decode(bytes).map_err(Error::CodingError),
+            None => Ok(PruningState::default()),
+        }
+    }
+
+    fn write_pruning_state(&mut self, state: &PruningState) -> Result<()> {
+        self.0
+            .put("pruning", types::encode(state))
+            .map_err(|e| Error::DBError(e.into_string()))
+    }
+
+    fn prune_height(
+        &mut self,
+        height: BlockHeight,
+        merge_diffs: bool,
+    ) -> Result<()> {
+        let mut batch = WriteBatch::default();
+        let prefix_key = Key::from(height.to_db_key());
+
+        // Merkle tree
+        let tree_key = prefix_key
+            .push(&"tree".to_owned())
+            .map_err(Error::KeyError)?;
+        for st in StoreType::iter() {
+            let store_key =
+                tree_key.push(&st.to_string()).map_err(Error::KeyError)?;
+            for suffix in ["root", "store"] {
+                let key = store_key
+                    .push(&suffix.to_owned())
+                    .map_err(Error::KeyError)?;
+                batch.delete(key.to_string());
+            }
+        }
+
+        // Diffs, collected as the old and the new values of the changed keys
+        let diffs_prefix = format!("{}/diffs/", height.raw());
+        let mut diffs: BTreeMap<String, (Option<Vec<u8>>, Option<Vec<u8>>)> =
+            BTreeMap::new();
+        let mut read_opts = ReadOptions::default();
+        // don't use the prefix bloom filter
+        read_opts.set_total_order_seek(true);
+        read_opts.set_iterate_upper_bound(format!(
+            "{}/",
+            height.next_height().raw()
+        ));
+        for value in self.0.iterator_opt(
+            IteratorMode::From(diffs_prefix.as_bytes(), Direction::Forward),
+            read_opts,
+        ) {
+            let (key, bytes) =
+                value.map_err(|e| Error::DBError(e.into_string()))?;
+            let path = String::from_utf8((*key).to_vec()).map_err(|e| {
+                Error::Temporary {
+                    error: format!(
+                        "Cannot convert path from utf8 bytes to string: {}",
+                        e
+                    ),
+                }
+            })?;
+            let diff = match path.strip_prefix(&diffs_prefix) {
+                Some(diff) => diff,
+                None => continue,
+            };
+            if let Some(sub_key) = diff.strip_prefix("old/") {
+                diffs.entry(sub_key.to_owned()).or_default().0 =
+                    Some(bytes.to_vec());
+            } else if let Some(sub_key) = diff.strip_prefix("new/") {
+                diffs.entry(sub_key.to_owned()).or_default().1 =
+                    Some(bytes.to_vec());
+            } else {
+                unknown_key_error(&path)?
+            }
+            batch.delete(path);
+        }
+
+        if merge_diffs {
+            let next_diffs_prefix =
+                format!("{}/diffs/", height.next_height().raw());
+            for (sub_key, (old_value, new_value)) in diffs {
+                let next_old_key =
+                    format!("{}old/{}", next_diffs_prefix, sub_key);
+                let next_new_key =
+                    format!("{}new/{}", next_diffs_prefix, sub_key);
+                let changed_next = self
+                    .0
+                    .get(&next_old_key)
+                    .map_err(|e| Error::DBError(e.into_string()))?
+                    .is_some()
+                    || self
+                        .0
+                        .get(&next_new_key)
+                        .map_err(|e| Error::DBError(e.into_string()))?
+                        .is_some();
+                // The value before the pruned height becomes the old value of
+                // the next height
+                match old_value {
+                    Some(old_value) => batch.put(&next_old_key, old_value),
+                    None => batch.delete(&next_old_key),
+                }
+                // The version set at the pruned height is moved to the next
+                // height, unless a new value is written at the next height,
+                // which is the latest one
+                batch.delete(history_key(&sub_key, height));
+                if !changed_next {
+                    batch.put(
+                        history_key(&sub_key, height.next_height()),
+                        types::encode(&new_value),
+                    );
+                    if let Some(new_value) = new_value {
+                        batch.put(&next_new_key, new_value);
+                    }
+                }
+            }
+        } else {
+            // Without any lower heights left to read from, the versions that
+            // are superseded by the ones set at the pruned height aren't
+            // needed anymore and neither are the deletions
+            for (sub_key, (_old_value, new_value)) in diffs {
+                if let Some(prev_height) = height.0.checked_sub(1) {
+                    if let Some((version_height, _)) = self
+                        .seek_subspace_version(
+                            &sub_key,
+                            BlockHeight(prev_height),
+                        )?
+                    {
+                        batch.delete(history_key(&sub_key, version_height));
+                    }
+                }
+                if new_value.is_none() {
+                    batch.delete(history_key(&sub_key, height));
+                }
+            }
+        }
+
+        self.exec_batch(batch)
+    }
+
+    fn is_pruned(&self, height: BlockHeight) -> Result<bool> {
+        let pruned_height = self.read_pruning_state()?.pruned_height;
+        // The pruning starts from the first height after the genesis
+        if pruned_height == BlockHeight::default() || height > pruned_height {
+            return Ok(false);
+        }
+        // The Merkle tree stores are only deleted when a height is pruned
+        let root_key =
+            format!("{}/tree/{}/root", height.raw(), StoreType::Base);
+        Ok(self
+            .0
+            .get(root_key)
+            .map_err(|e| Error::DBError(e.into_string()))?
+            .is_none())
+    }
+
+    fn checkpoint(&self, dir: &Path) -> Result<Self> {
+        // The memtables are flushed first, so that the blocks written without
+        // the WAL are included
+        Checkpoint::new(&self.0)
+            .and_then(|checkpoint| checkpoint.create_checkpoint(dir))
+            .map_err(|e| Error::DBError(e.into_string()))?;
+        open(dir, None)
+    }
 }
 
 impl<'iter> DBIter<'iter> for RocksDB {
@@ -917,6 +1563,68 @@ impl<'iter> DBIter<'iter> for RocksDB {
         );
         PersistentPrefixIterator(PrefixIterator::new(iter, db_prefix))
     }
+
+    fn iter_prefix_at_height(
+        &'iter self,
+        prefix: &Key,
+        height: BlockHeight,
+    ) -> Result<HistoricalPrefixIter> {
+        if self.is_pruned(height)? {
+            return Err(Error::Pruned { height });
+        }
+        let history_start = self.read_history_start()?.unwrap_or_default();
+        if height < history_start {
+            return Err(Error::NoHistory {
+                height,
+                history_start,
+            });
+        }
+
+        let prefix = prefix.to_string();
+        let mut read_opts = ReadOptions::default();
+        // don't use the prefix bloom filter
+        read_opts.set_total_order_seek(true);
+        let mut iter = self.0.raw_iterator_opt(read_opts);
+        // The versions of the keys with the prefix follow each other, starting
+        // from the versions of the prefix itself
+        iter.seek(history_key(&prefix, BlockHeight::default()));
+        let mut result = Vec::new();
+        while let Some(key) = iter
+            .key()
+            .and_then(parse_history_key)
+            .map(|(key, _)| key.to_owned())
+        {
+            let has_prefix = prefix.is_empty()
+                || key == prefix
+                || key.strip_prefix(&prefix).map_or(false, |rest| {
+                    rest.starts_with(KEY_SEGMENT_SEPARATOR)
+                });
+            if !has_prefix {
+                break;
+            }
+            // Find the last version of this key at or before the height
+            iter.seek_for_prev(history_key(&key, height));
+            if let (Some((found_key, _)), Some(bytes)) =
+                (iter.key().and_then(parse_history_key), iter.value())
+            {
+                if found_key == key {
+                    let value: Option<Vec<u8>> =
+                        types::decode(bytes).map_err(Error::CodingError)?;
+                    if let Some(value) = value {
+                        let gas = key.len() + value.len();
+                        result.push((key.clone(), value, gas as _));
+                    }
+                }
+            }
+            // Skip the remaining versions of this key
+            iter.seek(history_key(&key, BlockHeight(u64::MAX)));
+        }
+        iter.status().map_err(|e| Error::DBError(e.into_string()))?;
+        // The history index is sorted by the key segments, so the key-vals are
+        // sorted again in the order of the storage keys, as with `iter_prefix`
+        result.sort_by(|(key_a, _, _), (key_b, _, _)| key_a.cmp(key_b));
+        Ok(result.into_iter())
+    }
 }
 
 fn iter_prefix<'iter>(
@@ -1053,99 +1761,280 @@ mod imp {
 
 #[cfg(test)]
 mod test {
-    use namada::ledger::storage::{MerkleTree, Sha256Hasher};
-    use namada::types::address::EstablishedAddressGen;
-    use namada::types::storage::{BlockHash, Epoch, Epochs};
+    use std::time::{Duration, Instant};
+
     use tempfile::tempdir;
 
     use super::*;
 
-    /// Test that a block written can be loaded back from DB.
+    /// Test that the key comparator sorts the keys with a height first by the
+    /// height, the versions of a key in the history index next to each other
+    /// by their height and the other keys as strings, including the keys of
+    /// the history index against the others.
     #[test]
-    fn test_load_state() {
+    fn test_key_comparator() {
+        // In the expected order
+        let keys = [
+            "2/diffs/new/a",
+            "10/diffs/new/a",
+            "10/diffs/old/a",
+            "chain_id",
+            "height",
+            "history/a/2",
+            "history/a/10",
+            "history/a/b/1",
+            "history/a0/1",
+            "history/b/9",
+            "history/b/10",
+            "history_migration",
+            "history_start",
+            "pred/tx_queue",
+            "subspace/a",
+            "subspace/a/b",
+        ];
+        for (i, a) in keys.iter().enumerate() {
+            for (j, b) in keys.iter().enumerate() {
+                assert_eq!(
+                    key_comparator(a.as_bytes(), b.as_bytes()),
+                    i.cmp(&j),
+                    "comparing {} and {}",
+                    a,
+                    b
+                );
+            }
+        }
+
+        // The DB iterates the keys in the same order, whatever the order in
+        // which they're written
+        let dir = tempdir().unwrap();
+        let db = open(dir.path(), None).unwrap();
+        for key in keys.iter().rev() {
+            // Keep the `history_start` of the new DB
+            if db.0.get(key).unwrap().is_none() {
+                db.0.put(key, b"").unwrap();
+            }
+        }
+        let mut read_opts = ReadOptions::default();
+        read_opts.set_total_order_seek(true);
+        let iterated: Vec<String> =
+            db.0.iterator_opt(IteratorMode::Start, read_opts)
+                .map(|value| {
+                    let (key, _value) = value.unwrap();
+                    String::from_utf8(key.to_vec()).unwrap()
+                })
+                .collect();
+        assert_eq!(iterated, keys);
+    }
+
+    /// Test that the reads from the history index match the reads from the
+    /// diffs, including after the index of an existing DB is migrated.
+    #[test]
+    fn test_history_index() {
         let dir = tempdir().unwrap();
         let mut db = open(dir.path(), None).unwrap();
 
-        let mut batch = RocksDB::batch();
-        let last_height = BlockHeight::default();
-        db.batch_write_subspace_val(
-            &mut batch,
-            last_height,
-            &Key::parse("test").unwrap(),
-            vec![1_u8, 1, 1, 1],
-        )
-        .unwrap();
-        db.exec_batch(batch.0).unwrap();
-
-        let merkle_tree = MerkleTree::<Sha256Hasher>::default();
-        let merkle_tree_stores = merkle_tree.stores();
-        let hash = BlockHash::default();
-        let epoch = Epoch::default();
-        let pred_epochs = Epochs::default();
-        let height = BlockHeight::default();
-        let next_epoch_min_start_height = BlockHeight::default();
-        let next_epoch_min_start_time = DateTimeUtc::now();
-        let address_gen = EstablishedAddressGen::new("whatever");
-        let tx_queue = TxQueue::default();
-        let results = BlockResults::default();
-        let block = BlockStateWrite {
-            merkle_tree_stores,
-            header: None,
-            hash: &hash,
-            height,
-            epoch,
-            results: &results,
-            pred_epochs: &pred_epochs,
-            next_epoch_min_start_height,
-            next_epoch_min_start_time,
-            address_gen: &address_gen,
-            tx_queue: &tx_queue,
+        let key = Key::parse("test").unwrap();
+        // A key sorted right after the tested one in the history index
+        let sub_key = key.push(&"sub".to_owned()).unwrap();
+        let last_height = BlockHeight(20);
+        for height in 1..=last_height.0 {
+            let height = BlockHeight(height);
+            let mut batch = RocksDB::batch();
+            match height.0 % 4 {
+                0 => {
+                    db.batch_delete_subspace_val(&mut batch, height, &key)
+                        .unwrap();
+                }
+                1 | 2 => {
+                    db.batch_write_subspace_val(
+                        &mut batch,
+                        height,
+                        &key,
+                        vec![height.0 as u8],
+                    )
+                    .unwrap();
+                }
+                _ => {}
+            }
+            db.batch_write_subspace_val(
+                &mut batch,
+                height,
+                &sub_key,
+                vec![0_u8],
+            )
+            .unwrap();
+            db.exec_batch(batch.0).unwrap();
+        }
+        db.0.put("height", types::encode(&last_height)).unwrap();
+
+        let check_reads = |db: &RocksDB| {
+            for height in 0..=last_height.0 {
+                let height = BlockHeight(height);
+                let expected = db
+                    .read_subspace_val_from_diffs(&key, height, last_height)
+                    .unwrap();
+                let value = db
+                    .read_subspace_val_with_height(&key, height, last_height)
+                    .unwrap();
+                assert_eq!(value, expected, "at height {}", height);
+            }
         };
+        check_reads(&db);
 
-        db.write_block(block).unwrap();
-
-        let _state = db
-            .read_last_block()
-            .expect("Should be able to read last block")
-            .expect("Block should have been written");
+        // Drop the index, as if the DB was created before it existed
+        for height in 0..=last_height.0 {
+            for key in [&key, &sub_key] {
+                db.0.delete(history_key(key, BlockHeight(height))).unwrap();
+            }
+        }
+        db.0.delete("history_start").unwrap();
+        db.migrate_history_index_in_chunks(3).unwrap();
+        assert_eq!(
+            db.read_history_start().unwrap(),
+            Some(BlockHeight::default())
+        );
+        assert!(db.0.get("history_migration").unwrap().is_none());
+        check_reads(&db);
+
+        // Drop the versions from the height 11, as if the node stopped after
+        // the diffs up to the height 10 were indexed
+        for height in 11..=last_height.0 {
+            for key in [&key, &sub_key] {
+                db.0.delete(history_key(key, BlockHeight(height))).unwrap();
+            }
+        }
+        db.0.delete("history_start").unwrap();
+        db.0.put("history_migration", types::encode(&"10/~".to_owned()))
+            .unwrap();
+        db.migrate_history_index_in_chunks(3).unwrap();
+        assert!(db.0.get("history_migration").unwrap().is_none());
+        check_reads(&db);
     }
 
+    /// Compare the history index with the layout of the DBs created before
+    /// it existed, with the diffs only: the time to write the changes of a
+    /// block, the size of the DB and the time to read a key at a number of
+    /// heights back. Run with `cargo test --release bench_history_index --
+    /// --ignored --nocapture`.
     #[test]
-    fn test_read() {
-        let dir = tempdir().unwrap();
-        let mut db = open(dir.path(), None).unwrap();
-
+    #[ignore]
+    fn bench_history_index() {
+        const HEIGHTS: u64 = 10_000;
+        const KEYS_PER_BLOCK: u64 = 100;
+        const READS: u32 = 100;
+
+        // A key that is only written at the first height, so reading it from
+        // the diffs goes through every height after the read one
         let key = Key::parse("test").unwrap();
+        let block_keys: Vec<Key> = (0..KEYS_PER_BLOCK)
+            .map(|i| Key::parse(format!("other/{}", i)).unwrap())
+            .collect();
+
+        let index_dir = tempdir().unwrap();
+        let mut index_db = open(index_dir.path(), None).unwrap();
+        let diffs_dir = tempdir().unwrap();
+        let mut diffs_db = open(diffs_dir.path(), None).unwrap();
+
+        let mut index_write_time = Duration::default();
+        let mut diffs_write_time = Duration::default();
+        for height in 1..=HEIGHTS {
+            let height = BlockHeight(height);
+            let value = height.0.to_le_bytes();
+
+            let start = Instant::now();
+            let mut batch = RocksDB::batch();
+            if height.0 == 1 {
+                index_db
+                    .batch_write_subspace_val(
+                        &mut batch, height, &key, [1_u8; 32],
+                    )
+                    .unwrap();
+            }
+            for block_key in &block_keys {
+                index_db
+                    .batch_write_subspace_val(
+                        &mut batch, height, block_key, value,
+                    )
+                    .unwrap();
+            }
+            index_db.exec_batch(batch.0).unwrap();
+            index_write_time += start.elapsed();
+
+            // The same changes, written without the history index
+            let start = Instant::now();
+            let mut batch = WriteBatch::default();
+            let mut write_diff = |key: &Key, value: &[u8]| {
+                let subspace_key = format!("subspace/{}", key);
+                if let Some(old_value) = diffs_db.0.get(&subspace_key).unwrap()
+                {
+                    batch.put(
+                        format!("{}/diffs/old/{}", height.raw(), key),
+                        old_value,
+                    );
+                }
+                batch.put(format!("{}/diffs/new/{}", height.raw(), key), value);
+                batch.put(subspace_key, value);
+            };
+            if height.0 == 1 {
+                write_diff(&key, &[1_u8; 32]);
+            }
+            for block_key in &block_keys {
+                write_diff(block_key, &value);
+            }
+            diffs_db.exec_batch(batch).unwrap();
+            diffs_write_time += start.elapsed();
+        }
+        println!(
+            "Writing {} keys per block: {:?} with the diffs only, {:?} with \
+             the history index",
+            KEYS_PER_BLOCK,
+            diffs_write_time / HEIGHTS as u32,
+            index_write_time / HEIGHTS as u32
+        );
+
+        for db in [&index_db, &diffs_db] {
+            db.flush(true).unwrap();
+        }
+        let size = |db: &RocksDB| {
+            db.0.property_int_value("rocksdb.total-sst-files-size")
+                .unwrap()
+                .unwrap_or_default()
+        };
+        println!(
+            "DB size after {} heights: {} bytes with the diffs only, {} bytes \
+             with the history index",
+            HEIGHTS,
+            size(&diffs_db),
+            size(&index_db)
+        );
+
+        let last_height = BlockHeight(HEIGHTS);
+        for depth in [1, 100, 1_000, HEIGHTS - 2] {
+            let height = BlockHeight(HEIGHTS - depth);
 
-        let mut batch = RocksDB::batch();
-        let last_height = BlockHeight(100);
-        db.batch_write_subspace_val(
-            &mut batch,
-            last_height,
-            &key,
-            vec![1_u8, 1, 1, 1],
-        )
-        .unwrap();
-        db.exec_batch(batch.0).unwrap();
-
-        let mut batch = RocksDB::batch();
-        let last_height = BlockHeight(111);
-        db.batch_write_subspace_val(
-            &mut batch,
-            last_height,
-            &key,
-            vec![2_u8, 2, 2, 2],
-        )
-        .unwrap();
-        db.exec_batch(batch.0).unwrap();
-
-        let prev_value = db
-            .read_subspace_val_with_height(&key, BlockHeight(100), last_height)
-            .expect("read should succeed");
-        assert_eq!(prev_value, Some(vec![1_u8, 1, 1, 1]));
-
-        let latest_value =
-            db.read_subspace_val(&key).expect("read should succeed");
-        assert_eq!(latest_value, Some(vec![2_u8, 2, 2, 2]));
+            let start = Instant::now();
+            for _ in 0..READS {
+                let value = diffs_db
+                    .read_subspace_val_from_diffs(&key, height, last_height)
+                    .unwrap();
+                assert_eq!(value, Some(vec![1; 32]));
+            }
+            let diffs_time = start.elapsed() / READS;
+
+            let start = Instant::now();
+            for _ in 0..READS {
+                let value = index_db
+                    .read_subspace_val_with_height(&key, height, last_height)
+                    .unwrap();
+                assert_eq!(value, Some(vec![1; 32]));
+            }
+            let index_time = start.elapsed() / READS;
+
+            println!(
+                "Reading a value {} heights back: {:?} from the diffs, {:?} \
+                 from the history index",
+                depth, diffs_time, index_time
+            );
+        }
     }
 }
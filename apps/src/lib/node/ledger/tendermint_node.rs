@@ -6,6 +6,7 @@ use std::str::FromStr;
 use borsh::BorshSerialize;
 use namada::types::chain::ChainId;
 use namada::types::key::*;
+use namada::types::storage::BlockHeight;
 use namada::types::time::DateTimeUtc;
 use serde_json::json;
 use thiserror::Error;
@@ -37,6 +38,8 @@ pub enum Error {
     WriteConfig(std::io::Error),
     #[error("Failed to start up Tendermint node: {0}")]
     StartUp(std::io::Error),
+    #[error("Failed to roll back Tendermint state: {0}")]
+    Rollback(String),
     #[error("{0}")]
     Runtime(String),
     #[error("Failed to convert to String: {0:?}")]
@@ -185,6 +188,36 @@ pub fn reset(tendermint_dir: impl AsRef<Path>) -> Result<()> {
     Ok(())
 }
 
+/// Roll back the Tendermint state by one block height. Returns the height of
+/// the last block of the rolled back state.
+pub fn rollback(tendermint_dir: impl AsRef<Path>) -> Result<BlockHeight> {
+    let tendermint_path = from_env_or_default()?;
+    let tendermint_dir = tendermint_dir.as_ref().to_string_lossy();
+    let output = std::process::Command::new(tendermint_path)
+        .args(["rollback", "--home", &tendermint_dir])
+        .output()
+        .map_err(|e| Error::Rollback(e.to_string()))?;
+    if !output.status.success() {
+        return Err(Error::Rollback(
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ));
+    }
+    // Expected output is e.g.:
+    //
+    // ```
+    // Rolled back state to height 4 and hash F10B5E77F972F68CA051D289474B6E75574B446BF713A7B7B71D7ECFC61A3B21
+    // ```
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .split_once("height ")
+        .and_then(|(_, rest)| rest.split_whitespace().next())
+        .and_then(|height| height.parse().ok())
+        .map(BlockHeight)
+        .ok_or_else(|| {
+            Error::Rollback(format!("Unexpected output: {}", stdout))
+        })
+}
+
 /// Convert a common signing scheme validator key into JSON for
 /// Tendermint
 fn validator_key_to_json(
@@ -336,6 +369,17 @@ async fn update_tendermint_config(
     config.instrumentation.namespace =
         tendermint_config.instrumentation_namespace;
 
+    if let Some(state_sync) = tendermint_config.state_sync {
+        config.statesync.enable = true;
+        config.statesync.rpc_servers = state_sync
+            .rpc_servers
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        config.statesync.trust_height = state_sync.trust_height;
+        config.statesync.trust_hash = state_sync.trust_hash;
+    }
+
     #[cfg(feature = "abciplus")]
     {
         config.consensus.timeout_commit =
@@ -0,0 +1,337 @@
+//! Hierarchical deterministic derivation of keys from the seed of a BIP39
+//! mnemonic code. Ed25519 keys are derived with SLIP-10, secp256k1 keys with
+//! BIP32 and shielded spending keys with ZIP32.
+
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use hmac::{Hmac, Mac, NewMac};
+use masp_primitives::zip32::ChildIndex;
+use namada::types::key::*;
+use namada::types::masp::ExtendedSpendingKey;
+use serde::{Deserialize, Serialize};
+use sha2::Sha512;
+use thiserror::Error;
+
+/// The SLIP-44 coin type registered for Namada
+const COIN_TYPE: u32 = 877;
+/// The bit set in the indices of hardened children
+const HARDENED_BIT: u32 = 1 << 31;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum DerivationPathError {
+    #[error("Invalid derivation path \"{0}\": {1}")]
+    Parse(String, String),
+    #[error(
+        "Ed25519 keys can only be derived at hardened indices, found index {0}"
+    )]
+    NonHardenedEd25519(ChildNumber),
+    #[error("The derived secp256k1 key is invalid, try another index")]
+    InvalidSecp256k1Key,
+    #[error("Keys of the scheme {0:?} cannot be derived")]
+    UnsupportedScheme(SchemeType),
+}
+
+/// Result of a key derivation
+pub type Result<T> = std::result::Result<T, DerivationPathError>;
+
+/// The index of a child key in a derivation path
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChildNumber {
+    /// A child derived from the parent's secret key, written `i'`
+    Hardened(u32),
+    /// A child derived from the parent's public key, written `i`
+    Normal(u32),
+}
+
+impl ChildNumber {
+    /// The index as encoded in the derivation, with the hardened bit set for
+    /// hardened children
+    fn to_u32(self) -> u32 {
+        match self {
+            ChildNumber::Hardened(index) => index | HARDENED_BIT,
+            ChildNumber::Normal(index) => index,
+        }
+    }
+}
+
+impl Display for ChildNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChildNumber::Hardened(index) => write!(f, "{}'", index),
+            ChildNumber::Normal(index) => write!(f, "{}", index),
+        }
+    }
+}
+
+/// The path at which a key is derived from the master key of a seed, e.g.
+/// `m/44'/877'/0'/0'/0'`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct DerivationPath(Vec<ChildNumber>);
+
+impl DerivationPath {
+    /// The default BIP44 path of the first key of the given scheme. As
+    /// SLIP-10 only supports hardened derivation of ed25519 keys, all of the
+    /// indices of their path are hardened.
+    pub fn default_for_scheme(scheme: SchemeType) -> Self {
+        use ChildNumber::*;
+        match scheme {
+            SchemeType::Secp256k1 => Self(vec![
+                Hardened(44),
+                Hardened(COIN_TYPE),
+                Hardened(0),
+                Normal(0),
+                Normal(0),
+            ]),
+            SchemeType::Ed25519 | SchemeType::Common => Self(vec![
+                Hardened(44),
+                Hardened(COIN_TYPE),
+                Hardened(0),
+                Hardened(0),
+                Hardened(0),
+            ]),
+        }
+    }
+
+    /// The default ZIP32 path of the first shielded spending key
+    pub fn default_for_spending_key() -> Self {
+        use ChildNumber::*;
+        Self(vec![Hardened(32), Hardened(COIN_TYPE), Hardened(0)])
+    }
+
+    /// Derive the keypair of the given scheme at this path from a seed
+    pub fn derive_key(
+        &self,
+        scheme: SchemeType,
+        seed: &[u8],
+    ) -> Result<common::SecretKey> {
+        match scheme {
+            SchemeType::Ed25519 => {
+                Ok(self.derive_ed25519(seed)?.try_to_sk().unwrap())
+            }
+            SchemeType::Secp256k1 => {
+                Ok(self.derive_secp256k1(seed)?.try_to_sk().unwrap())
+            }
+            SchemeType::Common => {
+                Err(DerivationPathError::UnsupportedScheme(scheme))
+            }
+        }
+    }
+
+    /// Derive the shielded spending key at this path from a seed
+    pub fn derive_spending_key(&self, seed: &[u8]) -> ExtendedSpendingKey {
+        let master = masp_primitives::zip32::ExtendedSpendingKey::master(seed);
+        self.0
+            .iter()
+            .fold(master, |key, child| {
+                key.derive_child(match *child {
+                    ChildNumber::Hardened(index) => ChildIndex::Hardened(index),
+                    ChildNumber::Normal(index) => {
+                        ChildIndex::NonHardened(index)
+                    }
+                })
+            })
+            .into()
+    }
+
+    /// SLIP-10 derivation of an ed25519 key
+    fn derive_ed25519(&self, seed: &[u8]) -> Result<ed25519::SecretKey> {
+        let (mut key, mut chain_code) = hmac_sha512(b"ed25519 seed", seed);
+        for child in &self.0 {
+            if let ChildNumber::Normal(_) = child {
+                return Err(DerivationPathError::NonHardenedEd25519(*child));
+            }
+            let mut data = Vec::with_capacity(37);
+            data.push(0);
+            data.extend_from_slice(&key);
+            data.extend_from_slice(&child.to_u32().to_be_bytes());
+            (key, chain_code) = hmac_sha512(&chain_code, &data);
+        }
+        Ok(ed25519::SecretKey(Box::new(
+            ed25519_consensus::SigningKey::from(key),
+        )))
+    }
+
+    /// BIP32 derivation of a secp256k1 key
+    fn derive_secp256k1(&self, seed: &[u8]) -> Result<secp256k1::SecretKey> {
+        let parse = |bytes: &[u8; 32]| {
+            libsecp256k1::SecretKey::parse(bytes)
+                .map_err(|_| DerivationPathError::InvalidSecp256k1Key)
+        };
+        let (key, mut chain_code) = hmac_sha512(b"Bitcoin seed", seed);
+        let mut key = parse(&key)?;
+        for child in &self.0 {
+            let mut data = Vec::with_capacity(37);
+            match child {
+                ChildNumber::Hardened(_) => {
+                    data.push(0);
+                    data.extend_from_slice(&key.serialize());
+                }
+                ChildNumber::Normal(_) => data.extend_from_slice(
+                    &libsecp256k1::PublicKey::from_secret_key(&key)
+                        .serialize_compressed(),
+                ),
+            }
+            data.extend_from_slice(&child.to_u32().to_be_bytes());
+            let (tweak, child_chain_code) = hmac_sha512(&chain_code, &data);
+            key.tweak_add_assign(&parse(&tweak)?)
+                .map_err(|_| DerivationPathError::InvalidSecp256k1Key)?;
+            chain_code = child_chain_code;
+        }
+        Ok(secp256k1::SecretKey(Box::new(key)))
+    }
+}
+
+/// Split the HMAC-SHA512 of the data into a key and a chain code
+fn hmac_sha512(key: &[u8], data: &[u8]) -> ([u8; 32], [u8; 32]) {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key)
+        .expect("HMAC can take a key of any size");
+    mac.update(data);
+    let output = mac.finalize().into_bytes();
+    let mut left = [0; 32];
+    let mut right = [0; 32];
+    left.copy_from_slice(&output[..32]);
+    right.copy_from_slice(&output[32..]);
+    (left, right)
+}
+
+impl Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "m")?;
+        for child in &self.0 {
+            write!(f, "/{}", child)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = DerivationPathError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let err = |msg: &str| {
+            DerivationPathError::Parse(s.to_owned(), msg.to_owned())
+        };
+        let mut components = s.trim().split('/');
+        if components.next() != Some("m") {
+            return Err(err("it must start with \"m\""));
+        }
+        components
+            .map(|component| {
+                let (index, hardened) = match component
+                    .strip_suffix('\'')
+                    .or_else(|| component.strip_suffix('h'))
+                {
+                    Some(index) => (index, true),
+                    None => (component, false),
+                };
+                let index: u32 = index
+                    .parse()
+                    .map_err(|_| err("an index is not a number"))?;
+                if index >= HARDENED_BIT {
+                    return Err(err("an index is too large"));
+                }
+                Ok(if hardened {
+                    ChildNumber::Hardened(index)
+                } else {
+                    ChildNumber::Normal(index)
+                })
+            })
+            .collect::<Result<_>>()
+            .map(Self)
+    }
+}
+
+impl From<DerivationPath> for String {
+    fn from(path: DerivationPath) -> Self {
+        path.to_string()
+    }
+}
+
+impl TryFrom<String> for DerivationPath {
+    type Error = DerivationPathError;
+
+    fn try_from(path: String) -> Result<Self> {
+        path.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use data_encoding::HEXLOWER;
+
+    use super::*;
+
+    /// The seed of the first test vectors of BIP32 and SLIP-10
+    const SEED: &str = "000102030405060708090a0b0c0d0e0f";
+
+    #[test]
+    fn test_derivation_path_roundtrip() {
+        for path in [
+            DerivationPath::default_for_scheme(SchemeType::Ed25519),
+            DerivationPath::default_for_scheme(SchemeType::Secp256k1),
+            DerivationPath::default_for_spending_key(),
+        ] {
+            assert_eq!(
+                path.to_string().parse::<DerivationPath>().unwrap(),
+                path
+            );
+        }
+        assert_eq!(
+            "m/44h/877h/0h/0/0".parse::<DerivationPath>().unwrap(),
+            DerivationPath::default_for_scheme(SchemeType::Secp256k1)
+        );
+        assert_eq!(
+            "m".parse::<DerivationPath>().unwrap(),
+            DerivationPath(vec![])
+        );
+        for invalid in ["", "44'/0'", "m/", "m/a'", "m/2147483648"] {
+            assert!(invalid.parse::<DerivationPath>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_derive_ed25519() {
+        let seed = HEXLOWER.decode(SEED.as_bytes()).unwrap();
+        let key = "m/0'"
+            .parse::<DerivationPath>()
+            .unwrap()
+            .derive_ed25519(&seed)
+            .unwrap();
+        assert_eq!(
+            HEXLOWER.encode(&key.0.to_bytes()),
+            "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3"
+        );
+        assert!(matches!(
+            DerivationPath::default_for_scheme(SchemeType::Secp256k1)
+                .derive_ed25519(&seed),
+            Err(DerivationPathError::NonHardenedEd25519(_))
+        ));
+    }
+
+    #[test]
+    fn test_derive_secp256k1() {
+        let seed = HEXLOWER.decode(SEED.as_bytes()).unwrap();
+        let key = "m/0'/1"
+            .parse::<DerivationPath>()
+            .unwrap()
+            .derive_secp256k1(&seed)
+            .unwrap();
+        assert_eq!(
+            HEXLOWER.encode(&key.0.serialize()),
+            "3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368"
+        );
+    }
+
+    #[test]
+    fn test_derive_spending_key() {
+        let seed = HEXLOWER.decode(SEED.as_bytes()).unwrap();
+        let path = DerivationPath::default_for_spending_key();
+        let key = path.derive_spending_key(&seed).to_string();
+        assert_eq!(key, path.derive_spending_key(&seed).to_string());
+        let other_path: DerivationPath = "m/32'/877'/1'".parse().unwrap();
+        assert_ne!(key, other_path.derive_spending_key(&seed).to_string());
+    }
+}
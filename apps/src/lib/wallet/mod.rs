@@ -1,5 +1,6 @@
 mod alias;
 pub mod defaults;
+mod derivation_path;
 mod keys;
 pub mod pre_genesis;
 mod store;
@@ -10,6 +11,7 @@ use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::{env, fs};
 
+use bip39::{Language, Mnemonic, MnemonicType, Seed};
 use borsh::{BorshDeserialize, BorshSerialize};
 use masp_primitives::zip32::ExtendedFullViewingKey;
 use namada::types::address::Address;
@@ -21,6 +23,7 @@ pub use store::wallet_file;
 use thiserror::Error;
 
 use self::alias::Alias;
+pub use self::derivation_path::{DerivationPath, DerivationPathError};
 pub use self::keys::{DecryptionError, StoredKeypair};
 use self::store::Store;
 pub use self::store::{ValidatorData, ValidatorKeys};
@@ -158,6 +161,72 @@ impl Wallet {
         (alias.into(), key)
     }
 
+    /// Generate a new BIP39 mnemonic code of 24 English words from which keys
+    /// can be derived.
+    pub fn gen_mnemonic() -> Mnemonic {
+        Mnemonic::new(MnemonicType::Words24, Language::English)
+    }
+
+    /// Derive a keypair from a mnemonic code at the given derivation path, or
+    /// at the default path of its scheme if none provided, and insert it
+    /// into the store similarly to [`Wallet::gen_key`]. The seed is derived
+    /// from the mnemonic code and the BIP39 passphrase, which is empty if the
+    /// code isn't protected by one. The derivation path is stored with the
+    /// key's alias so that it can be restored from the mnemonic code.
+    pub fn derive_key_from_mnemonic(
+        &mut self,
+        scheme: SchemeType,
+        mnemonic: &Mnemonic,
+        passphrase: &str,
+        derivation_path: Option<DerivationPath>,
+        alias: Option<String>,
+        unsafe_dont_encrypt: bool,
+    ) -> (String, common::SecretKey) {
+        let derivation_path = derivation_path
+            .unwrap_or_else(|| DerivationPath::default_for_scheme(scheme));
+        let seed = Seed::new(mnemonic, passphrase);
+        let sk = derivation_path
+            .derive_key(scheme, seed.as_bytes())
+            .unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                cli::safe_exit(1)
+            });
+        let password = read_and_confirm_pwd(unsafe_dont_encrypt);
+        let (alias, key) =
+            self.store
+                .add_derived_key(sk, derivation_path, alias, password);
+        // Cache the newly added key
+        self.decrypted_key_cache.insert(alias.clone(), key.clone());
+        (alias.into(), key)
+    }
+
+    /// Derive a spending key from a mnemonic code at the given derivation
+    /// path, or at the default ZIP32 path if none provided, and insert it into
+    /// the store similarly to [`Wallet::gen_spending_key`].
+    pub fn derive_spending_key_from_mnemonic(
+        &mut self,
+        mnemonic: &Mnemonic,
+        passphrase: &str,
+        derivation_path: Option<DerivationPath>,
+        alias: String,
+        unsafe_dont_encrypt: bool,
+    ) -> (String, ExtendedSpendingKey) {
+        let derivation_path = derivation_path
+            .unwrap_or_else(DerivationPath::default_for_spending_key);
+        let seed = Seed::new(mnemonic, passphrase);
+        let spendkey = derivation_path.derive_spending_key(seed.as_bytes());
+        let password = Self::new_password_prompt(unsafe_dont_encrypt);
+        let (alias, key) = self.store.add_derived_spending_key(
+            spendkey,
+            derivation_path,
+            alias,
+            password,
+        );
+        // Cache the newly added key
+        self.decrypted_spendkey_cache.insert(alias.clone(), key);
+        (alias.into(), key)
+    }
+
     /// Generate keypair
     /// for signing protocol txs and for the DKG (which will also be stored)
     /// A protocol keypair may be optionally provided, indicating that
@@ -375,6 +444,53 @@ impl Wallet {
             .collect()
     }
 
+    /// Find the derivation path of a key by its alias, if it was derived from
+    /// a mnemonic code.
+    pub fn find_derivation_path(
+        &self,
+        alias: impl AsRef<str>,
+    ) -> Option<&DerivationPath> {
+        self.store.find_derivation_path(alias)
+    }
+
+    /// Find the derivation path of a spending key by its alias, if it was
+    /// derived from a mnemonic code.
+    pub fn find_spending_key_derivation_path(
+        &self,
+        alias: impl AsRef<str>,
+    ) -> Option<&DerivationPath> {
+        self.store.find_spending_key_derivation_path(alias)
+    }
+
+    /// Find the history of the public keys of an account by the alias of its
+    /// address, from the oldest to the current one, if its key has been
+    /// rotated.
+    pub fn find_key_history(
+        &self,
+        alias: impl AsRef<str>,
+    ) -> Option<&[PublicKeyHash]> {
+        self.store.find_key_history(alias)
+    }
+
+    /// Link the new public key of an account to its old key in the key
+    /// history of its address' alias. Returns the alias, or `None` if the
+    /// address is not in the wallet.
+    pub fn add_key_rotation(
+        &mut self,
+        address: &Address,
+        old_pk: Option<&common::PublicKey>,
+        new_pk: &common::PublicKey,
+    ) -> Option<String> {
+        let alias = self.store.find_alias(address)?.clone();
+        self.store.add_key_rotation(alias.clone(), old_pk, new_pk);
+        Some(alias.into())
+    }
+
+    /// Find the alias of a key by its public key hash.
+    pub fn find_alias_by_pkh(&self, pkh: &PublicKeyHash) -> Option<String> {
+        self.store.find_alias_by_pkh(pkh).map(Into::into)
+    }
+
     /// Find the stored address by an alias.
     pub fn find_address(&self, alias: impl AsRef<str>) -> Option<&Address> {
         self.store.find_address(alias)
@@ -536,6 +652,43 @@ pub fn read_and_confirm_pwd(unsafe_dont_encrypt: bool) -> Option<String> {
     password
 }
 
+/// Parse the given BIP39 mnemonic code, or read it from the terminal without
+/// echoing it if none given. Exits if it's not a valid English mnemonic code.
+pub fn read_mnemonic(phrase: Option<String>) -> Mnemonic {
+    let phrase = phrase.unwrap_or_else(|| {
+        rpassword::read_password_from_tty(Some("Enter your mnemonic code: "))
+            .unwrap_or_default()
+    });
+    let words: Vec<&str> = phrase.split_whitespace().collect();
+    Mnemonic::from_phrase(&words.join(" "), Language::English).unwrap_or_else(
+        |err| {
+            eprintln!("Invalid mnemonic code: {}", err);
+            cli::safe_exit(1)
+        },
+    )
+}
+
+/// Read the BIP39 passphrase that protects a mnemonic code from the terminal
+/// without echoing it. When the passphrase is new, it is read a second time
+/// for confirmation.
+pub fn read_bip39_passphrase(confirm: bool) -> String {
+    let passphrase = rpassword::read_password_from_tty(Some(
+        "Enter your BIP39 passphrase: ",
+    ))
+    .unwrap_or_default();
+    if confirm {
+        let to_confirm = rpassword::read_password_from_tty(Some(
+            "To confirm, please enter the same BIP39 passphrase once more: ",
+        ))
+        .unwrap_or_default();
+        if to_confirm != passphrase {
+            eprintln!("Your two inputs do not match!");
+            cli::safe_exit(1)
+        }
+    }
+    passphrase
+}
+
 /// Read the password for encryption/decryption from the file/env/stdin. Panics
 /// if all options are empty/invalid.
 pub fn read_password(prompt_msg: &str) -> String {
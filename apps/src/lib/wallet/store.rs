@@ -21,6 +21,7 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use super::alias::{self, Alias};
+use super::derivation_path::DerivationPath;
 use super::keys::StoredKeypair;
 use super::pre_genesis;
 use crate::cli;
@@ -67,6 +68,17 @@ pub struct Store {
     /// Known mappings of public key hashes to their aliases in the `keys`
     /// field. Used for look-up by a public key.
     pkhs: HashMap<PublicKeyHash, Alias>,
+    /// Derivation paths of the keys that were derived from a mnemonic code
+    #[serde(default)]
+    derivation_paths: HashMap<Alias, DerivationPath>,
+    /// Derivation paths of the spending keys that were derived from a
+    /// mnemonic code
+    #[serde(default)]
+    spend_key_derivation_paths: HashMap<Alias, DerivationPath>,
+    /// The public keys that established accounts have had, by the alias of
+    /// their address, from the oldest to the current one
+    #[serde(default)]
+    key_history: HashMap<Alias, Vec<PublicKeyHash>>,
     /// Special keys if the wallet belongs to a validator
     pub(crate) validator_data: Option<ValidatorData>,
 }
@@ -258,6 +270,48 @@ impl Store {
         self.addresses.get_by_left(&alias.into())
     }
 
+    /// Find the derivation path of a key by its alias, if it was derived from
+    /// a mnemonic code.
+    pub fn find_derivation_path(
+        &self,
+        alias: impl AsRef<str>,
+    ) -> Option<&DerivationPath> {
+        self.derivation_paths.get(&alias.into())
+    }
+
+    /// Find the derivation path of a spending key by its alias, if it was
+    /// derived from a mnemonic code.
+    pub fn find_spending_key_derivation_path(
+        &self,
+        alias: impl AsRef<str>,
+    ) -> Option<&DerivationPath> {
+        self.spend_key_derivation_paths.get(&alias.into())
+    }
+
+    /// Find the history of the public keys of an account by the alias of its
+    /// address, if its key has been rotated.
+    pub fn find_key_history(
+        &self,
+        alias: impl AsRef<str>,
+    ) -> Option<&[PublicKeyHash]> {
+        self.key_history.get(&alias.into()).map(Vec::as_slice)
+    }
+
+    /// Record that the public key of the account with the given address alias
+    /// has been rotated from the old key, if known, to the new one.
+    pub fn add_key_rotation(
+        &mut self,
+        alias: Alias,
+        old_pk: Option<&common::PublicKey>,
+        new_pk: &common::PublicKey,
+    ) {
+        let history = self.key_history.entry(alias).or_default();
+        if history.is_empty() {
+            history.extend(old_pk.map(PublicKeyHash::from));
+        }
+        history.push(new_pk.into());
+    }
+
     /// Find an alias by the address if it's in the wallet.
     pub fn find_alias(&self, address: &Address) -> Option<&Alias> {
         self.addresses.get_by_right(address)
@@ -330,7 +384,31 @@ impl Store {
         alias: Option<String>,
         password: Option<String>,
     ) -> (Alias, common::SecretKey) {
-        let sk = gen_sk(scheme);
+        self.add_new_key(gen_sk(scheme), alias, password)
+    }
+
+    /// Insert a keypair derived from a mnemonic code into the store similarly
+    /// to [`Store::gen_key`] and record its derivation path.
+    pub fn add_derived_key(
+        &mut self,
+        sk: common::SecretKey,
+        derivation_path: DerivationPath,
+        alias: Option<String>,
+        password: Option<String>,
+    ) -> (Alias, common::SecretKey) {
+        let (alias, sk) = self.add_new_key(sk, alias, password);
+        self.derivation_paths.insert(alias.clone(), derivation_path);
+        (alias, sk)
+    }
+
+    /// Insert a new keypair and its implicit address into the store with the
+    /// provided alias, or its public key hash if none provided.
+    fn add_new_key(
+        &mut self,
+        sk: common::SecretKey,
+        alias: Option<String>,
+        password: Option<String>,
+    ) -> (Alias, common::SecretKey) {
         let pkh: PublicKeyHash = PublicKeyHash::from(&sk.ref_to());
         let (keypair_to_store, raw_keypair) = StoredKeypair::new(sk, password);
         let address = Address::Implicit(ImplicitAddress(pkh.clone()));
@@ -355,7 +433,38 @@ impl Store {
         alias: String,
         password: Option<String>,
     ) -> (Alias, ExtendedSpendingKey) {
-        let spendkey = Self::generate_spending_key();
+        self.add_new_spending_key(
+            Self::generate_spending_key(),
+            alias,
+            password,
+        )
+    }
+
+    /// Insert a spending key derived from a mnemonic code into the store
+    /// similarly to [`Store::gen_spending_key`] and record its derivation
+    /// path.
+    pub fn add_derived_spending_key(
+        &mut self,
+        spendkey: ExtendedSpendingKey,
+        derivation_path: DerivationPath,
+        alias: String,
+        password: Option<String>,
+    ) -> (Alias, ExtendedSpendingKey) {
+        let (alias, spendkey) =
+            self.add_new_spending_key(spendkey, alias, password);
+        self.spend_key_derivation_paths
+            .insert(alias.clone(), derivation_path);
+        (alias, spendkey)
+    }
+
+    /// Insert a new spending key and its viewing key into the store with the
+    /// provided alias.
+    fn add_new_spending_key(
+        &mut self,
+        spendkey: ExtendedSpendingKey,
+        alias: String,
+        password: Option<String>,
+    ) -> (Alias, ExtendedSpendingKey) {
         let viewkey = ExtendedFullViewingKey::from(&spendkey.into()).into();
         let (spendkey_to_store, _raw_spendkey) =
             StoredKeypair::new(spendkey, password);
@@ -525,6 +634,9 @@ impl Store {
         self.keys.remove(alias);
         self.addresses.remove_by_left(alias);
         self.pkhs.retain(|_key, val| val != alias);
+        self.derivation_paths.remove(alias);
+        self.spend_key_derivation_paths.remove(alias);
+        self.key_history.remove(alias);
     }
 
     /// Insert payment addresses similarly to how it's done for keypairs
@@ -785,4 +897,42 @@ mod test_wallet {
         let data = store.encode();
         let _ = Store::decode(data).expect("Test failed");
     }
+
+    #[test]
+    fn test_toml_roundtrip_derivation_path() {
+        let mut store = Store::new();
+        let path = DerivationPath::default_for_scheme(SchemeType::Secp256k1);
+        let sk = path.derive_key(SchemeType::Secp256k1, &[0; 64]).unwrap();
+        let (alias, _) =
+            store.add_derived_key(sk, path.clone(), Some("hd".into()), None);
+        let data = store.encode();
+        let store = Store::decode(data).expect("Test failed");
+        assert_eq!(store.find_derivation_path(alias), Some(&path));
+    }
+
+    #[test]
+    fn test_derivation_paths_per_store() {
+        let mut store = Store::new();
+        let path = DerivationPath::default_for_scheme(SchemeType::Ed25519);
+        let sk = path.derive_key(SchemeType::Ed25519, &[0; 64]).unwrap();
+        let (key_alias, _) =
+            store.add_derived_key(sk, path.clone(), Some("hd".into()), None);
+        let spend_path = DerivationPath::default_for_spending_key();
+        let spendkey = spend_path.derive_spending_key(&[0; 64]);
+        let (spend_alias, _) = store.add_derived_spending_key(
+            spendkey,
+            spend_path.clone(),
+            "hd-spend".into(),
+            None,
+        );
+        let data = store.encode();
+        let store = Store::decode(data).expect("Test failed");
+        assert_eq!(store.find_derivation_path(&key_alias), Some(&path));
+        assert_eq!(store.find_spending_key_derivation_path(&key_alias), None);
+        assert_eq!(
+            store.find_spending_key_derivation_path(&spend_alias),
+            Some(&spend_path)
+        );
+        assert_eq!(store.find_derivation_path(&spend_alias), None);
+    }
 }
@@ -15,11 +15,12 @@
 pub mod btree_set;
 pub mod epoched;
 pub mod parameters;
+pub mod rewards;
 pub mod types;
 pub mod validation;
 
 use core::fmt::Debug;
-use std::collections::{BTreeSet, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::convert::TryFrom;
 use std::fmt::Display;
 use std::hash::Hash;
@@ -31,13 +32,14 @@ use epoched::{
     DynEpochOffset, EpochOffset, Epoched, EpochedDelta, OffsetPipelineLen,
 };
 use parameters::PosParams;
+use rewards::{PosRewardsCalculator, RewardsError};
 use rust_decimal::Decimal;
 use thiserror::Error;
 use types::{
-    ActiveValidator, Bonds, CommissionRates, Epoch, GenesisValidator, Slash,
-    SlashType, Slashes, TotalDeltas, Unbond, Unbonds, ValidatorConsensusKeys,
-    ValidatorDeltas, ValidatorSet, ValidatorSetUpdate, ValidatorSets,
-    ValidatorState, ValidatorStates,
+    ActiveValidator, Bonds, CommissionRates, Epoch, GenesisValidator,
+    MissedBlocks, Slash, SlashType, Slashes, TotalDeltas, Unbond, Unbonds,
+    ValidatorConsensusKeys, ValidatorDeltas, ValidatorSet, ValidatorSetUpdate,
+    ValidatorSets, ValidatorState, ValidatorStates,
 };
 
 use crate::btree_set::BTreeSetShims;
@@ -141,6 +143,13 @@ pub trait PosReadOnly {
         &self,
         key: &Self::Address,
     ) -> Result<Option<Decimal>, Self::Error>;
+    /// Read the rewards pool of a PoS bond (validator self-bond or a
+    /// delegation), into which the bond's share of the inflation rewards
+    /// earned by its validator is minted.
+    fn read_bond_rewards(
+        &self,
+        key: &BondId<Self::Address>,
+    ) -> Result<Self::TokenAmount, Self::Error>;
     /// Read PoS bond (validator self-bond or a delegation).
     fn read_bond(
         &self,
@@ -278,6 +287,13 @@ pub trait PosActions: PosReadOnly {
     type CommissionRateChangeError: From<Self::Error>
         + From<CommissionRateChangeError<Self::Address>>;
 
+    /// Error in `PosActions::unjail_validator`
+    type UnjailError: From<Self::Error> + From<UnjailError<Self::Address>>;
+
+    /// Error in `PosActions::claim_rewards`
+    type ClaimRewardsError: From<Self::Error>
+        + From<ClaimRewardsError<Self::Address>>;
+
     /// Write PoS parameters.
     fn write_pos_params(
         &mut self,
@@ -320,6 +336,13 @@ pub trait PosActions: PosReadOnly {
         key: &Self::Address,
         value: ValidatorDeltas<Self::TokenChange>,
     ) -> Result<(), Self::Error>;
+    /// Write the rewards pool of a PoS bond (validator self-bond or a
+    /// delegation).
+    fn write_bond_rewards(
+        &mut self,
+        key: &BondId<Self::Address>,
+        value: Self::TokenAmount,
+    ) -> Result<(), Self::Error>;
 
     /// Write PoS bond (validator self-bond or a delegation).
     fn write_bond(
@@ -669,6 +692,76 @@ pub trait PosActions: PosReadOnly {
 
         Ok(())
     }
+
+    /// Unjail a validator that has been jailed for missing too many blocks.
+    /// The validator re-enters the validator set at the pipeline offset.
+    fn unjail_validator(
+        &mut self,
+        validator: &Self::Address,
+        current_epoch: impl Into<Epoch>,
+    ) -> Result<(), Self::UnjailError> {
+        let current_epoch = current_epoch.into();
+        let params = self.read_pos_params()?;
+        let mut state = self
+            .read_validator_state(validator)?
+            .ok_or_else(|| UnjailError::NotAValidator(validator.clone()))?;
+        let validator_deltas = self.read_validator_deltas(validator)?;
+        let mut validator_set = self.read_validator_set()?;
+
+        unjail_validator(
+            &params,
+            validator,
+            &mut state,
+            validator_deltas.as_ref(),
+            &mut validator_set,
+            current_epoch,
+        )?;
+        self.write_validator_state(validator, state)?;
+        self.write_validator_set(validator_set)?;
+        Ok(())
+    }
+
+    /// Claim the inflation rewards accumulated in the rewards pool of a
+    /// validator's self-bond when `source` is `None` or equal to the
+    /// `validator` address, or of the delegation from the `source` to the
+    /// `validator`. The rewards are transferred from the PoS account to the
+    /// source and the pool is emptied. Returns the claimed amount.
+    fn claim_rewards(
+        &mut self,
+        source: Option<&Self::Address>,
+        validator: &Self::Address,
+    ) -> Result<Self::TokenAmount, Self::ClaimRewardsError> {
+        if self.read_validator_state(validator)?.is_none() {
+            return Err(
+                ClaimRewardsError::NotAValidator(validator.clone()).into()
+            );
+        }
+        let source = source.unwrap_or(validator);
+        let bond_id = BondId {
+            source: source.clone(),
+            validator: validator.clone(),
+        };
+        let rewards = self.read_bond_rewards(&bond_id)?;
+        let zero = Self::TokenAmount::default();
+        if rewards == zero {
+            return Err(ClaimRewardsError::NoRewards(
+                source.clone(),
+                validator.clone(),
+            )
+            .into());
+        }
+        self.write_bond_rewards(&bond_id, zero)?;
+
+        // Transfer the rewards from PoS to the source
+        self.transfer(
+            &self.staking_token_address(),
+            rewards,
+            &Self::POS_ADDRESS,
+            source,
+        )?;
+
+        Ok(rewards)
+    }
 }
 
 /// PoS system base trait for system initialization on genesis block, updating
@@ -777,6 +870,23 @@ pub trait PosBase {
     fn read_validator_set(&self) -> ValidatorSets<Self::Address>;
     /// Read PoS total deltas of all validators (active and inactive).
     fn read_total_deltas(&self) -> TotalDeltas<Self::TokenChange>;
+    /// Read the accumulated block rewards fractions of the active validators
+    /// in the current epoch.
+    fn read_rewards_accumulator(&self) -> BTreeMap<Self::Address, Decimal>;
+    /// Read all the PoS bonds (validator self-bonds and delegations).
+    fn read_bonds(
+        &self,
+    ) -> Vec<(BondId<Self::Address>, Bonds<Self::TokenAmount>)>;
+    /// Read the rewards pool of a PoS bond (validator self-bond or a
+    /// delegation).
+    fn read_bond_rewards(
+        &self,
+        key: &BondId<Self::Address>,
+    ) -> Self::TokenAmount;
+    /// Read the heights of the blocks in the liveness window that the
+    /// validator failed to sign.
+    fn read_validator_missed_blocks(&self, key: &Self::Address)
+        -> MissedBlocks;
 
     /// Write PoS parameters.
     fn write_pos_params(&mut self, params: &PosParams);
@@ -833,8 +943,27 @@ pub trait PosBase {
     fn write_validator_set(&mut self, value: &ValidatorSets<Self::Address>);
     /// Write total deltas in PoS for all validators (active and inactive)
     fn write_total_deltas(&mut self, value: &TotalDeltas<Self::TokenChange>);
+    /// Write the accumulated block rewards fractions of the active validators.
+    fn write_rewards_accumulator(
+        &mut self,
+        value: &BTreeMap<Self::Address, Decimal>,
+    );
+    /// Write the rewards pool of a PoS bond (validator self-bond or a
+    /// delegation).
+    fn write_bond_rewards(
+        &mut self,
+        key: &BondId<Self::Address>,
+        value: Self::TokenAmount,
+    );
+    /// Write the heights of the blocks in the liveness window that the
+    /// validator failed to sign.
+    fn write_validator_missed_blocks(
+        &mut self,
+        key: &Self::Address,
+        value: &MissedBlocks,
+    );
     /// Credit tokens to the `target` account. This should only be used at
-    /// genesis.
+    /// genesis and when minting inflation rewards.
     fn credit_tokens(
         &mut self,
         token: &Self::Address,
@@ -1029,7 +1158,195 @@ pub trait PosBase {
                 Some(ValidatorSetUpdate::Deactivated(consensus_key))
             },
         );
-        active_validators.chain(inactive_validators).for_each(f)
+        // Validators that were active in the previous epoch and have since
+        // been removed from the validator set (i.e. jailed) must also be
+        // removed from Tendermint's validator set.
+        let removed_validators = prev_validators
+            .into_iter()
+            .flat_map(|prev_validators| prev_validators.active.iter())
+            .filter(|validator: &&WeightedValidator<Self::Address>| {
+                !cur_validators
+                    .active
+                    .iter()
+                    .chain(cur_validators.inactive.iter())
+                    .any(|cur| cur.address == validator.address)
+            })
+            .map(|validator| {
+                let consensus_key = self
+                    .read_validator_consensus_key(&validator.address)
+                    .unwrap()
+                    .get(current_epoch)
+                    .unwrap()
+                    .clone();
+                ValidatorSetUpdate::Deactivated(consensus_key)
+            });
+        active_validators
+            .chain(inactive_validators)
+            .chain(removed_validators)
+            .for_each(f)
+    }
+
+    /// Record the fractions of a block's rewards earned by each of the active
+    /// validators in the given epoch. The block's rewards are split between
+    /// the block proposer, the validators that signed the block and all the
+    /// active validators, as determined by [`PosRewardsCalculator`]. The
+    /// fractions are accumulated until the end of the epoch, when the
+    /// inflation is distributed with [`PosBase::distribute_rewards`].
+    fn log_block_rewards(
+        &mut self,
+        epoch: impl Into<Epoch>,
+        proposer_address: &Self::Address,
+        signers: &HashSet<Self::Address>,
+    ) -> Result<(), RewardsError> {
+        let epoch: Epoch = epoch.into();
+        let params = self.read_pos_params();
+        let validator_sets = self.read_validator_set();
+        let active_validators = match validator_sets.get(epoch) {
+            Some(validator_set) => &validator_set.active,
+            None => return Ok(()),
+        };
+
+        let mut total_stake: u64 = 0;
+        let mut signing_stake: u64 = 0;
+        for validator in active_validators.iter() {
+            total_stake += validator.bonded_stake;
+            if signers.contains(&validator.address) {
+                signing_stake += validator.bonded_stake;
+            }
+        }
+        if total_stake == 0 {
+            return Ok(());
+        }
+
+        let coeffs = PosRewardsCalculator {
+            proposer_reward: params.block_proposer_reward,
+            signer_reward: params.block_vote_reward,
+            signing_stake,
+            total_stake,
+        }
+        .get_reward_coeffs()?;
+
+        let mut accumulator = self.read_rewards_accumulator();
+        for validator in active_validators.iter() {
+            let stake = Decimal::from(validator.bonded_stake);
+            let mut rewards_frac =
+                coeffs.active_val_coeff * stake / Decimal::from(total_stake);
+            if signers.contains(&validator.address) {
+                rewards_frac +=
+                    coeffs.signer_coeff * stake / Decimal::from(signing_stake);
+            }
+            if &validator.address == proposer_address {
+                rewards_frac += coeffs.proposer_coeff;
+            }
+            *accumulator.entry(validator.address.clone()).or_default() +=
+                rewards_frac;
+        }
+        self.write_rewards_accumulator(&accumulator);
+        Ok(())
+    }
+
+    /// Mint the given inflation amount of the staking token and distribute it
+    /// between the validators proportionally to the block rewards fractions
+    /// accumulated with [`PosBase::log_block_rewards`]. The accumulator is
+    /// cleared afterwards. Returns the amount that was actually minted, which
+    /// may be slightly lower than the `inflation` because of rounding.
+    ///
+    /// The rewards of a validator are minted into the rewards pools of its
+    /// bonds. The validator's commission at the given `epoch`, in which the
+    /// rewards were earned, goes to its self-bond and the rest is split
+    /// between its bonds pro rata to their amounts at that epoch. The
+    /// remainder of the rounding also goes to the validator's self-bond.
+    fn distribute_rewards(
+        &mut self,
+        inflation: Self::TokenAmount,
+        epoch: impl Into<Epoch>,
+    ) -> Self::TokenAmount {
+        let epoch = epoch.into();
+        let accumulator = self.read_rewards_accumulator();
+        let total_fracs: Decimal = accumulator.values().sum();
+        let inflation: u64 = inflation.into();
+        let mut minted: u64 = 0;
+        if !total_fracs.is_zero() && inflation != 0 {
+            // The amounts of the bonds of the rewarded validators at the
+            // epoch
+            let mut validators_bonds: HashMap<
+                Self::Address,
+                Vec<(BondId<Self::Address>, u64)>,
+            > = HashMap::new();
+            for (bond_id, bonds) in self.read_bonds() {
+                if !accumulator.contains_key(&bond_id.validator) {
+                    continue;
+                }
+                let amount = bonds
+                    .get(epoch)
+                    .map(|bond| {
+                        let pos_deltas: u64 = bond
+                            .pos_deltas
+                            .values()
+                            .map(|delta| -> u64 { (*delta).into() })
+                            .sum();
+                        let neg_deltas: u64 = bond.neg_deltas.into();
+                        pos_deltas - neg_deltas
+                    })
+                    .unwrap_or_default();
+                if amount != 0 {
+                    validators_bonds
+                        .entry(bond_id.validator.clone())
+                        .or_default()
+                        .push((bond_id, amount));
+                }
+            }
+
+            for (validator, frac) in accumulator.iter() {
+                let reward = decimal_mult_u64(*frac / total_fracs, inflation);
+                if reward == 0 {
+                    continue;
+                }
+                let commission_rate = self
+                    .read_validator_commission_rate(validator)
+                    .get(epoch)
+                    .copied()
+                    .unwrap_or_default();
+                let commission = decimal_mult_u64(commission_rate, reward);
+                let bonds_reward = u128::from(reward - commission);
+
+                let bonds =
+                    validators_bonds.remove(validator).unwrap_or_default();
+                let total_bonded: u128 =
+                    bonds.iter().map(|(_, amount)| u128::from(*amount)).sum();
+                let mut distributed: u64 = 0;
+                for (bond_id, amount) in bonds {
+                    let share = u64::try_from(
+                        bonds_reward * u128::from(amount) / total_bonded,
+                    )
+                    .expect("A share cannot exceed the validator's reward");
+                    if share == 0 {
+                        continue;
+                    }
+                    let mut pool = self.read_bond_rewards(&bond_id);
+                    pool += Self::TokenAmount::from(share);
+                    self.write_bond_rewards(&bond_id, pool);
+                    distributed += share;
+                }
+                // The commission and the remainder go to the self-bond
+                let self_bond = BondId {
+                    source: validator.clone(),
+                    validator: validator.clone(),
+                };
+                let mut pool = self.read_bond_rewards(&self_bond);
+                pool += Self::TokenAmount::from(reward - distributed);
+                self.write_bond_rewards(&self_bond, pool);
+                minted += reward;
+            }
+            // The minted rewards are held by the PoS account
+            self.credit_tokens(
+                &self.staking_token_address(),
+                &Self::POS_ADDRESS,
+                minted.into(),
+            );
+        }
+        self.write_rewards_accumulator(&BTreeMap::new());
+        minted.into()
     }
 
     /// Apply a slash to a byzantine validator for the given evidence.
@@ -1089,6 +1406,104 @@ pub trait PosBase {
         );
         Ok(())
     }
+
+    /// Record which of the active validators in the given epoch failed to
+    /// sign the block at the given height. Returns the validators that have
+    /// missed more blocks in their liveness window than allowed by the
+    /// `liveness_threshold` parameter, which should be jailed with
+    /// [`PosBase::jail_validator`].
+    fn track_liveness(
+        &mut self,
+        epoch: impl Into<Epoch>,
+        height: impl Into<u64>,
+        signers: &HashSet<Self::Address>,
+    ) -> Vec<Self::Address> {
+        let epoch: Epoch = epoch.into();
+        let height: u64 = height.into();
+        let params = self.read_pos_params();
+        let validator_sets = self.read_validator_set();
+        let active_validators = match validator_sets.get(epoch) {
+            Some(validator_set) => &validator_set.active,
+            None => return vec![],
+        };
+        let pipeline_epoch = epoch + DynEpochOffset::PipelineLen.value(&params);
+        let max_missed_blocks = decimal_mult_u64(
+            Decimal::ONE - params.liveness_threshold,
+            params.liveness_window_len,
+        );
+
+        let mut validators_to_jail = vec![];
+        for validator in active_validators.iter() {
+            let address = &validator.address;
+            // A validator that has already been jailed stays active until
+            // the pipeline epoch, but it's no longer tracked
+            let is_jailed = self
+                .read_validator_state(address)
+                .and_then(|state| state.get(pipeline_epoch).copied())
+                == Some(ValidatorState::Jailed);
+            if is_jailed {
+                continue;
+            }
+            let mut missed_blocks = self.read_validator_missed_blocks(address);
+            let len_pre = missed_blocks.len();
+            // Forget the blocks that are no longer in the liveness window
+            missed_blocks.retain(|missed_height| {
+                missed_height + params.liveness_window_len > height
+            });
+            let mut changed = missed_blocks.len() != len_pre;
+            if !signers.contains(address) {
+                missed_blocks.push(height);
+                changed = true;
+            }
+            if changed {
+                self.write_validator_missed_blocks(address, &missed_blocks);
+            }
+            if missed_blocks.len() as u64 > max_missed_blocks {
+                validators_to_jail.push(address.clone());
+            }
+        }
+        validators_to_jail
+    }
+
+    /// Jail a validator that failed to sign too many blocks. The validator is
+    /// slashed with the `downtime_slash_rate` and removed from the validator
+    /// set at the pipeline offset, until it gets unjailed with
+    /// [`PosActions::unjail_validator`].
+    fn jail_validator(
+        &mut self,
+        params: &PosParams,
+        current_epoch: impl Into<Epoch>,
+        evidence_epoch: impl Into<Epoch>,
+        evidence_block_height: impl Into<u64>,
+        validator: &Self::Address,
+    ) -> Result<(), SlashError<Self::Address>> {
+        let current_epoch = current_epoch.into();
+        self.slash(
+            params,
+            current_epoch,
+            evidence_epoch,
+            evidence_block_height,
+            SlashType::Downtime,
+            validator,
+        )?;
+
+        let mut state =
+            self.read_validator_state(validator).ok_or_else(|| {
+                SlashError::ValidatorHasNoState(validator.clone())
+            })?;
+        let mut validator_set = self.read_validator_set();
+        jail_validator(
+            params,
+            validator,
+            &mut state,
+            &mut validator_set,
+            current_epoch,
+        );
+        self.write_validator_state(validator, &state);
+        self.write_validator_set(&validator_set);
+        self.write_validator_missed_blocks(validator, &MissedBlocks::default());
+        Ok(())
+    }
 }
 
 #[allow(missing_docs)]
@@ -1178,6 +1593,8 @@ where
     VotingPowerOverflow(TryFromIntError),
     #[error("Unexpected negative stake {0} for validator {1}")]
     NegativeStake(i128, Address),
+    #[error("The validator {0} has no state value")]
+    ValidatorHasNoState(Address),
 }
 
 #[allow(missing_docs)]
@@ -1210,6 +1627,37 @@ where
     CannotRead(Address),
 }
 
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum ClaimRewardsError<Address>
+where
+    Address: Display + Debug + Clone + PartialOrd + Ord + Hash,
+{
+    #[error("The given address {0} is not a validator address")]
+    NotAValidator(Address),
+    #[error("The bond of {0} to validator {1} has no rewards to claim")]
+    NoRewards(Address, Address),
+}
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum UnjailError<Address>
+where
+    Address: Display + Debug + Clone + PartialOrd + Ord + Hash,
+{
+    #[error("The given address {0} is not a validator address")]
+    NotAValidator(Address),
+    #[error("The validator {0} is not jailed")]
+    NotJailed(Address),
+    #[error(
+        "The validator {0} cannot be unjailed before its jail takes effect at \
+         the pipeline epoch of its jailing"
+    )]
+    JailNotServed(Address),
+    #[error("The validator {0} has already been unjailed")]
+    AlreadyUnjailed(Address),
+}
+
 struct GenesisData<Validators, Address, TokenAmount, TokenChange, PK>
 where
     Validators: Iterator<
@@ -2018,10 +2466,7 @@ fn update_validator_set<Address, TokenChange>(
                         validator_set.inactive.remove(&validator_pre);
                         validator_set.inactive.insert(validator_post);
                     }
-                } else {
-                    debug_assert!(
-                        validator_set.active.contains(&validator_pre)
-                    );
+                } else if validator_set.active.contains(&validator_pre) {
                     let max_inactive_validator =
                         validator_set.inactive.last_shim();
                     let max_bonded_stake = max_inactive_validator
@@ -2050,6 +2495,142 @@ fn update_validator_set<Address, TokenChange>(
     )
 }
 
+/// A function that removes a jailed validator from the validator set from the
+/// pipeline offset. If the validator was active, its slot is taken by the
+/// inactive validator with the most bonded stake.
+fn jail_validator<Address>(
+    params: &PosParams,
+    validator: &Address,
+    state: &mut ValidatorStates,
+    validator_set: &mut ValidatorSets<Address>,
+    current_epoch: Epoch,
+) where
+    Address: Display
+        + Debug
+        + Clone
+        + PartialEq
+        + Eq
+        + PartialOrd
+        + Ord
+        + Hash
+        + BorshDeserialize
+        + BorshSerialize
+        + BorshSchema,
+{
+    state.set(ValidatorState::Jailed, current_epoch, params);
+
+    validator_set.update_from_offset(
+        |validator_set, _epoch| {
+            let active = validator_set
+                .active
+                .iter()
+                .find(|active| &active.address == validator)
+                .cloned();
+            match active {
+                Some(active) => {
+                    validator_set.active.remove(&active);
+                    if let Some(activate_max) =
+                        validator_set.inactive.pop_last_shim()
+                    {
+                        validator_set.active.insert(activate_max);
+                    }
+                }
+                None => validator_set
+                    .inactive
+                    .retain(|inactive| &inactive.address != validator),
+            }
+        },
+        current_epoch,
+        DynEpochOffset::PipelineLen,
+        params,
+    );
+}
+
+/// A function that puts a jailed validator back into the validator set from
+/// the pipeline offset, with its bonded stake at each epoch.
+fn unjail_validator<Address, TokenChange>(
+    params: &PosParams,
+    validator: &Address,
+    state: &mut ValidatorStates,
+    validator_deltas: Option<&ValidatorDeltas<TokenChange>>,
+    validator_set: &mut ValidatorSets<Address>,
+    current_epoch: Epoch,
+) -> Result<(), UnjailError<Address>>
+where
+    Address: Display
+        + Debug
+        + Clone
+        + PartialEq
+        + Eq
+        + PartialOrd
+        + Ord
+        + Hash
+        + BorshDeserialize
+        + BorshSerialize
+        + BorshSchema,
+    TokenChange: Display
+        + Default
+        + Debug
+        + Clone
+        + Copy
+        + Add<Output = TokenChange>
+        + Into<i128>
+        + BorshDeserialize
+        + BorshSerialize
+        + BorshSchema,
+{
+    let pipeline_epoch =
+        current_epoch + DynEpochOffset::PipelineLen.value(params);
+    // The validator stays jailed at least from the pipeline epoch of its
+    // jailing until the pipeline epoch of its unjailing
+    if state.get(current_epoch) != Some(&ValidatorState::Jailed) {
+        return Err(
+            if state.get(pipeline_epoch) == Some(&ValidatorState::Jailed) {
+                UnjailError::JailNotServed(validator.clone())
+            } else {
+                UnjailError::NotJailed(validator.clone())
+            },
+        );
+    }
+    if state.get(pipeline_epoch) != Some(&ValidatorState::Jailed) {
+        return Err(UnjailError::AlreadyUnjailed(validator.clone()));
+    }
+    state.set(ValidatorState::Candidate, current_epoch, params);
+
+    validator_set.update_from_offset(
+        |validator_set, epoch| {
+            let bonded_stake: i128 = validator_deltas
+                .and_then(|d| d.get(epoch))
+                .unwrap_or_default()
+                .into();
+            let weighted_validator = WeightedValidator {
+                bonded_stake: TryFrom::try_from(bonded_stake).unwrap(),
+                address: validator.clone(),
+            };
+            let min_active_validator = validator_set.active.first_shim();
+            let min_bonded_stake = min_active_validator
+                .map(|v| v.bonded_stake)
+                .unwrap_or_default();
+            if validator_set.active.len() < params.max_validator_slots as usize
+            {
+                validator_set.active.insert(weighted_validator);
+            } else if weighted_validator.bonded_stake > min_bonded_stake {
+                let deactivate_min = validator_set.active.pop_first_shim();
+                validator_set.active.insert(weighted_validator);
+                if let Some(deactivate_min) = deactivate_min {
+                    validator_set.inactive.insert(deactivate_min);
+                }
+            } else {
+                validator_set.inactive.insert(weighted_validator);
+            }
+        },
+        current_epoch,
+        DynEpochOffset::PipelineLen,
+        params,
+    );
+    Ok(())
+}
+
 struct WithdrawData<TokenAmount>
 where
     TokenAmount: Debug
@@ -2133,3 +2714,106 @@ where
         slashed,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that a jailed validator leaves the validator set at the pipeline
+    /// epoch and that it re-enters the set with its bonded stake once it's
+    /// unjailed
+    #[test]
+    fn test_jail_and_unjail_validator() {
+        let params = PosParams {
+            max_validator_slots: 1,
+            ..Default::default()
+        };
+        let pipeline_len = params.pipeline_len;
+        let jailed = "jailed".to_string();
+        let other = WeightedValidator {
+            bonded_stake: 50,
+            address: "other".to_string(),
+        };
+        let mut validator_set: ValidatorSets<String> = Epoched::init_at_genesis(
+            ValidatorSet {
+                active: BTreeSet::from([WeightedValidator {
+                    bonded_stake: 100,
+                    address: jailed.clone(),
+                }]),
+                inactive: BTreeSet::from([other.clone()]),
+            },
+            Epoch::default(),
+        );
+        let mut state: ValidatorStates = Epoched::init_at_genesis(
+            ValidatorState::Candidate,
+            Epoch::default(),
+        );
+        let deltas: ValidatorDeltas<i128> =
+            EpochedDelta::init_at_genesis(100, Epoch::default());
+
+        jail_validator(
+            &params,
+            &jailed,
+            &mut state,
+            &mut validator_set,
+            Epoch::default(),
+        );
+        // The validator is still active until the pipeline epoch
+        let current_set = validator_set.get(Epoch::default()).unwrap();
+        assert!(current_set.active.iter().any(|v| v.address == jailed));
+        let pipeline_set = validator_set.get(pipeline_len).unwrap();
+        assert_eq!(pipeline_set.active, BTreeSet::from([other.clone()]));
+        assert!(pipeline_set.inactive.is_empty());
+        assert_eq!(state.get(pipeline_len), Some(&ValidatorState::Jailed));
+
+        // The validator cannot be unjailed before its jail takes effect
+        for epoch in 0..pipeline_len {
+            assert!(matches!(
+                unjail_validator(
+                    &params,
+                    &jailed,
+                    &mut state,
+                    Some(&deltas),
+                    &mut validator_set,
+                    Epoch::from(epoch),
+                ),
+                Err(UnjailError::JailNotServed(_))
+            ));
+        }
+
+        let unjail_epoch = Epoch::from(pipeline_len);
+        unjail_validator(
+            &params,
+            &jailed,
+            &mut state,
+            Some(&deltas),
+            &mut validator_set,
+            unjail_epoch,
+        )
+        .unwrap();
+        let pipeline_epoch = unjail_epoch + pipeline_len;
+        assert_eq!(state.get(pipeline_epoch), Some(&ValidatorState::Candidate));
+        let pipeline_set = validator_set.get(pipeline_epoch).unwrap();
+        assert_eq!(
+            pipeline_set.active,
+            BTreeSet::from([WeightedValidator {
+                bonded_stake: 100,
+                address: jailed.clone(),
+            }])
+        );
+        assert_eq!(pipeline_set.inactive, BTreeSet::from([other]));
+
+        // The validator cannot be unjailed twice
+        assert!(matches!(
+            unjail_validator(
+                &params,
+                &jailed,
+                &mut state,
+                Some(&deltas),
+                &mut validator_set,
+                unjail_epoch,
+            ),
+            Err(UnjailError::AlreadyUnjailed(_))
+        ));
+    }
+}
@@ -39,6 +39,15 @@ pub struct PosParams {
     /// Portion of validator's stake that should be slashed on a light client
     /// attack.
     pub light_client_attack_min_slash_rate: Decimal,
+    /// Number of the most recent blocks over which the liveness of the active
+    /// validators is checked.
+    pub liveness_window_len: u64,
+    /// Minimum portion of the blocks in the liveness window that an active
+    /// validator must sign. Validators that sign fewer blocks get jailed.
+    pub liveness_threshold: Decimal,
+    /// Portion of validator's stake that should be slashed when it gets
+    /// jailed for downtime.
+    pub downtime_slash_rate: Decimal,
 }
 
 impl Default for PosParams {
@@ -60,6 +69,11 @@ impl Default for PosParams {
             duplicate_vote_min_slash_rate: dec!(0.001),
             // slash 0.1%
             light_client_attack_min_slash_rate: dec!(0.001),
+            liveness_window_len: 10_000,
+            // sign at least 90% of the blocks
+            liveness_threshold: dec!(0.9),
+            // slash 0.01%
+            downtime_slash_rate: dec!(0.0001),
         }
     }
 }
@@ -81,6 +95,10 @@ pub enum ValidationError {
          pipeline: {1}"
     )]
     UnbondingLenTooShort(u64, u64),
+    #[error("Liveness window length must be > 0")]
+    LivenessWindowLenZero,
+    #[error("Liveness threshold must be between 0 and 1, got {0}")]
+    LivenessThresholdOutOfRange(Decimal),
 }
 
 /// The number of fundamental units per whole token of the native staking token
@@ -136,6 +154,18 @@ impl PosParams {
             ))
         }
 
+        if self.liveness_window_len == 0 {
+            errors.push(ValidationError::LivenessWindowLenZero)
+        }
+
+        if self.liveness_threshold < Decimal::ZERO
+            || self.liveness_threshold > Decimal::ONE
+        {
+            errors.push(ValidationError::LivenessThresholdOutOfRange(
+                self.liveness_threshold,
+            ))
+        }
+
         errors
     }
 }
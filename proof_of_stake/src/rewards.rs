@@ -0,0 +1,135 @@
+//! PoS rewards
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use thiserror::Error;
+
+/// The minimum fraction of a block's rewards that is always given to the block
+/// proposer, on top of the proposer's bonus for including extra votes.
+const MIN_PROPOSER_REWARD: Decimal = dec!(0.01);
+
+#[allow(missing_docs)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RewardsError {
+    /// number of votes is less than the threshold of 2/3
+    #[error(
+        "Insufficient votes, needed at least 2/3 of the total bonded stake, \
+         got {signing_stake} out of {total_stake}"
+    )]
+    InsufficentVotes {
+        signing_stake: u64,
+        total_stake: u64,
+    },
+    /// rewards coefficients are not set
+    #[error("Reward coefficients are not properly set")]
+    CoeffsNotSet,
+}
+
+/// Holds coefficients for the three different ways to get PoS rewards
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PosRewards {
+    /// Fraction of the block rewards given to the block proposer
+    pub proposer_coeff: Decimal,
+    /// Fraction of the block rewards split among the validators that signed
+    /// the block, proportional to their stake
+    pub signer_coeff: Decimal,
+    /// Fraction of the block rewards split among all the active validators,
+    /// proportional to their stake
+    pub active_val_coeff: Decimal,
+}
+
+/// Holds relevant PoS parameters and is used to calculate the coefficients for
+/// the rewards
+#[derive(Debug, Copy, Clone)]
+pub struct PosRewardsCalculator {
+    /// Rewards fraction that goes to the block proposer
+    pub proposer_reward: Decimal,
+    /// Rewards fraction that goes to the block signers
+    pub signer_reward: Decimal,
+    /// Total stake of validators who signed the block
+    pub signing_stake: u64,
+    /// Total stake of the whole active validator set
+    pub total_stake: u64,
+}
+
+impl PosRewardsCalculator {
+    /// Calculate the reward coefficients. The three coefficients always sum up
+    /// to `1`.
+    pub fn get_reward_coeffs(&self) -> Result<PosRewards, RewardsError> {
+        let votes_needed = self.get_min_required_votes();
+        if self.signing_stake < votes_needed {
+            return Err(RewardsError::InsufficentVotes {
+                signing_stake: self.signing_stake,
+                total_stake: self.total_stake,
+            });
+        }
+
+        // Logic for determining the coefficients. The proposer is rewarded
+        // extra for including votes beyond the required 2/3.
+        let proposer_coeff = self.proposer_reward
+            * Decimal::from(self.signing_stake - votes_needed)
+            / Decimal::from(self.total_stake)
+            + MIN_PROPOSER_REWARD;
+        let signer_coeff = self.signer_reward;
+        let active_val_coeff = dec!(1.0) - proposer_coeff - signer_coeff;
+        if active_val_coeff < Decimal::ZERO {
+            return Err(RewardsError::CoeffsNotSet);
+        }
+
+        Ok(PosRewards {
+            proposer_coeff,
+            signer_coeff,
+            active_val_coeff,
+        })
+    }
+
+    /// Implement as ceiling of (2/3) * validator set stake
+    fn get_min_required_votes(&self) -> u64 {
+        ((2 * self.total_stake as u128 + 3 - 1) / 3) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reward_coeffs_sum_to_one() {
+        let calculator = PosRewardsCalculator {
+            proposer_reward: dec!(0.125),
+            signer_reward: dec!(0.1),
+            signing_stake: 90,
+            total_stake: 100,
+        };
+        let coeffs = calculator.get_reward_coeffs().unwrap();
+        assert_eq!(
+            coeffs.proposer_coeff
+                + coeffs.signer_coeff
+                + coeffs.active_val_coeff,
+            dec!(1.0)
+        );
+        // 90 - 67 = 23 extra votes
+        assert_eq!(
+            coeffs.proposer_coeff,
+            dec!(0.125) * dec!(23) / dec!(100) + MIN_PROPOSER_REWARD
+        );
+        assert_eq!(coeffs.signer_coeff, dec!(0.1));
+    }
+
+    #[test]
+    fn test_reward_coeffs_insufficient_votes() {
+        let calculator = PosRewardsCalculator {
+            proposer_reward: dec!(0.125),
+            signer_reward: dec!(0.1),
+            signing_stake: 66,
+            total_stake: 100,
+        };
+        assert_eq!(
+            calculator.get_reward_coeffs(),
+            Err(RewardsError::InsufficentVotes {
+                signing_stake: 66,
+                total_stake: 100,
+            })
+        );
+    }
+}
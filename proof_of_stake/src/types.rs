@@ -38,6 +38,9 @@ pub type TotalDeltas<TokenChange> =
     EpochedDelta<TokenChange, OffsetUnbondingLen>;
 /// Epoched validator commission rate
 pub type CommissionRates = Epoched<Decimal, OffsetPipelineLen>;
+/// The heights of the blocks in the liveness window that a validator failed
+/// to sign, in ascending order.
+pub type MissedBlocks = Vec<u64>;
 
 /// Epoch identifier. Epochs are identified by consecutive natural numbers.
 ///
@@ -244,7 +247,10 @@ pub enum ValidatorState {
     /// A `Candidate` validator may participate in the consensus. It is either
     /// in the active or inactive validator set.
     Candidate,
-    // TODO consider adding `Jailed`
+    /// A `Jailed` validator has been removed from the validator set for
+    /// failing to sign enough blocks and may not participate in the
+    /// consensus until it gets unjailed.
+    Jailed,
 }
 
 /// A bond is either a validator's self-bond or a delegation from a regular
@@ -304,6 +310,8 @@ pub enum SlashType {
     DuplicateVote,
     /// Light client attack.
     LightClientAttack,
+    /// Failing to sign enough blocks in the liveness window.
+    Downtime,
 }
 
 /// Derive Tendermint raw hash from the public key
@@ -516,6 +524,7 @@ impl SlashType {
             SlashType::LightClientAttack => {
                 params.light_client_attack_min_slash_rate
             }
+            SlashType::Downtime => params.downtime_slash_rate,
         }
     }
 }
@@ -525,6 +534,7 @@ impl Display for SlashType {
         match self {
             SlashType::DuplicateVote => write!(f, "Duplicate vote"),
             SlashType::LightClientAttack => write!(f, "Light client attack"),
+            SlashType::Downtime => write!(f, "Downtime"),
         }
     }
 }
@@ -69,12 +69,13 @@ where
     InvalidLastUpdate,
     #[error(
         "Invalid staking token balances. Balance Δ {balance_delta}, bonds Δ \
-         {bond_delta}, unbonds Δ {unbond_delta}"
+         {bond_delta}, unbonds Δ {unbond_delta}, rewards Δ {rewards_delta}"
     )]
     InvalidBalances {
         balance_delta: TokenChange,
         bond_delta: TokenChange,
         unbond_delta: TokenChange,
+        rewards_delta: TokenChange,
     },
     #[error(
         "Data must be set or updated in the correct epoch. Got epoch {got}, \
@@ -165,6 +166,12 @@ where
     NewValidatorMissingInValidatorSet(Address),
     #[error("Validator set has not been updated for new validators.")]
     MissingValidatorSetUpdate,
+    #[error("Unjailed validator {0} has not been added to the validator set.")]
+    UnjailedValidatorMissingInValidatorSet(Address),
+    #[error(
+        "The rewards pool of bond {0} can only be decreased by a transaction"
+    )]
+    RewardsPoolIncreased(BondId<Address>),
     #[error(
         "Changing the maximum commission rate change per epoch for validator \
          {0} is forbidden."
@@ -256,6 +263,13 @@ where
         /// The validator's address
         data: Data<Address>,
     },
+    /// Bond's rewards pool update
+    RewardsPool {
+        /// Bond ID
+        id: BondId<Address>,
+        /// Rewards pool prior and posterior state
+        data: Data<TokenAmount>,
+    },
 }
 
 /// An update of a validator's data.
@@ -276,8 +290,9 @@ where
         + BorshSchema,
     PublicKey: Debug + Clone + BorshDeserialize + BorshSerialize + BorshSchema,
 {
-    /// Validator's state update
-    State(Data<ValidatorStates>),
+    /// Validator's state update with the validator's deltas, which are needed
+    /// to validate the validator set when the validator gets unjailed
+    State(Data<ValidatorStates>, Option<ValidatorDeltas<TokenChange>>),
     /// Consensus key update
     ConsensusKey(Data<ValidatorConsensusKeys<PublicKey>>),
     /// Validator deltas update
@@ -407,6 +422,7 @@ where
             balance_delta,
             bond_delta,
             unbond_delta,
+            rewards_delta,
             total_deltas,
             total_stake_by_epoch,
             validator_set_pre,
@@ -414,6 +430,7 @@ where
             total_deltas_by_epoch: _,
             bonded_stake_by_epoch,
             new_validators,
+            unjailed_validators,
         } = Validate::<Address, TokenAmount, TokenChange, PublicKey>::accumulate_changes(
             changes, params, &constants, &mut errors
         );
@@ -447,6 +464,19 @@ where
         }
     }
 
+    // Unjailed validators re-enter the validator set with their bonded stake
+    let is_unjailed_with_stake =
+        |validator: &WeightedValidator<Address>, epoch: Epoch| {
+            unjailed_validators
+                .get(&validator.address)
+                .and_then(|deltas| deltas.get(epoch))
+                .map(|stake| {
+                    let stake: i128 = stake.into();
+                    u64::try_from(stake).ok() == Some(validator.bonded_stake)
+                })
+                .unwrap_or_default()
+        };
+
     // Check validator sets against validator total stakes.
     // Iter from the first epoch to the last epoch of `validator_set_post`
     if let Some(post) = &validator_set_post {
@@ -504,6 +534,9 @@ where
                                         && !pre.inactive.contains(validator)
                                         && !new_validators
                                             .contains_key(&validator.address)
+                                        && !is_unjailed_with_stake(
+                                            validator, epoch,
+                                        )
                                     {
                                         let mut is_valid = false;
 
@@ -573,6 +606,9 @@ where
                                         && !pre.inactive.contains(validator)
                                         && !new_validators
                                             .contains_key(&validator.address)
+                                        && !is_unjailed_with_stake(
+                                            validator, epoch,
+                                        )
                                     {
                                         let mut is_valid = false;
 
@@ -631,6 +667,15 @@ where
                         for (validator, (_stake_pre, tokens_at_epoch)) in
                             total_stake
                         {
+                            // Jailed validators are not in the validator set
+                            let is_in_set = post
+                                .active
+                                .iter()
+                                .chain(post.inactive.iter())
+                                .any(|weighted| &weighted.address == validator);
+                            if !is_in_set {
+                                continue;
+                            }
                             let weighted_validator = WeightedValidator {
                                 bonded_stake: (*tokens_at_epoch).into(),
                                 address: validator.clone(),
@@ -729,6 +774,25 @@ where
         }
     }
 
+    // Check unjailed validators are added back into the validator set
+    for address in unjailed_validators.keys() {
+        let is_in_set = validator_set_post
+            .as_ref()
+            .and_then(|sets| sets.get(pipeline_epoch))
+            .map(|set| {
+                set.active
+                    .iter()
+                    .chain(set.inactive.iter())
+                    .any(|validator| &validator.address == address)
+            })
+            .unwrap_or_default();
+        if !is_in_set {
+            errors.push(Error::UnjailedValidatorMissingInValidatorSet(
+                address.clone(),
+            ))
+        }
+    }
+
     // Sum the bond totals
     let bond_delta = bond_delta
         .values()
@@ -740,11 +804,14 @@ where
         .into_iter()
         .fold(TokenChange::default(), |acc, delta| acc + (*delta));
 
-    if balance_delta != bond_delta + unbond_delta {
+    // Rewards claimed from the bonds' rewards pools are paid out of the
+    // PoS account's balance
+    if balance_delta != bond_delta + unbond_delta + rewards_delta {
         errors.push(Error::InvalidBalances {
             balance_delta,
             bond_delta,
             unbond_delta,
+            rewards_delta,
         })
     }
 
@@ -807,6 +874,8 @@ where
     bond_delta: HashMap<Address, TokenChange>,
     /// Changes of validators' unbonds
     unbond_delta: HashMap<Address, TokenChange>,
+    /// Sum of the changes of bonds' rewards pools
+    rewards_delta: TokenChange,
 
     /// Changes of all validator total deltas (up to `unbonding_epoch`)
     total_deltas: HashMap<Address, TokenChange>,
@@ -820,6 +889,8 @@ where
     validator_set_pre: Option<ValidatorSets<Address>>,
     validator_set_post: Option<ValidatorSets<Address>>,
     new_validators: HashMap<Address, NewValidator<PublicKey>>,
+    /// Deltas of the validators that are being unjailed
+    unjailed_validators: HashMap<Address, ValidatorDeltas<TokenChange>>,
 }
 
 /// Accumulator of storage changes
@@ -879,6 +950,7 @@ where
             balance_delta: Default::default(),
             bond_delta: Default::default(),
             unbond_delta: Default::default(),
+            rewards_delta: Default::default(),
             total_deltas: Default::default(),
             total_stake_by_epoch: Default::default(),
             total_deltas_by_epoch: Default::default(),
@@ -886,6 +958,7 @@ where
             validator_set_pre: Default::default(),
             validator_set_post: Default::default(),
             new_validators: Default::default(),
+            unjailed_validators: Default::default(),
         }
     }
 }
@@ -969,6 +1042,7 @@ where
             balance_delta,
             bond_delta,
             unbond_delta,
+            rewards_delta,
             total_deltas,
             total_stake_by_epoch,
             total_deltas_by_epoch,
@@ -976,17 +1050,20 @@ where
             validator_set_pre,
             validator_set_post,
             new_validators,
+            unjailed_validators,
         } = &mut accumulator;
 
         for change in changes {
             match change {
                 Validator { address, update } => match update {
-                    State(data) => Self::validator_state(
+                    State(data, deltas) => Self::validator_state(
                         constants,
                         errors,
                         new_validators,
+                        unjailed_validators,
                         address,
                         data,
+                        deltas,
                     ),
                     ConsensusKey(data) => Self::validator_consensus_key(
                         constants,
@@ -1056,6 +1133,9 @@ where
                         data,
                     )
                 }
+                RewardsPool { id, data } => {
+                    Self::rewards_pool(errors, rewards_delta, id, data)
+                }
             }
         }
 
@@ -1066,8 +1146,13 @@ where
         constants: &Constants,
         errors: &mut Vec<Error<Address, TokenChange, PublicKey>>,
         new_validators: &mut HashMap<Address, NewValidator<PublicKey>>,
+        unjailed_validators: &mut HashMap<
+            Address,
+            ValidatorDeltas<TokenChange>,
+        >,
         address: Address,
         data: Data<ValidatorStates>,
+        deltas: Option<ValidatorDeltas<TokenChange>>,
     ) {
         match (data.pre, data.post) {
             (None, Some(post)) => {
@@ -1125,6 +1210,20 @@ where
                     (Some(Pending), Some(Candidate) | Some(Inactive))
                     | (Some(Candidate), Some(Inactive))
                     | (Some(Inactive), Some(Candidate) | Some(Pending)) => {}
+                    // A jailed validator can only be unjailed once its jail
+                    // has taken effect
+                    (Some(Jailed), Some(Candidate))
+                        if pre.get(constants.current_epoch)
+                            == Some(&Jailed) =>
+                    {
+                        match deltas {
+                            Some(deltas) => {
+                                unjailed_validators.insert(address, deltas);
+                            }
+                            None => errors
+                                .push(Error::MissingValidatorDeltas(address)),
+                        }
+                    }
                     _ => errors.push(Error::InvalidNewValidatorState(
                         constants.pipeline_epoch.into(),
                     )),
@@ -1482,6 +1581,23 @@ where
         }
     }
 
+    fn rewards_pool(
+        errors: &mut Vec<Error<Address, TokenChange, PublicKey>>,
+        rewards_delta: &mut TokenChange,
+        id: BondId<Address>,
+        data: Data<TokenAmount>,
+    ) {
+        // The rewards are only minted into the pools by the protocol, a
+        // transaction may only claim them
+        let pre = data.pre.map(TokenChange::from).unwrap_or_default();
+        let post = data.post.map(TokenChange::from).unwrap_or_default();
+        if post > pre {
+            errors.push(Error::RewardsPoolIncreased(id))
+        } else {
+            *rewards_delta += post - pre;
+        }
+    }
+
     fn bond(
         constants: &Constants,
         errors: &mut Vec<Error<Address, TokenChange, PublicKey>>,
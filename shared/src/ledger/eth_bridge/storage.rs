@@ -1,8 +1,16 @@
 //! storage helpers
 use super::vp::ADDRESS;
-use crate::types::storage::{Key, KeySeg};
+use crate::types::address::Address;
+use crate::types::ethereum_events::EthAddress;
+use crate::types::hash::Hash;
+use crate::types::storage::{DbKeySeg, Key, KeySeg};
 
 const QUEUE_STORAGE_KEY: &str = "queue";
+const ETH_MSGS_STORAGE_KEY: &str = "eth_msgs";
+const VALIDATOR_SET_UPDATE_STORAGE_KEY: &str = "validator_set_update";
+const ERC20_STORAGE_KEY: &str = "erc20";
+const BALANCE_STORAGE_KEY: &str = "balance";
+const SUPPLY_STORAGE_KEY: &str = "supply";
 
 /// Get the key corresponding to @EthBridge/queue
 pub fn queue_key() -> Key {
@@ -10,3 +18,77 @@ pub fn queue_key() -> Key {
         .push(&QUEUE_STORAGE_KEY.to_owned())
         .expect("Cannot obtain a storage key")
 }
+
+/// Get the key of the tally of the votes on the Ethereum event with the
+/// given hash, @EthBridge/eth_msgs/<hash>
+pub fn eth_msg_key(hash: &Hash) -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&ETH_MSGS_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+        .push(&hash.to_string())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the key of the last validator set update of the bridge contracts
+/// confirmed by the validators, @EthBridge/validator_set_update
+pub fn validator_set_update_key() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&VALIDATOR_SET_UPDATE_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the key of the balance of the given owner in the wrapped ERC20 token
+/// of the given asset, @EthBridge/erc20/<asset>/balance/<owner>
+pub fn wrapped_erc20_balance_key(asset: &EthAddress, owner: &Address) -> Key {
+    wrapped_erc20_prefix(asset)
+        .push(&BALANCE_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+        .push(&owner.to_db_key())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get the key of the total supply of the wrapped ERC20 token of the given
+/// asset, @EthBridge/erc20/<asset>/supply
+pub fn wrapped_erc20_supply_key(asset: &EthAddress) -> Key {
+    wrapped_erc20_prefix(asset)
+        .push(&SUPPLY_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+fn wrapped_erc20_prefix(asset: &EthAddress) -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&ERC20_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+        .push(&asset.to_string())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given key belongs to the Ethereum bridge
+pub fn is_eth_bridge_key(key: &Key) -> bool {
+    matches!(
+        key.segments.first(),
+        Some(DbKeySeg::AddressSeg(addr)) if addr == &ADDRESS
+    )
+}
+
+/// Check if the given key is the balance key of a wrapped ERC20 token and,
+/// if so, return the asset and the owner of the balance
+pub fn is_wrapped_erc20_balance_key(
+    key: &Key,
+) -> Option<(EthAddress, &Address)> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(erc20),
+            DbKeySeg::StringSeg(asset),
+            DbKeySeg::StringSeg(balance),
+            DbKeySeg::AddressSeg(owner),
+        ] if addr == &ADDRESS
+            && erc20 == ERC20_STORAGE_KEY
+            && balance == BALANCE_STORAGE_KEY =>
+        {
+            asset.parse().ok().map(|asset| (asset, owner))
+        }
+        _ => None,
+    }
+}
@@ -1,12 +1,15 @@
 //! Validity predicate for the Ethereum bridge
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
-use crate::ledger::native_vp::{Ctx, NativeVp};
+use super::storage::{is_eth_bridge_key, is_wrapped_erc20_balance_key};
+use crate::ledger::native_vp::{self, Ctx, NativeVp};
 use crate::ledger::storage as ledger_storage;
 use crate::ledger::storage::StorageHasher;
+use crate::ledger::storage_api::StorageRead;
 use crate::types::address::{Address, InternalAddress};
 use crate::types::storage::Key;
+use crate::types::token;
 use crate::vm::WasmCacheAccess;
 
 /// Internal address for the Ethereum bridge VP
@@ -26,8 +29,8 @@ where
 #[allow(missing_docs)]
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
-    #[error("Internal error")]
-    Internal,
+    #[error("Native VP error: {0}")]
+    NativeVpError(#[from] native_vp::Error),
 }
 
 impl<'a, DB, H, CA> NativeVp for EthBridge<'a, DB, H, CA>
@@ -40,18 +43,52 @@ where
 
     const ADDR: InternalAddress = InternalAddress::EthBridge;
 
+    /// The Ethereum events are only ever applied by the protocol, so the
+    /// only changes that txs can make in the bridge's storage are transfers
+    /// of the wrapped ERC20 tokens. These must preserve the supply of every
+    /// token and every debited owner must be a verifier of the tx. The
+    /// owner's own VP checks its signature for a debit of its balance.
     fn validate_tx(
         &self,
         _tx_data: &[u8],
-        _keys_changed: &BTreeSet<Key>,
-        _verifiers: &BTreeSet<Address>,
+        keys_changed: &BTreeSet<Key>,
+        verifiers: &BTreeSet<Address>,
     ) -> Result<bool, Self::Error> {
         tracing::debug!(
             tx_data_len = _tx_data.len(),
-            keys_changed_len = _keys_changed.len(),
-            verifiers_len = _verifiers.len(),
+            keys_changed_len = keys_changed.len(),
+            verifiers_len = verifiers.len(),
             "Validity predicate triggered",
         );
-        Ok(false)
+        let mut changes = BTreeMap::new();
+        for key in keys_changed.iter().filter(|key| is_eth_bridge_key(key)) {
+            let (asset, owner) = match is_wrapped_erc20_balance_key(key) {
+                Some(balance) => balance,
+                None => {
+                    tracing::debug!(
+                        "Rejecting a tx that changed the key {}",
+                        key
+                    );
+                    return Ok(false);
+                }
+            };
+            let pre: token::Amount =
+                self.ctx.pre().read(key)?.unwrap_or_default();
+            let post: token::Amount =
+                self.ctx.post().read(key)?.unwrap_or_default();
+            if post < pre && !verifiers.contains(owner) {
+                tracing::debug!(
+                    "Rejecting a tx that debited {} of {} without its \
+                     authorization",
+                    owner,
+                    asset
+                );
+                return Ok(false);
+            }
+            let change =
+                i128::from(u64::from(post)) - i128::from(u64::from(pre));
+            *changes.entry(asset).or_insert(0_i128) += change;
+        }
+        Ok(changes.values().all(|change| *change == 0))
     }
 }
@@ -21,9 +21,10 @@ const COMPILE_GAS_PER_BYTE: u64 = 1;
 const BASE_TRANSACTION_FEE: u64 = 2;
 const PARALLEL_GAS_DIVIDER: u64 = 10;
 
+/// The maximum gas that can be used in a block.
 /// The maximum value should be less or equal to i64::MAX
 /// to avoid the gas overflow when sending this to ABCI
-const BLOCK_GAS_LIMIT: u64 = 10_000_000_000_000;
+pub const BLOCK_GAS_LIMIT: u64 = 10_000_000_000_000;
 const TRANSACTION_GAS_LIMIT: u64 = 10_000_000_000;
 
 /// The minimum gas cost for accessing the storage
@@ -20,6 +20,7 @@ use crate::ledger::native_vp::{Ctx, NativeVp};
 use crate::ledger::pos::{self as pos_storage, BondId, Bonds};
 use crate::ledger::storage::{self as ledger_storage, StorageHasher};
 use crate::types::address::{Address, InternalAddress};
+use crate::types::governance::{ProposalType, ProposalVote};
 use crate::types::storage::{Epoch, Key};
 use crate::types::token as token_storage;
 use crate::vm::WasmCacheAccess;
@@ -82,6 +83,9 @@ where
                 (KeyType::CONTENT, Some(proposal_id)) => {
                     self.is_valid_content_key(proposal_id)
                 }
+                (KeyType::PROPOSAL_TYPE, Some(proposal_id)) => {
+                    self.is_valid_proposal_type(proposal_id)
+                }
                 (KeyType::PROPOSAL_CODE, Some(proposal_id)) => {
                     self.is_valid_proposal_code(proposal_id)
                 }
@@ -146,6 +150,7 @@ where
                 gov_storage::get_voting_start_epoch_key(counter),
                 gov_storage::get_voting_end_epoch_key(counter),
                 gov_storage::get_grace_epoch_key(counter),
+                gov_storage::get_proposal_type_key(counter),
             ]);
 
             // Check that expected set is a subset the actual one
@@ -157,12 +162,21 @@ where
         Ok((true, post_counter - pre_counter))
     }
 
+    /// Validate a vote key. A voter can cast a vote, or overwrite its earlier
+    /// vote, at any epoch of the voting period, except for validators that
+    /// can only vote in its first 2/3.
     fn is_valid_vote_key(
         &self,
         proposal_id: u64,
         key: &Key,
         verifiers: &BTreeSet<Address>,
     ) -> Result<bool> {
+        // a vote cannot be removed
+        let post_vote: Option<ProposalVote> = self.ctx.post().read(key)?;
+        if post_vote.is_none() {
+            return Ok(false);
+        }
+
         let counter_key = gov_storage::get_counter_key();
         let voting_start_epoch_key =
             gov_storage::get_voting_start_epoch_key(proposal_id);
@@ -257,9 +271,25 @@ where
         }
     }
 
-    /// Validate a proposal_code key
+    /// Validate a proposal type key
+    pub fn is_valid_proposal_type(&self, proposal_id: u64) -> Result<bool> {
+        let type_key = gov_storage::get_proposal_type_key(proposal_id);
+
+        let has_pre_type: bool = self.ctx.has_key_pre(&type_key)?;
+        if has_pre_type {
+            return Ok(false);
+        }
+
+        let post_type: Option<ProposalType> =
+            self.ctx.post().read(&type_key)?;
+        Ok(post_type.map_or(false, |post_type| post_type.is_valid()))
+    }
+
+    /// Validate a proposal_code key. Only proposals of the default type can
+    /// carry code.
     pub fn is_valid_proposal_code(&self, proposal_id: u64) -> Result<bool> {
         let code_key: Key = gov_storage::get_proposal_code_key(proposal_id);
+        let type_key = gov_storage::get_proposal_type_key(proposal_id);
         let max_code_size_parameter_key =
             gov_storage::get_max_proposal_code_size_key();
 
@@ -268,6 +298,12 @@ where
             return Ok(false);
         }
 
+        let post_type: Option<ProposalType> =
+            self.ctx.post().read(&type_key)?;
+        if post_type != Some(ProposalType::Default) {
+            return Ok(false);
+        }
+
         let max_proposal_length: Option<usize> =
             self.ctx.pre().read(&max_code_size_parameter_key)?;
         let post_code: Option<Vec<u8>> = self.ctx.read_bytes_post(&code_key)?;
@@ -616,6 +652,8 @@ enum KeyType {
     #[allow(non_camel_case_types)]
     PROPOSAL_CODE,
     #[allow(non_camel_case_types)]
+    PROPOSAL_TYPE,
+    #[allow(non_camel_case_types)]
     PROPOSAL_COMMIT,
     #[allow(non_camel_case_types)]
     GRACE_EPOCH,
@@ -645,6 +683,8 @@ impl KeyType {
             KeyType::CONTENT
         } else if gov_storage::is_proposal_code_key(key) {
             KeyType::PROPOSAL_CODE
+        } else if gov_storage::is_proposal_type_key(key) {
+            KeyType::PROPOSAL_TYPE
         } else if gov_storage::is_grace_epoch_key(key) {
             KeyType::GRACE_EPOCH
         } else if gov_storage::is_start_epoch_key(key) {
@@ -1,10 +1,12 @@
 use std::fmt::Display;
 
 use borsh::{BorshDeserialize, BorshSerialize};
+use rust_decimal::Decimal;
 
 use super::storage as gov_storage;
 use crate::ledger::storage::types::encode;
 use crate::ledger::storage::{self, Storage};
+use crate::types::governance::ProposalType;
 use crate::types::token::Amount;
 
 #[derive(
@@ -32,6 +34,18 @@ pub struct GovParams {
     pub max_proposal_content_size: u64,
     /// Minimum epochs between end and grace epochs
     pub min_proposal_grace_epochs: u64,
+    /// Minimum fraction of the total voting power that must vote on a
+    /// proposal, including the abstain votes, for it to pass
+    pub min_quorum: Decimal,
+    /// Minimum fraction of the yay and nay votes that must be yay for a
+    /// proposal to pass
+    pub pass_threshold: Decimal,
+    /// Pass threshold of parameter change proposals, if it differs from the
+    /// default one
+    pub parameter_change_pass_threshold: Option<Decimal>,
+    /// Pass threshold of treasury transfer proposals, if it differs from the
+    /// default one
+    pub treasury_transfer_pass_threshold: Option<Decimal>,
 }
 
 impl Display for GovParams {
@@ -40,13 +54,19 @@ impl Display for GovParams {
             f,
             "Min. proposal fund: {}\nMax. proposal code size: {}\nMin. \
              proposal period: {}\nMax. proposal period: {}\nMax. proposal \
-             content size: {}\nMin. proposal grace epochs: {}",
+             content size: {}\nMin. proposal grace epochs: {}\nMin. quorum: \
+             {}\nPass threshold: {}\nParameter change pass threshold: \
+             {}\nTreasury transfer pass threshold: {}",
             self.min_proposal_fund,
             self.max_proposal_code_size,
             self.min_proposal_period,
             self.max_proposal_period,
             self.max_proposal_content_size,
-            self.min_proposal_grace_epochs
+            self.min_proposal_grace_epochs,
+            self.min_quorum,
+            self.pass_threshold,
+            self.pass_threshold(&ProposalType::ParameterChange(vec![])),
+            self.pass_threshold(&ProposalType::TreasuryTransfer(vec![])),
         )
     }
 }
@@ -60,11 +80,29 @@ impl Default for GovParams {
             max_proposal_period: 27,
             max_proposal_content_size: 10_000,
             min_proposal_grace_epochs: 6,
+            min_quorum: Decimal::TWO / Decimal::from(3),
+            pass_threshold: Decimal::TWO / Decimal::from(3),
+            parameter_change_pass_threshold: None,
+            treasury_transfer_pass_threshold: None,
         }
     }
 }
 
 impl GovParams {
+    /// Get the pass threshold of proposals of the given type
+    pub fn pass_threshold(&self, proposal_type: &ProposalType) -> Decimal {
+        let threshold = match proposal_type {
+            ProposalType::Default => None,
+            ProposalType::ParameterChange(_) => {
+                self.parameter_change_pass_threshold
+            }
+            ProposalType::TreasuryTransfer(_) => {
+                self.treasury_transfer_pass_threshold
+            }
+        };
+        threshold.unwrap_or(self.pass_threshold)
+    }
+
     /// Initialize governance parameters into storage
     pub fn init_storage<DB, H>(&self, storage: &mut Storage<DB, H>)
     where
@@ -78,6 +116,10 @@ impl GovParams {
             max_proposal_period,
             max_proposal_content_size,
             min_proposal_grace_epochs,
+            min_quorum,
+            pass_threshold,
+            parameter_change_pass_threshold,
+            treasury_transfer_pass_threshold,
         } = self;
 
         let min_proposal_fund_key = gov_storage::get_min_proposal_fund_key();
@@ -122,6 +164,34 @@ impl GovParams {
             )
             .expect("Should be able to write to storage");
 
+        let min_quorum_key = gov_storage::get_min_quorum_key();
+        storage
+            .write(&min_quorum_key, encode(min_quorum))
+            .expect("Should be able to write to storage");
+
+        let pass_threshold_key = gov_storage::get_pass_threshold_key();
+        storage
+            .write(&pass_threshold_key, encode(pass_threshold))
+            .expect("Should be able to write to storage");
+
+        let parameter_change_pass_threshold_key =
+            gov_storage::get_parameter_change_pass_threshold_key();
+        storage
+            .write(
+                &parameter_change_pass_threshold_key,
+                encode(parameter_change_pass_threshold),
+            )
+            .expect("Should be able to write to storage");
+
+        let treasury_transfer_pass_threshold_key =
+            gov_storage::get_treasury_transfer_pass_threshold_key();
+        storage
+            .write(
+                &treasury_transfer_pass_threshold_key,
+                encode(treasury_transfer_pass_threshold),
+            )
+            .expect("Should be able to write to storage");
+
         let counter_key = gov_storage::get_counter_key();
         storage
             .write(&counter_key, encode(&u64::MIN))
@@ -11,6 +11,7 @@ const PROPOSAL_END_EPOCH: &str = "end_epoch";
 const PROPOSAL_GRACE_EPOCH: &str = "grace_epoch";
 const PROPOSAL_FUNDS: &str = "funds";
 const PROPOSAL_CODE: &str = "proposal_code";
+const PROPOSAL_TYPE: &str = "type";
 const PROPOSAL_COMMITTING_EPOCH: &str = "epoch";
 
 const MIN_PROPOSAL_FUND_KEY: &str = "min_fund";
@@ -19,6 +20,12 @@ const MIN_PROPOSAL_PERIOD_KEY: &str = "min_period";
 const MAX_PROPOSAL_PERIOD_KEY: &str = "max_period";
 const MAX_PROPOSAL_CONTENT_SIZE_KEY: &str = "max_content";
 const MIN_GRACE_EPOCH_KEY: &str = "min_grace_epoch";
+const MIN_QUORUM_KEY: &str = "min_quorum";
+const PASS_THRESHOLD_KEY: &str = "pass_threshold";
+const PARAMETER_CHANGE_PASS_THRESHOLD_KEY: &str =
+    "parameter_change_pass_threshold";
+const TREASURY_TRANSFER_PASS_THRESHOLD_KEY: &str =
+    "treasury_transfer_pass_threshold";
 const COUNTER_KEY: &str = "counter";
 const PENDING_PROPOSAL: &str = "pending";
 
@@ -83,6 +90,24 @@ pub fn is_proposal_code_key(key: &Key) -> bool {
     }
 }
 
+/// Check if key is proposal type key
+pub fn is_proposal_type_key(key: &Key) -> bool {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::StringSeg(id),
+            DbKeySeg::StringSeg(proposal_type),
+        ] if addr == &ADDRESS
+            && prefix == PROPOSAL_PREFIX
+            && proposal_type == PROPOSAL_TYPE =>
+        {
+            id.parse::<u64>().is_ok()
+        }
+        _ => false,
+    }
+}
+
 /// Check if key is grace epoch key
 pub fn is_grace_epoch_key(key: &Key) -> bool {
     match &key.segments[..] {
@@ -245,6 +270,29 @@ pub fn is_min_grace_epoch_key(key: &Key) -> bool {
                     && min_grace_epoch_param == MIN_GRACE_EPOCH_KEY)
 }
 
+/// Check if key is a min quorum key
+pub fn is_min_quorum_key(key: &Key) -> bool {
+    matches!(&key.segments[..], [
+                    DbKeySeg::AddressSeg(addr),
+                    DbKeySeg::StringSeg(min_quorum_param),
+                ] if addr == &ADDRESS
+                    && min_quorum_param == MIN_QUORUM_KEY)
+}
+
+/// Check if key is a pass threshold key, either the default one or the one
+/// of a proposal type
+pub fn is_pass_threshold_key(key: &Key) -> bool {
+    matches!(&key.segments[..], [
+                    DbKeySeg::AddressSeg(addr),
+                    DbKeySeg::StringSeg(pass_threshold_param),
+                ] if addr == &ADDRESS
+                    && (pass_threshold_param == PASS_THRESHOLD_KEY
+                        || pass_threshold_param
+                            == PARAMETER_CHANGE_PASS_THRESHOLD_KEY
+                        || pass_threshold_param
+                            == TREASURY_TRANSFER_PASS_THRESHOLD_KEY))
+}
+
 /// Check if key is parameter key
 pub fn is_parameter_key(key: &Key) -> bool {
     is_min_proposal_fund_key(key)
@@ -253,6 +301,8 @@ pub fn is_parameter_key(key: &Key) -> bool {
         || is_min_proposal_period_key(key)
         || is_max_proposal_period_key(key)
         || is_min_grace_epoch_key(key)
+        || is_min_quorum_key(key)
+        || is_pass_threshold_key(key)
 }
 
 /// Check if key is start epoch or end epoch key
@@ -309,6 +359,34 @@ pub fn get_min_proposal_grace_epoch_key() -> Key {
         .expect("Cannot obtain a storage key")
 }
 
+/// Get min quorum key
+pub fn get_min_quorum_key() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&MIN_QUORUM_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get default pass threshold key
+pub fn get_pass_threshold_key() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&PASS_THRESHOLD_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get pass threshold of parameter change proposals key
+pub fn get_parameter_change_pass_threshold_key() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&PARAMETER_CHANGE_PASS_THRESHOLD_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Get pass threshold of treasury transfer proposals key
+pub fn get_treasury_transfer_pass_threshold_key() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&TREASURY_TRANSFER_PASS_THRESHOLD_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
 /// Get key of proposal ids counter
 pub fn get_counter_key() -> Key {
     Key::from(ADDRESS.to_db_key())
@@ -379,6 +457,15 @@ pub fn get_proposal_code_key(id: u64) -> Key {
         .expect("Cannot obtain a storage key")
 }
 
+/// Get proposal type key
+pub fn get_proposal_type_key(id: u64) -> Key {
+    proposal_prefix()
+        .push(&id.to_string())
+        .expect("Cannot obtain a storage key")
+        .push(&PROPOSAL_TYPE.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
 /// Get the proposal committing key prefix
 pub fn get_commiting_proposals_prefix(epoch: u64) -> Key {
     proposal_prefix()
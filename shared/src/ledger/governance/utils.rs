@@ -3,26 +3,113 @@ use std::str::FromStr;
 
 use borsh::BorshDeserialize;
 use namada_proof_of_stake::PosReadOnly;
+use rust_decimal::Decimal;
 use thiserror::Error;
 
+use crate::ledger::governance::parameters::GovParams;
 use crate::ledger::governance::storage as gov_storage;
-use crate::ledger::pos::BondId;
+use crate::ledger::parameters::{storage as parameter_storage, EpochDuration};
+use crate::ledger::pos::{self, BondId, PosParams};
+use crate::ledger::slash_fund::ADDRESS as TREASURY_ADDRESS;
 use crate::ledger::storage::{DBIter, Storage, StorageHasher, DB};
-use crate::ledger::storage_api;
+use crate::ledger::storage_api::{self, StorageRead, StorageWrite};
 use crate::types::address::Address;
-use crate::types::governance::{ProposalVote, TallyResult, VotePower};
+use crate::types::governance::{
+    ParameterChange, ProposalResult, ProposalType, ProposalVote, TallyResult,
+    TreasuryTransfer, VotePower,
+};
 use crate::types::storage::{Epoch, Key};
+use crate::types::time::DurationSecs;
 use crate::types::token;
 
 /// Proposal structure holding votes information necessary to compute the
 /// outcome
 pub struct Votes {
-    /// Map from validators who votes yay to their total stake amount
-    pub yay_validators: HashMap<Address, VotePower>,
-    /// Map from delegation who votes yay to their bond amount
-    pub yay_delegators: HashMap<Address, HashMap<Address, VotePower>>,
-    /// Map from delegation who votes nay to their bond amount
-    pub nay_delegators: HashMap<Address, HashMap<Address, VotePower>>,
+    /// Map from validators who voted to their vote and total stake amount
+    pub validators: HashMap<Address, (ProposalVote, VotePower)>,
+    /// Map from delegators who voted to their vote and bond amount for each
+    /// of their validators
+    pub delegators:
+        HashMap<Address, HashMap<Address, (ProposalVote, VotePower)>>,
+}
+
+impl Votes {
+    /// Tally the votes against the total voting power of the tally epoch.
+    ///
+    /// The validators vote with their whole stake, but a delegator's vote
+    /// overrides the vote of its validator for the bonded amount. A proposal
+    /// passes if the fraction of the voting power that voted, including the
+    /// abstain votes, reaches the minimum quorum and the fraction of the yay
+    /// and nay votes that are yay reaches the pass threshold.
+    pub fn tally(
+        &self,
+        total_voting_power: VotePower,
+        min_quorum: Decimal,
+        pass_threshold: Decimal,
+    ) -> ProposalResult {
+        let mut totals = VoteTotals::default();
+        for (vote, vote_power) in self.validators.values() {
+            *totals.get_mut(vote) += vote_power;
+        }
+        for vote_map in self.delegators.values() {
+            for (validator_address, (vote, vote_power)) in vote_map.iter() {
+                if let Some((validator_vote, _)) =
+                    self.validators.get(validator_address)
+                {
+                    let total = totals.get_mut(validator_vote);
+                    *total = total.saturating_sub(*vote_power);
+                }
+                *totals.get_mut(vote) += vote_power;
+            }
+        }
+
+        let VoteTotals { yay, nay, abstain } = totals;
+        let has_quorum = is_fraction_reached(
+            yay + nay + abstain,
+            total_voting_power,
+            min_quorum,
+        );
+        let is_approved = is_fraction_reached(yay, yay + nay, pass_threshold);
+        ProposalResult {
+            result: if has_quorum && is_approved {
+                TallyResult::Passed
+            } else {
+                TallyResult::Rejected
+            },
+            total_voting_power,
+            total_yay_power: yay,
+            total_nay_power: nay,
+            total_abstain_power: abstain,
+        }
+    }
+}
+
+/// Check if `part` makes up at least the given fraction of `whole`. A fraction
+/// of nothing is never reached.
+fn is_fraction_reached(
+    part: VotePower,
+    whole: VotePower,
+    fraction: Decimal,
+) -> bool {
+    whole != 0 && Decimal::from(part) / Decimal::from(whole) >= fraction
+}
+
+/// The voting power of each kind of vote
+#[derive(Default)]
+struct VoteTotals {
+    yay: VotePower,
+    nay: VotePower,
+    abstain: VotePower,
+}
+
+impl VoteTotals {
+    fn get_mut(&mut self, vote: &ProposalVote) -> &mut VotePower {
+        match vote {
+            ProposalVote::Yay => &mut self.yay,
+            ProposalVote::Nay => &mut self.nay,
+            ProposalVote::Abstain => &mut self.abstain,
+        }
+    }
 }
 
 /// Proposal errors
@@ -72,48 +159,49 @@ impl ProposalEvent {
     }
 }
 
-/// Return a proposal result - accepted only when the result is `Ok(true)`.
+/// Compute the result of a proposal of the given type from its votes
 pub fn compute_tally<D, H>(
     storage: &Storage<D, H>,
     epoch: Epoch,
     votes: Votes,
-) -> storage_api::Result<bool>
+    proposal_type: &ProposalType,
+) -> storage_api::Result<ProposalResult>
 where
     D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
     H: StorageHasher + Sync + 'static,
 {
     let total_stake: VotePower = storage.total_stake(epoch)?.into();
+    let (min_quorum, pass_threshold) =
+        read_tally_thresholds(storage, proposal_type)?;
+    Ok(votes.tally(total_stake, min_quorum, pass_threshold))
+}
 
-    let Votes {
-        yay_validators,
-        yay_delegators,
-        nay_delegators,
-    } = votes;
-
-    let mut total_yay_staked_tokens = VotePower::from(0_u64);
-    for (_, amount) in yay_validators.clone().into_iter() {
-        total_yay_staked_tokens += amount;
-    }
-
-    // YAY: Add delegator amount whose validator didn't vote / voted nay
-    for (_, vote_map) in yay_delegators.iter() {
-        for (validator_address, vote_power) in vote_map.iter() {
-            if !yay_validators.contains_key(validator_address) {
-                total_yay_staked_tokens += vote_power;
-            }
-        }
-    }
-
-    // NAY: Remove delegator amount whose validator validator vote yay
-    for (_, vote_map) in nay_delegators.iter() {
-        for (validator_address, vote_power) in vote_map.iter() {
-            if yay_validators.contains_key(validator_address) {
-                total_yay_staked_tokens -= vote_power;
-            }
-        }
-    }
-
-    Ok(3 * total_yay_staked_tokens >= 2 * total_stake)
+/// Read the minimum quorum and the pass threshold of proposals of the given
+/// type. The thresholds missing from storage fall back to their defaults.
+pub fn read_tally_thresholds<S>(
+    storage: &S,
+    proposal_type: &ProposalType,
+) -> storage_api::Result<(Decimal, Decimal)>
+where
+    S: for<'iter> StorageRead<'iter>,
+{
+    let defaults = GovParams::default();
+    let params = GovParams {
+        min_quorum: storage
+            .read(&gov_storage::get_min_quorum_key())?
+            .unwrap_or(defaults.min_quorum),
+        pass_threshold: storage
+            .read(&gov_storage::get_pass_threshold_key())?
+            .unwrap_or(defaults.pass_threshold),
+        parameter_change_pass_threshold: storage
+            .read(&gov_storage::get_parameter_change_pass_threshold_key())?
+            .flatten(),
+        treasury_transfer_pass_threshold: storage
+            .read(&gov_storage::get_treasury_transfer_pass_threshold_key())?
+            .flatten(),
+        ..defaults
+    };
+    Ok((params.min_quorum, params.pass_threshold(proposal_type)))
 }
 
 /// Prepare Votes structure to compute proposal tally
@@ -132,11 +220,11 @@ where
         gov_storage::get_proposal_vote_prefix_key(proposal_id);
     let (vote_iter, _) = storage.iter_prefix(&vote_prefix_key);
 
-    let mut yay_validators = HashMap::new();
-    let mut yay_delegators: HashMap<Address, HashMap<Address, VotePower>> =
-        HashMap::new();
-    let mut nay_delegators: HashMap<Address, HashMap<Address, VotePower>> =
-        HashMap::new();
+    let mut validator_votes = HashMap::new();
+    let mut delegator_votes: HashMap<
+        Address,
+        HashMap<Address, (ProposalVote, VotePower)>,
+    > = HashMap::new();
 
     for (key, vote_bytes, _) in vote_iter {
         let vote_key = Key::from_str(key.as_str()).ok();
@@ -146,13 +234,13 @@ where
                 let voter_address = gov_storage::get_voter_address(&key);
                 match voter_address {
                     Some(voter_address) => {
-                        if vote.is_yay() && validators.contains(voter_address) {
+                        if validators.contains(voter_address) {
                             let amount: VotePower = storage
                                 .validator_stake(voter_address, epoch)?
                                 .into();
-                            yay_validators
-                                .insert(voter_address.clone(), amount);
-                        } else if !validators.contains(voter_address) {
+                            validator_votes
+                                .insert(voter_address.clone(), (vote, amount));
+                        } else {
                             let validator_address =
                                 gov_storage::get_vote_delegation_address(&key);
                             match validator_address {
@@ -164,23 +252,13 @@ where
                                     let amount =
                                         storage.bond_amount(&bond_id, epoch)?;
                                     if amount != token::Amount::default() {
-                                        if vote.is_yay() {
-                                            let entry = yay_delegators
-                                                .entry(voter_address.to_owned())
-                                                .or_default();
-                                            entry.insert(
+                                        delegator_votes
+                                            .entry(voter_address.to_owned())
+                                            .or_default()
+                                            .insert(
                                                 validator.to_owned(),
-                                                VotePower::from(amount),
+                                                (vote, VotePower::from(amount)),
                                             );
-                                        } else {
-                                            let entry = nay_delegators
-                                                .entry(voter_address.to_owned())
-                                                .or_default();
-                                            entry.insert(
-                                                validator.to_owned(),
-                                                VotePower::from(amount),
-                                            );
-                                        }
                                     }
                                 }
                                 None => continue,
@@ -195,12 +273,220 @@ where
     }
 
     Ok(Votes {
-        yay_validators,
-        yay_delegators,
-        nay_delegators,
+        validators: validator_votes,
+        delegators: delegator_votes,
     })
 }
 
+/// Execute a change of protocol parameters made by a passed proposal
+pub fn execute_parameter_change<S>(
+    storage: &mut S,
+    change: &ParameterChange,
+) -> storage_api::Result<()>
+where
+    S: for<'iter> StorageRead<'iter> + StorageWrite,
+{
+    match change {
+        ParameterChange::EpochDuration {
+            min_num_of_blocks,
+            min_duration,
+        } => storage.write(
+            &parameter_storage::get_epoch_duration_storage_key(),
+            EpochDuration {
+                min_num_of_blocks: *min_num_of_blocks,
+                min_duration: DurationSecs(*min_duration),
+            },
+        ),
+        ParameterChange::MaxExpectedTimePerBlock(value) => storage.write(
+            &parameter_storage::get_max_expected_time_per_block_key(),
+            DurationSecs(*value),
+        ),
+        ParameterChange::VpWhitelist(value) => storage.write(
+            &parameter_storage::get_vp_whitelist_storage_key(),
+            value.clone(),
+        ),
+        ParameterChange::TxWhitelist(value) => storage.write(
+            &parameter_storage::get_tx_whitelist_storage_key(),
+            value.clone(),
+        ),
+        ParameterChange::EpochsPerYear(value) => {
+            storage.write(&parameter_storage::get_epochs_per_year_key(), *value)
+        }
+        ParameterChange::PosGainP(value) => {
+            storage.write(&parameter_storage::get_pos_gain_p_key(), *value)
+        }
+        ParameterChange::PosGainD(value) => {
+            storage.write(&parameter_storage::get_pos_gain_d_key(), *value)
+        }
+        ParameterChange::MaxValidatorSlots(value) => {
+            update_pos_params(storage, |params| {
+                params.max_validator_slots = *value
+            })
+        }
+        ParameterChange::BlockProposerReward(value) => {
+            update_pos_params(storage, |params| {
+                params.block_proposer_reward = *value
+            })
+        }
+        ParameterChange::BlockVoteReward(value) => {
+            update_pos_params(storage, |params| {
+                params.block_vote_reward = *value
+            })
+        }
+        ParameterChange::MaxInflationRate(value) => {
+            update_pos_params(storage, |params| {
+                params.max_inflation_rate = *value
+            })
+        }
+        ParameterChange::TargetStakedRatio(value) => {
+            update_pos_params(storage, |params| {
+                params.target_staked_ratio = *value
+            })
+        }
+        ParameterChange::DuplicateVoteMinSlashRate(value) => {
+            update_pos_params(storage, |params| {
+                params.duplicate_vote_min_slash_rate = *value
+            })
+        }
+        ParameterChange::LightClientAttackMinSlashRate(value) => {
+            update_pos_params(storage, |params| {
+                params.light_client_attack_min_slash_rate = *value
+            })
+        }
+        ParameterChange::LivenessWindowLen(value) => {
+            update_pos_params(storage, |params| {
+                params.liveness_window_len = *value
+            })
+        }
+        ParameterChange::LivenessThreshold(value) => {
+            update_pos_params(storage, |params| {
+                params.liveness_threshold = *value
+            })
+        }
+        ParameterChange::DowntimeSlashRate(value) => {
+            update_pos_params(storage, |params| {
+                params.downtime_slash_rate = *value
+            })
+        }
+        ParameterChange::MinProposalFund(value) => storage.write(
+            &gov_storage::get_min_proposal_fund_key(),
+            token::Amount::whole(*value),
+        ),
+        ParameterChange::MaxProposalCodeSize(value) => storage
+            .write(&gov_storage::get_max_proposal_code_size_key(), *value),
+        ParameterChange::MinProposalPeriod(value) => {
+            storage.write(&gov_storage::get_min_proposal_period_key(), *value)
+        }
+        ParameterChange::MaxProposalPeriod(value) => {
+            storage.write(&gov_storage::get_max_proposal_period_key(), *value)
+        }
+        ParameterChange::MaxProposalContentSize(value) => {
+            storage.write(&gov_storage::get_max_proposal_content_key(), *value)
+        }
+        ParameterChange::MinProposalGraceEpochs(value) => storage
+            .write(&gov_storage::get_min_proposal_grace_epoch_key(), *value),
+        ParameterChange::MinQuorum(value) => {
+            storage.write(&gov_storage::get_min_quorum_key(), *value)
+        }
+        ParameterChange::PassThreshold(value) => {
+            storage.write(&gov_storage::get_pass_threshold_key(), *value)
+        }
+        ParameterChange::ParameterChangePassThreshold(value) => storage.write(
+            &gov_storage::get_parameter_change_pass_threshold_key(),
+            *value,
+        ),
+        ParameterChange::TreasuryTransferPassThreshold(value) => storage.write(
+            &gov_storage::get_treasury_transfer_pass_threshold_key(),
+            *value,
+        ),
+    }
+}
+
+/// Update the PoS parameters in storage
+fn update_pos_params<S>(
+    storage: &mut S,
+    update: impl FnOnce(&mut PosParams),
+) -> storage_api::Result<()>
+where
+    S: for<'iter> StorageRead<'iter> + StorageWrite,
+{
+    let mut params: PosParams =
+        storage.read(&pos::params_key())?.ok_or_else(|| {
+            storage_api::Error::new_const("Missing PoS parameters")
+        })?;
+    update(&mut params);
+    if let Some(err) = params.validate().into_iter().next() {
+        return Err(storage_api::Error::new(err));
+    }
+    storage.write(&pos::params_key(), params)
+}
+
+/// Execute all the changes of protocol parameters made by a passed proposal
+/// and check that the resulting parameters are consistent with each other
+pub fn execute_parameter_changes<S>(
+    storage: &mut S,
+    changes: &[ParameterChange],
+) -> storage_api::Result<()>
+where
+    S: for<'iter> StorageRead<'iter> + StorageWrite,
+{
+    for change in changes {
+        execute_parameter_change(storage, change)?;
+    }
+    let min_proposal_period: u64 = storage
+        .read(&gov_storage::get_min_proposal_period_key())?
+        .unwrap_or_default();
+    let max_proposal_period: u64 = storage
+        .read(&gov_storage::get_max_proposal_period_key())?
+        .unwrap_or_default();
+    if min_proposal_period > max_proposal_period {
+        return Err(storage_api::Error::new_const(
+            "The minimum proposal period is greater than the maximum one",
+        ));
+    }
+    Ok(())
+}
+
+/// Execute the transfers from the treasury made by a passed proposal. The
+/// transfers are only executed if the treasury can fund all of them. Returns
+/// whether they were executed.
+pub fn execute_treasury_transfers<S>(
+    storage: &mut S,
+    transfers: &[TreasuryTransfer],
+) -> storage_api::Result<bool>
+where
+    S: for<'iter> StorageRead<'iter> + StorageWrite,
+{
+    let native_token = storage.get_native_token()?;
+    let treasury_key = token::balance_key(&native_token, &TREASURY_ADDRESS);
+    let mut treasury_balance: token::Amount =
+        storage.read(&treasury_key)?.unwrap_or_default();
+    let total = transfers
+        .iter()
+        .try_fold(token::Amount::default(), |total, transfer| {
+            total.checked_add(&transfer.amount)
+        });
+    match total {
+        Some(total) if total <= treasury_balance => {}
+        _ => return Ok(false),
+    }
+
+    for transfer in transfers {
+        // a transfer from the treasury to itself is a no-op
+        if transfer.target == TREASURY_ADDRESS {
+            continue;
+        }
+        let target_key = token::balance_key(&native_token, &transfer.target);
+        let mut target_balance: token::Amount =
+            storage.read(&target_key)?.unwrap_or_default();
+        treasury_balance.spend(&transfer.amount);
+        target_balance.receive(&transfer.amount);
+        storage.write(&target_key, target_balance)?;
+    }
+    storage.write(&treasury_key, treasury_balance)?;
+    Ok(true)
+}
+
 /// Calculate the valid voting window for validator given a proposal epoch
 /// details
 pub fn is_valid_validator_voting_period(
@@ -230,3 +516,237 @@ where
         None => Ok(false),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::storage::testing::TestStorage;
+    use crate::types::address::testing::{
+        established_address_1, established_address_2, established_address_3,
+    };
+
+    /// Test that the votes of delegators override the vote of their
+    /// validator and that abstain votes count towards the quorum only
+    #[test]
+    fn test_tally_with_abstain_votes() {
+        let validator_1 = established_address_1();
+        let validator_2 = established_address_2();
+        let delegator = established_address_3();
+        let votes = Votes {
+            validators: HashMap::from([
+                (validator_1.clone(), (ProposalVote::Yay, 60)),
+                (validator_2, (ProposalVote::Abstain, 30)),
+            ]),
+            delegators: HashMap::from([(
+                delegator,
+                HashMap::from([(validator_1, (ProposalVote::Nay, 10))]),
+            )]),
+        };
+        let two_thirds = Decimal::TWO / Decimal::from(3);
+        let result = votes.tally(100, two_thirds, two_thirds);
+        assert!(matches!(result.result, TallyResult::Passed));
+        assert_eq!(result.total_yay_power, 50);
+        assert_eq!(result.total_nay_power, 10);
+        assert_eq!(result.total_abstain_power, 30);
+
+        // with more voting power, the turnout falls short of the quorum
+        let result = votes.tally(150, two_thirds, two_thirds);
+        assert!(matches!(result.result, TallyResult::Rejected));
+    }
+
+    /// Test that the tally enforces the given quorum and pass threshold
+    #[test]
+    fn test_tally_with_custom_thresholds() {
+        let votes = Votes {
+            validators: HashMap::from([
+                (established_address_1(), (ProposalVote::Yay, 50)),
+                (established_address_2(), (ProposalVote::Nay, 10)),
+            ]),
+            delegators: HashMap::new(),
+        };
+        let half = Decimal::new(5, 1);
+
+        let result = votes.tally(100, half, Decimal::new(8, 1));
+        assert!(matches!(result.result, TallyResult::Passed));
+
+        // 5/6 of the yay and nay votes are yay
+        let result = votes.tally(100, half, Decimal::new(9, 1));
+        assert!(matches!(result.result, TallyResult::Rejected));
+
+        // 60% of the voting power voted
+        let result = votes.tally(100, Decimal::new(7, 1), half);
+        assert!(matches!(result.result, TallyResult::Rejected));
+    }
+
+    /// Test that the pass threshold of a proposal type overrides the default
+    /// one and that missing thresholds fall back to their defaults
+    #[test]
+    fn test_read_tally_thresholds() {
+        let mut storage = TestStorage::default();
+        let defaults = GovParams::default();
+        let parameter_change = ProposalType::ParameterChange(vec![]);
+        assert_eq!(
+            read_tally_thresholds(&storage, &parameter_change).unwrap(),
+            (defaults.min_quorum, defaults.pass_threshold)
+        );
+
+        let threshold = Decimal::new(75, 2);
+        StorageWrite::write(
+            &mut storage,
+            &gov_storage::get_parameter_change_pass_threshold_key(),
+            Some(threshold),
+        )
+        .unwrap();
+        assert_eq!(
+            read_tally_thresholds(&storage, &parameter_change).unwrap(),
+            (defaults.min_quorum, threshold)
+        );
+        assert_eq!(
+            read_tally_thresholds(&storage, &ProposalType::Default).unwrap(),
+            (defaults.min_quorum, defaults.pass_threshold)
+        );
+    }
+
+    /// Test that a proposal with only abstain votes is rejected
+    #[test]
+    fn test_tally_only_abstain_votes() {
+        let votes = Votes {
+            validators: HashMap::from([(
+                established_address_1(),
+                (ProposalVote::Abstain, 100),
+            )]),
+            delegators: HashMap::new(),
+        };
+        let two_thirds = Decimal::TWO / Decimal::from(3);
+        let result = votes.tally(100, two_thirds, two_thirds);
+        assert!(matches!(result.result, TallyResult::Rejected));
+        assert_eq!(result.total_abstain_power, 100);
+    }
+
+    /// Test that a proposal doesn't reach the quorum when there is no voting
+    /// power at all
+    #[test]
+    fn test_tally_without_voting_power() {
+        let votes = Votes {
+            validators: HashMap::new(),
+            delegators: HashMap::new(),
+        };
+        let result = votes.tally(0, Decimal::ZERO, Decimal::ZERO);
+        assert!(matches!(result.result, TallyResult::Rejected));
+    }
+
+    /// Test that a treasury transfer proposal is only executed if the
+    /// treasury can fund all of its transfers
+    #[test]
+    fn test_execute_treasury_transfers() {
+        let mut storage = TestStorage::default();
+        let native_token = storage.native_token.clone();
+        let treasury_key = token::balance_key(&native_token, &TREASURY_ADDRESS);
+        StorageWrite::write(
+            &mut storage,
+            &treasury_key,
+            token::Amount::whole(100),
+        )
+        .unwrap();
+
+        let target_1 = established_address_1();
+        let target_2 = established_address_2();
+        let transfers = vec![
+            TreasuryTransfer {
+                target: target_1.clone(),
+                amount: token::Amount::whole(60),
+            },
+            TreasuryTransfer {
+                target: target_2.clone(),
+                amount: token::Amount::whole(50),
+            },
+        ];
+        assert!(!execute_treasury_transfers(&mut storage, &transfers).unwrap());
+        let balance = |storage: &TestStorage, owner: &Address| {
+            StorageRead::read::<token::Amount>(
+                storage,
+                &token::balance_key(&native_token, owner),
+            )
+            .unwrap()
+            .unwrap_or_default()
+        };
+        assert_eq!(
+            balance(&storage, &TREASURY_ADDRESS),
+            token::Amount::whole(100)
+        );
+        assert_eq!(balance(&storage, &target_1), token::Amount::default());
+
+        assert!(
+            execute_treasury_transfers(&mut storage, &transfers[..1]).unwrap()
+        );
+        assert_eq!(
+            balance(&storage, &TREASURY_ADDRESS),
+            token::Amount::whole(40)
+        );
+        assert_eq!(balance(&storage, &target_1), token::Amount::whole(60));
+    }
+
+    /// Test that a parameter change proposal updates the PoS parameters
+    #[test]
+    fn test_execute_parameter_change() {
+        let mut storage = TestStorage::default();
+        let params = PosParams::default();
+        StorageWrite::write(&mut storage, &pos::params_key(), params.clone())
+            .unwrap();
+
+        execute_parameter_change(
+            &mut storage,
+            &ParameterChange::MaxValidatorSlots(42),
+        )
+        .unwrap();
+        let updated: PosParams =
+            StorageRead::read(&storage, &pos::params_key())
+                .unwrap()
+                .unwrap();
+        assert_eq!(updated.max_validator_slots, 42);
+        assert_eq!(updated.pipeline_len, params.pipeline_len);
+    }
+
+    /// Test that the parameter changes that make the parameters inconsistent
+    /// with each other fail
+    #[test]
+    fn test_execute_inconsistent_parameter_changes() {
+        let mut storage = TestStorage::default();
+        let params = PosParams::default();
+        StorageWrite::write(&mut storage, &pos::params_key(), params).unwrap();
+        StorageWrite::write(
+            &mut storage,
+            &gov_storage::get_min_proposal_period_key(),
+            3_u64,
+        )
+        .unwrap();
+        StorageWrite::write(
+            &mut storage,
+            &gov_storage::get_max_proposal_period_key(),
+            27_u64,
+        )
+        .unwrap();
+
+        // the minimum period cannot exceed the current maximum one
+        assert!(execute_parameter_changes(
+            &mut storage,
+            &[ParameterChange::MinProposalPeriod(30)],
+        )
+        .is_err());
+        // unless it is raised as well
+        execute_parameter_changes(
+            &mut storage,
+            &[
+                ParameterChange::MinProposalPeriod(30),
+                ParameterChange::MaxProposalPeriod(40),
+            ],
+        )
+        .unwrap();
+        // the PoS parameters must stay valid
+        assert!(execute_parameter_change(
+            &mut storage,
+            &ParameterChange::LivenessWindowLen(0),
+        )
+        .is_err());
+    }
+}
@@ -15,11 +15,16 @@ use crate::ibc::core::ics02_client::client_state::{
 };
 use crate::ibc::core::ics02_client::client_type::ClientType;
 use crate::ibc::core::ics02_client::events::{
-    Attributes as ClientAttributes, CreateClient, UpdateClient, UpgradeClient,
+    Attributes as ClientAttributes, ClientMisbehaviour, CreateClient,
+    UpdateClient, UpgradeClient,
 };
 use crate::ibc::core::ics02_client::header::{AnyHeader, Header};
 use crate::ibc::core::ics02_client::height::Height;
+use crate::ibc::core::ics02_client::misbehaviour::{
+    AnyMisbehaviour, Misbehaviour,
+};
 use crate::ibc::core::ics02_client::msgs::create_client::MsgCreateAnyClient;
+use crate::ibc::core::ics02_client::msgs::misbehavior::MsgSubmitAnyMisbehaviour;
 use crate::ibc::core::ics02_client::msgs::update_client::MsgUpdateAnyClient;
 use crate::ibc::core::ics02_client::msgs::upgrade_client::MsgUpgradeAnyClient;
 use crate::ibc::core::ics02_client::msgs::ClientMsg;
@@ -93,6 +98,8 @@ pub enum Error {
     PortId(Ics24Error),
     #[error("Updating a client error: {0}")]
     ClientUpdate(String),
+    #[error("Client misbehaviour error: {0}")]
+    Misbehaviour(String),
     #[error("IBC data error: {0}")]
     IbcData(IbcDataError),
     #[error("Decoding IBC data error: {0}")]
@@ -186,7 +193,7 @@ pub trait IbcActions {
             Ics26Envelope::Ics2Msg(ics02_msg) => match ics02_msg {
                 ClientMsg::CreateClient(msg) => self.create_client(msg),
                 ClientMsg::UpdateClient(msg) => self.update_client(msg),
-                ClientMsg::Misbehaviour(_msg) => todo!(),
+                ClientMsg::Misbehaviour(msg) => self.submit_misbehaviour(msg),
                 ClientMsg::UpgradeClient(msg) => self.upgrade_client(msg),
             },
             Ics26Envelope::Ics3Msg(ics03_msg) => match ics03_msg {
@@ -316,6 +323,48 @@ pub trait IbcActions {
         Ok(())
     }
 
+    /// Freeze a client with the evidence of its misbehaviour
+    fn submit_misbehaviour(
+        &mut self,
+        msg: &MsgSubmitAnyMisbehaviour,
+    ) -> std::result::Result<(), Self::Error> {
+        let client_id = msg.client_id.clone();
+        let client_state_key = storage::client_state_key(&client_id);
+        let value =
+            self.read_ibc_data(&client_state_key)?.ok_or_else(|| {
+                Error::Client(format!(
+                    "The client to be frozen doesn't exist: ID {}",
+                    client_id
+                ))
+            })?;
+        let client_state =
+            AnyClientState::decode_vec(&value).map_err(Error::Decoding)?;
+        if msg.misbehaviour.client_id() != &client_id {
+            return Err(Error::Misbehaviour(format!(
+                "The client ID is mismatched: {} in the misbehaviour, {} in \
+                 the message",
+                msg.misbehaviour.client_id(),
+                client_id
+            ))
+            .into());
+        }
+        let new_client_state = freeze_client(client_state, &msg.misbehaviour)?;
+
+        // the consensus states and the update time are kept as they were
+        self.write_ibc_data(
+            &client_state_key,
+            new_client_state
+                .encode_vec()
+                .expect("encoding shouldn't fail"),
+        )?;
+
+        let event =
+            make_misbehaviour_event(&client_id, msg).try_into().unwrap();
+        self.emit_ibc_event(event)?;
+
+        Ok(())
+    }
+
     /// Upgrade a client
     fn upgrade_client(
         &mut self,
@@ -1213,6 +1262,88 @@ pub fn update_client(
     }
 }
 
+/// Freeze a client with the given evidence of its misbehaviour. The headers
+/// of the evidence are only checked to be conflicting, their validity for
+/// the client has to be verified separately.
+pub fn freeze_client(
+    client_state: AnyClientState,
+    misbehaviour: &AnyMisbehaviour,
+) -> Result<AnyClientState> {
+    if client_state.is_frozen() {
+        return Err(Error::Misbehaviour(format!(
+            "The client has already been frozen: ID {}",
+            misbehaviour.client_id()
+        )));
+    }
+    let (header1, header2) = misbehaviour_headers(misbehaviour);
+    if !is_conflicting(&header1, &header2) {
+        return Err(Error::Misbehaviour(
+            "The headers of the misbehaviour don't conflict".to_owned(),
+        ));
+    }
+    let height = misbehaviour.height();
+    match client_state {
+        AnyClientState::Tendermint(cs) => match misbehaviour {
+            AnyMisbehaviour::Tendermint(_) => cs
+                .with_frozen_height(height)
+                .map(|cs| cs.wrap_any())
+                .map_err(|e| Error::Misbehaviour(e.to_string())),
+            #[cfg(any(feature = "ibc-mocks-abcipp", feature = "ibc-mocks"))]
+            _ => Err(Error::Misbehaviour(
+                "The misbehaviour type is mismatched".to_owned(),
+            )),
+        },
+        #[cfg(any(feature = "ibc-mocks-abcipp", feature = "ibc-mocks"))]
+        AnyClientState::Mock(cs) => match misbehaviour {
+            AnyMisbehaviour::Mock(_) => Ok(MockClientState {
+                frozen_height: Some(height),
+                ..cs
+            }
+            .wrap_any()),
+            _ => Err(Error::Misbehaviour(
+                "The misbehaviour type is mismatched".to_owned(),
+            )),
+        },
+    }
+}
+
+/// Returns the two headers of the given misbehaviour
+pub fn misbehaviour_headers(
+    misbehaviour: &AnyMisbehaviour,
+) -> (AnyHeader, AnyHeader) {
+    match misbehaviour {
+        AnyMisbehaviour::Tendermint(m) => {
+            (m.header1.clone().wrap_any(), m.header2.clone().wrap_any())
+        }
+        #[cfg(any(feature = "ibc-mocks-abcipp", feature = "ibc-mocks"))]
+        AnyMisbehaviour::Mock(m) => {
+            (m.header1.wrap_any(), m.header2.wrap_any())
+        }
+    }
+}
+
+/// Check if two headers of a client are the evidence of a misbehaviour of
+/// the counterparty chain, i.e. they are different headers at the same
+/// height or the time of the higher header isn't after that of the lower one
+fn is_conflicting(header1: &AnyHeader, header2: &AnyHeader) -> bool {
+    if header1.height() == header2.height() {
+        match (header1, header2) {
+            (AnyHeader::Tendermint(h1), AnyHeader::Tendermint(h2)) => {
+                h1.signed_header.header.hash() != h2.signed_header.header.hash()
+            }
+            #[cfg(any(feature = "ibc-mocks-abcipp", feature = "ibc-mocks"))]
+            _ => header1 != header2,
+        }
+    } else {
+        let (lower, higher) = if header1.height() < header2.height() {
+            (header1, header2)
+        } else {
+            (header2, header1)
+        };
+        higher.timestamp().nanoseconds() <= lower.timestamp().nanoseconds()
+    }
+}
+
 /// Returns a new client ID
 pub fn client_id(client_type: ClientType, counter: u64) -> Result<ClientId> {
     ClientId::new(client_type, counter).map_err(Error::ClientId)
@@ -1387,6 +1518,25 @@ pub fn make_upgrade_client_event(
     IbcEvent::UpgradeClient(UpgradeClient::from(attributes))
 }
 
+/// Makes ClientMisbehaviour event
+pub fn make_misbehaviour_event(
+    client_id: &ClientId,
+    msg: &MsgSubmitAnyMisbehaviour,
+) -> IbcEvent {
+    let client_type = match msg.misbehaviour {
+        AnyMisbehaviour::Tendermint(_) => ClientType::Tendermint,
+        #[cfg(any(feature = "ibc-mocks-abcipp", feature = "ibc-mocks"))]
+        AnyMisbehaviour::Mock(_) => ClientType::Mock,
+    };
+    let attributes = ClientAttributes {
+        client_id: client_id.clone(),
+        client_type,
+        consensus_height: msg.misbehaviour.height(),
+        ..Default::default()
+    };
+    IbcEvent::ClientMisbehaviour(ClientMisbehaviour::from(attributes))
+}
+
 /// Makes OpenInitConnection event
 pub fn make_open_init_connection_event(
     conn_id: &ConnectionId,
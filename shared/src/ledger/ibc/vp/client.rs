@@ -5,8 +5,8 @@ use std::str::FromStr;
 use thiserror::Error;
 
 use super::super::handler::{
-    make_create_client_event, make_update_client_event,
-    make_upgrade_client_event,
+    freeze_client, make_create_client_event, make_misbehaviour_event,
+    make_update_client_event, make_upgrade_client_event, misbehaviour_headers,
 };
 use super::super::storage::{
     client_counter_key, client_state_key, client_type_key,
@@ -24,6 +24,8 @@ use crate::ibc::core::ics02_client::client_type::ClientType;
 use crate::ibc::core::ics02_client::context::ClientReader;
 use crate::ibc::core::ics02_client::error::Error as Ics02Error;
 use crate::ibc::core::ics02_client::height::Height;
+use crate::ibc::core::ics02_client::misbehaviour::Misbehaviour;
+use crate::ibc::core::ics02_client::msgs::misbehavior::MsgSubmitAnyMisbehaviour;
 use crate::ibc::core::ics02_client::msgs::update_client::MsgUpdateAnyClient;
 use crate::ibc::core::ics02_client::msgs::upgrade_client::MsgUpgradeAnyClient;
 use crate::ibc::core::ics02_client::msgs::ClientMsg;
@@ -230,6 +232,13 @@ where
         client_id: &ClientId,
         tx_data: &[u8],
     ) -> Result<()> {
+        // check the type of data in tx_data
+        let ibc_msg = IbcMessage::decode(tx_data)?;
+        // freezing a client doesn't update its time and height
+        if let Ics26Envelope::Ics2Msg(ClientMsg::Misbehaviour(msg)) = ibc_msg.0
+        {
+            return self.verify_misbehaviour(client_id, msg);
+        }
         if self.get_client_update_time_change(client_id)?
             != StateChange::Updated
         {
@@ -238,8 +247,6 @@ where
                 client_id,
             )));
         }
-        // check the type of data in tx_data
-        let ibc_msg = IbcMessage::decode(tx_data)?;
         match ibc_msg.0 {
             Ics26Envelope::Ics2Msg(ClientMsg::UpdateClient(msg)) => {
                 self.verify_update_client(client_id, msg)
@@ -377,6 +384,74 @@ where
             .map_err(|e| Error::IbcEvent(e.to_string()))
     }
 
+    fn verify_misbehaviour(
+        &self,
+        client_id: &ClientId,
+        msg: MsgSubmitAnyMisbehaviour,
+    ) -> Result<()> {
+        if msg.client_id != *client_id
+            || msg.misbehaviour.client_id() != client_id
+        {
+            return Err(Error::InvalidClient(format!(
+                "The client ID is mismatched: {} in the tx data, {} in the \
+                 misbehaviour, {} in the key",
+                msg.client_id,
+                msg.misbehaviour.client_id(),
+                client_id,
+            )));
+        }
+
+        // check the posterior state
+        let client_state = ClientReader::client_state(self, client_id)
+            .map_err(|_| {
+                Error::InvalidClient(format!(
+                    "The client state doesn't exist: ID {}",
+                    client_id
+                ))
+            })?;
+        // check the prior state
+        let prev_client_state = self.client_state_pre(client_id)?;
+
+        // both headers should be valid for the prior client
+        let client =
+            AnyClient::from_client_type(prev_client_state.client_type());
+        let (header1, header2) = misbehaviour_headers(&msg.misbehaviour);
+        for header in [header1, header2] {
+            client
+                .check_header_and_update_state(
+                    self,
+                    client_id.clone(),
+                    prev_client_state.clone(),
+                    header,
+                )
+                .map_err(|e| {
+                    Error::InvalidHeader(format!(
+                        "The header of the misbehaviour is invalid: ID {}, {}",
+                        client_id, e,
+                    ))
+                })?;
+        }
+        // the client should be frozen only by the conflicting headers
+        let frozen_client_state =
+            freeze_client(prev_client_state, &msg.misbehaviour).map_err(
+                |e| {
+                    Error::InvalidHeader(format!(
+                        "The misbehaviour is invalid: ID {}, {}",
+                        client_id, e,
+                    ))
+                },
+            )?;
+        if frozen_client_state != client_state {
+            return Err(Error::InvalidClient(
+                "The frozen client state is unexpected".to_owned(),
+            ));
+        }
+
+        let event = make_misbehaviour_event(client_id, &msg);
+        self.check_emitted_event(event)
+            .map_err(|e| Error::IbcEvent(e.to_string()))
+    }
+
     fn client_state_pre(&self, client_id: &ClientId) -> Result<AnyClientState> {
         let key = client_state_key(client_id);
         match self.ctx.read_bytes_pre(&key) {
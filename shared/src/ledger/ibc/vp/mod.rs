@@ -317,10 +317,14 @@ mod tests {
 
     use crate::ibc::applications::ics20_fungible_token_transfer::msgs::transfer::MsgTransfer;
     use crate::ibc::core::ics02_client::client_consensus::ConsensusState;
-    use crate::ibc::core::ics02_client::client_state::ClientState;
+    use crate::ibc::core::ics02_client::client_state::{
+        AnyClientState, ClientState,
+    };
     use crate::ibc::core::ics02_client::client_type::ClientType;
     use crate::ibc::core::ics02_client::header::Header;
+    use crate::ibc::core::ics02_client::misbehaviour::Misbehaviour;
     use crate::ibc::core::ics02_client::msgs::create_client::MsgCreateAnyClient;
+    use crate::ibc::core::ics02_client::msgs::misbehavior::MsgSubmitAnyMisbehaviour;
     use crate::ibc::core::ics02_client::msgs::update_client::MsgUpdateAnyClient;
     use crate::ibc::core::ics03_connection::connection::{
         ConnectionEnd, Counterparty as ConnCounterparty, State as ConnState,
@@ -347,6 +351,7 @@ mod tests {
     };
     use crate::ibc::mock::client_state::{MockClientState, MockConsensusState};
     use crate::ibc::mock::header::MockHeader;
+    use crate::ibc::mock::misbehaviour::Misbehaviour as MockMisbehaviour;
     use crate::ibc::proofs::{ConsensusProof, Proofs};
     use crate::ibc::signer::Signer;
     use crate::ibc::timestamp::Timestamp;
@@ -364,8 +369,8 @@ mod tests {
         make_open_confirm_channel_event, make_open_confirm_connection_event,
         make_open_init_channel_event, make_open_init_connection_event,
         make_open_try_channel_event, make_open_try_connection_event,
-        make_send_packet_event, make_update_client_event, packet_from_message,
-        try_connection,
+        make_misbehaviour_event, make_send_packet_event,
+        make_update_client_event, packet_from_message, try_connection,
     };
     use super::super::storage::{
         ack_key, capability_key, channel_key, client_state_key,
@@ -380,6 +385,7 @@ mod tests {
     use crate::ledger::storage::testing::TestStorage;
     use crate::ledger::storage::write_log::WriteLog;
     use crate::proto::Tx;
+    use crate::types::chain::ChainId;
     use crate::types::ibc::data::{PacketAck, PacketReceipt};
     use crate::vm::wasm;
     use crate::types::storage::TxIndex;
@@ -567,7 +573,8 @@ mod tests {
         let tx_code = vec![];
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
-        let tx = Tx::new(tx_code, Some(tx_data)).sign(&keypair_1());
+        let tx = Tx::new(tx_code, Some(tx_data), ChainId::default(), None)
+            .sign(&keypair_1());
         let gas_meter = VpGasMeter::new(0);
         let (vp_wasm_cache, _vp_cache_dir) =
             wasm::compilation_cache::common::testing::cache();
@@ -607,7 +614,8 @@ mod tests {
         let tx_index = TxIndex::default();
         let tx_code = vec![];
         let tx_data = vec![];
-        let tx = Tx::new(tx_code, Some(tx_data)).sign(&keypair_1());
+        let tx = Tx::new(tx_code, Some(tx_data), ChainId::default(), None)
+            .sign(&keypair_1());
         let gas_meter = VpGasMeter::new(0);
         let (vp_wasm_cache, _vp_cache_dir) =
             wasm::compilation_cache::common::testing::cache();
@@ -688,7 +696,8 @@ mod tests {
         let tx_code = vec![];
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
-        let tx = Tx::new(tx_code, Some(tx_data)).sign(&keypair_1());
+        let tx = Tx::new(tx_code, Some(tx_data), ChainId::default(), None)
+            .sign(&keypair_1());
         let gas_meter = VpGasMeter::new(0);
         let (vp_wasm_cache, _vp_cache_dir) =
             wasm::compilation_cache::common::testing::cache();
@@ -720,6 +729,99 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_submit_misbehaviour() {
+        let (mut storage, mut write_log) = insert_init_states();
+        write_log.commit_block(&mut storage).expect("commit failed");
+
+        // two different headers at the same height
+        let client_id = get_client_id();
+        let height = Height::new(0, 11);
+        let header1 = MockHeader {
+            height,
+            timestamp: Timestamp::now(),
+        };
+        let header2 = MockHeader {
+            height,
+            timestamp: Timestamp::from_nanoseconds(1).unwrap(),
+        };
+        let msg = MsgSubmitAnyMisbehaviour {
+            client_id: client_id.clone(),
+            misbehaviour: MockMisbehaviour {
+                client_id: client_id.clone(),
+                header1,
+                header2,
+            }
+            .wrap_any(),
+            signer: Signer::new("account0"),
+        };
+        // freeze the client
+        let client_state_key = client_state_key(&client_id);
+        let (value, _) = storage.read(&client_state_key).expect("read failed");
+        let prev_client_state = AnyClientState::decode_vec(&value.unwrap())
+            .expect("decoding failed");
+        let client_state = handler::freeze_client(
+            prev_client_state.clone(),
+            &msg.misbehaviour,
+        )
+        .expect("freezing failed");
+        assert!(client_state.is_frozen());
+        let bytes = client_state.encode_vec().expect("encoding failed");
+        write_log
+            .write(&client_state_key, bytes)
+            .expect("write failed");
+        let event = make_misbehaviour_event(&client_id, &msg);
+        write_log.set_ibc_event(event.try_into().unwrap());
+
+        let tx_index = TxIndex::default();
+        let tx_code = vec![];
+        let mut tx_data = vec![];
+        msg.to_any().encode(&mut tx_data).expect("encoding failed");
+        let tx = Tx::new(tx_code, Some(tx_data), ChainId::default(), None)
+            .sign(&keypair_1());
+        let gas_meter = VpGasMeter::new(0);
+        let (vp_wasm_cache, _vp_cache_dir) =
+            wasm::compilation_cache::common::testing::cache();
+
+        let mut keys_changed = BTreeSet::new();
+        keys_changed.insert(client_state_key);
+
+        let verifiers = BTreeSet::new();
+        let ctx = Ctx::new(
+            &ADDRESS,
+            &storage,
+            &write_log,
+            &tx,
+            &tx_index,
+            gas_meter,
+            &keys_changed,
+            &verifiers,
+            vp_wasm_cache,
+        );
+        let ibc = Ibc { ctx };
+        // this should return true because the client has been frozen
+        assert!(
+            ibc.validate_tx(
+                tx.data.as_ref().unwrap(),
+                &keys_changed,
+                &verifiers
+            )
+            .expect("validation failed")
+        );
+
+        // the same header isn't the evidence of a misbehaviour
+        let misbehaviour = MockMisbehaviour {
+            client_id,
+            header1,
+            header2: header1,
+        }
+        .wrap_any();
+        assert_matches!(
+            handler::freeze_client(prev_client_state, &misbehaviour),
+            Err(handler::Error::Misbehaviour(_))
+        );
+    }
+
     #[test]
     fn test_init_connection() {
         let (mut storage, mut write_log) = insert_init_states();
@@ -747,7 +849,8 @@ mod tests {
         let tx_code = vec![];
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
-        let tx = Tx::new(tx_code, Some(tx_data)).sign(&keypair_1());
+        let tx = Tx::new(tx_code, Some(tx_data), ChainId::default(), None)
+            .sign(&keypair_1());
         let gas_meter = VpGasMeter::new(0);
         let (vp_wasm_cache, _vp_cache_dir) =
             wasm::compilation_cache::common::testing::cache();
@@ -803,7 +906,8 @@ mod tests {
         let tx_code = vec![];
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
-        let tx = Tx::new(tx_code, Some(tx_data)).sign(&keypair_1());
+        let tx = Tx::new(tx_code, Some(tx_data), ChainId::default(), None)
+            .sign(&keypair_1());
         let gas_meter = VpGasMeter::new(0);
         let (vp_wasm_cache, _vp_cache_dir) =
             wasm::compilation_cache::common::testing::cache();
@@ -885,7 +989,8 @@ mod tests {
         let tx_code = vec![];
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
-        let tx = Tx::new(tx_code, Some(tx_data)).sign(&keypair_1());
+        let tx = Tx::new(tx_code, Some(tx_data), ChainId::default(), None)
+            .sign(&keypair_1());
         let gas_meter = VpGasMeter::new(0);
         let (vp_wasm_cache, _vp_cache_dir) =
             wasm::compilation_cache::common::testing::cache();
@@ -973,7 +1078,8 @@ mod tests {
         let tx_index = TxIndex::default();
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
-        let tx = Tx::new(tx_code, Some(tx_data)).sign(&keypair_1());
+        let tx = Tx::new(tx_code, Some(tx_data), ChainId::default(), None)
+            .sign(&keypair_1());
         let gas_meter = VpGasMeter::new(0);
         let (vp_wasm_cache, _vp_cache_dir) =
             wasm::compilation_cache::common::testing::cache();
@@ -1048,7 +1154,8 @@ mod tests {
         let tx_index = TxIndex::default();
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
-        let tx = Tx::new(tx_code, Some(tx_data)).sign(&keypair_1());
+        let tx = Tx::new(tx_code, Some(tx_data), ChainId::default(), None)
+            .sign(&keypair_1());
         let gas_meter = VpGasMeter::new(0);
         let (vp_wasm_cache, _vp_cache_dir) =
             wasm::compilation_cache::common::testing::cache();
@@ -1109,7 +1216,8 @@ mod tests {
         let tx_code = vec![];
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
-        let tx = Tx::new(tx_code, Some(tx_data)).sign(&keypair_1());
+        let tx = Tx::new(tx_code, Some(tx_data), ChainId::default(), None)
+            .sign(&keypair_1());
         let gas_meter = VpGasMeter::new(0);
         let (vp_wasm_cache, _vp_cache_dir) =
             wasm::compilation_cache::common::testing::cache();
@@ -1189,7 +1297,8 @@ mod tests {
         let tx_code = vec![];
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
-        let tx = Tx::new(tx_code, Some(tx_data)).sign(&keypair_1());
+        let tx = Tx::new(tx_code, Some(tx_data), ChainId::default(), None)
+            .sign(&keypair_1());
         let gas_meter = VpGasMeter::new(0);
         let (vp_wasm_cache, _vp_cache_dir) =
             wasm::compilation_cache::common::testing::cache();
@@ -1277,7 +1386,8 @@ mod tests {
         let tx_code = vec![];
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
-        let tx = Tx::new(tx_code, Some(tx_data)).sign(&keypair_1());
+        let tx = Tx::new(tx_code, Some(tx_data), ChainId::default(), None)
+            .sign(&keypair_1());
         let gas_meter = VpGasMeter::new(0);
         let (vp_wasm_cache, _vp_cache_dir) =
             wasm::compilation_cache::common::testing::cache();
@@ -1362,7 +1472,8 @@ mod tests {
         let tx_code = vec![];
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
-        let tx = Tx::new(tx_code, Some(tx_data)).sign(&keypair_1());
+        let tx = Tx::new(tx_code, Some(tx_data), ChainId::default(), None)
+            .sign(&keypair_1());
         let gas_meter = VpGasMeter::new(0);
         let (vp_wasm_cache, _vp_cache_dir) =
             wasm::compilation_cache::common::testing::cache();
@@ -1403,7 +1514,8 @@ mod tests {
         let tx_index = TxIndex::default();
         let tx_code = vec![];
         let tx_data = vec![];
-        let tx = Tx::new(tx_code, Some(tx_data)).sign(&keypair_1());
+        let tx = Tx::new(tx_code, Some(tx_data), ChainId::default(), None)
+            .sign(&keypair_1());
         let gas_meter = VpGasMeter::new(0);
         let (vp_wasm_cache, _vp_cache_dir) =
             wasm::compilation_cache::common::testing::cache();
@@ -1445,7 +1557,8 @@ mod tests {
         let tx_index = TxIndex::default();
         let tx_code = vec![];
         let tx_data = vec![];
-        let tx = Tx::new(tx_code, Some(tx_data)).sign(&keypair_1());
+        let tx = Tx::new(tx_code, Some(tx_data), ChainId::default(), None)
+            .sign(&keypair_1());
         let gas_meter = VpGasMeter::new(0);
         let (vp_wasm_cache, _vp_cache_dir) =
             wasm::compilation_cache::common::testing::cache();
@@ -1529,7 +1642,8 @@ mod tests {
         let tx_code = vec![];
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
-        let tx = Tx::new(tx_code, Some(tx_data)).sign(&keypair_1());
+        let tx = Tx::new(tx_code, Some(tx_data), ChainId::default(), None)
+            .sign(&keypair_1());
         let gas_meter = VpGasMeter::new(0);
         let (vp_wasm_cache, _vp_cache_dir) =
             wasm::compilation_cache::common::testing::cache();
@@ -1618,7 +1732,8 @@ mod tests {
         let tx_code = vec![];
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
-        let tx = Tx::new(tx_code, Some(tx_data)).sign(&keypair_1());
+        let tx = Tx::new(tx_code, Some(tx_data), ChainId::default(), None)
+            .sign(&keypair_1());
         let gas_meter = VpGasMeter::new(0);
         let (vp_wasm_cache, _vp_cache_dir) =
             wasm::compilation_cache::common::testing::cache();
@@ -1712,7 +1827,8 @@ mod tests {
         let tx_code = vec![];
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
-        let tx = Tx::new(tx_code, Some(tx_data)).sign(&keypair_1());
+        let tx = Tx::new(tx_code, Some(tx_data), ChainId::default(), None)
+            .sign(&keypair_1());
         let gas_meter = VpGasMeter::new(0);
         let (vp_wasm_cache, _vp_cache_dir) =
             wasm::compilation_cache::common::testing::cache();
@@ -1798,7 +1914,8 @@ mod tests {
         let tx_code = vec![];
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
-        let tx = Tx::new(tx_code, Some(tx_data)).sign(&keypair_1());
+        let tx = Tx::new(tx_code, Some(tx_data), ChainId::default(), None)
+            .sign(&keypair_1());
         let gas_meter = VpGasMeter::new(0);
         let (vp_wasm_cache, _vp_cache_dir) =
             wasm::compilation_cache::common::testing::cache();
@@ -1892,7 +2009,8 @@ mod tests {
         let tx_code = vec![];
         let mut tx_data = vec![];
         msg.to_any().encode(&mut tx_data).expect("encoding failed");
-        let tx = Tx::new(tx_code, Some(tx_data)).sign(&keypair_1());
+        let tx = Tx::new(tx_code, Some(tx_data), ChainId::default(), None)
+            .sign(&keypair_1());
         let gas_meter = VpGasMeter::new(0);
         let (vp_wasm_cache, _vp_cache_dir) =
             wasm::compilation_cache::common::testing::cache();
@@ -1942,7 +2060,8 @@ mod tests {
         let tx_index = TxIndex::default();
         let tx_code = vec![];
         let tx_data = vec![];
-        let tx = Tx::new(tx_code, Some(tx_data)).sign(&keypair_1());
+        let tx = Tx::new(tx_code, Some(tx_data), ChainId::default(), None)
+            .sign(&keypair_1());
         let gas_meter = VpGasMeter::new(0);
         let (vp_wasm_cache, _vp_cache_dir) =
             wasm::compilation_cache::common::testing::cache();
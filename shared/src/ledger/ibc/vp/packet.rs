@@ -10,6 +10,9 @@ use super::super::storage::{
     Error as IbcStorageError,
 };
 use super::{Ibc, StateChange};
+use crate::ibc::core::ics02_client::client_state::{
+    AnyClientState, ClientState,
+};
 use crate::ibc::core::ics02_client::height::Height;
 use crate::ibc::core::ics04_channel::channel::{
     ChannelEnd, Counterparty, Order, State,
@@ -411,19 +414,13 @@ where
             ));
         }
 
+        let client_id = connection.client_id();
+        let client_state = self.unfrozen_client_state(client_id)?;
+
         // check timeout
         match phase {
             Phase::Send => {
-                let client_id = connection.client_id();
-                let height = match self.client_state(client_id) {
-                    Ok(s) => s.latest_height(),
-                    Err(_) => {
-                        return Err(Error::InvalidClient(format!(
-                            "The client state doesn't exist: ID {}",
-                            client_id
-                        )));
-                    }
-                };
+                let height = client_state.latest_height();
                 self.check_timeout(client_id, height, packet)
                     .map_err(|e| Error::InvalidPacket(e.to_string()))?;
             }
@@ -441,6 +438,28 @@ where
         Ok(())
     }
 
+    /// Read the client state, which shouldn't be frozen because the
+    /// counterparty chain can no longer be trusted once its misbehaviour has
+    /// been submitted
+    fn unfrozen_client_state(
+        &self,
+        client_id: &ClientId,
+    ) -> Result<AnyClientState> {
+        let client_state = self.client_state(client_id).map_err(|_| {
+            Error::InvalidClient(format!(
+                "The client state doesn't exist: ID {}",
+                client_id
+            ))
+        })?;
+        if client_state.is_frozen() {
+            return Err(Error::InvalidClient(format!(
+                "The client is frozen: ID {}",
+                client_id
+            )));
+        }
+        Ok(client_state)
+    }
+
     fn validate_packet_commitment(
         &self,
         packet: &Packet,
@@ -583,6 +602,7 @@ where
             .connection_from_channel(&channel)
             .map_err(|e| Error::InvalidConnection(e.to_string()))?;
         let client_id = connection.client_id().clone();
+        self.unfrozen_client_state(&client_id)?;
 
         // check if the packet actually timed out
         match self.check_timeout(&client_id, proofs.height(), &packet) {
@@ -0,0 +1,149 @@
+//! General inflation system that will be used to process rewards for
+//! proof-of-stake, providing liquidity to shielded asset pools, and public
+//! goods funding.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use crate::types::token;
+
+/// The type of rewards that can be computed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RewardsType {
+    /// Proof-of-stake rewards
+    Staking,
+    /// Rewards for locking tokens in the multi-asset shielded pool
+    Masp,
+    /// Rewards for public goods funding (PGF)
+    PubGoodsFunding,
+}
+
+/// Holds the PD controller values that should be updated in storage
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValsToUpdate {
+    pub locked_ratio: Decimal,
+    pub inflation: u64,
+}
+
+/// PD controller used to dynamically adjust the rewards rates
+#[derive(Debug, Clone)]
+pub struct RewardsController {
+    /// Locked token amount in the relevant system
+    pub locked_tokens: token::Amount,
+    /// Total token supply
+    pub total_tokens: token::Amount,
+    /// PD target locked ratio
+    pub locked_ratio_target: Decimal,
+    /// PD last locked ratio
+    pub locked_ratio_last: Decimal,
+    /// Maximum reward rate
+    pub max_reward_rate: Decimal,
+    /// Last inflation amount
+    pub last_inflation_amount: token::Amount,
+    /// Nominal proportional gain
+    pub p_gain_nom: Decimal,
+    /// Nominal derivative gain
+    pub d_gain_nom: Decimal,
+    /// Number of epochs per year
+    pub epochs_per_year: u64,
+}
+
+impl RewardsController {
+    /// Calculate a new rewards rate
+    pub fn run(&self) -> ValsToUpdate {
+        let locked_tokens = Decimal::from(u64::from(self.locked_tokens));
+        let total_tokens = Decimal::from(u64::from(self.total_tokens));
+        let last_inflation_amount =
+            Decimal::from(u64::from(self.last_inflation_amount));
+        let epochs_py = Decimal::from(self.epochs_per_year);
+
+        let locked_ratio = if total_tokens.is_zero() {
+            Decimal::ZERO
+        } else {
+            locked_tokens / total_tokens
+        };
+        let max_inflation = if epochs_py.is_zero() {
+            Decimal::ZERO
+        } else {
+            total_tokens * self.max_reward_rate / epochs_py
+        };
+        let p_gain = self.p_gain_nom * max_inflation;
+        let d_gain = self.d_gain_nom * max_inflation;
+
+        let error = self.locked_ratio_target - locked_ratio;
+        let delta_error = self.locked_ratio_last - locked_ratio;
+        let control_val = p_gain * error - d_gain * delta_error;
+
+        let inflation = (last_inflation_amount + control_val)
+            .max(dec!(0.0))
+            .min(max_inflation);
+        let inflation: u64 = inflation
+            .floor()
+            .to_u64()
+            .expect("Inflation amount must fit into u64");
+
+        ValsToUpdate {
+            locked_ratio,
+            inflation,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn controller() -> RewardsController {
+        RewardsController {
+            locked_tokens: token::Amount::whole(500),
+            total_tokens: token::Amount::whole(1_000),
+            locked_ratio_target: dec!(0.66666666),
+            locked_ratio_last: dec!(0.5),
+            max_reward_rate: dec!(0.1),
+            last_inflation_amount: token::Amount::from(0),
+            p_gain_nom: dec!(0.1),
+            d_gain_nom: dec!(0.1),
+            epochs_per_year: 365,
+        }
+    }
+
+    /// With the locked ratio below the target, the inflation must increase,
+    /// but never beyond the maximum inflation per epoch.
+    #[test]
+    fn test_inflation_increases_below_target() {
+        let controller = controller();
+        let ValsToUpdate {
+            locked_ratio,
+            inflation,
+        } = controller.run();
+        assert_eq!(locked_ratio, dec!(0.5));
+        assert!(inflation > 0);
+
+        let max_inflation = u64::from(token::Amount::whole(1_000)) / 10 / 365;
+        let controller = RewardsController {
+            last_inflation_amount: token::Amount::from(max_inflation),
+            ..controller
+        };
+        let ValsToUpdate { inflation, .. } = controller.run();
+        assert_eq!(inflation, max_inflation);
+    }
+
+    /// With the locked ratio above the target and no previous inflation, the
+    /// inflation must be clamped to zero.
+    #[test]
+    fn test_inflation_zero_above_target() {
+        let controller = RewardsController {
+            locked_tokens: token::Amount::whole(900),
+            locked_ratio_last: dec!(0.9),
+            ..controller()
+        };
+        let ValsToUpdate {
+            locked_ratio,
+            inflation,
+        } = controller.run();
+        assert_eq!(locked_ratio, dec!(0.9));
+        assert_eq!(inflation, 0);
+    }
+}
@@ -5,6 +5,7 @@ pub mod events;
 pub mod gas;
 pub mod governance;
 pub mod ibc;
+pub mod inflation;
 pub mod masp;
 pub mod native_vp;
 pub mod parameters;
@@ -12,8 +13,10 @@ pub mod pos;
 #[cfg(all(feature = "wasm-runtime", feature = "ferveo-tpke"))]
 pub mod protocol;
 pub mod queries;
+pub mod replay_protection;
 pub mod slash_fund;
 pub mod storage;
 pub mod storage_api;
+pub mod token_metadata;
 pub mod tx_env;
 pub mod vp_env;
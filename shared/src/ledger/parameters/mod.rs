@@ -315,7 +315,7 @@ where
 /// gas cost.
 pub fn update_epochs_per_year_parameter<DB, H>(
     storage: &mut Storage<DB, H>,
-    value: &EpochDuration,
+    value: &u64,
 ) -> std::result::Result<u64, WriteError>
 where
     DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
@@ -329,7 +329,7 @@ where
 /// cost.
 pub fn update_pos_gain_p_parameter<DB, H>(
     storage: &mut Storage<DB, H>,
-    value: &EpochDuration,
+    value: &Decimal,
 ) -> std::result::Result<u64, WriteError>
 where
     DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
@@ -343,7 +343,7 @@ where
 /// cost.
 pub fn update_pos_gain_d_parameter<DB, H>(
     storage: &mut Storage<DB, H>,
-    value: &EpochDuration,
+    value: &Decimal,
 ) -> std::result::Result<u64, WriteError>
 where
     DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
@@ -357,7 +357,7 @@ where
 /// gas cost.
 pub fn update_staked_ratio_parameter<DB, H>(
     storage: &mut Storage<DB, H>,
-    value: &EpochDuration,
+    value: &Decimal,
 ) -> std::result::Result<u64, WriteError>
 where
     DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
@@ -371,7 +371,7 @@ where
 /// and gas cost.
 pub fn update_pos_inflation_amount_parameter<DB, H>(
     storage: &mut Storage<DB, H>,
-    value: &EpochDuration,
+    value: &u64,
 ) -> std::result::Result<u64, WriteError>
 where
     DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
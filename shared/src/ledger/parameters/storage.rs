@@ -12,6 +12,7 @@ const POS_GAIN_P_KEY: &str = "pos_gain_p";
 const POS_GAIN_D_KEY: &str = "pos_gain_d";
 const STAKED_RATIO_KEY: &str = "staked_ratio_key";
 const POS_INFLATION_AMOUNT_KEY: &str = "pos_inflation_amount_key";
+const MERKLE_TREE_UPGRADE_HEIGHT_KEY: &str = "merkle_tree_upgrade_height";
 
 /// Returns if the key is a parameter key.
 pub fn is_parameter_key(key: &Key) -> bool {
@@ -24,6 +25,7 @@ pub fn is_protocol_parameter_key(key: &Key) -> bool {
         || is_max_expected_time_per_block_key(key)
         || is_tx_whitelist_key(key)
         || is_vp_whitelist_key(key)
+        || is_merkle_tree_upgrade_height_key(key)
 }
 
 /// Returns if the key is an epoch storage key.
@@ -106,6 +108,14 @@ pub fn is_pos_inflation_amount_key(key: &Key) -> bool {
     ] if addr == &ADDRESS && pos_inflation_amount == POS_INFLATION_AMOUNT_KEY)
 }
 
+/// Returns if the key is the Merkle tree upgrade height key.
+pub fn is_merkle_tree_upgrade_height_key(key: &Key) -> bool {
+    matches!(&key.segments[..], [
+        DbKeySeg::AddressSeg(addr),
+        DbKeySeg::StringSeg(upgrade_height),
+    ] if addr == &ADDRESS && upgrade_height == MERKLE_TREE_UPGRADE_HEIGHT_KEY)
+}
+
 /// Storage key used for epoch parameter.
 pub fn get_epoch_duration_storage_key() -> Key {
     Key {
@@ -205,3 +215,15 @@ pub fn get_pos_inflation_amount_key() -> Key {
         ],
     }
 }
+
+/// Storage key used for the block height from which the Merkle tree of a
+/// chain started before the added sub-trees existed is migrated to them. It's
+/// not set at genesis and can only be set by governance.
+pub fn get_merkle_tree_upgrade_height_key() -> Key {
+    Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(ADDRESS),
+            DbKeySeg::StringSeg(MERKLE_TREE_UPGRADE_HEIGHT_KEY.to_string()),
+        ],
+    }
+}
@@ -143,6 +143,20 @@ impl From<namada_proof_of_stake::CommissionRateChangeError<Address>>
     }
 }
 
+impl From<namada_proof_of_stake::UnjailError<Address>> for storage_api::Error {
+    fn from(err: namada_proof_of_stake::UnjailError<Address>) -> Self {
+        Self::new(err)
+    }
+}
+
+impl From<namada_proof_of_stake::ClaimRewardsError<Address>>
+    for storage_api::Error
+{
+    fn from(err: namada_proof_of_stake::ClaimRewardsError<Address>) -> Self {
+        Self::new(err)
+    }
+}
+
 #[macro_use]
 mod macros {
     /// Implement `PosReadOnly` for a type that implements
@@ -213,6 +227,17 @@ mod macros {
                 Ok(value.map(|value| $crate::ledger::storage::types::decode(value).unwrap()))
             }
 
+            fn read_bond_rewards(
+                &self,
+                key: &BondId,
+            ) -> std::result::Result<Self::TokenAmount, Self::Error> {
+                let value =
+                    $crate::ledger::storage_api::StorageRead::read_bytes(self, &$crate::ledger::pos::bond_rewards_key(key))?;
+                Ok(value
+                    .map(|value| $crate::ledger::storage::types::decode(value).unwrap())
+                    .unwrap_or_default())
+            }
+
             fn read_validator_state(
                 &self,
                 key: &Self::Address,
@@ -1,5 +1,7 @@
 //! Proof-of-Stake storage keys and storage integration via [`PosBase`] trait.
 
+use std::collections::BTreeMap;
+
 use namada_proof_of_stake::parameters::PosParams;
 use namada_proof_of_stake::types::ValidatorStates;
 use namada_proof_of_stake::{types, PosBase};
@@ -10,6 +12,7 @@ use super::{
 };
 use crate::ledger::storage::types::{decode, encode};
 use crate::ledger::storage::{self, Storage, StorageHasher};
+use crate::ledger::storage_api;
 use crate::types::address::Address;
 use crate::types::storage::{DbKeySeg, Key, KeySeg};
 use crate::types::{key, token};
@@ -28,6 +31,9 @@ const BOND_STORAGE_KEY: &str = "bond";
 const UNBOND_STORAGE_KEY: &str = "unbond";
 const VALIDATOR_SET_STORAGE_KEY: &str = "validator_set";
 const TOTAL_DELTAS_STORAGE_KEY: &str = "total_deltas";
+const REWARDS_ACCUMULATOR_STORAGE_KEY: &str = "validator_rewards_accumulator";
+const BOND_REWARDS_STORAGE_KEY: &str = "bond_rewards";
+const VALIDATOR_MISSED_BLOCKS_STORAGE_KEY: &str = "missed_blocks";
 
 /// Is the given key a PoS storage key?
 pub fn is_pos_key(key: &Key) -> bool {
@@ -336,6 +342,75 @@ pub fn is_total_deltas_key(key: &Key) -> bool {
                     if addr == &ADDRESS && key == TOTAL_DELTAS_STORAGE_KEY)
 }
 
+/// Storage key for the accumulated block rewards fractions of the active
+/// validators in the current epoch.
+pub fn rewards_accumulator_key() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&REWARDS_ACCUMULATOR_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Is storage key for the rewards accumulator?
+pub fn is_rewards_accumulator_key(key: &Key) -> bool {
+    matches!(&key.segments[..],
+                [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(key)]
+                    if addr == &ADDRESS && key == REWARDS_ACCUMULATOR_STORAGE_KEY)
+}
+
+/// Storage key for the rewards pool of a bond with the given ID (source and
+/// validator).
+pub fn bond_rewards_key(bond_id: &BondId) -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&BOND_REWARDS_STORAGE_KEY.to_owned())
+        .and_then(|key| key.push(&bond_id.source.to_db_key()))
+        .and_then(|key| key.push(&bond_id.validator.to_db_key()))
+        .expect("Cannot obtain a storage key")
+}
+
+/// Is storage key for the rewards pool of a bond?
+pub fn is_bond_rewards_key(key: &Key) -> Option<BondId> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::AddressSeg(source),
+            DbKeySeg::AddressSeg(validator),
+        ] if addr == &ADDRESS && prefix == BOND_REWARDS_STORAGE_KEY => {
+            Some(BondId {
+                source: source.clone(),
+                validator: validator.clone(),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Storage key for the blocks in the liveness window that a validator failed
+/// to sign.
+pub fn validator_missed_blocks_key(validator: &Address) -> Key {
+    validator_prefix(validator)
+        .push(&VALIDATOR_MISSED_BLOCKS_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Is storage key for the blocks that a validator failed to sign?
+pub fn is_validator_missed_blocks_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::AddressSeg(validator),
+            DbKeySeg::StringSeg(key),
+        ] if addr == &ADDRESS
+            && prefix == VALIDATOR_STORAGE_PREFIX
+            && key == VALIDATOR_MISSED_BLOCKS_STORAGE_KEY =>
+        {
+            Some(validator)
+        }
+        _ => None,
+    }
+}
+
 /// Get validator address from bond key
 pub fn get_validator_address_from_bond(key: &Key) -> Option<Address> {
     match key.get_at(3) {
@@ -440,6 +515,43 @@ where
         decode(value.unwrap()).unwrap()
     }
 
+    fn read_rewards_accumulator(
+        &self,
+    ) -> BTreeMap<Self::Address, rust_decimal::Decimal> {
+        let (value, _gas) = self.read(&rewards_accumulator_key()).unwrap();
+        value
+            .map(|value| decode(value).unwrap())
+            .unwrap_or_default()
+    }
+
+    fn read_bonds(&self) -> Vec<(BondId, Bonds)> {
+        let (iter, _gas) = self.iter_prefix(&bonds_prefix());
+        iter.filter_map(|(key, value, _gas)| {
+            let key = Key::parse(key).ok()?;
+            let bond_id = is_bond_key(&key)?;
+            Some((bond_id, decode(value).unwrap()))
+        })
+        .collect()
+    }
+
+    fn read_bond_rewards(&self, key: &BondId) -> Self::TokenAmount {
+        let (value, _gas) = self.read(&bond_rewards_key(key)).unwrap();
+        value
+            .map(|value| decode(value).unwrap())
+            .unwrap_or_default()
+    }
+
+    fn read_validator_missed_blocks(
+        &self,
+        key: &Self::Address,
+    ) -> types::MissedBlocks {
+        let (value, _gas) =
+            self.read(&validator_missed_blocks_key(key)).unwrap();
+        value
+            .map(|value| decode(value).unwrap())
+            .unwrap_or_default()
+    }
+
     fn write_pos_params(&mut self, params: &PosParams) {
         self.write(&params_key(), encode(params)).unwrap();
     }
@@ -525,6 +637,27 @@ where
         self.write(&total_deltas_key(), encode(value)).unwrap();
     }
 
+    fn write_rewards_accumulator(
+        &mut self,
+        value: &BTreeMap<Self::Address, rust_decimal::Decimal>,
+    ) {
+        self.write(&rewards_accumulator_key(), encode(value))
+            .unwrap();
+    }
+
+    fn write_bond_rewards(&mut self, key: &BondId, value: Self::TokenAmount) {
+        self.write(&bond_rewards_key(key), encode(&value)).unwrap();
+    }
+
+    fn write_validator_missed_blocks(
+        &mut self,
+        key: &Self::Address,
+        value: &types::MissedBlocks,
+    ) {
+        self.write(&validator_missed_blocks_key(key), encode(value))
+            .unwrap();
+    }
+
     fn credit_tokens(
         &mut self,
         token: &Self::Address,
@@ -545,6 +678,8 @@ where
         };
         self.write(&key, encode(&new_balance))
             .expect("Unable to write token balance for PoS system");
+        storage_api::token::increase_total_supply(self, token, amount)
+            .expect("Unable to update the total supply for PoS system");
     }
 
     fn transfer(
@@ -7,7 +7,9 @@ use borsh::BorshDeserialize;
 use itertools::Itertools;
 pub use namada_proof_of_stake;
 pub use namada_proof_of_stake::parameters::PosParams;
-pub use namada_proof_of_stake::types::{self, Slash, Slashes, ValidatorStates};
+pub use namada_proof_of_stake::types::{
+    self, Slash, Slashes, ValidatorState, ValidatorStates,
+};
 use namada_proof_of_stake::validation::validate;
 use namada_proof_of_stake::{validation, PosReadOnly};
 use rust_decimal::Decimal;
@@ -28,8 +30,8 @@ use crate::ledger::native_vp::{
     self, Ctx, CtxPostStorageRead, CtxPreStorageRead, NativeVp,
 };
 use crate::ledger::pos::{
-    is_validator_address_raw_hash_key, is_validator_commission_rate_key,
-    is_validator_consensus_key_key,
+    is_bond_rewards_key, is_validator_address_raw_hash_key,
+    is_validator_commission_rate_key, is_validator_consensus_key_key,
     is_validator_max_commission_rate_change_key, is_validator_state_key,
 };
 use crate::ledger::storage::{self as ledger_storage, StorageHasher};
@@ -110,7 +112,7 @@ where
         &self,
         tx_data: &[u8],
         keys_changed: &BTreeSet<Key>,
-        _verifiers: &BTreeSet<Address>,
+        verifiers: &BTreeSet<Address>,
     ) -> Result<bool> {
         use validation::Data;
         use validation::DataUpdate::{self, *};
@@ -120,6 +122,8 @@ where
         let mut changes: Vec<DataUpdate<_, _, _, _>> = vec![];
         let current_epoch = self.ctx.pre().get_block_epoch()?;
         let staking_token_address = self.ctx.pre().get_native_token()?;
+        let params = self.ctx.pre().read_pos_params()?;
+        let pipeline_epoch = current_epoch + params.pipeline_len;
 
         for key in keys_changed {
             if is_params_key(key) {
@@ -137,15 +141,40 @@ where
                 });
                 changes.push(ValidatorSet(Data { pre, post }));
             } else if let Some(validator) = is_validator_state_key(key) {
+                let deltas = self
+                    .ctx
+                    .post()
+                    .read_bytes(&validator_deltas_key(validator))?
+                    .and_then(|bytes| {
+                        ValidatorDeltas::try_from_slice(&bytes[..]).ok()
+                    });
                 let pre = self.ctx.pre().read_bytes(key)?.and_then(|bytes| {
                     ValidatorStates::try_from_slice(&bytes[..]).ok()
                 });
                 let post = self.ctx.post().read_bytes(key)?.and_then(|bytes| {
                     ValidatorStates::try_from_slice(&bytes[..]).ok()
                 });
+                // The unjailing of a validator must be authorized by it, its
+                // VP checks the signature of the tx
+                let is_unjailed = matches!(
+                    (&pre, &post),
+                    (Some(pre), Some(post))
+                        if pre.get(pipeline_epoch)
+                            == Some(&ValidatorState::Jailed)
+                            && post.get(pipeline_epoch)
+                                == Some(&ValidatorState::Candidate)
+                );
+                if is_unjailed && !verifiers.contains(validator) {
+                    tracing::info!(
+                        "PoS unjailing of {} without its authorization \
+                         rejected",
+                        validator
+                    );
+                    return Ok(false);
+                }
                 changes.push(Validator {
                     address: validator.clone(),
-                    update: State(Data { pre, post }),
+                    update: State(Data { pre, post }, deltas),
                 });
             } else if let Some(validator) = is_validator_consensus_key_key(key)
             {
@@ -282,6 +311,17 @@ where
                     address: address.clone(),
                     update: MaxCommissionRateChange(Data { pre, post }),
                 });
+            } else if let Some(id) = is_bond_rewards_key(key) {
+                let pre = self.ctx.pre().read_bytes(key)?.and_then(|bytes| {
+                    token::Amount::try_from_slice(&bytes[..]).ok()
+                });
+                let post = self.ctx.post().read_bytes(key)?.and_then(|bytes| {
+                    token::Amount::try_from_slice(&bytes[..]).ok()
+                });
+                changes.push(RewardsPool {
+                    id,
+                    data: Data { pre, post },
+                });
             } else if key.segments.get(0) == Some(&addr.to_db_key()) {
                 // Unknown changes to this address space are disallowed
                 tracing::info!("PoS unrecognized key change {} rejected", key);
@@ -291,7 +331,6 @@ where
             }
         }
 
-        let params = self.ctx.pre().read_pos_params()?;
         let errors = validate(&params, changes, current_epoch);
         Ok(if errors.is_empty() {
             true
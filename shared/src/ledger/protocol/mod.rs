@@ -15,6 +15,7 @@ use crate::ledger::pos::{self, PosVP};
 use crate::ledger::slash_fund::SlashFundVp;
 use crate::ledger::storage::write_log::WriteLog;
 use crate::ledger::storage::{DBIter, Storage, StorageHasher, DB};
+use crate::ledger::token_metadata::{self, TokenMetadataVp};
 use crate::proto::{self, Tx};
 use crate::types::address::{Address, InternalAddress};
 use crate::types::storage;
@@ -56,6 +57,8 @@ pub enum Error {
     SlashFundNativeVpError(crate::ledger::slash_fund::Error),
     #[error("Ethereum bridge native VP error: {0}")]
     EthBridgeNativeVpError(crate::ledger::eth_bridge::vp::Error),
+    #[error("Token metadata native VP error: {0}")]
+    TokenMetadataNativeVpError(token_metadata::Error),
     #[error("Access to an internal address {0} is forbidden")]
     AccessForbidden(InternalAddress),
 }
@@ -327,7 +330,8 @@ where
                             gas_meter = parameters.ctx.gas_meter.into_inner();
                             result
                         }
-                        InternalAddress::PosSlashPool => {
+                        InternalAddress::PosSlashPool
+                        | InternalAddress::ReplayProtection => {
                             // Take the gas meter back out of the context
                             gas_meter = ctx.gas_meter.into_inner();
                             Err(Error::AccessForbidden(
@@ -370,6 +374,15 @@ where
                             gas_meter = bridge.ctx.gas_meter.into_inner();
                             result
                         }
+                        InternalAddress::TokenMetadata => {
+                            let token_metadata = TokenMetadataVp { ctx };
+                            let result = token_metadata
+                                .validate_tx(tx_data, &keys_changed, &verifiers)
+                                .map_err(Error::TokenMetadataNativeVpError);
+                            gas_meter =
+                                token_metadata.ctx.gas_meter.into_inner();
+                            result
+                        }
                     };
 
                     accepted
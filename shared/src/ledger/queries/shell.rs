@@ -6,14 +6,16 @@ use masp_primitives::sapling::Node;
 use crate::ledger::events::log::dumb_queries;
 use crate::ledger::events::Event;
 use crate::ledger::queries::types::{RequestCtx, RequestQuery};
-use crate::ledger::queries::{require_latest_height, EncodedResponseQuery};
+use crate::ledger::queries::EncodedResponseQuery;
 use crate::ledger::storage::traits::StorageHasher;
-use crate::ledger::storage::{DBIter, DB};
+use crate::ledger::storage::{DBIter, StoreType, DB};
 use crate::ledger::storage_api::{self, ResultExt, StorageRead};
 use crate::tendermint::merkle::proof::Proof;
 use crate::types::address::Address;
 use crate::types::hash::Hash;
-use crate::types::storage::{self, BlockResults, Epoch, PrefixValue};
+use crate::types::storage::{
+    self, BlockHeight, BlockResults, Epoch, PrefixProof, PrefixValue,
+};
 #[cfg(any(test, feature = "async-client"))]
 use crate::types::transaction::TxResult;
 
@@ -35,7 +37,8 @@ router! {SHELL,
     // Dry run a transaction
     ( "dry_run_tx" ) -> TxResult = (with_options dry_run_tx),
 
-    // Raw storage access - prefix iterator
+    // Raw storage access - prefix iterator. The kind of proof is given by
+    // the borsh-encoded `PrefixProof` in the request data, if any.
     ( "prefix" / [storage_key: storage::Key] )
         -> Vec<PrefixValue> = (with_options storage_prefix),
 
@@ -183,18 +186,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    if let Some(past_height_limit) = ctx.storage_read_past_height_limit {
-        if request.height.0 + past_height_limit < ctx.storage.last_height.0 {
-            return Err(storage_api::Error::new(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                format!(
-                    "Cannot query more than {past_height_limit} blocks in the \
-                     past (configured via \
-                     `shell.storage_read_past_height_limit`)."
-                ),
-            )));
-        }
-    }
+    require_within_past_height_limit(&ctx, request)?;
 
     match ctx
         .storage
@@ -249,30 +241,59 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    require_latest_height(&ctx, request)?;
-
-    let iter = storage_api::iter_prefix_bytes(ctx.storage, &storage_key)?;
-    let data: storage_api::Result<Vec<PrefixValue>> = iter
-        .map(|iter_result| {
-            let (key, value) = iter_result?;
-            Ok(PrefixValue { key, value })
-        })
-        .collect();
-    let data = data?;
-    let proof = if request.prove {
-        let mut ops = vec![];
-        for PrefixValue { key, value } in &data {
-            let mut proof = ctx
+    let proof_kind = if request.data.is_empty() {
+        PrefixProof::default()
+    } else {
+        PrefixProof::try_from_slice(&request.data).into_storage_result()?
+    };
+    let data = read_prefix(&ctx, request, &storage_key)?;
+    let proof = match proof_kind {
+        _ if !request.prove => None,
+        PrefixProof::PerKey => {
+            let mut ops = vec![];
+            for PrefixValue { key, value } in &data {
+                let mut proof = ctx
+                    .storage
+                    .get_existence_proof(
+                        key,
+                        value.clone().into(),
+                        request.height,
+                    )
+                    .into_storage_result()?;
+                ops.append(&mut proof.ops);
+            }
+            // ops is not empty in this case
+            Some(Proof { ops })
+        }
+        // The key-vals are proven with a single batch proof, in which the
+        // proofs of the keys in the same sub-tree share their common inner
+        // nodes. In the IBC sub-tree, the proof also shows that the range of
+        // the prefix is complete.
+        PrefixProof::Batch => {
+            let (keys, values): (Vec<_>, Vec<_>) = data
+                .iter()
+                .map(|PrefixValue { key, value }| {
+                    (key.clone(), value.clone().into())
+                })
+                .unzip();
+            let (store_type, _) =
+                StoreType::sub_key(&storage_key).into_storage_result()?;
+            let complete_prefixes = if store_type.proves_complete_ranges() {
+                vec![storage_key]
+            } else {
+                vec![]
+            };
+            let proof = ctx
                 .storage
-                .get_existence_proof(key, value.clone().into(), request.height)
+                .get_batch_proof(
+                    &keys,
+                    values,
+                    &complete_prefixes,
+                    request.height,
+                )
                 .into_storage_result()?;
-            ops.append(&mut proof.ops);
+            Some(proof)
         }
-        // ops is not empty in this case
-        let proof = Proof { ops };
-        Some(proof)
-    } else {
-        None
     };
     let data = data.try_to_vec().into_storage_result()?;
     Ok(EncodedResponseQuery {
@@ -282,6 +303,64 @@ where
     })
 }
 
+/// Read the key-vals with the given prefix at the queried height
+fn read_prefix<D, H>(
+    ctx: &RequestCtx<'_, D, H>,
+    request: &RequestQuery,
+    storage_key: &storage::Key,
+) -> storage_api::Result<Vec<PrefixValue>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let is_latest_height = request.height == BlockHeight(0)
+        || request.height == ctx.storage.last_height;
+    if is_latest_height {
+        storage_api::iter_prefix_bytes(ctx.storage, storage_key)?
+            .map(|iter_result| {
+                let (key, value) = iter_result?;
+                Ok(PrefixValue { key, value })
+            })
+            .collect()
+    } else {
+        require_within_past_height_limit(ctx, request)?;
+        let (iter, _gas) = ctx
+            .storage
+            .iter_prefix_at_height(storage_key, request.height)
+            .into_storage_result()?;
+        iter.map(|(key, value, _gas)| {
+            let key = storage::Key::parse(key).into_storage_result()?;
+            Ok(PrefixValue { key, value })
+        })
+        .collect()
+    }
+}
+
+/// Check that the queried height is within the configured limit of how far in
+/// the past the storage can be read.
+fn require_within_past_height_limit<D, H>(
+    ctx: &RequestCtx<'_, D, H>,
+    request: &RequestQuery,
+) -> storage_api::Result<()>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    if let Some(past_height_limit) = ctx.storage_read_past_height_limit {
+        if request.height.0 + past_height_limit < ctx.storage.last_height.0 {
+            return Err(storage_api::Error::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "Cannot query more than {past_height_limit} blocks in the \
+                     past (configured via \
+                     `shell.storage_read_past_height_limit`)."
+                ),
+            )));
+        }
+    }
+    Ok(())
+}
+
 fn storage_has_key<D, H>(
     ctx: RequestCtx<'_, D, H>,
     storage_key: storage::Key,
@@ -330,12 +409,15 @@ where
 
 #[cfg(test)]
 mod test {
-    use borsh::BorshDeserialize;
+    use borsh::{BorshDeserialize, BorshSerialize};
 
     use crate::ledger::queries::testing::TestClient;
     use crate::ledger::queries::RPC;
+    use crate::ledger::storage::{verify_batch_proof, Sha256Hasher};
     use crate::ledger::storage_api::{self, StorageWrite};
     use crate::proto::Tx;
+    use crate::types::chain::ChainId;
+    use crate::types::storage::{BlockHeight, PrefixProof, PrefixValue};
     use crate::types::{address, token};
 
     const TX_NO_OP_WASM: &str = "../wasm_for_tests/tx_no_op.wasm";
@@ -374,7 +456,7 @@ mod test {
 
         // Request dry run tx
         let tx_no_op = std::fs::read(TX_NO_OP_WASM).expect("cannot load wasm");
-        let tx = Tx::new(tx_no_op, None);
+        let tx = Tx::new(tx_no_op, None, ChainId::default(), None);
         let tx_bytes = tx.to_bytes();
         let result = RPC
             .shell()
@@ -435,6 +517,35 @@ mod test {
             .unwrap();
         assert_eq!(read_balances.data.len(), 1);
 
+        // Request storage prefix iterator with a batch proof
+        let read_balances = RPC
+            .shell()
+            .storage_prefix(
+                &client,
+                Some(PrefixProof::Batch.try_to_vec().unwrap()),
+                None,
+                true,
+                &balance_prefix,
+            )
+            .await
+            .unwrap();
+        let key_vals: Vec<_> = read_balances
+            .data
+            .into_iter()
+            .map(|PrefixValue { key, value }| (key, value))
+            .collect();
+        assert_eq!(
+            key_vals,
+            vec![(balance_key.clone(), balance.try_to_vec().unwrap())]
+        );
+        verify_batch_proof::<Sha256Hasher>(
+            &read_balances.proof.unwrap(),
+            &client.storage.merkle_root().0,
+            &key_vals,
+            &[],
+        )
+        .unwrap();
+
         // Request storage has key
         let has_balance_key = RPC
             .shell()
@@ -443,6 +554,30 @@ mod test {
             .unwrap();
         assert!(has_balance_key);
 
+        // Change the balance in a later block ...
+        client.storage.block.height = BlockHeight(2);
+        client.storage.last_height = BlockHeight(2);
+        let new_balance = token::Amount::from(2000);
+        StorageWrite::write(&mut client.storage, &balance_key, new_balance)?;
+        // ... the prefix iterator at a past height should have the old value
+        let read_balances = RPC
+            .shell()
+            .storage_prefix(
+                &client,
+                None,
+                Some(BlockHeight(1)),
+                false,
+                &balance_prefix,
+            )
+            .await
+            .unwrap();
+        assert_eq!(read_balances.data.len(), 1);
+        assert_eq!(
+            balance,
+            token::Amount::try_from_slice(&read_balances.data[0].value)
+                .unwrap()
+        );
+
         Ok(())
     }
 }
@@ -0,0 +1,64 @@
+//! Replay protection storage. The hashes of the applied wrapper and inner txs
+//! are stored under the internal replay protection address, so that the same
+//! tx cannot be applied more than once. The hashes of the txs with an
+//! expiration are also indexed by it, so that they can be pruned once the tx
+//! has expired and it cannot be applied anymore.
+
+use crate::types::address::{Address, InternalAddress};
+use crate::types::hash::Hash;
+use crate::types::storage::{DbKeySeg, Key, KeySeg};
+use crate::types::time::DateTimeUtc;
+
+const EXPIRATION_KEY_SEG: &str = "expiration";
+
+/// Internal replay protection address
+pub const ADDRESS: Address =
+    Address::Internal(InternalAddress::ReplayProtection);
+
+/// Check if a key is a replay protection key
+pub fn is_replay_protection_key(key: &Key) -> bool {
+    matches!(&key.segments[0], DbKeySeg::AddressSeg(addr) if addr == &ADDRESS)
+}
+
+/// Get the storage key under which the hash of an applied tx is recorded
+pub fn get_tx_hash_key(hash: &Hash) -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&hash.to_string())
+        .expect("Cannot obtain a valid db key")
+}
+
+/// Get the key prefix under which the hashes of the applied txs are indexed by
+/// the txs' expiration
+pub fn get_tx_expiration_prefix() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&EXPIRATION_KEY_SEG.to_owned())
+        .expect("Cannot obtain a valid db key")
+}
+
+/// Get the storage key under which the hash of an applied tx is indexed by the
+/// tx's expiration. The expiration is encoded as zero-padded seconds, so that
+/// the keys are ordered by the expiration.
+pub fn get_tx_expiration_key(hash: &Hash, expiration: &DateTimeUtc) -> Key {
+    get_tx_expiration_prefix()
+        .push(&format!("{:020}", expiration.0.timestamp()))
+        .and_then(|key| key.push(&hash.to_string()))
+        .expect("Cannot obtain a valid db key")
+}
+
+/// Get the expiration in seconds and the tx hash key from a key returned by
+/// [`get_tx_expiration_key`]
+pub fn parse_tx_expiration_key(key: &Key) -> Option<(i64, Key)> {
+    match &key.segments[..] {
+        [
+            DbKeySeg::AddressSeg(addr),
+            DbKeySeg::StringSeg(prefix),
+            DbKeySeg::StringSeg(expiration),
+            DbKeySeg::StringSeg(hash),
+        ] if addr == &ADDRESS && prefix == EXPIRATION_KEY_SEG => {
+            let expiration = expiration.parse().ok()?;
+            let hash_key = Key::from(ADDRESS.to_db_key()).push(hash).ok()?;
+            Some((expiration, hash_key))
+        }
+        _ => None,
+    }
+}
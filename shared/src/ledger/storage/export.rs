@@ -0,0 +1,189 @@
+//! Exports of the state of the storage at a committed block height, from
+//! which a new chain can be started. This allows to upgrade a network with a
+//! coordinated restart without losing its state.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::{DBIter, Error, Result, Storage, StorageHasher, DB};
+use crate::types::address::EstablishedAddressGen;
+use crate::types::chain::ChainId;
+use crate::types::hash::Hash;
+use crate::types::storage::{BlockHash, BlockHeight, Epoch, Epochs, Key};
+
+/// The state of the storage at a committed block height, from which a new
+/// chain can be started
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
+pub struct StateExport {
+    /// ID of the exported chain
+    pub chain_id: ChainId,
+    /// Height of the exported block
+    pub height: BlockHeight,
+    /// Hash of the exported block
+    pub hash: BlockHash,
+    /// Epoch of the exported block
+    pub epoch: Epoch,
+    /// Established address generator
+    pub address_gen: EstablishedAddressGen,
+    /// The key-vals of the account subspaces, sorted by their [`Key`]s.
+    /// These hold the accounts with their VPs and balances, the parameters,
+    /// the PoS bonds and unbonds and the governance proposals.
+    pub subspace: Vec<(Key, Vec<u8>)>,
+}
+
+impl StateExport {
+    /// The hash of the export, which the genesis of a chain started from it
+    /// commits to
+    pub fn hash(&self) -> Hash {
+        Hash::sha256(
+            self.try_to_vec()
+                .expect("Encoding a state export shouldn't fail"),
+        )
+    }
+}
+
+impl<D, H> Storage<D, H>
+where
+    D: DB + for<'iter> DBIter<'iter>,
+    H: StorageHasher,
+{
+    /// Export the state at the given committed block height. The export only
+    /// depends on the state at the height, so that every node exports the
+    /// same state. The key-vals are sorted by their [`Key`]s, whichever order
+    /// they're read from the DB in.
+    pub fn export_state(&self, height: BlockHeight) -> Result<StateExport> {
+        let metadata = self
+            .db
+            .read_block_metadata(height)?
+            .ok_or(Error::NoBlock { height })?;
+        let mut subspace = if height == self.last_height {
+            let (iter, _gas) = self.iter_prefix(&Key::default());
+            parse_key_vals(iter)?
+        } else {
+            let (iter, _gas) =
+                self.iter_prefix_at_height(&Key::default(), height)?;
+            parse_key_vals(iter)?
+        };
+        subspace.sort_by(|(key_a, _), (key_b, _)| key_a.cmp(key_b));
+        Ok(StateExport {
+            chain_id: self.chain_id.clone(),
+            height,
+            hash: metadata.hash,
+            epoch: metadata.epoch,
+            address_gen: metadata.address_gen,
+            subspace,
+        })
+    }
+
+    /// Import an exported state into the genesis block of a new chain, which
+    /// continues from the epoch of the export. Must be called from
+    /// `init_chain` instead of initializing the genesis accounts, followed by
+    /// [`Storage::init_genesis_epoch`].
+    pub fn import_state(
+        &mut self,
+        export: StateExport,
+        initial_height: BlockHeight,
+    ) -> Result<()> {
+        for (key, value) in &export.subspace {
+            self.write(key, value)?;
+        }
+        self.block.epoch = export.epoch;
+        self.block.pred_epochs = Epochs::new(export.epoch, initial_height);
+        self.last_epoch = export.epoch;
+        self.address_gen = export.address_gen;
+        // The conversions given as query results are kept in memory
+        self.load_conversion_state()
+    }
+}
+
+/// Parse the storage keys of the key-vals from a prefix iterator
+fn parse_key_vals(
+    iter: impl Iterator<Item = (String, Vec<u8>, u64)>,
+) -> Result<Vec<(Key, Vec<u8>)>> {
+    iter.map(|(key, value, _gas)| {
+        Ok((Key::parse(key).map_err(Error::KeyError)?, value))
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::storage::testing::TestStorage;
+    use crate::types::address;
+    use crate::types::storage::KeySeg;
+
+    /// Test that the state exported at a past height and at the last height
+    /// can be imported into a new chain.
+    #[test]
+    fn test_export_import_state() {
+        let mut storage = TestStorage::default();
+        let key =
+            Key::from(address::testing::established_address_1().to_db_key())
+                .push(&"balance".to_owned())
+                .unwrap();
+        storage.block.height = BlockHeight(1);
+        storage.write(&key, vec![1]).unwrap();
+        storage.commit().unwrap();
+        storage.block.height = BlockHeight(2);
+        storage.block.epoch = Epoch(1);
+        storage.write(&key, vec![2]).unwrap();
+        storage.commit().unwrap();
+
+        assert!(matches!(
+            storage.export_state(BlockHeight(3)),
+            Err(Error::NoBlock { .. })
+        ));
+        let past = storage.export_state(BlockHeight(1)).unwrap();
+        assert_eq!(past.epoch, Epoch(0));
+        assert_eq!(past.subspace, vec![(key.clone(), vec![1])]);
+        let last = storage.export_state(BlockHeight(2)).unwrap();
+        assert_eq!(last.epoch, Epoch(1));
+        assert_eq!(last.subspace, vec![(key.clone(), vec![2])]);
+        assert_ne!(past.hash(), last.hash());
+
+        let mut imported = TestStorage::default();
+        imported.import_state(last, BlockHeight(3)).unwrap();
+        assert_eq!(imported.block.epoch, Epoch(1));
+        assert_eq!(
+            imported.block.pred_epochs.get_epoch(BlockHeight(3)),
+            Some(Epoch(1))
+        );
+        let (value, _gas) = imported.read(&key).unwrap();
+        assert_eq!(value, Some(vec![2]));
+    }
+
+    /// Test that the same state is exported in the same order at the last
+    /// height and at a past height, with keys whose storage keys and whose
+    /// segments are sorted differently
+    #[test]
+    fn test_export_state_order() {
+        let mut storage = TestStorage::default();
+        let prefix =
+            Key::from(address::testing::established_address_1().to_db_key());
+        storage.block.height = BlockHeight(1);
+        for sub_key in ["a", "a/b", "a-c", "b"] {
+            let key = Key::parse(format!("{}/{}", prefix, sub_key)).unwrap();
+            storage.write(&key, vec![1]).unwrap();
+        }
+        storage.commit().unwrap();
+        storage.block.height = BlockHeight(2);
+        storage.commit().unwrap();
+
+        let past = storage.export_state(BlockHeight(1)).unwrap();
+        let last = storage.export_state(BlockHeight(2)).unwrap();
+        assert_eq!(past.subspace, last.subspace);
+        let mut sorted = last.subspace.clone();
+        sorted.sort();
+        assert_eq!(last.subspace, sorted);
+        // The exports only differ by their height and block hash
+        assert_eq!(
+            StateExport {
+                height: last.height,
+                hash: last.hash.clone(),
+                ..past
+            }
+            .hash(),
+            last.hash()
+        );
+    }
+}
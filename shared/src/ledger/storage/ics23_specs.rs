@@ -4,6 +4,7 @@ use arse_merkle_tree::H256;
 use ics23::{HashOp, LeafOp, LengthOp, ProofSpec};
 
 use super::traits::StorageHasher;
+use super::StoreType;
 
 /// Get the leaf spec for the base tree. The key is stored after hashing,
 /// but the stored value is the subtree's root without hashing.
@@ -59,6 +60,22 @@ pub fn ibc_proof_specs<H: StorageHasher>() -> Vec<ProofSpec> {
     vec![sub_tree_spec, base_tree_spec]
 }
 
+/// Get the proof specs for the sub-tree of the given store type, with which
+/// the proofs of the keys in the sub-tree can be verified
+pub fn store_proof_specs<H: StorageHasher>(
+    store_type: &StoreType,
+) -> Vec<ProofSpec> {
+    match store_type {
+        StoreType::Ibc => ibc_proof_specs::<H>(),
+        StoreType::Base
+        | StoreType::Account
+        | StoreType::PoS
+        | StoreType::Governance
+        | StoreType::Parameters
+        | StoreType::Balance => proof_specs::<H>(),
+    }
+}
+
 /// Get the proof specs
 #[allow(dead_code)]
 pub fn proof_specs<H: StorageHasher>() -> Vec<ProofSpec> {
@@ -1,4 +1,5 @@
 //! The merkle tree in the storage
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 use std::str::FromStr;
 
@@ -9,22 +10,28 @@ use arse_merkle_tree::{
 };
 use borsh::{BorshDeserialize, BorshSerialize};
 use ics23::commitment_proof::Proof as Ics23Proof;
-use ics23::{CommitmentProof, ExistenceProof, NonExistenceProof};
+use ics23::{
+    batch_entry, BatchEntry, BatchProof, CommitmentProof, ExistenceProof,
+    NonExistenceProof, ProofSpec,
+};
 use prost::Message;
 use thiserror::Error;
 
 use super::traits::{StorageHasher, SubTreeRead, SubTreeWrite};
 use super::IBC_KEY_LIMIT;
 use crate::bytes::ByteBuf;
-use crate::ledger::storage::ics23_specs::{self, ibc_leaf_spec};
+use crate::ledger::storage::ics23_specs::{
+    self, ibc_leaf_spec, proof_specs, store_proof_specs,
+};
 use crate::ledger::storage::types;
 use crate::tendermint::merkle::proof::{Proof, ProofOp};
 use crate::types::address::{Address, InternalAddress};
 use crate::types::hash::Hash;
 use crate::types::storage::{
     DbKeySeg, Error as StorageError, Key, MembershipProof, MerkleValue,
-    StringKey, TreeBytes,
+    StringKey, TreeBytes, KEY_SEGMENT_SEPARATOR,
 };
+use crate::types::token;
 
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
@@ -43,8 +50,8 @@ pub enum Error {
     NonExistenceProof(String),
     #[error("Invalid value given to sub-tree storage")]
     InvalidValue,
-    #[error("ICS23 commitment proofs do not support multiple leaves")]
-    Ics23MultiLeaf,
+    #[error("Invalid batch proof: {0}")]
+    InvalidBatchProof(String),
     #[error("A Tendermint proof can only be constructed from an ICS23 proof.")]
     TendermintProof,
 }
@@ -59,6 +66,10 @@ pub type Smt<H> = ArseMerkleTree<H, SmtHash, Hash, SmtStore, 32>;
 pub type Amt<H> =
     ArseMerkleTree<H, StringKey, TreeBytes, AmtStore, IBC_KEY_LIMIT>;
 
+/// The key in the base tree of the hash of a block's metadata, which is
+/// distinct from the keys of the roots of the sub-trees
+const BLOCK_METADATA_KEY: &str = "block_metadata";
+
 /// Store types for the merkle tree
 #[derive(
     Clone,
@@ -68,6 +79,7 @@ pub type Amt<H> =
     PartialEq,
     Eq,
     PartialOrd,
+    Ord,
     BorshSerialize,
     BorshDeserialize,
 )]
@@ -80,6 +92,12 @@ pub enum StoreType {
     Ibc,
     /// For PoS-related data
     PoS,
+    /// For governance-related data
+    Governance,
+    /// For the protocol parameters
+    Parameters,
+    /// For the balances of all the tokens
+    Balance,
 }
 
 /// Backing storage for merkle trees
@@ -92,6 +110,12 @@ pub enum Store {
     Ibc(AmtStore),
     /// For PoS-related data
     PoS(SmtStore),
+    /// For governance-related data
+    Governance(SmtStore),
+    /// For the protocol parameters
+    Parameters(SmtStore),
+    /// For the balances of all the tokens
+    Balance(SmtStore),
 }
 
 impl Store {
@@ -101,6 +125,9 @@ impl Store {
             Self::Account(store) => StoreRef::Account(store),
             Self::Ibc(store) => StoreRef::Ibc(store),
             Self::PoS(store) => StoreRef::PoS(store),
+            Self::Governance(store) => StoreRef::Governance(store),
+            Self::Parameters(store) => StoreRef::Parameters(store),
+            Self::Balance(store) => StoreRef::Balance(store),
         }
     }
 }
@@ -115,6 +142,12 @@ pub enum StoreRef<'a> {
     Ibc(&'a AmtStore),
     /// For PoS-related data
     PoS(&'a SmtStore),
+    /// For governance-related data
+    Governance(&'a SmtStore),
+    /// For the protocol parameters
+    Parameters(&'a SmtStore),
+    /// For the balances of all the tokens
+    Balance(&'a SmtStore),
 }
 
 impl<'a> StoreRef<'a> {
@@ -124,6 +157,9 @@ impl<'a> StoreRef<'a> {
             Self::Account(store) => Store::Account(store.to_owned()),
             Self::Ibc(store) => Store::Ibc(store.to_owned()),
             Self::PoS(store) => Store::PoS(store.to_owned()),
+            Self::Governance(store) => Store::Governance(store.to_owned()),
+            Self::Parameters(store) => Store::Parameters(store.to_owned()),
+            Self::Balance(store) => Store::Balance(store.to_owned()),
         }
     }
 
@@ -133,43 +169,110 @@ impl<'a> StoreRef<'a> {
             Self::Account(store) => store.try_to_vec(),
             Self::Ibc(store) => store.try_to_vec(),
             Self::PoS(store) => store.try_to_vec(),
+            Self::Governance(store) => store.try_to_vec(),
+            Self::Parameters(store) => store.try_to_vec(),
+            Self::Balance(store) => store.try_to_vec(),
         }
         .expect("Serialization failed")
     }
 }
 
+/// The registration of a sub-tree dedicated to the data of some internal
+/// addresses. The keys in such a sub-tree don't include the address.
+#[derive(Clone, Debug)]
+pub struct SubTreeRegistration {
+    /// The type of the sub-tree
+    pub store_type: StoreType,
+    /// The internal addresses whose data is kept in the sub-tree
+    pub addresses: &'static [InternalAddress],
+}
+
+/// The sub-trees dedicated to the data of internal addresses. The data of
+/// the other internal addresses is kept in the account sub-tree. To give an
+/// internal address its own sub-tree, register it here with the sub-tree's
+/// [`StoreType`].
+pub const INTERNAL_ADDRESS_SUB_TREES: &[SubTreeRegistration] = &[
+    SubTreeRegistration {
+        store_type: StoreType::PoS,
+        addresses: &[InternalAddress::PoS, InternalAddress::PosSlashPool],
+    },
+    SubTreeRegistration {
+        store_type: StoreType::Ibc,
+        addresses: &[InternalAddress::Ibc],
+    },
+    SubTreeRegistration {
+        store_type: StoreType::Governance,
+        addresses: &[InternalAddress::Governance],
+    },
+    SubTreeRegistration {
+        store_type: StoreType::Parameters,
+        addresses: &[InternalAddress::Parameters],
+    },
+];
+
 impl StoreType {
     /// Get an iterator for the base tree and subtrees
     pub fn iter() -> std::slice::Iter<'static, Self> {
-        static SUB_TREE_TYPES: [StoreType; 4] = [
+        static SUB_TREE_TYPES: [StoreType; 7] = [
             StoreType::Base,
             StoreType::Account,
             StoreType::PoS,
             StoreType::Ibc,
+            StoreType::Governance,
+            StoreType::Parameters,
+            StoreType::Balance,
         ];
         SUB_TREE_TYPES.iter()
     }
 
-    fn sub_key(key: &Key) -> Result<(Self, Key)> {
+    /// Get the type of the sub-tree registered for the data of the given
+    /// internal address in [`INTERNAL_ADDRESS_SUB_TREES`], if any
+    pub fn of_internal_address(address: &InternalAddress) -> Option<Self> {
+        INTERNAL_ADDRESS_SUB_TREES
+            .iter()
+            .find(|sub_tree| sub_tree.addresses.contains(address))
+            .map(|sub_tree| sub_tree.store_type)
+    }
+
+    /// Check if the sub-tree was added after the Merkle trees of the existing
+    /// chains were first written. The stores of these trees don't have the
+    /// sub-tree until they're migrated (see [`MerkleTree::migrate`]) and its
+    /// data is kept in the account sub-tree until then.
+    pub fn is_added(&self) -> bool {
+        matches!(
+            self,
+            StoreType::Governance | StoreType::Parameters | StoreType::Balance
+        )
+    }
+
+    /// Check if a batch proof can show that the key-vals with a prefix in this
+    /// sub-tree are complete, which requires its keys not to be hashed
+    pub fn proves_complete_ranges(&self) -> bool {
+        *self == StoreType::Ibc
+    }
+
+    /// Get the type of the sub-tree in which the given key is stored, with
+    /// the key in the sub-tree
+    pub fn sub_key(key: &Key) -> Result<(Self, Key)> {
         if key.is_empty() {
             return Err(Error::EmptyKey("the key is empty".to_owned()));
         }
-        match key.segments.get(0) {
-            Some(DbKeySeg::AddressSeg(Address::Internal(internal))) => {
-                match internal {
-                    InternalAddress::PoS | InternalAddress::PosSlashPool => {
-                        Ok((StoreType::PoS, key.sub_key()?))
-                    }
-                    InternalAddress::Ibc => {
-                        Ok((StoreType::Ibc, key.sub_key()?))
-                    }
-                    // use the same key for Parameters
-                    _ => Ok((StoreType::Account, key.clone())),
-                }
+        if let Some(DbKeySeg::AddressSeg(Address::Internal(internal))) =
+            key.segments.get(0)
+        {
+            if let Some(store_type) = Self::of_internal_address(internal) {
+                return Ok((store_type, key.sub_key()?));
             }
-            // use the same key for Account
-            _ => Ok((StoreType::Account, key.clone())),
         }
+        if token::is_any_token_balance_key(key).is_some()
+            || token::is_any_multitoken_balance_key(key).is_some()
+        {
+            // use the same key for balances, so that a proof of a balance
+            // commits to the token and the owner
+            return Ok((StoreType::Balance, key.clone()));
+        }
+        // use the same key for Account
+        Ok((StoreType::Account, key.clone()))
     }
 
     /// Decode the backing store from bytes and tag its type correctly
@@ -191,6 +294,15 @@ impl StoreType {
             Self::PoS => Ok(Store::PoS(
                 types::decode(bytes).map_err(Error::CodingError)?,
             )),
+            Self::Governance => Ok(Store::Governance(
+                types::decode(bytes).map_err(Error::CodingError)?,
+            )),
+            Self::Parameters => Ok(Store::Parameters(
+                types::decode(bytes).map_err(Error::CodingError)?,
+            )),
+            Self::Balance => Ok(Store::Balance(
+                types::decode(bytes).map_err(Error::CodingError)?,
+            )),
         }
     }
 }
@@ -204,6 +316,9 @@ impl FromStr for StoreType {
             "account" => Ok(StoreType::Account),
             "ibc" => Ok(StoreType::Ibc),
             "pos" => Ok(StoreType::PoS),
+            "governance" => Ok(StoreType::Governance),
+            "parameters" => Ok(StoreType::Parameters),
+            "balance" => Ok(StoreType::Balance),
             _ => Err(Error::StoreType(s.to_string())),
         }
     }
@@ -216,6 +331,9 @@ impl fmt::Display for StoreType {
             StoreType::Account => write!(f, "account"),
             StoreType::Ibc => write!(f, "ibc"),
             StoreType::PoS => write!(f, "pos"),
+            StoreType::Governance => write!(f, "governance"),
+            StoreType::Parameters => write!(f, "parameters"),
+            StoreType::Balance => write!(f, "balance"),
         }
     }
 }
@@ -227,6 +345,12 @@ pub struct MerkleTree<H: StorageHasher + Default> {
     account: Smt<H>,
     ibc: Amt<H>,
     pos: Smt<H>,
+    governance: Smt<H>,
+    parameters: Smt<H>,
+    balance: Smt<H>,
+    /// Is the data of the added sub-trees still kept in the account
+    /// sub-tree? (see [`StoreType::is_added`])
+    legacy: bool,
 }
 
 impl<H: StorageHasher + Default> core::fmt::Debug for MerkleTree<H> {
@@ -239,17 +363,71 @@ impl<H: StorageHasher + Default> core::fmt::Debug for MerkleTree<H> {
 }
 
 impl<H: StorageHasher + Default> MerkleTree<H> {
-    /// Restore the tree from the stores
+    /// Restore the tree from the stores. Without the stores of the added
+    /// sub-trees, the tree is restored as it was written before they existed.
     pub fn new(stores: MerkleTreeStoresRead) -> Self {
+        let legacy = stores.is_legacy();
         let base = Smt::new(stores.base.0.into(), stores.base.1);
         let account = Smt::new(stores.account.0.into(), stores.account.1);
         let ibc = Amt::new(stores.ibc.0.into(), stores.ibc.1);
         let pos = Smt::new(stores.pos.0.into(), stores.pos.1);
+        let governance =
+            Smt::new(stores.governance.0.into(), stores.governance.1);
+        let parameters =
+            Smt::new(stores.parameters.0.into(), stores.parameters.1);
+        let balance = Smt::new(stores.balance.0.into(), stores.balance.1);
         Self {
             base,
             account,
             ibc,
             pos,
+            governance,
+            parameters,
+            balance,
+            legacy,
+        }
+    }
+
+    /// Check if the data of the added sub-trees is still kept in the account
+    /// sub-tree, as the tree was written before they existed
+    pub fn is_legacy(&self) -> bool {
+        self.legacy
+    }
+
+    /// Move the data of the added sub-trees out of the account sub-tree of a
+    /// tree written before they existed. All the key-vals of the state that
+    /// belong to the added sub-trees must be given. As this changes the root
+    /// of the tree, every node must migrate it at the same block height.
+    pub fn migrate<'a>(
+        &mut self,
+        key_vals: impl IntoIterator<Item = (&'a Key, &'a [u8])>,
+    ) -> Result<()> {
+        if !self.legacy {
+            return Ok(());
+        }
+        for (key, value) in key_vals {
+            let (store_type, sub_key) = StoreType::sub_key(key)?;
+            if !store_type.is_added() {
+                continue;
+            }
+            let sub_root =
+                self.tree_mut(&StoreType::Account).subtree_delete(key)?;
+            let base_key = H::hash(StoreType::Account.to_string());
+            self.base.update(base_key.into(), sub_root)?;
+            self.update_tree(&store_type, &sub_key, value.into())?;
+        }
+        self.legacy = false;
+        Ok(())
+    }
+
+    /// Get the type of the sub-tree in which the given key is stored in this
+    /// tree, with the key in the sub-tree
+    fn sub_key(&self, key: &Key) -> Result<(StoreType, Key)> {
+        let (store_type, sub_key) = StoreType::sub_key(key)?;
+        if self.legacy && store_type.is_added() {
+            Ok((StoreType::Account, key.clone()))
+        } else {
+            Ok((store_type, sub_key))
         }
     }
 
@@ -259,6 +437,9 @@ impl<H: StorageHasher + Default> MerkleTree<H> {
             StoreType::Account => Box::new(&self.account),
             StoreType::Ibc => Box::new(&self.ibc),
             StoreType::PoS => Box::new(&self.pos),
+            StoreType::Governance => Box::new(&self.governance),
+            StoreType::Parameters => Box::new(&self.parameters),
+            StoreType::Balance => Box::new(&self.balance),
         }
     }
 
@@ -271,6 +452,9 @@ impl<H: StorageHasher + Default> MerkleTree<H> {
             StoreType::Account => Box::new(&mut self.account),
             StoreType::Ibc => Box::new(&mut self.ibc),
             StoreType::PoS => Box::new(&mut self.pos),
+            StoreType::Governance => Box::new(&mut self.governance),
+            StoreType::Parameters => Box::new(&mut self.parameters),
+            StoreType::Balance => Box::new(&mut self.balance),
         }
     }
 
@@ -291,7 +475,7 @@ impl<H: StorageHasher + Default> MerkleTree<H> {
 
     /// Check if the key exists in the tree
     pub fn has_key(&self, key: &Key) -> Result<bool> {
-        let (store_type, sub_key) = StoreType::sub_key(key)?;
+        let (store_type, sub_key) = self.sub_key(key)?;
         self.tree(&store_type).subtree_has_key(&sub_key)
     }
 
@@ -301,13 +485,13 @@ impl<H: StorageHasher + Default> MerkleTree<H> {
         key: &Key,
         value: impl Into<MerkleValue>,
     ) -> Result<()> {
-        let (store_type, sub_key) = StoreType::sub_key(key)?;
+        let (store_type, sub_key) = self.sub_key(key)?;
         self.update_tree(&store_type, &sub_key, value.into())
     }
 
     /// Delete the value corresponding to the given key
     pub fn delete(&mut self, key: &Key) -> Result<()> {
-        let (store_type, sub_key) = StoreType::sub_key(key)?;
+        let (store_type, sub_key) = self.sub_key(key)?;
         let sub_root = self.tree_mut(&store_type).subtree_delete(&sub_key)?;
         if store_type != StoreType::Base {
             let base_key = H::hash(store_type.to_string());
@@ -316,6 +500,14 @@ impl<H: StorageHasher + Default> MerkleTree<H> {
         Ok(())
     }
 
+    /// Commit to the hash of the state of a block that isn't kept in the
+    /// sub-trees (see [`super::BlockMetadata`]), in a leaf of the base tree
+    pub fn update_block_metadata(&mut self, hash: Hash) -> Result<()> {
+        let base_key = H::hash(BLOCK_METADATA_KEY);
+        self.base.update(base_key.into(), hash)?;
+        Ok(())
+    }
+
     /// Get the root
     pub fn root(&self) -> MerkleRoot {
         self.base.root().into()
@@ -328,6 +520,15 @@ impl<H: StorageHasher + Default> MerkleTree<H> {
             account: (self.account.root().into(), self.account.store()),
             ibc: (self.ibc.root().into(), self.ibc.store()),
             pos: (self.pos.root().into(), self.pos.store()),
+            governance: (
+                self.governance.root().into(),
+                self.governance.store(),
+            ),
+            parameters: (
+                self.parameters.root().into(),
+                self.parameters.store(),
+            ),
+            balance: (self.balance.root().into(), self.balance.store()),
         }
     }
 
@@ -342,9 +543,9 @@ impl<H: StorageHasher + Default> MerkleTree<H> {
                 "No keys provided for existence proof.".into(),
             )
         })?;
-        let (store_type, sub_key) = StoreType::sub_key(first_key)?;
+        let (store_type, sub_key) = self.sub_key(first_key)?;
         if !keys.iter().all(|k| {
-            if let Ok((s, _)) = StoreType::sub_key(k) {
+            if let Ok((s, _)) = self.sub_key(k) {
                 s == store_type
             } else {
                 false
@@ -362,36 +563,136 @@ impl<H: StorageHasher + Default> MerkleTree<H> {
 
     /// Get the non-existence proof
     pub fn get_non_existence_proof(&self, key: &Key) -> Result<Proof> {
-        let (store_type, sub_key) = StoreType::sub_key(key)?;
+        let (store_type, sub_key) = self.sub_key(key)?;
         if store_type != StoreType::Ibc {
             return Err(Error::NonExistenceProof(store_type.to_string()));
         }
 
-        let string_key =
-            StringKey::try_from_bytes(sub_key.to_string().as_bytes())?;
-        let mut nep = self.ibc.non_membership_proof(&string_key)?;
+        let nep = CommitmentProof {
+            proof: Some(Ics23Proof::Nonexist(
+                self.ibc_non_existence_proof(sub_key.to_string().as_bytes())?,
+            )),
+        };
+        // Get a proof of the sub tree
+        self.get_tendermint_proof(key, nep)
+    }
+
+    /// Get the non-existence proof of the given key in the IBC sub-tree
+    fn ibc_non_existence_proof(
+        &self,
+        sub_key: &[u8],
+    ) -> Result<NonExistenceProof> {
+        let string_key = StringKey::try_from_bytes(sub_key)?;
+        let nep = self.ibc.non_membership_proof(&string_key)?;
         // Replace the values and the leaf op for the verification
-        if let Some(ref mut nep) = nep.proof {
-            match nep {
-                Ics23Proof::Nonexist(ref mut ep) => {
-                    let NonExistenceProof {
-                        ref mut left,
-                        ref mut right,
-                        ..
-                    } = ep;
-                    if let Some(left) = left.as_mut() {
-                        left.leaf = Some(ibc_leaf_spec::<H>());
-                    }
-                    if let Some(right) = right.as_mut() {
-                        right.leaf = Some(ibc_leaf_spec::<H>());
-                    }
+        match nep.proof.expect("The proof should exist") {
+            Ics23Proof::Nonexist(mut ep) => {
+                if let Some(left) = ep.left.as_mut() {
+                    left.leaf = Some(ibc_leaf_spec::<H>());
                 }
-                _ => unreachable!(),
+                if let Some(right) = ep.right.as_mut() {
+                    right.leaf = Some(ibc_leaf_spec::<H>());
+                }
+                Ok(ep)
             }
+            // the proof should have a NonExistenceProof
+            _ => unreachable!(),
         }
+    }
 
-        // Get a proof of the sub tree
-        self.get_tendermint_proof(key, nep)
+    /// Get a batch proof of the existence of the given key-vals and that they
+    /// are all the key-vals with the given complete prefixes, i.e. the prefix
+    /// itself and the keys that start with its segments. The proofs of the
+    /// keys in the same sub-tree are compressed into a single ICS23 batch
+    /// proof, in which the inner nodes that they share are only included
+    /// once. It's followed by a batch proof of the roots of these sub-trees in
+    /// the base tree. Only the IBC sub-tree, whose keys aren't hashed, can
+    /// prove that a range of keys is complete, with the absence of the keys in
+    /// the gaps between the given ones.
+    pub fn get_batch_proof(
+        &self,
+        keys: &[Key],
+        values: Vec<MerkleValue>,
+        complete_prefixes: &[Key],
+    ) -> Result<Proof> {
+        if keys.len() != values.len() {
+            return Err(Error::InvalidMerkleKey(
+                "A batch proof needs a value for every key".into(),
+            ));
+        }
+        // The sub-keys with their values and the complete prefixes of every
+        // sub-tree
+        let mut sub_trees: BTreeMap<
+            StoreType,
+            (Vec<Key>, Vec<MerkleValue>, Vec<Key>),
+        > = BTreeMap::new();
+        for (key, value) in keys.iter().zip(values) {
+            let (store_type, sub_key) = self.sub_key(key)?;
+            let (sub_keys, values, _) =
+                sub_trees.entry(store_type).or_default();
+            sub_keys.push(sub_key);
+            values.push(value);
+        }
+        for prefix in complete_prefixes {
+            let (store_type, sub_key) = self.sub_key(prefix)?;
+            if !store_type.proves_complete_ranges() {
+                return Err(Error::NonExistenceProof(store_type.to_string()));
+            }
+            sub_trees.entry(store_type).or_default().2.push(sub_key);
+        }
+
+        let mut ops = Vec::with_capacity(sub_trees.len() + 1);
+        let mut base_entries = Vec::with_capacity(sub_trees.len());
+        for (store_type, (sub_keys, values, complete_prefixes)) in sub_trees {
+            let range_keys: BTreeSet<Vec<u8>> = sub_keys
+                .iter()
+                .map(|sub_key| sub_key.to_string().into_bytes())
+                .collect();
+            let mut entries = vec![];
+            if !sub_keys.is_empty() {
+                let MembershipProof::ICS23(cp) = self
+                    .tree(&store_type)
+                    .subtree_membership_proof(&sub_keys, values)?;
+                match cp.proof.expect("The proof should exist") {
+                    Ics23Proof::Exist(ep) => entries.push(BatchEntry {
+                        proof: Some(batch_entry::Proof::Exist(ep)),
+                    }),
+                    Ics23Proof::Batch(batch) => entries.extend(batch.entries),
+                    // the proof should have an ExistenceProof or a BatchProof
+                    _ => unreachable!(),
+                }
+            }
+            for prefix in &complete_prefixes {
+                let prefix = prefix.to_string().into_bytes();
+                let mut absent_keys = vec![];
+                if !prefix.is_empty() && !range_keys.contains(&prefix) {
+                    absent_keys.push(prefix.clone());
+                }
+                absent_keys.extend(
+                    prefix_range_gaps(&prefix, &range_keys)
+                        .into_iter()
+                        .map(|(gap_key, _next)| gap_key),
+                );
+                for key in absent_keys {
+                    entries.push(BatchEntry {
+                        proof: Some(batch_entry::Proof::Nonexist(
+                            self.ibc_non_existence_proof(&key)?,
+                        )),
+                    });
+                }
+            }
+            ops.push(batch_proof_op(store_type.to_string(), entries));
+            base_entries.push(BatchEntry {
+                proof: Some(batch_entry::Proof::Exist(
+                    self.base_existence_proof(&store_type)?,
+                )),
+            });
+        }
+        // Set ProofOps from leaf to root
+        if !base_entries.is_empty() {
+            ops.push(batch_proof_op(StoreType::Base.to_string(), base_entries));
+        }
+        Ok(Proof { ops })
     }
 
     /// Get the Tendermint proof with the base proof
@@ -412,20 +713,11 @@ impl<H: StorageHasher + Default> MerkleTree<H> {
 
         // Get a membership proof of the base tree because the sub root should
         // exist
-        let (store_type, _) = StoreType::sub_key(key)?;
-        let base_key = store_type.to_string();
-        let cp = self.base.membership_proof(&H::hash(&base_key).into())?;
-        // Replace the values and the leaf op for the verification
-        let base_proof = match cp.proof.expect("The proof should exist") {
-            Ics23Proof::Exist(ep) => CommitmentProof {
-                proof: Some(Ics23Proof::Exist(ExistenceProof {
-                    key: base_key.as_bytes().to_vec(),
-                    leaf: Some(ics23_specs::base_leaf_spec::<H>()),
-                    ..ep
-                })),
-            },
-            // the proof should have an ExistenceProof
-            _ => unreachable!(),
+        let (store_type, _) = self.sub_key(key)?;
+        let base_proof = CommitmentProof {
+            proof: Some(Ics23Proof::Exist(
+                self.base_existence_proof(&store_type)?,
+            )),
         };
 
         let mut data = vec![];
@@ -443,6 +735,255 @@ impl<H: StorageHasher + Default> MerkleTree<H> {
             ops: vec![sub_proof_op, base_proof_op],
         })
     }
+
+    /// Get the existence proof of the root of the given sub-tree in the base
+    /// tree
+    fn base_existence_proof(
+        &self,
+        store_type: &StoreType,
+    ) -> Result<ExistenceProof> {
+        let base_key = store_type.to_string();
+        let cp = self.base.membership_proof(&H::hash(&base_key).into())?;
+        // Replace the values and the leaf op for the verification
+        match cp.proof.expect("The proof should exist") {
+            Ics23Proof::Exist(ep) => Ok(ExistenceProof {
+                key: base_key.as_bytes().to_vec(),
+                leaf: Some(ics23_specs::base_leaf_spec::<H>()),
+                ..ep
+            }),
+            // the proof should have an ExistenceProof
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Get the proof operation of a compressed batch proof
+fn batch_proof_op(key: String, entries: Vec<BatchEntry>) -> ProofOp {
+    let proof = ics23::compress(&CommitmentProof {
+        proof: Some(Ics23Proof::Batch(BatchProof { entries })),
+    });
+    let mut data = vec![];
+    proof
+        .encode(&mut data)
+        .expect("Encoding proof shouldn't fail");
+    ProofOp {
+        field_type: "ics23_CommitmentProof".to_string(),
+        key: key.into_bytes(),
+        data,
+    }
+}
+
+/// Decode and decompress the batch proof of a proof operation
+fn decode_batch_proof(op: &ProofOp) -> Result<BatchProof> {
+    let proof = CommitmentProof::decode(&*op.data)
+        .map_err(|e| Error::InvalidBatchProof(e.to_string()))?;
+    let proof = ics23::decompress(&proof)
+        .map_err(|e| Error::InvalidBatchProof(e.to_string()))?;
+    match proof.proof {
+        Some(Ics23Proof::Batch(batch)) => Ok(batch),
+        _ => Err(Error::InvalidBatchProof(
+            "The proof isn't a batch proof".into(),
+        )),
+    }
+}
+
+/// Get the start of the keys of the IBC sub-tree that have the segments of the
+/// given prefix, other than the prefix itself
+fn prefix_range_start(prefix: &[u8]) -> Vec<u8> {
+    let mut start = prefix.to_vec();
+    if !start.is_empty() {
+        start.push(KEY_SEGMENT_SEPARATOR as u8);
+    }
+    start
+}
+
+/// Get the gaps between the given keys of the IBC sub-tree that must be
+/// proven empty for them to include all the keys that start with the segments
+/// of the given prefix. Every gap is given by a key that must be absent,
+/// paired with the key of the range that must follow it, if any.
+fn prefix_range_gaps(
+    prefix: &[u8],
+    keys: &BTreeSet<Vec<u8>>,
+) -> Vec<(Vec<u8>, Option<Vec<u8>>)> {
+    let start = prefix_range_start(prefix);
+    let mut gaps = vec![];
+    let mut gap_key = start.clone();
+    for key in keys.range(start.clone()..) {
+        if !key.starts_with(&start) {
+            break;
+        }
+        gaps.push((gap_key, Some(key.clone())));
+        // the least key after this one
+        gap_key = [key.as_slice(), &[0]].concat();
+    }
+    gaps.push((gap_key, None));
+    gaps
+}
+
+/// Find the non-existence proof of the given key in a batch proof and verify
+/// it against the root of the sub-tree
+fn verify_batch_non_existence<'a>(
+    batch: &'a BatchProof,
+    spec: &ProofSpec,
+    root: &[u8],
+    key: &[u8],
+) -> Result<&'a NonExistenceProof> {
+    let nep = batch.entries.iter().find_map(|entry| match &entry.proof {
+        Some(batch_entry::Proof::Nonexist(nep))
+            if nep.left.as_ref().map_or(true, |left| left.key < *key)
+                && nep
+                    .right
+                    .as_ref()
+                    .map_or(true, |right| right.key > *key) =>
+        {
+            Some(nep)
+        }
+        _ => None,
+    });
+    match nep {
+        Some(nep)
+            if ics23::verify_non_membership(
+                &CommitmentProof {
+                    proof: Some(Ics23Proof::Nonexist(nep.clone())),
+                },
+                spec,
+                &root.to_vec(),
+                key,
+            ) =>
+        {
+            Ok(nep)
+        }
+        _ => Err(Error::InvalidBatchProof(
+            "The absence of a key isn't proven".into(),
+        )),
+    }
+}
+
+/// Verify a batch proof, given by [`MerkleTree::get_batch_proof`], of the
+/// existence of the given key-vals and that they are all the key-vals with the
+/// given complete prefixes against the root of the Merkle tree. The roots of
+/// the sub-trees are calculated from their proofs and verified with the proof
+/// of the base tree. The complete prefixes must be in the IBC sub-tree, for
+/// which the absence of any key between the given ones in the range of a
+/// prefix is verified. For the other sub-trees, only the existence of the
+/// key-vals is proven. The keys are mapped to the sub-trees as in the latest
+/// layout of the tree.
+pub fn verify_batch_proof<H: StorageHasher>(
+    proof: &Proof,
+    root: &[u8],
+    key_vals: &[(Key, Vec<u8>)],
+    complete_prefixes: &[Key],
+) -> Result<()> {
+    let invalid = |msg: &str| Err(Error::InvalidBatchProof(msg.to_owned()));
+
+    // The sub-keys with their values and the complete prefixes of every
+    // sub-tree
+    let mut sub_trees: BTreeMap<
+        StoreType,
+        (BTreeMap<Vec<u8>, Vec<u8>>, Vec<Vec<u8>>),
+    > = BTreeMap::new();
+    for (key, value) in key_vals {
+        let (store_type, sub_key) = StoreType::sub_key(key)?;
+        sub_trees
+            .entry(store_type)
+            .or_default()
+            .0
+            .insert(sub_key.to_string().into_bytes(), value.clone());
+    }
+    for prefix in complete_prefixes {
+        let (store_type, sub_key) = StoreType::sub_key(prefix)?;
+        if !store_type.proves_complete_ranges() {
+            return Err(Error::NonExistenceProof(store_type.to_string()));
+        }
+        sub_trees
+            .entry(store_type)
+            .or_default()
+            .1
+            .push(sub_key.to_string().into_bytes());
+    }
+    let (base_op, sub_ops) = match proof.ops.split_last() {
+        Some(ops) => ops,
+        None if sub_trees.is_empty() => return Ok(()),
+        None => return invalid("The proof is empty"),
+    };
+    if sub_ops.len() != sub_trees.len() {
+        return invalid("The proof doesn't match the sub-trees of the keys");
+    }
+
+    let mut sub_roots = BTreeMap::new();
+    for (store_type, (items, prefixes)) in &sub_trees {
+        let store_key = store_type.to_string().into_bytes();
+        let batch = match sub_ops.iter().find(|op| op.key == store_key) {
+            Some(op) => decode_batch_proof(op)?,
+            None => return invalid("A sub-tree proof is missing"),
+        };
+        // Calculate the root of the sub-tree from any of its proofs
+        let sub_root =
+            match batch.entries.first().and_then(|e| e.proof.as_ref()) {
+                Some(batch_entry::Proof::Exist(ep)) => {
+                    ics23::calculate_existence_root(ep)
+                }
+                Some(batch_entry::Proof::Nonexist(nep)) => {
+                    match nep.left.as_ref().or(nep.right.as_ref()) {
+                        Some(ep) => ics23::calculate_existence_root(ep),
+                        None => {
+                            return invalid("A non-existence proof is empty")
+                        }
+                    }
+                }
+                None => return invalid("A sub-tree proof is empty"),
+            }
+            .map_err(|e| Error::InvalidBatchProof(e.to_string()))?;
+        let spec = &store_proof_specs::<H>(store_type)[0];
+        // The key after every gap in the range of a prefix must be the next
+        // key of the range, if any, or a key without the prefix
+        let range_keys: BTreeSet<Vec<u8>> = items.keys().cloned().collect();
+        for prefix in prefixes {
+            if !prefix.is_empty() && !range_keys.contains(prefix) {
+                verify_batch_non_existence(&batch, spec, &sub_root, prefix)?;
+            }
+            let start = prefix_range_start(prefix);
+            for (gap_key, next) in prefix_range_gaps(prefix, &range_keys) {
+                let nep = verify_batch_non_existence(
+                    &batch, spec, &sub_root, &gap_key,
+                )?;
+                let right = nep.right.as_ref().map(|right| &right.key);
+                let is_complete = match &next {
+                    Some(next) => right == Some(next),
+                    None => {
+                        right.map_or(true, |right| !right.starts_with(&start))
+                    }
+                };
+                if !is_complete {
+                    return invalid("A key with a complete prefix is missing");
+                }
+            }
+        }
+        let proof = CommitmentProof {
+            proof: Some(Ics23Proof::Batch(batch)),
+        };
+        let items = items
+            .iter()
+            .map(|(key, value)| (key.as_slice(), value.as_slice()))
+            .collect();
+        if !ics23::verify_batch_membership(&proof, spec, &sub_root, items) {
+            return invalid("The existence of a key-val isn't proven");
+        }
+        sub_roots.insert(store_key, sub_root);
+    }
+
+    let proof = CommitmentProof {
+        proof: Some(Ics23Proof::Batch(decode_batch_proof(base_op)?)),
+    };
+    let spec = &proof_specs::<H>()[1];
+    let items = sub_roots
+        .iter()
+        .map(|(key, root)| (key.as_slice(), root.as_slice()))
+        .collect();
+    if !ics23::verify_batch_membership(&proof, spec, &root.to_vec(), items) {
+        return invalid("The roots of the sub-trees aren't proven");
+    }
+    Ok(())
 }
 
 /// The root hash of the merkle tree as bytes
@@ -474,16 +1015,32 @@ pub struct MerkleTreeStoresRead {
     account: (Hash, SmtStore),
     ibc: (Hash, AmtStore),
     pos: (Hash, SmtStore),
+    governance: (Hash, SmtStore),
+    parameters: (Hash, SmtStore),
+    balance: (Hash, SmtStore),
+    /// The store types whose root has been set
+    roots_set: BTreeSet<StoreType>,
 }
 
 impl MerkleTreeStoresRead {
+    /// Check if the stores were written before the added sub-trees existed,
+    /// i.e. if any of their roots is missing (see [`StoreType::is_added`])
+    pub fn is_legacy(&self) -> bool {
+        StoreType::iter()
+            .any(|st| st.is_added() && !self.roots_set.contains(st))
+    }
+
     /// Set the root of the given store type
     pub fn set_root(&mut self, store_type: &StoreType, root: Hash) {
+        self.roots_set.insert(*store_type);
         match store_type {
             StoreType::Base => self.base.0 = root,
             StoreType::Account => self.account.0 = root,
             StoreType::Ibc => self.ibc.0 = root,
             StoreType::PoS => self.pos.0 = root,
+            StoreType::Governance => self.governance.0 = root,
+            StoreType::Parameters => self.parameters.0 = root,
+            StoreType::Balance => self.balance.0 = root,
         }
     }
 
@@ -494,6 +1051,9 @@ impl MerkleTreeStoresRead {
             Store::Account(store) => self.account.1 = store,
             Store::Ibc(store) => self.ibc.1 = store,
             Store::PoS(store) => self.pos.1 = store,
+            Store::Governance(store) => self.governance.1 = store,
+            Store::Parameters(store) => self.parameters.1 = store,
+            Store::Balance(store) => self.balance.1 = store,
         }
     }
 }
@@ -504,6 +1064,9 @@ pub struct MerkleTreeStoresWrite<'a> {
     account: (Hash, &'a SmtStore),
     ibc: (Hash, &'a AmtStore),
     pos: (Hash, &'a SmtStore),
+    governance: (Hash, &'a SmtStore),
+    parameters: (Hash, &'a SmtStore),
+    balance: (Hash, &'a SmtStore),
 }
 
 impl<'a> MerkleTreeStoresWrite<'a> {
@@ -514,6 +1077,9 @@ impl<'a> MerkleTreeStoresWrite<'a> {
             StoreType::Account => &self.account.0,
             StoreType::Ibc => &self.ibc.0,
             StoreType::PoS => &self.pos.0,
+            StoreType::Governance => &self.governance.0,
+            StoreType::Parameters => &self.parameters.0,
+            StoreType::Balance => &self.balance.0,
         }
     }
 
@@ -524,6 +1090,9 @@ impl<'a> MerkleTreeStoresWrite<'a> {
             StoreType::Account => StoreRef::Account(self.account.1),
             StoreType::Ibc => StoreRef::Ibc(self.ibc.1),
             StoreType::PoS => StoreRef::PoS(self.pos.1),
+            StoreType::Governance => StoreRef::Governance(self.governance.1),
+            StoreType::Parameters => StoreRef::Parameters(self.parameters.1),
+            StoreType::Balance => StoreRef::Balance(self.balance.1),
         }
     }
 }
@@ -543,7 +1112,9 @@ impl From<MtError> for Error {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::ledger::storage::ics23_specs::{ibc_proof_specs, proof_specs};
+    use crate::ledger::storage::ics23_specs::{
+        ibc_proof_specs, proof_specs, store_proof_specs,
+    };
     use crate::ledger::storage::traits::Sha256Hasher;
     use crate::types::storage::KeySeg;
 
@@ -655,6 +1226,64 @@ mod test {
         assert!(restored_tree.has_key(&pos_key).unwrap());
     }
 
+    /// Test that a tree restored from the stores written before the added
+    /// sub-trees existed keeps their data in the account sub-tree until it's
+    /// migrated
+    #[test]
+    fn test_migrate_legacy_tree() {
+        let account_key: Key =
+            crate::types::address::testing::established_address_1()
+                .to_db_key()
+                .into();
+        let balance_key = token::balance_key(
+            &crate::types::address::nam(),
+            &crate::types::address::testing::established_address_2(),
+        );
+        let key_prefix: Key = Address::Internal(InternalAddress::Governance)
+            .to_db_key()
+            .into();
+        let gov_key = key_prefix.push(&"test".to_string()).unwrap();
+        let key_vals = [
+            (account_key, vec![1u8; 8]),
+            (balance_key, vec![2u8; 8]),
+            (gov_key, vec![3u8; 8]),
+        ];
+
+        // the stores written before the added sub-trees existed
+        let stores_write = MerkleTree::<Sha256Hasher>::default().stores();
+        let mut stores_read = MerkleTreeStoresRead::default();
+        for st in StoreType::iter().filter(|st| !st.is_added()) {
+            stores_read.set_root(st, stores_write.root(st).clone());
+            stores_read.set_store(stores_write.store(st).to_owned());
+        }
+        let mut tree = MerkleTree::<Sha256Hasher>::new(stores_read);
+        assert!(tree.is_legacy());
+        for (key, value) in &key_vals {
+            tree.update(key, value).unwrap();
+            assert!(tree.has_key(key).unwrap());
+            assert!(tree
+                .tree(&StoreType::Account)
+                .subtree_has_key(key)
+                .unwrap());
+        }
+
+        tree.migrate(
+            key_vals.iter().map(|(key, value)| (key, value.as_slice())),
+        )
+        .unwrap();
+        assert!(!tree.is_legacy());
+        let mut expected = MerkleTree::<Sha256Hasher>::default();
+        for (key, value) in &key_vals {
+            expected.update(key, value).unwrap();
+            assert!(tree.has_key(key).unwrap());
+        }
+        assert!(!tree
+            .tree(&StoreType::Account)
+            .subtree_has_key(&key_vals[1].0)
+            .unwrap());
+        assert_eq!(tree.root().0, expected.root().0);
+    }
+
     #[test]
     fn test_ibc_existence_proof() {
         let mut tree = MerkleTree::<Sha256Hasher>::default();
@@ -765,6 +1394,180 @@ mod test {
         assert_eq!(sub_root, tree.root().0);
     }
 
+    /// Test that the balances, governance and parameters are kept in their
+    /// own sub-trees and that a balance can be proven on its own
+    #[test]
+    fn test_balance_existence_proof() {
+        let mut tree = MerkleTree::<Sha256Hasher>::default();
+
+        let balance_key = token::balance_key(
+            &crate::types::address::nam(),
+            &crate::types::address::testing::established_address_1(),
+        );
+        let (store_type, sub_key) = StoreType::sub_key(&balance_key).unwrap();
+        assert_eq!(store_type, StoreType::Balance);
+        assert_eq!(sub_key, balance_key);
+        for (internal, expected) in [
+            (InternalAddress::Governance, StoreType::Governance),
+            (InternalAddress::Parameters, StoreType::Parameters),
+            (InternalAddress::EthBridge, StoreType::Account),
+        ] {
+            let key_prefix: Key =
+                Address::Internal(internal).to_db_key().into();
+            let key = key_prefix.push(&"test".to_string()).unwrap();
+            let (store_type, _) = StoreType::sub_key(&key).unwrap();
+            assert_eq!(store_type, expected);
+        }
+
+        let balance_val = [3u8; 8].to_vec();
+        tree.update(&balance_key, balance_val.clone()).unwrap();
+        let account_key = Key::parse("account/test").unwrap();
+        tree.update(&account_key, [4u8; 8].to_vec()).unwrap();
+
+        let specs = store_proof_specs::<Sha256Hasher>(&StoreType::Balance);
+        let MembershipProof::ICS23(proof) = tree
+            .get_sub_tree_existence_proof(
+                std::array::from_ref(&balance_key),
+                vec![balance_val.clone().into()],
+            )
+            .unwrap();
+        let proof = tree.get_tendermint_proof(&balance_key, proof).unwrap();
+        let paths = vec![balance_key.to_string(), store_type.to_string()];
+        let mut sub_root = balance_val.clone();
+        let mut value = balance_val;
+        // First, the sub proof is verified. Next the base proof is verified
+        // with the sub root
+        for ((p, spec), key) in
+            proof.ops.iter().zip(specs.iter()).zip(paths.iter())
+        {
+            let commitment_proof = CommitmentProof::decode(&*p.data).unwrap();
+            let existence_proof = match commitment_proof.clone().proof.unwrap()
+            {
+                Ics23Proof::Exist(ep) => ep,
+                _ => unreachable!(),
+            };
+            sub_root =
+                ics23::calculate_existence_root(&existence_proof).unwrap();
+            assert!(ics23::verify_membership(
+                &commitment_proof,
+                spec,
+                &sub_root,
+                key.as_bytes(),
+                &value,
+            ));
+            // for the verification of the base tree
+            value = sub_root.clone();
+        }
+        // Check the base root
+        assert_eq!(sub_root, tree.root().0);
+    }
+
+    /// Test that a batch proof of key-vals in several sub-trees and of the
+    /// complete range of an IBC prefix can be verified, but not for other
+    /// key-vals or for a range with missing keys.
+    #[test]
+    fn test_batch_proof() {
+        let mut tree = MerkleTree::<Sha256Hasher>::default();
+
+        let ibc_prefix: Key =
+            Address::Internal(InternalAddress::Ibc).to_db_key().into();
+        let range_prefix = ibc_prefix.push(&"test".to_string()).unwrap();
+        let range_keys: Vec<Key> = std::iter::once(range_prefix.clone())
+            .chain(
+                (0..3)
+                    .map(|i| range_prefix.push(&format!("key{}", i)).unwrap()),
+            )
+            .collect();
+        // Not in the range, as they don't start with the prefix's segments,
+        // but next to its keys in the tree
+        let other_ibc_keys: Vec<Key> = ["tes", "test-other", "tests"]
+            .iter()
+            .map(|seg| ibc_prefix.push(&seg.to_string()).unwrap())
+            .collect();
+        let account_keys: Vec<Key> = (0..3)
+            .map(|i| Key::parse(format!("account/test{}", i)).unwrap())
+            .collect();
+        let balance_key = token::balance_key(
+            &crate::types::address::nam(),
+            &crate::types::address::testing::established_address_1(),
+        );
+        let key_vals: Vec<(Key, Vec<u8>)> = range_keys
+            .iter()
+            .chain(account_keys.iter())
+            .chain(std::iter::once(&balance_key))
+            .enumerate()
+            .map(|(i, key)| (key.clone(), vec![i as u8; 8]))
+            .collect();
+        for (key, value) in &key_vals {
+            tree.update(key, value.clone()).unwrap();
+        }
+        for key in &other_ibc_keys {
+            tree.update(key, [0xaa; 8]).unwrap();
+        }
+        let root = tree.root().0;
+        let get_proof = |key_vals: &[(Key, Vec<u8>)], prefix: &Key| {
+            let (keys, values): (Vec<Key>, Vec<MerkleValue>) = key_vals
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone().into()))
+                .unzip();
+            tree.get_batch_proof(&keys, values, std::slice::from_ref(prefix))
+        };
+        let verify = |proof: &Proof,
+                      key_vals: &[(Key, Vec<u8>)],
+                      prefix: &[Key]| {
+            verify_batch_proof::<Sha256Hasher>(proof, &root, key_vals, prefix)
+        };
+
+        let proof = get_proof(&key_vals, &range_prefix).unwrap();
+        // a proof of every sub-tree and of the base tree
+        assert_eq!(proof.ops.len(), 4);
+        let complete = std::slice::from_ref(&range_prefix);
+        verify(&proof, &key_vals, complete).unwrap();
+        // a subset of the key-vals is proven to exist, but not to be complete
+        verify(&proof, &key_vals[..2], &[]).unwrap();
+        assert!(verify(&proof, &key_vals[..2], complete).is_err());
+
+        let mut wrong_key_vals = key_vals.clone();
+        wrong_key_vals[4].1 = vec![0xff; 8];
+        assert!(verify(&proof, &wrong_key_vals, &[]).is_err());
+        assert!(verify_batch_proof::<Sha256Hasher>(
+            &proof,
+            &[0; 32],
+            &key_vals,
+            &[]
+        )
+        .is_err());
+
+        // the range isn't complete without any of its keys, even with a proof
+        // of the other ones, if it can be made at all
+        let is_incomplete = |key_vals: &[(Key, Vec<u8>)]| {
+            get_proof(key_vals, &range_prefix).map_or(true, |proof| {
+                verify(&proof, key_vals, complete).is_err()
+            })
+        };
+        for i in 0..range_keys.len() {
+            let mut partial_key_vals = key_vals.clone();
+            partial_key_vals.remove(i);
+            assert!(is_incomplete(&partial_key_vals));
+        }
+        assert!(is_incomplete(&[]));
+
+        // the range of a prefix without keys is empty
+        let empty_prefix = ibc_prefix.push(&"empty".to_string()).unwrap();
+        let proof = get_proof(&[], &empty_prefix).unwrap();
+        verify(&proof, &[], std::slice::from_ref(&empty_prefix)).unwrap();
+
+        // a range can't be proven complete in the other sub-trees
+        assert!(matches!(
+            tree.get_batch_proof(
+                &[],
+                vec![],
+                &[Key::parse("account/empty").unwrap()]
+            ),
+            Err(Error::NonExistenceProof(_))
+        ));
+    }
+
     #[test]
     fn test_ibc_non_existence_proof() {
         let mut tree = MerkleTree::<Sha256Hasher>::default();
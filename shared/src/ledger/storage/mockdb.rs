@@ -10,7 +10,8 @@ use borsh::{BorshDeserialize, BorshSerialize};
 
 use super::merkle_tree::{MerkleTreeStoresRead, StoreType};
 use super::{
-    BlockStateRead, BlockStateWrite, DBIter, DBWriteBatch, Error, Result, DB,
+    BlockMetadataRead, BlockStateRead, BlockStateWrite, DBIter, DBWriteBatch,
+    Error, HistoricalPrefixIter, PruningState, Result, DB,
 };
 use crate::ledger::storage::types::{self, KVBytes, PrefixIterator};
 #[cfg(feature = "ferveo-tpke")]
@@ -33,6 +34,23 @@ pub struct MockDB(
 // safe.
 unsafe impl Sync for MockDB {}
 
+impl MockDB {
+    /// Keep the version of an account subspace key-val set at the given
+    /// height, or `None` if it was deleted, to be able to iterate the state
+    /// at past heights
+    fn write_subspace_version(
+        &self,
+        height: BlockHeight,
+        key: &Key,
+        value: Option<&[u8]>,
+    ) {
+        self.0.borrow_mut().insert(
+            format!("history/{}/{}", key, height.raw()),
+            types::encode(&value.map(<[u8]>::to_vec)),
+        );
+    }
+}
+
 /// An in-memory write batch is not needed as it just updates values in memory.
 /// It's here to satisfy the storage interface.
 #[derive(Debug, Default)]
@@ -307,6 +325,29 @@ impl DB for MockDB {
         }
     }
 
+    fn read_block_metadata(
+        &self,
+        height: BlockHeight,
+    ) -> Result<Option<BlockMetadataRead>> {
+        let prefix_key = Key::from(height.to_db_key());
+        let read = |name: &str| -> Result<Option<Vec<u8>>> {
+            let key =
+                prefix_key.push(&name.to_owned()).map_err(Error::KeyError)?;
+            Ok(self.0.borrow().get(&key.to_string()).cloned())
+        };
+        match (read("hash")?, read("epoch")?, read("address_gen")?) {
+            (Some(hash), Some(epoch), Some(address_gen)) => {
+                Ok(Some(BlockMetadataRead {
+                    hash: types::decode(hash).map_err(Error::CodingError)?,
+                    epoch: types::decode(epoch).map_err(Error::CodingError)?,
+                    address_gen: types::decode(address_gen)
+                        .map_err(Error::CodingError)?,
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
+
     fn read_merkle_tree_stores(
         &self,
         height: BlockHeight,
@@ -328,6 +369,9 @@ impl DB for MockDB {
                     let root = types::decode(b).map_err(Error::CodingError)?;
                     merkle_tree_stores.set_root(st, root);
                 }
+                // the added sub-trees are missing in the stores written
+                // before they existed
+                None if st.is_added() => continue,
                 None => return Ok(None),
             }
 
@@ -362,11 +406,12 @@ impl DB for MockDB {
 
     fn write_subspace_val(
         &mut self,
-        _height: BlockHeight,
+        height: BlockHeight,
         key: &Key,
         value: impl AsRef<[u8]>,
     ) -> Result<i64> {
         let value = value.as_ref();
+        self.write_subspace_version(height, key, Some(value));
         let key = Key::parse("subspace").map_err(Error::KeyError)?.join(key);
         let current_len = value.len() as i64;
         Ok(
@@ -383,9 +428,10 @@ impl DB for MockDB {
 
     fn delete_subspace_val(
         &mut self,
-        _height: BlockHeight,
+        height: BlockHeight,
         key: &Key,
     ) -> Result<i64> {
+        self.write_subspace_version(height, key, None);
         let key = Key::parse("subspace").map_err(Error::KeyError)?.join(key);
         Ok(match self.0.borrow_mut().remove(&key.to_string()) {
             Some(value) => value.len() as i64,
@@ -406,11 +452,12 @@ impl DB for MockDB {
     fn batch_write_subspace_val(
         &self,
         _batch: &mut Self::WriteBatch,
-        _height: BlockHeight,
+        height: BlockHeight,
         key: &Key,
         value: impl AsRef<[u8]>,
     ) -> Result<i64> {
         let value = value.as_ref();
+        self.write_subspace_version(height, key, Some(value));
         let key = Key::parse("subspace").map_err(Error::KeyError)?.join(key);
         let current_len = value.len() as i64;
         Ok(
@@ -428,15 +475,70 @@ impl DB for MockDB {
     fn batch_delete_subspace_val(
         &self,
         _batch: &mut Self::WriteBatch,
-        _height: BlockHeight,
+        height: BlockHeight,
         key: &Key,
     ) -> Result<i64> {
+        self.write_subspace_version(height, key, None);
         let key = Key::parse("subspace").map_err(Error::KeyError)?.join(key);
         Ok(match self.0.borrow_mut().remove(&key.to_string()) {
             Some(value) => value.len() as i64,
             None => 0,
         })
     }
+
+    fn read_pruning_state(&self) -> Result<PruningState> {
+        match self.0.borrow().get("pruning") {
+            Some(bytes) => types::decode(bytes).map_err(Error::CodingError),
+            None => Ok(PruningState::default()),
+        }
+    }
+
+    fn write_pruning_state(&mut self, state: &PruningState) -> Result<()> {
+        self.0
+            .borrow_mut()
+            .insert("pruning".into(), types::encode(state));
+        Ok(())
+    }
+
+    fn prune_height(
+        &mut self,
+        height: BlockHeight,
+        _merge_diffs: bool,
+    ) -> Result<()> {
+        // Mock DB doesn't keep the diffs
+        let tree_key = Key::from(height.to_db_key())
+            .push(&"tree".to_owned())
+            .map_err(Error::KeyError)?;
+        for st in StoreType::iter() {
+            let prefix_key =
+                tree_key.push(&st.to_string()).map_err(Error::KeyError)?;
+            for suffix in ["root", "store"] {
+                let key = prefix_key
+                    .push(&suffix.to_owned())
+                    .map_err(Error::KeyError)?;
+                self.0.borrow_mut().remove(&key.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    fn is_pruned(&self, height: BlockHeight) -> Result<bool> {
+        let pruned_height = self.read_pruning_state()?.pruned_height;
+        // The pruning starts from the first height after the genesis
+        if pruned_height == BlockHeight::default() || height > pruned_height {
+            return Ok(false);
+        }
+        let root_key = Key::from(height.to_db_key())
+            .push(&"tree".to_owned())
+            .and_then(|key| key.push(&StoreType::Base.to_string()))
+            .and_then(|key| key.push(&"root".to_owned()))
+            .map_err(Error::KeyError)?;
+        Ok(!self.0.borrow().contains_key(&root_key.to_string()))
+    }
+
+    fn checkpoint(&self, _dir: &Path) -> Result<Self> {
+        Ok(Self(RefCell::new(self.0.borrow().clone())))
+    }
 }
 
 impl<'iter> DBIter<'iter> for MockDB {
@@ -483,6 +585,55 @@ impl<'iter> DBIter<'iter> for MockDB {
             db_prefix,
         )
     }
+
+    fn iter_prefix_at_height(
+        &'iter self,
+        prefix: &Key,
+        height: BlockHeight,
+    ) -> Result<HistoricalPrefixIter> {
+        if self.is_pruned(height)? {
+            return Err(Error::Pruned { height });
+        }
+        let prefix = prefix.to_string();
+        // The last version of every key with the prefix at the height
+        let mut versions: BTreeMap<String, (BlockHeight, Option<Vec<u8>>)> =
+            BTreeMap::new();
+        for (path, bytes) in self.0.borrow().iter() {
+            let (key, version_height) = match path
+                .strip_prefix("history/")
+                .and_then(|path| path.rsplit_once(KEY_SEGMENT_SEPARATOR))
+                .and_then(|(key, raw_height)| {
+                    Some((key, BlockHeight(raw_height.parse().ok()?)))
+                }) {
+                Some(version) => version,
+                None => continue,
+            };
+            let has_prefix = prefix.is_empty()
+                || key == prefix
+                || key.strip_prefix(&prefix).map_or(false, |rest| {
+                    rest.starts_with(KEY_SEGMENT_SEPARATOR)
+                });
+            if !has_prefix || version_height > height {
+                continue;
+            }
+            let is_later = versions
+                .get(key)
+                .map_or(true, |(last_height, _)| *last_height < version_height);
+            if is_later {
+                let value = types::decode(bytes).map_err(Error::CodingError)?;
+                versions.insert(key.to_owned(), (version_height, value));
+            }
+        }
+        Ok(versions
+            .into_iter()
+            .filter_map(|(key, (_, value))| {
+                let value = value?;
+                let gas = key.len() + value.len();
+                Some((key, value, gas as _))
+            })
+            .collect::<Vec<_>>()
+            .into_iter())
+    }
 }
 
 /// A prefix iterator base for the [`MockPrefixIterator`].
@@ -1,9 +1,11 @@
 //! Ledger's state storage with key-value backed store and a merkle tree
 
+pub mod export;
 pub mod ics23_specs;
 mod merkle_tree;
 #[cfg(any(test, feature = "testing"))]
 pub mod mockdb;
+pub mod snapshot;
 pub mod traits;
 pub mod types;
 pub mod write_log;
@@ -23,6 +25,7 @@ use rayon::iter::{
 };
 #[cfg(feature = "wasm-runtime")]
 use rayon::prelude::ParallelSlice;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use super::parameters::{self, Parameters};
@@ -34,7 +37,8 @@ use crate::ledger::storage::merkle_tree::{
     Error as MerkleTreeError, MerkleRoot,
 };
 pub use crate::ledger::storage::merkle_tree::{
-    MerkleTree, MerkleTreeStoresRead, MerkleTreeStoresWrite, StoreType,
+    verify_batch_proof, MerkleTree, MerkleTreeStoresRead,
+    MerkleTreeStoresWrite, StoreType,
 };
 pub use crate::ledger::storage::traits::{Sha256Hasher, StorageHasher};
 use crate::tendermint::merkle::proof::Proof;
@@ -42,6 +46,7 @@ use crate::types::address::{
     masp, Address, EstablishedAddressGen, InternalAddress,
 };
 use crate::types::chain::{ChainId, CHAIN_ID_LENGTH};
+use crate::types::hash::Hash;
 #[cfg(feature = "ferveo-tpke")]
 use crate::types::storage::TxQueue;
 use crate::types::storage::{
@@ -151,6 +156,90 @@ pub enum Error {
     BorshCodingError(std::io::Error),
     #[error("Merkle tree at the height {height} is not stored")]
     NoMerkleTree { height: BlockHeight },
+    #[error("The state at the height {height} has been pruned")]
+    Pruned { height: BlockHeight },
+    #[error(
+        "The state at the height {height} cannot be iterated, the history \
+         starts at the height {history_start}"
+    )]
+    NoHistory {
+        height: BlockHeight,
+        history_start: BlockHeight,
+    },
+    #[error(
+        "The state restored from the snapshot at the height {height} doesn't \
+         match the app hash"
+    )]
+    SnapshotMismatch { height: BlockHeight },
+    #[error("No block has been committed at the height {height}")]
+    NoBlock { height: BlockHeight },
+}
+
+/// The heights whose state, i.e. the diffs and the Merkle tree stores needed
+/// to read and prove values at a past height, is kept in the DB
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum PruningMode {
+    /// Keep the state of every height
+    #[default]
+    Archive,
+    /// Keep the state of the last `keep_recent` heights only
+    KeepRecent {
+        /// The number of the most recent heights to keep
+        keep_recent: u64,
+    },
+    /// Keep the state of the last `keep_recent` heights and of every height
+    /// that is a multiple of `keep_every`
+    KeepEvery {
+        /// The number of the most recent heights to keep
+        keep_recent: u64,
+        /// The interval of the older heights to keep
+        keep_every: u64,
+    },
+}
+
+impl PruningMode {
+    /// Returns the highest height whose state may be pruned once the given
+    /// height is committed, if any. The state of the last committed height
+    /// is always kept.
+    pub fn last_prunable_height(
+        &self,
+        last_height: BlockHeight,
+    ) -> Option<BlockHeight> {
+        let keep_recent = match self {
+            PruningMode::Archive => return None,
+            PruningMode::KeepRecent { keep_recent }
+            | PruningMode::KeepEvery { keep_recent, .. } => *keep_recent,
+        };
+        match last_height.0.checked_sub(keep_recent.max(1)) {
+            Some(height) if height > 0 => Some(BlockHeight(height)),
+            _ => None,
+        }
+    }
+
+    /// Check if the state of the given height is kept once it's no longer
+    /// one of the most recent heights
+    pub fn keeps(&self, height: BlockHeight) -> bool {
+        match self {
+            PruningMode::Archive => true,
+            PruningMode::KeepRecent { .. } => false,
+            PruningMode::KeepEvery { keep_every, .. } => {
+                *keep_every != 0 && height.0 % keep_every == 0
+            }
+        }
+    }
+}
+
+/// The progress of the pruning of the state in the DB
+#[derive(Clone, Debug, Default, BorshSerialize, BorshDeserialize)]
+pub struct PruningState {
+    /// The state of every height up to this one has been either pruned or
+    /// kept
+    pub pruned_height: BlockHeight,
+    /// The lowest height whose state has been kept while pruning, if any
+    pub lowest_kept_height: Option<BlockHeight>,
 }
 
 /// The block's state as stored in the database.
@@ -178,6 +267,16 @@ pub struct BlockStateRead {
     pub tx_queue: TxQueue,
 }
 
+/// The metadata of a committed block as stored in the database.
+pub struct BlockMetadataRead {
+    /// Hash of the block
+    pub hash: BlockHash,
+    /// Epoch of the block
+    pub epoch: Epoch,
+    /// Established address generator
+    pub address_gen: EstablishedAddressGen,
+}
+
 /// The block's state to write into the database.
 pub struct BlockStateWrite<'a> {
     /// Merkle tree stores
@@ -205,8 +304,60 @@ pub struct BlockStateWrite<'a> {
     pub tx_queue: &'a TxQueue,
 }
 
+/// The state of a block that isn't kept in the account subspace. Its hash is
+/// committed to in the Merkle tree, so that the whole state of a block can be
+/// verified against its app hash, e.g. when it's restored from a snapshot.
+pub struct BlockMetadata<'a> {
+    /// Hash of the block
+    pub hash: &'a BlockHash,
+    /// Header of the block
+    pub header: &'a Option<Header>,
+    /// Height of the block
+    pub height: BlockHeight,
+    /// Epoch of the block
+    pub epoch: Epoch,
+    /// Predecessor block epochs
+    pub pred_epochs: &'a Epochs,
+    /// Minimum block height at which the next epoch may start
+    pub next_epoch_min_start_height: BlockHeight,
+    /// Minimum block time at which the next epoch may start
+    pub next_epoch_min_start_time: DateTimeUtc,
+    /// Established address generator
+    pub address_gen: &'a EstablishedAddressGen,
+    /// Results of applying transactions
+    pub results: &'a BlockResults,
+    /// Wrapper txs to be decrypted in the next block proposal
+    #[cfg(feature = "ferveo-tpke")]
+    pub tx_queue: &'a TxQueue,
+}
+
+impl BlockMetadata<'_> {
+    /// Hash the metadata, which commits to all of its fields
+    pub fn hash(&self) -> Hash {
+        let mut bytes = vec![];
+        self.encode(&mut bytes)
+            .expect("Encoding a block's metadata shouldn't fail");
+        Hash::sha256(bytes)
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) -> std::io::Result<()> {
+        self.hash.serialize(bytes)?;
+        self.header.serialize(bytes)?;
+        self.height.serialize(bytes)?;
+        self.epoch.serialize(bytes)?;
+        self.pred_epochs.serialize(bytes)?;
+        self.next_epoch_min_start_height.serialize(bytes)?;
+        self.next_epoch_min_start_time.serialize(bytes)?;
+        self.address_gen.serialize(bytes)?;
+        self.results.serialize(bytes)?;
+        #[cfg(feature = "ferveo-tpke")]
+        self.tx_queue.serialize(bytes)?;
+        Ok(())
+    }
+}
+
 /// A database backend.
-pub trait DB: std::fmt::Debug {
+pub trait DB: std::fmt::Debug + Send {
     /// A DB's cache
     type Cache;
     /// A handle for batch writes
@@ -230,6 +381,12 @@ pub trait DB: std::fmt::Debug {
     /// Read the block header with the given height from the DB
     fn read_block_header(&self, height: BlockHeight) -> Result<Option<Header>>;
 
+    /// Read the metadata of the block with the given height from the DB
+    fn read_block_metadata(
+        &self,
+        height: BlockHeight,
+    ) -> Result<Option<BlockMetadataRead>>;
+
     /// Read the merkle tree stores with the given height
     fn read_merkle_tree_stores(
         &self,
@@ -240,9 +397,10 @@ pub trait DB: std::fmt::Debug {
     fn read_subspace_val(&self, key: &Key) -> Result<Option<Vec<u8>>>;
 
     /// Read the value for account subspace key at the given height from the DB.
-    /// In our `PersistentStorage` (rocksdb), to find a value from arbitrary
-    /// height requires looking for diffs from the given `height`, possibly
-    /// up to the `last_height`.
+    /// In our `PersistentStorage` (rocksdb), the value is found with a single
+    /// seek in the history index, in which the versions of a key are sorted by
+    /// their height. Below the start of the index, it requires looking for
+    /// diffs from the given `height`, possibly up to the `last_height`.
     fn read_subspace_val_with_height(
         &self,
         key: &Key,
@@ -295,6 +453,32 @@ pub trait DB: std::fmt::Debug {
         height: BlockHeight,
         key: &Key,
     ) -> Result<i64>;
+
+    /// Read the progress of the pruning of the state
+    fn read_pruning_state(&self) -> Result<PruningState>;
+
+    /// Write the progress of the pruning of the state
+    fn write_pruning_state(&mut self, state: &PruningState) -> Result<()>;
+
+    /// Delete the diffs and the Merkle tree stores of the given height. When
+    /// `merge_diffs` is set, the diffs are merged into those of the next
+    /// height instead, so that the values at the lower heights can still be
+    /// read.
+    fn prune_height(
+        &mut self,
+        height: BlockHeight,
+        merge_diffs: bool,
+    ) -> Result<()>;
+
+    /// Check if the state at the given height has been pruned
+    fn is_pruned(&self, height: BlockHeight) -> Result<bool>;
+
+    /// Create a consistent copy of the DB that can be read while this DB
+    /// keeps being written to. A DB persisted on disk creates it in the given
+    /// directory, which must not exist yet.
+    fn checkpoint(&self, dir: &std::path::Path) -> Result<Self>
+    where
+        Self: Sized;
 }
 
 /// A database prefix iterator.
@@ -312,8 +496,21 @@ pub trait DBIter<'iter> {
 
     /// Read results subspace key value pairs from the DB
     fn iter_results(&'iter self) -> Self::PrefixIter;
+
+    /// Read account subspace key value pairs with the given prefix from the DB,
+    /// as they were at the given height, ordered by the storage keys. Unlike
+    /// [`DBIter::iter_prefix`], the prefix must match whole key segments.
+    fn iter_prefix_at_height(
+        &'iter self,
+        prefix: &Key,
+        height: BlockHeight,
+    ) -> Result<HistoricalPrefixIter>;
 }
 
+/// A prefix iterator over the account subspace key value pairs at a past
+/// height, with the same items as [`DBIter::PrefixIter`]
+pub type HistoricalPrefixIter = std::vec::IntoIter<(String, Vec<u8>, u64)>;
+
 /// Atomic batch write.
 pub trait DBWriteBatch {
     /// Insert a value into the database under the given key.
@@ -423,6 +620,58 @@ where
         Ok(())
     }
 
+    /// Load the conversions given as query results, which are kept in memory,
+    /// from the MASP address space, if they have been written
+    pub(crate) fn load_conversion_state(&mut self) -> Result<()> {
+        let state_key = Key::from(masp().to_db_key())
+            .push(&(token::CONVERSION_KEY_PREFIX.to_owned()))
+            .map_err(Error::KeyError)?;
+        if let (Some(bytes), _gas) = self.read(&state_key)? {
+            self.conversion_state =
+                types::decode(bytes).map_err(Error::CodingError)?;
+        }
+        Ok(())
+    }
+
+    /// Check if a Merkle tree written before the added sub-trees existed must
+    /// be migrated to them with the current block. The migration changes the
+    /// root of the tree, so every node does it at the upgrade height set by
+    /// governance (see
+    /// [`parameters::storage::get_merkle_tree_upgrade_height_key`]) and the
+    /// legacy tree is kept until then.
+    fn is_merkle_tree_upgrade_due(&self) -> Result<bool> {
+        let key = parameters::storage::get_merkle_tree_upgrade_height_key();
+        let (value, _gas) = self.read(&key)?;
+        match value {
+            Some(bytes) => {
+                let upgrade_height: BlockHeight =
+                    types::decode(bytes).map_err(Error::CodingError)?;
+                Ok(self.block.height >= upgrade_height)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Build the added sub-trees of a Merkle tree written before they existed
+    /// from the state, at the upgrade height
+    fn migrate_merkle_tree(&mut self) -> Result<()> {
+        let mut key_vals = vec![];
+        for (key, value, _gas) in self.db.iter_prefix(&Key::default()) {
+            let key = Key::parse(key).map_err(Error::KeyError)?;
+            if StoreType::sub_key(&key)?.0.is_added() {
+                key_vals.push((key, value));
+            }
+        }
+        self.block.tree.migrate(
+            key_vals.iter().map(|(key, value)| (key, value.as_slice())),
+        )?;
+        tracing::info!(
+            "Migrated the Merkle tree to the added sub-trees at height {}",
+            self.block.height
+        );
+        Ok(())
+    }
+
     /// Returns the Merkle root hash and the height of the committed block. If
     /// no block exists, returns None.
     pub fn get_state(&self) -> Option<(MerkleRoot, u64)> {
@@ -435,6 +684,15 @@ where
 
     /// Persist the current block's state to the database
     pub fn commit(&mut self) -> Result<()> {
+        if self.block.tree.is_legacy() && self.is_merkle_tree_upgrade_due()? {
+            self.migrate_merkle_tree()?;
+        }
+        // The metadata is only committed to once the tree is migrated, as it
+        // changes the root of the tree
+        if !self.block.tree.is_legacy() {
+            let metadata_hash = self.block_metadata().hash();
+            self.block.tree.update_block_metadata(metadata_hash)?;
+        }
         let state = BlockStateWrite {
             merkle_tree_stores: self.block.tree.stores(),
             header: self.header.as_ref(),
@@ -456,6 +714,51 @@ where
         Ok(())
     }
 
+    /// Get the metadata of the current block
+    fn block_metadata(&self) -> BlockMetadata<'_> {
+        BlockMetadata {
+            hash: &self.block.hash,
+            header: &self.header,
+            height: self.block.height,
+            epoch: self.block.epoch,
+            pred_epochs: &self.block.pred_epochs,
+            next_epoch_min_start_height: self.next_epoch_min_start_height,
+            next_epoch_min_start_time: self.next_epoch_min_start_time,
+            address_gen: &self.address_gen,
+            results: &self.block.results,
+            #[cfg(feature = "ferveo-tpke")]
+            tx_queue: &self.tx_queue,
+        }
+    }
+
+    /// Prune the state of the heights that are no longer kept by the given
+    /// pruning mode. Must be called after a block is committed.
+    pub fn prune(&mut self, mode: PruningMode) -> Result<()> {
+        let prune_to = match mode.last_prunable_height(self.last_height) {
+            Some(height) => height,
+            None => return Ok(()),
+        };
+        let mut state = self.db.read_pruning_state()?;
+        if prune_to <= state.pruned_height {
+            return Ok(());
+        }
+        let mut height = state.pruned_height.next_height();
+        while height <= prune_to {
+            if mode.keeps(height) {
+                state.lowest_kept_height.get_or_insert(height);
+            } else {
+                // The diffs are still needed to read the values at the lower
+                // heights that have been kept
+                let merge_diffs = state.lowest_kept_height.is_some();
+                self.db.prune_height(height, merge_diffs)?;
+            }
+            height = height.next_height();
+        }
+        tracing::debug!("Pruned the state up to height {}", prune_to);
+        state.pruned_height = prune_to;
+        self.db.write_pruning_state(&state)
+    }
+
     /// Find the root hash of the merkle tree
     pub fn merkle_root(&self) -> MerkleRoot {
         self.block.tree.root()
@@ -525,6 +828,19 @@ where
         (self.db.rev_iter_prefix(prefix), prefix.len() as _)
     }
 
+    /// Returns a prefix iterator over the state at the given height, ordered
+    /// by storage keys, and the gas cost
+    pub fn iter_prefix_at_height(
+        &self,
+        prefix: &Key,
+        height: BlockHeight,
+    ) -> Result<(HistoricalPrefixIter, u64)> {
+        Ok((
+            self.db.iter_prefix_at_height(prefix, height)?,
+            prefix.len() as _,
+        ))
+    }
+
     /// Returns a prefix iterator and the gas cost
     pub fn iter_results(&self) -> (<D as DBIter<'_>>::PrefixIter, u64) {
         (self.db.iter_results(), 0)
@@ -651,6 +967,9 @@ where
                     tree.get_tendermint_proof(key, proof)
                         .map_err(Error::MerkleTreeError)
                 }
+                None if self.db.is_pruned(height)? => {
+                    Err(Error::Pruned { height })
+                }
                 None => Err(Error::NoMerkleTree { height }),
             }
         }
@@ -668,6 +987,37 @@ where
             match self.db.read_merkle_tree_stores(height)? {
                 Some(stores) => Ok(MerkleTree::<H>::new(stores)
                     .get_non_existence_proof(key)?),
+                None if self.db.is_pruned(height)? => {
+                    Err(Error::Pruned { height })
+                }
+                None => Err(Error::NoMerkleTree { height }),
+            }
+        }
+    }
+
+    /// Get a batch proof of the existence of the given key-vals and that they
+    /// are all the key-vals with the given complete prefixes, which must be in
+    /// the IBC sub-tree (see [`MerkleTree::get_batch_proof`])
+    pub fn get_batch_proof(
+        &self,
+        keys: &[Key],
+        values: Vec<MerkleValue>,
+        complete_prefixes: &[Key],
+        height: BlockHeight,
+    ) -> Result<Proof> {
+        if height >= self.last_height {
+            Ok(self.block.tree.get_batch_proof(
+                keys,
+                values,
+                complete_prefixes,
+            )?)
+        } else {
+            match self.db.read_merkle_tree_stores(height)? {
+                Some(stores) => Ok(MerkleTree::<H>::new(stores)
+                    .get_batch_proof(keys, values, complete_prefixes)?),
+                None if self.db.is_pruned(height)? => {
+                    Err(Error::Pruned { height })
+                }
                 None => Err(Error::NoMerkleTree { height }),
             }
         }
@@ -877,6 +1227,8 @@ where
             self.write(&reward_key, types::encode(&total_reward))
                 .expect("unable to update MASP transparent balance");
         }
+        storage_api::token::increase_total_supply(self, &nam(), total_reward)
+            .expect("unable to update the total supply of the reward token");
         // Try to distribute Merkle tree construction as evenly as possible
         // across multiple cores
         // Merkle trees must have exactly 2^n leaves to be mergeable
@@ -1357,4 +1709,78 @@ mod tests {
                 time_of_update + parameters.epoch_duration.min_duration);
         }
     }
+
+    /// Test that a Merkle tree written before the added sub-trees existed is
+    /// only migrated to them at the upgrade height set by governance
+    #[test]
+    fn test_merkle_tree_upgrade_height() {
+        let mut storage = TestStorage::default();
+        // the stores written before the added sub-trees existed
+        let stores_write = MerkleTree::<Sha256Hasher>::default().stores();
+        let mut stores_read = MerkleTreeStoresRead::default();
+        for st in StoreType::iter().filter(|st| !st.is_added()) {
+            stores_read.set_root(st, stores_write.root(st).clone());
+            stores_read.set_store(stores_write.store(st).to_owned());
+        }
+        storage.block.tree = MerkleTree::new(stores_read);
+        assert!(storage.block.tree.is_legacy());
+
+        storage
+            .begin_block(BlockHash::default(), BlockHeight(1))
+            .unwrap();
+        storage.commit().unwrap();
+        assert!(storage.block.tree.is_legacy());
+
+        storage
+            .begin_block(BlockHash::default(), BlockHeight(2))
+            .unwrap();
+        storage
+            .write(
+                &parameters::storage::get_merkle_tree_upgrade_height_key(),
+                types::encode(&BlockHeight(3)),
+            )
+            .unwrap();
+        storage.commit().unwrap();
+        assert!(storage.block.tree.is_legacy());
+
+        storage
+            .begin_block(BlockHash::default(), BlockHeight(3))
+            .unwrap();
+        storage.commit().unwrap();
+        assert!(!storage.block.tree.is_legacy());
+    }
+
+    /// Test that the key-vals at a past height are in the same order as the
+    /// ones from `iter_prefix`, with keys that are sorted differently by their
+    /// segments than by their strings
+    #[test]
+    fn test_iter_prefix_at_height_order() {
+        let mut storage = TestStorage::default();
+        storage
+            .begin_block(BlockHash::default(), BlockHeight(1))
+            .unwrap();
+        let prefix = Key::parse("prefix").unwrap();
+        for sub_key in ["a", "a/b", "a-c", "b"] {
+            let key = Key::parse(format!("{}/{}", prefix, sub_key)).unwrap();
+            storage.write(&key, types::encode(&1_u64)).unwrap();
+        }
+        storage.commit().unwrap();
+
+        let (iter, _gas) = storage.iter_prefix(&prefix);
+        let expected: Vec<String> = iter.map(|(key, _, _)| key).collect();
+        assert_eq!(
+            expected,
+            vec![
+                "prefix/a".to_owned(),
+                "prefix/a-c".to_owned(),
+                "prefix/a/b".to_owned(),
+                "prefix/b".to_owned(),
+            ]
+        );
+        let (iter, _gas) = storage
+            .iter_prefix_at_height(&prefix, BlockHeight(1))
+            .unwrap();
+        let keys: Vec<String> = iter.map(|(key, _, _)| key).collect();
+        assert_eq!(keys, expected);
+    }
 }
@@ -0,0 +1,334 @@
+//! Snapshots of the state of the storage at a committed block height, with
+//! which a new node can sync with the chain instead of replaying all of its
+//! blocks from the genesis.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::{
+    BlockMetadata, BlockStateRead, BlockStateWrite, DBIter, Error, MerkleTree,
+    PruningState, Result, Storage, StorageHasher, DB,
+};
+use crate::types::address::EstablishedAddressGen;
+use crate::types::hash::Hash;
+#[cfg(feature = "ferveo-tpke")]
+use crate::types::storage::TxQueue;
+use crate::types::storage::{
+    BlockHash, BlockHeight, BlockResults, Epoch, Epochs, Header, Key,
+};
+use crate::types::time::DateTimeUtc;
+
+/// The version of the encoding of the snapshots
+pub const SNAPSHOT_FORMAT: u32 = 1;
+
+/// The maximum size in bytes of a chunk of a snapshot
+pub const SNAPSHOT_CHUNK_SIZE: usize = 10 * 1024 * 1024;
+
+/// The state of the storage at a committed block height
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
+pub struct Snapshot {
+    /// Height of the block
+    pub height: BlockHeight,
+    /// Hash of the block
+    pub hash: BlockHash,
+    /// Header of the block
+    pub header: Option<Header>,
+    /// Epoch of the block
+    pub epoch: Epoch,
+    /// Predecessor block epochs
+    pub pred_epochs: Epochs,
+    /// Minimum block height at which the next epoch may start
+    pub next_epoch_min_start_height: BlockHeight,
+    /// Minimum block time at which the next epoch may start
+    pub next_epoch_min_start_time: DateTimeUtc,
+    /// Established address generator
+    pub address_gen: EstablishedAddressGen,
+    /// Results of applying transactions
+    pub results: BlockResults,
+    /// Wrapper txs to be decrypted in the next block proposal
+    #[cfg(feature = "ferveo-tpke")]
+    pub tx_queue: TxQueue,
+    /// The key-vals of the account subspaces, ordered by the storage keys
+    pub subspace: Vec<(Key, Vec<u8>)>,
+}
+
+/// The metadata of a snapshot, with which its chunks can be verified one by
+/// one as they're received
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct SnapshotMetadata {
+    /// The height of the snapshot's state
+    pub height: BlockHeight,
+    /// The hashes of the chunks, in order
+    pub chunk_hashes: Vec<Hash>,
+}
+
+impl Snapshot {
+    /// Encode the snapshot and split it into chunks of at most the given
+    /// size. Returns the chunks with their metadata.
+    pub fn to_chunks(
+        &self,
+        chunk_size: usize,
+    ) -> (Vec<Vec<u8>>, SnapshotMetadata) {
+        let bytes = self
+            .try_to_vec()
+            .expect("Encoding a snapshot shouldn't fail");
+        let chunks: Vec<Vec<u8>> =
+            bytes.chunks(chunk_size).map(<[u8]>::to_vec).collect();
+        let chunk_hashes = chunks.iter().map(Hash::sha256).collect();
+        let metadata = SnapshotMetadata {
+            height: self.height,
+            chunk_hashes,
+        };
+        (chunks, metadata)
+    }
+
+    /// Take a snapshot of the state at the last committed block height of the
+    /// DB. As the whole account subspace is read, this should be called on a
+    /// checkpoint of the DB (see [`DB::checkpoint`]) rather than on the DB
+    /// that the blocks are committed to.
+    pub fn from_db<D>(db: &mut D) -> Result<Self>
+    where
+        D: DB + for<'iter> DBIter<'iter>,
+    {
+        let BlockStateRead {
+            hash,
+            height,
+            epoch,
+            pred_epochs,
+            results,
+            next_epoch_min_start_height,
+            next_epoch_min_start_time,
+            address_gen,
+            #[cfg(feature = "ferveo-tpke")]
+            tx_queue,
+            ..
+        } = db.read_last_block()?.ok_or(Error::NoBlock {
+            height: BlockHeight::default(),
+        })?;
+        let subspace = db
+            .iter_prefix(&Key::default())
+            .map(|(key, value, _gas)| {
+                Ok((Key::parse(key).map_err(Error::KeyError)?, value))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Snapshot {
+            height,
+            hash,
+            header: db.read_block_header(height)?,
+            epoch,
+            pred_epochs,
+            next_epoch_min_start_height,
+            next_epoch_min_start_time,
+            address_gen,
+            results,
+            #[cfg(feature = "ferveo-tpke")]
+            tx_queue,
+            subspace,
+        })
+    }
+
+    /// Get the metadata of the snapshot's block, which is committed to in the
+    /// Merkle tree
+    pub fn block_metadata(&self) -> BlockMetadata<'_> {
+        BlockMetadata {
+            hash: &self.hash,
+            header: &self.header,
+            height: self.height,
+            epoch: self.epoch,
+            pred_epochs: &self.pred_epochs,
+            next_epoch_min_start_height: self.next_epoch_min_start_height,
+            next_epoch_min_start_time: self.next_epoch_min_start_time,
+            address_gen: &self.address_gen,
+            results: &self.results,
+            #[cfg(feature = "ferveo-tpke")]
+            tx_queue: &self.tx_queue,
+        }
+    }
+
+    /// Decode a snapshot from its chunks, in order
+    pub fn from_chunks(
+        chunks: impl IntoIterator<Item = Vec<u8>>,
+    ) -> std::io::Result<Self> {
+        let bytes: Vec<u8> = chunks.into_iter().flatten().collect();
+        Self::try_from_slice(&bytes)
+    }
+}
+
+impl SnapshotMetadata {
+    /// The hash of the snapshot, which commits to all of its chunks
+    pub fn hash(&self) -> Hash {
+        Hash::sha256(
+            self.try_to_vec()
+                .expect("Encoding a snapshot's metadata shouldn't fail"),
+        )
+    }
+
+    /// Check a chunk of the snapshot against its hash
+    pub fn verify_chunk(&self, index: usize, chunk: &[u8]) -> bool {
+        self.chunk_hashes.get(index) == Some(&Hash::sha256(chunk))
+    }
+}
+
+impl<D, H> Storage<D, H>
+where
+    D: DB + for<'iter> DBIter<'iter>,
+    H: StorageHasher,
+{
+    /// Restore the state from a snapshot into an empty storage. The state is
+    /// only written if the snapshot is at the given height and once the root
+    /// of its Merkle tree, rebuilt from the key-vals and the block metadata
+    /// of the snapshot, matches the given app hash of that height.
+    pub fn restore_snapshot(
+        &mut self,
+        snapshot: Snapshot,
+        height: BlockHeight,
+        app_hash: &[u8],
+    ) -> Result<()> {
+        if snapshot.height != height {
+            return Err(Error::SnapshotMismatch {
+                height: snapshot.height,
+            });
+        }
+        let mut tree = MerkleTree::<H>::default();
+        for (key, value) in &snapshot.subspace {
+            tree.update(key, value.as_slice())?;
+        }
+        tree.update_block_metadata(snapshot.block_metadata().hash())?;
+        if tree.root().0 != app_hash {
+            return Err(Error::SnapshotMismatch {
+                height: snapshot.height,
+            });
+        }
+
+        let mut batch = D::batch();
+        for (key, value) in &snapshot.subspace {
+            self.db.batch_write_subspace_val(
+                &mut batch,
+                snapshot.height,
+                key,
+                value,
+            )?;
+        }
+        self.db.exec_batch(batch)?;
+        self.db.write_block(BlockStateWrite {
+            merkle_tree_stores: tree.stores(),
+            header: snapshot.header.as_ref(),
+            hash: &snapshot.hash,
+            height: snapshot.height,
+            epoch: snapshot.epoch,
+            results: &snapshot.results,
+            pred_epochs: &snapshot.pred_epochs,
+            next_epoch_min_start_height: snapshot.next_epoch_min_start_height,
+            next_epoch_min_start_time: snapshot.next_epoch_min_start_time,
+            address_gen: &snapshot.address_gen,
+            #[cfg(feature = "ferveo-tpke")]
+            tx_queue: &snapshot.tx_queue,
+        })?;
+        // The state of the heights before the snapshot is not available
+        if let Some(pruned_height) = snapshot.height.0.checked_sub(1) {
+            self.db.write_pruning_state(&PruningState {
+                pruned_height: BlockHeight(pruned_height),
+                lowest_kept_height: None,
+            })?;
+        }
+        self.load_last_state()?;
+        self.load_conversion_state()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use masp_primitives::sapling::Node;
+
+    use super::*;
+    use crate::ledger::storage::testing::TestStorage;
+    use crate::ledger::storage::{types, ConversionState};
+    use crate::types::storage::KeySeg;
+    use crate::types::{address, token};
+
+    /// Test that a snapshot split into chunks can be verified and restored
+    /// into an empty storage, but only at its height and with the app hash of
+    /// its state and block metadata, and that the conversions kept in memory
+    /// are restored with it.
+    #[test]
+    fn test_snapshot_restore() {
+        let mut storage = TestStorage::default();
+        storage.block.height = BlockHeight(1);
+        let keys: Vec<Key> = (0..10_u8)
+            .map(|i| {
+                Key::from(address::testing::established_address_1().to_db_key())
+                    .push(&i.to_string())
+                    .unwrap()
+            })
+            .collect();
+        for (i, key) in keys.iter().enumerate() {
+            storage.write(key, vec![i as u8; 100]).unwrap();
+        }
+        let conversion_state = ConversionState {
+            prev_root: Node::new([1; 32]),
+            ..Default::default()
+        };
+        let conversion_state_key = Key::from(address::masp().to_db_key())
+            .push(&token::CONVERSION_KEY_PREFIX.to_owned())
+            .unwrap();
+        storage
+            .write(&conversion_state_key, types::encode(&conversion_state))
+            .unwrap();
+        storage.commit().unwrap();
+        let app_hash = storage.merkle_root().0;
+
+        let snapshot = Snapshot::from_db(&mut storage.db).unwrap();
+        assert_eq!(snapshot.subspace.len(), keys.len() + 1);
+        let (chunks, metadata) = snapshot.to_chunks(128);
+        assert!(chunks.len() > 1);
+        for (index, chunk) in chunks.iter().enumerate() {
+            assert!(metadata.verify_chunk(index, chunk));
+        }
+        assert!(!metadata.verify_chunk(0, &chunks[1]));
+        let snapshot = Snapshot::from_chunks(chunks).unwrap();
+
+        let height = BlockHeight(1);
+        let mut restored = TestStorage::default();
+        assert!(matches!(
+            restored.restore_snapshot(snapshot.clone(), height, &[0; 32]),
+            Err(Error::SnapshotMismatch { .. })
+        ));
+        assert!(matches!(
+            restored.restore_snapshot(
+                snapshot.clone(),
+                BlockHeight(2),
+                &app_hash
+            ),
+            Err(Error::SnapshotMismatch { .. })
+        ));
+        // the block metadata is verified too
+        let tampered = Snapshot {
+            epoch: snapshot.epoch.next(),
+            ..snapshot.clone()
+        };
+        assert!(matches!(
+            restored.restore_snapshot(tampered, height, &app_hash),
+            Err(Error::SnapshotMismatch { .. })
+        ));
+        let tampered = Snapshot {
+            next_epoch_min_start_height: BlockHeight(100),
+            ..snapshot.clone()
+        };
+        assert!(matches!(
+            restored.restore_snapshot(tampered, height, &app_hash),
+            Err(Error::SnapshotMismatch { .. })
+        ));
+        restored
+            .restore_snapshot(snapshot, height, &app_hash)
+            .unwrap();
+        assert_eq!(restored.last_height, BlockHeight(1));
+        assert_eq!(restored.merkle_root().0, app_hash);
+        for (i, key) in keys.iter().enumerate() {
+            let (value, _gas) = restored.read(key).unwrap();
+            assert_eq!(value, Some(vec![i as u8; 100]));
+        }
+        assert_eq!(
+            restored.conversion_state.prev_root,
+            conversion_state.prev_root
+        );
+    }
+}
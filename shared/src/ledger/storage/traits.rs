@@ -6,7 +6,9 @@ use std::fmt;
 use arse_merkle_tree::traits::{Hasher, Value};
 use arse_merkle_tree::{Key as TreeKey, H256};
 use ics23::commitment_proof::Proof as Ics23Proof;
-use ics23::{CommitmentProof, ExistenceProof};
+use ics23::{
+    batch_entry, BatchEntry, BatchProof, CommitmentProof, ExistenceProof,
+};
 use sha2::{Digest, Sha256};
 
 use super::merkle_tree::{Amt, Error, Smt};
@@ -21,7 +23,8 @@ use crate::types::storage::{
 pub trait SubTreeRead {
     /// Check if a key is present in the sub-tree
     fn subtree_has_key(&self, key: &Key) -> Result<bool, Error>;
-    /// Get a membership proof for various key-value pairs
+    /// Get a membership proof for various key-value pairs. The proof of
+    /// several key-value pairs is a batch of their existence proofs.
     fn subtree_membership_proof(
         &self,
         keys: &[Key],
@@ -53,28 +56,33 @@ impl<'a, H: StorageHasher + Default> SubTreeRead for &'a Smt<H> {
     fn subtree_membership_proof(
         &self,
         keys: &[Key],
-        mut values: Vec<MerkleValue>,
+        values: Vec<MerkleValue>,
     ) -> Result<MembershipProof, Error> {
-        if keys.len() != 1 || values.len() != 1 {
-            return Err(Error::Ics23MultiLeaf);
-        }
-        let key: &Key = &keys[0];
-        let MerkleValue::Bytes(value) = values.remove(0);
-        let cp = self.membership_proof(&H::hash(key.to_string()).into())?;
-        // Replace the values and the leaf op for the verification
-        match cp.proof.expect("The proof should exist") {
-            Ics23Proof::Exist(ep) => Ok(CommitmentProof {
-                proof: Some(Ics23Proof::Exist(ExistenceProof {
-                    key: key.to_string().as_bytes().to_vec(),
-                    value,
-                    leaf: Some(ics23_specs::leaf_spec::<H>()),
-                    ..ep
-                })),
-            }
-            .into()),
-            // the proof should have an ExistenceProof
-            _ => unreachable!(),
+        if keys.is_empty() || keys.len() != values.len() {
+            return Err(Error::InvalidMerkleKey(
+                "A membership proof needs a value for every key".into(),
+            ));
         }
+        let proofs = keys
+            .iter()
+            .zip(values)
+            .map(|(key, MerkleValue::Bytes(value))| {
+                let cp =
+                    self.membership_proof(&H::hash(key.to_string()).into())?;
+                // Replace the values and the leaf op for the verification
+                match cp.proof.expect("The proof should exist") {
+                    Ics23Proof::Exist(ep) => Ok(ExistenceProof {
+                        key: key.to_string().as_bytes().to_vec(),
+                        value,
+                        leaf: Some(ics23_specs::leaf_spec::<H>()),
+                        ..ep
+                    }),
+                    // the proof should have an ExistenceProof
+                    _ => unreachable!(),
+                }
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(ics23_membership_proof(proofs))
     }
 }
 
@@ -114,24 +122,29 @@ impl<'a, H: StorageHasher + Default> SubTreeRead for &'a Amt<H> {
         keys: &[Key],
         _: Vec<MerkleValue>,
     ) -> Result<MembershipProof, Error> {
-        if keys.len() != 1 {
-            return Err(Error::Ics23MultiLeaf);
-        }
-
-        let key = StringKey::try_from_bytes(keys[0].to_string().as_bytes())?;
-        let cp = self.membership_proof(&key)?;
-        // Replace the values and the leaf op for the verification
-        match cp.proof.expect("The proof should exist") {
-            Ics23Proof::Exist(ep) => Ok(CommitmentProof {
-                proof: Some(Ics23Proof::Exist(ExistenceProof {
-                    leaf: Some(ics23_specs::ibc_leaf_spec::<H>()),
-                    ..ep
-                })),
-            }
-            .into()),
-            // the proof should have an ExistenceProof
-            _ => unreachable!(),
+        if keys.is_empty() {
+            return Err(Error::InvalidMerkleKey(
+                "No keys provided for membership proof.".into(),
+            ));
         }
+        let proofs = keys
+            .iter()
+            .map(|key| {
+                let key =
+                    StringKey::try_from_bytes(key.to_string().as_bytes())?;
+                let cp = self.membership_proof(&key)?;
+                // Replace the values and the leaf op for the verification
+                match cp.proof.expect("The proof should exist") {
+                    Ics23Proof::Exist(ep) => Ok(ExistenceProof {
+                        leaf: Some(ics23_specs::ibc_leaf_spec::<H>()),
+                        ..ep
+                    }),
+                    // the proof should have an ExistenceProof
+                    _ => unreachable!(),
+                }
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(ics23_membership_proof(proofs))
     }
 }
 
@@ -159,6 +172,24 @@ impl<'a, H: StorageHasher + Default> SubTreeWrite for &'a mut Amt<H> {
     }
 }
 
+/// Get the membership proof of the given existence proofs, batched if there
+/// are several
+fn ics23_membership_proof(mut proofs: Vec<ExistenceProof>) -> MembershipProof {
+    let proof = if proofs.len() == 1 {
+        Ics23Proof::Exist(proofs.remove(0))
+    } else {
+        Ics23Proof::Batch(BatchProof {
+            entries: proofs
+                .into_iter()
+                .map(|ep| BatchEntry {
+                    proof: Some(batch_entry::Proof::Exist(ep)),
+                })
+                .collect(),
+        })
+    };
+    CommitmentProof { proof: Some(proof) }.into()
+}
+
 impl TreeKey<IBC_KEY_LIMIT> for StringKey {
     type Error = Error;
 
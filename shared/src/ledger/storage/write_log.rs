@@ -6,10 +6,11 @@ use std::collections::{BTreeSet, HashMap, HashSet};
 use thiserror::Error;
 
 use crate::ledger;
-use crate::ledger::storage::{Storage, StorageHasher};
+use crate::ledger::storage::{DBIter, Storage, StorageHasher, DB};
+use crate::ledger::storage_api::{self, ResultExt, StorageRead, StorageWrite};
 use crate::types::address::{Address, EstablishedAddressGen};
 use crate::types::ibc::IbcEvent;
-use crate::types::storage;
+use crate::types::storage::{self, BlockHash, BlockHeight, Epoch, TxIndex};
 
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
@@ -426,6 +427,148 @@ impl WriteLog {
     }
 }
 
+/// The storage with the modifications of the write log on top of it. The
+/// protocol can write through it to make changes that are committed or
+/// dropped together with the current transaction's write log.
+pub struct WriteLogStorage<'a, D, H>
+where
+    D: DB + for<'iter> DBIter<'iter>,
+    H: StorageHasher,
+{
+    /// The write log in which the changes are written
+    pub write_log: &'a mut WriteLog,
+    /// The storage read when a key is not in the write log
+    pub storage: &'a Storage<D, H>,
+}
+
+impl<'a, D, H> WriteLogStorage<'a, D, H>
+where
+    D: DB + for<'iter> DBIter<'iter>,
+    H: StorageHasher,
+{
+    /// Combine the write log and the storage
+    pub fn new(
+        write_log: &'a mut WriteLog,
+        storage: &'a Storage<D, H>,
+    ) -> Self {
+        Self { write_log, storage }
+    }
+}
+
+impl<'iter, 'a, D, H> StorageRead<'iter> for WriteLogStorage<'a, D, H>
+where
+    D: DB + for<'iter_> DBIter<'iter_>,
+    H: StorageHasher,
+{
+    type PrefixIter = <D as DBIter<'iter>>::PrefixIter;
+
+    fn read_bytes(
+        &self,
+        key: &storage::Key,
+    ) -> storage_api::Result<Option<Vec<u8>>> {
+        match self.write_log.read(key).0 {
+            Some(StorageModification::Write { value })
+            | Some(StorageModification::Temp { value }) => {
+                Ok(Some(value.clone()))
+            }
+            Some(StorageModification::InitAccount { vp }) => {
+                Ok(Some(vp.clone()))
+            }
+            Some(StorageModification::Delete) => Ok(None),
+            None => StorageRead::read_bytes(self.storage, key),
+        }
+    }
+
+    fn has_key(&self, key: &storage::Key) -> storage_api::Result<bool> {
+        match self.write_log.read(key).0 {
+            Some(StorageModification::Delete) => Ok(false),
+            Some(_) => Ok(true),
+            None => StorageRead::has_key(self.storage, key),
+        }
+    }
+
+    fn iter_prefix(
+        &'iter self,
+        prefix: &storage::Key,
+    ) -> storage_api::Result<Self::PrefixIter> {
+        Ok(self.storage.db.iter_prefix(prefix))
+    }
+
+    fn rev_iter_prefix(
+        &'iter self,
+        prefix: &storage::Key,
+    ) -> storage_api::Result<Self::PrefixIter> {
+        Ok(self.storage.db.rev_iter_prefix(prefix))
+    }
+
+    /// Like the iterators of the txs, this only iterates the keys that are
+    /// in the storage, with the values modified in the write log
+    fn iter_next(
+        &self,
+        iter: &mut Self::PrefixIter,
+    ) -> storage_api::Result<Option<(String, Vec<u8>)>> {
+        for (key, val, _gas) in iter {
+            let parsed_key = storage::Key::parse(&key).into_storage_result()?;
+            match self.write_log.read(&parsed_key).0 {
+                Some(StorageModification::Write { value })
+                | Some(StorageModification::Temp { value }) => {
+                    return Ok(Some((key, value.clone())));
+                }
+                Some(StorageModification::Delete)
+                | Some(StorageModification::InitAccount { .. }) => continue,
+                None => return Ok(Some((key, val))),
+            }
+        }
+        Ok(None)
+    }
+
+    fn get_chain_id(&self) -> storage_api::Result<String> {
+        StorageRead::get_chain_id(self.storage)
+    }
+
+    fn get_block_height(&self) -> storage_api::Result<BlockHeight> {
+        StorageRead::get_block_height(self.storage)
+    }
+
+    fn get_block_hash(&self) -> storage_api::Result<BlockHash> {
+        StorageRead::get_block_hash(self.storage)
+    }
+
+    fn get_block_epoch(&self) -> storage_api::Result<Epoch> {
+        StorageRead::get_block_epoch(self.storage)
+    }
+
+    fn get_tx_index(&self) -> storage_api::Result<TxIndex> {
+        StorageRead::get_tx_index(self.storage)
+    }
+
+    fn get_native_token(&self) -> storage_api::Result<Address> {
+        StorageRead::get_native_token(self.storage)
+    }
+}
+
+impl<'a, D, H> StorageWrite for WriteLogStorage<'a, D, H>
+where
+    D: DB + for<'iter> DBIter<'iter>,
+    H: StorageHasher,
+{
+    fn write_bytes(
+        &mut self,
+        key: &storage::Key,
+        val: impl AsRef<[u8]>,
+    ) -> storage_api::Result<()> {
+        self.write_log
+            .write(key, val.as_ref().to_vec())
+            .into_storage_result()?;
+        Ok(())
+    }
+
+    fn delete(&mut self, key: &storage::Key) -> storage_api::Result<()> {
+        self.write_log.delete(key).into_storage_result()?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
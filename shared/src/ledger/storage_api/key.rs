@@ -14,6 +14,35 @@ where
     storage.read(&key)
 }
 
+/// Get the public keys and threshold of a multisignature account. Returns
+/// `Ok(None)` if the account is not a multisignature account.
+pub fn get_multisig<S>(storage: &S, owner: &Address) -> Result<Option<Multisig>>
+where
+    S: for<'iter> StorageRead<'iter>,
+{
+    let key = multisig_key(owner);
+    storage.read(&key)
+}
+
+/// Replace the PK of an established account. The key of an implicit account
+/// cannot be updated, as its address is derived from it.
+pub fn update_pk<S>(
+    storage: &mut S,
+    owner: &Address,
+    pk: &common::PublicKey,
+) -> Result<()>
+where
+    S: StorageWrite,
+{
+    if let Address::Implicit(_) = owner {
+        return Err(Error::new_const(
+            "The public key of an implicit account cannot be updated",
+        ));
+    }
+    let key = pk_key(owner);
+    storage.write(&key, pk)
+}
+
 /// Reveal a PK of an implicit account - the PK is written into the storage
 /// of the address derived from the PK.
 pub fn reveal_pk<S>(storage: &mut S, pk: &common::PublicKey) -> Result<()>
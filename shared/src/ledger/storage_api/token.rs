@@ -0,0 +1,33 @@
+//! Token storage API
+
+use super::*;
+use crate::types::address::Address;
+use crate::types::token;
+
+/// Read the total supply of the given token. Returns zero if none of it has
+/// been minted.
+pub fn read_total_supply<S>(
+    storage: &S,
+    token: &Address,
+) -> Result<token::Amount>
+where
+    S: for<'iter> StorageRead<'iter>,
+{
+    let key = token::total_supply_key(token);
+    Ok(storage.read(&key)?.unwrap_or_default())
+}
+
+/// Add the given minted amount to the total supply of the token. This must be
+/// called by the protocol whenever it credits new tokens to an account.
+pub fn increase_total_supply<S>(
+    storage: &mut S,
+    token: &Address,
+    amount: token::Amount,
+) -> Result<()>
+where
+    S: StorageWrite + for<'iter> StorageRead<'iter>,
+{
+    let supply = read_total_supply(storage, token)?;
+    let key = token::total_supply_key(token);
+    storage.write(&key, supply + amount)
+}
@@ -0,0 +1,91 @@
+//! Token metadata VP. The metadata of the tokens is stored under the internal
+//! token metadata address, so that this VP is triggered by any change of it,
+//! whichever VP the token account runs. It can only be written when the token
+//! account is initialized, or at genesis.
+
+use std::collections::BTreeSet;
+
+use thiserror::Error;
+
+use super::storage_api::StorageRead;
+use crate::ledger::native_vp::{self, Ctx, NativeVp};
+use crate::ledger::storage::{self as ledger_storage, StorageHasher};
+use crate::types::address::{Address, InternalAddress};
+use crate::types::storage::{DbKeySeg, Key};
+use crate::types::token;
+use crate::vm::WasmCacheAccess;
+
+/// Internal token metadata address
+pub const ADDRESS: Address = token::METADATA_ADDRESS;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Native VP error: {0}")]
+    NativeVpError(#[from] native_vp::Error),
+}
+
+/// Token metadata functions result
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Token metadata VP
+pub struct TokenMetadataVp<'a, DB, H, CA>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    /// Context to interact with the host structures.
+    pub ctx: Ctx<'a, DB, H, CA>,
+}
+
+impl<'a, DB, H, CA> NativeVp for TokenMetadataVp<'a, DB, H, CA>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    type Error = Error;
+
+    const ADDR: InternalAddress = InternalAddress::TokenMetadata;
+
+    fn validate_tx(
+        &self,
+        _tx_data: &[u8],
+        keys_changed: &BTreeSet<Key>,
+        _verifiers: &BTreeSet<Address>,
+    ) -> Result<bool> {
+        let initialized_accounts =
+            self.ctx.write_log.get_initialized_accounts();
+        for key in keys_changed.iter().filter(|key| is_token_metadata_key(key))
+        {
+            // The metadata can only be written by the tx that initializes the
+            // token account, and it must be valid
+            let accepted = match token::is_metadata_key(key) {
+                Some(token) if initialized_accounts.contains(token) => self
+                    .ctx
+                    .post()
+                    .read::<token::Metadata>(key)?
+                    .map(|metadata| metadata.is_valid())
+                    .unwrap_or(false),
+                _ => false,
+            };
+            if !accepted {
+                tracing::info!(
+                    "The token metadata key {} cannot be changed",
+                    key
+                );
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Check if a key is in the storage sub-space of the token metadata address
+fn is_token_metadata_key(key: &Key) -> bool {
+    matches!(
+        key.segments.first(),
+        Some(DbKeySeg::AddressSeg(addr)) if addr == &ADDRESS
+    )
+}
@@ -12,6 +12,7 @@ mod tests {
     use prost::Message;
 
     use super::*;
+    use crate::types::chain::ChainId;
 
     #[test]
     fn encoding_round_trip() {
@@ -19,6 +20,8 @@ mod tests {
             code: "wasm code".as_bytes().to_owned(),
             data: Some("arbitrary data".as_bytes().to_owned()),
             timestamp: Some(std::time::SystemTime::now().into()),
+            chain_id: ChainId::default().as_str().to_owned(),
+            expiration: None,
         };
         let mut tx_bytes = vec![];
         tx.encode(&mut tx_bytes).unwrap();
@@ -1,5 +1,7 @@
+use std::collections::BTreeMap;
 use std::convert::{TryFrom, TryInto};
 use std::hash::{Hash, Hasher};
+use std::str::FromStr;
 
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use prost::Message;
@@ -12,6 +14,7 @@ use crate::tendermint_proto::abci::Event;
 #[cfg(feature = "ferveo-tpke")]
 use crate::tendermint_proto::abci::EventAttribute;
 use crate::tendermint_proto::abci::ResponseDeliverTx;
+use crate::types::chain::{ChainId, ChainIdParseError};
 use crate::types::key::*;
 use crate::types::time::DateTimeUtc;
 #[cfg(feature = "ferveo-tpke")]
@@ -36,6 +39,8 @@ pub enum Error {
     NoTimestampError,
     #[error("Timestamp is invalid: {0}")]
     InvalidTimestamp(prost_types::TimestampOutOfSystemRangeError),
+    #[error("Chain ID is invalid: {0}")]
+    InvalidChainId(ChainIdParseError),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -47,18 +52,57 @@ pub type Result<T> = std::result::Result<T, Error>;
 /// Because the signature is not checked by the ledger, we don't inline it into
 /// the `Tx` type directly. Instead, the signature is attached to the `tx.data`,
 /// which can then be checked by a validity predicate wasm.
-#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema)]
+///
+/// A transaction has a single valid encoding, as its hash is used for replay
+/// protection: the multisignatures are always encoded and they're only
+/// decoded in the strictly ascending order of their indices.
+#[derive(Clone, Debug, BorshSerialize, BorshSchema)]
 pub struct SignedTxData {
     /// The original tx data bytes, if any
     pub data: Option<Vec<u8>>,
     /// The signature is produced on the tx data concatenated with the tx code
     /// and the timestamp.
     pub sig: common::Signature,
+    /// The signatures of the keys of a multisignature account, indexed by
+    /// the position of their key in the account's [`Multisig`] public keys.
+    /// They are produced on the same data as `sig`, so that they can be
+    /// collected from their signers independently. Empty for transactions
+    /// that are not authorized by a multisignature account.
+    pub sigs: BTreeMap<u8, common::Signature>,
+}
+
+impl BorshDeserialize for SignedTxData {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let data = BorshDeserialize::deserialize(buf)?;
+        let sig = BorshDeserialize::deserialize(buf)?;
+        // A map is encoded as the sequence of its entries, which is only
+        // canonical in the order of the map
+        let sigs: Vec<(u8, common::Signature)> =
+            BorshDeserialize::deserialize(buf)?;
+        if sigs.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "The multisignatures must be in the strictly ascending order \
+                 of their indices",
+            ));
+        }
+        Ok(Self {
+            data,
+            sig,
+            sigs: sigs.into_iter().collect(),
+        })
+    }
 }
 
 /// A generic signed data wrapper for Borsh encode-able data.
 #[derive(
-    Clone, Debug, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
+    Clone,
+    Debug,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
 )]
 pub struct Signed<T: BorshSerialize + BorshDeserialize> {
     /// Arbitrary data to be signed
@@ -134,6 +178,12 @@ pub struct Tx {
     pub code: Vec<u8>,
     pub data: Option<Vec<u8>>,
     pub timestamp: DateTimeUtc,
+    /// The ID of the chain on which the tx may be applied. The chain ID is
+    /// signed together with the rest of the tx to prevent replaying it on
+    /// another chain.
+    pub chain_id: ChainId,
+    /// Optional time after which the tx may no longer be applied
+    pub expiration: Option<DateTimeUtc>,
 }
 
 impl TryFrom<&[u8]> for Tx {
@@ -145,10 +195,18 @@ impl TryFrom<&[u8]> for Tx {
             Some(t) => t.try_into().map_err(Error::InvalidTimestamp)?,
             None => return Err(Error::NoTimestampError),
         };
+        let chain_id =
+            ChainId::from_str(&tx.chain_id).map_err(Error::InvalidChainId)?;
+        let expiration = match tx.expiration {
+            Some(e) => Some(e.try_into().map_err(Error::InvalidTimestamp)?),
+            None => None,
+        };
         Ok(Tx {
             code: tx.code,
             data: tx.data,
             timestamp,
+            chain_id,
+            expiration,
         })
     }
 }
@@ -156,10 +214,13 @@ impl TryFrom<&[u8]> for Tx {
 impl From<Tx> for types::Tx {
     fn from(tx: Tx) -> Self {
         let timestamp = Some(tx.timestamp.into());
+        let expiration = tx.expiration.map(|e| e.into());
         types::Tx {
             code: tx.code,
             data: tx.data,
             timestamp,
+            chain_id: tx.chain_id.as_str().to_owned(),
+            expiration,
         }
     }
 }
@@ -244,11 +305,18 @@ impl From<Tx> for ResponseDeliverTx {
 }
 
 impl Tx {
-    pub fn new(code: Vec<u8>, data: Option<Vec<u8>>) -> Self {
+    pub fn new(
+        code: Vec<u8>,
+        data: Option<Vec<u8>>,
+        chain_id: ChainId,
+        expiration: Option<DateTimeUtc>,
+    ) -> Self {
         Tx {
             code,
             data,
             timestamp: DateTimeUtc::now(),
+            chain_id,
+            expiration,
         }
     }
 
@@ -275,6 +343,7 @@ impl Tx {
         let signed = SignedTxData {
             data: self.data,
             sig,
+            sigs: BTreeMap::new(),
         }
         .try_to_vec()
         .expect("Encoding transaction data shouldn't fail");
@@ -282,9 +351,34 @@ impl Tx {
             code: self.code,
             data: Some(signed),
             timestamp: self.timestamp,
+            chain_id: self.chain_id,
+            expiration: self.expiration,
         }
     }
 
+    /// Add the signature of a key of a multisignature account to a
+    /// transaction signed with [`Tx::sign`]. The index is the position of the
+    /// key in the account's [`Multisig`] public keys.
+    pub fn sign_multisig(
+        self,
+        keypair: &common::SecretKey,
+        index: u8,
+    ) -> std::result::Result<Self, VerifySigError> {
+        let tx_data = self.data.clone().ok_or(VerifySigError::MissingData)?;
+        let mut signed_tx_data = SignedTxData::try_from_slice(&tx_data[..])
+            .map_err(VerifySigError::DataEncodingError)?;
+        let to_sign = self.signed_hash(signed_tx_data.data.clone());
+        let sig = common::SigScheme::sign(keypair, to_sign);
+        signed_tx_data.sigs.insert(index, sig);
+        let data = signed_tx_data
+            .try_to_vec()
+            .expect("Encoding transaction data shouldn't fail");
+        Ok(Tx {
+            data: Some(data),
+            ..self
+        })
+    }
+
     /// Verify that the transaction has been signed by the secret key
     /// counterpart of the given public key.
     pub fn verify_sig(
@@ -296,14 +390,52 @@ impl Tx {
         let tx_data = self.data.clone().ok_or(VerifySigError::MissingData)?;
         let signed_tx_data = SignedTxData::try_from_slice(&tx_data[..])
             .expect("Decoding transaction data shouldn't fail");
-        let data = signed_tx_data.data;
-        let tx = Tx {
+        let signed_data = self.signed_hash(signed_tx_data.data);
+        common::SigScheme::verify_signature_raw(pk, &signed_data, sig)
+    }
+
+    /// Verify that the transaction has been signed by at least the threshold
+    /// of the keys of a multisignature account. Returns the number of valid
+    /// signatures.
+    pub fn verify_multisig(
+        &self,
+        multisig: &Multisig,
+    ) -> std::result::Result<usize, VerifySigError> {
+        let tx_data = self.data.clone().ok_or(VerifySigError::MissingData)?;
+        let signed_tx_data = SignedTxData::try_from_slice(&tx_data[..])
+            .map_err(VerifySigError::DataEncodingError)?;
+        let signed_data = self.signed_hash(signed_tx_data.data);
+        let valid =
+            multisig.verify_threshold(&signed_tx_data.sigs, |pk, sig| {
+                Ok::<_, VerifySigError>(
+                    common::SigScheme::verify_signature_raw(
+                        pk,
+                        &signed_data,
+                        sig,
+                    )
+                    .is_ok(),
+                )
+            })?;
+        valid.ok_or_else(|| {
+            VerifySigError::SigVerifyError(format!(
+                "Fewer than the required {} signatures of the multisignature \
+                 account are valid",
+                multisig.threshold
+            ))
+        })
+    }
+
+    /// The hash of the transaction with the given original data, on which
+    /// its signatures are produced
+    fn signed_hash(&self, data: Option<Vec<u8>>) -> [u8; 32] {
+        Tx {
             code: self.code.clone(),
             data,
             timestamp: self.timestamp,
-        };
-        let signed_data = tx.hash();
-        common::SigScheme::verify_signature_raw(pk, &signed_data, sig)
+            chain_id: self.chain_id.clone(),
+            expiration: self.expiration,
+        }
+        .hash()
     }
 }
 
@@ -389,7 +521,12 @@ mod tests {
     fn test_tx() {
         let code = "wasm code".as_bytes().to_owned();
         let data = "arbitrary data".as_bytes().to_owned();
-        let tx = Tx::new(code.clone(), Some(data.clone()));
+        let tx = Tx::new(
+            code.clone(),
+            Some(data.clone()),
+            ChainId::default(),
+            Some(DateTimeUtc::now()),
+        );
 
         let bytes = tx.to_bytes();
         let tx_from_bytes =
@@ -397,9 +534,11 @@ mod tests {
         assert_eq!(tx_from_bytes, tx);
 
         let types_tx = types::Tx {
-            code,
-            data: Some(data),
+            code: code.clone(),
+            data: Some(data.clone()),
             timestamp: None,
+            chain_id: ChainId::default().as_str().to_owned(),
+            expiration: None,
         };
         let mut bytes = vec![];
         types_tx.encode(&mut bytes).expect("encoding failed");
@@ -407,6 +546,95 @@ mod tests {
             Err(Error::NoTimestampError) => {}
             _ => panic!("unexpected result"),
         }
+
+        let types_tx = types::Tx {
+            code,
+            data: Some(data),
+            timestamp: Some(std::time::SystemTime::now().into()),
+            chain_id: "invalid chain ID".to_owned(),
+            expiration: None,
+        };
+        let mut bytes = vec![];
+        types_tx.encode(&mut bytes).expect("encoding failed");
+        match Tx::try_from(bytes.as_ref()) {
+            Err(Error::InvalidChainId(_)) => {}
+            _ => panic!("unexpected result"),
+        }
+    }
+
+    /// Test that the signatures of a multisignature account are only valid
+    /// at the index of their key and only once the threshold is reached
+    #[test]
+    fn test_tx_multisig() {
+        let keypair_1 = testing::keypair_1();
+        let keypair_2 = testing::keypair_2();
+        let multisig = Multisig {
+            public_keys: vec![keypair_1.ref_to(), keypair_2.ref_to()],
+            threshold: 2,
+        };
+        let tx = Tx::new(
+            "wasm code".as_bytes().to_owned(),
+            Some("arbitrary data".as_bytes().to_owned()),
+            ChainId::default(),
+            None,
+        );
+        assert!(matches!(
+            tx.clone().sign_multisig(&keypair_1, 0),
+            Err(VerifySigError::DataEncodingError(_))
+        ));
+
+        let signed = tx.sign(&keypair_1).sign_multisig(&keypair_1, 0).unwrap();
+        assert!(signed.verify_multisig(&multisig).is_err());
+        let swapped = signed.clone().sign_multisig(&keypair_1, 1).unwrap();
+        assert!(swapped.verify_multisig(&multisig).is_err());
+        let signed = signed.sign_multisig(&keypair_2, 1).unwrap();
+        assert_eq!(signed.verify_multisig(&multisig).unwrap(), 2);
+
+        let unreachable = Multisig {
+            threshold: 3,
+            ..multisig
+        };
+        assert!(!unreachable.is_valid());
+        assert!(signed.verify_multisig(&unreachable).is_err());
+    }
+
+    /// Test that the tx data has a single valid encoding, with or without
+    /// multisignatures
+    #[test]
+    fn test_signed_tx_data_encoding() {
+        let keypair = testing::keypair_1();
+        let sig = common::SigScheme::sign(&keypair, [0_u8; 32]);
+        let data = Some("arbitrary data".as_bytes().to_owned());
+        let mut signed_tx_data = SignedTxData {
+            data: data.clone(),
+            sig: sig.clone(),
+            sigs: BTreeMap::new(),
+        };
+        let bytes = signed_tx_data.try_to_vec().unwrap();
+        let decoded = SignedTxData::try_from_slice(&bytes).unwrap();
+        assert!(decoded.sigs.is_empty());
+        // the multisignatures must be encoded, even if there are none
+        let without_sigs = (data.clone(), sig.clone()).try_to_vec().unwrap();
+        assert!(SignedTxData::try_from_slice(&without_sigs).is_err());
+        // no trailing bytes are allowed
+        let trailing = [bytes, vec![0]].concat();
+        assert!(SignedTxData::try_from_slice(&trailing).is_err());
+
+        signed_tx_data.sigs.insert(1, sig.clone());
+        signed_tx_data.sigs.insert(2, sig.clone());
+        let bytes = signed_tx_data.try_to_vec().unwrap();
+        let decoded = SignedTxData::try_from_slice(&bytes).unwrap();
+        assert_eq!(decoded.sigs, signed_tx_data.sigs);
+        assert_eq!(decoded.try_to_vec().unwrap(), bytes);
+        // the multisignatures must be in the ascending order of their indices
+        // and the indices must not be repeated
+        for sigs in [
+            vec![(2_u8, sig.clone()), (1, sig.clone())],
+            vec![(1_u8, sig.clone()), (1, sig.clone())],
+        ] {
+            let bytes = (data.clone(), sig.clone(), sigs).try_to_vec().unwrap();
+            assert!(SignedTxData::try_from_slice(&bytes).is_err());
+        }
     }
 
     #[test]
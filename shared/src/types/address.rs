@@ -69,6 +69,10 @@ mod internal {
         "ibc::IBC Mint Address                        ";
     pub const ETH_BRIDGE: &str =
         "ano::ETH Bridge Address                      ";
+    pub const REPLAY_PROTECTION: &str =
+        "ano::Replay Protection                       ";
+    pub const TOKEN_METADATA: &str =
+        "ano::Token Metadata                          ";
 }
 
 /// Fixed-length address strings prefix for established addresses.
@@ -198,6 +202,12 @@ impl Address {
                     InternalAddress::EthBridge => {
                         internal::ETH_BRIDGE.to_string()
                     }
+                    InternalAddress::ReplayProtection => {
+                        internal::REPLAY_PROTECTION.to_string()
+                    }
+                    InternalAddress::TokenMetadata => {
+                        internal::TOKEN_METADATA.to_string()
+                    }
                 };
                 debug_assert_eq!(string.len(), FIXED_LEN_STRING_BYTES);
                 string
@@ -251,6 +261,12 @@ impl Address {
                 internal::ETH_BRIDGE => {
                     Ok(Address::Internal(InternalAddress::EthBridge))
                 }
+                internal::REPLAY_PROTECTION => {
+                    Ok(Address::Internal(InternalAddress::ReplayProtection))
+                }
+                internal::TOKEN_METADATA => {
+                    Ok(Address::Internal(InternalAddress::TokenMetadata))
+                }
                 _ => Err(Error::new(
                     ErrorKind::InvalidData,
                     "Invalid internal address",
@@ -466,6 +482,10 @@ pub enum InternalAddress {
     SlashFund,
     /// Bridge to Ethereum
     EthBridge,
+    /// Replay protection contains the hashes of the applied txs
+    ReplayProtection,
+    /// Token metadata contains the metadata of the token accounts
+    TokenMetadata,
 }
 
 impl InternalAddress {
@@ -500,6 +520,8 @@ impl Display for InternalAddress {
                 Self::IbcBurn => "IbcBurn".to_string(),
                 Self::IbcMint => "IbcMint".to_string(),
                 Self::EthBridge => "EthBridge".to_string(),
+                Self::ReplayProtection => "ReplayProtection".to_string(),
+                Self::TokenMetadata => "TokenMetadata".to_string(),
             }
         )
     }
@@ -771,8 +793,10 @@ pub mod testing {
             InternalAddress::IbcEscrow => {}
             InternalAddress::IbcBurn => {}
             InternalAddress::IbcMint => {}
-            InternalAddress::EthBridge => {} /* Add new addresses in the
-                                              * `prop_oneof` below. */
+            InternalAddress::EthBridge => {}
+            InternalAddress::ReplayProtection => {}
+            InternalAddress::TokenMetadata => {} /* Add new addresses in the
+                                                  * `prop_oneof` below. */
         };
         prop_oneof![
             Just(InternalAddress::PoS),
@@ -787,6 +811,8 @@ pub mod testing {
             Just(InternalAddress::Governance),
             Just(InternalAddress::SlashFund),
             Just(InternalAddress::EthBridge),
+            Just(InternalAddress::ReplayProtection),
+            Just(InternalAddress::TokenMetadata),
         ]
     }
 
@@ -0,0 +1,203 @@
+//! Types representing the events emitted by the Ethereum bridge smart
+//! contracts and the votes of the validators on them.
+
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use thiserror::Error;
+
+use crate::proto::Signed;
+use crate::types::address::Address;
+use crate::types::hash::Hash;
+use crate::types::storage::BlockHeight;
+use crate::types::token;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum ParseEthAddressError {
+    #[error("An Ethereum address must start with 0x")]
+    MissingPrefix,
+    #[error("Invalid hex encoding of an Ethereum address: {0}")]
+    InvalidHex(hex::FromHexError),
+    #[error("An Ethereum address must have 20 bytes, got {0}")]
+    InvalidLength(usize),
+}
+
+/// An Ethereum address
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+)]
+pub struct EthAddress(pub [u8; 20]);
+
+impl Display for EthAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+impl FromStr for EthAddress {
+    type Err = ParseEthAddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s
+            .strip_prefix("0x")
+            .ok_or(ParseEthAddressError::MissingPrefix)?;
+        let bytes =
+            hex::decode(hex).map_err(ParseEthAddressError::InvalidHex)?;
+        let len = bytes.len();
+        let bytes: [u8; 20] = bytes
+            .try_into()
+            .map_err(|_| ParseEthAddressError::InvalidLength(len))?;
+        Ok(Self(bytes))
+    }
+}
+
+/// A transfer of an ERC20 token from Ethereum to Namada
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+)]
+pub struct TransferToNamada {
+    /// The address of the ERC20 token contract
+    pub asset: EthAddress,
+    /// The amount of tokens transferred
+    pub amount: token::Amount,
+    /// The Namada address receiving the wrapped tokens
+    pub receiver: Address,
+}
+
+/// An event emitted by the Ethereum bridge smart contracts
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+)]
+pub enum EthereumEvent {
+    /// A batch of transfers from Ethereum to Namada
+    TransfersToNamada {
+        /// The nonce of the batch, unique to every batch
+        nonce: u64,
+        /// The transfers in the batch
+        transfers: Vec<TransferToNamada>,
+    },
+    /// An update of the validator sets of the bridge contracts
+    ValidatorSetUpdate {
+        /// The nonce of the update, unique to every update
+        nonce: u64,
+        /// The hash of the new set of validators that can relay transfers
+        bridge_validator_hash: Hash,
+        /// The hash of the new set of validators that can upgrade the
+        /// bridge contracts
+        governance_validator_hash: Hash,
+    },
+}
+
+impl EthereumEvent {
+    /// Get the hash identifying this event
+    pub fn hash(&self) -> Hash {
+        Hash::sha256(
+            self.try_to_vec()
+                .expect("Serializing an Ethereum event shouldn't fail"),
+        )
+    }
+}
+
+/// The Ethereum events seen by a validator, which it votes on in its vote
+/// extension
+#[derive(
+    Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize, BorshSchema,
+)]
+pub struct EthEventsVext {
+    /// The address of the voting validator
+    pub validator_addr: Address,
+    /// The height of the block that the vote was made for
+    pub block_height: BlockHeight,
+    /// The Ethereum events seen by the validator, in ascending order and
+    /// without duplicates
+    pub ethereum_events: Vec<EthereumEvent>,
+}
+
+/// An [`EthEventsVext`] signed with the protocol key of the voting validator
+pub type SignedEthEventsVext = Signed<EthEventsVext>;
+
+/// The tally of the votes on an Ethereum event
+#[derive(
+    Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize, BorshSchema,
+)]
+pub struct EthMsg {
+    /// The event being voted on
+    pub body: EthereumEvent,
+    /// The validators that voted for the event
+    pub seen_by: BTreeSet<Address>,
+    /// The sum of the bonded stake of the validators that voted for the
+    /// event, in the epoch in which they voted
+    pub voting_power: u64,
+    /// Whether more than 2/3 of the voting power voted for the event, in
+    /// which case it has been applied
+    pub seen: bool,
+}
+
+impl EthMsg {
+    /// Start the tally of the votes on the given event
+    pub fn new(body: EthereumEvent) -> Self {
+        Self {
+            body,
+            seen_by: BTreeSet::new(),
+            voting_power: 0,
+            seen: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that Ethereum addresses round-trip through their string
+    /// representation
+    #[test]
+    fn test_eth_address_string_round_trip() {
+        let address = EthAddress([0xab; 20]);
+        let encoded = address.to_string();
+        assert_eq!(
+            encoded,
+            "0xabababababababababababababababababababab".to_string()
+        );
+        assert_eq!(EthAddress::from_str(&encoded).unwrap(), address);
+        assert!(matches!(
+            EthAddress::from_str("abababababababababababababababababababab"),
+            Err(ParseEthAddressError::MissingPrefix)
+        ));
+        assert!(matches!(
+            EthAddress::from_str("0xabab"),
+            Err(ParseEthAddressError::InvalidLength(2))
+        ));
+    }
+}
@@ -14,7 +14,7 @@ use super::hash::Hash;
 use super::key::common::{self, Signature};
 use super::key::SigScheme;
 use super::storage::Epoch;
-use super::token::SCALE;
+use super::token::{self, SCALE};
 use super::transaction::governance::InitProposalData;
 
 /// Type alias for vote power
@@ -36,6 +36,8 @@ pub enum ProposalVote {
     Yay,
     /// No
     Nay,
+    /// Present but neutral, counted towards the quorum but not the approval
+    Abstain,
 }
 
 impl ProposalVote {
@@ -43,7 +45,7 @@ impl ProposalVote {
     pub fn is_yay(&self) -> bool {
         match self {
             ProposalVote::Yay => true,
-            ProposalVote::Nay => false,
+            ProposalVote::Nay | ProposalVote::Abstain => false,
         }
     }
 }
@@ -53,6 +55,7 @@ impl Display for ProposalVote {
         match self {
             ProposalVote::Yay => write!(f, "yay"),
             ProposalVote::Nay => write!(f, "nay"),
+            ProposalVote::Abstain => write!(f, "abstain"),
         }
     }
 }
@@ -60,7 +63,7 @@ impl Display for ProposalVote {
 #[allow(missing_docs)]
 #[derive(Debug, Error)]
 pub enum ProposalVoteParseError {
-    #[error("Invalid vote. Vote shall be yay or nay.")]
+    #[error("Invalid vote. Vote shall be yay, nay or abstain.")]
     InvalidVote,
 }
 
@@ -72,6 +75,8 @@ impl FromStr for ProposalVote {
             Ok(ProposalVote::Yay)
         } else if s.eq("nay") {
             Ok(ProposalVote::Nay)
+        } else if s.eq("abstain") {
+            Ok(ProposalVote::Abstain)
         } else {
             Err(ProposalVoteParseError::InvalidVote)
         }
@@ -86,6 +91,9 @@ pub enum TallyResult {
     Rejected,
     /// A critical error in tally computation
     Failed,
+    /// Proposal was accepted, but its execution failed and none of its
+    /// changes were applied
+    ExecutionFailed,
 }
 
 /// The result with votes of a proposal
@@ -96,8 +104,10 @@ pub struct ProposalResult {
     pub total_voting_power: VotePower,
     /// The total voting power from yay votes
     pub total_yay_power: VotePower,
-    /// The total voting power from nay votes (unused at the moment)
+    /// The total voting power from nay votes
     pub total_nay_power: VotePower,
+    /// The total voting power from abstain votes
+    pub total_abstain_power: VotePower,
 }
 
 impl Display for ProposalResult {
@@ -110,9 +120,12 @@ impl Display for ProposalResult {
 
         write!(
             f,
-            "{} with {} yay votes over {} ({:.2}%)",
+            "{} with {} yay votes, {} nay votes and {} abstain votes over {} \
+             ({:.2}%)",
             self.result,
             self.total_yay_power / SCALE as u128,
+            self.total_nay_power / SCALE as u128,
+            self.total_abstain_power / SCALE as u128,
             self.total_voting_power / SCALE as u128,
             percentage.checked_mul(100.into()).unwrap_or_default()
         )
@@ -125,10 +138,340 @@ impl Display for TallyResult {
             TallyResult::Passed => write!(f, "passed"),
             TallyResult::Rejected => write!(f, "rejected"),
             TallyResult::Failed => write!(f, "failed"),
+            TallyResult::ExecutionFailed => write!(f, "execution failed"),
         }
     }
 }
 
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+/// The kind of a proposal, which determines how it is validated and what is
+/// executed when it passes
+pub enum ProposalType {
+    /// A text-only proposal, which can carry the code of a custom tx to
+    /// execute when it passes
+    Default,
+    /// A change of protocol parameters
+    ParameterChange(Vec<ParameterChange>),
+    /// Transfers of native tokens from the treasury, funding public goods
+    TreasuryTransfer(Vec<TreasuryTransfer>),
+}
+
+impl Default for ProposalType {
+    fn default() -> Self {
+        Self::Default
+    }
+}
+
+impl ProposalType {
+    /// Check that the proposal carries changes that can be executed
+    pub fn is_valid(&self) -> bool {
+        match self {
+            ProposalType::Default => true,
+            ProposalType::ParameterChange(changes) => {
+                !changes.is_empty()
+                    && changes.iter().all(ParameterChange::is_valid)
+                    && ParameterChange::are_consistent(changes)
+            }
+            ProposalType::TreasuryTransfer(transfers) => {
+                !transfers.is_empty()
+                    && transfers.iter().all(|transfer| {
+                        transfer.amount != token::Amount::default()
+                    })
+            }
+        }
+    }
+}
+
+impl Display for ProposalType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProposalType::Default => write!(f, "default"),
+            ProposalType::ParameterChange(changes) => {
+                write!(f, "parameter change")?;
+                for change in changes {
+                    write!(f, "\n  {}", change)?;
+                }
+                Ok(())
+            }
+            ProposalType::TreasuryTransfer(transfers) => {
+                write!(f, "treasury transfer")?;
+                for transfer in transfers {
+                    write!(
+                        f,
+                        "\n  {} to {}",
+                        transfer.amount, transfer.target
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+/// A change of a protocol parameter made by a proposal. The parameters are
+/// those of the ledger, of the PoS system and of governance.
+pub enum ParameterChange {
+    /// The minimum duration of an epoch
+    EpochDuration {
+        /// Minimum number of blocks in an epoch
+        min_num_of_blocks: u64,
+        /// Minimum duration of an epoch in seconds
+        min_duration: u64,
+    },
+    /// The maximum expected time per block in seconds
+    MaxExpectedTimePerBlock(u64),
+    /// The whitelisted validity predicate hashes
+    VpWhitelist(Vec<String>),
+    /// The whitelisted tx hashes
+    TxWhitelist(Vec<String>),
+    /// The expected number of epochs per year
+    EpochsPerYear(u64),
+    /// The P gain of the PoS inflation controller
+    PosGainP(Decimal),
+    /// The D gain of the PoS inflation controller
+    PosGainD(Decimal),
+    /// The maximum number of active validators
+    MaxValidatorSlots(u64),
+    /// The reward of a block proposer
+    BlockProposerReward(Decimal),
+    /// The reward of a block voter
+    BlockVoteReward(Decimal),
+    /// The maximum staking rewards rate per annum
+    MaxInflationRate(Decimal),
+    /// The target ratio of staked tokens
+    TargetStakedRatio(Decimal),
+    /// The minimum slash rate of a duplicate vote
+    DuplicateVoteMinSlashRate(Decimal),
+    /// The minimum slash rate of a light client attack
+    LightClientAttackMinSlashRate(Decimal),
+    /// The number of the most recent blocks over which the liveness of the
+    /// active validators is tracked
+    LivenessWindowLen(u64),
+    /// The minimum portion of the blocks in the liveness window that an
+    /// active validator must sign to avoid being jailed
+    LivenessThreshold(Decimal),
+    /// The slash rate of a validator jailed for downtime
+    DowntimeSlashRate(Decimal),
+    /// The minimum amount of locked funds of a proposal, in whole tokens
+    MinProposalFund(u64),
+    /// The maximum size of the code of a proposal
+    MaxProposalCodeSize(u64),
+    /// The minimum voting period of a proposal in epochs
+    MinProposalPeriod(u64),
+    /// The maximum voting period of a proposal in epochs
+    MaxProposalPeriod(u64),
+    /// The maximum size of the content of a proposal
+    MaxProposalContentSize(u64),
+    /// The minimum number of epochs between the end and the grace epochs of
+    /// a proposal
+    MinProposalGraceEpochs(u64),
+    /// The minimum fraction of the voting power that must vote on a proposal
+    MinQuorum(Decimal),
+    /// The default fraction of the yay and nay votes that must be yay for a
+    /// proposal to pass
+    PassThreshold(Decimal),
+    /// The pass threshold of parameter change proposals, or `None` to use the
+    /// default one
+    ParameterChangePassThreshold(Option<Decimal>),
+    /// The pass threshold of treasury transfer proposals, or `None` to use
+    /// the default one
+    TreasuryTransferPassThreshold(Option<Decimal>),
+}
+
+impl ParameterChange {
+    /// Check that the new value of the parameter is within its bounds
+    pub fn is_valid(&self) -> bool {
+        let is_rate =
+            |rate: &Decimal| *rate >= Decimal::ZERO && *rate <= Decimal::ONE;
+        match self {
+            ParameterChange::EpochDuration {
+                min_num_of_blocks, ..
+            } => *min_num_of_blocks > 0,
+            ParameterChange::EpochsPerYear(value)
+            | ParameterChange::MaxValidatorSlots(value)
+            | ParameterChange::LivenessWindowLen(value)
+            | ParameterChange::MinProposalPeriod(value)
+            | ParameterChange::MaxProposalPeriod(value)
+            | ParameterChange::MaxProposalContentSize(value) => *value > 0,
+            ParameterChange::PosGainP(gain)
+            | ParameterChange::PosGainD(gain) => *gain >= Decimal::ZERO,
+            ParameterChange::BlockProposerReward(rate)
+            | ParameterChange::BlockVoteReward(rate)
+            | ParameterChange::MaxInflationRate(rate)
+            | ParameterChange::TargetStakedRatio(rate)
+            | ParameterChange::DuplicateVoteMinSlashRate(rate)
+            | ParameterChange::LightClientAttackMinSlashRate(rate)
+            | ParameterChange::LivenessThreshold(rate)
+            | ParameterChange::DowntimeSlashRate(rate)
+            | ParameterChange::MinQuorum(rate)
+            | ParameterChange::PassThreshold(rate) => is_rate(rate),
+            ParameterChange::ParameterChangePassThreshold(rate)
+            | ParameterChange::TreasuryTransferPassThreshold(rate) => {
+                rate.as_ref().map_or(true, is_rate)
+            }
+            ParameterChange::MaxExpectedTimePerBlock(_)
+            | ParameterChange::VpWhitelist(_)
+            | ParameterChange::TxWhitelist(_)
+            | ParameterChange::MinProposalFund(_)
+            | ParameterChange::MaxProposalCodeSize(_)
+            | ParameterChange::MinProposalGraceEpochs(_) => true,
+        }
+    }
+
+    /// Check that the changes made together by a proposal don't contradict
+    /// each other. The parameters that are not changed by the proposal are
+    /// checked when it is executed.
+    pub fn are_consistent(changes: &[ParameterChange]) -> bool {
+        // the last change of a parameter is the one that applies
+        let min_proposal_period =
+            changes.iter().rev().find_map(|change| match change {
+                ParameterChange::MinProposalPeriod(value) => Some(*value),
+                _ => None,
+            });
+        let max_proposal_period =
+            changes.iter().rev().find_map(|change| match change {
+                ParameterChange::MaxProposalPeriod(value) => Some(*value),
+                _ => None,
+            });
+        match (min_proposal_period, max_proposal_period) {
+            (Some(min), Some(max)) => min <= max,
+            _ => true,
+        }
+    }
+}
+
+impl Display for ParameterChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParameterChange::EpochDuration {
+                min_num_of_blocks,
+                min_duration,
+            } => write!(
+                f,
+                "epoch_duration = {} blocks and {} seconds",
+                min_num_of_blocks, min_duration
+            ),
+            ParameterChange::MaxExpectedTimePerBlock(value) => {
+                write!(f, "max_expected_time_per_block = {}", value)
+            }
+            ParameterChange::VpWhitelist(value) => {
+                write!(f, "vp_whitelist = {:?}", value)
+            }
+            ParameterChange::TxWhitelist(value) => {
+                write!(f, "tx_whitelist = {:?}", value)
+            }
+            ParameterChange::EpochsPerYear(value) => {
+                write!(f, "epochs_per_year = {}", value)
+            }
+            ParameterChange::PosGainP(value) => {
+                write!(f, "pos_gain_p = {}", value)
+            }
+            ParameterChange::PosGainD(value) => {
+                write!(f, "pos_gain_d = {}", value)
+            }
+            ParameterChange::MaxValidatorSlots(value) => {
+                write!(f, "max_validator_slots = {}", value)
+            }
+            ParameterChange::BlockProposerReward(value) => {
+                write!(f, "block_proposer_reward = {}", value)
+            }
+            ParameterChange::BlockVoteReward(value) => {
+                write!(f, "block_vote_reward = {}", value)
+            }
+            ParameterChange::MaxInflationRate(value) => {
+                write!(f, "max_inflation_rate = {}", value)
+            }
+            ParameterChange::TargetStakedRatio(value) => {
+                write!(f, "target_staked_ratio = {}", value)
+            }
+            ParameterChange::DuplicateVoteMinSlashRate(value) => {
+                write!(f, "duplicate_vote_min_slash_rate = {}", value)
+            }
+            ParameterChange::LightClientAttackMinSlashRate(value) => {
+                write!(f, "light_client_attack_min_slash_rate = {}", value)
+            }
+            ParameterChange::LivenessWindowLen(value) => {
+                write!(f, "liveness_window_len = {}", value)
+            }
+            ParameterChange::LivenessThreshold(value) => {
+                write!(f, "liveness_threshold = {}", value)
+            }
+            ParameterChange::DowntimeSlashRate(value) => {
+                write!(f, "downtime_slash_rate = {}", value)
+            }
+            ParameterChange::MinProposalFund(value) => {
+                write!(f, "min_proposal_fund = {}", value)
+            }
+            ParameterChange::MaxProposalCodeSize(value) => {
+                write!(f, "max_proposal_code_size = {}", value)
+            }
+            ParameterChange::MinProposalPeriod(value) => {
+                write!(f, "min_proposal_period = {}", value)
+            }
+            ParameterChange::MaxProposalPeriod(value) => {
+                write!(f, "max_proposal_period = {}", value)
+            }
+            ParameterChange::MaxProposalContentSize(value) => {
+                write!(f, "max_proposal_content_size = {}", value)
+            }
+            ParameterChange::MinProposalGraceEpochs(value) => {
+                write!(f, "min_proposal_grace_epochs = {}", value)
+            }
+            ParameterChange::MinQuorum(value) => {
+                write!(f, "min_quorum = {}", value)
+            }
+            ParameterChange::PassThreshold(value) => {
+                write!(f, "pass_threshold = {}", value)
+            }
+            ParameterChange::ParameterChangePassThreshold(value) => write!(
+                f,
+                "parameter_change_pass_threshold = {}",
+                value.map_or("default".to_string(), |value| value.to_string())
+            ),
+            ParameterChange::TreasuryTransferPassThreshold(value) => write!(
+                f,
+                "treasury_transfer_pass_threshold = {}",
+                value.map_or("default".to_string(), |value| value.to_string())
+            ),
+        }
+    }
+}
+
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+/// A transfer of native tokens from the treasury, which is the slash fund,
+/// made by a proposal
+pub struct TreasuryTransfer {
+    /// The address receiving the tokens
+    pub target: Address,
+    /// The amount of native tokens
+    pub amount: token::Amount,
+}
+
 #[derive(
     Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
 )]
@@ -146,6 +489,9 @@ pub struct Proposal {
     pub voting_end_epoch: Epoch,
     /// The epoch from which this changes are executed
     pub grace_epoch: Epoch,
+    /// The kind of the proposal
+    #[serde(default)]
+    pub r#type: ProposalType,
     /// The code containing the storage changes
     pub proposal_code_path: Option<String>,
 }
@@ -183,6 +529,7 @@ impl TryFrom<Proposal> for InitProposalData {
             voting_start_epoch: proposal.voting_start_epoch,
             voting_end_epoch: proposal.voting_end_epoch,
             grace_epoch: proposal.grace_epoch,
+            r#type: proposal.r#type,
             proposal_code,
         })
     }
@@ -10,9 +10,9 @@ use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use data_encoding::HEXLOWER;
 use serde::{Deserialize, Serialize};
 
-use crate::types::address::Address;
+use crate::types::address::{Address, InternalAddress};
 use crate::types::key::ParsePublicKeyError;
-use crate::types::storage::{DbKeySeg, Key, KeySeg};
+use crate::types::storage::{DbKeySeg, Epoch, Key, KeySeg};
 use crate::types::transaction::EllipticCurve;
 
 /// A keypair used in the DKG protocol
@@ -179,4 +179,51 @@ pub fn is_dkg_pk_key(key: &Key) -> Option<&Address> {
     }
 }
 
+/// Obtain a storage key for a validator's encryption key share of the given
+/// epoch. The key shares are kept in the PoS address space, so that they can
+/// only be written by the protocol.
+pub fn dkg_encryption_share_key(validator: &Address, epoch: Epoch) -> Key {
+    dkg_share_key(DKG_ENCRYPTION_SHARE_STORAGE_KEY, validator, epoch)
+}
+
+/// Obtain a storage key for a validator's decryption key share of the given
+/// epoch.
+pub fn dkg_decryption_share_key(validator: &Address, epoch: Epoch) -> Key {
+    dkg_share_key(DKG_DECRYPTION_SHARE_STORAGE_KEY, validator, epoch)
+}
+
+/// Obtain a storage key for the validators participating in the DKG session
+/// of the given epoch
+pub fn dkg_participants_key(epoch: Epoch) -> Key {
+    dkg_session_key(DKG_PARTICIPANTS_STORAGE_KEY, epoch)
+}
+
+/// Obtain a storage key for the encryption key of the given epoch, aggregated
+/// from the shares of the DKG session participants
+pub fn dkg_session_pk_key(epoch: Epoch) -> Key {
+    dkg_session_key(DKG_SESSION_PK_STORAGE_KEY, epoch)
+}
+
+fn dkg_session_key(prefix: &str, epoch: Epoch) -> Key {
+    Key::from(Address::Internal(InternalAddress::PoS).to_db_key())
+        .push(&prefix.to_owned())
+        .expect("Cannot obtain a storage key")
+        .push(&epoch.0)
+        .expect("Cannot obtain a storage key")
+}
+
+fn dkg_share_key(prefix: &str, validator: &Address, epoch: Epoch) -> Key {
+    Key::from(Address::Internal(InternalAddress::PoS).to_db_key())
+        .push(&prefix.to_owned())
+        .expect("Cannot obtain a storage key")
+        .push(validator)
+        .expect("Cannot obtain a storage key")
+        .push(&epoch.0)
+        .expect("Cannot obtain a storage key")
+}
+
 const DKG_PK_STORAGE_KEY: &str = "dkg_pk_key";
+const DKG_ENCRYPTION_SHARE_STORAGE_KEY: &str = "dkg_encryption_share";
+const DKG_DECRYPTION_SHARE_STORAGE_KEY: &str = "dkg_decryption_share";
+const DKG_PARTICIPANTS_STORAGE_KEY: &str = "dkg_participants";
+const DKG_SESSION_PK_STORAGE_KEY: &str = "dkg_session_pk";
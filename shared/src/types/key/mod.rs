@@ -2,6 +2,7 @@
 /// Elliptic curve keys for the DKG
 pub mod dkg_session_keys;
 
+use std::collections::BTreeMap;
 use std::fmt::{Debug, Display};
 use std::hash::Hash;
 use std::str::FromStr;
@@ -24,6 +25,7 @@ pub mod secp256k1;
 
 const PK_STORAGE_KEY: &str = "public_key";
 const PROTOCOL_PK_STORAGE_KEY: &str = "protocol_public_key";
+const MULTISIG_STORAGE_KEY: &str = "multisig";
 
 /// Obtain a storage key for user's public key.
 pub fn pk_key(owner: &Address) -> storage::Key {
@@ -63,6 +65,88 @@ pub fn is_protocol_pk_key(key: &Key) -> Option<&Address> {
     }
 }
 
+/// Obtain a storage key for the public keys and threshold of a multisignature
+/// account.
+pub fn multisig_key(owner: &Address) -> storage::Key {
+    Key::from(owner.to_db_key())
+        .push(&MULTISIG_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is the public keys and threshold of a
+/// multisignature account. If it is, returns the owner.
+pub fn is_multisig_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(owner), DbKeySeg::StringSeg(key)]
+            if key == MULTISIG_STORAGE_KEY =>
+        {
+            Some(owner)
+        }
+        _ => None,
+    }
+}
+
+/// The public keys of an m-of-n multisignature account, of which at least
+/// `threshold` must sign its transactions
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct Multisig {
+    /// The public keys of the account. The signatures of a transaction are
+    /// indexed by the position of their key in this list.
+    pub public_keys: Vec<common::PublicKey>,
+    /// The number of signatures required to authorize a transaction
+    pub threshold: u8,
+}
+
+impl Multisig {
+    /// Check that the threshold can be reached with the public keys, which
+    /// must not contain any duplicates
+    pub fn is_valid(&self) -> bool {
+        let unique: std::collections::BTreeSet<_> =
+            self.public_keys.iter().collect();
+        self.threshold > 0
+            && usize::from(self.threshold) <= self.public_keys.len()
+            && unique.len() == self.public_keys.len()
+            && self.public_keys.len() <= usize::from(u8::MAX) + 1
+    }
+
+    /// Check that a transaction has been signed by at least the threshold of
+    /// the keys of a valid account, given its signatures indexed by the
+    /// position of their key and a function that verifies a signature with
+    /// its key. Returns the number of valid signatures when the threshold is
+    /// reached. Both the client and the validity predicates use this check.
+    pub fn verify_threshold<E>(
+        &self,
+        sigs: &BTreeMap<u8, common::Signature>,
+        mut verify_sig: impl FnMut(
+            &common::PublicKey,
+            &common::Signature,
+        ) -> Result<bool, E>,
+    ) -> Result<Option<usize>, E> {
+        if !self.is_valid() {
+            return Ok(None);
+        }
+        let mut valid = 0_usize;
+        for (index, sig) in sigs {
+            if let Some(pk) = self.public_keys.get(usize::from(*index)) {
+                if verify_sig(pk, sig)? {
+                    valid += 1;
+                }
+            }
+        }
+        Ok((valid >= usize::from(self.threshold)).then_some(valid))
+    }
+}
+
 /// Represents an error in signature verification
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
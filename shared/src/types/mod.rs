@@ -3,6 +3,7 @@
 pub mod address;
 pub mod chain;
 pub mod dylib;
+pub mod ethereum_events;
 pub mod governance;
 pub mod hash;
 pub mod ibc;
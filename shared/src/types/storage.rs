@@ -1002,6 +1002,18 @@ impl Default for Epochs {
 }
 
 impl Epochs {
+    /// Initialize predecessor epochs, starting on the given epoch and block
+    /// height.
+    pub fn new(
+        first_known_epoch: Epoch,
+        first_block_height: BlockHeight,
+    ) -> Self {
+        Self {
+            first_known_epoch,
+            first_block_heights: vec![first_block_height],
+        }
+    }
+
     /// Record start of a new epoch at the given block height and trim any
     /// epochs that ended more than `max_age_num_blocks` ago.
     pub fn new_epoch(
@@ -1088,6 +1100,29 @@ pub struct PrefixValue {
     pub value: Vec<u8>,
 }
 
+/// The kind of proof of the key-vals returned by a storage prefix query with
+/// `prove`, requested with its borsh encoding in the query data
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+)]
+pub enum PrefixProof {
+    /// The existence proofs of every key-val, one after the other
+    #[default]
+    PerKey,
+    /// A single batch proof of the existence of the key-vals. For a prefix
+    /// in the IBC sub-tree, it also proves that none of the key-vals with the
+    /// prefix is missing.
+    Batch,
+}
+
 #[cfg(test)]
 mod tests {
     use proptest::prelude::*;
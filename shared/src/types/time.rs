@@ -3,6 +3,7 @@
 use std::convert::{TryFrom, TryInto};
 use std::fmt::Display;
 use std::ops::{Add, Sub};
+use std::str::FromStr;
 
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 pub use chrono::{DateTime, Duration, TimeZone, Utc};
@@ -113,6 +114,15 @@ impl DateTimeUtc {
     }
 }
 
+impl FromStr for DateTimeUtc {
+    type Err = chrono::ParseError;
+
+    /// Parse an rfc3339 string (e.g., "1970-01-01T00:00:00Z").
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.parse::<DateTime<Utc>>()?))
+    }
+}
+
 impl Add<DurationSecs> for DateTimeUtc {
     type Output = DateTimeUtc;
 
@@ -11,18 +11,22 @@ use rust_decimal::prelude::{Decimal, ToPrimitive};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::types::address::{masp, Address, DecodeError as AddressError};
+use crate::types::address::{
+    masp, Address, DecodeError as AddressError, InternalAddress,
+};
 use crate::types::ibc::data::FungibleTokenPacketData;
 use crate::types::storage::{DbKeySeg, Key, KeySeg};
 
 /// Amount in micro units. For different granularity another representation
 /// might be more appropriate.
+///
+/// The raw amount is at most [`Change::MAX`], so that any amount can be
+/// converted into a [`Change`]. The decoding rejects the larger amounts.
 #[derive(
     Clone,
     Copy,
     Default,
     BorshSerialize,
-    BorshDeserialize,
     BorshSchema,
     PartialEq,
     Eq,
@@ -32,12 +36,14 @@ use crate::types::storage::{DbKeySeg, Key, KeySeg};
     Hash,
 )]
 pub struct Amount {
-    micro: u64,
+    micro: u128,
 }
 
-/// Maximum decimal places in a token [`Amount`] and [`Change`].
+/// Maximum decimal places in a token [`Amount`] and [`Change`] of the native
+/// denomination, used for the tokens that have no [`Metadata`].
 pub const MAX_DECIMAL_PLACES: u32 = 6;
-/// Decimal scale of token [`Amount`] and [`Change`].
+/// Decimal scale of token [`Amount`] and [`Change`] of the native
+/// denomination.
 pub const SCALE: u64 = 1_000_000;
 
 /// A change in tokens amount
@@ -61,30 +67,155 @@ impl Amount {
         self.micro = self.micro.checked_add(amount.micro).unwrap();
     }
 
+    /// Add an amount, unless the sum is greater than [`Amount::max`]
+    pub fn checked_add(&self, amount: &Amount) -> Option<Self> {
+        self.micro
+            .checked_add(amount.micro)
+            .and_then(|micro| Self::try_from(micro).ok())
+    }
+
     /// Create a new amount from whole number of tokens
     pub const fn whole(amount: u64) -> Self {
         Self {
-            micro: amount * SCALE,
+            micro: amount as u128 * SCALE as u128,
         }
     }
 
-    /// Create a new amount with the maximum value
+    /// Create a new amount from whole number of tokens of the given
+    /// denomination. Errors if the amount is greater than [`Amount::max`].
+    pub fn whole_with_denom(
+        amount: u64,
+        denom: u8,
+    ) -> Result<Self, AmountParseError> {
+        let micro = 10_u128
+            .checked_pow(denom as u32)
+            .and_then(|scale| u128::from(amount).checked_mul(scale))
+            .ok_or(AmountParseError::InvalidRange)?;
+        Self::try_from(micro)
+    }
+
+    /// Parse an amount of tokens of the given denomination written in
+    /// decimal notation
+    pub fn from_str_with_denom(
+        s: &str,
+        denom: u8,
+    ) -> Result<Self, AmountParseError> {
+        Self::from_decimal_with_denom(DecimalAmount::from_str(s)?, denom)
+    }
+
+    /// Convert a decimal number of tokens of the given denomination into an
+    /// amount
+    pub fn from_decimal_with_denom(
+        decimal: DecimalAmount,
+        denom: u8,
+    ) -> Result<Self, AmountParseError> {
+        if decimal.scale > denom {
+            return Err(AmountParseError::ScaleTooLarge(
+                decimal.scale as u32,
+                denom,
+            ));
+        }
+        let micro = 10_u128
+            .checked_pow((denom - decimal.scale) as u32)
+            .and_then(|scale| decimal.mantissa.checked_mul(scale))
+            .ok_or(AmountParseError::InvalidRange)?;
+        Self::try_from(micro)
+    }
+
+    /// Format the amount in decimal notation as tokens of the given
+    /// denomination
+    pub fn to_string_with_denom(&self, denom: u8) -> String {
+        DecimalAmount {
+            mantissa: self.micro,
+            scale: denom,
+        }
+        .to_string()
+    }
+
+    /// Create a new amount with the maximum value, [`Change::MAX`]
     pub fn max() -> Self {
-        Self { micro: u64::MAX }
+        Self {
+            micro: Change::MAX as u128,
+        }
     }
 
     /// Create amount from Change
     ///
     /// # Panics
     ///
-    /// Panics if the change is negative or overflows `u64`.
+    /// Panics if the change is negative.
     pub fn from_change(change: Change) -> Self {
         Self {
-            micro: change as u64,
+            micro: u128::try_from(change)
+                .expect("A token change to convert must not be negative"),
         }
     }
 }
 
+/// A number of tokens written in decimal notation, whose raw [`Amount`]
+/// depends on the denomination of its token. Unlike [`Decimal`], it holds as
+/// many digits as an [`Amount`], so it represents the amounts of the tokens
+/// of any denomination exactly.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DecimalAmount {
+    /// The digits of the number
+    mantissa: u128,
+    /// The number of the digits that are decimal places, without the
+    /// trailing zeros
+    scale: u8,
+}
+
+impl FromStr for DecimalAmount {
+    type Err = AmountParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || AmountParseError::InvalidDecimal(s.to_owned());
+        let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+        let fraction = fraction.trim_end_matches('0');
+        if whole.is_empty() && fraction.is_empty() {
+            return Err(invalid());
+        }
+        let mut mantissa: u128 = 0;
+        for digit in whole.chars().chain(fraction.chars()) {
+            let digit = digit.to_digit(10).ok_or_else(invalid)?;
+            mantissa = mantissa
+                .checked_mul(10)
+                .and_then(|mantissa| mantissa.checked_add(u128::from(digit)))
+                .ok_or(AmountParseError::InvalidRange)?;
+        }
+        let scale = u8::try_from(fraction.len())
+            .map_err(|_| AmountParseError::InvalidRange)?;
+        Ok(Self { mantissa, scale })
+    }
+}
+
+impl Display for DecimalAmount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let digits = format!(
+            "{:0>width$}",
+            self.mantissa,
+            width = self.scale as usize + 1
+        );
+        let (whole, fraction) =
+            digits.split_at(digits.len() - self.scale as usize);
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.is_empty() {
+            write!(f, "{}", whole)
+        } else {
+            write!(f, "{}.{}", whole, fraction)
+        }
+    }
+}
+
+impl BorshDeserialize for Amount {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let micro: u128 = BorshDeserialize::deserialize(buf)?;
+        Self::try_from(micro).map_err(|err| {
+            std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+        })
+    }
+}
+
 impl serde::Serialize for Amount {
     fn serialize<S>(
         &self,
@@ -118,26 +249,46 @@ impl From<Amount> for Decimal {
 
 impl From<Decimal> for Amount {
     fn from(micro: Decimal) -> Self {
-        let res = (micro * Into::<Decimal>::into(SCALE)).to_u64().unwrap();
+        let res = (micro * Into::<Decimal>::into(SCALE)).to_u128().unwrap();
         Self { micro: res }
     }
 }
 
 impl From<u64> for Amount {
     fn from(micro: u64) -> Self {
-        Self { micro }
+        Self {
+            micro: u128::from(micro),
+        }
+    }
+}
+
+impl TryFrom<u128> for Amount {
+    type Error = AmountParseError;
+
+    fn try_from(micro: u128) -> Result<Self, Self::Error> {
+        if micro > Change::MAX as u128 {
+            return Err(AmountParseError::InvalidRange);
+        }
+        Ok(Self { micro })
     }
 }
 
+/// Convert an amount that is known to fit in `u64`, such as the amounts of
+/// the native token, whose total supply does, or the values of the MASP
+/// notes.
+///
+/// # Panics
+///
+/// Panics if the amount overflows `u64`.
 impl From<Amount> for u64 {
     fn from(amount: Amount) -> Self {
-        amount.micro
+        u64::try_from(amount.micro).expect("Token amount overflows u64")
     }
 }
 
 impl From<Amount> for u128 {
     fn from(amount: Amount) -> Self {
-        u128::from(amount.micro)
+        amount.micro
     }
 }
 
@@ -154,7 +305,7 @@ impl Mul<u64> for Amount {
     type Output = Amount;
 
     fn mul(mut self, rhs: u64) -> Self::Output {
-        self.micro *= rhs;
+        self.micro *= u128::from(rhs);
         self
     }
 }
@@ -165,8 +316,10 @@ impl Mul<(u64, u64)> for Amount {
     type Output = (Amount, Amount);
 
     fn mul(mut self, rhs: (u64, u64)) -> Self::Output {
-        let ant = Amount::from((self.micro / rhs.1) * rhs.0);
-        self.micro %= rhs.1;
+        let ant = Amount {
+            micro: (self.micro / u128::from(rhs.1)) * u128::from(rhs.0),
+        };
+        self.micro %= u128::from(rhs.1);
         (ant, self)
     }
 }
@@ -174,9 +327,10 @@ impl Mul<(u64, u64)> for Amount {
 impl Mul<Amount> for u64 {
     type Output = Amount;
 
-    fn mul(mut self, rhs: Amount) -> Self::Output {
-        self *= rhs.micro;
-        Self::Output::from(self)
+    fn mul(self, rhs: Amount) -> Self::Output {
+        Self::Output {
+            micro: u128::from(self) * rhs.micro,
+        }
     }
 }
 
@@ -204,13 +358,13 @@ impl SubAssign for Amount {
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
 pub enum AmountParseError {
-    #[error("Error decoding token amount: {0}")]
-    InvalidDecimal(rust_decimal::Error),
+    #[error("Error decoding token amount, invalid decimal number: {0}")]
+    InvalidDecimal(String),
     #[error(
         "Error decoding token amount, too many decimal places: {0}. Maximum \
-         {MAX_DECIMAL_PLACES}"
+         {1}"
     )]
-    ScaleTooLarge(u32),
+    ScaleTooLarge(u32, u8),
     #[error("Error decoding token amount, the value is within invalid range.")]
     InvalidRange,
 }
@@ -219,32 +373,45 @@ impl FromStr for Amount {
     type Err = AmountParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match rust_decimal::Decimal::from_str(s) {
-            Ok(decimal) => {
-                let scale = decimal.scale();
-                if scale > MAX_DECIMAL_PLACES {
-                    return Err(AmountParseError::ScaleTooLarge(scale));
-                }
-                let whole =
-                    decimal * rust_decimal::Decimal::new(SCALE as i64, 0);
-                let micro: u64 =
-                    rust_decimal::prelude::ToPrimitive::to_u64(&whole)
-                        .ok_or(AmountParseError::InvalidRange)?;
-                Ok(Self { micro })
-            }
-            Err(err) => Err(AmountParseError::InvalidDecimal(err)),
-        }
+        Self::from_str_with_denom(s, MAX_DECIMAL_PLACES as u8)
     }
 }
 
 impl Display for Amount {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let decimal = rust_decimal::Decimal::from_i128_with_scale(
-            self.micro as i128,
-            MAX_DECIMAL_PLACES,
-        )
-        .normalize();
-        write!(f, "{}", decimal)
+        write!(f, "{}", self.to_string_with_denom(MAX_DECIMAL_PLACES as u8))
+    }
+}
+
+/// The metadata of a token, written under the [`METADATA_ADDRESS`] at genesis
+/// or when the token account is created. Its native VP rejects any change of
+/// it afterwards.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct Metadata {
+    /// The number of decimal places of the token's amounts
+    pub denomination: u8,
+    /// The ticker symbol of the token, e.g. `NAM`
+    pub symbol: String,
+    /// The display name of the token
+    pub name: String,
+}
+
+impl Metadata {
+    /// Check that a whole token of the denomination fits in an [`Amount`],
+    /// i.e. it has at most 38 decimal places, and the symbol is not empty
+    pub fn is_valid(&self) -> bool {
+        Amount::whole_with_denom(1, self.denomination).is_ok()
+            && !self.symbol.is_empty()
     }
 }
 
@@ -264,6 +431,8 @@ pub const TX_KEY_PREFIX: &str = "tx-";
 pub const CONVERSION_KEY_PREFIX: &str = "conv";
 /// Key segment prefix for pinned shielded transactions
 pub const PIN_KEY_PREFIX: &str = "pin-";
+/// Key segment for the total supply of a token
+pub const TOTAL_SUPPLY_STORAGE_KEY: &str = "total_supply";
 
 /// Obtain a storage key for user's balance.
 pub fn balance_key(token_addr: &Address, owner: &Address) -> Key {
@@ -274,6 +443,38 @@ pub fn balance_key(token_addr: &Address, owner: &Address) -> Key {
         .expect("Cannot obtain a storage key")
 }
 
+/// Obtain a storage key for the total supply of a token, which is only
+/// changed by the protocol when it mints new tokens.
+pub fn total_supply_key(token_addr: &Address) -> Key {
+    Key::from(token_addr.to_db_key())
+        .push(&TOTAL_SUPPLY_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Internal address under which the metadata of the tokens is stored
+pub const METADATA_ADDRESS: Address =
+    Address::Internal(InternalAddress::TokenMetadata);
+
+/// Obtain a storage key for a token's metadata.
+pub fn metadata_key(token_addr: &Address) -> Key {
+    Key::from(METADATA_ADDRESS.to_db_key())
+        .push(&token_addr.to_db_key())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is the metadata key of a token. If it is,
+/// returns the token.
+pub fn is_metadata_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(addr), DbKeySeg::AddressSeg(token)]
+            if addr == &METADATA_ADDRESS =>
+        {
+            Some(token)
+        }
+        _ => None,
+    }
+}
+
 /// Obtain a storage key prefix for all users' balances.
 pub fn balance_prefix(token_addr: &Address) -> Key {
     Key::from(token_addr.to_db_key())
@@ -483,6 +684,122 @@ mod tests {
         let zero = Amount::from(0);
         assert_eq!("0", zero.to_string());
     }
+
+    #[test]
+    fn test_token_amount_denomination() {
+        let amount = Amount::from_str_with_denom("1.5", 8).unwrap();
+        assert_eq!(amount, Amount::from(150_000_000));
+        assert_eq!("1.5", amount.to_string_with_denom(8));
+        assert_eq!(
+            Amount::whole_with_denom(18, 8).unwrap(),
+            Amount::from(18 * 10_u64.pow(8))
+        );
+        // Any genesis balance fits with 18 decimal places, like the ones of
+        // the ERC20 tokens
+        assert_eq!(
+            Amount::whole_with_denom(u64::MAX, 18).unwrap(),
+            Amount::try_from(u128::from(u64::MAX) * 10_u128.pow(18)).unwrap()
+        );
+        assert!(matches!(
+            Amount::whole_with_denom(u64::MAX, 38),
+            Err(AmountParseError::InvalidRange)
+        ));
+
+        // Amounts with 18 decimal places round-trip, beyond the precision
+        // of `Decimal`
+        for s in [
+            "123456789012345678901.123456789012345678",
+            "0.000000000000000001",
+            "170141183460469231731.687303715884105727",
+        ] {
+            let amount = Amount::from_str_with_denom(s, 18).unwrap();
+            assert_eq!(s, amount.to_string_with_denom(18));
+        }
+        assert_eq!(
+            Amount::from_str_with_denom(
+                "170141183460469231731.687303715884105727",
+                18
+            )
+            .unwrap(),
+            Amount::max()
+        );
+        // An amount must fit in a `Change`
+        assert!(matches!(
+            Amount::from_str_with_denom(
+                "170141183460469231731.687303715884105728",
+                18
+            ),
+            Err(AmountParseError::InvalidRange)
+        ));
+        assert_eq!(Amount::max().change(), Change::MAX);
+        assert_eq!(Amount::max().checked_add(&Amount::from(1)), None);
+        let encoded = (Change::MAX as u128 + 1).try_to_vec().unwrap();
+        assert!(Amount::try_from_slice(&encoded).is_err());
+        let encoded = Amount::max().try_to_vec().unwrap();
+        assert_eq!(Amount::try_from_slice(&encoded).unwrap(), Amount::max());
+        assert_eq!(
+            Amount::from_str_with_denom("1.50", 1).unwrap(),
+            Amount::from(15)
+        );
+        assert_eq!(
+            Amount::from_str_with_denom(".5", 1).unwrap(),
+            Amount::from(5)
+        );
+        assert_eq!(
+            Amount::from_str_with_denom("7.", 1).unwrap(),
+            Amount::from(70)
+        );
+
+        let whole = Amount::from_str_with_denom("42", 0).unwrap();
+        assert_eq!(whole, Amount::from(42));
+        assert_eq!("42", whole.to_string_with_denom(0));
+        assert_eq!(
+            Amount::from_str_with_denom("1.5", 6).unwrap(),
+            Amount::from_str("1.5").unwrap()
+        );
+
+        assert!(matches!(
+            Amount::from_str_with_denom("0.5", 0),
+            Err(AmountParseError::ScaleTooLarge(1, 0))
+        ));
+        assert!(matches!(
+            Amount::from_str_with_denom("19", 38),
+            Err(AmountParseError::InvalidRange)
+        ));
+        for invalid in ["-1", "", ".", "1.2.3", "1e3", " 1"] {
+            assert!(matches!(
+                Amount::from_str_with_denom(invalid, 2),
+                Err(AmountParseError::InvalidDecimal(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_token_metadata_denomination() {
+        let metadata = |denomination| Metadata {
+            denomination,
+            symbol: "ETH".to_owned(),
+            name: "Ether".to_owned(),
+        };
+        assert!(metadata(18).is_valid());
+        assert!(metadata(38).is_valid());
+        assert!(!metadata(39).is_valid());
+        assert!(!Metadata {
+            symbol: String::new(),
+            ..metadata(18)
+        }
+        .is_valid());
+    }
+
+    #[test]
+    fn test_token_metadata_key() {
+        let token = crate::types::address::nam();
+        let key = metadata_key(&token);
+        // The key must trigger the token metadata VP
+        assert!(key.find_addresses().contains(&METADATA_ADDRESS));
+        assert_eq!(is_metadata_key(&key), Some(&token));
+        assert_eq!(is_metadata_key(&balance_key(&token, &token)), None);
+    }
 }
 
 /// Helpers for testing with addresses.
@@ -10,6 +10,7 @@ pub mod decrypted_tx {
 
     use super::EllipticCurve;
     use crate::proto::Tx;
+    use crate::types::chain::ChainId;
     use crate::types::transaction::{hash_tx, Hash, TxType, WrapperTx};
 
     #[derive(Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema)]
@@ -60,6 +61,8 @@ pub mod decrypted_tx {
 
     impl From<DecryptedTx> for Tx {
         fn from(decrypted: DecryptedTx) -> Self {
+            // The chain ID and expiration of a decrypted tx are checked on
+            // the inner tx, so the outer tx doesn't need them
             Tx::new(
                 vec![],
                 Some(
@@ -67,6 +70,8 @@ pub mod decrypted_tx {
                         .try_to_vec()
                         .expect("Encrypting transaction should not fail"),
                 ),
+                ChainId::default(),
+                None,
             )
         }
     }
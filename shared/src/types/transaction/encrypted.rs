@@ -5,15 +5,24 @@
 pub mod encrypted_tx {
     use std::io::{Error, ErrorKind, Write};
 
-    use ark_ec::PairingEngine;
+    use ark_ec::{PairingEngine, ProjectiveCurve};
+    use ark_ff::{PrimeField, Zero};
     use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-    use borsh::{BorshDeserialize, BorshSerialize};
+    use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
     use serde::{Deserialize, Serialize};
+    use sha2::{Digest, Sha512};
     use tpke::{encrypt, Ciphertext};
 
+    use crate::types::address::Address;
+    use crate::types::key::dkg_session_keys::DkgKeypair;
+    use crate::types::storage::Epoch;
     use crate::types::transaction::{AffineCurve, EllipticCurve};
     /// The first group in our elliptic curve bilinear pairing
     pub type G1 = <EllipticCurve as PairingEngine>::G1Affine;
+    /// The second group in our elliptic curve bilinear pairing
+    pub type G2 = <EllipticCurve as PairingEngine>::G2Affine;
+    /// The scalar field of our elliptic curve
+    type Fr = <EllipticCurve as PairingEngine>::Fr;
     /// An encryption key for txs
     #[derive(Debug, Clone, PartialEq)]
     pub struct EncryptionKey(pub G1);
@@ -43,6 +52,299 @@ pub mod encrypted_tx {
         }
     }
 
+    impl borsh::BorshSchema for EncryptionKey {
+        fn add_definitions_recursively(
+            definitions: &mut std::collections::HashMap<
+                borsh::schema::Declaration,
+                borsh::schema::Definition,
+            >,
+        ) {
+            // Encoded as `Vec<u8>`
+            let elements = "u8".into();
+            let definition = borsh::schema::Definition::Sequence { elements };
+            definitions.insert(Self::declaration(), definition);
+        }
+
+        fn declaration() -> borsh::schema::Declaration {
+            "EncryptionKey".into()
+        }
+    }
+
+    impl EncryptionKey {
+        /// Aggregate the encryption key shares of all the participants of a
+        /// DKG session into the encryption key of the session
+        pub fn aggregate<'a>(
+            shares: impl IntoIterator<Item = &'a EncryptionKey>,
+        ) -> Self {
+            let mut key =
+                <EllipticCurve as PairingEngine>::G1Projective::zero();
+            for share in shares {
+                key.add_assign_mixed(&share.0);
+            }
+            Self(key.into_affine())
+        }
+    }
+
+    /// A decryption key for txs, paired with an [`EncryptionKey`]
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct DecryptionKey(pub G2);
+
+    impl Default for DecryptionKey {
+        fn default() -> Self {
+            Self(G2::prime_subgroup_generator())
+        }
+    }
+
+    impl borsh::ser::BorshSerialize for DecryptionKey {
+        fn serialize<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+            let mut buf = Vec::<u8>::new();
+            CanonicalSerialize::serialize(&self.0, &mut buf)
+                .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+            BorshSerialize::serialize(&buf, writer)
+        }
+    }
+
+    impl borsh::de::BorshDeserialize for DecryptionKey {
+        fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+            let key: Vec<u8> = BorshDeserialize::deserialize(buf)?;
+            Ok(DecryptionKey(
+                CanonicalDeserialize::deserialize(&*key)
+                    .map_err(|err| Error::new(ErrorKind::InvalidData, err))?,
+            ))
+        }
+    }
+
+    impl borsh::BorshSchema for DecryptionKey {
+        fn add_definitions_recursively(
+            definitions: &mut std::collections::HashMap<
+                borsh::schema::Declaration,
+                borsh::schema::Definition,
+            >,
+        ) {
+            // Encoded as `Vec<u8>`
+            let elements = "u8".into();
+            let definition = borsh::schema::Definition::Sequence { elements };
+            definitions.insert(Self::declaration(), definition);
+        }
+
+        fn declaration() -> borsh::schema::Declaration {
+            "DecryptionKey".into()
+        }
+    }
+
+    impl DecryptionKey {
+        /// Aggregate the decryption key shares revealed by all the
+        /// participants of a DKG session into the decryption key of the
+        /// session
+        pub fn aggregate<'a>(
+            shares: impl IntoIterator<Item = &'a DecryptionKey>,
+        ) -> Self {
+            let mut key =
+                <EllipticCurve as PairingEngine>::G2Projective::zero();
+            for share in shares {
+                key.add_assign_mixed(&share.0);
+            }
+            Self(key.into_affine())
+        }
+    }
+
+    /// The secret share of a DKG participant in the keys of an epoch.
+    ///
+    /// The encryption key of an epoch is the sum of the encryption key
+    /// shares of all the participants and its decryption key is the sum of
+    /// their decryption key shares. The decryption key can therefore only be
+    /// computed once every participant has revealed its decryption key share.
+    /// This is an n-of-n scheme, not the threshold decryption of ferveo.
+    pub struct EpochKeyShare(Fr);
+
+    impl EpochKeyShare {
+        /// Derive the share of a participant in the keys of the given epoch
+        /// from its DKG session keypair
+        pub fn derive(keypair: &DkgKeypair, epoch: Epoch) -> Self {
+            let keypair = keypair
+                .try_to_vec()
+                .expect("Encoding a DKG keypair shouldn't fail");
+            Self(hash_to_scalar(&[
+                b"epoch_key_share",
+                &keypair,
+                &epoch.0.to_le_bytes(),
+            ]))
+        }
+
+        /// Get the public encryption key share of the given participant,
+        /// together with a proof that it knows the secret share
+        pub fn encryption_share(
+            &self,
+            owner: &Address,
+            epoch: Epoch,
+        ) -> EncryptionKeyShare {
+            let key = EncryptionKey(mul_g1(&self.0));
+            // The nonce is derived from the secret share, which makes the
+            // proof deterministic without ever reusing a nonce
+            let nonce = hash_to_scalar(&[
+                b"epoch_key_share_nonce",
+                &canonical_bytes(&self.0),
+                &canonical_bytes(&key.0),
+            ]);
+            let commitment = mul_g1(&nonce);
+            let challenge =
+                key_share_challenge(&key, &commitment, owner, epoch);
+            EncryptionKeyShare {
+                key,
+                commitment,
+                response: nonce + challenge * self.0,
+            }
+        }
+
+        /// Get the decryption key share, which must only be revealed once
+        /// the epoch is over
+        pub fn decryption_share(&self) -> DecryptionKey {
+            DecryptionKey(
+                G2::prime_subgroup_generator()
+                    .mul(self.0.into_repr())
+                    .into_affine(),
+            )
+        }
+    }
+
+    /// The encryption key share of a DKG participant with a Schnorr proof of
+    /// knowledge of the secret share. The proof prevents a participant from
+    /// choosing its share as a function of the other participants' shares
+    /// in order to cancel them out of the encryption key.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct EncryptionKeyShare {
+        /// The share of the encryption key
+        pub key: EncryptionKey,
+        commitment: G1,
+        response: Fr,
+    }
+
+    impl EncryptionKeyShare {
+        /// Check the proof of knowledge of the secret share, which is bound
+        /// to the given participant and epoch
+        pub fn verify(&self, owner: &Address, epoch: Epoch) -> bool {
+            if self.key.0.is_zero() {
+                return false;
+            }
+            let challenge =
+                key_share_challenge(&self.key, &self.commitment, owner, epoch);
+            let expected = self.commitment.into_projective()
+                + self.key.0.mul(challenge.into_repr());
+            mul_g1(&self.response) == expected.into_affine()
+        }
+
+        /// Check that the given decryption key share is derived from the
+        /// same secret share as this encryption key share
+        pub fn matches(&self, decryption: &DecryptionKey) -> bool {
+            EllipticCurve::pairing(self.key.0, G2::prime_subgroup_generator())
+                == EllipticCurve::pairing(
+                    G1::prime_subgroup_generator(),
+                    decryption.0,
+                )
+        }
+    }
+
+    impl borsh::ser::BorshSerialize for EncryptionKeyShare {
+        fn serialize<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+            let mut commitment = Vec::<u8>::new();
+            CanonicalSerialize::serialize(&self.commitment, &mut commitment)
+                .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+            let mut response = Vec::<u8>::new();
+            CanonicalSerialize::serialize(&self.response, &mut response)
+                .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+            BorshSerialize::serialize(&self.key, writer)?;
+            BorshSerialize::serialize(&(commitment, response), writer)
+        }
+    }
+
+    impl borsh::de::BorshDeserialize for EncryptionKeyShare {
+        fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+            let key: EncryptionKey = BorshDeserialize::deserialize(buf)?;
+            let (commitment, response): (Vec<u8>, Vec<u8>) =
+                BorshDeserialize::deserialize(buf)?;
+            Ok(EncryptionKeyShare {
+                key,
+                commitment: CanonicalDeserialize::deserialize(&*commitment)
+                    .map_err(|err| Error::new(ErrorKind::InvalidData, err))?,
+                response: CanonicalDeserialize::deserialize(&*response)
+                    .map_err(|err| Error::new(ErrorKind::InvalidData, err))?,
+            })
+        }
+    }
+
+    impl borsh::BorshSchema for EncryptionKeyShare {
+        fn add_definitions_recursively(
+            definitions: &mut std::collections::HashMap<
+                borsh::schema::Declaration,
+                borsh::schema::Definition,
+            >,
+        ) {
+            // Encoded as `(EncryptionKey, Vec<u8>, Vec<u8>)`
+            EncryptionKey::add_definitions_recursively(definitions);
+            let elements = "u8".into();
+            let definition = borsh::schema::Definition::Sequence { elements };
+            definitions.insert("Vec<u8>".into(), definition);
+            let elements = vec![
+                EncryptionKey::declaration(),
+                "Vec<u8>".into(),
+                "Vec<u8>".into(),
+            ];
+            let definition = borsh::schema::Definition::Tuple { elements };
+            definitions.insert(Self::declaration(), definition);
+        }
+
+        fn declaration() -> borsh::schema::Declaration {
+            "EncryptionKeyShare".into()
+        }
+    }
+
+    /// Multiply the generator of the first group by the given scalar
+    fn mul_g1(scalar: &Fr) -> G1 {
+        G1::prime_subgroup_generator()
+            .mul(scalar.into_repr())
+            .into_affine()
+    }
+
+    /// Hash the given byte strings into a scalar. The hash is twice as long
+    /// as the scalar field modulus to keep the bias of the reduction
+    /// negligible.
+    fn hash_to_scalar(parts: &[&[u8]]) -> Fr {
+        let mut hasher = Sha512::new();
+        for part in parts {
+            hasher.update(part);
+        }
+        Fr::from_le_bytes_mod_order(&hasher.finalize())
+    }
+
+    /// The Fiat-Shamir challenge of the proof of knowledge of a secret key
+    /// share
+    fn key_share_challenge(
+        key: &EncryptionKey,
+        commitment: &G1,
+        owner: &Address,
+        epoch: Epoch,
+    ) -> Fr {
+        let owner = owner
+            .try_to_vec()
+            .expect("Encoding an address shouldn't fail");
+        hash_to_scalar(&[
+            b"epoch_key_share_challenge",
+            &canonical_bytes(&key.0),
+            &canonical_bytes(commitment),
+            &owner,
+            &epoch.0.to_le_bytes(),
+        ])
+    }
+
+    /// Serialize a curve element or a scalar
+    fn canonical_bytes(value: &impl CanonicalSerialize) -> Vec<u8> {
+        let mut buf = Vec::<u8>::new();
+        value
+            .serialize(&mut buf)
+            .expect("Serializing a curve element shouldn't fail");
+        buf
+    }
+
     /// We use a specific choice of two groups and bilinear pairing
     /// We use a wrapper type to add traits
     #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -158,6 +460,15 @@ pub mod encrypted_tx {
         use ark_ec::AffineCurve;
 
         use super::*;
+        use crate::types::address::testing::{
+            established_address_1, established_address_2,
+        };
+
+        /// Generate a random DKG session keypair
+        fn gen_dkg_keypair() -> DkgKeypair {
+            let mut rng = rand::thread_rng();
+            ferveo_common::Keypair::<EllipticCurve>::new(&mut rng).into()
+        }
 
         /// Test that encryption and decryption are inverses.
         #[test]
@@ -215,6 +526,73 @@ pub mod encrypted_tx {
             // check that decryption works as expected
             assert_eq!(decrypted, "Super secret stuff".as_bytes());
         }
+
+        /// Test that the epoch keys aggregated from the shares of several
+        /// DKG participants are a valid keypair
+        #[test]
+        fn test_aggregated_epoch_keys() {
+            let epoch = Epoch(1);
+            let owner = established_address_1();
+            let shares: Vec<EpochKeyShare> = (0..3)
+                .map(|_| EpochKeyShare::derive(&gen_dkg_keypair(), epoch))
+                .collect();
+            let encryption_shares: Vec<EncryptionKeyShare> = shares
+                .iter()
+                .map(|share| share.encryption_share(&owner, epoch))
+                .collect();
+            let decryption_shares: Vec<DecryptionKey> =
+                shares.iter().map(EpochKeyShare::decryption_share).collect();
+            let pubkey = EncryptionKey::aggregate(
+                encryption_shares.iter().map(|share| &share.key),
+            );
+            let privkey = DecryptionKey::aggregate(&decryption_shares);
+            assert_ne!(pubkey, EncryptionKey::default());
+            // generate encrypted payload
+            let encrypted =
+                EncryptedTx::encrypt("Super secret stuff".as_bytes(), pubkey);
+            // decrypt the payload and check we got original data back
+            let decrypted = encrypted.decrypt(privkey.0);
+            assert_eq!(decrypted, "Super secret stuff".as_bytes());
+        }
+
+        /// Test that the proof of knowledge of an encryption key share is
+        /// bound to its participant and epoch and that only the matching
+        /// decryption key share is accepted
+        #[test]
+        fn test_encryption_key_share_proof() {
+            let keypair = gen_dkg_keypair();
+            let owner = established_address_1();
+            let share = EpochKeyShare::derive(&keypair, Epoch(1));
+            let encryption_share = share.encryption_share(&owner, Epoch(1));
+            assert!(encryption_share.verify(&owner, Epoch(1)));
+            assert!(encryption_share.matches(&share.decryption_share()));
+
+            // the proof cannot be reused by another participant or in
+            // another epoch
+            assert!(
+                !encryption_share.verify(&established_address_2(), Epoch(1))
+            );
+            assert!(!encryption_share.verify(&owner, Epoch(2)));
+
+            // the shares of different epochs are independent
+            let next_share = EpochKeyShare::derive(&keypair, Epoch(2));
+            assert!(!encryption_share.matches(&next_share.decryption_share()));
+
+            // a share with a forged key doesn't verify
+            let forged = EncryptionKeyShare {
+                key: next_share.encryption_share(&owner, Epoch(1)).key,
+                ..encryption_share.clone()
+            };
+            assert!(!forged.verify(&owner, Epoch(1)));
+
+            // check that the proof survives a Borsh roundtrip
+            let borsh = encryption_share.try_to_vec().expect("Test failed");
+            let decoded: EncryptionKeyShare =
+                BorshDeserialize::deserialize(&mut borsh.as_ref())
+                    .expect("Test failed");
+            assert_eq!(decoded, encryption_share);
+            assert!(decoded.verify(&owner, Epoch(1)));
+        }
     }
 }
 
@@ -2,7 +2,7 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
 
 use crate::types::address::Address;
-use crate::types::governance::ProposalVote;
+use crate::types::governance::{ProposalType, ProposalVote};
 use crate::types::storage::Epoch;
 
 /// A tx data type to hold proposal data
@@ -28,7 +28,10 @@ pub struct InitProposalData {
     pub voting_end_epoch: Epoch,
     /// The epoch from which this changes are executed
     pub grace_epoch: Epoch,
-    /// The code containing the storage changes
+    /// The kind of the proposal
+    pub r#type: ProposalType,
+    /// The code containing the storage changes, only allowed in proposals
+    /// of the default type
     pub proposal_code: Option<Vec<u8>>,
 }
 
@@ -7,6 +7,7 @@ pub mod decrypted;
 pub mod encrypted;
 /// txs to manage governance
 pub mod governance;
+pub mod offline;
 pub mod pos;
 /// transaction protocols made by validators
 pub mod protocol;
@@ -19,7 +20,9 @@ use std::fmt;
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 pub use decrypted::*;
 #[cfg(feature = "ferveo-tpke")]
-pub use encrypted::EncryptionKey;
+pub use encrypted::{
+    DecryptionKey, EncryptionKey, EncryptionKeyShare, EpochKeyShare,
+};
 pub use protocol::UpdateDkgSessionKey;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
@@ -32,6 +35,7 @@ use crate::ledger::gas::VpsGas;
 use crate::types::address::Address;
 use crate::types::hash::Hash;
 use crate::types::key::*;
+use crate::types::token;
 
 /// Get the hash of a transaction
 pub fn hash_tx(tx_bytes: &[u8]) -> Hash {
@@ -147,6 +151,66 @@ pub struct UpdateVp {
     pub vp_code: Vec<u8>,
 }
 
+/// A tx data type to rotate the public key of an established account
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+pub struct UpdateKey {
+    /// An address of the account
+    pub addr: Address,
+    /// The new public key of the account. The tx must be signed by the
+    /// account's current key.
+    pub public_key: common::PublicKey,
+    /// The signature of the account's address and the new public key by the
+    /// new key, proving that its secret key is held
+    pub possession_sig: common::Signature,
+}
+
+impl UpdateKey {
+    /// Rotate the public key of the account to the public key of the given
+    /// keypair, which proves its possession by signing it
+    pub fn new(addr: Address, keypair: &common::SecretKey) -> Self {
+        let public_key = keypair.ref_to();
+        let possession_sig = common::SigScheme::sign(
+            keypair,
+            Self::possession_data(&addr, &public_key),
+        );
+        Self {
+            addr,
+            public_key,
+            possession_sig,
+        }
+    }
+
+    /// Check that the new public key has signed the account's address and
+    /// itself
+    pub fn verify_possession(&self) -> bool {
+        common::SigScheme::verify_signature_raw(
+            &self.public_key,
+            &Self::possession_data(&self.addr, &self.public_key),
+            &self.possession_sig,
+        )
+        .is_ok()
+    }
+
+    /// The data signed by the new public key to prove its possession
+    fn possession_data(
+        addr: &Address,
+        public_key: &common::PublicKey,
+    ) -> Vec<u8> {
+        (addr, public_key)
+            .try_to_vec()
+            .expect("Encoding the key's possession data shouldn't fail")
+    }
+}
+
 /// A tx data type to initialize a new established account
 #[derive(
     Debug,
@@ -165,6 +229,13 @@ pub struct InitAccount {
     pub public_key: common::PublicKey,
     /// The VP code
     pub vp_code: Vec<u8>,
+    /// Public keys and threshold to be written into the storage of a new
+    /// multisignature account. With the default user VP, these replace the
+    /// `public_key` for signature verification of its transactions.
+    pub multisig: Option<Multisig>,
+    /// Metadata to be written for a new token account. The token metadata
+    /// native VP rejects any change of it once the account is created.
+    pub token_metadata: Option<token::Metadata>,
 }
 
 /// A tx data type to initialize a new validator account.
@@ -210,6 +281,7 @@ pub mod tx_types {
 
     use super::*;
     use crate::proto::{SignedTxData, Tx};
+    use crate::types::chain::ChainId;
     use crate::types::transaction::protocol::ProtocolTx;
 
     /// Errors relating to decrypting a wrapper tx and its
@@ -240,8 +312,16 @@ pub mod tx_types {
     }
 
     impl From<TxType> for Tx {
+        /// The resulting tx is not bound to any chain and it doesn't expire.
+        /// This is only meant to be used for txs that are not submitted by
+        /// users, e.g. the decrypted txs included by the block proposer.
         fn from(ty: TxType) -> Self {
-            Tx::new(vec![], Some(ty.try_to_vec().unwrap()))
+            Tx::new(
+                vec![],
+                Some(ty.try_to_vec().unwrap()),
+                ChainId::default(),
+                None,
+            )
         }
     }
 
@@ -287,6 +367,7 @@ pub mod tx_types {
         if let Some(Ok(SignedTxData {
             data: Some(data),
             ref sig,
+            ..
         })) = tx
             .data
             .as_ref()
@@ -296,12 +377,16 @@ pub mod tx_types {
                 code: tx.code,
                 data: Some(data.clone()),
                 timestamp: tx.timestamp,
+                chain_id: tx.chain_id.clone(),
+                expiration: tx.expiration,
             }
             .hash();
             match TxType::try_from(Tx {
                 code: vec![],
                 data: Some(data),
                 timestamp: tx.timestamp,
+                chain_id: tx.chain_id,
+                expiration: tx.expiration,
             })
             .map_err(|err| TxError::Deserialization(err.to_string()))?
             {
@@ -355,7 +440,12 @@ pub mod tx_types {
         /// data and returns an identical copy
         #[test]
         fn test_process_tx_raw_tx_no_data() {
-            let tx = Tx::new("wasm code".as_bytes().to_owned(), None);
+            let tx = Tx::new(
+                "wasm code".as_bytes().to_owned(),
+                None,
+                ChainId::default(),
+                None,
+            );
 
             match process_tx(tx.clone()).expect("Test failed") {
                 TxType::Raw(raw) => assert_eq!(tx, raw),
@@ -371,6 +461,8 @@ pub mod tx_types {
             let inner = Tx::new(
                 "code".as_bytes().to_owned(),
                 Some("transaction data".as_bytes().to_owned()),
+                ChainId::default(),
+                None,
             );
             let tx = Tx::new(
                 "wasm code".as_bytes().to_owned(),
@@ -379,6 +471,8 @@ pub mod tx_types {
                         .try_to_vec()
                         .expect("Test failed"),
                 ),
+                ChainId::default(),
+                None,
             );
 
             match process_tx(tx).expect("Test failed") {
@@ -394,6 +488,8 @@ pub mod tx_types {
             let inner = Tx::new(
                 "code".as_bytes().to_owned(),
                 Some("transaction data".as_bytes().to_owned()),
+                ChainId::default(),
+                None,
             );
             let tx = Tx::new(
                 "wasm code".as_bytes().to_owned(),
@@ -402,6 +498,8 @@ pub mod tx_types {
                         .try_to_vec()
                         .expect("Test failed"),
                 ),
+                ChainId::default(),
+                None,
             )
             .sign(&gen_keypair());
 
@@ -419,6 +517,8 @@ pub mod tx_types {
             let tx = Tx::new(
                 "wasm code".as_bytes().to_owned(),
                 Some("transaction data".as_bytes().to_owned()),
+                ChainId::default(),
+                None,
             );
             // the signed tx
             let wrapper = WrapperTx::new(
@@ -432,7 +532,7 @@ pub mod tx_types {
                 tx.clone(),
                 Default::default(),
             )
-            .sign(&keypair)
+            .sign(&keypair, ChainId::default(), None)
             .expect("Test failed");
 
             match process_tx(wrapper).expect("Test failed") {
@@ -454,6 +554,8 @@ pub mod tx_types {
             let tx = Tx::new(
                 "wasm code".as_bytes().to_owned(),
                 Some("transaction data".as_bytes().to_owned()),
+                ChainId::default(),
+                None,
             );
             // the signed tx
             let wrapper = WrapperTx::new(
@@ -473,6 +575,8 @@ pub mod tx_types {
                 Some(
                     TxType::Wrapper(wrapper).try_to_vec().expect("Test failed"),
                 ),
+                ChainId::default(),
+                None,
             );
             let result = process_tx(tx).expect_err("Test failed");
             assert_matches!(result, TxError::Unsigned(_));
@@ -486,6 +590,8 @@ pub mod tx_types {
         let payload = Tx::new(
             "transaction data".as_bytes().to_owned(),
             Some("transaction data".as_bytes().to_owned()),
+            ChainId::default(),
+            None,
         );
         let decrypted = DecryptedTx::Decrypted(payload.clone());
         let tx = Tx::from(TxType::Decrypted(decrypted));
@@ -505,6 +611,8 @@ pub mod tx_types {
         let payload = Tx::new(
             "transaction data".as_bytes().to_owned(),
             Some("transaction data".as_bytes().to_owned()),
+            ChainId::default(),
+            None,
         );
         let decrypted = DecryptedTx::Decrypted(payload.clone());
         // Invalid signed data
@@ -517,10 +625,15 @@ pub mod tx_types {
                     .expect("Test failed"),
             ),
             sig: common::Signature::try_from_sig(&ed_sig).unwrap(),
+            sigs: Default::default(),
         };
         // create the tx with signed decrypted data
-        let tx =
-            Tx::new(vec![], Some(signed.try_to_vec().expect("Test failed")));
+        let tx = Tx::new(
+            vec![],
+            Some(signed.try_to_vec().expect("Test failed")),
+            ChainId::default(),
+            None,
+        );
         match process_tx(tx).expect("Test failed") {
             TxType::Decrypted(DecryptedTx::Decrypted(processed)) => {
                 assert_eq!(payload, processed);
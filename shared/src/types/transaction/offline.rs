@@ -0,0 +1,167 @@
+//! Transactions that are built on a machine connected to the ledger and
+//! signed on an offline one.
+
+use std::collections::BTreeMap;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use thiserror::Error;
+
+use crate::proto::{SignedTxData, Tx};
+use crate::types::address::Address;
+use crate::types::key::*;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("The key {0} is not a signing key of the account {1}")]
+    NotSigningKey(common::PublicKey, Address),
+    #[error("The transaction has not been signed by the account {0}")]
+    MissingSig(Address),
+}
+
+/// Result of an offline transaction operation
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// An unsigned transaction with the chain metadata needed to sign it without
+/// access to the ledger, and the signatures collected so far
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
+pub struct OfflineTx {
+    /// The unsigned transaction. Its chain ID, timestamp and expiration are
+    /// covered by the signatures.
+    pub tx: Tx,
+    /// The account that authorizes the transaction
+    pub signer: Address,
+    /// The public key of the signer, if known
+    pub public_key: Option<common::PublicKey>,
+    /// The public keys and threshold of the signer, if it is a
+    /// multisignature account
+    pub multisig: Option<Multisig>,
+    /// The signature of the signer's public key
+    pub sig: Option<common::Signature>,
+    /// The signatures of the signer's multisignature keys, indexed by the
+    /// position of their key
+    pub sigs: BTreeMap<u8, common::Signature>,
+}
+
+impl OfflineTx {
+    /// Prepare an unsigned transaction to be signed offline
+    pub fn new(
+        tx: Tx,
+        signer: Address,
+        public_key: Option<common::PublicKey>,
+        multisig: Option<Multisig>,
+    ) -> Self {
+        Self {
+            tx,
+            signer,
+            public_key,
+            multisig,
+            sig: None,
+            sigs: BTreeMap::new(),
+        }
+    }
+
+    /// Sign the transaction with a key of its signer, which is either its
+    /// public key, the key of its implicit address or one of its
+    /// multisignature keys.
+    pub fn sign(&mut self, keypair: &common::SecretKey) -> Result<()> {
+        let pk = keypair.ref_to();
+        let to_sign = self.tx.hash();
+        let mut signed = false;
+        if self.public_key.as_ref() == Some(&pk)
+            || Address::from(&pk) == self.signer
+        {
+            self.sig = Some(common::SigScheme::sign(keypair, to_sign));
+            self.public_key = Some(pk.clone());
+            signed = true;
+        }
+        if let Some(multisig) = &self.multisig {
+            for (index, multisig_pk) in (0..=u8::MAX).zip(&multisig.public_keys)
+            {
+                if multisig_pk == &pk {
+                    self.sigs.insert(
+                        index,
+                        common::SigScheme::sign(keypair, to_sign),
+                    );
+                    signed = true;
+                }
+            }
+        }
+        if signed {
+            Ok(())
+        } else {
+            Err(Error::NotSigningKey(pk, self.signer.clone()))
+        }
+    }
+
+    /// Attach the collected signatures to the transaction, which can then be
+    /// wrapped and submitted. The validity predicate of a multisignature
+    /// account only checks the multisignature keys, so any of their
+    /// signatures can stand in for the signature of the public key.
+    pub fn signed_tx(self) -> Result<Tx> {
+        let sig = self
+            .sig
+            .or_else(|| self.sigs.values().next().cloned())
+            .ok_or_else(|| Error::MissingSig(self.signer.clone()))?;
+        let signed = SignedTxData {
+            data: self.tx.data,
+            sig,
+            sigs: self.sigs,
+        }
+        .try_to_vec()
+        .expect("Encoding transaction data shouldn't fail");
+        Ok(Tx {
+            data: Some(signed),
+            ..self.tx
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::address;
+    use crate::types::chain::ChainId;
+
+    /// Test that a transaction signed offline carries the same signatures as
+    /// one signed online.
+    #[test]
+    fn test_offline_tx_sign() {
+        let keypair_1 = testing::keypair_1();
+        let keypair_2 = testing::keypair_2();
+        let tx = Tx::new(
+            "wasm code".as_bytes().to_owned(),
+            Some("arbitrary data".as_bytes().to_owned()),
+            ChainId::default(),
+            None,
+        );
+
+        let signer = address::testing::established_address_1();
+        let mut offline =
+            OfflineTx::new(tx.clone(), signer, Some(keypair_1.ref_to()), None);
+        assert!(matches!(
+            offline.clone().signed_tx(),
+            Err(Error::MissingSig(_))
+        ));
+        assert!(matches!(
+            offline.sign(&keypair_2),
+            Err(Error::NotSigningKey(..))
+        ));
+        offline.sign(&keypair_1).unwrap();
+        assert_eq!(offline.signed_tx().unwrap(), tx.clone().sign(&keypair_1));
+
+        let multisig = Multisig {
+            public_keys: vec![keypair_1.ref_to(), keypair_2.ref_to()],
+            threshold: 2,
+        };
+        let signer = address::testing::established_address_2();
+        let mut offline =
+            OfflineTx::new(tx, signer, None, Some(multisig.clone()));
+        offline.sign(&keypair_2).unwrap();
+        let signed = offline.clone().signed_tx().unwrap();
+        assert!(signed.verify_multisig(&multisig).is_err());
+        offline.sign(&keypair_1).unwrap();
+        let signed = offline.signed_tx().unwrap();
+        assert_eq!(signed.verify_multisig(&multisig).unwrap(), 2);
+    }
+}
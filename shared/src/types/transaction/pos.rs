@@ -55,6 +55,9 @@ pub struct Withdraw {
     pub source: Option<Address>,
 }
 
+/// A claim of the rewards of a bond.
+pub type ClaimRewards = Withdraw;
+
 /// A change to the validator commission rate.
 #[derive(
     Debug,
@@ -33,8 +33,13 @@ mod protocol_txs {
 
     use super::*;
     use crate::proto::Tx;
+    use crate::types::chain::ChainId;
+    use crate::types::ethereum_events::SignedEthEventsVext;
     use crate::types::key::*;
-    use crate::types::transaction::{EllipticCurve, TxError, TxType};
+    use crate::types::storage::Epoch;
+    use crate::types::transaction::{
+        DecryptionKey, EllipticCurve, EncryptionKeyShare, TxError, TxType,
+    };
 
     const TX_NEW_DKG_KP_WASM: &str = "tx_update_dkg_session_keypair.wasm";
 
@@ -79,6 +84,14 @@ mod protocol_txs {
         /// Aggregation of Ethereum state changes
         /// voted on by validators in last block
         EthereumStateUpdate(Tx),
+        /// A validator's share of the encryption key of the given epoch
+        EncryptionKeyShare(Epoch, EncryptionKeyShare),
+        /// A validator's share of the decryption key of the given epoch,
+        /// revealed once the epoch is over
+        DecryptionKeyShare(Epoch, DecryptionKey),
+        /// The votes of validators on the Ethereum events that they have
+        /// seen
+        EthereumEvents(Vec<SignedEthEventsVext>),
     }
 
     impl ProtocolTxType {
@@ -87,6 +100,7 @@ mod protocol_txs {
             self,
             pk: &common::PublicKey,
             signing_key: &common::SecretKey,
+            chain_id: ChainId,
         ) -> Tx {
             Tx::new(
                 vec![],
@@ -98,6 +112,8 @@ mod protocol_txs {
                     .try_to_vec()
                     .expect("Could not serialize ProtocolTx"),
                 ),
+                chain_id,
+                None,
             )
             .sign(signing_key)
         }
@@ -108,6 +124,7 @@ mod protocol_txs {
             signing_key: &common::SecretKey,
             wasm_dir: &'a Path,
             wasm_loader: F,
+            chain_id: ChainId,
         ) -> Self
         where
             F: FnOnce(&'a str, &'static str) -> Vec<u8>,
@@ -125,6 +142,8 @@ mod protocol_txs {
                         data.try_to_vec()
                             .expect("Serializing request should not fail"),
                     ),
+                    chain_id,
+                    None,
                 )
                 .sign(signing_key),
             )
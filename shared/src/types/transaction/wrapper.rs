@@ -13,8 +13,10 @@ pub mod wrapper_tx {
 
     use crate::proto::Tx;
     use crate::types::address::Address;
+    use crate::types::chain::ChainId;
     use crate::types::key::*;
     use crate::types::storage::Epoch;
+    use crate::types::time::DateTimeUtc;
     use crate::types::token::Amount;
     use crate::types::transaction::encrypted::EncryptedTx;
     use crate::types::transaction::{
@@ -236,10 +238,13 @@ pub mod wrapper_tx {
             }
         }
 
-        /// Sign the wrapper transaction and convert to a normal Tx type
+        /// Sign the wrapper transaction and convert to a normal Tx type. The
+        /// given chain ID and expiration are signed together with the wrapper.
         pub fn sign(
             &self,
             keypair: &common::SecretKey,
+            chain_id: ChainId,
+            expiration: Option<DateTimeUtc>,
         ) -> Result<Tx, WrapperTxErr> {
             if self.pk != keypair.ref_to() {
                 return Err(WrapperTxErr::InvalidKeyPair);
@@ -251,6 +256,8 @@ pub mod wrapper_tx {
                         .try_to_vec()
                         .expect("Could not serialize WrapperTx"),
                 ),
+                chain_id,
+                expiration,
             )
             .sign(keypair))
         }
@@ -355,6 +362,8 @@ pub mod wrapper_tx {
             let tx = Tx::new(
                 "wasm code".as_bytes().to_owned(),
                 Some("transaction data".as_bytes().to_owned()),
+                ChainId::default(),
+                None,
             );
 
             let wrapper = WrapperTx::new(
@@ -381,6 +390,8 @@ pub mod wrapper_tx {
             let tx = Tx::new(
                 "wasm code".as_bytes().to_owned(),
                 Some("transaction data".as_bytes().to_owned()),
+                ChainId::default(),
+                None,
             );
 
             let mut wrapper = WrapperTx::new(
@@ -413,6 +424,8 @@ pub mod wrapper_tx {
             let tx = Tx::new(
                 "wasm code".as_bytes().to_owned(),
                 Some("transaction data".as_bytes().to_owned()),
+                ChainId::default(),
+                None,
             );
             // the signed tx
             let mut tx = WrapperTx::new(
@@ -426,7 +439,7 @@ pub mod wrapper_tx {
                 tx,
                 Default::default(),
             )
-            .sign(&keypair)
+            .sign(&keypair, ChainId::default(), None)
             .expect("Test failed");
 
             // we now try to alter the inner tx maliciously
@@ -444,8 +457,12 @@ pub mod wrapper_tx {
                     .expect("Test failed");
 
             // malicious transaction
-            let malicious =
-                Tx::new("Give me all the money".as_bytes().to_owned(), None);
+            let malicious = Tx::new(
+                "Give me all the money".as_bytes().to_owned(),
+                None,
+                ChainId::default(),
+                None,
+            );
 
             // We replace the inner tx with a malicious one
             wrapper.inner_tx = EncryptedTx::encrypt(
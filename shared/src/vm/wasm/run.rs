@@ -411,6 +411,7 @@ mod tests {
 
     use super::*;
     use crate::ledger::storage::testing::TestStorage;
+    use crate::types::chain::ChainId;
     use crate::types::validity_predicate::EvalVp;
     use crate::vm::wasm;
 
@@ -547,7 +548,7 @@ mod tests {
             input,
         };
         let tx_data = eval_vp.try_to_vec().unwrap();
-        let tx = Tx::new(vec![], Some(tx_data));
+        let tx = Tx::new(vec![], Some(tx_data), ChainId::default(), None);
         let (vp_cache, _) = wasm::compilation_cache::common::testing::cache();
         // When the `eval`ed VP doesn't run out of memory, it should return
         // `true`
@@ -574,7 +575,7 @@ mod tests {
             input,
         };
         let tx_data = eval_vp.try_to_vec().unwrap();
-        let tx = Tx::new(vec![], Some(tx_data));
+        let tx = Tx::new(vec![], Some(tx_data), ChainId::default(), None);
         // When the `eval`ed VP runs out of memory, its result should be
         // `false`, hence we should also get back `false` from the VP that
         // called `eval`.
@@ -617,7 +618,7 @@ mod tests {
         // Allocating `2^23` (8 MiB) should be below the memory limit and
         // shouldn't fail
         let tx_data = 2_usize.pow(23).try_to_vec().unwrap();
-        let tx = Tx::new(vec![], Some(tx_data));
+        let tx = Tx::new(vec![], Some(tx_data), ChainId::default(), None);
         let (vp_cache, _) = wasm::compilation_cache::common::testing::cache();
         let result = vp(
             vp_code.clone(),
@@ -636,7 +637,7 @@ mod tests {
         // Allocating `2^24` (16 MiB) should be above the memory limit and
         // should fail
         let tx_data = 2_usize.pow(24).try_to_vec().unwrap();
-        let tx = Tx::new(vec![], Some(tx_data));
+        let tx = Tx::new(vec![], Some(tx_data), ChainId::default(), None);
         let error = vp(
             vp_code,
             &tx,
@@ -727,7 +728,7 @@ mod tests {
         // limit and should fail
         let len = 2_usize.pow(24);
         let tx_data: Vec<u8> = vec![6_u8; len];
-        let tx = Tx::new(vec![], Some(tx_data));
+        let tx = Tx::new(vec![], Some(tx_data), ChainId::default(), None);
         let (vp_cache, _) = wasm::compilation_cache::common::testing::cache();
         let result = vp(
             vp_code,
@@ -834,7 +835,7 @@ mod tests {
         // Borsh.
         storage.write(&key, value.try_to_vec().unwrap()).unwrap();
         let tx_data = key.try_to_vec().unwrap();
-        let tx = Tx::new(vec![], Some(tx_data));
+        let tx = Tx::new(vec![], Some(tx_data), ChainId::default(), None);
         let (vp_cache, _) = wasm::compilation_cache::common::testing::cache();
         let error = vp(
             vp_read_key,
@@ -890,7 +891,7 @@ mod tests {
             input,
         };
         let tx_data = eval_vp.try_to_vec().unwrap();
-        let tx = Tx::new(vec![], Some(tx_data));
+        let tx = Tx::new(vec![], Some(tx_data), ChainId::default(), None);
         let (vp_cache, _) = wasm::compilation_cache::common::testing::cache();
         let passed = vp(
             vp_eval,
@@ -993,7 +994,7 @@ mod tests {
         )
         .expect("unexpected error converting wat2wasm").into_owned();
 
-        let tx = Tx::new(vec![], None);
+        let tx = Tx::new(vec![], None, ChainId::default(), None);
         let tx_index = TxIndex::default();
         let mut storage = TestStorage::default();
         let addr = storage.address_gen.generate_address("rng seed");
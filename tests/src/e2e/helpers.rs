@@ -171,6 +171,14 @@ pub fn get_epoch(test: &Test, ledger_address: &str) -> Result<Epoch> {
 
 /// Get the last committed block height.
 pub fn get_height(test: &Test, ledger_address: &str) -> Result<u64> {
+    get_last_block(test, ledger_address).map(|(_hash, height)| height)
+}
+
+/// Get the hash and the height of the last committed block.
+pub fn get_last_block(
+    test: &Test,
+    ledger_address: &str,
+) -> Result<(String, u64)> {
     let mut find = run!(
         test,
         Bin::Client,
@@ -183,25 +191,26 @@ pub fn get_height(test: &Test, ledger_address: &str) -> Result<u64> {
     // ```
     // Last committed block F10B5E77F972F68CA051D289474B6E75574B446BF713A7B7B71D7ECFC61A3B21, height: 4, time: 2022-10-20T10:52:28.828745Z
     // ```
-    let height_str = strip_trailing_newline(&matched)
+    let (hash_str, height_str) = strip_trailing_newline(&matched)
         .trim()
         // Find the height part ...
-        .split_once("height: ")
-        .unwrap()
-        // ... take what's after it ...
-        .1
-        // ... find the next comma ...
+        .split_once(", height: ")
+        .unwrap();
+    let hash_str = hash_str.rsplit_once(' ').unwrap().1;
+    // ... find the next comma ...
+    let height_str = height_str
         .rsplit_once(',')
         .unwrap()
         // ... and take what's before it.
         .0;
-    u64::from_str(height_str).map_err(|e| {
+    let height = u64::from_str(height_str).map_err(|e| {
         eyre!(format!(
             "Height parsing failed from {} trimmed from {}, Error: \
              {}\n\nUnread output: {}",
             height_str, matched, e, unread
         ))
-    })
+    })?;
+    Ok((hash_str.to_owned(), height))
 }
 
 /// Sleep until the given height is reached or panic when time out is reached
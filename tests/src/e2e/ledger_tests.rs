@@ -28,7 +28,9 @@ use namada_apps::config::genesis::genesis_config::{
 use serde_json::json;
 use setup::constants::*;
 
-use super::helpers::{get_height, is_debug_mode, wait_for_block_height};
+use super::helpers::{
+    get_height, get_last_block, is_debug_mode, wait_for_block_height,
+};
 use super::setup::get_all_wasms_hashes;
 use crate::e2e::helpers::{
     epoch_sleep, find_address, find_bonded_stake, get_actor_rpc, get_epoch,
@@ -3367,6 +3369,83 @@ fn implicit_account_reveal_pk() -> Result<()> {
     Ok(())
 }
 
+/// In this test we:
+/// 1. Run a validator node that takes snapshots of its state
+/// 2. Find a trusted block on the validator
+/// 3. Wait for the validator to take a snapshot
+/// 4. Run a non-validator node configured to state sync from the validator
+/// 5. Check that the non-validator restores the state from the snapshot and
+///    keeps syncing from there
+#[test]
+fn test_state_sync() -> Result<()> {
+    use std::net::SocketAddr;
+
+    use namada_apps::config::{Config, StateSync, TendermintMode};
+
+    let test = setup::single_node_net()?;
+    let chain_id = test.net.chain_id.clone();
+    let snapshot_interval = 5;
+
+    // 1. Run a validator node that takes snapshots of its state
+    let validator_base_dir = test.get_base_dir(&Who::Validator(0));
+    let mut validator_config =
+        Config::load(&validator_base_dir, &chain_id, None);
+    validator_config.ledger.shell.snapshot_interval = Some(snapshot_interval);
+    validator_config
+        .write(&validator_base_dir, &chain_id, true)
+        .unwrap();
+
+    let mut validator =
+        run_as!(test, Who::Validator(0), Bin::Node, &["ledger"], Some(40))?;
+    validator.exp_string("Anoma ledger node started")?;
+    let _bg_validator = validator.background();
+
+    // 2. Find a trusted block on the validator
+    let validator_rpc = get_actor_rpc(&test, &Who::Validator(0));
+    wait_for_block_height(&test, &validator_rpc, 1, 30)?;
+    let (trust_hash, trust_height) = get_last_block(&test, &validator_rpc)?;
+
+    // 3. Wait for the validator to take a snapshot, with a few more blocks
+    // for the non-validator to verify it with
+    let snapshot_height =
+        (trust_height / snapshot_interval + 1) * snapshot_interval;
+    wait_for_block_height(&test, &validator_rpc, snapshot_height + 2, 60)?;
+
+    // 4. Run a non-validator node configured to state sync from the
+    // validator
+    let non_validator_base_dir = test.get_base_dir(&Who::NonValidator);
+    let mut non_validator_config = Config::load(
+        &non_validator_base_dir,
+        &chain_id,
+        Some(TendermintMode::Full),
+    );
+    let rpc_server = SocketAddr::from_str(&validator_rpc).unwrap();
+    non_validator_config.ledger.tendermint.state_sync = Some(StateSync {
+        // Tendermint requires at least two RPC servers
+        rpc_servers: vec![rpc_server, rpc_server],
+        trust_height,
+        trust_hash,
+    });
+    non_validator_config
+        .write(&non_validator_base_dir, &chain_id, true)
+        .unwrap();
+
+    let mut non_validator =
+        run_as!(test, Who::NonValidator, Bin::Node, &["ledger"], Some(60))?;
+    non_validator.exp_string("Anoma ledger node started")?;
+
+    // 5. Check that the non-validator restores the state from the snapshot
+    // and keeps syncing from there
+    non_validator.exp_string("Restored the state from a snapshot at height")?;
+    let _bg_non_validator = non_validator.background();
+
+    let non_validator_rpc = get_actor_rpc(&test, &Who::NonValidator);
+    let height = get_height(&test, &validator_rpc)?;
+    wait_for_block_height(&test, &non_validator_rpc, height, 30)?;
+
+    Ok(())
+}
+
 /// Prepare proposal data in the test's temp dir from the given source address.
 /// This can be submitted with "init-proposal" command.
 fn prepare_proposal_data(test: &setup::Test, source: Address) -> PathBuf {
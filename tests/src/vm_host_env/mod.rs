@@ -30,6 +30,7 @@ mod tests {
     use namada::ledger::tx_env::TxEnv;
     use namada::proto::{SignedTxData, Tx};
     use namada::tendermint_proto::Protobuf;
+    use namada::types::chain::ChainId;
     use namada::types::key::*;
     use namada::types::storage::{self, BlockHash, BlockHeight, Key, KeySeg};
     use namada::types::time::DateTimeUtc;
@@ -469,7 +470,13 @@ mod tests {
             None,
         ] {
             let signed_tx_data = vp_host_env::with(|env| {
-                env.tx = Tx::new(code.clone(), data.clone()).sign(&keypair);
+                env.tx = Tx::new(
+                    code.clone(),
+                    data.clone(),
+                    ChainId::default(),
+                    None,
+                )
+                .sign(&keypair);
                 let tx_data = env.tx.data.as_ref().expect("data should exist");
 
                 SignedTxData::try_from_slice(&tx_data[..])
@@ -566,6 +573,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
         // get and increment the connection counter
@@ -603,6 +612,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
 
@@ -636,6 +647,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
         // get and update the client without a header
@@ -681,6 +694,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
         // update the client with the message
@@ -710,6 +725,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
         // upgrade the client with the message
@@ -748,6 +765,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
         // get and increment the connection counter
@@ -785,6 +804,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
         // init a connection with the message
@@ -811,6 +832,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
         // open the connection with the message
@@ -847,6 +870,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
         // open try a connection with the message
@@ -874,6 +899,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
         // open the connection with the mssage
@@ -915,6 +942,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
         // not bind a port
@@ -956,6 +985,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
         // bind a port
@@ -1000,6 +1031,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
         // init a channel with the message
@@ -1024,6 +1057,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
         // open the channle with the message
@@ -1062,6 +1097,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
         // try open a channel with the message
@@ -1087,6 +1124,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
         // open a channel with the message
@@ -1127,6 +1166,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
         // close the channel with the message
@@ -1167,6 +1208,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
 
@@ -1212,6 +1255,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
         // send the token and a packet with the data
@@ -1252,6 +1297,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
         // ack the packet with the message
@@ -1301,6 +1348,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
         // send the token and a packet with the data
@@ -1366,6 +1415,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
         // receive a packet with the message
@@ -1443,6 +1494,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
         // receive a packet with the message
@@ -1490,6 +1543,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
         // send a packet with the message
@@ -1519,6 +1574,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
         // ack the packet with the message
@@ -1570,6 +1627,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
         // receive a packet with the message
@@ -1632,6 +1691,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
 
@@ -1704,6 +1765,8 @@ mod tests {
             code: vec![],
             data: Some(tx_data.clone()),
             timestamp: DateTimeUtc::now(),
+            chain_id: ChainId::default(),
+            expiration: None,
         }
         .sign(&key::testing::keypair_1());
 
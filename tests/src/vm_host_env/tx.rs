@@ -9,6 +9,7 @@ use namada::ledger::storage::testing::TestStorage;
 use namada::ledger::storage::write_log::WriteLog;
 use namada::proto::Tx;
 use namada::types::address::Address;
+use namada::types::chain::ChainId;
 use namada::types::storage::{Key, TxIndex};
 use namada::types::time::DurationSecs;
 use namada::types::{key, token};
@@ -77,7 +78,7 @@ impl Default for TestTxEnv {
             vp_cache_dir,
             tx_wasm_cache,
             tx_cache_dir,
-            tx: Tx::new(vec![], None),
+            tx: Tx::new(vec![], None, ChainId::default(), None),
         }
     }
 }
@@ -183,6 +184,18 @@ impl TestTxEnv {
             .unwrap();
     }
 
+    /// Set the multisignature public keys and threshold for the address.
+    pub fn write_multisig(
+        &mut self,
+        address: &Address,
+        multisig: &key::Multisig,
+    ) {
+        let storage_key = key::multisig_key(address);
+        self.storage
+            .write(&storage_key, multisig.try_to_vec().unwrap())
+            .unwrap();
+    }
+
     /// Apply the tx changes to the write log.
     pub fn execute_tx(&mut self) -> Result<(), Error> {
         let empty_data = vec![];
@@ -6,6 +6,7 @@ use namada::ledger::storage::testing::TestStorage;
 use namada::ledger::storage::write_log::WriteLog;
 use namada::proto::Tx;
 use namada::types::address::{self, Address};
+use namada::types::chain::ChainId;
 use namada::types::storage::{self, Key, TxIndex};
 use namada::vm::prefix_iter::PrefixIterators;
 use namada::vm::wasm::{self, VpCache};
@@ -67,7 +68,7 @@ impl Default for TestVpEnv {
             write_log: WriteLog::default(),
             iterators: PrefixIterators::default(),
             gas_meter: VpGasMeter::default(),
-            tx: Tx::new(vec![], None),
+            tx: Tx::new(vec![], None, ChainId::default(), None),
             tx_index: TxIndex::default(),
             keys_changed: BTreeSet::default(),
             verifiers: BTreeSet::default(),
@@ -34,6 +34,9 @@ pub fn init_proposal(ctx: &mut Ctx, data: InitProposalData) -> TxResult {
     let grace_epoch_key = storage::get_grace_epoch_key(proposal_id);
     ctx.write(&grace_epoch_key, data.grace_epoch)?;
 
+    let proposal_type_key = storage::get_proposal_type_key(proposal_id);
+    ctx.write(&proposal_type_key, data.r#type)?;
+
     if let Some(proposal_code) = data.proposal_code {
         let proposal_code_key = storage::get_proposal_code_key(proposal_id);
         ctx.write_bytes(&proposal_code_key, proposal_code)?;
@@ -4,6 +4,15 @@ pub use namada::types::key::*;
 
 use super::*;
 
+/// Replace the PK of an established account with a new one.
+pub fn update_pk(
+    ctx: &mut Ctx,
+    owner: &Address,
+    pk: &common::PublicKey,
+) -> EnvResult<()> {
+    storage_api::key::update_pk(ctx, owner, pk)
+}
+
 /// Reveal a PK of an implicit account - the PK is written into the storage
 /// of the address derived from the PK.
 pub fn reveal_pk(ctx: &mut Ctx, pk: &common::PublicKey) -> EnvResult<()> {
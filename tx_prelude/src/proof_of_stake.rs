@@ -2,7 +2,7 @@
 
 pub use namada::ledger::pos::*;
 use namada::ledger::pos::{
-    bond_key, namada_proof_of_stake, params_key, unbond_key,
+    bond_key, bond_rewards_key, namada_proof_of_stake, params_key, unbond_key,
     validator_address_raw_hash_key, validator_commission_rate_key,
     validator_consensus_key_key, validator_deltas_key,
     validator_max_commission_rate_change_key, validator_set_key,
@@ -89,6 +89,33 @@ impl Ctx {
         )
     }
 
+    /// Unjail a validator that has been jailed for missing too many blocks.
+    /// The validator is added to the verifiers of the tx, so that its VP
+    /// checks the tx's signature.
+    pub fn unjail_validator(&mut self, validator: &Address) -> TxResult {
+        self.insert_verifier(validator)?;
+        let current_epoch = self.get_block_epoch()?;
+        namada_proof_of_stake::PosActions::unjail_validator(
+            self,
+            validator,
+            current_epoch,
+        )
+    }
+
+    /// Claim the inflation rewards of a self-bond to a validator when
+    /// `source` is `None` or equal to the `validator` address, or of the
+    /// tokens delegated to the `validator` to the `source`. Returns the
+    /// claimed amount.
+    pub fn claim_rewards(
+        &mut self,
+        source: Option<&Address>,
+        validator: &Address,
+    ) -> EnvResult<token::Amount> {
+        namada_proof_of_stake::PosActions::claim_rewards(
+            self, source, validator,
+        )
+    }
+
     /// Attempt to initialize a validator account. On success, returns the
     /// initialized validator account's address.
     pub fn init_validator(
@@ -133,8 +160,10 @@ namada::impl_pos_read_only! {
 impl namada_proof_of_stake::PosActions for Ctx {
     type BecomeValidatorError = crate::Error;
     type BondError = crate::Error;
+    type ClaimRewardsError = crate::Error;
     type CommissionRateChangeError = crate::Error;
     type UnbondError = crate::Error;
+    type UnjailError = crate::Error;
     type WithdrawError = crate::Error;
 
     fn write_pos_params(
@@ -193,6 +222,14 @@ impl namada_proof_of_stake::PosActions for Ctx {
         self.write(&validator_deltas_key(key), &value)
     }
 
+    fn write_bond_rewards(
+        &mut self,
+        key: &BondId,
+        value: token::Amount,
+    ) -> Result<(), Self::Error> {
+        self.write(&bond_rewards_key(key), value)
+    }
+
     fn write_bond(
         &mut self,
         key: &BondId,
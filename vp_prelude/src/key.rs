@@ -10,3 +10,24 @@ use super::*;
 pub fn get(ctx: &Ctx, owner: &Address) -> EnvResult<Option<common::PublicKey>> {
     storage_api::key::get(&ctx.pre(), owner)
 }
+
+/// Get the public keys and threshold of a multisignature account from the
+/// state prior to tx execution. Returns `Ok(None)` if the account is not a
+/// multisignature account.
+pub fn get_multisig(ctx: &Ctx, owner: &Address) -> EnvResult<Option<Multisig>> {
+    storage_api::key::get_multisig(&ctx.pre(), owner)
+}
+
+/// Check that the tx has been signed by at least the threshold of the keys of
+/// a multisignature account.
+pub fn verify_multisig(
+    ctx: &Ctx,
+    multisig: &Multisig,
+    signed_tx_data: &SignedTxData,
+) -> EnvResult<bool> {
+    let valid = multisig
+        .verify_threshold(&signed_tx_data.sigs, |pk, sig| {
+            ctx.verify_tx_signature(pk, sig)
+        })?;
+    Ok(valid.is_some())
+}
@@ -17,6 +17,7 @@ use std::convert::TryFrom;
 use std::marker::PhantomData;
 
 pub use borsh::{BorshDeserialize, BorshSerialize};
+pub use namada::ledger::eth_bridge::storage as eth_bridge_storage;
 pub use namada::ledger::governance::storage as gov_storage;
 pub use namada::ledger::storage_api::{
     self, iter_prefix, iter_prefix_bytes, rev_iter_prefix,
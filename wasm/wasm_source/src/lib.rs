@@ -2,6 +2,8 @@
 pub mod tx_bond;
 #[cfg(feature = "tx_change_validator_commission")]
 pub mod tx_change_validator_commission;
+#[cfg(feature = "tx_claim_rewards")]
+pub mod tx_claim_rewards;
 #[cfg(feature = "tx_ibc")]
 pub mod tx_ibc;
 #[cfg(feature = "tx_init_account")]
@@ -16,6 +18,10 @@ pub mod tx_reveal_pk;
 pub mod tx_transfer;
 #[cfg(feature = "tx_unbond")]
 pub mod tx_unbond;
+#[cfg(feature = "tx_unjail_validator")]
+pub mod tx_unjail_validator;
+#[cfg(feature = "tx_update_key")]
+pub mod tx_update_key;
 #[cfg(feature = "tx_update_vp")]
 pub mod tx_update_vp;
 #[cfg(feature = "tx_vote_proposal")]
@@ -0,0 +1,204 @@
+//! A tx to claim the inflation rewards accumulated in the rewards pool of a
+//! self-bond or a delegation.
+
+use namada_tx_prelude::*;
+
+#[transaction]
+fn apply_tx(ctx: &mut Ctx, tx_data: Vec<u8>) -> TxResult {
+    let signed = SignedTxData::try_from_slice(&tx_data[..])
+        .wrap_err("failed to decode SignedTxData")?;
+    let data = signed.data.ok_or_err_msg("Missing data")?;
+    let claim = transaction::pos::ClaimRewards::try_from_slice(&data[..])
+        .wrap_err("failed to decode ClaimRewards")?;
+    let claimed = ctx.claim_rewards(claim.source.as_ref(), &claim.validator)?;
+    debug_log!("Claimed rewards {}", claimed);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use namada::ledger::pos::namada_proof_of_stake::PosBase;
+    use namada::ledger::pos::PosParams;
+    use namada::proto::Tx;
+    use namada::types::chain::ChainId;
+    use namada::types::storage::Epoch;
+    use namada_tests::log::test;
+    use namada_tests::native_vp::pos::init_pos;
+    use namada_tests::native_vp::TestNativeVpEnv;
+    use namada_tests::tx::*;
+    use namada_tx_prelude::address::InternalAddress;
+    use namada_tx_prelude::key::RefTo;
+    use namada_vp_prelude::proof_of_stake::types::Bond;
+    use namada_vp_prelude::proof_of_stake::{
+        bond_rewards_key, BondId, Bonds, GenesisValidator, PosVP,
+    };
+    use rust_decimal::Decimal;
+
+    use super::*;
+
+    /// Setup the PoS system with a genesis validator with a self-bond of
+    /// 1000 tokens and a commission rate of 5%, and a delegation of the given
+    /// amount to it. Returns the native token.
+    fn setup(
+        validator: &Address,
+        delegator: &Address,
+        delegation: token::Amount,
+    ) -> Address {
+        let pos_params = PosParams::default();
+        let genesis_validators = [GenesisValidator {
+            address: validator.clone(),
+            tokens: token::Amount::whole(1_000),
+            consensus_key: key::testing::keypair_1().ref_to(),
+            commission_rate: Decimal::new(5, 2),
+            max_commission_rate_change: Decimal::new(1, 2),
+        }];
+        init_pos(&genesis_validators[..], &pos_params, Epoch(0));
+
+        let native_token = tx_host_env::with(|tx_env| {
+            tx_env.spawn_accounts([delegator]);
+            let bond_id = BondId {
+                source: delegator.clone(),
+                validator: validator.clone(),
+            };
+            let bond = Bond {
+                pos_deltas: HashMap::from([(Epoch(0), delegation)]),
+                neg_deltas: token::Amount::default(),
+            };
+            tx_env
+                .storage
+                .write_bond(&bond_id, &Bonds::init_at_genesis(bond, Epoch(0)));
+            tx_env.storage.native_token.clone()
+        });
+        tx_host_env::commit_tx_and_block();
+        native_token
+    }
+
+    /// Mint the given inflation as the rewards earned by the validator in
+    /// the epoch 0.
+    fn distribute_rewards(validator: &Address, inflation: token::Amount) {
+        tx_host_env::with(|tx_env| {
+            tx_env.storage.write_rewards_accumulator(&BTreeMap::from([(
+                validator.clone(),
+                Decimal::ONE,
+            )]));
+            let minted = tx_env.storage.distribute_rewards(inflation, Epoch(0));
+            assert_eq!(minted, inflation);
+        });
+    }
+
+    /// Test that the rewards of a validator are split between its bonds pro
+    /// rata to their amounts after its commission is taken, and that a
+    /// delegator can claim the rewards of its delegation.
+    #[test]
+    fn test_tx_claim_delegation_rewards() -> TxResult {
+        let validator = address::testing::established_address_1();
+        let delegator = address::testing::established_address_2();
+        let native_token =
+            setup(&validator, &delegator, token::Amount::whole(1_000));
+        distribute_rewards(&validator, token::Amount::whole(100));
+
+        // The commission of 5 tokens goes to the validator and the remaining
+        // 95 tokens are split evenly between the two bonds
+        let self_bond_id = BondId {
+            source: validator.clone(),
+            validator: validator.clone(),
+        };
+        let delegation_id = BondId {
+            source: delegator.clone(),
+            validator: validator.clone(),
+        };
+        assert_eq!(
+            ctx().read_bond_rewards(&self_bond_id)?,
+            token::Amount::from(52_500_000)
+        );
+        assert_eq!(
+            ctx().read_bond_rewards(&delegation_id)?,
+            token::Amount::from(47_500_000)
+        );
+        tx_host_env::commit_tx_and_block();
+
+        let tx_code = vec![];
+        let tx_data = transaction::pos::ClaimRewards {
+            validator: validator.clone(),
+            source: Some(delegator.clone()),
+        }
+        .try_to_vec()
+        .unwrap();
+        let tx = Tx::new(tx_code, Some(tx_data), ChainId::default(), None);
+        let signed_tx = tx.sign(&key::testing::keypair_2());
+        let tx_data = signed_tx.data.unwrap();
+
+        let pos_balance_key = token::balance_key(
+            &native_token,
+            &Address::Internal(InternalAddress::PoS),
+        );
+        let pos_balance_pre: token::Amount =
+            ctx().read(&pos_balance_key)?.unwrap();
+
+        apply_tx(ctx(), tx_data)?;
+
+        let pos_balance_post: token::Amount =
+            ctx().read(&pos_balance_key)?.unwrap();
+        let rewards = token::Amount::from(47_500_000);
+        assert_eq!(pos_balance_pre - pos_balance_post, rewards);
+        let delegator_balance: token::Amount = ctx()
+            .read(&token::balance_key(&native_token, &delegator))?
+            .unwrap();
+        assert_eq!(delegator_balance, rewards);
+        assert_eq!(
+            ctx().read_bond_rewards(&delegation_id)?,
+            token::Amount::default()
+        );
+        // The validator's rewards are left to be claimed
+        assert_eq!(
+            ctx().read_bond_rewards(&self_bond_id)?,
+            token::Amount::from(52_500_000)
+        );
+
+        // Use the tx_env to run PoS VP
+        let tx_env = tx_host_env::take();
+        let vp_env = TestNativeVpEnv::from_tx_env(tx_env, address::POS);
+        let result = vp_env.validate_tx(PosVP::new);
+        let result =
+            result.expect("Validation of valid changes must not fail!");
+        assert!(
+            result,
+            "PoS Validity predicate must accept this transaction"
+        );
+        Ok(())
+    }
+
+    /// Test that the PoS VP rejects a transaction that adds tokens to a
+    /// rewards pool.
+    #[test]
+    fn test_tx_increase_rewards_pool_rejected() -> TxResult {
+        let validator = address::testing::established_address_1();
+        let delegator = address::testing::established_address_2();
+        setup(&validator, &delegator, token::Amount::whole(1_000));
+        distribute_rewards(&validator, token::Amount::whole(100));
+        tx_host_env::commit_tx_and_block();
+
+        let delegation_id = BondId {
+            source: delegator,
+            validator,
+        };
+        ctx().write(
+            &bond_rewards_key(&delegation_id),
+            token::Amount::whole(100),
+        )?;
+
+        // Use the tx_env to run PoS VP
+        let tx_env = tx_host_env::take();
+        let vp_env = TestNativeVpEnv::from_tx_env(tx_env, address::POS);
+        let result = vp_env.validate_tx(PosVP::new);
+        let result =
+            result.expect("Validation of invalid changes must not fail!");
+        assert!(
+            !result,
+            "PoS Validity predicate must reject this transaction"
+        );
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 //! A tx to initialize a new established address with a given public key and
-//! a validity predicate.
+//! a validity predicate, optionally with the public keys and threshold of a
+//! multisignature account and the metadata of a token account.
 
 use namada_tx_prelude::*;
 
@@ -15,5 +16,23 @@ fn apply_tx(ctx: &mut Ctx, tx_data: Vec<u8>) -> TxResult {
     let address = ctx.init_account(&tx_data.vp_code)?;
     let pk_key = key::pk_key(&address);
     ctx.write(&pk_key, &tx_data.public_key)?;
+    if let Some(multisig) = tx_data.multisig {
+        if !multisig.is_valid() {
+            return Err(Error::new_const(
+                "The multisignature threshold cannot be reached",
+            ));
+        }
+        let multisig_key = key::multisig_key(&address);
+        ctx.write(&multisig_key, &multisig)?;
+    }
+    if let Some(metadata) = tx_data.token_metadata {
+        if !metadata.is_valid() {
+            return Err(Error::new_const("The token metadata is invalid"));
+        }
+        // The token metadata VP only accepts the metadata of an account
+        // initialized in the same tx
+        let metadata_key = token::metadata_key(&address);
+        ctx.write(&metadata_key, &metadata)?;
+    }
     Ok(())
 }
@@ -0,0 +1,89 @@
+//! A tx for a validator to unjail itself after it has been jailed for
+//! missing too many blocks.
+
+use namada_tx_prelude::*;
+
+#[transaction]
+fn apply_tx(ctx: &mut Ctx, tx_data: Vec<u8>) -> TxResult {
+    let signed = SignedTxData::try_from_slice(&tx_data[..])
+        .wrap_err("failed to decode SignedTxData")?;
+    let data = signed.data.ok_or_err_msg("Missing data")?;
+    let validator = Address::try_from_slice(&data[..])
+        .wrap_err("failed to decode an Address")?;
+    ctx.unjail_validator(&validator)
+}
+
+#[cfg(test)]
+mod tests {
+    use namada::ledger::pos::namada_proof_of_stake::PosBase;
+    use namada::ledger::pos::PosParams;
+    use namada::proto::Tx;
+    use namada::types::chain::ChainId;
+    use namada::types::storage::Epoch;
+    use namada_tests::log::test;
+    use namada_tests::native_vp::pos::init_pos;
+    use namada_tests::native_vp::TestNativeVpEnv;
+    use namada_tests::tx::*;
+    use namada_tx_prelude::key::RefTo;
+    use namada_tx_prelude::token;
+    use namada_vp_prelude::proof_of_stake::types::ValidatorState;
+    use namada_vp_prelude::proof_of_stake::{GenesisValidator, PosVP};
+
+    use super::*;
+
+    /// Initialize PoS with the given validator and jail it in the first
+    /// epoch, then move to the epoch in which its jail takes effect
+    fn setup(validator: &Address, pos_params: &PosParams) {
+        let genesis_validators = [GenesisValidator {
+            address: validator.clone(),
+            tokens: token::Amount::from(1_000_000),
+            consensus_key: key::testing::keypair_1().ref_to(),
+            commission_rate: rust_decimal::Decimal::new(5, 2),
+            max_commission_rate_change: rust_decimal::Decimal::new(1, 2),
+        }];
+        init_pos(&genesis_validators[..], pos_params, Epoch(0));
+
+        tx_host_env::with(|tx_env| {
+            tx_env
+                .storage
+                .jail_validator(
+                    pos_params,
+                    Epoch(0),
+                    Epoch(0),
+                    0_u64,
+                    validator,
+                )
+                .unwrap();
+            tx_env.storage.block.epoch = Epoch(pos_params.pipeline_len);
+        });
+    }
+
+    /// Test that a jailed validator can unjail itself once its jail has taken
+    /// effect and that the tx adds it to the verifiers
+    #[test]
+    fn test_tx_unjail_validator() {
+        let validator = address::testing::established_address_1();
+        let pos_params = PosParams::default();
+        setup(&validator, &pos_params);
+
+        let tx_data = validator.try_to_vec().unwrap();
+        let tx = Tx::new(vec![], Some(tx_data), ChainId::default(), None);
+        let tx_data = tx.sign(&key::testing::keypair_1()).data.unwrap();
+        apply_tx(ctx(), tx_data).unwrap();
+
+        let state = ctx().read_validator_state(&validator).unwrap().unwrap();
+        let pipeline_epoch = Epoch(2 * pos_params.pipeline_len);
+        assert_eq!(state.get(pipeline_epoch), Some(&ValidatorState::Candidate));
+
+        let tx_env = tx_host_env::take();
+        assert!(tx_env.verifiers.contains(&validator));
+        let vp_env = TestNativeVpEnv::from_tx_env(tx_env, address::POS);
+        let result = vp_env.validate_tx(PosVP::new);
+        let result =
+            result.expect("Validation of valid changes must not fail!");
+        assert!(
+            result,
+            "PoS Validity predicate must accept this transaction"
+        );
+    }
+}
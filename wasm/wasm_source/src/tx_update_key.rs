@@ -0,0 +1,20 @@
+//! A tx for rotating the public key of an established account.
+//! This tx wraps the new public key inside `SignedTxData` as its input as
+//! declared in `shared` crate. It must be signed with the account's current
+//! key and carry the new key's proof of possession, which are checked by the
+//! account's validity predicate.
+
+use namada_tx_prelude::*;
+
+#[transaction]
+fn apply_tx(ctx: &mut Ctx, tx_data: Vec<u8>) -> TxResult {
+    let signed = SignedTxData::try_from_slice(&tx_data[..])
+        .wrap_err("failed to decode SignedTxData")?;
+    let data = signed.data.ok_or_err_msg("Missing data")?;
+    let update_key = transaction::UpdateKey::try_from_slice(&data[..])
+        .wrap_err("failed to decode UpdateKey")?;
+
+    debug_log!("update key for: {:#?}", update_key.addr);
+
+    key::update_pk(ctx, &update_key.addr, &update_key.public_key)
+}
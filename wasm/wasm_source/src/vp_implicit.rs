@@ -34,6 +34,10 @@ impl<'a> From<&'a storage::Key> for KeyType<'a> {
             token::is_any_multitoken_balance_key(key)
         {
             Self::Token(address)
+        } else if let Some((_, address)) =
+            eth_bridge_storage::is_wrapped_erc20_balance_key(key)
+        {
+            Self::Token(address)
         } else if proof_of_stake::is_pos_key(key) {
             Self::PoS
         } else if gov_storage::is_vote_key(key) {
@@ -148,11 +152,12 @@ fn validate_tx(
             KeyType::PoS => {
                 // Allow the account to be used in PoS
                 let bond_id = proof_of_stake::is_bond_key(key)
-                    .or_else(|| proof_of_stake::is_unbond_key(key));
+                    .or_else(|| proof_of_stake::is_unbond_key(key))
+                    .or_else(|| proof_of_stake::is_bond_rewards_key(key));
                 let valid = match bond_id {
                     Some(bond_id) => {
-                        // Bonds and unbonds changes for this address
-                        // must be signed
+                        // Bonds, unbonds and rewards claims changes for this
+                        // address must be signed
                         bond_id.source != addr || *valid_sig
                     }
                     None => {
@@ -7,6 +7,14 @@
 //! valid signature.
 //!
 //! Any other storage key changes are allowed only with a valid signature.
+//!
+//! If the account has multisignature public keys in its storage, a valid
+//! signature requires the signatures of at least the threshold of these keys
+//! instead of the signature of its public key.
+//!
+//! The public key can be rotated with a valid signature of the key it
+//! replaces and a proof of possession of the new key, but it cannot be
+//! removed.
 
 use namada_vp_prelude::address::masp;
 use namada_vp_prelude::storage::KeySeg;
@@ -19,6 +27,8 @@ enum KeyType<'a> {
     Vp(&'a Address),
     Masp,
     GovernanceVote(&'a Address),
+    Multisig(&'a Address),
+    PublicKey(&'a Address),
     Unknown,
 }
 
@@ -30,6 +40,10 @@ impl<'a> From<&'a storage::Key> for KeyType<'a> {
             token::is_any_multitoken_balance_key(key)
         {
             Self::Token(address)
+        } else if let Some((_, address)) =
+            eth_bridge_storage::is_wrapped_erc20_balance_key(key)
+        {
+            Self::Token(address)
         } else if proof_of_stake::is_pos_key(key) {
             Self::PoS
         } else if gov_storage::is_vote_key(key) {
@@ -43,6 +57,10 @@ impl<'a> From<&'a storage::Key> for KeyType<'a> {
             Self::Vp(address)
         } else if token::is_masp_key(key) {
             Self::Masp
+        } else if let Some(address) = key::is_multisig_key(key) {
+            Self::Multisig(address)
+        } else if let Some(address) = key::is_pk_key(key) {
+            Self::PublicKey(address)
         } else {
             Self::Unknown
         }
@@ -68,18 +86,27 @@ fn validate_tx(
         Lazy::new(|| SignedTxData::try_from_slice(&tx_data[..]));
 
     let valid_sig = Lazy::new(|| match &*signed_tx_data {
-        Ok(signed_tx_data) => {
-            let pk = key::get(ctx, &addr);
-            match pk {
-                Ok(Some(pk)) => {
-                    matches!(
-                        ctx.verify_tx_signature(&pk, &signed_tx_data.sig),
-                        Ok(true)
-                    )
+        Ok(signed_tx_data) => match key::get_multisig(ctx, &addr) {
+            Ok(Some(multisig)) => {
+                matches!(
+                    key::verify_multisig(ctx, &multisig, signed_tx_data),
+                    Ok(true)
+                )
+            }
+            Ok(None) => {
+                let pk = key::get(ctx, &addr);
+                match pk {
+                    Ok(Some(pk)) => {
+                        matches!(
+                            ctx.verify_tx_signature(&pk, &signed_tx_data.sig),
+                            Ok(true)
+                        )
+                    }
+                    _ => false,
                 }
-                _ => false,
             }
-        }
+            Err(_) => false,
+        },
         _ => false,
     });
 
@@ -123,11 +150,12 @@ fn validate_tx(
             KeyType::PoS => {
                 // Allow the account to be used in PoS
                 let bond_id = proof_of_stake::is_bond_key(key)
-                    .or_else(|| proof_of_stake::is_unbond_key(key));
+                    .or_else(|| proof_of_stake::is_unbond_key(key))
+                    .or_else(|| proof_of_stake::is_bond_rewards_key(key));
                 let valid = match bond_id {
                     Some(bond_id) => {
-                        // Bonds and unbonds changes for this address
-                        // must be signed
+                        // Bonds, unbonds and rewards claims changes for this
+                        // address must be signed
                         bond_id.source != addr || *valid_sig
                     }
                     None => {
@@ -163,6 +191,33 @@ fn validate_tx(
                     is_vp_whitelisted(ctx, &vp)?
                 }
             }
+            KeyType::Multisig(owner) => {
+                if owner == &addr {
+                    // The keys must be changed with a valid signature and the
+                    // new threshold must be reachable
+                    let multisig: Option<key::Multisig> = ctx.read_post(key)?;
+                    *valid_sig
+                        && multisig.map_or(true, |multisig| multisig.is_valid())
+                } else {
+                    true
+                }
+            }
+            KeyType::PublicKey(owner) => {
+                if owner == &addr {
+                    // The signature is checked against the key prior to the
+                    // tx, so only the current key can rotate itself. The new
+                    // key must prove that it is held, so that the account
+                    // cannot be locked by a key nobody can sign with.
+                    let pk: Option<key::common::PublicKey> =
+                        ctx.read_post(key)?;
+                    *valid_sig
+                        && pk.map_or(false, |pk| {
+                            is_key_possessed(&*signed_tx_data, &addr, &pk)
+                        })
+                } else {
+                    true
+                }
+            }
             KeyType::Masp => true,
             KeyType::Unknown => {
                 if key.segments.get(0) == Some(&addr.to_db_key()) {
@@ -184,6 +239,25 @@ fn validate_tx(
     accept()
 }
 
+/// Check that the tx data rotates the key of the account to the given public
+/// key with a valid proof of its possession
+fn is_key_possessed(
+    signed_tx_data: &Result<SignedTxData, std::io::Error>,
+    addr: &Address,
+    pk: &key::common::PublicKey,
+) -> bool {
+    signed_tx_data
+        .as_ref()
+        .ok()
+        .and_then(|signed_tx_data| signed_tx_data.data.as_ref())
+        .and_then(|data| transaction::UpdateKey::try_from_slice(data).ok())
+        .map_or(false, |update_key| {
+            &update_key.addr == addr
+                && &update_key.public_key == pk
+                && update_key.verify_possession()
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use address::testing::arb_non_internal_address;
@@ -310,6 +384,54 @@ mod tests {
         );
     }
 
+    /// Test that a tx of a third party that debits the VP owner's balance of
+    /// a wrapped ERC20 token without its signature is rejected, even if the
+    /// VP owner is one of the verifiers.
+    #[test]
+    fn test_unsigned_wrapped_erc20_debit_rejected() {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+
+        let vp_owner = address::testing::established_address_1();
+        let third_party = address::testing::established_address_2();
+        let asset = ethereum_events::EthAddress([1; 20]);
+        let amount = token::Amount::from(10_098_123);
+        let owner_key =
+            eth_bridge_storage::wrapped_erc20_balance_key(&asset, &vp_owner);
+        let third_party_key =
+            eth_bridge_storage::wrapped_erc20_balance_key(&asset, &third_party);
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner, &third_party]);
+
+        // Credit the wrapped tokens to the VP owner before running the
+        // transaction
+        tx_env
+            .storage
+            .write(&owner_key, amount.try_to_vec().unwrap())
+            .unwrap();
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |_address| {
+            // Move the wrapped tokens to the third party in a transaction
+            tx::ctx()
+                .write(&owner_key, token::Amount::default())
+                .unwrap();
+            tx::ctx().write(&third_party_key, amount).unwrap();
+        });
+
+        let vp_env = vp_host_env::take();
+        let tx_data: Vec<u8> = vec![];
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: BTreeSet<Address> = [vp_owner.clone()].into();
+        vp_host_env::set(vp_env);
+        assert!(
+            !validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers)
+                .unwrap()
+        );
+    }
+
     /// Test that a debit transfer with a valid signature is accepted.
     #[test]
     fn test_signed_debit_transfer_accepted() {
@@ -363,6 +485,219 @@ mod tests {
         );
     }
 
+    /// Test that a debit transfer from a multisignature account is accepted
+    /// only with the signatures of the threshold of its keys.
+    #[test]
+    fn test_multisig_debit_transfer() {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+
+        let vp_owner = address::testing::established_address_1();
+        let keypair_1 = key::testing::keypair_1();
+        let keypair_2 = key::testing::keypair_2();
+        let multisig = key::Multisig {
+            public_keys: vec![keypair_1.ref_to(), keypair_2.ref_to()],
+            threshold: 2,
+        };
+        let target = address::testing::established_address_2();
+        let token = address::nam();
+        let amount = token::Amount::from(10_098_123);
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner, &target, &token]);
+
+        // Credit the tokens to the VP owner before running the transaction to
+        // be able to transfer from it
+        tx_env.credit_tokens(&vp_owner, &token, None, amount);
+
+        tx_env.write_public_key(&vp_owner, &keypair_1.ref_to());
+        tx_env.write_multisig(&vp_owner, &multisig);
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
+            // Apply transfer in a transaction
+            tx_host_env::token::transfer(
+                tx::ctx(),
+                address,
+                &target,
+                &token,
+                None,
+                amount,
+                &None,
+                &None,
+            )
+            .unwrap();
+        });
+
+        let mut vp_env = vp_host_env::take();
+        let tx = vp_env.tx.clone();
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+
+        // The signature of the account's public key is not enough
+        let signed_tx =
+            tx.sign(&keypair_1).sign_multisig(&keypair_1, 0).unwrap();
+        let tx_data: Vec<u8> = signed_tx.data.as_ref().cloned().unwrap();
+        vp_env.tx = signed_tx.clone();
+        vp_host_env::set(vp_env);
+        assert!(!validate_tx(
+            &CTX,
+            tx_data,
+            vp_owner.clone(),
+            keys_changed.clone(),
+            verifiers.clone()
+        )
+        .unwrap());
+
+        // A signature at the wrong index is not valid
+        let mut vp_env = vp_host_env::take();
+        let wrong_tx = signed_tx.clone().sign_multisig(&keypair_2, 0).unwrap();
+        let tx_data: Vec<u8> = wrong_tx.data.as_ref().cloned().unwrap();
+        vp_env.tx = wrong_tx;
+        vp_host_env::set(vp_env);
+        assert!(!validate_tx(
+            &CTX,
+            tx_data,
+            vp_owner.clone(),
+            keys_changed.clone(),
+            verifiers.clone()
+        )
+        .unwrap());
+
+        let mut vp_env = vp_host_env::take();
+        let signed_tx = signed_tx.sign_multisig(&keypair_2, 1).unwrap();
+        let tx_data: Vec<u8> = signed_tx.data.as_ref().cloned().unwrap();
+        vp_env.tx = signed_tx;
+        vp_host_env::set(vp_env);
+        assert!(
+            validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers)
+                .unwrap()
+        );
+    }
+
+    /// Test that the public key can only be rotated with a signature of the
+    /// key that it replaces and a proof of possession of the new key.
+    #[test]
+    fn test_update_key() {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+
+        let vp_owner = address::testing::established_address_1();
+        let old_keypair = key::testing::keypair_1();
+        let new_keypair = key::testing::keypair_2();
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner]);
+
+        tx_env.write_public_key(&vp_owner, &old_keypair.ref_to());
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
+            // Rotate the key in a transaction
+            tx_host_env::key::update_pk(
+                tx::ctx(),
+                address,
+                &new_keypair.ref_to(),
+            )
+            .unwrap();
+        });
+
+        let mut vp_env = vp_host_env::take();
+        let mut tx = vp_env.tx.clone();
+        let update_key =
+            transaction::UpdateKey::new(vp_owner.clone(), &new_keypair);
+        tx.data = Some(update_key.try_to_vec().unwrap());
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+
+        // The new key cannot authorize the rotation
+        let signed_tx = tx.clone().sign(&new_keypair);
+        let tx_data: Vec<u8> = signed_tx.data.as_ref().cloned().unwrap();
+        vp_env.tx = signed_tx;
+        vp_host_env::set(vp_env);
+        assert!(!validate_tx(
+            &CTX,
+            tx_data,
+            vp_owner.clone(),
+            keys_changed.clone(),
+            verifiers.clone()
+        )
+        .unwrap());
+
+        // The new key must prove its possession with its own signature
+        let mut vp_env = vp_host_env::take();
+        let mut unproven_tx = tx.clone();
+        let unproven_update_key = transaction::UpdateKey {
+            possession_sig: transaction::UpdateKey::new(
+                vp_owner.clone(),
+                &old_keypair,
+            )
+            .possession_sig,
+            ..update_key
+        };
+        unproven_tx.data = Some(unproven_update_key.try_to_vec().unwrap());
+        let signed_tx = unproven_tx.sign(&old_keypair);
+        let tx_data: Vec<u8> = signed_tx.data.as_ref().cloned().unwrap();
+        vp_env.tx = signed_tx;
+        vp_host_env::set(vp_env);
+        assert!(!validate_tx(
+            &CTX,
+            tx_data,
+            vp_owner.clone(),
+            keys_changed.clone(),
+            verifiers.clone()
+        )
+        .unwrap());
+
+        let mut vp_env = vp_host_env::take();
+        let signed_tx = tx.sign(&old_keypair);
+        let tx_data: Vec<u8> = signed_tx.data.as_ref().cloned().unwrap();
+        vp_env.tx = signed_tx;
+        vp_host_env::set(vp_env);
+        assert!(
+            validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers)
+                .unwrap()
+        );
+    }
+
+    /// Test that the public key cannot be removed, even with a valid
+    /// signature.
+    #[test]
+    fn test_signed_key_removal_rejected() {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+
+        let vp_owner = address::testing::established_address_1();
+        let keypair = key::testing::keypair_1();
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner]);
+
+        tx_env.write_public_key(&vp_owner, &keypair.ref_to());
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
+            // Remove the key in a transaction
+            tx::ctx().delete(&key::pk_key(address)).unwrap();
+        });
+
+        let mut vp_env = vp_host_env::take();
+        let tx = vp_env.tx.clone();
+        let signed_tx = tx.sign(&keypair);
+        let tx_data: Vec<u8> = signed_tx.data.as_ref().cloned().unwrap();
+        vp_env.tx = signed_tx;
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: BTreeSet<Address> = BTreeSet::default();
+        vp_host_env::set(vp_env);
+        assert!(
+            !validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers)
+                .unwrap()
+        );
+    }
+
     /// Test that a transfer on with accounts other than self is accepted.
     #[test]
     fn test_transfer_between_other_parties_accepted() {
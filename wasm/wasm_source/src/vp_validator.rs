@@ -7,8 +7,8 @@
 //! valid signature.
 //!
 //! Currently, the only difference with respect to the user VP is for a tx to
-//! change a validator's commission rate: we require a valid signature only from
-//! the validator whose commission rate is being changed.
+//! change a validator's commission rate or state: we require a valid signature
+//! only from the validator whose commission rate or state is being changed.
 //!
 //! Any other storage key changes are allowed only with a valid signature.
 
@@ -32,6 +32,10 @@ impl<'a> From<&'a storage::Key> for KeyType<'a> {
             token::is_any_multitoken_balance_key(key)
         {
             Self::Token(address)
+        } else if let Some((_, address)) =
+            eth_bridge_storage::is_wrapped_erc20_balance_key(key)
+        {
+            Self::Token(address)
         } else if proof_of_stake::is_pos_key(key) {
             Self::PoS
         } else if gov_storage::is_vote_key(key) {
@@ -123,11 +127,12 @@ fn validate_tx(
             KeyType::PoS => {
                 // Allow the account to be used in PoS
                 let bond_id = proof_of_stake::is_bond_key(key)
-                    .or_else(|| proof_of_stake::is_unbond_key(key));
+                    .or_else(|| proof_of_stake::is_unbond_key(key))
+                    .or_else(|| proof_of_stake::is_bond_rewards_key(key));
                 let valid_bond_or_unbond_change = match bond_id {
                     Some(bond_id) => {
-                        // Bonds and unbonds changes for this address
-                        // must be signed
+                        // Bonds, unbonds and rewards claims changes for this
+                        // address must be signed
                         bond_id.source != addr || *valid_sig
                     }
                     None => {
@@ -142,8 +147,15 @@ fn validate_tx(
                     Some(source) => *source != addr || *valid_sig,
                     None => true,
                 };
-                let valid =
-                    valid_bond_or_unbond_change && valid_commission_rate_change;
+                let state = proof_of_stake::is_validator_state_key(key);
+                // Validator's state change, e.g. its unjailing, must be signed
+                let valid_state_change = match state {
+                    Some(validator) => *validator != addr || *valid_sig,
+                    None => true,
+                };
+                let valid = valid_bond_or_unbond_change
+                    && valid_commission_rate_change
+                    && valid_state_change;
                 debug_log!(
                     "PoS key {} {}",
                     key,
@@ -200,6 +212,7 @@ mod tests {
     use namada_tests::tx::{self, tx_host_env, TestTxEnv};
     use namada_tests::vp::vp_host_env::storage::Key;
     use namada_tests::vp::*;
+    use namada_tx_prelude::proof_of_stake::epoched::Epoched;
     use namada_tx_prelude::{StorageWrite, TxEnv};
     use namada_vp_prelude::key::RefTo;
     use proptest::prelude::*;
@@ -517,6 +530,45 @@ mod tests {
         }
     }
 
+    /// Test that the unjailing of the validator without a valid signature is
+    /// rejected.
+    #[test]
+    fn test_unsigned_unjail_rejected() {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+
+        let vp_owner = address::testing::established_address_1();
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner]);
+
+        // Initialize VP environment from a transaction
+        vp_host_env::init_from_tx(vp_owner.clone(), tx_env, |address| {
+            // Change the validator's state back to a candidate in a
+            // transaction
+            let state: proof_of_stake::ValidatorStates =
+                Epoched::init_at_genesis(
+                    proof_of_stake::types::ValidatorState::Candidate,
+                    storage::Epoch::default(),
+                );
+            tx::ctx()
+                .write(&proof_of_stake::validator_state_key(address), state)
+                .unwrap();
+        });
+
+        let vp_env = vp_host_env::take();
+        let tx_data: Vec<u8> = vec![];
+        let keys_changed: BTreeSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        // The unjail tx adds the validator to the verifiers
+        let verifiers: BTreeSet<Address> = BTreeSet::from([vp_owner.clone()]);
+        vp_host_env::set(vp_env);
+        assert!(
+            !validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers)
+                .unwrap()
+        );
+    }
+
     /// Test that a validity predicate update without a valid signature is
     /// rejected.
     #[test]